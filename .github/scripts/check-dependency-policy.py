@@ -15,7 +15,12 @@ from collections import deque
 CORE_FORBIDDEN = {
     "aws-lc-rs",
     "aws-lc-sys",
+    "fusen-consul",
+    "fusen-etcd",
+    "fusen-kubernetes",
     "fusen-nacos",
+    "fusen-static",
+    "hickory-resolver",
     "opentelemetry",
     "opentelemetry-otlp",
     "opentelemetry_sdk",
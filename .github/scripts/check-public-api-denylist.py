@@ -11,20 +11,28 @@ from typing import Optional
 
 CRATES = {
     "fusen_config",
+    "fusen_consul",
     "fusen_contract",
+    "fusen_etcd",
+    "fusen_kubernetes",
     "fusen_nacos",
     "fusen_observability",
     "fusen_procedural_macro",
     "fusen_register",
     "fusen_rs",
+    "fusen_static",
 }
 SOURCE_DIRECTORIES = (
     "fusen-config/src",
+    "fusen-consul/src",
     "fusen-contract/src",
+    "fusen-etcd/src",
+    "fusen-kubernetes/src",
     "fusen-macro/procedural-macro/src",
     "fusen-nacos/src",
     "fusen-observability/src",
     "fusen-register/src",
+    "fusen-static/src",
     "fusen/src",
 )
 
@@ -152,9 +160,11 @@ ALLOWED_PUBLIC_TRAITS = {
         "Interceptor",
         "LoadBalancer",
         "MetricsRecorder",
+        "RequestDecoder",
         "RequestEncoder",
         "Registry",
         "ResponseDecoder",
+        "ResponseEncoder",
         "RetryPolicy",
         "Sanitizer",
         "SensitiveFields",
@@ -164,6 +174,7 @@ MACRO_V1_EXPORTS = {
     "ArgumentField",
     "ArgumentSource",
     "Arguments",
+    "BodyStream",
     "Call",
     "ClientBuilder",
     "ClientRuntime",
@@ -171,11 +182,14 @@ MACRO_V1_EXPORTS = {
     "Interceptor",
     "InterceptorFuture",
     "IntoServerService",
+    "MethodClientDefaults",
     "MethodDescriptor",
     "MethodId",
+    "MethodPriority",
     "MethodSensitivity",
     "PreparedService",
     "Response",
+    "ResponseStream",
     "SensitiveArgument",
     "SensitiveFields",
     "SensitiveShape",
@@ -189,6 +203,7 @@ MACRO_V1_EXPORTS = {
     "http",
     "http_method",
     "method_not_found",
+    "with_body_stream",
 }
 
 REMOVED_METHODS = {
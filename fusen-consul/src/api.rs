@@ -0,0 +1,253 @@
+use crate::ConsulConfig;
+use bytes::Bytes;
+use http::{HeaderValue, Method, Request, StatusCode};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use std::time::Duration;
+
+/// Deadline for requests that do not block on the agent.
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+const INDEX_HEADER: &str = "x-consul-index";
+const TOKEN_HEADER: &str = "x-consul-token";
+
+/// Minimal Consul agent HTTP client shared by the registry and configuration adapters.
+///
+/// The ACL token is only attached to outgoing requests; errors never contain it.
+pub(crate) struct ConsulApi {
+    client: Client<HttpConnector, Full<Bytes>>,
+    base: url::Url,
+    datacenter: Option<String>,
+    token: Option<HeaderValue>,
+    wait_time: Duration,
+}
+
+/// One agent response with its blocking-query index.
+pub(crate) struct Reply {
+    pub(crate) status: StatusCode,
+    pub(crate) index: Option<u64>,
+    pub(crate) body: Bytes,
+}
+
+impl Reply {
+    /// Converts a non-success status into an error.
+    pub(crate) fn success(self) -> Result<Self, ApiError> {
+        if self.status.is_success() {
+            Ok(self)
+        } else {
+            Err(ApiError::Status(self.status))
+        }
+    }
+}
+
+/// Failure of one agent request, classified by each adapter.
+#[derive(Debug)]
+pub(crate) enum ApiError {
+    Transport(hyper_util::client::legacy::Error),
+    Body(Box<dyn std::error::Error + Send + Sync>),
+    TooLarge,
+    Timeout,
+    Status(StatusCode),
+    Decode(serde_json::Error),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(_) => formatter.write_str("Consul agent connection failed"),
+            Self::Body(_) => formatter.write_str("Consul agent response body failed"),
+            Self::TooLarge => write!(
+                formatter,
+                "Consul agent response exceeded {MAX_RESPONSE_BYTES} bytes"
+            ),
+            Self::Timeout => formatter.write_str("Consul agent request timed out"),
+            Self::Status(status) => write!(formatter, "Consul agent returned HTTP {status}"),
+            Self::Decode(_) => formatter.write_str("Consul agent response was not valid JSON"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(error) => Some(error),
+            Self::Body(error) => Some(error.as_ref()),
+            Self::Decode(error) => Some(error),
+            Self::TooLarge | Self::Timeout | Self::Status(_) => None,
+        }
+    }
+}
+
+impl ConsulApi {
+    /// Creates a client from validated configuration without performing network I/O.
+    pub(crate) fn new(config: &ConsulConfig) -> Self {
+        let mut base = url::Url::parse(config.address()).expect("validated Consul address");
+        if let Ok(mut segments) = base.path_segments_mut() {
+            segments.pop_if_empty();
+        }
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            base,
+            datacenter: config.datacenter().map(str::to_owned),
+            token: config.token().map(|token| {
+                let mut value = HeaderValue::from_str(token).expect("validated Consul token");
+                value.set_sensitive(true);
+                value
+            }),
+            wait_time: config.wait_time(),
+        }
+    }
+
+    /// Sends a request to the local agent, which ignores the configured datacenter.
+    pub(crate) async fn agent(
+        &self,
+        method: Method,
+        segments: &[&str],
+        query: &[(&str, &str)],
+        body: Option<Vec<u8>>,
+    ) -> Result<Reply, ApiError> {
+        let url = self.url(segments, query, false);
+        self.send(method, url, body, REQUEST_TIMEOUT).await
+    }
+
+    /// Reads a catalog or KV resource once the agent's index moves past `index`.
+    ///
+    /// An `index` of zero returns immediately with the current value.
+    pub(crate) async fn blocking_get(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+        index: u64,
+    ) -> Result<Reply, ApiError> {
+        let mut url = self.url(segments, query, true);
+        let mut timeout = REQUEST_TIMEOUT;
+        if index != 0 {
+            url.query_pairs_mut()
+                .append_pair("index", &index.to_string())
+                .append_pair("wait", &format!("{}ms", self.wait_time.as_millis()));
+            // The agent adds up to wait/16 of jitter before answering an unchanged query.
+            timeout += self.wait_time + self.wait_time / 16;
+        }
+        self.send(Method::GET, url, None, timeout).await
+    }
+
+    fn url(&self, segments: &[&str], query: &[(&str, &str)], datacenter: bool) -> url::Url {
+        let mut url = self.base.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.push("v1").extend(segments);
+        }
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.extend_pairs(query);
+            if let Some(datacenter) = self.datacenter.as_deref().filter(|_| datacenter) {
+                pairs.append_pair("dc", datacenter);
+            }
+        }
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+        url
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: url::Url,
+        body: Option<Vec<u8>>,
+        timeout: Duration,
+    ) -> Result<Reply, ApiError> {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        if let Some(token) = &self.token {
+            request = request.header(TOKEN_HEADER, token.clone());
+        }
+        if body.is_some() {
+            request = request.header(http::header::CONTENT_TYPE, "application/json");
+        }
+        let request = request
+            .body(Full::new(Bytes::from(body.unwrap_or_default())))
+            .expect("agent request parts are valid");
+        tokio::time::timeout(timeout, async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(ApiError::Transport)?;
+            let status = response.status();
+            let index = response
+                .headers()
+                .get(INDEX_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok());
+            let body = Limited::new(response.into_body(), MAX_RESPONSE_BYTES)
+                .collect()
+                .await
+                .map_err(|error| {
+                    if error.is::<LengthLimitError>() {
+                        ApiError::TooLarge
+                    } else {
+                        ApiError::Body(error)
+                    }
+                })?
+                .to_bytes();
+            Ok(Reply {
+                status,
+                index,
+                body,
+            })
+        })
+        .await
+        .map_err(|_| ApiError::Timeout)?
+    }
+}
+
+/// Computes the next blocking-query index.
+///
+/// Consul documents that an index moving backwards, or reported as zero, must restart the watch
+/// from scratch instead of blocking on a value the agent will never reach.
+pub(crate) fn next_index(previous: u64, reported: Option<u64>) -> u64 {
+    match reported {
+        Some(index) if index >= previous && index > 0 => index,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(address: &str, datacenter: Option<&str>) -> ConsulApi {
+        let mut builder = ConsulConfig::builder().address(address);
+        if let Some(datacenter) = datacenter {
+            builder = builder.datacenter(datacenter);
+        }
+        ConsulApi::new(&builder.build().unwrap())
+    }
+
+    #[test]
+    fn urls_encode_segments_and_only_add_the_datacenter_to_catalog_reads() {
+        let api = api("http://127.0.0.1:8500/", Some("dc-east"));
+
+        assert_eq!(
+            api.url(&["kv", "app/one", "settings.toml"], &[("raw", "")], true)
+                .as_str(),
+            "http://127.0.0.1:8500/v1/kv/app%2Fone/settings.toml?raw=&dc=dc-east"
+        );
+        assert_eq!(
+            api.url(&["agent", "service", "register"], &[], false)
+                .as_str(),
+            "http://127.0.0.1:8500/v1/agent/service/register"
+        );
+    }
+
+    #[test]
+    fn indexes_reset_when_they_move_backwards_or_are_missing() {
+        assert_eq!(next_index(10, Some(12)), 12);
+        assert_eq!(next_index(10, Some(10)), 10);
+        assert_eq!(next_index(10, Some(3)), 0);
+        assert_eq!(next_index(10, Some(0)), 0);
+        assert_eq!(next_index(10, None), 0);
+    }
+}
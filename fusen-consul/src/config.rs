@@ -0,0 +1,273 @@
+use crate::{
+    ConsulConfig,
+    api::{ApiError, ConsulApi, next_index},
+};
+use fusen_config::{
+    ConfigDocument, ConfigError, ConfigErrorKind, ConfigFormat, ConfigHandle, ConfigKey,
+    ConfigOperation, ConfigSource,
+    provider::{self, ConfigPublisher},
+};
+use http::StatusCode;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Consul KV-backed hot configuration source.
+///
+/// A key reads `<group>/<name>` (or `<name>` without a group) from the configured datacenter and
+/// follows it with blocking queries. The name's extension selects TOML or YAML.
+#[derive(Clone)]
+pub struct ConsulConfigSource {
+    api: Arc<ConsulApi>,
+}
+
+impl ConsulConfigSource {
+    /// Creates a source for one agent.
+    ///
+    /// An unusable agent address, datacenter, token, or blocking-query wait time fails with
+    /// `InvalidInput`. Keys are read from the KV store only after their prepared handle is
+    /// activated.
+    pub fn new(config: ConsulConfig) -> Result<Self, ConfigError> {
+        config.validate().map_err(|error| {
+            ConfigError::new(
+                ConfigOperation::Prepare,
+                ConfigErrorKind::InvalidInput,
+                error,
+            )
+        })?;
+        Ok(Self {
+            api: Arc::new(ConsulApi::new(&config)),
+        })
+    }
+}
+
+impl std::fmt::Debug for ConsulConfigSource {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("ConsulConfigSource")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConfigSource for ConsulConfigSource {
+    fn prepare(&self, key: ConfigKey) -> Result<ConfigHandle, ConfigError> {
+        let path = Arc::new(kv_path(&key)?);
+        let format = std::path::Path::new(key.name())
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(ConfigFormat::from_name)
+            .ok_or_else(|| {
+                ConfigError::message(
+                    ConfigOperation::Prepare,
+                    ConfigErrorKind::UnsupportedFormat,
+                    "Consul configuration names must end in .toml, .yaml, or .yml",
+                )
+            })?;
+        let watch = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let activate_api = self.api.clone();
+        let activate_watch = watch.clone();
+
+        Ok(provider::lifecycle(move |publisher| {
+            (
+                async move {
+                    let (document, index) =
+                        read(&activate_api, &path, format, 0, ConfigOperation::Activate)
+                            .await?
+                            .ok_or_else(|| {
+                                ConfigError::message(
+                                    ConfigOperation::Activate,
+                                    ConfigErrorKind::Unavailable,
+                                    "Consul key does not exist",
+                                )
+                            })?;
+                    let task = tokio::spawn(watch_key(
+                        activate_api,
+                        path,
+                        format,
+                        publisher,
+                        document.clone(),
+                        next_index(0, index),
+                    ));
+                    *lock(&activate_watch) = Some(task);
+                    Ok(document)
+                },
+                move || async move {
+                    let task = lock(&watch).take();
+                    if let Some(task) = task {
+                        task.abort();
+                        let _ = task.await;
+                    }
+                    Ok(())
+                },
+            )
+        }))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn kv_path(key: &ConfigKey) -> Result<Vec<String>, ConfigError> {
+    let segments = key
+        .group()
+        .into_iter()
+        .chain([key.name()])
+        .flat_map(|part| part.split('/'))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if segments
+        .iter()
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(ConfigError::message(
+            ConfigOperation::Prepare,
+            ConfigErrorKind::InvalidInput,
+            "Consul key segments must not be empty, '.', or '..'",
+        ));
+    }
+    Ok(segments)
+}
+
+/// Reads one KV entry; `None` means the key does not exist.
+async fn read(
+    api: &ConsulApi,
+    path: &[String],
+    format: ConfigFormat,
+    index: u64,
+    operation: ConfigOperation,
+) -> Result<Option<(ConfigDocument, Option<u64>)>, ConfigError> {
+    let segments = std::iter::once("kv")
+        .chain(path.iter().map(String::as_str))
+        .collect::<Vec<_>>();
+    let reply = api
+        .blocking_get(&segments, &[("raw", "")], index)
+        .await
+        .map_err(|error| config_error(operation, error))?;
+    if reply.status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let reply = reply
+        .success()
+        .map_err(|error| config_error(operation, error))?;
+    let content = String::from_utf8(reply.body.to_vec()).map_err(|_| {
+        ConfigError::message(
+            operation,
+            ConfigErrorKind::InvalidData,
+            "Consul value is not UTF-8",
+        )
+    })?;
+    Ok(Some((ConfigDocument::new(format, content), reply.index)))
+}
+
+/// Publishes changed values; deletions and outages keep the last published document.
+async fn watch_key(
+    api: Arc<ConsulApi>,
+    path: Arc<Vec<String>>,
+    format: ConfigFormat,
+    publisher: ConfigPublisher,
+    mut current: ConfigDocument,
+    mut index: u64,
+) {
+    let mut delay = MIN_RETRY_DELAY;
+    let mut failing = false;
+    let mut missing = false;
+    loop {
+        match read(&api, &path, format, index, ConfigOperation::Watch).await {
+            Ok(Some((document, reported))) => {
+                index = next_index(index, reported);
+                delay = MIN_RETRY_DELAY;
+                failing = false;
+                missing = false;
+                if document != current {
+                    if let Err(error) = publisher.publish(document.clone()) {
+                        if error.kind() == ConfigErrorKind::Cancelled {
+                            return;
+                        }
+                        tracing::warn!(
+                            error = %error,
+                            key = %path.join("/"),
+                            "Consul configuration update rejected"
+                        );
+                    }
+                    current = document;
+                }
+            }
+            Ok(None) => {
+                if !missing {
+                    tracing::warn!(
+                        key = %path.join("/"),
+                        "Consul configuration key was deleted; keeping the last value"
+                    );
+                    missing = true;
+                }
+                // Without a key index the next query answers immediately, so poll with backoff
+                // until the key is written again.
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+                index = 0;
+            }
+            Err(error) => {
+                if !failing {
+                    tracing::warn!(
+                        error = %error,
+                        key = %path.join("/"),
+                        "Consul configuration watch failed; keeping the last value"
+                    );
+                    failing = true;
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+fn config_error(operation: ConfigOperation, error: ApiError) -> ConfigError {
+    let kind = match &error {
+        ApiError::Status(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+            ConfigErrorKind::Unauthorized
+        }
+        ApiError::Status(StatusCode::BAD_REQUEST) => ConfigErrorKind::InvalidInput,
+        ApiError::Timeout => ConfigErrorKind::Timeout,
+        ApiError::TooLarge | ApiError::Decode(_) => ConfigErrorKind::InvalidData,
+        _ => ConfigErrorKind::Unavailable,
+    };
+    ConfigError::new(operation, kind, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_map_groups_and_names_to_kv_segments() {
+        let key = ConfigKey::builder("orders/app.toml")
+            .group("prod")
+            .build()
+            .unwrap();
+        assert_eq!(kv_path(&key).unwrap(), ["prod", "orders", "app.toml"]);
+        assert_eq!(
+            kv_path(&ConfigKey::new("app.yaml").unwrap()).unwrap(),
+            ["app.yaml"]
+        );
+        let error = kv_path(&ConfigKey::new("orders//app.toml").unwrap()).unwrap_err();
+        assert_eq!(error.kind(), ConfigErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn names_without_a_known_extension_are_rejected_before_network_io() {
+        let source = ConsulConfigSource::new(ConsulConfig::default()).unwrap();
+        let error = source
+            .prepare(ConfigKey::new("settings.json").unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigErrorKind::UnsupportedFormat);
+    }
+}
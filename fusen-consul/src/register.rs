@@ -0,0 +1,930 @@
+use crate::{
+    ConsulConfig, ConsulConfigValidationError,
+    api::{ApiError, ConsulApi, next_index},
+};
+use fusen_contract::{
+    EndpointCapabilities, HttpBindingId, HttpVersionSet, InstanceId, ServiceEndpoint,
+    ServiceInstance, ServiceRegistration, ServiceSelector, ServiceWeight,
+};
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    directory::{DirectoryPublisher, DirectoryState, directory},
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use http::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+const META_SCHEME: &str = "fusen-scheme";
+const META_BASE_PATH: &str = "fusen-base-path";
+const META_SERVICE_ID: &str = "fusen-service-id";
+const META_VERSION: &str = "fusen-version";
+const META_GROUP: &str = "fusen-group";
+const META_HTTP_BINDINGS: &str = "fusen-http-bindings";
+const META_HTTP_VERSIONS: &str = "fusen-http-versions";
+const META_INVOCATION_CONTROLS: &str = "fusen-invocation-controls";
+const INVOCATION_CONTROLS_V1: &str = "v1";
+const RESERVED_META_PREFIXES: [&str; 2] = ["fusen-", "consul-"];
+const MAX_META_PAIRS: usize = 64;
+const MAX_META_KEY_BYTES: usize = 128;
+const MAX_META_VALUE_BYTES: usize = 512;
+const MIN_CHECK_PERIOD: Duration = Duration::from_secs(1);
+const MAX_CHECK_PERIOD: Duration = Duration::from_secs(3600);
+const MIN_DEREGISTER_AFTER: Duration = Duration::from_secs(60);
+const MAX_HTTP_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Health check the Consul agent attaches to each registration.
+///
+/// A critical check removes the instance from discovery; after three periods, and at least one
+/// minute, the agent deregisters it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsulHealthCheck {
+    kind: CheckKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CheckKind {
+    Ttl(Duration),
+    Http { path: String, interval: Duration },
+}
+
+impl ConsulHealthCheck {
+    /// The registry reports the instance as passing every third of `ttl` while it is registered.
+    pub fn ttl(ttl: Duration) -> Result<Self, ConsulConfigValidationError> {
+        validate_period(ttl, "consul.check.ttl")?;
+        Ok(Self {
+            kind: CheckKind::Ttl(ttl),
+        })
+    }
+
+    /// The agent probes `path` on the advertised endpoint's origin every `interval`.
+    ///
+    /// The instance passes while the probe answers with a 2xx status. The application serves the
+    /// path; fusen does not add a health route.
+    pub fn http(
+        path: impl Into<String>,
+        interval: Duration,
+    ) -> Result<Self, ConsulConfigValidationError> {
+        let path = path.into();
+        if !path.starts_with('/')
+            || path
+                .bytes()
+                .any(|byte| byte.is_ascii_whitespace() || byte.is_ascii_control() || byte == b'#')
+        {
+            return Err(ConsulConfigValidationError::invalid(
+                "consul.check.http.path",
+                "must start with '/' and contain no whitespace, control characters, or fragment",
+            ));
+        }
+        validate_period(interval, "consul.check.http.interval")?;
+        Ok(Self {
+            kind: CheckKind::Http { path, interval },
+        })
+    }
+
+    /// Returns the TTL of a registry-reported check.
+    pub fn ttl_period(&self) -> Option<Duration> {
+        match self.kind {
+            CheckKind::Ttl(ttl) => Some(ttl),
+            CheckKind::Http { .. } => None,
+        }
+    }
+
+    /// Returns the probed path and interval of an agent HTTP check.
+    pub fn http_probe(&self) -> Option<(&str, Duration)> {
+        match &self.kind {
+            CheckKind::Ttl(_) => None,
+            CheckKind::Http { path, interval } => Some((path, *interval)),
+        }
+    }
+
+    const fn period(&self) -> Duration {
+        match &self.kind {
+            CheckKind::Ttl(ttl) => *ttl,
+            CheckKind::Http { interval, .. } => *interval,
+        }
+    }
+
+    fn deregister_after(&self) -> Duration {
+        (self.period() * 3).max(MIN_DEREGISTER_AFTER)
+    }
+}
+
+impl Default for ConsulHealthCheck {
+    fn default() -> Self {
+        Self {
+            kind: CheckKind::Ttl(Duration::from_secs(15)),
+        }
+    }
+}
+
+fn validate_period(
+    value: Duration,
+    field_path: &'static str,
+) -> Result<(), ConsulConfigValidationError> {
+    if (MIN_CHECK_PERIOD..=MAX_CHECK_PERIOD).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConsulConfigValidationError::out_of_range(
+            field_path,
+            "must be between 1 second and 1 hour",
+        ))
+    }
+}
+
+/// Consul-backed service registry and discovery provider.
+///
+/// Registrations go to the configured local agent with a [`ConsulHealthCheck`] (a 15 second TTL
+/// by default). Subscriptions use blocking queries on the passing instances of the selector's
+/// `service_id` in the configured datacenter.
+#[derive(Clone)]
+pub struct ConsulRegistry {
+    api: Arc<ConsulApi>,
+    health_check: ConsulHealthCheck,
+}
+
+impl ConsulRegistry {
+    /// Creates a registry for one agent.
+    ///
+    /// An unusable agent address, datacenter, token, or blocking-query wait time fails with
+    /// `InvalidResource`. The agent is first contacted when a registration or subscription handle
+    /// is activated.
+    pub fn new(config: ConsulConfig) -> Result<Self, RegistryError> {
+        config.validate().map_err(|error| {
+            RegistryError::new(
+                RegistryOperation::Build,
+                RegistryErrorKind::InvalidResource,
+                error,
+            )
+        })?;
+        Ok(Self {
+            api: Arc::new(ConsulApi::new(&config)),
+            health_check: ConsulHealthCheck::default(),
+        })
+    }
+
+    /// Replaces the health check attached to later registrations.
+    pub fn with_health_check(mut self, health_check: ConsulHealthCheck) -> Self {
+        self.health_check = health_check;
+        self
+    }
+
+    /// Returns the health check attached to registrations.
+    pub const fn health_check(&self) -> &ConsulHealthCheck {
+        &self.health_check
+    }
+}
+
+impl std::fmt::Debug for ConsulRegistry {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("ConsulRegistry")
+            .field("health_check", &self.health_check)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Registry for ConsulRegistry {
+    fn prepare_registration(
+        &self,
+        request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        let service = Arc::new(build_service(
+            &request.into_registration(),
+            &self.health_check,
+        )?);
+        let heartbeat = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let activate_api = self.api.clone();
+        let activate_service = service.clone();
+        let activate_heartbeat = heartbeat.clone();
+        let close_api = self.api.clone();
+        let ttl = self.health_check.ttl_period();
+
+        Ok(provider::registration(
+            async move {
+                register(&activate_api, &activate_service)
+                    .await
+                    .map_err(|error| {
+                        registry_error(RegistryOperation::ActivateRegistration, error)
+                    })?;
+                if let Some(ttl) = ttl {
+                    pass(&activate_api, &activate_service)
+                        .await
+                        .map_err(|error| {
+                            registry_error(RegistryOperation::ActivateRegistration, error)
+                        })?;
+                    let task = tokio::spawn(keep_alive(activate_api, activate_service, ttl));
+                    *lock(&activate_heartbeat) = Some(task);
+                }
+                Ok(())
+            },
+            move || async move {
+                let task = lock(&heartbeat).take();
+                if let Some(task) = task {
+                    task.abort();
+                    let _ = task.await;
+                }
+                let reply = close_api
+                    .agent(
+                        Method::PUT,
+                        &["agent", "service", "deregister", &service.id],
+                        &[],
+                        None,
+                    )
+                    .await
+                    .map_err(|error| registry_error(RegistryOperation::CloseRegistration, error))?;
+                if reply.status == StatusCode::NOT_FOUND {
+                    return Ok(());
+                }
+                reply
+                    .success()
+                    .map(drop)
+                    .map_err(|error| registry_error(RegistryOperation::CloseRegistration, error))
+            },
+        ))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let selector = Arc::new(request.into_selector());
+        let (publisher, directory) = directory();
+        let watch = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let activate_api = self.api.clone();
+        let activate_watch = watch.clone();
+
+        Ok(provider::subscription(
+            directory,
+            async move {
+                let (instances, index) = query_instances(&activate_api, &selector, 0)
+                    .await
+                    .map_err(|error| {
+                        registry_error(RegistryOperation::ActivateSubscription, error)
+                    })?;
+                publisher.publish_ready(instances.clone())?;
+                let task = tokio::spawn(watch_instances(
+                    activate_api,
+                    selector,
+                    publisher,
+                    instances,
+                    next_index(0, index),
+                ));
+                *lock(&activate_watch) = Some(task);
+                Ok(())
+            },
+            move || async move {
+                let task = lock(&watch).take();
+                if let Some(task) = task {
+                    task.abort();
+                    let _ = task.await;
+                }
+                Ok(())
+            },
+        ))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn register(api: &ConsulApi, service: &AgentService) -> Result<(), ApiError> {
+    let body = serde_json::to_vec(service).map_err(ApiError::Decode)?;
+    api.agent(
+        Method::PUT,
+        &["agent", "service", "register"],
+        &[("replace-existing-checks", "true")],
+        Some(body),
+    )
+    .await?
+    .success()
+    .map(drop)
+}
+
+async fn pass(api: &ConsulApi, service: &AgentService) -> Result<(), ApiError> {
+    api.agent(
+        Method::PUT,
+        &["agent", "check", "pass", &service.check.check_id],
+        &[],
+        None,
+    )
+    .await?
+    .success()
+    .map(drop)
+}
+
+/// Reports a TTL check as passing until the registration is closed.
+///
+/// An agent that lost the registration, for example after a restart without persisted state,
+/// answers 404; the service is then registered again.
+async fn keep_alive(api: Arc<ConsulApi>, service: Arc<AgentService>, ttl: Duration) {
+    let mut ticker = tokio::time::interval(ttl / 3);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await;
+    let mut failing = false;
+    loop {
+        ticker.tick().await;
+        let result = match pass(&api, &service).await {
+            Err(ApiError::Status(StatusCode::NOT_FOUND)) => match register(&api, &service).await {
+                Ok(()) => pass(&api, &service).await,
+                Err(error) => Err(error),
+            },
+            result => result,
+        };
+        match result {
+            Ok(()) => failing = false,
+            Err(error) if !failing => {
+                failing = true;
+                tracing::warn!(
+                    error = %error,
+                    service = %service.name,
+                    "Consul TTL check update failed"
+                );
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+async fn query_instances(
+    api: &ConsulApi,
+    selector: &ServiceSelector,
+    index: u64,
+) -> Result<(Vec<ServiceInstance>, Option<u64>), ApiError> {
+    let reply = api
+        .blocking_get(
+            &["health", "service", selector.service_id()],
+            &[("passing", "true")],
+            index,
+        )
+        .await?
+        .success()?;
+    let entries: Vec<HealthEntry> =
+        serde_json::from_slice(&reply.body).map_err(ApiError::Decode)?;
+    Ok((to_service_instances(entries, selector), reply.index))
+}
+
+/// Follows blocking queries, marking the directory stale while the agent cannot answer.
+async fn watch_instances(
+    api: Arc<ConsulApi>,
+    selector: Arc<ServiceSelector>,
+    publisher: DirectoryPublisher,
+    mut current: Vec<ServiceInstance>,
+    mut index: u64,
+) {
+    let mut delay = MIN_RETRY_DELAY;
+    let mut stale = false;
+    loop {
+        if publisher.is_closed() {
+            return;
+        }
+        match query_instances(&api, &selector, index).await {
+            Ok((instances, reported)) => {
+                index = next_index(index, reported);
+                delay = MIN_RETRY_DELAY;
+                if stale || !same_instances(&current, &instances) {
+                    if publisher.publish_ready(instances.clone()).is_err() {
+                        return;
+                    }
+                    current = instances;
+                    stale = false;
+                }
+            }
+            Err(error) => {
+                if !stale {
+                    tracing::warn!(
+                        error = %error,
+                        service = %selector.identity(),
+                        "Consul blocking query failed; keeping the previous instances"
+                    );
+                    if publisher.publish_state(DirectoryState::Stale).is_err() {
+                        return;
+                    }
+                    stale = true;
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+fn same_instances(left: &[ServiceInstance], right: &[ServiceInstance]) -> bool {
+    left.len() == right.len()
+        && left.iter().zip(right).all(|(left, right)| {
+            left.instance_id() == right.instance_id()
+                && left.endpoint() == right.endpoint()
+                && left.capabilities() == right.capabilities()
+                && left.weight() == right.weight()
+                && left.metadata() == right.metadata()
+        })
+}
+
+fn registry_error(operation: RegistryOperation, error: ApiError) -> RegistryError {
+    let kind = match &error {
+        ApiError::Status(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+            RegistryErrorKind::Unauthorized
+        }
+        ApiError::Status(StatusCode::BAD_REQUEST) => RegistryErrorKind::InvalidResource,
+        ApiError::Timeout => RegistryErrorKind::Timeout,
+        ApiError::Decode(_) => RegistryErrorKind::Internal,
+        _ => RegistryErrorKind::Unavailable,
+    };
+    RegistryError::new(operation, kind, error)
+}
+
+/// Agent service definition accepted by `PUT /v1/agent/service/register`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct AgentService {
+    #[serde(rename = "ID")]
+    id: String,
+    name: String,
+    address: String,
+    port: u16,
+    meta: BTreeMap<String, String>,
+    weights: AgentWeights,
+    check: AgentCheck,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct AgentWeights {
+    passing: u32,
+    warning: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct AgentCheck {
+    #[serde(rename = "CheckID")]
+    check_id: String,
+    name: String,
+    #[serde(rename = "TTL", skip_serializing_if = "Option::is_none")]
+    ttl: Option<String>,
+    #[serde(rename = "HTTP", skip_serializing_if = "Option::is_none")]
+    http: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+    deregister_critical_service_after: String,
+}
+
+fn invalid_registration(message: &'static str) -> RegistryError {
+    RegistryError::message(
+        RegistryOperation::PrepareRegistration,
+        RegistryErrorKind::InvalidResource,
+        message,
+    )
+}
+
+fn build_service(
+    registration: &ServiceRegistration,
+    health_check: &ConsulHealthCheck,
+) -> Result<AgentService, RegistryError> {
+    let url = registration.endpoint().as_url();
+    let host = url
+        .host_str()
+        .ok_or_else(|| invalid_registration("advertised endpoint has no host"))?;
+    let address = host
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .unwrap_or(host);
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| invalid_registration("advertised endpoint has no port"))?;
+    let mut meta = registration
+        .selector()
+        .metadata()
+        .iter()
+        .chain(registration.metadata())
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<BTreeMap<_, _>>();
+    if meta.iter().any(|(key, value)| {
+        key.len() > MAX_META_KEY_BYTES
+            || value.len() > MAX_META_VALUE_BYTES
+            || RESERVED_META_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+            || !key
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'))
+    }) {
+        return Err(invalid_registration(
+            "Consul metadata keys must use ASCII letters, digits, '-' or '_', must not start \
+             with fusen- or consul-, and must fit Consul's key and value limits",
+        ));
+    }
+    meta.insert(META_SCHEME.into(), url.scheme().into());
+    meta.insert(
+        META_SERVICE_ID.into(),
+        registration.selector().service_id().to_owned(),
+    );
+    meta.insert(
+        META_HTTP_BINDINGS.into(),
+        registration
+            .capabilities()
+            .bindings()
+            .iter()
+            .map(HttpBindingId::as_str)
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    meta.insert(
+        META_HTTP_VERSIONS.into(),
+        encode_http_versions(registration.capabilities().http_versions()),
+    );
+    if registration.capabilities().invocation_controls() {
+        meta.insert(
+            META_INVOCATION_CONTROLS.into(),
+            INVOCATION_CONTROLS_V1.into(),
+        );
+    }
+    if url.path() != "/" && !url.path().is_empty() {
+        meta.insert(META_BASE_PATH.into(), url.path().into());
+    }
+    if let Some(version) = registration.selector().version() {
+        meta.insert(META_VERSION.into(), version.to_owned());
+    }
+    if let Some(group) = registration.selector().group() {
+        meta.insert(META_GROUP.into(), group.to_owned());
+    }
+    if meta.len() > MAX_META_PAIRS {
+        return Err(invalid_registration(
+            "Consul accepts at most 64 metadata pairs per service, including fusen capability keys",
+        ));
+    }
+
+    let id = registration.instance_id().to_string();
+    let deregister_after = format_duration(health_check.deregister_after());
+    let check = match &health_check.kind {
+        CheckKind::Ttl(ttl) => AgentCheck {
+            check_id: format!("service:{id}"),
+            name: "fusen TTL".into(),
+            ttl: Some(format_duration(*ttl)),
+            http: None,
+            interval: None,
+            timeout: None,
+            deregister_critical_service_after: deregister_after,
+        },
+        CheckKind::Http { path, interval } => AgentCheck {
+            check_id: format!("service:{id}"),
+            name: "fusen HTTP".into(),
+            ttl: None,
+            http: Some(format!("{}{path}", url.origin().ascii_serialization())),
+            interval: Some(format_duration(*interval)),
+            timeout: Some(format_duration((*interval).min(MAX_HTTP_CHECK_TIMEOUT))),
+            deregister_critical_service_after: deregister_after,
+        },
+    };
+    Ok(AgentService {
+        id,
+        name: registration.selector().service_id().to_owned(),
+        address: address.to_owned(),
+        port,
+        meta,
+        weights: AgentWeights {
+            passing: consul_weight(registration.weight()),
+            warning: 1,
+        },
+        check,
+    })
+}
+
+/// Consul weights are positive integers; fractional weights are rounded.
+fn consul_weight(weight: ServiceWeight) -> u32 {
+    weight.get().round().clamp(1.0, f64::from(u32::MAX)) as u32
+}
+
+fn format_duration(value: Duration) -> String {
+    format!("{}ms", value.as_millis())
+}
+
+fn encode_http_versions(versions: HttpVersionSet) -> String {
+    versions.labels().collect::<Vec<_>>().join(",")
+}
+
+/// One entry of `GET /v1/health/service/:service`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HealthEntry {
+    node: HealthNode,
+    service: HealthService,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HealthNode {
+    #[serde(default)]
+    address: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HealthService {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(default)]
+    address: String,
+    port: i64,
+    #[serde(default)]
+    meta: Option<HashMap<String, String>>,
+    #[serde(default)]
+    weights: Option<HealthWeights>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HealthWeights {
+    passing: i64,
+}
+
+fn to_service_instances(
+    entries: Vec<HealthEntry>,
+    selector: &ServiceSelector,
+) -> Vec<ServiceInstance> {
+    let mut instances = entries
+        .into_iter()
+        .filter_map(|entry| to_service_instance(entry, selector))
+        .collect::<Vec<_>>();
+    instances.sort_by(|left, right| left.instance_id().cmp(right.instance_id()));
+    instances
+}
+
+fn to_service_instance(entry: HealthEntry, selector: &ServiceSelector) -> Option<ServiceInstance> {
+    let service = entry.service;
+    let metadata = service.meta.unwrap_or_default();
+    if metadata.get(META_SERVICE_ID).map(String::as_str) != Some(selector.service_id())
+        || metadata.get(META_VERSION).map(String::as_str) != selector.version()
+        || metadata.get(META_GROUP).map(String::as_str) != selector.group()
+    {
+        return None;
+    }
+    let port = u16::try_from(service.port).ok().filter(|port| *port != 0)?;
+    let scheme = metadata
+        .get(META_SCHEME)
+        .map(String::as_str)
+        .unwrap_or("http");
+    if !matches!(scheme, "http" | "https") {
+        return None;
+    }
+    let address = if service.address.is_empty() {
+        entry.node.address
+    } else {
+        service.address
+    };
+    let host = if address.parse::<std::net::Ipv6Addr>().is_ok() {
+        format!("[{address}]")
+    } else {
+        address.clone()
+    };
+    let mut url = url::Url::parse(&format!("{scheme}://{host}:{port}")).ok()?;
+    if let Some(path) = metadata.get(META_BASE_PATH) {
+        let mut segments = url.path_segments_mut().ok()?;
+        segments.clear();
+        for segment in path.trim_matches('/').split('/') {
+            if segment.is_empty() {
+                continue;
+            }
+            let segment = percent_encoding::percent_decode_str(segment)
+                .decode_utf8()
+                .ok()?;
+            segments.push(&segment);
+        }
+    }
+    let instance_id = InstanceId::new(service.id)
+        .or_else(|_| InstanceId::new(format!("consul:{address}:{port}")))
+        .ok()?;
+    let endpoint = ServiceEndpoint::try_from(url).ok()?;
+    let capabilities = decode_capabilities(&metadata)?;
+    let weight = service.weights.map_or(1, |weights| weights.passing).max(1);
+    let weight = ServiceWeight::new(weight as f64).ok()?;
+    let user_metadata = metadata
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with("fusen-"))
+        .collect();
+    ServiceInstance::new(instance_id, endpoint, capabilities, weight)
+        .with_metadata(user_metadata)
+        .ok()
+}
+
+fn decode_capabilities(metadata: &HashMap<String, String>) -> Option<EndpointCapabilities> {
+    let bindings = decode_bindings(metadata.get(META_HTTP_BINDINGS)?)?;
+    let versions = decode_http_versions(metadata.get(META_HTTP_VERSIONS)?)?;
+    let invocation_controls = match metadata.get(META_INVOCATION_CONTROLS).map(String::as_str) {
+        None => false,
+        Some(INVOCATION_CONTROLS_V1) => true,
+        Some(_) => return None,
+    };
+    EndpointCapabilities::new(versions, bindings, invocation_controls).ok()
+}
+
+fn decode_bindings(value: &str) -> Option<Vec<HttpBindingId>> {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut bindings = Vec::new();
+    for value in value.split(',') {
+        let binding = HttpBindingId::new(value).ok()?;
+        if !seen.insert(binding.clone()) {
+            return None;
+        }
+        bindings.push(binding);
+    }
+    (!bindings.is_empty()).then_some(bindings)
+}
+
+fn decode_http_versions(value: &str) -> Option<HttpVersionSet> {
+    HttpVersionSet::from_labels(value.split(',')).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusen_contract::{HttpOperation, MethodDescriptor, MethodId, ServiceDescriptor};
+
+    fn selector() -> ServiceSelector {
+        ServiceSelector::new("demo", Some("blue".into()), Some("1.0.0".into())).unwrap()
+    }
+
+    fn registration_at(endpoint: &str, metadata: &[(&str, &str)]) -> ServiceRegistration {
+        let descriptor = Box::leak(Box::new(
+            ServiceDescriptor::new(
+                selector(),
+                vec![
+                    MethodDescriptor::new(
+                        MethodId::new(0),
+                        "call",
+                        HttpOperation::new(
+                            "POST".parse().unwrap(),
+                            "/call",
+                            vec![],
+                            "application/json",
+                            "application/json",
+                        )
+                        .unwrap(),
+                    )
+                    .unwrap(),
+                ],
+            )
+            .unwrap(),
+        ));
+        ServiceRegistration::new(
+            InstanceId::new("demo-1").unwrap(),
+            descriptor,
+            endpoint.parse().unwrap(),
+            EndpointCapabilities::new(
+                HttpVersionSet::ALL,
+                [
+                    HttpBindingId::new("vendor-v1").unwrap(),
+                    HttpBindingId::default(),
+                ],
+                true,
+            )
+            .unwrap(),
+            ServiceWeight::new(2.6).unwrap(),
+        )
+        .with_metadata(
+            metadata
+                .iter()
+                .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn entry(service: &AgentService) -> HealthEntry {
+        HealthEntry {
+            node: HealthNode {
+                address: "10.0.0.9".into(),
+            },
+            service: HealthService {
+                id: service.id.clone(),
+                address: service.address.clone(),
+                port: i64::from(service.port),
+                meta: Some(service.meta.clone().into_iter().collect()),
+                weights: Some(HealthWeights {
+                    passing: i64::from(service.weights.passing),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn registration_round_trips_through_discovery_metadata() {
+        let service = build_service(
+            &registration_at("https://[::1]:8443/rpc", &[("zone", "east")]),
+            &ConsulHealthCheck::default(),
+        )
+        .unwrap();
+        assert_eq!(service.name, "demo");
+        assert_eq!(service.address, "::1");
+        assert_eq!(service.weights.passing, 3);
+        assert_eq!(service.meta[META_VERSION], "1.0.0");
+        assert_eq!(service.meta[META_GROUP], "blue");
+        assert_eq!(service.meta[META_HTTP_VERSIONS], "1.1,2");
+
+        let instance = to_service_instance(entry(&service), &selector()).unwrap();
+        assert_eq!(instance.instance_id().as_str(), "demo-1");
+        assert_eq!(instance.endpoint().as_str(), "https://[::1]:8443/rpc");
+        assert_eq!(instance.weight().get(), 3.0);
+        assert!(instance.capabilities().invocation_controls());
+        assert_eq!(
+            instance.metadata(),
+            &BTreeMap::from([("zone".to_owned(), "east".to_owned())])
+        );
+    }
+
+    #[test]
+    fn discovery_matches_identity_strictly_and_falls_back_to_the_node_address() {
+        let service = build_service(
+            &registration_at("http://127.0.0.1:8080", &[]),
+            &ConsulHealthCheck::default(),
+        )
+        .unwrap();
+        let other = ServiceSelector::new("demo", Some("blue".into()), None).unwrap();
+        assert!(to_service_instance(entry(&service), &other).is_none());
+
+        let mut external = entry(&service);
+        external.service.address.clear();
+        let instance = to_service_instance(external, &selector()).unwrap();
+        assert_eq!(instance.endpoint().as_str(), "http://10.0.0.9:8080/");
+
+        let mut partial = entry(&service);
+        partial
+            .service
+            .meta
+            .as_mut()
+            .unwrap()
+            .remove(META_HTTP_VERSIONS);
+        assert!(to_service_instance(partial, &selector()).is_none());
+    }
+
+    #[test]
+    fn checks_encode_ttl_and_http_probes() {
+        let ttl = build_service(
+            &registration_at("http://127.0.0.1:8080/rpc", &[]),
+            &ConsulHealthCheck::ttl(Duration::from_secs(10)).unwrap(),
+        )
+        .unwrap();
+        let json = serde_json::to_value(&ttl).unwrap();
+        assert_eq!(json["Check"]["CheckID"], "service:demo-1");
+        assert_eq!(json["Check"]["TTL"], "10000ms");
+        assert_eq!(json["Check"]["DeregisterCriticalServiceAfter"], "60000ms");
+        assert!(json["Check"].get("HTTP").is_none());
+
+        let http = build_service(
+            &registration_at("http://127.0.0.1:8080/rpc", &[]),
+            &ConsulHealthCheck::http("/healthz", Duration::from_secs(30)).unwrap(),
+        )
+        .unwrap();
+        let json = serde_json::to_value(&http).unwrap();
+        assert_eq!(json["Check"]["HTTP"], "http://127.0.0.1:8080/healthz");
+        assert_eq!(json["Check"]["Interval"], "30000ms");
+        assert_eq!(json["Check"]["Timeout"], "10000ms");
+        assert_eq!(json["Check"]["DeregisterCriticalServiceAfter"], "90000ms");
+    }
+
+    #[test]
+    fn metadata_and_checks_outside_consul_limits_are_rejected() {
+        for key in ["with.dot", "fusen-version", "consul-network-segment"] {
+            let error = build_service(
+                &registration_at("http://127.0.0.1:8080", &[(key, "value")]),
+                &ConsulHealthCheck::default(),
+            )
+            .unwrap_err();
+            assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+        }
+        assert_eq!(
+            ConsulHealthCheck::ttl(Duration::from_millis(500))
+                .unwrap_err()
+                .field_path(),
+            "consul.check.ttl"
+        );
+        assert_eq!(
+            ConsulHealthCheck::http("healthz", Duration::from_secs(5))
+                .unwrap_err()
+                .field_path(),
+            "consul.check.http.path"
+        );
+    }
+
+    #[test]
+    fn unusable_configuration_fails_as_a_build_error() {
+        let config: ConsulConfig =
+            serde_json::from_value(serde_json::json!({ "wait_time_ms": 10 })).unwrap();
+        let error = ConsulRegistry::new(config).unwrap_err();
+        assert_eq!(error.operation(), RegistryOperation::Build);
+        assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+    }
+}
@@ -0,0 +1,478 @@
+//! Registry and configuration adapters against an in-process fake of the Consul agent HTTP API.
+
+use bytes::Bytes;
+use fusen_config::{ConfigErrorKind, ConfigKey, ConfigSource};
+use fusen_consul::{ConsulConfig, ConsulConfigSource, ConsulHealthCheck, ConsulRegistry};
+use fusen_register::{
+    Registry, SubscriptionRequest,
+    directory::DirectoryState,
+    testing::{eventually, ids, registration, selector, wait_for},
+};
+use http::{Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::watch};
+
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct State {
+    services: BTreeMap<String, Value>,
+    checks: BTreeMap<String, &'static str>,
+    passes: usize,
+    kv: BTreeMap<String, String>,
+    failure: Option<StatusCode>,
+    tokens: Vec<Option<String>>,
+}
+
+/// Agent fake covering service registration, TTL checks, health queries, and raw KV reads.
+///
+/// Every mutation advances one global index; blocking reads wait until it moves past the
+/// requested index or the requested wait elapses.
+#[derive(Clone)]
+struct FakeConsul {
+    address: String,
+    state: Arc<Mutex<State>>,
+    index: Arc<watch::Sender<u64>>,
+}
+
+impl FakeConsul {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let fake = Self {
+            address: format!("http://{}", listener.local_addr().unwrap()),
+            state: Arc::default(),
+            index: Arc::new(watch::channel(1).0),
+        };
+        let server = fake.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let fake = server.clone();
+                tokio::spawn(async move {
+                    let _ = http1::Builder::new()
+                        .serve_connection(
+                            TokioIo::new(stream),
+                            service_fn(move |request| {
+                                let fake = fake.clone();
+                                async move { Ok::<_, Infallible>(fake.handle(request).await) }
+                            }),
+                        )
+                        .await;
+                });
+            }
+        });
+        fake
+    }
+
+    fn config(&self) -> ConsulConfig {
+        ConsulConfig::builder()
+            .address(&self.address)
+            .token("agent-token")
+            .wait_time(Duration::from_secs(1))
+            .build()
+            .unwrap()
+    }
+
+    fn mutate<T>(&self, change: impl FnOnce(&mut State) -> T) -> T {
+        let result = change(&mut self.state.lock().unwrap());
+        self.index.send_modify(|index| *index += 1);
+        result
+    }
+
+    fn read<T>(&self, read: impl FnOnce(&State) -> T) -> T {
+        read(&self.state.lock().unwrap())
+    }
+
+    fn set_check(&self, service_id: &str, status: &'static str) {
+        self.mutate(|state| state.checks.insert(format!("service:{service_id}"), status));
+    }
+
+    /// Simulates an agent restart that lost its local service definitions.
+    fn forget_services(&self) {
+        self.mutate(|state| {
+            state.services.clear();
+            state.checks.clear();
+        });
+    }
+
+    async fn handle(&self, request: Request<Incoming>) -> Response<Full<Bytes>> {
+        let url = url::Url::parse(&format!("http://fake{}", request.uri())).unwrap();
+        let segments = url.path_segments().unwrap().collect::<Vec<_>>();
+        let query = url.query_pairs().into_owned().collect::<BTreeMap<_, _>>();
+        let method = request.method().clone();
+        let token = request
+            .headers()
+            .get("x-consul-token")
+            .map(|value| value.to_str().unwrap().to_owned());
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        self.state.lock().unwrap().tokens.push(token);
+
+        match (method, segments.as_slice()) {
+            (Method::PUT, ["v1", "agent", "service", "register"]) => {
+                let service: Value = serde_json::from_slice(&body).unwrap();
+                self.mutate(|state| {
+                    let id = service["ID"].as_str().unwrap().to_owned();
+                    let check = service["Check"]["CheckID"].as_str().unwrap().to_owned();
+                    // The agent starts TTL checks critical; HTTP probes are assumed to pass.
+                    let status = if service["Check"]["TTL"].is_string() {
+                        "critical"
+                    } else {
+                        "passing"
+                    };
+                    state.checks.insert(check, status);
+                    state.services.insert(id, service);
+                });
+                reply(StatusCode::OK, "")
+            }
+            (Method::PUT, ["v1", "agent", "service", "deregister", id]) => {
+                let id = (*id).to_owned();
+                let removed = self.mutate(|state| {
+                    state.checks.remove(&format!("service:{id}"));
+                    state.services.remove(&id).is_some()
+                });
+                reply(
+                    if removed {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::NOT_FOUND
+                    },
+                    "",
+                )
+            }
+            (Method::PUT, ["v1", "agent", "check", "pass", check]) => {
+                let check = percent_encoding::percent_decode_str(check)
+                    .decode_utf8()
+                    .unwrap()
+                    .into_owned();
+                let known = self.mutate(|state| match state.checks.get_mut(&check) {
+                    Some(status) => {
+                        *status = "passing";
+                        state.passes += 1;
+                        true
+                    }
+                    None => false,
+                });
+                reply(
+                    if known {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::NOT_FOUND
+                    },
+                    "",
+                )
+            }
+            (Method::GET, ["v1", "health", "service", name]) => {
+                let index = self.block(&query).await;
+                if let Some(status) = self.read(|state| state.failure) {
+                    return reply(status, "");
+                }
+                let entries = self.read(|state| {
+                    state
+                        .services
+                        .values()
+                        .filter(|service| service["Name"] == *name)
+                        .filter(|service| {
+                            let check = format!("service:{}", service["ID"].as_str().unwrap());
+                            query.get("passing").map(String::as_str) != Some("true")
+                                || state.checks.get(&check) == Some(&"passing")
+                        })
+                        .map(|service| {
+                            json!({
+                                "Node": { "Node": "node-1", "Address": "10.0.0.1" },
+                                "Service": service,
+                                "Checks": [],
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                });
+                indexed(StatusCode::OK, Value::Array(entries).to_string(), index)
+            }
+            (Method::GET, ["v1", "kv", path @ ..]) => {
+                let index = self.block(&query).await;
+                if let Some(status) = self.read(|state| state.failure) {
+                    return reply(status, "");
+                }
+                let path = path
+                    .iter()
+                    .map(|segment| percent_encoding::percent_decode_str(segment).decode_utf8())
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+                    .join("/");
+                match self.read(|state| state.kv.get(&path).cloned()) {
+                    Some(value) => indexed(StatusCode::OK, value, index),
+                    None => indexed(StatusCode::NOT_FOUND, String::new(), index),
+                }
+            }
+            _ => reply(StatusCode::NOT_FOUND, ""),
+        }
+    }
+
+    /// Waits while the requested index is current, as a Consul blocking query does.
+    async fn block(&self, query: &BTreeMap<String, String>) -> u64 {
+        let mut receiver = self.index.subscribe();
+        if let Some(index) = query
+            .get("index")
+            .and_then(|value| value.parse::<u64>().ok())
+        {
+            let wait = query
+                .get("wait")
+                .and_then(|value| value.strip_suffix("ms"))
+                .and_then(|value| value.parse().ok())
+                .map_or(Duration::from_secs(1), Duration::from_millis);
+            let _ = tokio::time::timeout(wait, receiver.wait_for(|current| *current > index)).await;
+        }
+        *receiver.borrow()
+    }
+}
+
+fn reply(status: StatusCode, body: &'static str) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .body(Full::new(Bytes::from_static(body.as_bytes())))
+        .unwrap()
+}
+
+fn indexed(status: StatusCode, body: String, index: u64) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header("x-consul-index", index)
+        .body(Full::new(Bytes::from(body)))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn ttl_registrations_heartbeat_and_deregister_on_close() {
+    let fake = FakeConsul::start().await;
+    let registry = ConsulRegistry::new(fake.config())
+        .unwrap()
+        .with_health_check(ConsulHealthCheck::ttl(Duration::from_secs(3)).unwrap());
+    let handle = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    assert!(fake.read(|state| state.services.is_empty()));
+
+    handle.activate().await.unwrap();
+    let service = fake.read(|state| state.services["greeter-1"].clone());
+    assert_eq!(service["Name"], "greeter");
+    assert_eq!(service["Port"], 8080);
+    assert_eq!(service["Meta"]["fusen-version"], "1.0");
+    assert_eq!(service["Check"]["TTL"], "3000ms");
+    assert_eq!(
+        fake.read(|state| state.checks["service:greeter-1"]),
+        "passing"
+    );
+    eventually("the TTL check is refreshed", || {
+        fake.read(|state| state.passes >= 2)
+    })
+    .await;
+
+    handle.close().await.unwrap();
+    assert!(fake.read(|state| state.services.is_empty()));
+    assert!(fake.read(|state| {
+        state
+            .tokens
+            .iter()
+            .all(|token| token.as_deref() == Some("agent-token"))
+    }));
+}
+
+#[tokio::test]
+async fn lost_registrations_are_restored_by_the_heartbeat() {
+    let fake = FakeConsul::start().await;
+    let registry = ConsulRegistry::new(fake.config())
+        .unwrap()
+        .with_health_check(ConsulHealthCheck::ttl(Duration::from_secs(3)).unwrap());
+    let handle = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    handle.activate().await.unwrap();
+
+    fake.forget_services();
+    eventually("the service is registered again", || {
+        fake.read(|state| {
+            state.services.contains_key("greeter-1")
+                && state.checks.get("service:greeter-1") == Some(&"passing")
+        })
+    })
+    .await;
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn http_checks_are_left_to_the_agent() {
+    let fake = FakeConsul::start().await;
+    let registry = ConsulRegistry::new(fake.config())
+        .unwrap()
+        .with_health_check(ConsulHealthCheck::http("/healthz", Duration::from_secs(5)).unwrap());
+    let handle = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    handle.activate().await.unwrap();
+
+    let service = fake.read(|state| state.services["greeter-1"].clone());
+    assert_eq!(service["Check"]["HTTP"], "http://127.0.0.1:8080/healthz");
+    assert_eq!(service["Check"]["Interval"], "5000ms");
+    assert_eq!(fake.read(|state| state.passes), 0);
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscriptions_follow_passing_instances_of_the_selected_version() {
+    let fake = FakeConsul::start().await;
+    let registry = ConsulRegistry::new(fake.config())
+        .unwrap()
+        .with_health_check(ConsulHealthCheck::http("/healthz", Duration::from_secs(5)).unwrap());
+    let first = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    let other_version = registry
+        .prepare_registration(registration("greeter-v2", 9090, "2.0"))
+        .unwrap();
+    first.activate().await.unwrap();
+    other_version.activate().await.unwrap();
+
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    let snapshot = directory.snapshot();
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+    assert_eq!(
+        snapshot.iter().next().unwrap().endpoint().as_str(),
+        "http://127.0.0.1:8080/"
+    );
+
+    let second = registry
+        .prepare_registration(registration("greeter-2", 8081, "1.0"))
+        .unwrap();
+    second.activate().await.unwrap();
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 2
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1", "greeter-2"]);
+
+    fake.set_check("greeter-1", "critical");
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 1
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-2"]);
+
+    handle.close().await.unwrap();
+    assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+    for registration in [first, second, other_version] {
+        registration.close().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn agent_outages_mark_the_directory_stale_until_queries_recover() {
+    let fake = FakeConsul::start().await;
+    let registry = ConsulRegistry::new(fake.config())
+        .unwrap()
+        .with_health_check(ConsulHealthCheck::http("/healthz", Duration::from_secs(5)).unwrap());
+    let registration = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    registration.activate().await.unwrap();
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+
+    fake.mutate(|state| state.failure = Some(StatusCode::INTERNAL_SERVER_ERROR));
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Stale
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+
+    fake.mutate(|state| state.failure = None);
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Ready
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+
+    handle.close().await.unwrap();
+    registration.close().await.unwrap();
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Settings {
+    value: String,
+}
+
+fn put(fake: &FakeConsul, path: &str, value: &str) {
+    fake.mutate(|state| state.kv.insert(path.to_owned(), value.to_owned()));
+}
+
+#[tokio::test]
+async fn kv_values_hot_reload_and_keep_the_last_good_value() {
+    let fake = FakeConsul::start().await;
+    put(&fake, "orders/settings.toml", "value = 'one'\n");
+    let source = ConsulConfigSource::new(fake.config()).unwrap();
+    let handle = source
+        .prepare(
+            ConfigKey::builder("settings.toml")
+                .group("orders")
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+    handle.activate().await.unwrap();
+    let mut config = handle.typed::<Settings>().unwrap();
+    assert_eq!(config.current().value, "one");
+
+    put(&fake, "orders/settings.toml", "value = 'two'\n");
+    assert_eq!(
+        tokio::time::timeout(Duration::from_secs(10), config.changed())
+            .await
+            .unwrap()
+            .unwrap()
+            .value()
+            .value,
+        "two"
+    );
+
+    put(&fake, "orders/settings.toml", "value = [\n");
+    eventually("the invalid revision is reported", || {
+        config.last_error().is_some()
+    })
+    .await;
+    assert_eq!(config.current().value, "two");
+
+    fake.mutate(|state| state.kv.remove("orders/settings.toml"));
+    put(&fake, "orders/settings.toml", "value = 'three'\n");
+    eventually("the recreated key is published", || {
+        config.current().value == "three"
+    })
+    .await;
+
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn missing_keys_fail_activation() {
+    let fake = FakeConsul::start().await;
+    let source = ConsulConfigSource::new(fake.config()).unwrap();
+    let handle = source
+        .prepare(ConfigKey::new("absent.toml").unwrap())
+        .unwrap();
+
+    let error = handle.activate().await.unwrap_err();
+    assert_eq!(error.kind(), ConfigErrorKind::Unavailable);
+}
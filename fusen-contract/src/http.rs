@@ -4,6 +4,12 @@ use std::collections::BTreeSet;
 /// Stable identifier for the HTTP representation used by a service invocation.
 pub const HTTP_JSON_V1: &str = "http-json-v1";
 
+/// Stable identifier for the MessagePack variant of [`HTTP_JSON_V1`].
+///
+/// Requests and successful responses carry `application/msgpack` bodies; the path, query, header
+/// and error mappings are the same as in `http-json-v1`.
+pub const HTTP_MSGPACK_V1: &str = "http-msgpack-v1";
+
 /// A validated service-invocation HTTP binding identifier.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct HttpBindingId(String);
@@ -46,17 +52,24 @@ impl std::fmt::Display for HttpBindingId {
 }
 
 /// A validated non-empty set of supported HTTP transport versions.
+///
+/// HTTP/3 is only representable with the `http3` feature.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct HttpVersionSet(u8);
 
 impl HttpVersionSet {
     const HTTP_1_1_BIT: u8 = 1 << 0;
     const HTTP_2_BIT: u8 = 1 << 1;
+    #[cfg(feature = "http3")]
+    const HTTP_3_BIT: u8 = 1 << 2;
 
     /// Only HTTP/1.1 is supported.
     pub const HTTP_1_1: Self = Self(Self::HTTP_1_1_BIT);
     /// Only HTTP/2 is supported.
     pub const HTTP_2: Self = Self(Self::HTTP_2_BIT);
+    /// Only HTTP/3 over QUIC is supported.
+    #[cfg(feature = "http3")]
+    pub const HTTP_3: Self = Self(Self::HTTP_3_BIT);
     /// HTTP/1.1 and HTTP/2 are both supported.
     pub const ALL: Self = Self(Self::HTTP_1_1_BIT | Self::HTTP_2_BIT);
 
@@ -75,21 +88,60 @@ impl HttpVersionSet {
 
     /// Returns whether this set includes `version`.
     pub fn contains(self, version: http::Version) -> bool {
-        let bit = if version == http::Version::HTTP_11 {
-            Self::HTTP_1_1_BIT
-        } else if version == http::Version::HTTP_2 {
-            Self::HTTP_2_BIT
-        } else {
-            0
-        };
-        self.0 & bit != 0
+        Self::bit(version).is_ok_and(|bit| self.0 & bit != 0)
     }
 
-    /// Iterates in stable HTTP/1.1, HTTP/2 order.
+    /// Iterates in stable HTTP/1.1, HTTP/2, HTTP/3 order.
     pub fn iter(self) -> impl Iterator<Item = http::Version> {
-        [http::Version::HTTP_11, http::Version::HTTP_2]
-            .into_iter()
-            .filter(move |version| self.contains(*version))
+        [
+            http::Version::HTTP_11,
+            http::Version::HTTP_2,
+            http::Version::HTTP_3,
+        ]
+        .into_iter()
+        .filter(move |version| self.contains(*version))
+    }
+
+    /// Parses registry labels (`"1.1"`, `"2"`, `"3"`) into a set.
+    ///
+    /// Unknown and repeated labels are rejected. `"3"` is accepted but left out in builds without
+    /// the `http3` feature, so an endpoint that also advertises HTTP/1.1 or HTTP/2 stays usable.
+    pub fn from_labels<'a>(
+        labels: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, ContractError> {
+        let mut seen = 0_u8;
+        let mut bits = 0;
+        for label in labels {
+            let (flag, version) = match label {
+                "1.1" => (1 << 0, http::Version::HTTP_11),
+                "2" => (1 << 1, http::Version::HTTP_2),
+                "3" => (1 << 2, http::Version::HTTP_3),
+                _ => return Err(ContractError::InvalidHttpVersionLabel(label.to_owned())),
+            };
+            if seen & flag != 0 {
+                return Err(ContractError::InvalidHttpVersionLabel(label.to_owned()));
+            }
+            seen |= flag;
+            bits |= Self::bit(version).unwrap_or_default();
+        }
+        if bits == 0 {
+            Err(ContractError::EmptyHttpVersionSet)
+        } else {
+            Ok(Self(bits))
+        }
+    }
+
+    /// Returns the registry label of each version, in [`iter`](Self::iter) order.
+    pub fn labels(self) -> impl Iterator<Item = &'static str> {
+        self.iter().map(|version| {
+            if version == http::Version::HTTP_11 {
+                "1.1"
+            } else if version == http::Version::HTTP_2 {
+                "2"
+            } else {
+                "3"
+            }
+        })
     }
 
     fn bit(version: http::Version) -> Result<u8, ContractError> {
@@ -97,10 +149,24 @@ impl HttpVersionSet {
             Ok(Self::HTTP_1_1_BIT)
         } else if version == http::Version::HTTP_2 {
             Ok(Self::HTTP_2_BIT)
+        } else {
+            Self::extension_bit(version)
+        }
+    }
+
+    #[cfg(feature = "http3")]
+    fn extension_bit(version: http::Version) -> Result<u8, ContractError> {
+        if version == http::Version::HTTP_3 {
+            Ok(Self::HTTP_3_BIT)
         } else {
             Err(ContractError::UnsupportedHttpVersion(version))
         }
     }
+
+    #[cfg(not(feature = "http3"))]
+    fn extension_bit(version: http::Version) -> Result<u8, ContractError> {
+        Err(ContractError::UnsupportedHttpVersion(version))
+    }
 }
 
 impl Default for HttpVersionSet {
@@ -122,6 +188,9 @@ pub enum HttpVersionPolicy {
     Http2,
     /// Require cleartext HTTP/2 prior knowledge.
     H2c,
+    /// Require HTTP/3 over QUIC.
+    #[cfg(feature = "http3")]
+    Http3,
 }
 
 /// Transport and invocation features advertised by one HTTP endpoint.
@@ -199,6 +268,10 @@ mod tests {
     #[test]
     fn binding_ids_are_bounded_lowercase_segments() {
         assert_eq!(HttpBindingId::default().as_str(), HTTP_JSON_V1);
+        assert_eq!(
+            HttpBindingId::new(HTTP_MSGPACK_V1).unwrap().as_str(),
+            "http-msgpack-v1"
+        );
         assert_eq!(
             HttpBindingId::new("vendor.http-v2").unwrap().as_str(),
             "vendor.http-v2"
@@ -221,6 +294,71 @@ mod tests {
         );
         assert!(HttpVersionSet::new([]).is_err());
         assert!(HttpVersionSet::new([http::Version::HTTP_10]).is_err());
+        #[cfg(not(feature = "http3"))]
+        assert!(HttpVersionSet::new([http::Version::HTTP_3]).is_err());
+    }
+
+    #[test]
+    fn version_labels_round_trip_and_reject_unknown_or_repeated_labels() {
+        let versions = HttpVersionSet::from_labels(["2", "1.1"]).unwrap();
+        assert_eq!(versions, HttpVersionSet::ALL);
+        assert_eq!(versions.labels().collect::<Vec<_>>(), ["1.1", "2"]);
+        assert_eq!(
+            HttpVersionSet::from_labels(["1.1", "3"]).unwrap(),
+            HttpVersionSet::new(
+                [http::Version::HTTP_11, http::Version::HTTP_3]
+                    .into_iter()
+                    .filter(|version| HttpVersionSet::new([*version]).is_ok())
+            )
+            .unwrap()
+        );
+        for labels in [&["1.0"][..], &["2", "2"], &["3", "3"], &[" 2"]] {
+            assert_eq!(
+                HttpVersionSet::from_labels(labels.iter().copied()),
+                Err(ContractError::InvalidHttpVersionLabel(
+                    labels.last().copied().unwrap().to_owned()
+                ))
+            );
+        }
+        assert_eq!(
+            HttpVersionSet::from_labels([]),
+            Err(ContractError::EmptyHttpVersionSet)
+        );
+        #[cfg(not(feature = "http3"))]
+        assert_eq!(
+            HttpVersionSet::from_labels(["3"]),
+            Err(ContractError::EmptyHttpVersionSet)
+        );
+        #[cfg(feature = "http3")]
+        assert_eq!(
+            HttpVersionSet::from_labels(["3"])
+                .unwrap()
+                .labels()
+                .collect::<Vec<_>>(),
+            ["3"]
+        );
+    }
+
+    #[cfg(feature = "http3")]
+    #[test]
+    fn http3_is_an_independent_version_bit() {
+        let versions = HttpVersionSet::new([
+            http::Version::HTTP_3,
+            http::Version::HTTP_2,
+            http::Version::HTTP_11,
+        ])
+        .unwrap();
+        assert_eq!(
+            versions.iter().collect::<Vec<_>>(),
+            [
+                http::Version::HTTP_11,
+                http::Version::HTTP_2,
+                http::Version::HTTP_3
+            ]
+        );
+        assert!(!HttpVersionSet::ALL.contains(http::Version::HTTP_3));
+        assert!(HttpVersionSet::HTTP_3.contains(http::Version::HTTP_3));
+        assert!(!HttpVersionSet::HTTP_3.contains(http::Version::HTTP_2));
     }
 
     #[test]
@@ -6,7 +6,8 @@ mod sensitivity;
 mod service;
 
 pub use http::{
-    EndpointCapabilities, HTTP_JSON_V1, HttpBindingId, HttpVersionPolicy, HttpVersionSet,
+    EndpointCapabilities, HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId, HttpVersionPolicy,
+    HttpVersionSet,
 };
 pub use sensitivity::{
     MethodSensitivity, SensitiveArgument, SensitiveField, SensitiveFields, SensitiveShape,
@@ -14,8 +15,9 @@ pub use sensitivity::{
 };
 pub use service::{
     ContractError, HttpOperation, HttpParameter, HttpParameterCardinality, HttpParameterSource,
-    InstanceId, Metadata, MethodDescriptor, MethodId, ServiceDescriptor, ServiceEndpoint,
-    ServiceInstance, ServiceRegistration, ServiceSelector, ServiceWeight,
+    InstanceId, Metadata, MethodClientDefaults, MethodDescriptor, MethodId, MethodPriority,
+    ServiceDescriptor, ServiceEndpoint, ServiceInstance, ServiceRegistration, ServiceSelector,
+    ServiceWeight,
 };
 
 #[cfg(feature = "derive")]
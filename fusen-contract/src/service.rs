@@ -1,6 +1,9 @@
 use crate::EndpointCapabilities;
 use http::Method;
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
 use url::Url;
 
 const MAX_IDENTITY_BYTES: usize = 128;
@@ -41,6 +44,9 @@ pub enum ContractError {
     /// An HTTP version set contained an unsupported version.
     #[error("unsupported HTTP version {0:?}")]
     UnsupportedHttpVersion(http::Version),
+    /// A registry HTTP version label is unknown or repeated.
+    #[error("invalid HTTP version label {0:?}: expected distinct \"1.1\", \"2\" or \"3\"")]
+    InvalidHttpVersionLabel(String),
     /// Endpoint capabilities contained no invocation bindings.
     #[error("endpoint capabilities must contain at least one HTTP binding")]
     EmptyHttpBindings,
@@ -307,6 +313,11 @@ pub enum HttpParameterSource {
     /// All otherwise-unmapped HTTP headers as one JSON object.
     /// An operation may contain at most one header map.
     HeaderMap,
+    /// The raw request body delivered as a stream of chunks.
+    ///
+    /// It cannot be combined with any JSON body source and is restricted to POST and
+    /// PATCH, which are never replayed, because a streamed body cannot be re-sent.
+    BodyStream,
 }
 
 /// The number of values represented by one HTTP parameter.
@@ -415,16 +426,85 @@ impl HttpOperation {
     }
 }
 
+/// Client invocation defaults declared on an interface method.
+///
+/// Clients apply these below explicit per-method configuration and above runtime-wide settings.
+/// Values are validated when a client connects, not here.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MethodClientDefaults {
+    timeout: Option<Duration>,
+    retries: Option<u8>,
+}
+
+impl MethodClientDefaults {
+    /// Creates defaults from an optional logical timeout and optional retry count.
+    pub const fn new(timeout: Option<Duration>, retries: Option<u8>) -> Self {
+        Self { timeout, retries }
+    }
+
+    /// Returns the logical invocation timeout.
+    pub const fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the number of retries after the first attempt.
+    pub const fn retries(&self) -> Option<u8> {
+        self.retries
+    }
+}
+
+/// Load-shedding class declared on an interface method or sent in `x-fusen-priority`.
+///
+/// Servers with load shedding enabled reject lower classes first; `Critical` is never shed and is
+/// bounded only by the static admission limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MethodPriority {
+    /// Background or best-effort traffic, shed first.
+    Low,
+    /// Ordinary traffic.
+    #[default]
+    Normal,
+    /// Traffic shed only after every `Low` and `Normal` request.
+    High,
+    /// Traffic that load shedding never rejects.
+    Critical,
+}
+
+impl MethodPriority {
+    /// Returns the lower-case wire and attribute spelling.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// Parses the exact lower-case wire and attribute spelling.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "high" => Some(Self::High),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
 /// Versioned wire metadata and optional process-local policy metadata for one generated service method.
 ///
-/// Equality intentionally excludes sensitivity metadata because it does not participate in the
-/// method's wire identity or service contract.
+/// Equality intentionally excludes sensitivity metadata, client defaults, and priority because they
+/// do not participate in the method's wire identity or service contract.
 #[derive(Clone)]
 pub struct MethodDescriptor {
     id: MethodId,
     invocation_name: String,
     http: HttpOperation,
     sensitivity: Option<crate::MethodSensitivity>,
+    client_defaults: MethodClientDefaults,
+    priority: MethodPriority,
 }
 
 impl MethodDescriptor {
@@ -441,6 +521,8 @@ impl MethodDescriptor {
             invocation_name,
             http,
             sensitivity: None,
+            client_defaults: MethodClientDefaults::default(),
+            priority: MethodPriority::Normal,
         })
     }
 
@@ -452,6 +534,22 @@ impl MethodDescriptor {
         self
     }
 
+    /// Attaches process-local client timeout and retry defaults.
+    ///
+    /// This metadata does not affect wire identity, discovery, or registration.
+    pub const fn with_client_defaults(mut self, defaults: MethodClientDefaults) -> Self {
+        self.client_defaults = defaults;
+        self
+    }
+
+    /// Attaches the process-local server load-shedding class.
+    ///
+    /// This metadata does not affect wire identity, discovery, or registration.
+    pub const fn with_priority(mut self, priority: MethodPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Returns the process-local declaration-order identifier.
     pub const fn id(&self) -> MethodId {
         self.id
@@ -481,6 +579,16 @@ impl MethodDescriptor {
     pub const fn sensitivity(&self) -> Option<&crate::MethodSensitivity> {
         self.sensitivity.as_ref()
     }
+
+    /// Returns process-local client timeout and retry defaults.
+    pub const fn client_defaults(&self) -> MethodClientDefaults {
+        self.client_defaults
+    }
+
+    /// Returns the process-local server load-shedding class.
+    pub const fn priority(&self) -> MethodPriority {
+        self.priority
+    }
 }
 
 impl std::fmt::Debug for MethodDescriptor {
@@ -491,6 +599,8 @@ impl std::fmt::Debug for MethodDescriptor {
             .field("invocation_name", &self.invocation_name)
             .field("http", &self.http)
             .field("has_sensitivity", &self.sensitivity.is_some())
+            .field("client_defaults", &self.client_defaults)
+            .field("priority", &self.priority)
             .finish()
     }
 }
@@ -679,6 +789,12 @@ impl ServiceInstance {
         Ok(self)
     }
 
+    /// Replaces the load-balancing weight.
+    pub const fn with_weight(mut self, weight: ServiceWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
     /// Returns the stable provider identity.
     pub const fn instance_id(&self) -> &InstanceId {
         &self.instance_id
@@ -806,6 +922,7 @@ fn validate_http_operation(
     let mut body_field_count = 0;
     let mut query_map_count = 0;
     let mut header_map_count = 0;
+    let mut body_stream_count = 0;
     for parameter in parameters {
         if !names.insert(parameter.name()) {
             return Err(ContractError::InvalidMethod(format!(
@@ -836,6 +953,9 @@ fn validate_http_operation(
             HttpParameterSource::HeaderMap => {
                 header_map_count += 1;
             }
+            HttpParameterSource::BodyStream => {
+                body_stream_count += 1;
+            }
             HttpParameterSource::Query
             | HttpParameterSource::Header
             | HttpParameterSource::Cookie => {}
@@ -868,6 +988,21 @@ fn validate_http_operation(
             "HTTP {method} methods do not accept a JSON request body"
         )));
     }
+    if body_stream_count > 1 {
+        return Err(ContractError::InvalidMethod(
+            "an HTTP operation permits at most one BodyStream parameter".into(),
+        ));
+    }
+    if body_stream_count == 1 && body_count + body_field_count != 0 {
+        return Err(ContractError::InvalidMethod(
+            "a streamed request body cannot be combined with JSON body parameters".into(),
+        ));
+    }
+    if body_stream_count == 1 && !matches!(*method, Method::POST | Method::PATCH) {
+        return Err(ContractError::InvalidMethod(format!(
+            "HTTP {method} methods may be replayed and cannot stream a request body"
+        )));
+    }
     if placeholders != path_parameters {
         return Err(ContractError::InvalidMethod(
             "HTTP route placeholders do not match Path parameters".into(),
@@ -1294,6 +1429,7 @@ mod tests {
             HttpParameterSource::Body,
             HttpParameterSource::QueryMap,
             HttpParameterSource::HeaderMap,
+            HttpParameterSource::BodyStream,
         ] {
             let parameter =
                 HttpParameter::new("value", source, HttpParameterCardinality::Repeated).unwrap();
@@ -1323,6 +1459,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn body_stream_is_single_exclusive_and_limited_to_non_replayable_methods() {
+        let stream = |name: &str| {
+            HttpParameter::new(
+                name,
+                HttpParameterSource::BodyStream,
+                HttpParameterCardinality::Scalar,
+            )
+            .unwrap()
+        };
+        for method in [Method::POST, Method::PATCH] {
+            assert!(http_operation(method, "/uploads", vec![stream("chunks")]).is_ok());
+        }
+        for method in [
+            Method::GET,
+            Method::HEAD,
+            Method::OPTIONS,
+            Method::PUT,
+            Method::DELETE,
+        ] {
+            let error = http_operation(method, "/uploads", vec![stream("chunks")]).unwrap_err();
+            assert!(error.to_string().contains("cannot stream"), "{error}");
+        }
+
+        let two = http_operation(
+            Method::POST,
+            "/uploads",
+            vec![stream("first"), stream("second")],
+        )
+        .unwrap_err();
+        assert!(two.to_string().contains("at most one"));
+
+        for source in [HttpParameterSource::Body, HttpParameterSource::BodyField] {
+            let mixed = http_operation(
+                Method::POST,
+                "/uploads",
+                vec![
+                    stream("chunks"),
+                    HttpParameter::new("document", source, HttpParameterCardinality::Scalar)
+                        .unwrap(),
+                ],
+            )
+            .unwrap_err();
+            assert!(mixed.to_string().contains("cannot be combined"));
+        }
+    }
+
     #[test]
     fn http_routes_require_canonical_rfc3986_literals() {
         for path in [
@@ -1423,6 +1606,30 @@ mod tests {
         assert_eq!(classified, plain);
         assert!(format!("{classified:?}").contains("has_sensitivity: true"));
 
+        let defaults = MethodClientDefaults::new(Some(Duration::from_secs(30)), Some(0));
+        let tuned = classified.clone().with_client_defaults(defaults);
+        assert_eq!(plain.client_defaults(), MethodClientDefaults::default());
+        assert_eq!(
+            tuned.client_defaults().timeout(),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(tuned.client_defaults().retries(), Some(0));
+        assert_eq!(tuned, plain);
+
+        let background = plain.clone().with_priority(MethodPriority::Low);
+        assert_eq!(plain.priority(), MethodPriority::Normal);
+        assert_eq!(background.priority(), MethodPriority::Low);
+        assert_eq!(background, plain);
+        for priority in [
+            MethodPriority::Low,
+            MethodPriority::Normal,
+            MethodPriority::High,
+            MethodPriority::Critical,
+        ] {
+            assert_eq!(MethodPriority::parse(priority.as_str()), Some(priority));
+        }
+        assert_eq!(MethodPriority::parse("High"), None);
+
         let selector = ServiceSelector::new("inventory", None, None).unwrap();
         let plain_service = ServiceDescriptor::new(selector.clone(), vec![plain]).unwrap();
         let classified_service = ServiceDescriptor::new(selector, vec![classified]).unwrap();
@@ -0,0 +1,398 @@
+use crate::EtcdConfig;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::{Bytes, BytesMut};
+use http::{Request, StatusCode};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::Incoming;
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Minimal client for the etcd v3 JSON gateway (`/v3/...`).
+///
+/// Keys and values are base64-encoded and 64-bit integers are decimal strings, as the gateway's
+/// protobuf JSON mapping requires.
+pub(crate) struct EtcdApi {
+    client: Client<HttpConnector, Full<Bytes>>,
+    base: url::Url,
+}
+
+/// Failure of one gateway request, classified by the registry.
+#[derive(Debug)]
+pub(crate) enum ApiError {
+    Transport(hyper_util::client::legacy::Error),
+    Body(Box<dyn std::error::Error + Send + Sync>),
+    TooLarge,
+    Timeout,
+    Status(StatusCode),
+    Decode(serde_json::Error),
+    Stream(String),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(_) => formatter.write_str("etcd gateway connection failed"),
+            Self::Body(_) => formatter.write_str("etcd gateway response body failed"),
+            Self::TooLarge => write!(
+                formatter,
+                "etcd gateway response exceeded {MAX_RESPONSE_BYTES} bytes"
+            ),
+            Self::Timeout => formatter.write_str("etcd gateway request timed out"),
+            Self::Status(status) => write!(formatter, "etcd gateway returned HTTP {status}"),
+            Self::Decode(_) => formatter.write_str("etcd gateway response was not valid JSON"),
+            Self::Stream(message) => write!(formatter, "etcd watch failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(error) => Some(error),
+            Self::Body(error) => Some(error.as_ref()),
+            Self::Decode(error) => Some(error),
+            Self::TooLarge | Self::Timeout | Self::Status(_) | Self::Stream(_) => None,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub(crate) struct ResponseHeader {
+    #[serde(default, deserialize_with = "int64")]
+    pub(crate) revision: i64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct KeyValue {
+    #[serde(default, deserialize_with = "base64")]
+    pub(crate) key: Vec<u8>,
+    #[serde(default, deserialize_with = "base64")]
+    pub(crate) value: Vec<u8>,
+    #[serde(default, deserialize_with = "int64")]
+    pub(crate) mod_revision: i64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RangeResponse {
+    #[serde(default)]
+    pub(crate) header: ResponseHeader,
+    #[serde(default)]
+    pub(crate) kvs: Vec<KeyValue>,
+}
+
+#[derive(Deserialize)]
+struct LeaseResponse {
+    #[serde(rename = "ID", default, deserialize_with = "int64")]
+    id: i64,
+    #[serde(rename = "TTL", default, deserialize_with = "int64")]
+    ttl: i64,
+}
+
+#[derive(Deserialize)]
+struct KeepAliveResponse {
+    result: LeaseResponse,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WatchResponse {
+    #[serde(default)]
+    pub(crate) canceled: bool,
+    #[serde(default, deserialize_with = "int64")]
+    pub(crate) compact_revision: i64,
+    #[serde(default)]
+    pub(crate) cancel_reason: String,
+    #[serde(default)]
+    pub(crate) events: Vec<WatchEvent>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WatchEvent {
+    /// Absent for `PUT`, the zero value of the protobuf enum.
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    pub(crate) kv: KeyValue,
+}
+
+impl WatchEvent {
+    pub(crate) fn is_delete(&self) -> bool {
+        self.kind.as_deref() == Some("DELETE")
+    }
+}
+
+#[derive(Deserialize)]
+struct WatchMessage {
+    result: Option<WatchResponse>,
+    error: Option<serde_json::Value>,
+}
+
+/// Newline-delimited watch responses of one gateway stream.
+pub(crate) struct WatchStream {
+    body: Incoming,
+    buffer: BytesMut,
+}
+
+impl WatchStream {
+    /// Returns the next watch response, or `None` when the gateway ended the stream.
+    pub(crate) async fn next(&mut self) -> Result<Option<WatchResponse>, ApiError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line = self.buffer.split_to(end + 1);
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let message: WatchMessage =
+                    serde_json::from_slice(&line).map_err(ApiError::Decode)?;
+                return match (message.result, message.error) {
+                    (Some(response), None) => Ok(Some(response)),
+                    (_, Some(error)) => Err(ApiError::Stream(
+                        error
+                            .get("message")
+                            .and_then(serde_json::Value::as_str)
+                            .unwrap_or("gateway reported a stream error")
+                            .to_owned(),
+                    )),
+                    (None, None) => Err(ApiError::Stream("empty watch message".into())),
+                };
+            }
+            match self.body.frame().await {
+                None => return Ok(None),
+                Some(Err(error)) => return Err(ApiError::Body(Box::new(error))),
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        if self.buffer.len() + data.len() > MAX_RESPONSE_BYTES {
+                            return Err(ApiError::TooLarge);
+                        }
+                        self.buffer.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl EtcdApi {
+    /// Creates a client from validated configuration without performing network I/O.
+    pub(crate) fn new(config: &EtcdConfig) -> Self {
+        let mut connector = HttpConnector::new();
+        // Watches stay idle for long periods; keepalive detects a member that vanished.
+        connector.set_keepalive(Some(TCP_KEEPALIVE));
+        Self {
+            client: Client::builder(TokioExecutor::new()).build(connector),
+            base: url::Url::parse(config.endpoint()).expect("validated etcd endpoint"),
+        }
+    }
+
+    /// Grants a lease and returns its ID.
+    pub(crate) async fn grant(&self, ttl: Duration) -> Result<i64, ApiError> {
+        let response: LeaseResponse = self
+            .call(
+                "lease/grant",
+                &serde_json::json!({ "TTL": ttl.as_secs().to_string(), "ID": "0" }),
+            )
+            .await?;
+        Ok(response.id)
+    }
+
+    /// Refreshes a lease once and returns its remaining TTL, zero when it no longer exists.
+    pub(crate) async fn keep_alive(&self, lease: i64) -> Result<i64, ApiError> {
+        let response: KeepAliveResponse = self
+            .call(
+                "lease/keepalive",
+                &serde_json::json!({ "ID": lease.to_string() }),
+            )
+            .await?;
+        Ok(response.result.ttl)
+    }
+
+    /// Revokes a lease and deletes every key attached to it.
+    pub(crate) async fn revoke(&self, lease: i64) -> Result<(), ApiError> {
+        self.call::<_, serde_json::Value>(
+            "lease/revoke",
+            &serde_json::json!({ "ID": lease.to_string() }),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Writes one key attached to `lease`.
+    pub(crate) async fn put(&self, key: &str, value: &[u8], lease: i64) -> Result<(), ApiError> {
+        self.call::<_, serde_json::Value>(
+            "kv/put",
+            &serde_json::json!({
+                "key": STANDARD.encode(key),
+                "value": STANDARD.encode(value),
+                "lease": lease.to_string(),
+            }),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Reads every key under `prefix` at the current revision.
+    pub(crate) async fn range(&self, prefix: &str) -> Result<RangeResponse, ApiError> {
+        self.call(
+            "kv/range",
+            &serde_json::json!({
+                "key": STANDARD.encode(prefix),
+                "range_end": STANDARD.encode(prefix_end(prefix)),
+            }),
+        )
+        .await
+    }
+
+    /// Opens a watch on every key under `prefix`, starting at `start_revision`.
+    pub(crate) async fn watch(
+        &self,
+        prefix: &str,
+        start_revision: i64,
+    ) -> Result<WatchStream, ApiError> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "create_request": {
+                "key": STANDARD.encode(prefix),
+                "range_end": STANDARD.encode(prefix_end(prefix)),
+                "start_revision": start_revision.to_string(),
+            }
+        }))
+        .map_err(ApiError::Decode)?;
+        let response = tokio::time::timeout(
+            REQUEST_TIMEOUT,
+            self.client.request(self.request("watch", body)),
+        )
+        .await
+        .map_err(|_| ApiError::Timeout)?
+        .map_err(ApiError::Transport)?;
+        if !response.status().is_success() {
+            return Err(ApiError::Status(response.status()));
+        }
+        Ok(WatchStream {
+            body: response.into_body(),
+            buffer: BytesMut::new(),
+        })
+    }
+
+    async fn call<Q, R>(&self, path: &str, request: &Q) -> Result<R, ApiError>
+    where
+        Q: Serialize,
+        R: DeserializeOwned,
+    {
+        let body = serde_json::to_vec(request).map_err(ApiError::Decode)?;
+        let request = self.request(path, body);
+        tokio::time::timeout(REQUEST_TIMEOUT, async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(ApiError::Transport)?;
+            let status = response.status();
+            let body = Limited::new(response.into_body(), MAX_RESPONSE_BYTES)
+                .collect()
+                .await
+                .map_err(|error| {
+                    if error.is::<LengthLimitError>() {
+                        ApiError::TooLarge
+                    } else {
+                        ApiError::Body(error)
+                    }
+                })?
+                .to_bytes();
+            if !status.is_success() {
+                return Err(ApiError::Status(status));
+            }
+            // Streaming endpoints answer with newline-delimited messages; the first is the reply.
+            let first = body
+                .split(|byte| *byte == b'\n')
+                .find(|line| !line.iter().all(u8::is_ascii_whitespace))
+                .unwrap_or(b"{}");
+            serde_json::from_slice(first).map_err(ApiError::Decode)
+        })
+        .await
+        .map_err(|_| ApiError::Timeout)?
+    }
+
+    fn request(&self, path: &str, body: Vec<u8>) -> Request<Full<Bytes>> {
+        let mut url = self.base.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().push("v3").extend(path.split('/'));
+        }
+        Request::post(url.as_str())
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(body)))
+            .expect("gateway request parts are valid")
+    }
+}
+
+/// Returns the smallest key greater than every key starting with `prefix`.
+fn prefix_end(prefix: &str) -> Vec<u8> {
+    let mut end = prefix.as_bytes().to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return end;
+        }
+    }
+    // Every byte was 0xff: etcd uses "\0" to mean "all keys".
+    vec![0]
+}
+
+fn int64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Int64 {
+        Text(String),
+        Number(i64),
+    }
+
+    match Int64::deserialize(deserializer)? {
+        Int64::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        Int64::Number(number) => Ok(number),
+    }
+}
+
+fn base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    STANDARD
+        .decode(String::deserialize(deserializer)?)
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_ends_cover_exactly_the_prefix() {
+        assert_eq!(prefix_end("/svc/a/"), b"/svc/a0");
+        assert_eq!(prefix_end("ab\u{7f}"), [b'a', b'b', 0x80]);
+    }
+
+    #[test]
+    fn gateway_messages_decode_string_integers_and_default_put_events() {
+        let message: WatchMessage = serde_json::from_str(
+            r#"{"result":{"header":{"revision":"42"},"events":[
+                {"kv":{"key":"L2E=","value":"eA==","mod_revision":"42"}},
+                {"type":"DELETE","kv":{"key":"L2I="}}
+            ]}}"#,
+        )
+        .unwrap();
+        let response = message.result.unwrap();
+        assert_eq!(response.events[0].kv.mod_revision, 42);
+        assert!(!response.events[0].is_delete());
+        assert_eq!(response.events[0].kv.key, b"/a");
+        assert_eq!(response.events[0].kv.value, b"x");
+        assert!(response.events[1].is_delete());
+        assert!(response.events[1].kv.value.is_empty());
+    }
+}
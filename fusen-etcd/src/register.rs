@@ -0,0 +1,612 @@
+use crate::{
+    EtcdConfig,
+    api::{ApiError, EtcdApi, WatchEvent},
+};
+use fusen_contract::{
+    EndpointCapabilities, HttpBindingId, HttpVersionSet, InstanceId, ServiceEndpoint,
+    ServiceInstance, ServiceRegistration, ServiceSelector, ServiceWeight,
+};
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    directory::{DirectoryPublisher, DirectoryState, directory},
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+const NO_LEASE: i64 = 0;
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// etcd-backed service registry and discovery provider.
+///
+/// Each registration is stored at `<key_prefix>/<service_id>/<instance_id>` and bound to its own
+/// lease, so a crashed process disappears from discovery once the lease expires. Subscriptions
+/// watch `<key_prefix>/<service_id>/` and strictly match the selector's version and group.
+#[derive(Clone)]
+pub struct EtcdRegistry {
+    api: Arc<EtcdApi>,
+    key_prefix: Arc<str>,
+    lease_ttl: Duration,
+}
+
+impl EtcdRegistry {
+    /// Creates a registry for one etcd gateway.
+    ///
+    /// An unusable gateway endpoint, key prefix, or lease TTL fails with `InvalidResource`.
+    /// Leases are granted when a registration handle is activated, and keys are watched when a
+    /// subscription handle is.
+    pub fn new(config: EtcdConfig) -> Result<Self, RegistryError> {
+        config.validate().map_err(|error| {
+            RegistryError::new(
+                RegistryOperation::Build,
+                RegistryErrorKind::InvalidResource,
+                error,
+            )
+        })?;
+        Ok(Self {
+            api: Arc::new(EtcdApi::new(&config)),
+            key_prefix: config.key_prefix().into(),
+            lease_ttl: config.lease_ttl(),
+        })
+    }
+
+    fn service_prefix(&self, selector: &ServiceSelector) -> String {
+        format!("{}/{}/", self.key_prefix, selector.service_id())
+    }
+}
+
+impl std::fmt::Debug for EtcdRegistry {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("EtcdRegistry")
+            .field("key_prefix", &self.key_prefix)
+            .field("lease_ttl", &self.lease_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Registry for EtcdRegistry {
+    fn prepare_registration(
+        &self,
+        request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        let registration = request.into_registration();
+        let key = Arc::new(format!(
+            "{}{}",
+            self.service_prefix(registration.selector()),
+            registration.instance_id()
+        ));
+        let value = Arc::new(
+            serde_json::to_vec(&InstanceRecord::from_registration(&registration)).map_err(
+                |error| {
+                    RegistryError::new(
+                        RegistryOperation::PrepareRegistration,
+                        RegistryErrorKind::Internal,
+                        error,
+                    )
+                },
+            )?,
+        );
+        let lease = Arc::new(AtomicI64::new(NO_LEASE));
+        let keeper = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let ttl = self.lease_ttl;
+        let activate_api = self.api.clone();
+        let activate_lease = lease.clone();
+        let activate_keeper = keeper.clone();
+        let close_api = self.api.clone();
+
+        Ok(provider::registration(
+            async move {
+                attach(&activate_api, &key, &value, ttl, &activate_lease)
+                    .await
+                    .map_err(|error| {
+                        registry_error(RegistryOperation::ActivateRegistration, error)
+                    })?;
+                let task = tokio::spawn(keep_alive(activate_api, key, value, ttl, activate_lease));
+                *lock(&activate_keeper) = Some(task);
+                Ok(())
+            },
+            move || async move {
+                let task = lock(&keeper).take();
+                if let Some(task) = task {
+                    task.abort();
+                    let _ = task.await;
+                }
+                match lease.swap(NO_LEASE, Ordering::AcqRel) {
+                    NO_LEASE => Ok(()),
+                    id => match close_api.revoke(id).await {
+                        Ok(()) | Err(ApiError::Status(StatusCode::NOT_FOUND)) => Ok(()),
+                        Err(error) => {
+                            Err(registry_error(RegistryOperation::CloseRegistration, error))
+                        }
+                    },
+                }
+            },
+        ))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let selector = Arc::new(request.into_selector());
+        let prefix = Arc::new(self.service_prefix(&selector));
+        let (publisher, directory) = directory();
+        let watch = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let activate_api = self.api.clone();
+        let activate_watch = watch.clone();
+
+        Ok(provider::subscription(
+            directory,
+            async move {
+                let (entries, revision) =
+                    list(&activate_api, &prefix, &selector)
+                        .await
+                        .map_err(|error| {
+                            registry_error(RegistryOperation::ActivateSubscription, error)
+                        })?;
+                publisher.publish_ready(entries.values().cloned().collect())?;
+                let task = tokio::spawn(watch_instances(Watcher {
+                    api: activate_api,
+                    prefix,
+                    selector,
+                    publisher,
+                    entries,
+                    revision,
+                    stale: false,
+                }));
+                *lock(&activate_watch) = Some(task);
+                Ok(())
+            },
+            move || async move {
+                let task = lock(&watch).take();
+                if let Some(task) = task {
+                    task.abort();
+                    let _ = task.await;
+                }
+                Ok(())
+            },
+        ))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Grants a lease and writes the key under it.
+///
+/// The lease is recorded before the write so that close revokes it even when the write's outcome
+/// is unknown; a failed write revokes it immediately.
+async fn attach(
+    api: &EtcdApi,
+    key: &str,
+    value: &[u8],
+    ttl: Duration,
+    lease: &AtomicI64,
+) -> Result<(), ApiError> {
+    let id = api.grant(ttl).await?;
+    lease.store(id, Ordering::Release);
+    if let Err(error) = api.put(key, value, id).await {
+        if lease
+            .compare_exchange(id, NO_LEASE, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            let _ = api.revoke(id).await;
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Refreshes the lease every third of its TTL until the registration is closed.
+///
+/// A lease that expired anyway, for example while the process was suspended, took the key with
+/// it; the key is then written again under a new lease.
+async fn keep_alive(
+    api: Arc<EtcdApi>,
+    key: Arc<String>,
+    value: Arc<Vec<u8>>,
+    ttl: Duration,
+    lease: Arc<AtomicI64>,
+) {
+    let mut ticker = tokio::time::interval(ttl / 3);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await;
+    let mut failing = false;
+    loop {
+        ticker.tick().await;
+        let result = match api.keep_alive(lease.load(Ordering::Acquire)).await {
+            Ok(remaining) if remaining > 0 => Ok(()),
+            Ok(_) => attach(&api, &key, &value, ttl, &lease).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(()) => failing = false,
+            Err(error) if !failing => {
+                failing = true;
+                tracing::warn!(error = %error, key = %key, "etcd lease keepalive failed");
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+async fn list(
+    api: &EtcdApi,
+    prefix: &str,
+    selector: &ServiceSelector,
+) -> Result<(BTreeMap<InstanceId, ServiceInstance>, i64), ApiError> {
+    let response = api.range(prefix).await?;
+    let mut entries = BTreeMap::new();
+    for kv in response.kvs {
+        if let Some((id, Some(instance))) = decode_entry(&kv.key, &kv.value, prefix, selector) {
+            entries.insert(id, instance);
+        }
+    }
+    Ok((entries, response.header.revision))
+}
+
+struct Watcher {
+    api: Arc<EtcdApi>,
+    prefix: Arc<String>,
+    selector: Arc<ServiceSelector>,
+    publisher: DirectoryPublisher,
+    entries: BTreeMap<InstanceId, ServiceInstance>,
+    revision: i64,
+    stale: bool,
+}
+
+enum WatchEnd {
+    Compacted,
+    Ended,
+    Failed(ApiError),
+    Closed,
+}
+
+/// Follows the prefix watch, re-listing after compaction and marking the directory stale while
+/// the gateway cannot be watched.
+async fn watch_instances(mut watcher: Watcher) {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        if watcher.publisher.is_closed() {
+            return;
+        }
+        let error = match watcher.follow(&mut delay).await {
+            WatchEnd::Closed => return,
+            WatchEnd::Ended => {
+                // Gateways and proxies end idle streams; resume from the last seen revision.
+                tokio::time::sleep(MIN_RETRY_DELAY).await;
+                continue;
+            }
+            WatchEnd::Compacted => match watcher.relist().await {
+                Ok(true) => {
+                    delay = MIN_RETRY_DELAY;
+                    continue;
+                }
+                Ok(false) => return,
+                Err(error) => error,
+            },
+            WatchEnd::Failed(error) => error,
+        };
+        if !watcher.stale {
+            tracing::warn!(
+                error = %error,
+                service = %watcher.selector.identity(),
+                "etcd watch failed; keeping the previous instances"
+            );
+            if watcher
+                .publisher
+                .publish_state(DirectoryState::Stale)
+                .is_err()
+            {
+                return;
+            }
+            watcher.stale = true;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+impl Watcher {
+    async fn follow(&mut self, delay: &mut Duration) -> WatchEnd {
+        let mut stream = match self.api.watch(&self.prefix, self.revision + 1).await {
+            Ok(stream) => stream,
+            Err(error) => return WatchEnd::Failed(error),
+        };
+        loop {
+            let response = match stream.next().await {
+                Ok(Some(response)) => response,
+                Ok(None) => return WatchEnd::Ended,
+                Err(error) => return WatchEnd::Failed(error),
+            };
+            if response.compact_revision > 0 {
+                return WatchEnd::Compacted;
+            }
+            if response.canceled {
+                return WatchEnd::Failed(ApiError::Stream(if response.cancel_reason.is_empty() {
+                    "watch canceled by the server".into()
+                } else {
+                    response.cancel_reason
+                }));
+            }
+            *delay = MIN_RETRY_DELAY;
+            let mut changed = self.stale;
+            for event in &response.events {
+                changed |= self.apply(event);
+                self.revision = self.revision.max(event.kv.mod_revision);
+            }
+            if changed {
+                if self
+                    .publisher
+                    .publish_ready(self.entries.values().cloned().collect())
+                    .is_err()
+                {
+                    return WatchEnd::Closed;
+                }
+                self.stale = false;
+            }
+        }
+    }
+
+    /// Replaces every entry from a fresh listing; the events since `revision` were compacted.
+    async fn relist(&mut self) -> Result<bool, ApiError> {
+        let (entries, revision) = list(&self.api, &self.prefix, &self.selector).await?;
+        self.entries = entries;
+        self.revision = revision;
+        self.stale = false;
+        Ok(self
+            .publisher
+            .publish_snapshot(
+                DirectoryState::Ready,
+                self.entries.values().cloned().collect(),
+            )
+            .is_ok())
+    }
+
+    /// Applies one event and reports whether the matching instances changed.
+    fn apply(&mut self, event: &WatchEvent) -> bool {
+        let decoded = if event.is_delete() {
+            decode_entry(&event.kv.key, b"", &self.prefix, &self.selector).map(|(id, _)| (id, None))
+        } else {
+            decode_entry(&event.kv.key, &event.kv.value, &self.prefix, &self.selector)
+        };
+        match decoded {
+            Some((id, Some(instance))) => {
+                self.entries.insert(id, instance);
+                true
+            }
+            Some((id, None)) => self.entries.remove(&id).is_some(),
+            None => false,
+        }
+    }
+}
+
+fn registry_error(operation: RegistryOperation, error: ApiError) -> RegistryError {
+    let kind = match &error {
+        ApiError::Status(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+            RegistryErrorKind::Unauthorized
+        }
+        ApiError::Status(StatusCode::BAD_REQUEST) => RegistryErrorKind::InvalidResource,
+        ApiError::Timeout => RegistryErrorKind::Timeout,
+        ApiError::Decode(_) => RegistryErrorKind::Internal,
+        _ => RegistryErrorKind::Unavailable,
+    };
+    RegistryError::new(operation, kind, error)
+}
+
+/// JSON value stored at each instance key.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct InstanceRecord {
+    endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    http_versions: Vec<String>,
+    bindings: Vec<String>,
+    #[serde(default)]
+    invocation_controls: bool,
+    weight: f64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+}
+
+impl InstanceRecord {
+    fn from_registration(registration: &ServiceRegistration) -> Self {
+        let selector = registration.selector();
+        let capabilities = registration.capabilities();
+        Self {
+            endpoint: registration.endpoint().as_str().to_owned(),
+            version: selector.version().map(str::to_owned),
+            group: selector.group().map(str::to_owned),
+            http_versions: capabilities
+                .http_versions()
+                .labels()
+                .map(str::to_owned)
+                .collect(),
+            bindings: capabilities
+                .bindings()
+                .iter()
+                .map(|binding| binding.as_str().to_owned())
+                .collect(),
+            invocation_controls: capabilities.invocation_controls(),
+            weight: registration.weight().get(),
+            metadata: selector
+                .metadata()
+                .iter()
+                .chain(registration.metadata())
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+
+    fn into_instance(self, instance_id: InstanceId) -> Option<ServiceInstance> {
+        let endpoint = self.endpoint.parse::<ServiceEndpoint>().ok()?;
+        let versions =
+            HttpVersionSet::from_labels(self.http_versions.iter().map(String::as_str)).ok()?;
+        let bindings = self
+            .bindings
+            .iter()
+            .map(HttpBindingId::new)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        let capabilities =
+            EndpointCapabilities::new(versions, bindings, self.invocation_controls).ok()?;
+        ServiceInstance::new(
+            instance_id,
+            endpoint,
+            capabilities,
+            ServiceWeight::new(self.weight).ok()?,
+        )
+        .with_metadata(self.metadata)
+        .ok()
+    }
+}
+
+/// Decodes one key under `prefix`.
+///
+/// Returns `None` for keys that are not instance keys, and `Some((id, None))` for an instance key
+/// whose value is deleted, invalid, or registered for another version or group.
+fn decode_entry(
+    key: &[u8],
+    value: &[u8],
+    prefix: &str,
+    selector: &ServiceSelector,
+) -> Option<(InstanceId, Option<ServiceInstance>)> {
+    let suffix = std::str::from_utf8(key)
+        .ok()?
+        .strip_prefix(prefix)
+        .filter(|suffix| !suffix.contains('/'))?;
+    let id = InstanceId::new(suffix).ok()?;
+    let instance = serde_json::from_slice::<InstanceRecord>(value)
+        .ok()
+        .filter(|record| {
+            record.version.as_deref() == selector.version()
+                && record.group.as_deref() == selector.group()
+        })
+        .and_then(|record| record.into_instance(id.clone()));
+    Some((id, instance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusen_contract::{HttpOperation, MethodDescriptor, MethodId, ServiceDescriptor};
+
+    const PREFIX: &str = "/fusen/services/demo/";
+
+    fn selector() -> ServiceSelector {
+        ServiceSelector::new("demo", Some("blue".into()), Some("1.0.0".into())).unwrap()
+    }
+
+    fn registration() -> ServiceRegistration {
+        let descriptor = Box::leak(Box::new(
+            ServiceDescriptor::new(
+                selector(),
+                vec![
+                    MethodDescriptor::new(
+                        MethodId::new(0),
+                        "call",
+                        HttpOperation::new(
+                            "POST".parse().unwrap(),
+                            "/call",
+                            vec![],
+                            "application/json",
+                            "application/json",
+                        )
+                        .unwrap(),
+                    )
+                    .unwrap(),
+                ],
+            )
+            .unwrap(),
+        ));
+        ServiceRegistration::new(
+            InstanceId::new("demo-1").unwrap(),
+            descriptor,
+            "https://10.0.0.7:8443/rpc".parse().unwrap(),
+            EndpointCapabilities::new(
+                HttpVersionSet::ALL,
+                [
+                    HttpBindingId::new("vendor-v1").unwrap(),
+                    HttpBindingId::default(),
+                ],
+                true,
+            )
+            .unwrap(),
+            ServiceWeight::new(2.5).unwrap(),
+        )
+        .with_metadata(BTreeMap::from([("zone".into(), "east".into())]))
+        .unwrap()
+    }
+
+    fn record_json() -> Vec<u8> {
+        serde_json::to_vec(&InstanceRecord::from_registration(&registration())).unwrap()
+    }
+
+    #[test]
+    fn records_round_trip_registration_capabilities_and_metadata() {
+        let (id, instance) = decode_entry(
+            format!("{PREFIX}demo-1").as_bytes(),
+            &record_json(),
+            PREFIX,
+            &selector(),
+        )
+        .unwrap();
+        let instance = instance.unwrap();
+        assert_eq!(id.as_str(), "demo-1");
+        assert_eq!(instance.endpoint().as_str(), "https://10.0.0.7:8443/rpc");
+        assert_eq!(instance.weight().get(), 2.5);
+        assert!(instance.capabilities().invocation_controls());
+        assert_eq!(instance.capabilities().bindings().len(), 2);
+        assert_eq!(instance.metadata()["zone"], "east");
+    }
+
+    #[test]
+    fn other_versions_invalid_values_and_nested_keys_do_not_match() {
+        let key = format!("{PREFIX}demo-1");
+        let unversioned = ServiceSelector::new("demo", Some("blue".into()), None).unwrap();
+        let (_, instance) =
+            decode_entry(key.as_bytes(), &record_json(), PREFIX, &unversioned).unwrap();
+        assert!(instance.is_none());
+
+        let (_, instance) = decode_entry(key.as_bytes(), b"{", PREFIX, &selector()).unwrap();
+        assert!(instance.is_none());
+
+        let nested = format!("{PREFIX}demo-1/extra");
+        assert!(decode_entry(nested.as_bytes(), &record_json(), PREFIX, &selector()).is_none());
+        assert!(
+            decode_entry(
+                b"/fusen/services/other/x",
+                &record_json(),
+                PREFIX,
+                &selector()
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn unusable_configuration_fails_as_a_build_error() {
+        let config: EtcdConfig =
+            serde_json::from_value(serde_json::json!({ "lease_ttl_secs": 1 })).unwrap();
+        let error = EtcdRegistry::new(config).unwrap_err();
+        assert_eq!(error.operation(), RegistryOperation::Build);
+        assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+    }
+}
@@ -0,0 +1,522 @@
+//! Lease-bound registration and prefix-watch discovery against an in-memory etcd stand-in that
+//! speaks the v3 JSON gateway protocol.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use fusen_etcd::{EtcdConfig, EtcdRegistry};
+use fusen_register::{
+    RegistrationHandle, Registry, SubscriptionRequest,
+    directory::DirectoryState,
+    error::RegistryErrorKind,
+    testing::{eventually, ids, registration, selector, wait_for},
+};
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full, StreamBody, combinators::BoxBody};
+use hyper::{
+    body::{Frame, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::mpsc};
+
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Body = BoxBody<Bytes, Infallible>;
+
+struct Entry {
+    value: Vec<u8>,
+    mod_revision: i64,
+    lease: i64,
+}
+
+struct Watcher {
+    key: Vec<u8>,
+    range_end: Vec<u8>,
+    sender: mpsc::UnboundedSender<Bytes>,
+}
+
+impl Watcher {
+    fn covers(&self, key: &[u8]) -> bool {
+        key >= self.key.as_slice() && key < self.range_end.as_slice()
+    }
+}
+
+/// One revision of the key space, retained until it is compacted.
+struct Event {
+    revision: i64,
+    key: Vec<u8>,
+    value: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct Store {
+    revision: i64,
+    compacted: i64,
+    next_lease: i64,
+    entries: BTreeMap<Vec<u8>, Entry>,
+    leases: BTreeMap<i64, i64>,
+    history: Vec<Event>,
+    watchers: Vec<Watcher>,
+    keepalives: usize,
+    compactions_reported: usize,
+    failure: Option<StatusCode>,
+}
+
+impl Store {
+    fn record(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+        let line = event_line(self.revision, &key, value.as_deref());
+        self.watchers
+            .retain(|watcher| !watcher.covers(&key) || watcher.sender.send(line.clone()).is_ok());
+        self.history.push(Event {
+            revision: self.revision,
+            key,
+            value,
+        });
+    }
+
+    fn put(&mut self, key: Vec<u8>, value: Vec<u8>, lease: i64) {
+        self.revision += 1;
+        self.entries.insert(
+            key.clone(),
+            Entry {
+                value: value.clone(),
+                mod_revision: self.revision,
+                lease,
+            },
+        );
+        self.record(key, Some(value));
+    }
+
+    fn delete(&mut self, key: &[u8]) {
+        if self.entries.remove(key).is_some() {
+            self.revision += 1;
+            self.record(key.to_vec(), None);
+        }
+    }
+
+    fn drop_lease(&mut self, lease: i64) -> bool {
+        if self.leases.remove(&lease).is_none() {
+            return false;
+        }
+        let keys = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.lease == lease)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.delete(&key);
+        }
+        true
+    }
+}
+
+fn event_line(revision: i64, key: &[u8], value: Option<&[u8]>) -> Bytes {
+    let mut event = json!({
+        "kv": {
+            "key": STANDARD.encode(key),
+            "mod_revision": revision.to_string(),
+        }
+    });
+    match value {
+        Some(value) => event["kv"]["value"] = json!(STANDARD.encode(value)),
+        None => event["type"] = json!("DELETE"),
+    }
+    line(&json!({
+        "result": {
+            "header": { "revision": revision.to_string() },
+            "events": [event],
+        }
+    }))
+}
+
+fn line(value: &Value) -> Bytes {
+    Bytes::from(format!("{value}\n"))
+}
+
+fn int(value: &Value) -> i64 {
+    match value {
+        Value::String(text) => text.parse().unwrap(),
+        Value::Number(number) => number.as_i64().unwrap(),
+        _ => 0,
+    }
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+    value
+        .as_str()
+        .map(|text| STANDARD.decode(text).unwrap())
+        .unwrap_or_default()
+}
+
+fn respond(status: StatusCode, value: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Full::new(line(value)).boxed())
+        .unwrap()
+}
+
+#[derive(Clone)]
+struct FakeEtcd {
+    endpoint: String,
+    store: Arc<Mutex<Store>>,
+}
+
+impl FakeEtcd {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let fake = Self {
+            endpoint: format!("http://{}", listener.local_addr().unwrap()),
+            store: Arc::new(Mutex::new(Store {
+                revision: 1,
+                next_lease: 100,
+                ..Store::default()
+            })),
+        };
+        let server = fake.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let fake = server.clone();
+                tokio::spawn(async move {
+                    let _ = http1::Builder::new()
+                        .serve_connection(
+                            TokioIo::new(stream),
+                            service_fn(move |request| {
+                                let fake = fake.clone();
+                                async move { Ok::<_, Infallible>(fake.handle(request).await) }
+                            }),
+                        )
+                        .await;
+                });
+            }
+        });
+        fake
+    }
+
+    fn registry(&self) -> EtcdRegistry {
+        EtcdRegistry::new(
+            EtcdConfig::builder()
+                .endpoint(&self.endpoint)
+                .key_prefix("/test/services")
+                .lease_ttl(Duration::from_secs(3))
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn with<T>(&self, change: impl FnOnce(&mut Store) -> T) -> T {
+        change(&mut self.store.lock().unwrap())
+    }
+
+    fn lease_of(&self, key: &str) -> Option<i64> {
+        self.with(|store| store.entries.get(key.as_bytes()).map(|entry| entry.lease))
+    }
+
+    /// Ends every open watch stream, as a restarting member or proxy would.
+    fn disconnect_watchers(&self) {
+        self.with(|store| store.watchers.clear());
+    }
+
+    async fn handle(&self, request: Request<Incoming>) -> Response<Body> {
+        let path = request.uri().path().to_owned();
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let mut store = self.store.lock().unwrap();
+        if let Some(status) = store
+            .failure
+            .filter(|_| matches!(path.as_str(), "/v3/kv/range" | "/v3/watch"))
+        {
+            return respond(status, &json!({ "error": "injected", "code": 14 }));
+        }
+        let header = json!({ "revision": store.revision.to_string() });
+        match path.as_str() {
+            "/v3/lease/grant" => {
+                store.next_lease += 1;
+                let id = store.next_lease;
+                let ttl = int(&request["TTL"]);
+                store.leases.insert(id, ttl);
+                respond(
+                    StatusCode::OK,
+                    &json!({ "header": header, "ID": id.to_string(), "TTL": ttl.to_string() }),
+                )
+            }
+            "/v3/lease/keepalive" => {
+                let id = int(&request["ID"]);
+                // etcd answers an unknown lease with TTL 0 rather than an error.
+                let ttl = store.leases.get(&id).copied().unwrap_or(0);
+                if ttl > 0 {
+                    store.keepalives += 1;
+                }
+                respond(
+                    StatusCode::OK,
+                    &json!({ "result": { "header": header, "ID": id.to_string(), "TTL": ttl.to_string() } }),
+                )
+            }
+            "/v3/lease/revoke" => {
+                if store.drop_lease(int(&request["ID"])) {
+                    respond(StatusCode::OK, &json!({ "header": header }))
+                } else {
+                    respond(
+                        StatusCode::NOT_FOUND,
+                        &json!({ "error": "etcdserver: requested lease not found", "code": 5 }),
+                    )
+                }
+            }
+            "/v3/kv/put" => {
+                let lease = int(&request["lease"]);
+                if lease != 0 && !store.leases.contains_key(&lease) {
+                    return respond(
+                        StatusCode::NOT_FOUND,
+                        &json!({ "error": "etcdserver: requested lease not found", "code": 5 }),
+                    );
+                }
+                store.put(bytes(&request["key"]), bytes(&request["value"]), lease);
+                respond(
+                    StatusCode::OK,
+                    &json!({ "header": { "revision": store.revision.to_string() } }),
+                )
+            }
+            "/v3/kv/range" => {
+                let (key, range_end) = (bytes(&request["key"]), bytes(&request["range_end"]));
+                let kvs = store
+                    .entries
+                    .range(key..range_end)
+                    .map(|(key, entry)| {
+                        json!({
+                            "key": STANDARD.encode(key),
+                            "value": STANDARD.encode(&entry.value),
+                            "mod_revision": entry.mod_revision.to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                respond(
+                    StatusCode::OK,
+                    &json!({ "header": header, "count": kvs.len().to_string(), "kvs": kvs }),
+                )
+            }
+            "/v3/watch" => {
+                let create = &request["create_request"];
+                let watcher_key = bytes(&create["key"]);
+                let range_end = bytes(&create["range_end"]);
+                let start = int(&create["start_revision"]);
+                let (sender, receiver) = mpsc::unbounded_channel();
+                let _ = sender.send(line(
+                    &json!({ "result": { "header": header, "created": true } }),
+                ));
+                if start <= store.compacted {
+                    store.compactions_reported += 1;
+                    let _ = sender.send(line(&json!({
+                        "result": {
+                            "header": header,
+                            "compact_revision": store.compacted.to_string(),
+                            "canceled": true,
+                        }
+                    })));
+                } else {
+                    let watcher = Watcher {
+                        key: watcher_key,
+                        range_end,
+                        sender,
+                    };
+                    for event in store
+                        .history
+                        .iter()
+                        .filter(|event| event.revision >= start && watcher.covers(&event.key))
+                    {
+                        let _ = watcher.sender.send(event_line(
+                            event.revision,
+                            &event.key,
+                            event.value.as_deref(),
+                        ));
+                    }
+                    store.watchers.push(watcher);
+                }
+                let frames = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                    let line = receiver.recv().await?;
+                    Some((Ok::<_, Infallible>(Frame::data(line)), receiver))
+                });
+                Response::new(StreamBody::new(frames).boxed())
+            }
+            _ => respond(StatusCode::NOT_FOUND, &json!({ "error": "unknown path" })),
+        }
+    }
+}
+
+async fn register(
+    registry: &EtcdRegistry,
+    id: &str,
+    port: u16,
+    version: &str,
+) -> RegistrationHandle {
+    let handle = registry
+        .prepare_registration(registration(id, port, version))
+        .unwrap();
+    handle.activate().await.unwrap();
+    handle
+}
+
+const KEY: &str = "/test/services/greeter/greeter-1";
+
+#[tokio::test]
+async fn registrations_are_lease_bound_kept_alive_and_revoked_on_close() {
+    let fake = FakeEtcd::start().await;
+    let registry = fake.registry();
+    let handle = registry
+        .prepare_registration(registration("greeter-1", 8080, "1.0"))
+        .unwrap();
+    assert!(fake.lease_of(KEY).is_none());
+
+    handle.activate().await.unwrap();
+    let lease = fake.lease_of(KEY).unwrap();
+    assert_eq!(fake.with(|store| store.leases[&lease]), 3);
+    let value: Value =
+        fake.with(|store| serde_json::from_slice(&store.entries[KEY.as_bytes()].value).unwrap());
+    assert_eq!(value["endpoint"], "http://127.0.0.1:8080/");
+    assert_eq!(value["version"], "1.0");
+    eventually("the lease is kept alive", || {
+        fake.with(|store| store.keepalives >= 1)
+    })
+    .await;
+
+    handle.close().await.unwrap();
+    assert!(fake.lease_of(KEY).is_none());
+    assert!(fake.with(|store| store.leases.is_empty()));
+}
+
+#[tokio::test]
+async fn expired_leases_are_replaced_and_the_key_is_written_again() {
+    let fake = FakeEtcd::start().await;
+    let handle = register(&fake.registry(), "greeter-1", 8080, "1.0").await;
+    let lease = fake.lease_of(KEY).unwrap();
+
+    fake.with(|store| store.drop_lease(lease));
+    assert!(fake.lease_of(KEY).is_none());
+    eventually("the key is attached to a new lease", || {
+        fake.lease_of(KEY).is_some_and(|current| current != lease)
+    })
+    .await;
+
+    handle.close().await.unwrap();
+    assert!(fake.lease_of(KEY).is_none());
+}
+
+#[tokio::test]
+async fn subscriptions_follow_prefix_events_of_the_selected_version() {
+    let fake = FakeEtcd::start().await;
+    let registry = fake.registry();
+    let first = register(&registry, "greeter-1", 8080, "1.0").await;
+    let other_version = register(&registry, "greeter-v2", 9090, "2.0").await;
+
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    let snapshot = directory.snapshot();
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+
+    let second = register(&registry, "greeter-2", 8081, "1.0").await;
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 2
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1", "greeter-2"]);
+
+    first.close().await.unwrap();
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 1
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-2"]);
+
+    handle.close().await.unwrap();
+    assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+    second.close().await.unwrap();
+    other_version.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn compacted_watches_relist_the_prefix() {
+    let fake = FakeEtcd::start().await;
+    let registry = fake.registry();
+    let first = register(&registry, "greeter-1", 8080, "1.0").await;
+    let second = register(&registry, "greeter-2", 8081, "1.0").await;
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    assert_eq!(directory.snapshot().len(), 2);
+
+    // The deletion happens while no watch is open and is compacted before the watch resumes.
+    fake.with(|store| {
+        store.watchers.clear();
+        store.delete(KEY.as_bytes());
+        store.compacted = store.revision;
+        store.history.clear();
+    });
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 1
+    })
+    .await;
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(ids(&snapshot), ["greeter-2"]);
+    assert!(fake.with(|store| store.compactions_reported) >= 1);
+
+    handle.close().await.unwrap();
+    first.close().await.unwrap();
+    second.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn unreachable_watches_mark_the_directory_stale_until_they_resume() {
+    let fake = FakeEtcd::start().await;
+    let registry = fake.registry();
+    let registration = register(&registry, "greeter-1", 8080, "1.0").await;
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+
+    fake.with(|store| store.failure = Some(StatusCode::SERVICE_UNAVAILABLE));
+    fake.disconnect_watchers();
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Stale
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+
+    fake.with(|store| store.failure = None);
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Ready
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["greeter-1"]);
+
+    handle.close().await.unwrap();
+    registration.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn failed_listings_fail_activation() {
+    let fake = FakeEtcd::start().await;
+    fake.with(|store| store.failure = Some(StatusCode::SERVICE_UNAVAILABLE));
+    let handle = fake
+        .registry()
+        .prepare_subscription(SubscriptionRequest::new(selector("1.0")))
+        .unwrap();
+
+    let error = handle.activate().await.unwrap_err();
+    assert_eq!(error.kind(), RegistryErrorKind::Unavailable);
+}
@@ -0,0 +1,487 @@
+use crate::KubernetesConfig;
+use bytes::{Bytes, BytesMut};
+use http::{HeaderValue, Request, StatusCode};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::Incoming;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use rustls::{
+    ClientConfig as TlsClientConfig, RootCertStore,
+    pki_types::{CertificateDer, pem::PemObject},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::Instant;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+/// Server-side lifetime of one watch; the API server ends the stream cleanly afterwards.
+const WATCH_TIMEOUT: Duration = Duration::from_secs(290);
+const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+/// Minimal read-only client for the Kubernetes API server.
+pub(crate) struct KubernetesApi {
+    client: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+    base: url::Url,
+    token_path: Option<PathBuf>,
+}
+
+/// Failure of one API server request, classified by the registry.
+#[derive(Debug)]
+pub(crate) enum ApiError {
+    Certificate(&'static str, Option<std::io::Error>),
+    Token(std::io::Error),
+    Transport(hyper_util::client::legacy::Error),
+    Body(Box<dyn std::error::Error + Send + Sync>),
+    TooLarge,
+    Timeout,
+    Status(StatusCode),
+    Decode(serde_json::Error),
+    Expired,
+    Stream(String),
+    /// A `fusen.rs/*` annotation of the watched Service cannot be parsed.
+    InvalidAnnotation(&'static str),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Certificate(reason, _) => {
+                write!(formatter, "Kubernetes CA certificate file {reason}")
+            }
+            Self::Token(_) => formatter.write_str("failed to read the Kubernetes token file"),
+            Self::Transport(_) => formatter.write_str("Kubernetes API server connection failed"),
+            Self::Body(_) => formatter.write_str("Kubernetes API server response body failed"),
+            Self::TooLarge => write!(
+                formatter,
+                "Kubernetes API server response exceeded {MAX_RESPONSE_BYTES} bytes"
+            ),
+            Self::Timeout => formatter.write_str("Kubernetes API server request timed out"),
+            Self::Status(status) => {
+                write!(formatter, "Kubernetes API server returned HTTP {status}")
+            }
+            Self::Decode(_) => {
+                formatter.write_str("Kubernetes API server response was not valid JSON")
+            }
+            Self::Expired => formatter.write_str("Kubernetes watch resource version expired"),
+            Self::Stream(message) => write!(formatter, "Kubernetes watch failed: {message}"),
+            Self::InvalidAnnotation(annotation) => {
+                write!(formatter, "Service annotation {annotation} is invalid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Certificate(_, Some(error)) | Self::Token(error) => Some(error),
+            Self::Transport(error) => Some(error),
+            Self::Body(error) => Some(error.as_ref()),
+            Self::Decode(error) => Some(error),
+            Self::Certificate(_, None)
+            | Self::TooLarge
+            | Self::Timeout
+            | Self::Status(_)
+            | Self::Expired
+            | Self::Stream(_)
+            | Self::InvalidAnnotation(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ObjectMeta {
+    #[serde(default)]
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) resource_version: String,
+    #[serde(default)]
+    pub(crate) annotations: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Service {
+    #[serde(default)]
+    pub(crate) metadata: ObjectMeta,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct EndpointSliceList {
+    #[serde(default)]
+    pub(crate) metadata: ObjectMeta,
+    #[serde(default)]
+    pub(crate) items: Option<Vec<EndpointSlice>>,
+}
+
+/// `discovery.k8s.io/v1` EndpointSlice, reduced to the fields used for discovery.
+///
+/// `endpoints` and `ports` are serialized as `null` when empty, hence the options.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EndpointSlice {
+    #[serde(default)]
+    pub(crate) metadata: ObjectMeta,
+    #[serde(default)]
+    pub(crate) address_type: String,
+    #[serde(default)]
+    pub(crate) endpoints: Option<Vec<Endpoint>>,
+    #[serde(default)]
+    pub(crate) ports: Option<Vec<EndpointPort>>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Endpoint {
+    #[serde(default)]
+    pub(crate) addresses: Vec<String>,
+    #[serde(default)]
+    pub(crate) conditions: EndpointConditions,
+    #[serde(default)]
+    pub(crate) target_ref: Option<ObjectReference>,
+    #[serde(default)]
+    pub(crate) zone: Option<String>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+pub(crate) struct EndpointConditions {
+    /// Unknown readiness is reported as absent and, per the API contract, treated as ready.
+    #[serde(default)]
+    pub(crate) ready: Option<bool>,
+}
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct ObjectReference {
+    #[serde(default)]
+    pub(crate) kind: String,
+    #[serde(default)]
+    pub(crate) name: String,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EndpointPort {
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) port: Option<u16>,
+    #[serde(default)]
+    pub(crate) protocol: Option<String>,
+    #[serde(default)]
+    pub(crate) app_protocol: Option<String>,
+}
+
+/// One change reported by a watch.
+pub(crate) enum WatchEvent {
+    Applied(Box<EndpointSlice>),
+    Deleted(Box<EndpointSlice>),
+    Bookmark(String),
+}
+
+#[derive(Deserialize)]
+struct RawWatchEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    object: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    #[serde(default)]
+    code: u16,
+    #[serde(default)]
+    message: String,
+}
+
+/// Newline-delimited events of one watch stream.
+pub(crate) struct WatchStream {
+    body: Incoming,
+    buffer: BytesMut,
+    deadline: Instant,
+}
+
+impl WatchStream {
+    /// Returns the next event, or `None` when the stream ended.
+    ///
+    /// A stream the server failed to close on time is treated as ended, so a half-open
+    /// connection cannot stall discovery.
+    pub(crate) async fn next(&mut self) -> Result<Option<WatchEvent>, ApiError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line = self.buffer.split_to(end + 1);
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                return decode_event(&line).map(Some);
+            }
+            let frame = match tokio::time::timeout_at(self.deadline, self.body.frame()).await {
+                Ok(frame) => frame,
+                Err(_) => return Ok(None),
+            };
+            match frame {
+                None => return Ok(None),
+                Some(Err(error)) => return Err(ApiError::Body(Box::new(error))),
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        if self.buffer.len() + data.len() > MAX_RESPONSE_BYTES {
+                            return Err(ApiError::TooLarge);
+                        }
+                        self.buffer.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn decode_event(line: &[u8]) -> Result<WatchEvent, ApiError> {
+    let event: RawWatchEvent = serde_json::from_slice(line).map_err(ApiError::Decode)?;
+    match event.kind.as_str() {
+        "ADDED" | "MODIFIED" => serde_json::from_value(event.object)
+            .map(|slice| WatchEvent::Applied(Box::new(slice)))
+            .map_err(ApiError::Decode),
+        "DELETED" => serde_json::from_value(event.object)
+            .map(|slice| WatchEvent::Deleted(Box::new(slice)))
+            .map_err(ApiError::Decode),
+        "BOOKMARK" => serde_json::from_value::<EndpointSlice>(event.object)
+            .map(|slice| WatchEvent::Bookmark(slice.metadata.resource_version))
+            .map_err(ApiError::Decode),
+        "ERROR" => {
+            let status: Status = serde_json::from_value(event.object).map_err(ApiError::Decode)?;
+            Err(if status.code == StatusCode::GONE.as_u16() {
+                ApiError::Expired
+            } else if status.message.is_empty() {
+                ApiError::Stream(format!("server reported status {}", status.code))
+            } else {
+                ApiError::Stream(status.message)
+            })
+        }
+        _ => Err(ApiError::Stream("unknown watch event type".into())),
+    }
+}
+
+impl KubernetesApi {
+    /// Creates a client from validated configuration; only the CA file is read.
+    pub(crate) fn new(config: &KubernetesConfig) -> Result<Self, ApiError> {
+        let tls = tls_config(config.ca_certificate_path())?;
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        // Watches stay idle for long periods; keepalive detects an API server that vanished.
+        http.set_keepalive(Some(TCP_KEEPALIVE));
+        let connector = HttpsConnectorBuilder::new()
+            .with_tls_config(tls)
+            .https_or_http()
+            .enable_http1()
+            .wrap_connector(http);
+        Ok(Self {
+            client: Client::builder(TokioExecutor::new()).build(connector),
+            base: url::Url::parse(config.api_server()).expect("validated API server"),
+            token_path: config.token_path().map(Path::to_path_buf),
+        })
+    }
+
+    /// Reads one Service, or `None` when it does not exist.
+    pub(crate) async fn service(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<Service>, ApiError> {
+        let url = self.url(&["api", "v1", "namespaces", namespace, "services", name]);
+        match self.get(url).await {
+            Ok(service) => Ok(Some(service)),
+            Err(ApiError::Status(StatusCode::NOT_FOUND)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Lists the EndpointSlices owned by one Service.
+    pub(crate) async fn endpoint_slices(
+        &self,
+        namespace: &str,
+        service: &str,
+    ) -> Result<EndpointSliceList, ApiError> {
+        self.get(self.slices_url(namespace, service)).await
+    }
+
+    /// Watches the EndpointSlices owned by one Service after `resource_version`.
+    pub(crate) async fn watch_endpoint_slices(
+        &self,
+        namespace: &str,
+        service: &str,
+        resource_version: &str,
+    ) -> Result<WatchStream, ApiError> {
+        let mut url = self.slices_url(namespace, service);
+        url.query_pairs_mut()
+            .append_pair("watch", "true")
+            .append_pair("allowWatchBookmarks", "true")
+            .append_pair("resourceVersion", resource_version)
+            .append_pair("timeoutSeconds", &WATCH_TIMEOUT.as_secs().to_string());
+        let request = self.request(url).await?;
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request))
+            .await
+            .map_err(|_| ApiError::Timeout)?
+            .map_err(ApiError::Transport)?;
+        match response.status() {
+            status if status.is_success() => Ok(WatchStream {
+                body: response.into_body(),
+                buffer: BytesMut::new(),
+                deadline: Instant::now() + WATCH_TIMEOUT + REQUEST_TIMEOUT,
+            }),
+            StatusCode::GONE => Err(ApiError::Expired),
+            status => Err(ApiError::Status(status)),
+        }
+    }
+
+    async fn get<R: DeserializeOwned>(&self, url: url::Url) -> Result<R, ApiError> {
+        let request = self.request(url).await?;
+        tokio::time::timeout(REQUEST_TIMEOUT, async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(ApiError::Transport)?;
+            let status = response.status();
+            let body = Limited::new(response.into_body(), MAX_RESPONSE_BYTES)
+                .collect()
+                .await
+                .map_err(|error| {
+                    if error.is::<LengthLimitError>() {
+                        ApiError::TooLarge
+                    } else {
+                        ApiError::Body(error)
+                    }
+                })?
+                .to_bytes();
+            if !status.is_success() {
+                return Err(ApiError::Status(status));
+            }
+            serde_json::from_slice(&body).map_err(ApiError::Decode)
+        })
+        .await
+        .map_err(|_| ApiError::Timeout)?
+    }
+
+    async fn request(&self, url: url::Url) -> Result<Request<Full<Bytes>>, ApiError> {
+        let mut request = Request::get(url.as_str())
+            .header(http::header::ACCEPT, "application/json")
+            .body(Full::new(Bytes::new()))
+            .expect("API server request parts are valid");
+        if let Some(path) = &self.token_path {
+            // The kubelet rotates projected tokens in place; never cache one.
+            let token = tokio::fs::read_to_string(path)
+                .await
+                .map_err(ApiError::Token)?;
+            let mut value =
+                HeaderValue::from_str(&format!("Bearer {}", token.trim())).map_err(|_| {
+                    ApiError::Token(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "token is not a valid header value",
+                    ))
+                })?;
+            value.set_sensitive(true);
+            request
+                .headers_mut()
+                .insert(http::header::AUTHORIZATION, value);
+        }
+        Ok(request)
+    }
+
+    fn slices_url(&self, namespace: &str, service: &str) -> url::Url {
+        let mut url = self.url(&[
+            "apis",
+            "discovery.k8s.io",
+            "v1",
+            "namespaces",
+            namespace,
+            "endpointslices",
+        ]);
+        url.query_pairs_mut()
+            .append_pair("labelSelector", &format!("{SERVICE_NAME_LABEL}={service}"));
+        url
+    }
+
+    fn url(&self, segments: &[&str]) -> url::Url {
+        let mut url = self.base.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+}
+
+fn tls_config(ca_certificate_path: Option<&Path>) -> Result<TlsClientConfig, ApiError> {
+    let mut roots = RootCertStore::empty();
+    match ca_certificate_path {
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        Some(path) => {
+            // The error chain ends up in registry errors; keep the I/O cause of an unreadable
+            // CA bundle, but not a parse error that could echo the bundle's lines.
+            let certificates = CertificateDer::pem_file_iter(path)
+                .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+                .map_err(|error| match error {
+                    rustls::pki_types::pem::Error::Io(error) => {
+                        ApiError::Certificate("could not be read", Some(error))
+                    }
+                    _ => ApiError::Certificate("is not valid PEM", None),
+                })?;
+            for certificate in certificates {
+                roots
+                    .add(certificate)
+                    .map_err(|_| ApiError::Certificate("contains an invalid trust anchor", None))?;
+            }
+            if roots.is_empty() {
+                return Err(ApiError::Certificate("contains no certificates", None));
+            }
+        }
+    }
+    Ok(
+        TlsClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|_| ApiError::Certificate("could not be used with TLS 1.2 or 1.3", None))?
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_events_decode_slices_bookmarks_and_expiry() {
+        let added = br#"{"type":"ADDED","object":{"metadata":{"name":"greeter-abc","resourceVersion":"7"},"addressType":"IPv4","endpoints":null,"ports":null}}"#;
+        let Ok(WatchEvent::Applied(slice)) = decode_event(added) else {
+            panic!("expected an applied slice");
+        };
+        assert_eq!(slice.metadata.name, "greeter-abc");
+        assert!(slice.endpoints.is_none());
+
+        let bookmark = br#"{"type":"BOOKMARK","object":{"kind":"EndpointSlice","metadata":{"resourceVersion":"12"}}}"#;
+        assert!(matches!(
+            decode_event(bookmark),
+            Ok(WatchEvent::Bookmark(version)) if version == "12"
+        ));
+
+        let expired = br#"{"type":"ERROR","object":{"kind":"Status","code":410,"reason":"Expired","message":"too old resource version"}}"#;
+        assert!(matches!(decode_event(expired), Err(ApiError::Expired)));
+    }
+
+    #[test]
+    fn a_ca_file_without_certificates_is_rejected() {
+        let path = std::env::temp_dir().join(format!("fusen-k8s-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate\n").unwrap();
+        let error = tls_config(Some(&path)).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(error, ApiError::Certificate(..)));
+    }
+}
@@ -0,0 +1,754 @@
+use crate::{
+    KubernetesConfig,
+    api::{ApiError, EndpointPort, EndpointSlice, KubernetesApi, WatchEvent},
+    is_dns_label,
+};
+use fusen_contract::{
+    EndpointCapabilities, HttpBindingId, HttpVersionSet, InstanceId, Metadata, ServiceEndpoint,
+    ServiceInstance, ServiceSelector, ServiceWeight,
+};
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    directory::{DirectoryPublisher, DirectoryState, directory},
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use http::StatusCode;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const ANNOTATION_HTTP_VERSIONS: &str = "fusen.rs/http-versions";
+const ANNOTATION_HTTP_BINDINGS: &str = "fusen.rs/http-bindings";
+const ANNOTATION_INVOCATION_CONTROLS: &str = "fusen.rs/invocation-controls";
+const INVOCATION_CONTROLS_V1: &str = "v1";
+const H2C_APP_PROTOCOL: &str = "kubernetes.io/h2c";
+/// Instance metadata key read by locality-aware routing.
+const ZONE_METADATA_KEY: &str = "zone";
+
+/// The Kubernetes Service whose ready endpoints become the instances of one fusen service.
+#[derive(Clone, Debug)]
+pub struct KubernetesTarget {
+    service: String,
+    namespace: Option<String>,
+    port: Option<String>,
+    capabilities: EndpointCapabilities,
+    metadata: Metadata,
+}
+
+impl KubernetesTarget {
+    /// Watches the EndpointSlices of the Service named `service`.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            namespace: None,
+            port: None,
+            capabilities: EndpointCapabilities::default(),
+            metadata: Metadata::new(),
+        }
+    }
+
+    /// Looks the Service up in `namespace` instead of the configured default namespace.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Calls the Service port named `name`.
+    ///
+    /// Without a name, the Service must expose exactly one TCP port.
+    pub fn port(mut self, name: impl Into<String>) -> Self {
+        self.port = Some(name.into());
+        self
+    }
+
+    /// Replaces the capabilities assumed when the Service carries no `fusen.rs/*` annotations.
+    pub fn capabilities(mut self, value: EndpointCapabilities) -> Self {
+        self.capabilities = value;
+        self
+    }
+
+    /// Replaces the user metadata attached to every discovered instance.
+    pub fn metadata(mut self, value: Metadata) -> Self {
+        self.metadata = value;
+        self
+    }
+
+    /// Returns the watched Service name.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+}
+
+/// Discovers service instances from Kubernetes EndpointSlices.
+///
+/// Each service selector is mapped to one [`KubernetesTarget`] by its `service[/group][@version]`
+/// identity; subscribing to an unmapped service fails. Every ready endpoint of the Service
+/// becomes one instance, identified by its pod, on the target's port. The slices are listed once
+/// and then watched; when the watch falls too far behind the API server, they are listed again.
+/// While the API server cannot be reached, the previous instances are kept and the directory is
+/// `Stale`.
+///
+/// Capabilities start from the target and may be overridden by the Service annotations
+/// `fusen.rs/http-versions` (for example `1.1,2`), `fusen.rs/http-bindings`, and
+/// `fusen.rs/invocation-controls: v1`. Annotations are read again whenever a watch starts. A port
+/// named `https` or `https-*`, or with `appProtocol: https`, is called over HTTPS; a port named
+/// `h2c`, `http2`, or with a matching prefix, or with `appProtocol: kubernetes.io/h2c`, is called
+/// over HTTP/2 unless the versions are annotated.
+///
+/// Registrations are accepted without side effects: the kubelet's readiness probes decide which
+/// pods are published.
+#[derive(Clone)]
+pub struct KubernetesRegistry {
+    api: Arc<KubernetesApi>,
+    targets: Arc<BTreeMap<String, Arc<ResolvedTarget>>>,
+}
+
+struct ResolvedTarget {
+    namespace: String,
+    target: KubernetesTarget,
+}
+
+impl std::fmt::Debug for KubernetesRegistry {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("KubernetesRegistry")
+            .field(
+                "targets",
+                &self
+                    .targets
+                    .iter()
+                    .map(|(identity, resolved)| (identity, &resolved.target))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for [`KubernetesRegistry`].
+#[derive(Clone, Debug)]
+pub struct KubernetesRegistryBuilder {
+    config: KubernetesConfig,
+    targets: Vec<(ServiceSelector, KubernetesTarget)>,
+}
+
+impl KubernetesRegistry {
+    /// Starts a registry without service targets.
+    pub fn builder(config: KubernetesConfig) -> KubernetesRegistryBuilder {
+        KubernetesRegistryBuilder {
+            config,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Returns the target watched for one service.
+    pub fn target(&self, selector: &ServiceSelector) -> Option<&KubernetesTarget> {
+        self.targets
+            .get(selector.identity())
+            .map(|resolved| &resolved.target)
+    }
+}
+
+impl KubernetesRegistryBuilder {
+    /// Watches `target` for subscriptions to `selector`.
+    pub fn target(mut self, selector: ServiceSelector, target: KubernetesTarget) -> Self {
+        self.targets.push((selector, target));
+        self
+    }
+
+    /// Validates targets and loads the API server trust anchors.
+    ///
+    /// No request is sent before a subscription is activated.
+    pub fn build(self) -> Result<KubernetesRegistry, RegistryError> {
+        self.config.validate().map_err(|error| {
+            RegistryError::new(
+                RegistryOperation::Build,
+                RegistryErrorKind::InvalidResource,
+                error,
+            )
+        })?;
+        let mut targets = BTreeMap::new();
+        for (selector, target) in self.targets {
+            let namespace = target
+                .namespace
+                .clone()
+                .unwrap_or_else(|| self.config.namespace().to_owned());
+            if !is_dns_label(&target.service) || !is_dns_label(&namespace) {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "Kubernetes targets require RFC 1123 label Service and namespace names",
+                ));
+            }
+            if target.port.as_deref().is_some_and(str::is_empty) {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "Kubernetes target port names must not be empty",
+                ));
+            }
+            if selector
+                .clone()
+                .with_metadata(target.metadata.clone())
+                .is_err()
+            {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "Kubernetes target metadata keys must be valid and must not use the fusen. prefix",
+                ));
+            }
+            if targets
+                .insert(
+                    selector.identity().to_owned(),
+                    Arc::new(ResolvedTarget { namespace, target }),
+                )
+                .is_some()
+            {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "each service may have only one Kubernetes target",
+                ));
+            }
+        }
+        let api = KubernetesApi::new(&self.config).map_err(|error| {
+            RegistryError::new(
+                RegistryOperation::Build,
+                RegistryErrorKind::InvalidResource,
+                error,
+            )
+        })?;
+        Ok(KubernetesRegistry {
+            api: Arc::new(api),
+            targets: Arc::new(targets),
+        })
+    }
+}
+
+impl Registry for KubernetesRegistry {
+    fn prepare_registration(
+        &self,
+        _request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        Ok(provider::registration(async { Ok(()) }, || async {
+            Ok(())
+        }))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let target = self
+            .targets
+            .get(request.selector().identity())
+            .cloned()
+            .ok_or_else(|| {
+                invalid(
+                    RegistryOperation::PrepareSubscription,
+                    "service has no Kubernetes target",
+                )
+            })?;
+        let selector = Arc::new(request.into_selector());
+        let (publisher, directory) = directory();
+        let watch = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+        let activate_api = self.api.clone();
+        let activate_watch = watch.clone();
+
+        Ok(provider::subscription(
+            directory,
+            async move {
+                let mut watcher = Watcher {
+                    api: activate_api,
+                    target,
+                    selector,
+                    publisher,
+                    slices: BTreeMap::new(),
+                    profile: None,
+                    resource_version: String::new(),
+                    stale: false,
+                };
+                watcher.load().await.map_err(|error| {
+                    registry_error(RegistryOperation::ActivateSubscription, error)
+                })?;
+                watcher.publisher.publish_ready(watcher.instances())?;
+                let task = tokio::spawn(watch_instances(watcher));
+                *lock(&activate_watch) = Some(task);
+                Ok(())
+            },
+            move || async move {
+                let task = lock(&watch).take();
+                if let Some(task) = task {
+                    task.abort();
+                    let _ = task.await;
+                }
+                Ok(())
+            },
+        ))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Capabilities derived from the target and the Service annotations.
+#[derive(Clone, PartialEq)]
+struct Profile {
+    capabilities: EndpointCapabilities,
+    /// Whether the HTTP versions were annotated and therefore win over port hints.
+    versions_annotated: bool,
+}
+
+impl Profile {
+    fn new(
+        target: &KubernetesTarget,
+        annotations: &BTreeMap<String, String>,
+    ) -> Result<Self, ApiError> {
+        let defaults = &target.capabilities;
+        let versions = match annotations.get(ANNOTATION_HTTP_VERSIONS) {
+            Some(value) => decode_http_versions(value)
+                .ok_or(ApiError::InvalidAnnotation(ANNOTATION_HTTP_VERSIONS))?,
+            None => defaults.http_versions(),
+        };
+        let bindings = match annotations.get(ANNOTATION_HTTP_BINDINGS) {
+            Some(value) => decode_bindings(value)
+                .ok_or(ApiError::InvalidAnnotation(ANNOTATION_HTTP_BINDINGS))?,
+            None => defaults.bindings().to_vec(),
+        };
+        let invocation_controls = match annotations
+            .get(ANNOTATION_INVOCATION_CONTROLS)
+            .map(|value| value.trim())
+        {
+            None => defaults.invocation_controls(),
+            Some(INVOCATION_CONTROLS_V1) => true,
+            Some(_) => {
+                return Err(ApiError::InvalidAnnotation(ANNOTATION_INVOCATION_CONTROLS));
+            }
+        };
+        Ok(Self {
+            capabilities: EndpointCapabilities::new(versions, bindings, invocation_controls)
+                .map_err(|_| ApiError::InvalidAnnotation(ANNOTATION_HTTP_BINDINGS))?,
+            versions_annotated: annotations.contains_key(ANNOTATION_HTTP_VERSIONS),
+        })
+    }
+
+    /// Applies the scheme and HTTP/2 hints of one Service port.
+    fn for_port(&self, port: &EndpointPort) -> (&'static str, EndpointCapabilities) {
+        let name = port.name.as_deref().unwrap_or_default();
+        let app_protocol = port.app_protocol.as_deref().unwrap_or_default();
+        let scheme = if app_protocol == "https" || has_prefix(name, "https") {
+            "https"
+        } else {
+            "http"
+        };
+        let h2c = app_protocol == H2C_APP_PROTOCOL
+            || has_prefix(name, "h2c")
+            || has_prefix(name, "http2");
+        let capabilities = if h2c && !self.versions_annotated {
+            EndpointCapabilities::new(
+                HttpVersionSet::HTTP_2,
+                self.capabilities.bindings().to_vec(),
+                self.capabilities.invocation_controls(),
+            )
+            .unwrap_or_else(|_| self.capabilities.clone())
+        } else {
+            self.capabilities.clone()
+        };
+        (scheme, capabilities)
+    }
+}
+
+/// Reports whether a port name is `prefix` or starts with `prefix-`, the Istio naming convention.
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+}
+
+fn decode_bindings(value: &str) -> Option<Vec<HttpBindingId>> {
+    let mut seen = BTreeSet::new();
+    let mut bindings = Vec::new();
+    for value in value.split(',').map(str::trim) {
+        let binding = HttpBindingId::new(value).ok()?;
+        if !seen.insert(binding.clone()) {
+            return None;
+        }
+        bindings.push(binding);
+    }
+    (!bindings.is_empty()).then_some(bindings)
+}
+
+fn decode_http_versions(value: &str) -> Option<HttpVersionSet> {
+    HttpVersionSet::from_labels(value.split(',').map(str::trim)).ok()
+}
+
+struct Watcher {
+    api: Arc<KubernetesApi>,
+    target: Arc<ResolvedTarget>,
+    selector: Arc<ServiceSelector>,
+    publisher: DirectoryPublisher,
+    slices: BTreeMap<String, EndpointSlice>,
+    profile: Option<Profile>,
+    resource_version: String,
+    stale: bool,
+}
+
+enum WatchEnd {
+    Expired,
+    Ended,
+    Failed(ApiError),
+    Closed,
+}
+
+/// Follows the EndpointSlice watch, listing again after the resource version expired and marking
+/// the directory stale while the API server cannot be watched.
+async fn watch_instances(mut watcher: Watcher) {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        if watcher.publisher.is_closed() {
+            return;
+        }
+        let error = match watcher.follow(&mut delay).await {
+            WatchEnd::Closed => return,
+            WatchEnd::Ended => {
+                // Every watch ends after its server-side timeout; resume from the last version.
+                tokio::time::sleep(MIN_RETRY_DELAY).await;
+                continue;
+            }
+            WatchEnd::Expired => match watcher.relist().await {
+                Ok(true) => {
+                    delay = MIN_RETRY_DELAY;
+                    continue;
+                }
+                Ok(false) => return,
+                Err(error) => error,
+            },
+            WatchEnd::Failed(error) => error,
+        };
+        if !watcher.stale {
+            tracing::warn!(
+                error = %error,
+                service = %watcher.selector.identity(),
+                "Kubernetes EndpointSlice watch failed; keeping the previous instances"
+            );
+            if watcher
+                .publisher
+                .publish_state(DirectoryState::Stale)
+                .is_err()
+            {
+                return;
+            }
+            watcher.stale = true;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+impl Watcher {
+    /// Reads the Service annotations and every EndpointSlice of the Service.
+    async fn load(&mut self) -> Result<(), ApiError> {
+        self.refresh_profile().await?;
+        let list = self
+            .api
+            .endpoint_slices(&self.target.namespace, &self.target.target.service)
+            .await?;
+        self.slices = list
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .map(|slice| (slice.metadata.name.clone(), slice))
+            .collect();
+        self.resource_version = list.metadata.resource_version;
+        Ok(())
+    }
+
+    /// Replaces every slice from a fresh listing; the events since `resource_version` are gone.
+    async fn relist(&mut self) -> Result<bool, ApiError> {
+        self.load().await?;
+        self.stale = false;
+        Ok(self
+            .publisher
+            .publish_snapshot(DirectoryState::Ready, self.instances())
+            .is_ok())
+    }
+
+    /// Reads the Service annotations and reports whether the derived capabilities changed.
+    async fn refresh_profile(&mut self) -> Result<bool, ApiError> {
+        let annotations = self
+            .api
+            .service(&self.target.namespace, &self.target.target.service)
+            .await?
+            .map(|service| service.metadata.annotations)
+            .unwrap_or_default();
+        let profile = Profile::new(&self.target.target, &annotations)?;
+        let changed = self.profile.as_ref() != Some(&profile);
+        self.profile = Some(profile);
+        Ok(changed)
+    }
+
+    async fn follow(&mut self, delay: &mut Duration) -> WatchEnd {
+        // Annotations are not part of the slices; re-reading them here bounds their staleness
+        // by one watch timeout.
+        let mut changed = match self.refresh_profile().await {
+            Ok(changed) => changed || self.stale,
+            Err(error) => return WatchEnd::Failed(error),
+        };
+        let mut stream = match self
+            .api
+            .watch_endpoint_slices(
+                &self.target.namespace,
+                &self.target.target.service,
+                &self.resource_version,
+            )
+            .await
+        {
+            Ok(stream) => stream,
+            Err(ApiError::Expired) => return WatchEnd::Expired,
+            Err(error) => return WatchEnd::Failed(error),
+        };
+        loop {
+            if changed {
+                if self.publisher.publish_ready(self.instances()).is_err() {
+                    return WatchEnd::Closed;
+                }
+                self.stale = false;
+                changed = false;
+            }
+            let event = match stream.next().await {
+                Ok(Some(event)) => event,
+                Ok(None) => return WatchEnd::Ended,
+                Err(ApiError::Expired) => return WatchEnd::Expired,
+                Err(error) => return WatchEnd::Failed(error),
+            };
+            *delay = MIN_RETRY_DELAY;
+            match event {
+                WatchEvent::Applied(slice) => {
+                    self.resource_version = slice.metadata.resource_version.clone();
+                    self.slices.insert(slice.metadata.name.clone(), *slice);
+                    changed = true;
+                }
+                WatchEvent::Deleted(slice) => {
+                    self.resource_version = slice.metadata.resource_version.clone();
+                    changed |= self.slices.remove(&slice.metadata.name).is_some();
+                }
+                WatchEvent::Bookmark(resource_version) => {
+                    self.resource_version = resource_version;
+                }
+            }
+        }
+    }
+
+    /// Maps the ready endpoints of every slice, keeping the first entry for a repeated pod.
+    fn instances(&self) -> Vec<ServiceInstance> {
+        let Some(profile) = &self.profile else {
+            return Vec::new();
+        };
+        let mut instances = BTreeMap::new();
+        for slice in self.slices.values() {
+            if !matches!(slice.address_type.as_str(), "IPv4" | "IPv6") {
+                continue;
+            }
+            let Some(port) = select_port(
+                slice.ports.as_deref().unwrap_or_default(),
+                self.target.target.port.as_deref(),
+            ) else {
+                continue;
+            };
+            let (scheme, capabilities) = profile.for_port(port);
+            for endpoint in slice.endpoints.as_deref().unwrap_or_default() {
+                if endpoint.conditions.ready == Some(false) {
+                    continue;
+                }
+                let Some(ip) = endpoint
+                    .addresses
+                    .first()
+                    .and_then(|address| address.parse::<IpAddr>().ok())
+                else {
+                    continue;
+                };
+                let port = port.port.unwrap_or_default();
+                let instance_id = match &endpoint.target_ref {
+                    Some(target) if target.kind == "Pod" && !target.name.is_empty() => {
+                        format!("k8s:{}:{}", self.target.namespace, target.name)
+                    }
+                    _ => format!("k8s:{ip}:{port}"),
+                };
+                let mut metadata = self.target.target.metadata.clone();
+                if let Some(zone) = &endpoint.zone {
+                    metadata.insert(ZONE_METADATA_KEY.to_owned(), zone.clone());
+                }
+                let Some(instance) = instance(
+                    &instance_id,
+                    scheme,
+                    ip,
+                    port,
+                    capabilities.clone(),
+                    metadata,
+                ) else {
+                    continue;
+                };
+                instances
+                    .entry(instance.instance_id().clone())
+                    .or_insert(instance);
+            }
+        }
+        instances.into_values().collect()
+    }
+}
+
+/// Picks the named TCP port, or the only TCP port when no name is configured.
+fn select_port<'a>(ports: &'a [EndpointPort], name: Option<&str>) -> Option<&'a EndpointPort> {
+    let mut tcp = ports.iter().filter(|port| {
+        port.protocol.as_deref().unwrap_or("TCP") == "TCP"
+            && port.port.is_some_and(|port| port != 0)
+    });
+    match name {
+        Some(name) => tcp.find(|port| port.name.as_deref() == Some(name)),
+        None => {
+            let first = tcp.next()?;
+            tcp.next().is_none().then_some(first)
+        }
+    }
+}
+
+fn instance(
+    instance_id: &str,
+    scheme: &str,
+    ip: IpAddr,
+    port: u16,
+    capabilities: EndpointCapabilities,
+    metadata: Metadata,
+) -> Option<ServiceInstance> {
+    let host = match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{ip}]"),
+    };
+    let endpoint = format!("{scheme}://{host}:{port}")
+        .parse::<ServiceEndpoint>()
+        .ok()?;
+    ServiceInstance::new(
+        InstanceId::new(instance_id).ok()?,
+        endpoint,
+        capabilities,
+        ServiceWeight::new(1.0).ok()?,
+    )
+    .with_metadata(metadata)
+    .ok()
+}
+
+fn registry_error(operation: RegistryOperation, error: ApiError) -> RegistryError {
+    let kind = match &error {
+        ApiError::Status(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) | ApiError::Token(_) => {
+            RegistryErrorKind::Unauthorized
+        }
+        ApiError::InvalidAnnotation(_) | ApiError::Certificate(..) => {
+            RegistryErrorKind::InvalidResource
+        }
+        ApiError::Timeout => RegistryErrorKind::Timeout,
+        ApiError::Decode(_) => RegistryErrorKind::Internal,
+        _ => RegistryErrorKind::Unavailable,
+    };
+    RegistryError::new(operation, kind, error)
+}
+
+fn invalid(operation: RegistryOperation, message: &'static str) -> RegistryError {
+    RegistryError::message(operation, RegistryErrorKind::InvalidResource, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(name: &str, app_protocol: Option<&str>) -> EndpointPort {
+        EndpointPort {
+            name: Some(name.into()),
+            port: Some(8080),
+            protocol: Some("TCP".into()),
+            app_protocol: app_protocol.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn annotations_override_target_capabilities() {
+        let target = KubernetesTarget::new("greeter");
+        let annotations = BTreeMap::from([
+            (ANNOTATION_HTTP_VERSIONS.to_owned(), "2".to_owned()),
+            (
+                ANNOTATION_HTTP_BINDINGS.to_owned(),
+                "vendor-v1, http-json-v1".to_owned(),
+            ),
+            (ANNOTATION_INVOCATION_CONTROLS.to_owned(), "v1".to_owned()),
+        ]);
+        let profile = Profile::new(&target, &annotations).unwrap();
+        assert_eq!(profile.capabilities.http_versions(), HttpVersionSet::HTTP_2);
+        assert_eq!(profile.capabilities.bindings().len(), 2);
+        assert!(profile.capabilities.invocation_controls());
+
+        let defaults = Profile::new(&target, &BTreeMap::new()).unwrap();
+        assert_eq!(defaults.capabilities, EndpointCapabilities::default());
+    }
+
+    #[test]
+    fn invalid_annotations_name_the_annotation() {
+        let target = KubernetesTarget::new("greeter");
+        for (annotation, value) in [
+            (ANNOTATION_HTTP_VERSIONS, "1.0"),
+            (ANNOTATION_HTTP_BINDINGS, ""),
+            (ANNOTATION_INVOCATION_CONTROLS, "yes"),
+        ] {
+            let annotations = BTreeMap::from([(annotation.to_owned(), value.to_owned())]);
+            assert!(matches!(
+                Profile::new(&target, &annotations),
+                Err(ApiError::InvalidAnnotation(name)) if name == annotation
+            ));
+        }
+    }
+
+    #[test]
+    fn port_names_and_app_protocols_select_scheme_and_http2() {
+        let profile = Profile::new(&KubernetesTarget::new("greeter"), &BTreeMap::new()).unwrap();
+        assert_eq!(profile.for_port(&port("https-api", None)).0, "https");
+        assert_eq!(profile.for_port(&port("web", Some("https"))).0, "https");
+        assert_eq!(profile.for_port(&port("httpsish", None)).0, "http");
+        let (scheme, capabilities) = profile.for_port(&port("web", Some(H2C_APP_PROTOCOL)));
+        assert_eq!(scheme, "http");
+        assert_eq!(capabilities.http_versions(), HttpVersionSet::HTTP_2);
+        assert_eq!(
+            profile.for_port(&port("http2", None)).1.http_versions(),
+            HttpVersionSet::HTTP_2
+        );
+
+        let annotated = Profile::new(
+            &KubernetesTarget::new("greeter"),
+            &BTreeMap::from([(ANNOTATION_HTTP_VERSIONS.to_owned(), "1.1".to_owned())]),
+        )
+        .unwrap();
+        assert_eq!(
+            annotated.for_port(&port("h2c", None)).1.http_versions(),
+            HttpVersionSet::HTTP_1_1
+        );
+    }
+
+    #[test]
+    fn unnamed_targets_require_a_single_tcp_port() {
+        let mut udp = port("dns", None);
+        udp.protocol = Some("UDP".into());
+        let ports = [port("http", None), udp];
+        assert_eq!(
+            select_port(&ports, None).and_then(|port| port.name.as_deref()),
+            Some("http")
+        );
+        let ports = [port("http", None), port("admin", None)];
+        assert!(select_port(&ports, None).is_none());
+        assert_eq!(
+            select_port(&ports, Some("admin")).and_then(|port| port.name.as_deref()),
+            Some("admin")
+        );
+    }
+}
@@ -0,0 +1,587 @@
+//! EndpointSlice discovery against an in-memory Kubernetes API server stand-in that lists and
+//! watches slices of one Service.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use fusen_contract::{HttpVersionSet, ServiceSelector};
+use fusen_kubernetes::{KubernetesConfig, KubernetesRegistry, KubernetesTarget};
+use fusen_register::{
+    Registry, SubscriptionRequest,
+    directory::{DirectorySnapshot, DirectoryState},
+    error::{RegistryErrorKind, RegistryOperation},
+    testing::{ids, wait_for},
+};
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full, StreamBody, combinators::BoxBody};
+use hyper::{
+    body::{Frame, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use rustls::{ServerConfig as TlsServerConfig, pki_types::PrivatePkcs8KeyDer};
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_rustls::TlsAcceptor;
+
+type Body = BoxBody<Bytes, Infallible>;
+
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+const SERVICE_PATH: &str = "/api/v1/namespaces/default/services/greeter";
+const SLICES_PATH: &str = "/apis/discovery.k8s.io/v1/namespaces/default/endpointslices";
+
+#[derive(Default)]
+struct Cluster {
+    resource_version: u64,
+    compacted: u64,
+    annotations: BTreeMap<String, String>,
+    slices: BTreeMap<String, Value>,
+    history: Vec<(u64, Bytes)>,
+    watchers: Vec<mpsc::UnboundedSender<Bytes>>,
+    lists: usize,
+    authorizations: Vec<Option<String>>,
+    failure: Option<StatusCode>,
+}
+
+impl Cluster {
+    /// Creates or replaces one slice of the `greeter` Service.
+    fn apply(&mut self, name: &str, endpoints: Value) {
+        self.resource_version += 1;
+        let kind = if self.slices.contains_key(name) {
+            "MODIFIED"
+        } else {
+            "ADDED"
+        };
+        let slice = json!({
+            "kind": "EndpointSlice",
+            "metadata": {
+                "name": name,
+                "namespace": "default",
+                "resourceVersion": self.resource_version.to_string(),
+                "labels": { "kubernetes.io/service-name": "greeter" },
+            },
+            "addressType": "IPv4",
+            "endpoints": endpoints,
+            "ports": [
+                { "name": "admin", "port": 9090, "protocol": "TCP" },
+                { "name": "http", "port": 8080, "protocol": "TCP" },
+            ],
+        });
+        self.slices.insert(name.to_owned(), slice.clone());
+        self.record(kind, slice);
+    }
+
+    fn delete(&mut self, name: &str) {
+        if let Some(mut slice) = self.slices.remove(name) {
+            self.resource_version += 1;
+            slice["metadata"]["resourceVersion"] = json!(self.resource_version.to_string());
+            self.record("DELETED", slice);
+        }
+    }
+
+    fn record(&mut self, kind: &str, object: Value) {
+        let event = line(&json!({ "type": kind, "object": object }));
+        self.watchers
+            .retain(|watcher| watcher.send(event.clone()).is_ok());
+        self.history.push((self.resource_version, event));
+    }
+
+    /// Forgets the event history, so watches from an older resource version are rejected.
+    fn compact(&mut self) {
+        self.compacted = self.resource_version;
+        self.history.clear();
+    }
+}
+
+fn endpoint(pod: &str, ip: &str, ready: Option<bool>, zone: &str) -> Value {
+    let mut endpoint = json!({
+        "addresses": [ip],
+        "conditions": {},
+        "targetRef": { "kind": "Pod", "namespace": "default", "name": pod },
+        "zone": zone,
+    });
+    if let Some(ready) = ready {
+        endpoint["conditions"]["ready"] = json!(ready);
+    }
+    endpoint
+}
+
+fn line(value: &Value) -> Bytes {
+    Bytes::from(format!("{value}\n"))
+}
+
+fn status(code: StatusCode) -> Value {
+    json!({ "kind": "Status", "status": "Failure", "code": code.as_u16(), "message": "injected" })
+}
+
+fn respond(code: StatusCode, value: &Value) -> Response<Body> {
+    Response::builder()
+        .status(code)
+        .body(Full::new(line(value)).boxed())
+        .unwrap()
+}
+
+#[derive(Clone)]
+struct FakeApiServer {
+    api_server: String,
+    cluster: Arc<Mutex<Cluster>>,
+}
+
+impl FakeApiServer {
+    async fn start() -> Self {
+        Self::start_with(None).await
+    }
+
+    /// Serves HTTPS with `identity` when given, as an in-cluster API server does.
+    async fn start_with(identity: Option<&CertifiedKey>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let acceptor = identity.map(|identity| {
+            let config = TlsServerConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![identity.cert.der().clone()],
+                PrivatePkcs8KeyDer::from(identity.key_pair.serialize_der()).into(),
+            )
+            .unwrap();
+            TlsAcceptor::from(Arc::new(config))
+        });
+        let fake = Self {
+            api_server: match acceptor {
+                Some(_) => format!("https://{address}"),
+                None => format!("http://{address}"),
+            },
+            cluster: Arc::new(Mutex::new(Cluster::default())),
+        };
+        let server = fake.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let fake = server.clone();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        let fake = fake.clone();
+                        async move { Ok::<_, Infallible>(fake.handle(request)) }
+                    });
+                    let connection = http1::Builder::new();
+                    let _ = match acceptor {
+                        Some(acceptor) => {
+                            let Ok(stream) = acceptor.accept(stream).await else {
+                                return;
+                            };
+                            connection
+                                .serve_connection(TokioIo::new(stream), service)
+                                .await
+                        }
+                        None => {
+                            connection
+                                .serve_connection(TokioIo::new(stream), service)
+                                .await
+                        }
+                    };
+                });
+            }
+        });
+        fake
+    }
+
+    fn config(&self) -> KubernetesConfig {
+        KubernetesConfig::builder()
+            .api_server(&self.api_server)
+            .build()
+            .unwrap()
+    }
+
+    fn registry(&self, config: KubernetesConfig) -> KubernetesRegistry {
+        KubernetesRegistry::builder(config)
+            .target(
+                selector(),
+                KubernetesTarget::new("greeter")
+                    .port("http")
+                    .metadata(BTreeMap::from([("team".into(), "payments".into())])),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn with<T>(&self, change: impl FnOnce(&mut Cluster) -> T) -> T {
+        change(&mut self.cluster.lock().unwrap())
+    }
+
+    /// Ends every open watch, as the API server does once a watch's timeout elapses.
+    fn end_watches(&self) {
+        self.with(|cluster| cluster.watchers.clear());
+    }
+
+    fn handle(&self, request: Request<Incoming>) -> Response<Body> {
+        let mut cluster = self.cluster.lock().unwrap();
+        cluster.authorizations.push(
+            request
+                .headers()
+                .get(http::header::AUTHORIZATION)
+                .map(|value| value.to_str().unwrap().to_owned()),
+        );
+        if let Some(code) = cluster.failure {
+            return respond(code, &status(code));
+        }
+        let query =
+            url::form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes())
+                .into_owned()
+                .collect::<BTreeMap<_, _>>();
+        match request.uri().path() {
+            SERVICE_PATH => respond(
+                StatusCode::OK,
+                &json!({
+                    "kind": "Service",
+                    "metadata": { "name": "greeter", "annotations": cluster.annotations },
+                }),
+            ),
+            SLICES_PATH => {
+                assert_eq!(query["labelSelector"], "kubernetes.io/service-name=greeter");
+                if query.get("watch").map(String::as_str) != Some("true") {
+                    cluster.lists += 1;
+                    return respond(
+                        StatusCode::OK,
+                        &json!({
+                            "kind": "EndpointSliceList",
+                            "metadata": { "resourceVersion": cluster.resource_version.to_string() },
+                            "items": cluster.slices.values().collect::<Vec<_>>(),
+                        }),
+                    );
+                }
+                let start: u64 = query["resourceVersion"].parse().unwrap();
+                let (sender, receiver) = mpsc::unbounded_channel();
+                if start < cluster.compacted {
+                    let _ = sender.send(line(
+                        &json!({ "type": "ERROR", "object": status(StatusCode::GONE) }),
+                    ));
+                } else {
+                    for (_, event) in cluster
+                        .history
+                        .iter()
+                        .filter(|(version, _)| *version > start)
+                    {
+                        let _ = sender.send(event.clone());
+                    }
+                    cluster.watchers.push(sender);
+                }
+                let frames = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                    let line = receiver.recv().await?;
+                    Some((Ok::<_, Infallible>(Frame::data(line)), receiver))
+                });
+                Response::new(StreamBody::new(frames).boxed())
+            }
+            _ => respond(StatusCode::NOT_FOUND, &status(StatusCode::NOT_FOUND)),
+        }
+    }
+}
+
+fn selector() -> ServiceSelector {
+    ServiceSelector::new("greeter", None, Some("1.0".into())).unwrap()
+}
+
+/// Encodes the fake API server's certificate as the PEM CA bundle the config expects.
+fn pem(der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----\n");
+    pem
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fusen-k8s-{}-{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn ready_endpoints_of_the_named_port_become_instances() {
+    let fake = FakeApiServer::start().await;
+    fake.with(|cluster| {
+        cluster
+            .annotations
+            .insert("fusen.rs/http-versions".into(), "2".into());
+        cluster.apply(
+            "greeter-abc",
+            json!([
+                endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a"),
+                endpoint("greeter-2", "10.0.0.2", Some(false), "zone-a"),
+                endpoint("greeter-3", "10.0.0.3", None, "zone-b"),
+            ]),
+        );
+    });
+    let registry = fake.registry(fake.config());
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let directory = handle.activate().await.unwrap();
+
+    let snapshot = directory.snapshot();
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(
+        ids(&snapshot),
+        ["k8s:default:greeter-1", "k8s:default:greeter-3"]
+    );
+    let instance = snapshot.iter().next().unwrap();
+    assert_eq!(instance.endpoint().as_str(), "http://10.0.0.1:8080/");
+    assert_eq!(
+        instance.capabilities().http_versions(),
+        HttpVersionSet::HTTP_2
+    );
+    assert_eq!(instance.metadata()["zone"], "zone-a");
+    assert_eq!(instance.metadata()["team"], "payments");
+    assert!(fake.with(|cluster| cluster.authorizations.iter().all(Option::is_none)));
+
+    handle.close().await.unwrap();
+    assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+}
+
+#[tokio::test]
+async fn watch_events_add_update_and_remove_instances() {
+    let fake = FakeApiServer::start().await;
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a")]),
+        );
+    });
+    let handle = fake
+        .registry(fake.config())
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    assert_eq!(ids(&directory.snapshot()), ["k8s:default:greeter-1"]);
+
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-def",
+            json!([endpoint("greeter-2", "10.0.0.2", Some(true), "zone-b")]),
+        )
+    });
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 2
+    })
+    .await;
+    assert_eq!(
+        ids(&snapshot),
+        ["k8s:default:greeter-1", "k8s:default:greeter-2"]
+    );
+
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(false), "zone-a")]),
+        )
+    });
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 1
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["k8s:default:greeter-2"]);
+
+    fake.with(|cluster| cluster.delete("greeter-def"));
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.is_empty()
+    })
+    .await;
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn expired_resource_versions_relist_the_slices() {
+    let fake = FakeApiServer::start().await;
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a")]),
+        )
+    });
+    let handle = fake
+        .registry(fake.config())
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+
+    fake.with(|cluster| {
+        cluster.watchers.clear();
+        cluster.delete("greeter-abc");
+        cluster.apply(
+            "greeter-def",
+            json!([endpoint("greeter-2", "10.0.0.2", Some(true), "zone-b")]),
+        );
+        cluster.compact();
+    });
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        ids(snapshot) == ["k8s:default:greeter-2"]
+    })
+    .await;
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(fake.with(|cluster| cluster.lists), 2);
+
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn unreachable_api_servers_mark_the_directory_stale_until_the_watch_resumes() {
+    let fake = FakeApiServer::start().await;
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a")]),
+        )
+    });
+    let handle = fake
+        .registry(fake.config())
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+
+    fake.with(|cluster| cluster.failure = Some(StatusCode::SERVICE_UNAVAILABLE));
+    fake.end_watches();
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Stale
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["k8s:default:greeter-1"]);
+
+    fake.with(|cluster| cluster.failure = None);
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Ready
+    })
+    .await;
+    assert_eq!(ids(&snapshot), ["k8s:default:greeter-1"]);
+
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn service_annotations_are_read_again_when_a_watch_restarts() {
+    let fake = FakeApiServer::start().await;
+    fake.with(|cluster| {
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a")]),
+        )
+    });
+    let handle = fake
+        .registry(fake.config())
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    let versions = |snapshot: &DirectorySnapshot| {
+        snapshot
+            .iter()
+            .next()
+            .map(|instance| instance.capabilities().http_versions())
+    };
+    assert_eq!(
+        versions(&directory.snapshot()),
+        Some(HttpVersionSet::default())
+    );
+
+    fake.with(|cluster| {
+        cluster
+            .annotations
+            .insert("fusen.rs/http-versions".into(), "2".into())
+    });
+    fake.end_watches();
+    wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        versions(snapshot) == Some(HttpVersionSet::HTTP_2)
+    })
+    .await;
+
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn failed_requests_fail_activation_and_unmapped_services_are_rejected() {
+    let fake = FakeApiServer::start().await;
+    let error = KubernetesRegistry::builder(fake.config())
+        .target(selector(), KubernetesTarget::new("Greeter"))
+        .build()
+        .unwrap_err();
+    assert_eq!(error.operation(), RegistryOperation::Build);
+    assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+
+    let registry = fake.registry(fake.config());
+    let unmapped = ServiceSelector::new("billing", None, None).unwrap();
+    let error = registry
+        .prepare_subscription(SubscriptionRequest::new(unmapped))
+        .unwrap_err();
+    assert_eq!(error.operation(), RegistryOperation::PrepareSubscription);
+    assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+
+    fake.with(|cluster| cluster.failure = Some(StatusCode::FORBIDDEN));
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let error = handle.activate().await.unwrap_err();
+    assert_eq!(error.kind(), RegistryErrorKind::Unauthorized);
+}
+
+#[tokio::test]
+async fn rotated_bearer_tokens_are_sent_to_a_trusted_https_api_server() {
+    let identity = generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+    let fake = FakeApiServer::start_with(Some(&identity)).await;
+    let ca = temp_file("ca.crt", &pem(identity.cert.der()));
+    let token = temp_file("token", "first-token\n");
+    let config = KubernetesConfig::builder()
+        .api_server(&fake.api_server)
+        .token_path(&token)
+        .ca_certificate_path(&ca)
+        .build()
+        .unwrap();
+    let handle = fake
+        .registry(config)
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    assert!(fake.with(|cluster| {
+        cluster
+            .authorizations
+            .iter()
+            .all(|value| value.as_deref() == Some("Bearer first-token"))
+    }));
+
+    std::fs::write(&token, "second-token").unwrap();
+    fake.with(|cluster| {
+        cluster.watchers.clear();
+        cluster.apply(
+            "greeter-abc",
+            json!([endpoint("greeter-1", "10.0.0.1", Some(true), "zone-a")]),
+        );
+    });
+    wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 1
+    })
+    .await;
+    assert_eq!(
+        fake.with(|cluster| cluster.authorizations.last().cloned().flatten()),
+        Some("Bearer second-token".to_owned())
+    );
+
+    handle.close().await.unwrap();
+    std::fs::remove_file(ca).unwrap();
+    std::fs::remove_file(token).unwrap();
+}
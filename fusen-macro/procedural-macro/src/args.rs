@@ -2,7 +2,7 @@
 
 use syn::parse::{Parse, ParseStream, Parser};
 use syn::punctuated::Punctuated;
-use syn::{Expr, ExprLit, Lit, LitStr, Meta, Token};
+use syn::{Expr, ExprLit, Lit, LitInt, LitStr, Meta, Token};
 
 #[derive(Default)]
 pub(crate) struct ServiceArgs {
@@ -67,6 +67,9 @@ pub(crate) struct MethodArgs {
     pub(crate) path: Option<LitStr>,
     pub(crate) consumes: Option<LitStr>,
     pub(crate) produces: Option<LitStr>,
+    pub(crate) timeout_ms: Option<LitInt>,
+    pub(crate) retries: Option<LitInt>,
+    pub(crate) priority: Option<LitStr>,
 }
 
 impl MethodArgs {
@@ -116,11 +119,29 @@ impl MethodArgs {
                     &field,
                     "produces",
                 )?,
+                "timeout_ms" => set_once(
+                    &mut args.timeout_ms,
+                    parse_int(field.value.clone(), "timeout_ms", 1..=u64::MAX)?,
+                    &field,
+                    "timeout_ms",
+                )?,
+                "retries" => set_once(
+                    &mut args.retries,
+                    parse_int(field.value.clone(), "retries", 0..=2)?,
+                    &field,
+                    "retries",
+                )?,
+                "priority" => set_once(
+                    &mut args.priority,
+                    parse_priority(field.value.clone())?,
+                    &field,
+                    "priority",
+                )?,
                 unknown => {
                     return Err(syn::Error::new_spanned(
                         field,
                         format!(
-                            "unknown method field `{unknown}`; expected `method`, `path`, `consumes`, or `produces`"
+                            "unknown method field `{unknown}`; expected `method`, `path`, `consumes`, `produces`, `timeout_ms`, `retries`, or `priority`"
                         ),
                     ));
                 }
@@ -150,6 +171,51 @@ fn parse_string(value: Expr, field: &str) -> syn::Result<LitStr> {
     Ok(value)
 }
 
+fn parse_priority(value: Expr) -> syn::Result<LitStr> {
+    let value = parse_string(value, "priority")?;
+    if !matches!(
+        value.value().as_str(),
+        "low" | "normal" | "high" | "critical"
+    ) {
+        return Err(syn::Error::new_spanned(
+            value,
+            "`priority` must be \"low\", \"normal\", \"high\", or \"critical\"",
+        ));
+    }
+    Ok(value)
+}
+
+fn parse_int(
+    value: Expr,
+    field: &str,
+    range: std::ops::RangeInclusive<u64>,
+) -> syn::Result<LitInt> {
+    let Expr::Lit(ExprLit {
+        lit: Lit::Int(value),
+        ..
+    }) = value
+    else {
+        return Err(syn::Error::new_spanned(
+            value,
+            format!("`{field}` must be an integer literal"),
+        ));
+    };
+    if !value
+        .base10_parse::<u64>()
+        .is_ok_and(|parsed| range.contains(&parsed))
+    {
+        return Err(syn::Error::new_spanned(
+            value,
+            format!(
+                "`{field}` must be between {} and {} inclusive",
+                range.start(),
+                range.end()
+            ),
+        ));
+    }
+    Ok(value)
+}
+
 fn set_once<T>(
     slot: &mut Option<T>,
     value: T,
@@ -193,6 +259,38 @@ mod tests {
         assert_eq!(args.path.unwrap().value(), "/users/{id}");
         assert_eq!(args.consumes.unwrap().value(), "application/json");
         assert_eq!(args.produces.unwrap().value(), "application/problem+json");
+        assert!(args.timeout_ms.is_none() && args.retries.is_none() && args.priority.is_none());
+    }
+
+    #[test]
+    fn parses_bounded_client_defaults() {
+        let args = MethodArgs::parse_tokens(quote!(timeout_ms = 2_500, retries = 0)).unwrap();
+        assert_eq!(
+            args.timeout_ms.unwrap().base10_parse::<u64>().unwrap(),
+            2_500
+        );
+        assert_eq!(args.retries.unwrap().base10_parse::<u8>().unwrap(), 0);
+        let args = MethodArgs::parse_tokens(quote!(priority = "low")).unwrap();
+        assert_eq!(args.priority.unwrap().value(), "low");
+
+        for (tokens, message) in [
+            (quote!(timeout_ms = 0), "`timeout_ms` must be between 1 and"),
+            (
+                quote!(retries = 3),
+                "`retries` must be between 0 and 2 inclusive",
+            ),
+            (
+                quote!(retries = "1"),
+                "`retries` must be an integer literal",
+            ),
+            (
+                quote!(priority = "urgent"),
+                "`priority` must be \"low\", \"normal\", \"high\", or \"critical\"",
+            ),
+        ] {
+            let error = MethodArgs::parse_tokens(tokens).err().unwrap().to_string();
+            assert!(error.starts_with(message), "{error}");
+        }
     }
 
     #[test]
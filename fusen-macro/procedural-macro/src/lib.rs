@@ -18,15 +18,18 @@ use args::{MethodArgs, ServiceArgs};
 ///
 /// The annotated trait must be non-generic and contain only ordinary `async` methods with an
 /// immutable `&self` receiver. Methods take owned, named parameters and return exactly
-/// `Result<Response<T>, Error>`. Wire parameters and successful response values must implement
-/// the Serde and `SensitiveFields` contracts required in both client and server directions; values
-/// captured by generated futures must also be `Send`.
+/// `Result<Response<T>, Error>`, or `Result<Response<ResponseStream<T>>, Error>` for a
+/// server-streaming method whose items are `T`. Wire parameters and successful response values
+/// must implement the Serde and `SensitiveFields` contracts required in both client and server
+/// directions; values captured by generated futures must also be `Send`.
 ///
 /// Every method needs one [`method`] attribute. Parameters may use `#[param(path)]`,
 /// `#[param(query)]`, `#[param(header)]`, `#[param(cookie)]`, `#[param(body_field)]`,
-/// `#[param(body)]`, `#[param(query_map)]`, `#[param(header_map)]`, or `#[param(context)]`.
-/// `#[param(name = "...")]` changes a named wire parameter and
-/// `#[param(query, repeated)]` declares repeated query keys.
+/// `#[param(body)]`, `#[param(query_map)]`, `#[param(header_map)]`, `#[param(body_stream)]`, or
+/// `#[param(context)]`. `#[param(name = "...")]` changes a named wire parameter and
+/// `#[param(query, repeated)]` declares repeated query keys. A POST or PATCH method may declare one
+/// `#[param(body_stream)] BodyStream` parameter to stream the raw request body; it cannot be
+/// combined with JSON body parameters.
 ///
 /// The expansion defines `TraitNameClient` and `TraitNameServer<T>`. Generated code uses only the
 /// versioned runtime macro ABI and supports a renamed `fusen-rs` dependency.
@@ -39,9 +42,22 @@ pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// Declares the HTTP operation required by a method inside [`interface`].
 ///
 /// The required fields are `method = "..."` and `path = "/..."`. Optional `consumes` and
-/// `produces` fields each accept one MIME media type and default to `application/json`. Supported
-/// methods are GET, POST, PUT, PATCH, DELETE, HEAD, and OPTIONS. GET, HEAD, and OPTIONS reject JSON
-/// body and body-field parameters; HEAD additionally requires `Response<()>`.
+/// `produces` fields each accept one MIME media type and default to `application/json`. Methods
+/// with a streamed request body default `consumes` to `application/octet-stream`, and
+/// server-streaming methods default `produces` to `application/x-ndjson` and may only produce it or
+/// `text/event-stream`; other methods must not use either. Supported methods are GET, POST, PUT,
+/// PATCH, DELETE, HEAD, and OPTIONS. GET, HEAD, and OPTIONS reject JSON body and body-field
+/// parameters, only POST and PATCH accept a streamed body, and HEAD additionally requires
+/// `Response<()>`.
+///
+/// Optional `timeout_ms` (at least 1) and `retries` (0 to 2) declare client defaults for the
+/// method's logical deadline and retries after the first attempt. They apply below
+/// `ClientBuilder::method_config` and above runtime settings; `retries` is rejected on POST and
+/// PATCH, which are never retried.
+///
+/// Optional `priority` (`"low"`, `"normal"`, `"high"`, or `"critical"`, default `"normal"`)
+/// declares the class a server with load shedding enabled rejects first; a request's
+/// `x-fusen-priority` header overrides it.
 #[proc_macro_attribute]
 pub fn method(attr: TokenStream, item: TokenStream) -> TokenStream {
     match MethodArgs::parse_tokens(attr.into()) {
@@ -251,6 +251,7 @@ fn descriptor(
                 validate::ParameterSource::Body => quote!(Body),
                 validate::ParameterSource::QueryMap => quote!(QueryMap),
                 validate::ParameterSource::HeaderMap => quote!(HeaderMap),
+                validate::ParameterSource::BodyStream => quote!(BodyStream),
                 validate::ParameterSource::Context => unreachable!(),
             };
             let repeated = parameter.repeated;
@@ -263,7 +264,10 @@ fn descriptor(
             })
         });
         let sensitive_arguments = method.parameters.iter().filter_map(|parameter| {
-            if parameter.source == validate::ParameterSource::Context {
+            if matches!(
+                parameter.source,
+                validate::ParameterSource::Context | validate::ParameterSource::BodyStream
+            ) {
                 return None;
             }
             let name = &parameter.wire_name;
@@ -285,6 +289,20 @@ fn descriptor(
             })
         });
         let response = &method.response;
+        let timeout = match method.client_defaults.0 {
+            Some(millis) => quote!(Some(::std::time::Duration::from_millis(#millis))),
+            None => quote!(None),
+        };
+        let retries = match method.client_defaults.1 {
+            Some(retries) => quote!(Some(#retries)),
+            None => quote!(None),
+        };
+        let priority = match method.priority.as_str() {
+            "low" => quote!(Low),
+            "high" => quote!(High),
+            "critical" => quote!(Critical),
+            _ => quote!(Normal),
+        };
         let consumes = &mapping.consumes;
         let produces = &mapping.produces;
         let http = quote! {
@@ -307,6 +325,8 @@ fn descriptor(
                 ::std::vec![#(#sensitive_arguments),*],
                 Some(<#response as #abi::SensitiveFields>::sensitive_shape),
             ))
+            .with_client_defaults(#abi::MethodClientDefaults::new(#timeout, #retries))
+            .with_priority(#abi::MethodPriority::#priority)
         }
     });
     quote! {{
@@ -356,8 +376,22 @@ fn client_methods(
                         quote!(#ident)
                     },
                 );
+            let call = method
+                .parameters
+                .iter()
+                .find(|parameter| parameter.source == validate::ParameterSource::BodyStream)
+                .map_or_else(
+                    || call.clone(),
+                    |parameter| {
+                        let ident = &parameter.ident;
+                        quote!(#abi::with_body_stream(#call, #ident))
+                    },
+                );
             let arguments = method.parameters.iter().filter_map(|parameter| {
-                if parameter.source == validate::ParameterSource::Context {
+                if matches!(
+                    parameter.source,
+                    validate::ParameterSource::Context | validate::ParameterSource::BodyStream
+                ) {
                     return None;
                 }
                 let ident = &parameter.ident;
@@ -369,14 +403,22 @@ fn client_methods(
                     );
                 })
             });
+            let (output, invoke) = if method.streaming {
+                (
+                    quote!(#abi::Response<#abi::ResponseStream<#response>>),
+                    quote!(invoke_stream),
+                )
+            } else {
+                (quote!(#abi::Response<#response>), quote!(invoke))
+            };
             quote! {
                 #(#attributes)*
                 async fn #ident(
                     &self,
                     #(#parameters),*
-                ) -> ::core::result::Result<#abi::Response<#response>, #abi::Error> {
+                ) -> ::core::result::Result<#output, #abi::Error> {
                     self.#inner
-                        .invoke::<#response, _>(
+                        .#invoke::<#response, _>(
                             #abi::MethodId::new(#index as u16),
                             #call,
                             move || {
@@ -415,6 +457,10 @@ fn dispatch_arms(
                     quote! {
                         let #ident: #kind = #invocation.call();
                     }
+                } else if parameter.source == validate::ParameterSource::BodyStream {
+                    quote! {
+                        let #ident: #kind = #invocation.take_body_stream()?;
+                    }
                 } else {
                     let name = &parameter.wire_name;
                     let text_encoded = parameter.text_encoded;
@@ -427,6 +473,11 @@ fn dispatch_arms(
                 }
             });
             let arguments = method.parameters.iter().map(|parameter| &parameter.ident);
+            let encode = if method.streaming {
+                quote!(encode_stream_response)
+            } else {
+                quote!(encode_response)
+            };
             quote! {
                 #method_id => {
                     #(#declarations)*
@@ -435,7 +486,7 @@ fn dispatch_arms(
                         #handler,
                         #(#arguments),*
                     ).await?;
-                    #invocation.encode_response(#response)
+                    #invocation.#encode(#response)
                 }
             }
         })
@@ -12,6 +12,9 @@ use syn::{
 };
 
 const MAX_IDENTITY_BYTES: usize = 128;
+// The first entry is the default `produces` of a streaming method.
+const STREAM_MEDIA_TYPES: [&str; 2] = ["application/x-ndjson", "text/event-stream"];
+const BODY_STREAM_MEDIA_TYPE: &str = "application/octet-stream";
 
 pub(crate) struct HttpMapping {
     pub(crate) method: String,
@@ -31,6 +34,7 @@ pub(crate) enum ParameterSource {
     Body,
     QueryMap,
     HeaderMap,
+    BodyStream,
 }
 
 #[derive(Clone)]
@@ -47,8 +51,14 @@ pub(crate) struct Parameter {
 pub(crate) struct Method {
     pub(crate) ident: Ident,
     pub(crate) parameters: Vec<Parameter>,
+    /// Success payload type, or the item type of a streaming method.
     pub(crate) response: Type,
+    pub(crate) streaming: bool,
     pub(crate) http: HttpMapping,
+    /// Optional `timeout_ms` and `retries` client defaults.
+    pub(crate) client_defaults: (Option<u64>, Option<u8>),
+    /// Server load-shedding class; `normal` when not declared.
+    pub(crate) priority: String,
 }
 
 pub(crate) struct Service {
@@ -117,17 +127,49 @@ pub(crate) fn validate(args: ServiceArgs, item: &ItemTrait) -> syn::Result<Servi
             method.sig.ident.span(),
         )?;
         let method_args = method_args(&method.attrs, &method.sig.ident)?;
-        let http = validate_http(method_args, &method.sig.ident)?;
+        let explicit_consumes = method_args.consumes.is_some();
+        let retries = method_args.retries.clone();
+        let priority = method_args
+            .priority
+            .as_ref()
+            .map_or_else(|| "normal".to_owned(), syn::LitStr::value);
+        let client_defaults = (
+            method_args
+                .timeout_ms
+                .as_ref()
+                .map(syn::LitInt::base10_parse)
+                .transpose()?,
+            retries
+                .as_ref()
+                .map(syn::LitInt::base10_parse)
+                .transpose()?,
+        );
+        let (response, streaming) = response_type(&method.sig.output)?;
+        let mut http = validate_http(method_args, &method.sig.ident, streaming)?;
         let parameters = parameters(&method.sig, &http)?;
-        let response = response_type(&method.sig.output)?;
+        if !explicit_consumes
+            && parameters
+                .iter()
+                .any(|parameter| parameter.source == ParameterSource::BodyStream)
+        {
+            http.consumes = BODY_STREAM_MEDIA_TYPE.to_owned();
+        }
         if http.method == "HEAD"
-            && !matches!(&response, Type::Tuple(tuple) if tuple.elems.is_empty())
+            && (streaming || !matches!(&response, Type::Tuple(tuple) if tuple.elems.is_empty()))
         {
             return Err(syn::Error::new_spanned(
                 &method.sig.output,
                 "HTTP HEAD mappings must return Result<Response<()>, Error>",
             ));
         }
+        if let Some(retries) = &retries
+            && matches!(http.method.as_str(), "POST" | "PATCH")
+        {
+            return Err(syn::Error::new_spanned(
+                retries,
+                "`retries` requires a replayable HTTP method; POST and PATCH are never retried",
+            ));
+        }
         let key = (http.method.clone(), route_shape(&http.path));
         if let Some(first) = http_routes.insert(key, method.sig.ident.clone()) {
             return Err(syn::Error::new(
@@ -142,7 +184,10 @@ pub(crate) fn validate(args: ServiceArgs, item: &ItemTrait) -> syn::Result<Servi
             ident: method.sig.ident.clone(),
             parameters,
             response,
+            streaming,
             http,
+            client_defaults,
+            priority,
         });
     }
 
@@ -315,6 +360,7 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
     let placeholders = validate_route(&http.path, signature.ident.span())?;
     let body_fields_by_default = matches!(http.method.as_str(), "POST" | "PUT" | "PATCH");
     let mut raw_body = None;
+    let mut body_stream = None;
     let mut first_body_field = None;
     let mut context = None;
     let mut query_map = None;
@@ -364,6 +410,7 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
                     || meta.path.is_ident("body")
                     || meta.path.is_ident("query_map")
                     || meta.path.is_ident("header_map")
+                    || meta.path.is_ident("body_stream")
                 {
                     let source = if meta.path.is_ident("context") {
                         ParameterSource::Context
@@ -381,6 +428,8 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
                         ParameterSource::QueryMap
                     } else if meta.path.is_ident("header_map") {
                         ParameterSource::HeaderMap
+                    } else if meta.path.is_ident("body_stream") {
+                        ParameterSource::BodyStream
                     } else {
                         ParameterSource::Body
                     };
@@ -431,7 +480,7 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
                     Ok(())
                 } else {
                     Err(meta.error(
-                        "unknown parameter field; expected context, path, query, header, cookie, body_field, body, query_map, header_map, name, or repeated",
+                        "unknown parameter field; expected context, path, query, header, cookie, body_field, body, query_map, header_map, body_stream, name, or repeated",
                     ))
                 }
             })?;
@@ -491,6 +540,54 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
             continue;
         }
 
+        if explicit_source.is_some_and(|(source, _)| source == ParameterSource::BodyStream) {
+            if sensitivity.is_some() {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "#[param(body_stream)] parameters cannot declare sensitivity metadata",
+                ));
+            }
+            if let Some(name) = &wire_name {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "#[param(body_stream)] parameters must not declare a wire name",
+                ));
+            }
+            if let Some(span) = repeated {
+                return Err(syn::Error::new(
+                    span,
+                    "#[param(body_stream)] parameters cannot be repeated",
+                ));
+            }
+            if !matches!(http.method.as_str(), "POST" | "PATCH") {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    format!(
+                        "{} methods may be retried; #[param(body_stream)] requires POST or PATCH because a streamed body cannot be replayed",
+                        http.method
+                    ),
+                ));
+            }
+            if body_stream.replace(pattern.ident.span()).is_some() {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "a service invocation method may declare at most one #[param(body_stream)] parameter",
+                ));
+            }
+            let Type::Path(kind) = input.ty.as_ref() else {
+                return Err(syn::Error::new_spanned(
+                    &input.ty,
+                    "#[param(body_stream)] parameters must use the BodyStream type",
+                ));
+            };
+            if !is_runtime_type_path(&kind.path, "BodyStream") {
+                return Err(syn::Error::new_spanned(
+                    &input.ty,
+                    "#[param(body_stream)] parameters must use the BodyStream type",
+                ));
+            }
+        }
+
         let source = explicit_source.map(|(source, _)| source);
         if matches!(
             source,
@@ -684,11 +781,21 @@ fn parameters(signature: &Signature, http: &HttpMapping) -> syn::Result<Vec<Para
             "body fields cannot be combined with a #[param(body)] raw body; mark the other parameters #[param(query)]",
         ));
     }
+    if let Some(stream_span) = body_stream
+        && let Some(span) = raw_body.or(first_body_field)
+    {
+        let mut error = syn::Error::new(
+            span,
+            "JSON body parameters cannot be combined with a #[param(body_stream)] streamed body; mark the other parameters #[param(query)]",
+        );
+        error.combine(syn::Error::new(stream_span, "streamed body declared here"));
+        return Err(error);
+    }
 
     Ok(parameters)
 }
 
-fn response_type(output: &ReturnType) -> syn::Result<Type> {
+fn response_type(output: &ReturnType) -> syn::Result<(Type, bool)> {
     let ReturnType::Type(_, output) = output else {
         return Err(syn::Error::new_spanned(
             output,
@@ -731,6 +838,10 @@ fn response_type(output: &ReturnType) -> syn::Result<Type> {
         ));
     };
     let response = runtime_wrapper_inner(success)?;
+    let (response, streaming) = match response_stream_item(&response)? {
+        Some(item) => (item, true),
+        None => (response, false),
+    };
     validate_owned_type(&response)?;
     let Some(GenericArgument::Type(Type::Path(error))) = args.next() else {
         return Err(syn::Error::new_spanned(
@@ -744,7 +855,52 @@ fn response_type(output: &ReturnType) -> syn::Result<Type> {
             "service invocation methods must use Error as their error type",
         ));
     }
-    Ok(response)
+    Ok((response, streaming))
+}
+
+fn response_stream_item(kind: &Type) -> syn::Result<Option<Type>> {
+    let Type::Path(TypePath {
+        qself: None, path, ..
+    }) = kind
+    else {
+        return Ok(None);
+    };
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+    let is_stream = matches!(segments.as_slice(), [stream] if stream == "ResponseStream")
+        || matches!(segments.as_slice(), [runtime, stream]
+            if runtime == &crate::runtime_crate_name() && stream == "ResponseStream");
+    if !is_stream {
+        return Ok(None);
+    }
+    let Some(segment) = path.segments.last() else {
+        unreachable!("the stream path has segments")
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments)
+            if arguments.args.len() == 1
+                && path
+                    .segments
+                    .iter()
+                    .take(path.segments.len() - 1)
+                    .all(|segment| segment.arguments.is_empty()) =>
+        {
+            match arguments.args.first() {
+                Some(GenericArgument::Type(item)) => Ok(Some(item.clone())),
+                _ => Err(syn::Error::new_spanned(
+                    kind,
+                    "streaming responses must use the fusen-rs ResponseStream<T>",
+                )),
+            }
+        }
+        _ => Err(syn::Error::new_spanned(
+            kind,
+            "streaming responses must use the fusen-rs ResponseStream<T>",
+        )),
+    }
 }
 
 fn is_standard_result_path(path: &syn::Path) -> bool {
@@ -830,7 +986,11 @@ fn runtime_wrapper_inner(kind: &Type) -> syn::Result<Type> {
     }
 }
 
-fn validate_http(args: MethodArgs, method_ident: &Ident) -> syn::Result<HttpMapping> {
+fn validate_http(
+    args: MethodArgs,
+    method_ident: &Ident,
+    streaming: bool,
+) -> syn::Result<HttpMapping> {
     let method = args.method.ok_or_else(|| {
         syn::Error::new(
             method_ident.span(),
@@ -856,7 +1016,29 @@ fn validate_http(args: MethodArgs, method_ident: &Ident) -> syn::Result<HttpMapp
     let path_value = path.value();
     validate_route(&path_value, path.span())?;
     let consumes = validate_media_type(args.consumes, "consumes")?;
-    let produces = validate_media_type(args.produces, "produces")?;
+    let produces_span = args
+        .produces
+        .as_ref()
+        .map_or_else(|| method_ident.span(), syn::LitStr::span);
+    let produces = match args.produces {
+        None if streaming => STREAM_MEDIA_TYPES[0].to_owned(),
+        produces => validate_media_type(produces, "produces")?,
+    };
+    let stream_media_type = produces
+        .parse::<mime::Mime>()
+        .is_ok_and(|media_type| STREAM_MEDIA_TYPES.contains(&media_type.essence_str()));
+    if streaming && !stream_media_type {
+        return Err(syn::Error::new(
+            produces_span,
+            "streaming methods must produce application/x-ndjson or text/event-stream",
+        ));
+    }
+    if !streaming && stream_media_type {
+        return Err(syn::Error::new(
+            produces_span,
+            "application/x-ndjson and text/event-stream are reserved for methods that return ResponseStream<T>",
+        ));
+    }
     Ok(HttpMapping {
         method: method_value,
         path: path_value,
@@ -1082,6 +1264,7 @@ const fn source_name(source: ParameterSource) -> &'static str {
         ParameterSource::Body => "body",
         ParameterSource::QueryMap => "query map",
         ParameterSource::HeaderMap => "header map",
+        ParameterSource::BodyStream => "body_stream",
     }
 }
 
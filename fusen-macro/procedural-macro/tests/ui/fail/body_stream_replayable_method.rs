@@ -0,0 +1,13 @@
+use fusen_procedural_macro::interface;
+
+struct BodyStream;
+struct Error;
+struct Response<T>(T);
+
+#[interface(name = "body-stream-replayable")]
+trait BodyStreamReplayable {
+    #[fusen_procedural_macro::method(method = "PUT", path = "/files")]
+    async fn put(&self, #[param(body_stream)] body: BodyStream) -> Result<Response<()>, Error>;
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use fusen_procedural_macro::interface;
+
+struct BodyStream;
+struct Error;
+struct Response<T>(T);
+
+#[interface(name = "body-stream-with-json-body")]
+trait BodyStreamWithJsonBody {
+    #[fusen_procedural_macro::method(method = "POST", path = "/files")]
+    async fn upload(
+        &self,
+        #[param(body_stream)] body: BodyStream,
+        checksum: String,
+    ) -> Result<Response<()>, Error>;
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use fusen_procedural_macro::interface;
+
+struct Error;
+struct Response<T>(T);
+struct ResponseStream<T>(T);
+
+#[interface(name = "head-stream")]
+trait HeadStream {
+    #[fusen_procedural_macro::method(method = "HEAD", path = "/health")]
+    async fn health(&self) -> Result<Response<ResponseStream<()>>, Error>;
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use fusen_procedural_macro::interface;
+
+struct Error;
+struct Response<T>(T);
+
+#[interface(name = "invalid-body-stream-type")]
+trait InvalidBodyStreamType {
+    #[fusen_procedural_macro::method(method = "POST", path = "/files")]
+    async fn upload(&self, #[param(body_stream)] body: Vec<u8>) -> Result<Response<()>, Error>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fusen_procedural_macro::interface;
+
+#[interface(name = "invalid-priority")]
+trait InvalidPriority {
+    #[fusen_procedural_macro::method(method = "GET", path = "/reports", priority = "urgent")]
+    async fn report(&self) -> Result<Response<()>, Error>;
+}
+
+fn main() {}
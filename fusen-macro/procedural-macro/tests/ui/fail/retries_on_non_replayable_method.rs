@@ -0,0 +1,9 @@
+use fusen_procedural_macro::interface;
+
+#[interface(name = "non-replayable-retries")]
+trait NonReplayableRetries {
+    #[fusen_procedural_macro::method(method = "POST", path = "/orders", retries = 1)]
+    async fn create(&self) -> Result<Response<String>, Error>;
+}
+
+fn main() {}
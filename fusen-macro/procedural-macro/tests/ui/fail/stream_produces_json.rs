@@ -0,0 +1,17 @@
+use fusen_procedural_macro::interface;
+
+struct Error;
+struct Response<T>(T);
+struct ResponseStream<T>(T);
+
+#[interface(name = "stream-produces-json")]
+trait StreamProducesJson {
+    #[fusen_procedural_macro::method(
+        method = "GET",
+        path = "/events",
+        produces = "application/json"
+    )]
+    async fn events(&self) -> Result<Response<ResponseStream<String>>, Error>;
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use fusen_procedural_macro::interface;
+
+struct Error;
+struct Response<T>(T);
+
+#[interface(name = "unary-stream-media-type")]
+trait UnaryStreamMediaType {
+    #[fusen_procedural_macro::method(
+        method = "GET",
+        path = "/events",
+        produces = "application/x-ndjson"
+    )]
+    async fn events(&self) -> Result<Response<String>, Error>;
+}
+
+fn main() {}
@@ -29,6 +29,7 @@ pub mod __macro {
             Body,
             QueryMap,
             HeaderMap,
+            BodyStream,
         }
 
         #[derive(Clone, Copy)]
@@ -94,6 +95,32 @@ pub mod __macro {
             pub fn with_sensitivity(self, _sensitivity: MethodSensitivity) -> Self {
                 self
             }
+
+            pub const fn with_client_defaults(self, _defaults: MethodClientDefaults) -> Self {
+                self
+            }
+
+            pub const fn with_priority(self, _priority: MethodPriority) -> Self {
+                self
+            }
+        }
+
+        pub enum MethodPriority {
+            Low,
+            Normal,
+            High,
+            Critical,
+        }
+
+        pub struct MethodClientDefaults;
+
+        impl MethodClientDefaults {
+            pub const fn new(
+                _timeout: Option<std::time::Duration>,
+                _retries: Option<u8>,
+            ) -> Self {
+                Self
+            }
         }
 
         #[derive(Clone, Copy)]
@@ -204,6 +231,12 @@ pub mod __macro {
             Ok(())
         }
 
+        pub struct BodyStream;
+
+        pub fn with_body_stream(call: Call, _body: BodyStream) -> Call {
+            call
+        }
+
         pub struct Response<T>(T);
 
         impl<T> Response<T> {
@@ -212,6 +245,14 @@ pub mod __macro {
             }
         }
 
+        pub struct ResponseStream<T>(Vec<T>);
+
+        impl<T> ResponseStream<T> {
+            pub fn from_items(items: Vec<T>) -> Self {
+                Self(items)
+            }
+        }
+
         #[derive(Debug)]
         pub struct Error;
 
@@ -230,6 +271,18 @@ pub mod __macro {
             {
                 unimplemented!()
             }
+
+            pub async fn invoke_stream<T, F>(
+                &self,
+                _method: MethodId,
+                _call: Call,
+                _encode: F,
+            ) -> Result<Response<ResponseStream<T>>, Error>
+            where
+                F: FnOnce() -> Result<Arguments, Error>,
+            {
+                unimplemented!()
+            }
         }
 
         pub struct ClientRuntime;
@@ -270,6 +323,10 @@ pub mod __macro {
                 unimplemented!()
             }
 
+            pub fn take_body_stream(&mut self) -> Result<BodyStream, Error> {
+                Ok(BodyStream)
+            }
+
             pub fn finish_arguments(&self) -> Result<(), Error> {
                 Ok(())
             }
@@ -277,6 +334,13 @@ pub mod __macro {
             pub fn encode_response<T>(self, _response: Response<T>) -> InterceptorResult {
                 unimplemented!()
             }
+
+            pub fn encode_stream_response<T>(
+                self,
+                _response: Response<ResponseStream<T>>,
+            ) -> InterceptorResult {
+                unimplemented!()
+            }
         }
 
         pub fn method_not_found(_method: MethodId) -> Error {
@@ -317,7 +381,7 @@ pub mod __macro {
     }
 }
 
-pub use __macro::v1::{Call, Error, Response};
+pub use __macro::v1::{BodyStream, Call, Error, Response, ResponseStream};
 
 struct User(String);
 
@@ -339,14 +403,19 @@ trait UserApi {
         #[param(query)] expand: Option<bool>,
     ) -> Result<Response<User>, Error>;
 
-    #[fusen_procedural_macro::method(method = "POST", path = "/users/batch")]
+    #[fusen_procedural_macro::method(method = "POST", path = "/users/batch", priority = "low")]
     async fn batch(
         &self,
         #[sensitive(opaque)] names: Vec<String>,
         notify: bool,
     ) -> Result<Response<User>, Error>;
 
-    #[fusen_procedural_macro::method(method = "PUT", path = "/users/{id}")]
+    #[fusen_procedural_macro::method(
+        method = "PUT",
+        path = "/users/{id}",
+        timeout_ms = 2_500,
+        retries = 1
+    )]
     async fn replace(
         &self,
         #[param(path)] id: String,
@@ -411,6 +480,41 @@ trait UserApi {
         &self,
         #[param(path)] r#type: String,
     ) -> Result<Response<User>, Error>;
+
+    #[fusen_procedural_macro::method(method = "GET", path = "/users/export")]
+    async fn export(
+        &self,
+        #[param(query)] limit: Option<bool>,
+    ) -> Result<Response<ResponseStream<User>>, Error>;
+
+    #[fusen_procedural_macro::method(
+        method = "POST",
+        path = "/users/watch",
+        produces = "text/event-stream"
+    )]
+    async fn watch(
+        &self,
+        #[param(body)] filter: String,
+    ) -> Result<Response<fusen_rs::ResponseStream<User>>, Error>;
+
+    #[fusen_procedural_macro::method(method = "POST", path = "/users/{id}/avatar")]
+    async fn upload(
+        &self,
+        #[param(context)] call: Call,
+        #[param(path)] id: String,
+        #[param(body_stream)] avatar: BodyStream,
+    ) -> Result<Response<User>, Error>;
+
+    #[fusen_procedural_macro::method(
+        method = "PATCH",
+        path = "/users/import",
+        consumes = "text/csv"
+    )]
+    async fn import(
+        &self,
+        #[param(query)] dry_run: bool,
+        #[param(body_stream)] rows: fusen_rs::BodyStream,
+    ) -> Result<Response<ResponseStream<User>>, Error>;
 }
 
 struct Handler;
@@ -484,6 +588,35 @@ impl UserApi for Handler {
     async fn r#match(&self, r#type: String) -> Result<Response<User>, Error> {
         Ok(Response::new(User(r#type)))
     }
+
+    async fn export(&self, limit: Option<bool>) -> Result<Response<ResponseStream<User>>, Error> {
+        Ok(Response::new(ResponseStream::from_items(vec![User(
+            format!("{limit:?}"),
+        )])))
+    }
+
+    async fn watch(&self, filter: String) -> Result<Response<ResponseStream<User>>, Error> {
+        Ok(Response::new(ResponseStream::from_items(vec![User(filter)])))
+    }
+
+    async fn upload(
+        &self,
+        _call: Call,
+        id: String,
+        _avatar: BodyStream,
+    ) -> Result<Response<User>, Error> {
+        Ok(Response::new(User(id)))
+    }
+
+    async fn import(
+        &self,
+        dry_run: bool,
+        _rows: BodyStream,
+    ) -> Result<Response<ResponseStream<User>>, Error> {
+        Ok(Response::new(ResponseStream::from_items(vec![User(
+            dry_run.to_string(),
+        )])))
+    }
 }
 
 fn assert_interface<T: UserApi>() {}
@@ -25,6 +25,7 @@ const META_GROUP: &str = "fusen.group";
 const META_INSTANCE_ID: &str = "fusen.instance_id";
 const META_HTTP_BINDINGS: &str = "fusen.http.bindings";
 const META_HTTP_VERSIONS: &str = "fusen.http.versions";
+const META_HTTP_EXTRA_VERSIONS: &str = "fusen.http.extra-versions";
 const META_INVOCATION_CONTROLS: &str = "fusen.invocation-controls";
 const INVOCATION_CONTROLS_V1: &str = "v1";
 const DEFAULT_GROUP: &str = "DEFAULT_GROUP";
@@ -481,7 +482,7 @@ fn decode_capabilities(
         return (convention == NacosConvention::SpringCloud).then(EndpointCapabilities::default);
     }
     let bindings = decode_bindings(bindings?)?;
-    let versions = decode_http_versions(versions?)?;
+    let versions = decode_http_versions(versions?, metadata.get(META_HTTP_EXTRA_VERSIONS))?;
     let invocation_controls = match controls.map(String::as_str) {
         None => false,
         Some(INVOCATION_CONTROLS_V1) => true,
@@ -503,26 +504,9 @@ fn decode_bindings(value: &str) -> Option<Vec<HttpBindingId>> {
     (!bindings.is_empty()).then_some(bindings)
 }
 
-fn decode_http_versions(value: &str) -> Option<HttpVersionSet> {
-    let mut http1 = false;
-    let mut http2 = false;
-    for version in value.split(',') {
-        let seen = match version {
-            "1.1" => &mut http1,
-            "2" => &mut http2,
-            _ => return None,
-        };
-        if *seen {
-            return None;
-        }
-        *seen = true;
-    }
-    match (http1, http2) {
-        (true, true) => Some(HttpVersionSet::ALL),
-        (true, false) => Some(HttpVersionSet::HTTP_1_1),
-        (false, true) => Some(HttpVersionSet::HTTP_2),
-        (false, false) => None,
-    }
+fn decode_http_versions(value: &str, extra: Option<&String>) -> Option<HttpVersionSet> {
+    let extra = extra.map(|extra| extra.split(','));
+    HttpVersionSet::from_labels(value.split(',').chain(extra.into_iter().flatten())).ok()
 }
 
 fn service_name(selector: &ServiceSelector) -> String {
@@ -586,10 +570,12 @@ fn build_instance(
             .collect::<Vec<_>>()
             .join(","),
     );
-    metadata.insert(
-        META_HTTP_VERSIONS.into(),
-        encode_http_versions(registration.capabilities().http_versions()).into(),
-    );
+    let (versions, extra_versions) =
+        encode_http_versions(registration.capabilities().http_versions());
+    metadata.insert(META_HTTP_VERSIONS.into(), versions);
+    if let Some(extra_versions) = extra_versions {
+        metadata.insert(META_HTTP_EXTRA_VERSIONS.into(), extra_versions);
+    }
     if registration.capabilities().invocation_controls() {
         metadata.insert(
             META_INVOCATION_CONTROLS.into(),
@@ -615,13 +601,19 @@ fn build_instance(
     })
 }
 
-fn encode_http_versions(versions: HttpVersionSet) -> &'static str {
-    if versions == HttpVersionSet::HTTP_1_1 {
-        "1.1"
-    } else if versions == HttpVersionSet::HTTP_2 {
-        "2"
+/// Splits labels so the baseline `fusen.http.versions` key keeps only HTTP/1.1 and HTTP/2.
+///
+/// Clients released before HTTP/3 support drop an instance whose version list has an unknown
+/// label; HTTP/3 is therefore written to a separate key unless it is the only version.
+fn encode_http_versions(versions: HttpVersionSet) -> (String, Option<String>) {
+    let (baseline, extra): (Vec<_>, Vec<_>) = versions.labels().partition(|label| *label != "3");
+    if baseline.is_empty() {
+        (extra.join(","), None)
     } else {
-        "1.1,2"
+        (
+            baseline.join(","),
+            (!extra.is_empty()).then(|| extra.join(",")),
+        )
     }
 }
 
@@ -851,6 +843,44 @@ mod tests {
         assert_eq!(capabilities.http_versions(), HttpVersionSet::ALL);
         assert!(capabilities.invocation_controls());
 
+        let mut with_http3 = valid.clone();
+        with_http3.insert(META_HTTP_VERSIONS.into(), "3,1.1".into());
+        let versions = decode_capabilities(&with_http3, NacosConvention::Canonical)
+            .unwrap()
+            .http_versions();
+        assert!(versions.contains(http::Version::HTTP_11));
+        assert_eq!(
+            versions.contains(http::Version::HTTP_3),
+            HttpVersionSet::new([http::Version::HTTP_3]).is_ok()
+        );
+        let (baseline, extra) = encode_http_versions(versions);
+        assert_eq!(
+            decode_http_versions(&baseline, extra.as_ref()),
+            Some(versions)
+        );
+
+        let mut split_http3 = valid.clone();
+        split_http3.insert(META_HTTP_VERSIONS.into(), "1.1".into());
+        split_http3.insert(META_HTTP_EXTRA_VERSIONS.into(), "3".into());
+        assert_eq!(
+            decode_capabilities(&split_http3, NacosConvention::Canonical)
+                .unwrap()
+                .http_versions(),
+            versions
+        );
+        split_http3.insert(META_HTTP_EXTRA_VERSIONS.into(), "1.1".into());
+        assert!(decode_capabilities(&split_http3, NacosConvention::Canonical).is_none());
+
+        let all = HttpVersionSet::from_labels(["1.1", "2", "3"]).unwrap();
+        if all.contains(http::Version::HTTP_3) {
+            assert_eq!(
+                encode_http_versions(all),
+                ("1.1,2".to_owned(), Some("3".to_owned()))
+            );
+            let http3 = HttpVersionSet::from_labels(["3"]).unwrap();
+            assert_eq!(encode_http_versions(http3), ("3".to_owned(), None));
+        }
+
         for metadata in [
             std::collections::HashMap::from([(META_HTTP_BINDINGS.into(), "http-json-v1".into())]),
             std::collections::HashMap::from([
@@ -878,7 +908,7 @@ mod tests {
             ]),
             std::collections::HashMap::from([
                 (META_HTTP_BINDINGS.into(), "http-json-v1".into()),
-                (META_HTTP_VERSIONS.into(), "3".into()),
+                (META_HTTP_VERSIONS.into(), "1.1,4".into()),
             ]),
             std::collections::HashMap::from([
                 (META_HTTP_BINDINGS.into(), "http-json-v1".into()),
@@ -38,6 +38,8 @@ pub enum MetricOutcome {
     Cancelled,
     /// Admission or a resource budget rejected the work.
     Rejected,
+    /// A concurrent hedged attempt succeeded first and this attempt was cancelled.
+    Superseded,
 }
 
 /// The state of a circuit breaker.
@@ -216,6 +218,7 @@ pub struct AttemptFinishedEvent<'a> {
     outcome: MetricOutcome,
     failure_class: Option<&'a str>,
     duration: Duration,
+    locality: Option<&'a str>,
 }
 
 impl<'a> AttemptFinishedEvent<'a> {
@@ -240,8 +243,14 @@ impl<'a> AttemptFinishedEvent<'a> {
             outcome,
             failure_class,
             duration,
+            locality: None,
         }
     }
+    /// Tags the zone, or region, of the instance the attempt was sent to.
+    pub const fn with_locality(mut self, locality: Option<&'a str>) -> Self {
+        self.locality = locality;
+        self
+    }
     /// Returns the stable HTTP binding identifier.
     pub const fn binding(&self) -> &'a str {
         self.binding
@@ -274,6 +283,10 @@ impl<'a> AttemptFinishedEvent<'a> {
     pub const fn duration(&self) -> Duration {
         self.duration
     }
+    /// Returns the zone, or region, advertised by the selected instance.
+    pub const fn locality(&self) -> Option<&'a str> {
+        self.locality
+    }
 }
 
 /// An admission or bounded-resource rejection.
@@ -152,6 +152,9 @@ fn attempt_attributes(event: &AttemptFinishedEvent<'_>) -> Vec<KeyValue> {
     if let Some(failure_class) = event.failure_class() {
         attributes.push(KeyValue::new("failure_class", failure_class.to_owned()));
     }
+    if let Some(locality) = event.locality() {
+        attributes.push(KeyValue::new("locality", locality.to_owned()));
+    }
     attributes
 }
 
@@ -203,6 +206,7 @@ const fn outcome_name(outcome: MetricOutcome) -> &'static str {
         MetricOutcome::Timeout => "timeout",
         MetricOutcome::Cancelled => "cancelled",
         MetricOutcome::Rejected => "rejected",
+        MetricOutcome::Superseded => "superseded",
     }
 }
 
@@ -288,6 +292,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn attempt_attributes_include_a_tagged_locality() {
+        let event = AttemptFinishedEvent::new(
+            "http-json-v1",
+            Some("1.1"),
+            "service",
+            "call",
+            1,
+            MetricOutcome::Success,
+            None,
+            std::time::Duration::ZERO,
+        );
+        assert!(
+            !attempt_attributes(&event)
+                .iter()
+                .any(|attribute| attribute.key.as_str() == "locality")
+        );
+
+        let tagged = event.with_locality(Some("zone-a"));
+        assert!(attempt_attributes(&tagged).iter().any(|attribute| {
+            attribute.key.as_str() == "locality" && attribute.value.to_string() == "zone-a"
+        }));
+    }
+
     #[test]
     fn circuit_attributes_include_the_http_binding() {
         let event = CircuitStateChangedEvent::new(
@@ -0,0 +1,810 @@
+use crate::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    directory::{Directory, DirectoryPublisher, DirectorySnapshot, DirectoryState, directory},
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use fusen_contract::{InstanceId, ServiceInstance, ServiceSelector};
+use futures_util::future::join_all;
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::Notify, task::JoinHandle};
+
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Registry that combines several providers, for example while migrating between them.
+///
+/// Sources are ordered by precedence: higher priorities first, then the order in which they were
+/// added. When several sources publish an instance with the same [`InstanceId`], the source with
+/// the highest precedence wins.
+///
+/// A subscription follows every source and publishes the merged instances of the sources that
+/// are `Ready` or `Stale`. The merged directory is `Ready` while at least one source is `Ready`,
+/// `Stale` while at least one is `Stale`, and `Unavailable` otherwise. Activation succeeds when at
+/// least one source activates. A source that fails to activate, or whose directory closes later,
+/// is subscribed again in the background with exponential backoff until it succeeds or the
+/// subscription is closed; meanwhile its instances are left out of the merged directory.
+///
+/// A registration is published to every source. If any source fails to activate, the sources
+/// that succeeded are closed again before the first error is returned, so a registration is
+/// never left half-published.
+#[derive(Clone)]
+pub struct CompositeRegistry {
+    sources: Arc<[Arc<dyn Registry>]>,
+}
+
+impl std::fmt::Debug for CompositeRegistry {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("CompositeRegistry")
+            .field("sources", &self.sources.len())
+            .finish()
+    }
+}
+
+/// Builder for [`CompositeRegistry`].
+#[derive(Default)]
+pub struct CompositeRegistryBuilder {
+    sources: Vec<(u32, Arc<dyn Registry>)>,
+}
+
+impl std::fmt::Debug for CompositeRegistryBuilder {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("CompositeRegistryBuilder")
+            .field("sources", &self.sources.len())
+            .finish()
+    }
+}
+
+impl CompositeRegistry {
+    /// Starts a registry without sources.
+    pub fn builder() -> CompositeRegistryBuilder {
+        CompositeRegistryBuilder::default()
+    }
+
+    /// Returns the number of combined sources.
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+}
+
+impl CompositeRegistryBuilder {
+    /// Adds a source with priority 0.
+    ///
+    /// Among sources with the same priority, it has lower precedence than those added before it.
+    pub fn source(self, registry: impl Registry) -> Self {
+        self.prioritized_source(registry, 0)
+    }
+
+    /// Adds a source whose instances take precedence over those of lower-priority sources.
+    pub fn prioritized_source(mut self, registry: impl Registry, priority: u32) -> Self {
+        self.sources.push((priority, Arc::new(registry)));
+        self
+    }
+
+    /// Builds the registry; at least one source is required.
+    pub fn build(mut self) -> Result<CompositeRegistry, RegistryError> {
+        if self.sources.is_empty() {
+            return Err(RegistryError::message(
+                RegistryOperation::Build,
+                RegistryErrorKind::InvalidResource,
+                "a composite registry requires at least one source",
+            ));
+        }
+        // The sort is stable, so equal priorities keep their insertion order.
+        self.sources
+            .sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+        Ok(CompositeRegistry {
+            sources: self.sources.into_iter().map(|(_, source)| source).collect(),
+        })
+    }
+}
+
+impl Registry for CompositeRegistry {
+    fn prepare_registration(
+        &self,
+        request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        let handles = Arc::new(
+            self.sources
+                .iter()
+                .map(|source| source.prepare_registration(request.clone()))
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+        let close_handles = handles.clone();
+        Ok(provider::registration(
+            async move {
+                let results = join_all(handles.iter().map(RegistrationHandle::activate)).await;
+                match results.into_iter().find_map(Result::err) {
+                    None => Ok(()),
+                    Some(error) => {
+                        // Compensation is best effort; the activation error is the one reported.
+                        let _ = close_registrations(&handles).await;
+                        Err(error)
+                    }
+                }
+            },
+            move || async move { close_registrations(&close_handles).await },
+        ))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let selector = request.into_selector();
+        let handles = self
+            .sources
+            .iter()
+            .map(|source| source.prepare_subscription(SubscriptionRequest::new(selector.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (publisher, directory) = directory();
+        let shared = Arc::new(Mutex::new(Shared {
+            members: (0..handles.len()).map(|_| None).collect(),
+            activating: (0..handles.len()).map(|_| None).collect(),
+            tasks: Vec::new(),
+            closed: false,
+        }));
+        let sources = self.sources.clone();
+        let close_shared = shared.clone();
+
+        Ok(provider::subscription(
+            directory,
+            async move {
+                let results = join_all(handles.iter().map(SubscriptionHandle::activate)).await;
+                if results.iter().all(Result::is_err) {
+                    let _ = close_subscriptions(handles).await;
+                    let error = results.into_iter().find_map(Result::err);
+                    return Err(error.expect("a composite registry has at least one source"));
+                }
+                let notify = Arc::new(Notify::new());
+                let mut guard = lock(&shared);
+                for (index, (handle, result)) in handles.into_iter().zip(results).enumerate() {
+                    let slot = SourceSlot {
+                        source: sources[index].clone(),
+                        selector: selector.clone(),
+                        index,
+                        shared: shared.clone(),
+                        notify: notify.clone(),
+                    };
+                    match result {
+                        Ok(directory) => {
+                            guard.spawn(forward(slot, directory.clone()));
+                            guard.members[index] = Some(Member { handle, directory });
+                        }
+                        // The failed handle has finished; a fresh one is prepared per attempt.
+                        Err(_) => guard.spawn(resubscribe(slot)),
+                    }
+                }
+                if let Some((state, instances)) = merge(&guard.members) {
+                    publisher.publish_snapshot(state, instances)?;
+                }
+                guard.spawn(republish(shared.clone(), publisher, notify));
+                Ok(())
+            },
+            move || async move {
+                let (tasks, members, activating) = {
+                    let mut guard = lock(&close_shared);
+                    guard.closed = true;
+                    (
+                        std::mem::take(&mut guard.tasks),
+                        std::mem::take(&mut guard.members),
+                        std::mem::take(&mut guard.activating),
+                    )
+                };
+                for task in &tasks {
+                    task.abort();
+                }
+                for task in tasks {
+                    let _ = task.await;
+                }
+                // Handles whose activation was interrupted are closed here as well, so their
+                // cleanup has finished once this returns.
+                let handles = members
+                    .into_iter()
+                    .flatten()
+                    .map(|member| member.handle)
+                    .chain(activating.into_iter().flatten());
+                close_subscriptions(handles).await
+            },
+        ))
+    }
+}
+
+/// Source subscriptions and background tasks of one composite subscription.
+struct Shared {
+    /// Active source subscriptions, indexed by precedence.
+    members: Vec<Option<Member>>,
+    /// Source subscriptions that are being subscribed again, indexed by precedence.
+    activating: Vec<Option<SubscriptionHandle>>,
+    tasks: Vec<JoinHandle<()>>,
+    closed: bool,
+}
+
+impl Shared {
+    /// Spawns a background task and forgets those that have already finished.
+    fn spawn(&mut self, task: impl Future<Output = ()> + Send + 'static) {
+        self.tasks.retain(|task| !task.is_finished());
+        self.tasks.push(tokio::spawn(task));
+    }
+}
+
+struct Member {
+    handle: SubscriptionHandle,
+    directory: Directory,
+}
+
+/// One source of a composite subscription, as seen by its background tasks.
+#[derive(Clone)]
+struct SourceSlot {
+    source: Arc<dyn Registry>,
+    selector: ServiceSelector,
+    /// Precedence index into [`Shared::members`].
+    index: usize,
+    shared: Arc<Mutex<Shared>>,
+    notify: Arc<Notify>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Closes every handle and returns the first failure.
+async fn close_registrations(handles: &[RegistrationHandle]) -> Result<(), RegistryError> {
+    join_all(handles.iter().map(RegistrationHandle::close))
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Closes every handle and returns the first failure.
+async fn close_subscriptions(
+    handles: impl IntoIterator<Item = SubscriptionHandle>,
+) -> Result<(), RegistryError> {
+    join_all(handles.into_iter().map(|handle| handle.close()))
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Signals every change of one source directory and subscribes again once it closes.
+async fn forward(slot: SourceSlot, mut directory: Directory) {
+    while let Ok(snapshot) = directory.changed().await
+        && snapshot.state() != DirectoryState::Closed
+    {
+        slot.notify.notify_one();
+    }
+    let shared = slot.shared.clone();
+    let mut guard = lock(&shared);
+    if guard.closed {
+        return;
+    }
+    if let Some(member) = guard.members[slot.index].take() {
+        member.handle.request_close();
+    }
+    slot.notify.notify_one();
+    guard.spawn(resubscribe(slot));
+}
+
+/// Publishes the merged snapshot after every source change.
+async fn republish(shared: Arc<Mutex<Shared>>, publisher: DirectoryPublisher, notify: Arc<Notify>) {
+    loop {
+        notify.notified().await;
+        if publisher.is_closed() {
+            return;
+        }
+        let Some((state, instances)) = merge(&lock(&shared).members) else {
+            continue;
+        };
+        if publisher.publish_snapshot(state, instances).is_err() {
+            return;
+        }
+    }
+}
+
+/// Subscribes to one source again until activation succeeds.
+///
+/// The future is boxed because it and [`forward`] spawn each other.
+fn resubscribe(slot: SourceSlot) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let mut delay = MIN_RETRY_DELAY;
+        loop {
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+            let request = SubscriptionRequest::new(slot.selector.clone());
+            let Ok(handle) = slot.source.prepare_subscription(request) else {
+                continue;
+            };
+            {
+                let mut guard = lock(&slot.shared);
+                if guard.closed {
+                    return;
+                }
+                guard.activating[slot.index] = Some(handle.clone());
+            }
+            let result = handle.activate().await;
+            if result.is_err() {
+                let _ = handle.close().await;
+            }
+            let shared = slot.shared.clone();
+            let mut guard = lock(&shared);
+            // Once closed, the composite subscription owns the handle and closes it.
+            if guard.closed {
+                return;
+            }
+            guard.activating[slot.index] = None;
+            let Ok(directory) = result else {
+                continue;
+            };
+            guard.members[slot.index] = Some(Member {
+                handle,
+                directory: directory.clone(),
+            });
+            slot.notify.notify_one();
+            guard.spawn(forward(slot, directory));
+            return;
+        }
+    })
+}
+
+/// Merges the routable source snapshots in precedence order.
+///
+/// Returns `None` while no source is routable and at least one has not published yet.
+fn merge(members: &[Option<Member>]) -> Option<(DirectoryState, Vec<ServiceInstance>)> {
+    merge_snapshots(
+        members
+            .iter()
+            .flatten()
+            .map(|member| member.directory.snapshot()),
+    )
+}
+
+fn merge_snapshots(
+    snapshots: impl IntoIterator<Item = DirectorySnapshot>,
+) -> Option<(DirectoryState, Vec<ServiceInstance>)> {
+    let mut state = DirectoryState::Unavailable;
+    let mut initializing = false;
+    let mut seen = BTreeSet::<InstanceId>::new();
+    let mut instances = Vec::new();
+    for snapshot in snapshots {
+        match snapshot.state() {
+            DirectoryState::Ready => state = DirectoryState::Ready,
+            DirectoryState::Stale if state != DirectoryState::Ready => {
+                state = DirectoryState::Stale;
+            }
+            DirectoryState::Stale => {}
+            DirectoryState::Initializing => {
+                initializing = true;
+                continue;
+            }
+            _ => continue,
+        }
+        for instance in snapshot.iter() {
+            if seen.insert(instance.instance_id().clone()) {
+                instances.push(instance.clone());
+            }
+        }
+    }
+    if state == DirectoryState::Unavailable && initializing {
+        return None;
+    }
+    Some((state, instances))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::registration;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn instance(id: &str, host: &str) -> ServiceInstance {
+        ServiceInstance::new(
+            InstanceId::new(id).unwrap(),
+            format!("http://{host}").parse().unwrap(),
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    fn selector() -> ServiceSelector {
+        ServiceSelector::new("greeter", None, None).unwrap()
+    }
+
+    /// Provider whose subscriptions expose their publishers and whose activations can fail or
+    /// wait on a gate.
+    #[derive(Clone, Default)]
+    struct FakeRegistry {
+        publishers: Arc<Mutex<Vec<DirectoryPublisher>>>,
+        failing_activations: Arc<AtomicUsize>,
+        gate: Arc<Mutex<Option<Arc<Notify>>>>,
+        gated_activations: Arc<AtomicUsize>,
+        closed_subscriptions: Arc<AtomicUsize>,
+        registered: Arc<AtomicUsize>,
+        closed_registrations: Arc<AtomicUsize>,
+    }
+
+    impl FakeRegistry {
+        fn failing(activations: usize) -> Self {
+            let registry = Self::default();
+            registry
+                .failing_activations
+                .store(activations, Ordering::SeqCst);
+            registry
+        }
+
+        fn publisher(&self) -> DirectoryPublisher {
+            lock(&self.publishers).last().cloned().unwrap()
+        }
+
+        fn fail(&self) -> Result<(), RegistryError> {
+            let remaining = self.failing_activations.load(Ordering::SeqCst);
+            if remaining == 0 {
+                return Ok(());
+            }
+            self.failing_activations
+                .store(remaining - 1, Ordering::SeqCst);
+            Err(RegistryError::message(
+                RegistryOperation::ActivateSubscription,
+                RegistryErrorKind::Unavailable,
+                "source unavailable",
+            ))
+        }
+    }
+
+    impl Registry for FakeRegistry {
+        fn prepare_registration(
+            &self,
+            _request: RegistrationRequest,
+        ) -> Result<RegistrationHandle, RegistryError> {
+            let activate = self.clone();
+            let close = self.clone();
+            Ok(provider::registration(
+                async move {
+                    activate.fail()?;
+                    activate.registered.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                },
+                move || async move {
+                    close.closed_registrations.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                },
+            ))
+        }
+
+        fn prepare_subscription(
+            &self,
+            _request: SubscriptionRequest,
+        ) -> Result<SubscriptionHandle, RegistryError> {
+            let (publisher, directory) = directory();
+            let registry = self.clone();
+            let close = self.clone();
+            Ok(provider::subscription(
+                directory,
+                async move {
+                    registry.fail()?;
+                    let gate = lock(&registry.gate).clone();
+                    if let Some(gate) = gate {
+                        registry.gated_activations.fetch_add(1, Ordering::SeqCst);
+                        gate.notified().await;
+                    }
+                    lock(&registry.publishers).push(publisher);
+                    Ok(())
+                },
+                move || async move {
+                    close.closed_subscriptions.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                },
+            ))
+        }
+    }
+
+    async fn wait_for(
+        directory: &mut Directory,
+        ready: impl Fn(&DirectorySnapshot) -> bool,
+    ) -> DirectorySnapshot {
+        crate::testing::wait_for(directory, Duration::from_secs(60), ready).await
+    }
+
+    fn hosts(snapshot: &DirectorySnapshot) -> Vec<String> {
+        snapshot
+            .iter()
+            .map(|instance| {
+                format!(
+                    "{}@{}",
+                    instance.instance_id().as_str(),
+                    instance.endpoint().as_url().host_str().unwrap()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merging_prefers_earlier_sources_and_skips_unroutable_ones() {
+        let (primary, primary_directory) = directory();
+        let (secondary, secondary_directory) = directory();
+        let (unavailable, unavailable_directory) = directory();
+        primary
+            .publish_snapshot(
+                DirectoryState::Stale,
+                vec![instance("a", "primary"), instance("b", "primary")],
+            )
+            .unwrap();
+        secondary
+            .publish_ready(vec![instance("a", "secondary"), instance("c", "secondary")])
+            .unwrap();
+        unavailable
+            .publish_snapshot(DirectoryState::Unavailable, vec![instance("d", "lost")])
+            .unwrap();
+
+        let (state, instances) = merge_snapshots([
+            primary_directory.snapshot(),
+            secondary_directory.snapshot(),
+            unavailable_directory.snapshot(),
+        ])
+        .unwrap();
+        assert_eq!(state, DirectoryState::Ready);
+        let ids = instances
+            .iter()
+            .map(|instance| instance.endpoint().as_url().host_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["primary", "primary", "secondary"]);
+
+        let (state, instances) = merge_snapshots([
+            primary_directory.snapshot(),
+            unavailable_directory.snapshot(),
+        ])
+        .unwrap();
+        assert_eq!(state, DirectoryState::Stale);
+        assert_eq!(instances.len(), 2);
+        let (state, instances) = merge_snapshots([unavailable_directory.snapshot()]).unwrap();
+        assert_eq!(state, DirectoryState::Unavailable);
+        assert!(instances.is_empty());
+
+        let (_initializing, initializing_directory) = directory();
+        assert!(
+            merge_snapshots([
+                initializing_directory.snapshot(),
+                unavailable_directory.snapshot()
+            ])
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriptions_stay_ready_while_one_source_is_healthy() {
+        let primary = FakeRegistry::default();
+        let secondary = FakeRegistry::default();
+        let registry = CompositeRegistry::builder()
+            .source(primary.clone())
+            .source(secondary.clone())
+            .build()
+            .unwrap();
+        let handle = registry
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let mut directory = handle.activate().await.unwrap();
+        assert_eq!(directory.snapshot().state(), DirectoryState::Initializing);
+
+        primary
+            .publisher()
+            .publish_ready(vec![instance("shared", "primary")])
+            .unwrap();
+        secondary
+            .publisher()
+            .publish_ready(vec![
+                instance("shared", "secondary"),
+                instance("new", "secondary"),
+            ])
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 2).await;
+        assert_eq!(snapshot.state(), DirectoryState::Ready);
+        assert_eq!(hosts(&snapshot), ["shared@primary", "new@secondary"]);
+
+        primary
+            .publisher()
+            .publish_state(DirectoryState::Unavailable)
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 2).await;
+        assert_eq!(snapshot.state(), DirectoryState::Ready);
+        assert_eq!(hosts(&snapshot), ["shared@secondary", "new@secondary"]);
+
+        secondary
+            .publisher()
+            .publish_state(DirectoryState::Stale)
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| {
+            snapshot.state() == DirectoryState::Stale
+        })
+        .await;
+        assert_eq!(snapshot.len(), 2);
+
+        handle.close().await.unwrap();
+        assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sources_that_fail_to_activate_are_subscribed_again() {
+        let primary = FakeRegistry::failing(2);
+        let secondary = FakeRegistry::default();
+        let handle = CompositeRegistry::builder()
+            .source(primary.clone())
+            .source(secondary.clone())
+            .build()
+            .unwrap()
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let mut directory = handle.activate().await.unwrap();
+        secondary
+            .publisher()
+            .publish_ready(vec![instance("b", "secondary")])
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 1).await;
+        assert_eq!(snapshot.state(), DirectoryState::Ready);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        primary
+            .publisher()
+            .publish_ready(vec![instance("a", "primary")])
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 2).await;
+        assert_eq!(hosts(&snapshot), ["a@primary", "b@secondary"]);
+
+        handle.close().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sources_whose_directory_closes_are_subscribed_again() {
+        let primary = FakeRegistry::default();
+        let secondary = FakeRegistry::default();
+        let handle = CompositeRegistry::builder()
+            .source(primary.clone())
+            .source(secondary.clone())
+            .build()
+            .unwrap()
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let mut directory = handle.activate().await.unwrap();
+        primary
+            .publisher()
+            .publish_ready(vec![instance("a", "primary")])
+            .unwrap();
+        secondary
+            .publisher()
+            .publish_ready(vec![instance("b", "secondary")])
+            .unwrap();
+        wait_for(&mut directory, |snapshot| snapshot.len() == 2).await;
+
+        // Dropping the only publisher closes the primary source's directory.
+        lock(&primary.publishers).clear();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 1).await;
+        assert_eq!(hosts(&snapshot), ["b@secondary"]);
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        primary
+            .publisher()
+            .publish_ready(vec![instance("a", "primary")])
+            .unwrap();
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 2).await;
+        assert_eq!(hosts(&snapshot), ["a@primary", "b@secondary"]);
+
+        handle.close().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closing_waits_for_sources_whose_activation_is_pending() {
+        let primary = FakeRegistry::failing(1);
+        let secondary = FakeRegistry::default();
+        let handle = CompositeRegistry::builder()
+            .source(primary.clone())
+            .source(secondary.clone())
+            .build()
+            .unwrap()
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        handle.activate().await.unwrap();
+        let gate = Arc::new(Notify::new());
+        *lock(&primary.gate) = Some(gate.clone());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(primary.gated_activations.load(Ordering::SeqCst), 1);
+
+        let close = tokio::spawn(async move { handle.close().await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!close.is_finished());
+        // Only the subscription whose first activation failed has been cleaned up so far.
+        assert_eq!(primary.closed_subscriptions.load(Ordering::SeqCst), 1);
+
+        gate.notify_one();
+        close.await.unwrap().unwrap();
+        assert_eq!(primary.closed_subscriptions.load(Ordering::SeqCst), 2);
+        assert_eq!(secondary.closed_subscriptions.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn priorities_override_insertion_order() {
+        let low = FakeRegistry::default();
+        let high = FakeRegistry::default();
+        let also_low = FakeRegistry::default();
+        let handle = CompositeRegistry::builder()
+            .source(low.clone())
+            .prioritized_source(high.clone(), 1)
+            .source(also_low.clone())
+            .build()
+            .unwrap()
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let mut directory = handle.activate().await.unwrap();
+        for (registry, host) in [(&low, "low"), (&high, "high"), (&also_low, "also-low")] {
+            registry
+                .publisher()
+                .publish_ready(vec![instance("shared", host), instance(host, host)])
+                .unwrap();
+        }
+        let snapshot = wait_for(&mut directory, |snapshot| snapshot.len() == 4).await;
+        assert_eq!(
+            hosts(&snapshot),
+            ["shared@high", "high@high", "low@low", "also-low@also-low"]
+        );
+
+        handle.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn subscriptions_fail_when_every_source_fails() {
+        let handle = CompositeRegistry::builder()
+            .source(FakeRegistry::failing(1))
+            .source(FakeRegistry::failing(1))
+            .build()
+            .unwrap()
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let error = handle.activate().await.unwrap_err();
+        assert_eq!(error.kind(), RegistryErrorKind::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn registrations_are_published_everywhere_or_nowhere() {
+        let primary = FakeRegistry::default();
+        let secondary = FakeRegistry::default();
+        let registry = CompositeRegistry::builder()
+            .source(primary.clone())
+            .source(secondary.clone())
+            .build()
+            .unwrap();
+        let handle = registry
+            .prepare_registration(registration("greeter-1", 8080, "1.0"))
+            .unwrap();
+        handle.activate().await.unwrap();
+        assert_eq!(primary.registered.load(Ordering::SeqCst), 1);
+        assert_eq!(secondary.registered.load(Ordering::SeqCst), 1);
+        handle.close().await.unwrap();
+        assert_eq!(primary.closed_registrations.load(Ordering::SeqCst), 1);
+        assert_eq!(secondary.closed_registrations.load(Ordering::SeqCst), 1);
+
+        secondary.failing_activations.store(1, Ordering::SeqCst);
+        let handle = registry
+            .prepare_registration(registration("greeter-1", 8080, "1.0"))
+            .unwrap();
+        let error = handle.activate().await.unwrap_err();
+        assert_eq!(error.kind(), RegistryErrorKind::Unavailable);
+        assert_eq!(primary.registered.load(Ordering::SeqCst), 2);
+        assert_eq!(primary.closed_registrations.load(Ordering::SeqCst), 2);
+        handle.close().await.unwrap();
+        assert_eq!(primary.closed_registrations.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn at_least_one_source_is_required() {
+        let error = CompositeRegistry::builder().build().unwrap_err();
+        assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+        assert_eq!(error.operation(), RegistryOperation::Build);
+    }
+}
@@ -19,6 +19,8 @@ pub enum RegistryOperation {
     CloseSubscription,
     /// Reading or publishing a directory snapshot failed.
     Directory,
+    /// A registry builder or constructor rejected its settings.
+    Build,
 }
 
 impl fmt::Display for RegistryOperation {
@@ -31,6 +33,7 @@ impl fmt::Display for RegistryOperation {
             Self::ActivateSubscription => "activate subscription",
             Self::CloseSubscription => "close subscription",
             Self::Directory => "access directory",
+            Self::Build => "build registry",
         })
     }
 }
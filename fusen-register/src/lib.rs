@@ -19,10 +19,15 @@ use crate::{
     error::{RegistryError, RegistryErrorKind, RegistryOperation},
 };
 
+/// Registries that combine several providers.
+pub mod composite;
 /// Latest-wins service directories and provider publication handles.
 pub mod directory;
 /// Classified registry failures.
 pub mod error;
+/// Helpers for registry provider tests.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 /// Owned, sendable future returned by registry lifecycle APIs.
 pub type RegistryFuture<T> =
     Pin<Box<dyn Future<Output = Result<T, RegistryError>> + Send + 'static>>;
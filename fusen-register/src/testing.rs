@@ -0,0 +1,134 @@
+use crate::{
+    RegistrationRequest,
+    directory::{Directory, DirectorySnapshot},
+};
+use fusen_contract::{
+    EndpointCapabilities, HttpBindingId, HttpOperation, HttpVersionSet, InstanceId,
+    MethodDescriptor, MethodId, ServiceDescriptor, ServiceRegistration, ServiceSelector,
+    ServiceWeight,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Returns the `greeter` selector at `version`, in the default group.
+pub fn selector(version: &str) -> ServiceSelector {
+    ServiceSelector::new("greeter", None, Some(version.to_owned())).expect("valid selector")
+}
+
+/// Returns a registration of instance `id` of [`selector`]`(version)` at `127.0.0.1:port`.
+///
+/// The `greeter` descriptor has one JSON `POST /hello` method and is leaked, so each call costs
+/// one small allocation for the rest of the test process.
+pub fn registration(id: &str, port: u16, version: &str) -> RegistrationRequest {
+    let descriptor = Box::leak(Box::new(
+        ServiceDescriptor::new(
+            selector(version),
+            vec![
+                MethodDescriptor::new(
+                    MethodId::new(0),
+                    "hello",
+                    HttpOperation::new(
+                        "POST".parse().expect("valid method"),
+                        "/hello",
+                        vec![],
+                        "application/json",
+                        "application/json",
+                    )
+                    .expect("valid operation"),
+                )
+                .expect("valid method descriptor"),
+            ],
+        )
+        .expect("valid service descriptor"),
+    ));
+    RegistrationRequest::new(Arc::new(ServiceRegistration::new(
+        InstanceId::new(id).expect("valid instance ID"),
+        descriptor,
+        format!("http://127.0.0.1:{port}")
+            .parse()
+            .expect("valid endpoint"),
+        EndpointCapabilities::new(HttpVersionSet::default(), [HttpBindingId::default()], false)
+            .expect("valid capabilities"),
+        ServiceWeight::new(1.0).expect("valid weight"),
+    )))
+}
+
+/// Returns the instance IDs of `snapshot` in published order.
+pub fn ids(snapshot: &DirectorySnapshot) -> Vec<&str> {
+    snapshot
+        .iter()
+        .map(|instance| instance.instance_id().as_str())
+        .collect()
+}
+
+/// Polls `ready` every 20 ms until it holds.
+///
+/// # Panics
+///
+/// Panics, naming `description`, when `ready` does not hold within 10 seconds.
+pub async fn eventually(description: &str, ready: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !ready() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timed out waiting until {description}"));
+}
+
+/// Waits for the first snapshot that satisfies `ready`, skipping already published revisions.
+///
+/// # Panics
+///
+/// Panics when the directory closes or no matching snapshot is published within `timeout`.
+pub async fn wait_for(
+    directory: &mut Directory,
+    timeout: Duration,
+    ready: impl Fn(&DirectorySnapshot) -> bool,
+) -> DirectorySnapshot {
+    tokio::time::timeout(timeout, async {
+        loop {
+            let snapshot = directory
+                .changed()
+                .await
+                .expect("the directory must stay open");
+            if ready(&snapshot) {
+                return snapshot;
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("the registry must publish a matching snapshot within {timeout:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directory::{DirectoryState, directory};
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_snapshots_until_one_is_ready() {
+        let (publisher, mut directory) = directory();
+        publisher.publish_ready(Vec::new()).unwrap();
+        let waiter = tokio::spawn(async move {
+            wait_for(&mut directory, Duration::from_secs(1), |snapshot| {
+                snapshot.state() == DirectoryState::Stale
+            })
+            .await
+        });
+        tokio::task::yield_now().await;
+        publisher.publish_state(DirectoryState::Stale).unwrap();
+        assert_eq!(waiter.await.unwrap().state(), DirectoryState::Stale);
+    }
+
+    #[test]
+    fn registrations_describe_the_requested_instance() {
+        let request = registration("greeter-1", 8080, "2.0");
+        let registration = request.registration();
+        assert_eq!(registration.instance_id().as_str(), "greeter-1");
+        assert_eq!(registration.selector(), &selector("2.0"));
+        assert_eq!(
+            registration.endpoint(),
+            &"http://127.0.0.1:8080".parse().unwrap()
+        );
+    }
+}
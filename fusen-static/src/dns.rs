@@ -0,0 +1,562 @@
+use crate::{
+    DEFAULT_REFRESH_INTERVAL,
+    refresh::{self, Fetch},
+};
+use fusen_contract::{
+    EndpointCapabilities, InstanceId, Metadata, ServiceEndpoint, ServiceInstance, ServiceSelector,
+    ServiceWeight,
+};
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, RegistryFuture, SubscriptionHandle,
+    SubscriptionRequest,
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use hickory_resolver::{ResolveError, TokioResolver};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// DNS records resolved into the instances of one service.
+#[derive(Clone, Debug)]
+pub struct DnsTarget {
+    query: DnsQuery,
+    scheme: &'static str,
+    capabilities: EndpointCapabilities,
+    metadata: Metadata,
+}
+
+#[derive(Clone, Debug)]
+enum DnsQuery {
+    Address { host: String, port: u16 },
+    Srv { name: String },
+}
+
+impl DnsTarget {
+    /// Resolves A and AAAA records of `host`; every address becomes one instance on `port`.
+    ///
+    /// This matches a Kubernetes headless service, whose name resolves to its ready pods.
+    pub fn address(host: impl Into<String>, port: u16) -> Self {
+        Self::new(DnsQuery::Address {
+            host: host.into(),
+            port,
+        })
+    }
+
+    /// Resolves SRV records of `name`, such as `_http._tcp.greeter.default.svc.cluster.local`.
+    ///
+    /// Only records with the lowest priority are used. Each target's addresses become instances on
+    /// the record's port, weighted by the record's weight (at least 1).
+    pub fn srv(name: impl Into<String>) -> Self {
+        Self::new(DnsQuery::Srv { name: name.into() })
+    }
+
+    fn new(query: DnsQuery) -> Self {
+        Self {
+            query,
+            scheme: "http",
+            capabilities: EndpointCapabilities::default(),
+            metadata: Metadata::new(),
+        }
+    }
+
+    /// Calls resolved instances over HTTPS instead of cleartext HTTP.
+    pub const fn https(mut self) -> Self {
+        self.scheme = "https";
+        self
+    }
+
+    /// Replaces the capabilities advertised by every resolved instance.
+    pub fn capabilities(mut self, value: EndpointCapabilities) -> Self {
+        self.capabilities = value;
+        self
+    }
+
+    /// Replaces the user metadata attached to every resolved instance.
+    pub fn metadata(mut self, value: Metadata) -> Self {
+        self.metadata = value;
+        self
+    }
+}
+
+/// Discovers service instances by resolving DNS records periodically.
+///
+/// Each service selector is mapped to one [`DnsTarget`] by its `service[/group][@version]`
+/// identity; subscribing to an unmapped service fails. A name that does not exist or has no
+/// records resolves to an empty `Ready` snapshot. Any other resolver failure keeps the previous
+/// instances and marks the directory `Stale` until a later resolution succeeds. DNS TTLs are not
+/// used: records are resolved again after every refresh interval.
+///
+/// Registrations are accepted without side effects; publishing DNS records is left to the
+/// platform, for example a Kubernetes headless service.
+#[derive(Clone)]
+pub struct DnsRegistry {
+    targets: Arc<BTreeMap<String, Arc<DnsTarget>>>,
+    refresh_interval: Duration,
+    lookup: Arc<dyn Lookup>,
+}
+
+impl std::fmt::Debug for DnsRegistry {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("DnsRegistry")
+            .field("targets", &self.targets)
+            .field("refresh_interval", &self.refresh_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for [`DnsRegistry`].
+#[derive(Clone, Debug)]
+pub struct DnsRegistryBuilder {
+    targets: Vec<(ServiceSelector, DnsTarget)>,
+    refresh_interval: Duration,
+}
+
+impl DnsRegistry {
+    /// Starts a registry without service targets.
+    pub fn builder() -> DnsRegistryBuilder {
+        DnsRegistryBuilder {
+            targets: Vec::new(),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// Returns the target resolved for one service.
+    pub fn target(&self, selector: &ServiceSelector) -> Option<&DnsTarget> {
+        self.targets
+            .get(selector.identity())
+            .map(|target| &**target)
+    }
+
+    /// Returns the interval between two resolutions of a target.
+    pub const fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+}
+
+impl DnsRegistryBuilder {
+    /// Resolves `target` for subscriptions to `selector`.
+    pub fn target(mut self, selector: ServiceSelector, target: DnsTarget) -> Self {
+        self.targets.push((selector, target));
+        self
+    }
+
+    /// Replaces the interval between two resolutions of a target.
+    pub const fn refresh_interval(mut self, value: Duration) -> Self {
+        self.refresh_interval = value;
+        self
+    }
+
+    /// Validates targets and reads the system resolver configuration.
+    pub fn build(self) -> Result<DnsRegistry, RegistryError> {
+        let resolver = TokioResolver::builder_tokio()
+            .map_err(|error| {
+                RegistryError::new(
+                    RegistryOperation::Build,
+                    RegistryErrorKind::Unavailable,
+                    error,
+                )
+            })?
+            .build();
+        self.build_with(Arc::new(SystemLookup { resolver }))
+    }
+
+    fn build_with(self, lookup: Arc<dyn Lookup>) -> Result<DnsRegistry, RegistryError> {
+        if self.refresh_interval.is_zero() {
+            return Err(invalid(
+                RegistryOperation::Build,
+                "DNS refresh interval must be positive",
+            ));
+        }
+        let mut targets = BTreeMap::new();
+        for (selector, target) in self.targets {
+            let valid = match &target.query {
+                DnsQuery::Address { host, port } => !host.trim().is_empty() && *port != 0,
+                DnsQuery::Srv { name } => !name.trim().is_empty(),
+            };
+            if !valid {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "DNS targets require a non-empty name and a non-zero port",
+                ));
+            }
+            if selector
+                .clone()
+                .with_metadata(target.metadata.clone())
+                .is_err()
+            {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "DNS target metadata keys must be valid and must not use the fusen. prefix",
+                ));
+            }
+            if targets
+                .insert(selector.identity().to_owned(), Arc::new(target))
+                .is_some()
+            {
+                return Err(invalid(
+                    RegistryOperation::Build,
+                    "each service may have only one DNS target",
+                ));
+            }
+        }
+        Ok(DnsRegistry {
+            targets: Arc::new(targets),
+            refresh_interval: self.refresh_interval,
+            lookup,
+        })
+    }
+}
+
+impl Registry for DnsRegistry {
+    fn prepare_registration(
+        &self,
+        _request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        Ok(provider::registration(async { Ok(()) }, || async {
+            Ok(())
+        }))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let target = self
+            .targets
+            .get(request.selector().identity())
+            .cloned()
+            .ok_or_else(|| {
+                invalid(
+                    RegistryOperation::PrepareSubscription,
+                    "service has no DNS target",
+                )
+            })?;
+        let lookup = self.lookup.clone();
+        let fetch: Fetch = Arc::new(move || {
+            Box::pin(resolve(lookup.clone(), target.clone())) as RegistryFuture<_>
+        });
+        Ok(refresh::subscription(fetch, self.refresh_interval, "dns"))
+    }
+}
+
+async fn resolve(
+    lookup: Arc<dyn Lookup>,
+    target: Arc<DnsTarget>,
+) -> Result<Vec<ServiceInstance>, RegistryError> {
+    let mut addresses = Vec::new();
+    match &target.query {
+        DnsQuery::Address { host, port } => {
+            for ip in lookup.ips(host).await? {
+                addresses.push((ip, *port, 1));
+            }
+        }
+        DnsQuery::Srv { name } => {
+            let records = lookup.srv(name).await?;
+            let priority = records.iter().map(|record| record.priority).min();
+            for record in records
+                .iter()
+                .filter(|record| Some(record.priority) == priority && record.port != 0)
+            {
+                for ip in lookup.ips(&record.target).await? {
+                    addresses.push((ip, record.port, record.weight.max(1)));
+                }
+            }
+        }
+    }
+    let mut seen = BTreeSet::new();
+    let mut instances = Vec::new();
+    for (ip, port, weight) in addresses {
+        if !seen.insert((ip, port)) {
+            continue;
+        }
+        instances.push(instance(&target, ip, port, weight)?);
+    }
+    Ok(instances)
+}
+
+fn instance(
+    target: &DnsTarget,
+    ip: IpAddr,
+    port: u16,
+    weight: u16,
+) -> Result<ServiceInstance, RegistryError> {
+    let host = match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{ip}]"),
+    };
+    let endpoint = format!("{}://{host}:{port}", target.scheme)
+        .parse::<ServiceEndpoint>()
+        .map_err(|error| resolved_error(RegistryErrorKind::Internal, error))?;
+    let instance_id = InstanceId::new(format!("dns:{ip}:{port}"))
+        .map_err(|error| resolved_error(RegistryErrorKind::Internal, error))?;
+    let weight = ServiceWeight::new(f64::from(weight))
+        .map_err(|error| resolved_error(RegistryErrorKind::Internal, error))?;
+    ServiceInstance::new(instance_id, endpoint, target.capabilities.clone(), weight)
+        .with_metadata(target.metadata.clone())
+        .map_err(|error| resolved_error(RegistryErrorKind::InvalidResource, error))
+}
+
+fn resolved_error<E>(kind: RegistryErrorKind, error: E) -> RegistryError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    RegistryError::new(RegistryOperation::ActivateSubscription, kind, error)
+}
+
+fn invalid(operation: RegistryOperation, message: &'static str) -> RegistryError {
+    RegistryError::message(operation, RegistryErrorKind::InvalidResource, message)
+}
+
+/// One SRV record reduced to the fields used for instance selection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SrvRecord {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: String,
+}
+
+/// Resolver operations, replaced by fakes in tests.
+trait Lookup: Send + Sync + 'static {
+    /// Resolves A and AAAA records; a missing name yields no addresses.
+    fn ips(&self, name: &str) -> RegistryFuture<Vec<IpAddr>>;
+
+    /// Resolves SRV records; a missing name yields no records.
+    fn srv(&self, name: &str) -> RegistryFuture<Vec<SrvRecord>>;
+}
+
+struct SystemLookup {
+    resolver: TokioResolver,
+}
+
+impl Lookup for SystemLookup {
+    fn ips(&self, name: &str) -> RegistryFuture<Vec<IpAddr>> {
+        let resolver = self.resolver.clone();
+        let name = name.to_owned();
+        Box::pin(async move {
+            match resolver.lookup_ip(name).await {
+                Ok(lookup) => Ok(lookup.iter().collect()),
+                Err(error) if is_missing(&error) => Ok(Vec::new()),
+                Err(error) => Err(resolved_error(RegistryErrorKind::Unavailable, error)),
+            }
+        })
+    }
+
+    fn srv(&self, name: &str) -> RegistryFuture<Vec<SrvRecord>> {
+        let resolver = self.resolver.clone();
+        let name = name.to_owned();
+        Box::pin(async move {
+            match resolver.srv_lookup(name).await {
+                Ok(lookup) => Ok(lookup
+                    .iter()
+                    .filter(|record| !record.target().is_root())
+                    .map(|record| SrvRecord {
+                        priority: record.priority(),
+                        weight: record.weight(),
+                        port: record.port(),
+                        target: record.target().to_utf8(),
+                    })
+                    .collect()),
+                Err(error) if is_missing(&error) => Ok(Vec::new()),
+                Err(error) => Err(resolved_error(RegistryErrorKind::Unavailable, error)),
+            }
+        })
+    }
+}
+
+fn is_missing(error: &ResolveError) -> bool {
+    error.is_nx_domain() || error.is_no_records_found()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusen_register::directory::DirectoryState;
+    use std::sync::Mutex;
+
+    /// Answers from a mutable table; names without an entry fail like an unreachable resolver.
+    #[derive(Default)]
+    struct FakeLookup {
+        ips: Mutex<BTreeMap<String, Vec<IpAddr>>>,
+        srv: Mutex<BTreeMap<String, Vec<SrvRecord>>>,
+    }
+
+    impl Lookup for FakeLookup {
+        fn ips(&self, name: &str) -> RegistryFuture<Vec<IpAddr>> {
+            let answer = self.ips.lock().unwrap().get(name).cloned();
+            Box::pin(async move {
+                answer.ok_or_else(|| {
+                    RegistryError::message(
+                        RegistryOperation::ActivateSubscription,
+                        RegistryErrorKind::Unavailable,
+                        "resolver unreachable",
+                    )
+                })
+            })
+        }
+
+        fn srv(&self, name: &str) -> RegistryFuture<Vec<SrvRecord>> {
+            let answer = self.srv.lock().unwrap().get(name).cloned();
+            Box::pin(async move {
+                answer.ok_or_else(|| {
+                    RegistryError::message(
+                        RegistryOperation::ActivateSubscription,
+                        RegistryErrorKind::Unavailable,
+                        "resolver unreachable",
+                    )
+                })
+            })
+        }
+    }
+
+    fn selector() -> ServiceSelector {
+        ServiceSelector::new("greeter", None, None).unwrap()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn srv(priority: u16, weight: u16, port: u16, target: &str) -> SrvRecord {
+        SrvRecord {
+            priority,
+            weight,
+            port,
+            target: target.to_owned(),
+        }
+    }
+
+    fn ids(instances: &[ServiceInstance]) -> Vec<&str> {
+        instances
+            .iter()
+            .map(|instance| instance.instance_id().as_str())
+            .collect()
+    }
+
+    async fn wait_for(
+        directory: &mut fusen_register::directory::Directory,
+        state: DirectoryState,
+    ) -> fusen_register::directory::DirectorySnapshot {
+        loop {
+            let snapshot = directory.changed().await.unwrap();
+            if snapshot.state() == state {
+                return snapshot;
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn address_targets_follow_record_changes_and_resolver_failures() {
+        let lookup = Arc::new(FakeLookup::default());
+        lookup
+            .ips
+            .lock()
+            .unwrap()
+            .insert("greeter".to_owned(), vec![ip("10.0.0.2"), ip("fd00::1")]);
+        let registry = DnsRegistry::builder()
+            .target(
+                selector(),
+                DnsTarget::address("greeter", 8080)
+                    .metadata(Metadata::from([("zone".to_owned(), "zone-a".to_owned())])),
+            )
+            .refresh_interval(Duration::from_secs(1))
+            .build_with(lookup.clone())
+            .unwrap();
+        let handle = registry
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let mut directory = handle.activate().await.unwrap();
+
+        let snapshot = directory.snapshot();
+        assert_eq!(snapshot.state(), DirectoryState::Ready);
+        assert_eq!(ids(&snapshot), ["dns:10.0.0.2:8080", "dns:fd00::1:8080"]);
+        assert_eq!(snapshot[1].endpoint().as_str(), "http://[fd00::1]:8080/");
+        assert_eq!(snapshot[0].metadata()["zone"], "zone-a");
+
+        lookup.ips.lock().unwrap().clear();
+        let snapshot = wait_for(&mut directory, DirectoryState::Stale).await;
+        assert_eq!(snapshot.len(), 2);
+
+        lookup
+            .ips
+            .lock()
+            .unwrap()
+            .insert("greeter".to_owned(), vec![ip("10.0.0.3")]);
+        let snapshot = wait_for(&mut directory, DirectoryState::Ready).await;
+        assert_eq!(ids(&snapshot), ["dns:10.0.0.3:8080"]);
+
+        handle.close().await.unwrap();
+        assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+    }
+
+    #[tokio::test]
+    async fn srv_targets_use_the_lowest_priority_and_record_weights() {
+        let lookup = Arc::new(FakeLookup::default());
+        lookup.srv.lock().unwrap().insert(
+            "_http._tcp.greeter".to_owned(),
+            vec![
+                srv(10, 0, 8080, "a.greeter."),
+                srv(10, 30, 8081, "b.greeter."),
+                srv(20, 50, 8080, "backup.greeter."),
+            ],
+        );
+        lookup.ips.lock().unwrap().extend([
+            ("a.greeter.".to_owned(), vec![ip("10.0.0.1")]),
+            ("b.greeter.".to_owned(), vec![ip("10.0.0.2")]),
+        ]);
+        let registry = DnsRegistry::builder()
+            .target(selector(), DnsTarget::srv("_http._tcp.greeter").https())
+            .build_with(lookup)
+            .unwrap();
+        let handle = registry
+            .prepare_subscription(SubscriptionRequest::new(selector()))
+            .unwrap();
+        let snapshot = handle.activate().await.unwrap().snapshot();
+
+        assert_eq!(ids(&snapshot), ["dns:10.0.0.1:8080", "dns:10.0.0.2:8081"]);
+        assert_eq!(snapshot[0].endpoint().as_str(), "https://10.0.0.1:8080/");
+        assert_eq!(snapshot[0].weight().get(), 1.0);
+        assert_eq!(snapshot[1].weight().get(), 30.0);
+        handle.close().await.unwrap();
+    }
+
+    #[test]
+    fn builder_rejects_unmapped_services_and_invalid_targets() {
+        let lookup = Arc::new(FakeLookup::default());
+        let registry = DnsRegistry::builder()
+            .target(selector(), DnsTarget::address("greeter", 80))
+            .build_with(lookup.clone())
+            .unwrap();
+        let other = ServiceSelector::new("greeter", None, Some("2".to_owned())).unwrap();
+        let error = registry
+            .prepare_subscription(SubscriptionRequest::new(other))
+            .err()
+            .unwrap();
+        assert_eq!(error.operation(), RegistryOperation::PrepareSubscription);
+        assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+
+        for builder in [
+            DnsRegistry::builder().target(selector(), DnsTarget::address("greeter", 0)),
+            DnsRegistry::builder().target(selector(), DnsTarget::srv(" ")),
+            DnsRegistry::builder()
+                .target(selector(), DnsTarget::address("a", 80))
+                .target(selector(), DnsTarget::address("b", 80)),
+            DnsRegistry::builder().target(
+                selector(),
+                DnsTarget::address("a", 80).metadata(Metadata::from([(
+                    "fusen.scheme".to_owned(),
+                    "h".to_owned(),
+                )])),
+            ),
+            DnsRegistry::builder().refresh_interval(Duration::ZERO),
+        ] {
+            let error = builder.build_with(lookup.clone()).unwrap_err();
+            assert_eq!(error.operation(), RegistryOperation::Build);
+        }
+    }
+}
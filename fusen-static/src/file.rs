@@ -0,0 +1,359 @@
+use crate::{
+    DEFAULT_REFRESH_INTERVAL,
+    refresh::{self, Fetch},
+};
+use fusen_config::{ConfigError, ConfigErrorKind, ConfigFormat};
+use fusen_contract::{
+    ContractError, EndpointCapabilities, HttpBindingId, HttpVersionSet, InstanceId, Metadata,
+    ServiceEndpoint, ServiceInstance, ServiceSelector, ServiceWeight,
+};
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Discovers service instances from a TOML or YAML file that is re-read periodically.
+///
+/// The file lists instances per service:
+///
+/// ```toml
+/// [[services]]
+/// service_id = "com.example.Greeter"
+/// version = "1.0.0"
+///
+/// [[services.instances]]
+/// endpoint = "http://10.0.0.7:8080"
+/// weight = 2.0
+/// metadata = { zone = "zone-a" }
+/// ```
+///
+/// Instances may also set `instance_id` (default `file:{host}:{port}`), `http_versions`
+/// (`"1.1"`, `"2"`, `"3"`), `bindings`, and `invocation_controls`. The whole file is validated
+/// on every read: a file that cannot be read or parsed keeps the previous instances and marks the
+/// directory `Stale` until a later read succeeds. A service absent from the file resolves to an
+/// empty `Ready` snapshot.
+///
+/// Registrations are accepted without side effects, so a server can share this registry with its
+/// clients; the file remains the only source of instances.
+#[derive(Clone, Debug)]
+pub struct FileRegistry {
+    path: Arc<Path>,
+    refresh_interval: Duration,
+}
+
+/// Builder for [`FileRegistry`].
+#[derive(Clone, Debug)]
+pub struct FileRegistryBuilder {
+    path: PathBuf,
+    refresh_interval: Duration,
+}
+
+impl FileRegistry {
+    /// Starts a registry reading `path`; the extension selects TOML or YAML.
+    pub fn builder(path: impl Into<PathBuf>) -> FileRegistryBuilder {
+        FileRegistryBuilder {
+            path: path.into(),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// Returns the watched instance file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the interval between two reads of the file.
+    pub const fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+}
+
+impl FileRegistryBuilder {
+    /// Replaces the interval between two reads of the file.
+    pub const fn refresh_interval(mut self, value: Duration) -> Self {
+        self.refresh_interval = value;
+        self
+    }
+
+    /// Validates settings without reading the file.
+    pub fn build(self) -> Result<FileRegistry, RegistryError> {
+        if self.refresh_interval.is_zero() {
+            return Err(invalid(
+                RegistryOperation::Build,
+                "instance file refresh interval must be positive",
+            ));
+        }
+        let format = self
+            .path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(ConfigFormat::from_name)
+            .ok_or_else(|| {
+                invalid(
+                    RegistryOperation::Build,
+                    "instance file extension must be toml, yaml, or yml",
+                )
+            })?;
+        if format == ConfigFormat::Yaml && !cfg!(feature = "yaml") {
+            return Err(invalid(
+                RegistryOperation::Build,
+                "YAML instance files require the fusen-static yaml feature",
+            ));
+        }
+        Ok(FileRegistry {
+            path: Arc::from(self.path),
+            refresh_interval: self.refresh_interval,
+        })
+    }
+}
+
+impl Registry for FileRegistry {
+    fn prepare_registration(
+        &self,
+        _request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        Ok(provider::registration(async { Ok(()) }, || async {
+            Ok(())
+        }))
+    }
+
+    fn prepare_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        let path = self.path.clone();
+        let identity: Arc<str> = Arc::from(request.selector().identity());
+        let fetch: Fetch = Arc::new(move || {
+            let path = path.clone();
+            let identity = identity.clone();
+            Box::pin(async move {
+                let file =
+                    tokio::task::spawn_blocking(move || fusen_config::load::<InstanceFile>(&*path))
+                        .await
+                        .map_err(|error| {
+                            RegistryError::new(
+                                RegistryOperation::ActivateSubscription,
+                                RegistryErrorKind::Internal,
+                                error,
+                            )
+                        })?
+                        .map_err(load_error)?;
+                Ok(file.services.get(&*identity).cloned().unwrap_or_default())
+            })
+        });
+        Ok(refresh::subscription(fetch, self.refresh_interval, "file"))
+    }
+}
+
+fn load_error(error: ConfigError) -> RegistryError {
+    let kind = match error.kind() {
+        ConfigErrorKind::Io => RegistryErrorKind::Unavailable,
+        _ => RegistryErrorKind::InvalidResource,
+    };
+    RegistryError::new(RegistryOperation::ActivateSubscription, kind, error)
+}
+
+fn invalid(operation: RegistryOperation, message: &'static str) -> RegistryError {
+    RegistryError::message(operation, RegistryErrorKind::InvalidResource, message)
+}
+
+/// One validated instance file, keyed by selector identity.
+#[derive(Deserialize)]
+#[serde(try_from = "InstanceFileDocument")]
+struct InstanceFile {
+    services: BTreeMap<String, Vec<ServiceInstance>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceFileDocument {
+    #[serde(default)]
+    services: Vec<ServiceDocument>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServiceDocument {
+    service_id: String,
+    group: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    instances: Vec<InstanceDocument>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceDocument {
+    endpoint: String,
+    instance_id: Option<String>,
+    weight: Option<f64>,
+    http_versions: Option<Vec<String>>,
+    bindings: Option<Vec<String>>,
+    #[serde(default)]
+    invocation_controls: bool,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+impl TryFrom<InstanceFileDocument> for InstanceFile {
+    type Error = &'static str;
+
+    fn try_from(document: InstanceFileDocument) -> Result<Self, Self::Error> {
+        let mut services = BTreeMap::new();
+        for service in document.services {
+            let selector = ServiceSelector::new(service.service_id, service.group, service.version)
+                .map_err(|_| "services contain an invalid service_id, group, or version")?;
+            let mut ids = BTreeSet::new();
+            let instances = service
+                .instances
+                .into_iter()
+                .map(|instance| {
+                    let instance = instance.into_instance()?;
+                    if ids.insert(instance.instance_id().clone()) {
+                        Ok(instance)
+                    } else {
+                        Err("instance IDs must be unique within a service")
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if services
+                .insert(selector.identity().to_owned(), instances)
+                .is_some()
+            {
+                return Err("each service may be listed only once");
+            }
+        }
+        Ok(Self { services })
+    }
+}
+
+impl InstanceDocument {
+    fn into_instance(self) -> Result<ServiceInstance, &'static str> {
+        let endpoint = self
+            .endpoint
+            .parse::<ServiceEndpoint>()
+            .map_err(|_| "instance endpoint must be an absolute HTTP or HTTPS URL")?;
+        let instance_id = match self.instance_id {
+            Some(value) => InstanceId::new(value),
+            None => InstanceId::new(default_instance_id(&endpoint)),
+        }
+        .map_err(|_| "instance_id is invalid or cannot be derived from the endpoint host")?;
+        let weight = ServiceWeight::new(self.weight.unwrap_or(1.0))
+            .map_err(|_| "instance weight must be finite and positive")?;
+        let http_versions = match self.http_versions {
+            Some(labels) => http_versions(&labels)?,
+            None => HttpVersionSet::default(),
+        };
+        let bindings = match self.bindings {
+            Some(values) => values
+                .into_iter()
+                .map(HttpBindingId::new)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| "instance bindings contain an invalid binding ID")?,
+            None => vec![HttpBindingId::default()],
+        };
+        let capabilities =
+            EndpointCapabilities::new(http_versions, bindings, self.invocation_controls)
+                .map_err(|_| "instance bindings must not be empty")?;
+        ServiceInstance::new(instance_id, endpoint, capabilities, weight)
+            .with_metadata(self.metadata)
+            .map_err(|_| "instance metadata keys must be valid and must not use the fusen. prefix")
+    }
+}
+
+fn default_instance_id(endpoint: &ServiceEndpoint) -> String {
+    let url = endpoint.as_url();
+    let host = url.host_str().unwrap_or_default();
+    let host = host
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .unwrap_or(host);
+    let port = url.port_or_known_default().unwrap_or_default();
+    format!("file:{host}:{port}")
+}
+
+fn http_versions(labels: &[String]) -> Result<HttpVersionSet, &'static str> {
+    HttpVersionSet::from_labels(labels.iter().map(String::as_str)).map_err(|error| match error {
+        ContractError::EmptyHttpVersionSet => {
+            "instance http_versions must include a version supported by this build"
+        }
+        _ => "instance http_versions accept distinct \"1.1\", \"2\", and \"3\"",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Result<InstanceFile, fusen_config::ConfigError> {
+        fusen_config::parse_toml(content)
+    }
+
+    #[test]
+    fn instances_are_keyed_by_selector_identity_with_defaults() {
+        let file = parse(
+            "[[services]]\nservice_id = 'greeter'\nversion = '1.0'\n\
+             [[services.instances]]\nendpoint = 'http://[::1]:8080'\n\
+             [[services.instances]]\nendpoint = 'http://10.0.0.7:8081'\ninstance_id = 'b'\n\
+             weight = 2.5\nhttp_versions = ['1.1', '2']\nmetadata = { zone = 'zone-a' }\n",
+        )
+        .unwrap();
+        let instances = &file.services["greeter@1.0"];
+
+        assert_eq!(instances[0].instance_id().as_str(), "file:::1:8080");
+        assert_eq!(
+            instances[0].capabilities(),
+            &EndpointCapabilities::default()
+        );
+        assert_eq!(instances[1].instance_id().as_str(), "b");
+        assert_eq!(instances[1].weight().get(), 2.5);
+        assert_eq!(
+            instances[1].capabilities().http_versions(),
+            HttpVersionSet::ALL
+        );
+        assert_eq!(instances[1].metadata()["zone"], "zone-a");
+    }
+
+    #[test]
+    fn one_invalid_entry_rejects_the_whole_file() {
+        for content in [
+            "[[services]]\nservice_id = 'a'\n[[services.instances]]\nendpoint = 'ftp://host:1'\n",
+            "[[services]]\nservice_id = 'a'\n[[services.instances]]\nendpoint = 'http://h:1'\n\
+             weight = 0.0\n",
+            "[[services]]\nservice_id = 'a'\n[[services.instances]]\nendpoint = 'http://h:1'\n\
+             [[services.instances]]\nendpoint = 'http://h:1'\n",
+            "[[services]]\nservice_id = 'a'\n[[services]]\nservice_id = 'a'\n",
+            "[[services]]\nservice_id = 'a'\n[[services.instances]]\nendpoint = 'http://h:1'\n\
+             http_versions = ['1.0']\n",
+            "[[services]]\nservice_id = 'a'\n[[services.instances]]\nendpoint = 'http://h:1'\n\
+             metadata = { 'fusen.scheme' = 'https' }\n",
+            "[[services]]\nservice_id = 'a'\nzone = 'x'\n",
+        ] {
+            assert!(parse(content).is_err(), "{content}");
+        }
+    }
+
+    #[test]
+    fn builder_rejects_unusable_settings() {
+        for builder in [
+            FileRegistry::builder("instances.toml").refresh_interval(Duration::ZERO),
+            FileRegistry::builder("instances.json"),
+        ] {
+            let error = builder.build().unwrap_err();
+            assert_eq!(error.operation(), RegistryOperation::Build);
+            assert_eq!(error.kind(), RegistryErrorKind::InvalidResource);
+        }
+        assert_eq!(
+            FileRegistry::builder("instances.yaml").build().is_ok(),
+            cfg!(feature = "yaml")
+        );
+    }
+}
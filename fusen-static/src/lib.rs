@@ -0,0 +1,19 @@
+#![warn(missing_docs)]
+//! Service discovery from static instance files and DNS records for fusen-rs.
+//!
+//! [`FileRegistry`] reads a watched TOML or YAML instance list. With the `dns` feature,
+//! `DnsRegistry` resolves A/AAAA or SRV records, which covers Kubernetes headless services.
+//! Both providers poll their source and publish `Ready` snapshots, or `Stale` while the source is
+//! unreadable.
+
+#[cfg(feature = "dns")]
+mod dns;
+mod file;
+mod refresh;
+
+#[cfg(feature = "dns")]
+pub use dns::{DnsRegistry, DnsRegistryBuilder, DnsTarget};
+pub use file::{FileRegistry, FileRegistryBuilder};
+
+/// Default interval between two refreshes of an instance file or DNS target.
+pub const DEFAULT_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
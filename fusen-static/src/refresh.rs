@@ -0,0 +1,112 @@
+use fusen_contract::ServiceInstance;
+use fusen_register::{
+    RegistryFuture, SubscriptionHandle,
+    directory::{DirectoryPublisher, DirectoryState, directory},
+    provider,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
+
+/// Resolves the complete instance list for one subscription.
+pub(crate) type Fetch = Arc<dyn Fn() -> RegistryFuture<Vec<ServiceInstance>> + Send + Sync>;
+
+/// Creates a subscription that publishes the first fetch and then polls every `interval`.
+///
+/// A failed refresh keeps the previous instances and marks the directory `Stale`; the next
+/// successful refresh publishes `Ready` again. Closing aborts the poller and waits for it, so the
+/// directory is `Closed` before close returns.
+pub(crate) fn subscription(
+    fetch: Fetch,
+    interval: Duration,
+    source: &'static str,
+) -> SubscriptionHandle {
+    let (publisher, directory) = directory();
+    let task = Arc::new(Mutex::new(None::<JoinHandle<()>>));
+    let close_task = task.clone();
+    provider::subscription(
+        directory,
+        async move {
+            let mut instances = fetch().await?;
+            sort(&mut instances);
+            publisher.publish_ready(instances.clone())?;
+            let handle = tokio::spawn(refresh(fetch, publisher, instances, interval, source));
+            *task.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(handle);
+            Ok(())
+        },
+        move || async move {
+            let handle = close_task
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .take();
+            if let Some(handle) = handle {
+                handle.abort();
+                let _ = handle.await;
+            }
+            Ok(())
+        },
+    )
+}
+
+async fn refresh(
+    fetch: Fetch,
+    publisher: DirectoryPublisher,
+    mut current: Vec<ServiceInstance>,
+    interval: Duration,
+    source: &'static str,
+) {
+    let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut stale = false;
+    loop {
+        ticker.tick().await;
+        if publisher.is_closed() {
+            return;
+        }
+        match fetch().await {
+            Ok(mut instances) => {
+                sort(&mut instances);
+                if stale || !same_instances(&current, &instances) {
+                    if publisher.publish_ready(instances.clone()).is_err() {
+                        return;
+                    }
+                    current = instances;
+                    stale = false;
+                }
+            }
+            Err(error) => {
+                if !stale {
+                    tracing::warn!(
+                        error = %error,
+                        source,
+                        "static discovery refresh failed; keeping the previous instances"
+                    );
+                    if publisher.publish_state(DirectoryState::Stale).is_err() {
+                        return;
+                    }
+                    stale = true;
+                }
+            }
+        }
+    }
+}
+
+fn sort(instances: &mut [ServiceInstance]) {
+    instances.sort_by(|left, right| left.instance_id().cmp(right.instance_id()));
+}
+
+fn same_instances(left: &[ServiceInstance], right: &[ServiceInstance]) -> bool {
+    left.len() == right.len()
+        && left.iter().zip(right).all(|(left, right)| {
+            left.instance_id() == right.instance_id()
+                && left.endpoint() == right.endpoint()
+                && left.capabilities() == right.capabilities()
+                && left.weight() == right.weight()
+                && left.metadata() == right.metadata()
+        })
+}
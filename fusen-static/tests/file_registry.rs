@@ -0,0 +1,132 @@
+//! File-backed discovery through the public `Registry` contract.
+
+use fusen_contract::ServiceSelector;
+use fusen_register::{
+    Registry, SubscriptionRequest,
+    directory::{DirectorySnapshot, DirectoryState},
+    testing::wait_for,
+};
+use fusen_static::FileRegistry;
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct InstanceFile(PathBuf);
+
+impl InstanceFile {
+    fn new(content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "fusen-static-{}-{}.toml",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, content).unwrap();
+        Self(path)
+    }
+
+    fn replace(&self, content: &str) {
+        let staged = self.0.with_extension("staged");
+        std::fs::write(&staged, content).unwrap();
+        std::fs::rename(staged, &self.0).unwrap();
+    }
+}
+
+impl Drop for InstanceFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn instances(ports: &[u16]) -> String {
+    let mut content = "[[services]]\nservice_id = 'greeter'\nversion = '1.0'\n".to_owned();
+    for port in ports {
+        content.push_str(&format!(
+            "[[services.instances]]\nendpoint = 'http://127.0.0.1:{port}'\n"
+        ));
+    }
+    content
+}
+
+fn selector() -> ServiceSelector {
+    ServiceSelector::new("greeter", None, Some("1.0".to_owned())).unwrap()
+}
+
+fn endpoints(snapshot: &DirectorySnapshot) -> Vec<&str> {
+    snapshot
+        .iter()
+        .map(|instance| instance.endpoint().as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn file_changes_publish_ready_and_unreadable_revisions_publish_stale() {
+    let file = InstanceFile::new(&instances(&[8080]));
+    let registry = FileRegistry::builder(&file.0)
+        .refresh_interval(Duration::from_millis(20))
+        .build()
+        .unwrap();
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+    let mut directory = handle.activate().await.unwrap();
+    let snapshot = directory.snapshot();
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert_eq!(endpoints(&snapshot), ["http://127.0.0.1:8080/"]);
+
+    file.replace(&instances(&[8080, 8081]));
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.len() == 2
+    })
+    .await;
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+
+    // One invalid entry rejects the revision; routing keeps the previous instances.
+    file.replace(&format!("{}weight = -1.0\n", instances(&[8082])));
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Stale
+    })
+    .await;
+    assert_eq!(snapshot.len(), 2);
+
+    file.replace(&instances(&[8082]));
+    let snapshot = wait_for(&mut directory, PUBLISH_TIMEOUT, |snapshot| {
+        snapshot.state() == DirectoryState::Ready
+    })
+    .await;
+    assert_eq!(endpoints(&snapshot), ["http://127.0.0.1:8082/"]);
+
+    handle.close().await.unwrap();
+    assert_eq!(directory.snapshot().state(), DirectoryState::Closed);
+}
+
+#[tokio::test]
+async fn services_missing_from_the_file_are_ready_and_empty() {
+    let file = InstanceFile::new(&instances(&[8080]));
+    let registry = FileRegistry::builder(&file.0).build().unwrap();
+    let other = ServiceSelector::new("greeter", None, None).unwrap();
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(other))
+        .unwrap();
+    let snapshot = handle.activate().await.unwrap().snapshot();
+
+    assert_eq!(snapshot.state(), DirectoryState::Ready);
+    assert!(snapshot.is_empty());
+    handle.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unreadable_file_fails_activation() {
+    let file = InstanceFile::new("services = 'not-a-list'\n");
+    let registry = FileRegistry::builder(&file.0).build().unwrap();
+    let handle = registry
+        .prepare_subscription(SubscriptionRequest::new(selector()))
+        .unwrap();
+
+    assert!(handle.activate().await.is_err());
+}
@@ -0,0 +1,285 @@
+//! Loopback comparison of `http-json-v1` and `http-msgpack-v1` for the same structured calls.
+//!
+//! Both bindings keep arguments and results as JSON inside the runtime, so this reports what the
+//! MessagePack transcoding adds on top of the JSON work rather than a saving against it.
+
+use fusen_rs::{
+    ClientConfig, ClientRuntime, Error, HTTP_JSON_V1, HTTP_MSGPACK_V1, Response, RetryConfig,
+    SensitiveFields, Server, ServerConfig,
+    contract::{EndpointCapabilities, HttpBindingId, HttpVersionPolicy, HttpVersionSet},
+    interface,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    error::Error as StdError,
+    hint::black_box,
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinSet;
+
+const DEFAULT_WARMUP_ITERATIONS: usize = 500;
+const DEFAULT_ITERATIONS: usize = 5_000;
+const CONCURRENCIES: [usize; 2] = [1, 64];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SensitiveFields)]
+struct Record {
+    id: u64,
+    name: String,
+    score: f64,
+    tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SensitiveFields)]
+struct Batch {
+    records: Vec<Record>,
+}
+
+#[interface(name = "msgpack-benchmark")]
+trait BatchService {
+    #[method(method = "POST", path = "/benchmark/batch")]
+    async fn echo(&self, #[param(body)] batch: Batch) -> Result<Response<Batch>, Error>;
+}
+
+struct BatchServiceImpl;
+
+impl BatchService for BatchServiceImpl {
+    async fn echo(&self, batch: Batch) -> Result<Response<Batch>, Error> {
+        Ok(Response::new(batch))
+    }
+}
+
+struct Payload {
+    label: &'static str,
+    value: Arc<Batch>,
+}
+
+fn batch(records: u64) -> Batch {
+    Batch {
+        records: (0..records)
+            .map(|id| Record {
+                id: u64::MAX - id,
+                name: format!("record-{id:06}"),
+                score: id as f64 / 7.0,
+                tags: vec!["alpha".to_owned(), "beta".to_owned(), "gamma".to_owned()],
+            })
+            .collect(),
+    }
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("benchmark runtime must build");
+    runtime
+        .block_on(run())
+        .expect("MessagePack binding benchmark must complete");
+}
+
+async fn run() -> Result<(), Box<dyn StdError>> {
+    let warmup_iterations =
+        positive_env("FUSEN_BENCH_WARMUP_ITERATIONS", DEFAULT_WARMUP_ITERATIONS)?;
+    let iterations = positive_env("FUSEN_BENCH_ITERATIONS", DEFAULT_ITERATIONS)?;
+    println!(
+        "msgpack-binding-parameters warmup_iterations={warmup_iterations} iterations={iterations}"
+    );
+
+    let bindings = [
+        HttpBindingId::new(HTTP_JSON_V1)?,
+        HttpBindingId::new(HTTP_MSGPACK_V1)?,
+    ];
+    let capabilities = EndpointCapabilities::new(HttpVersionSet::ALL, bindings.clone(), true)?;
+    let server = Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .capabilities(capabilities.clone())
+                .build()?,
+        )
+        .interface(BatchServiceServer::new(BatchServiceImpl))
+        .build()?
+        .start()
+        .await?;
+    let server_url = format!("http://{}", server.local_addr());
+
+    let payloads = [
+        Payload {
+            label: "4-records",
+            value: Arc::new(batch(4)),
+        },
+        Payload {
+            label: "512-records",
+            value: Arc::new(batch(512)),
+        },
+    ];
+    let mut failed = 0_u64;
+
+    for binding in &bindings {
+        let client_runtime = ClientRuntime::builder()
+            .config(
+                ClientConfig::builder()
+                    .retry(RetryConfig::builder().max_attempts(1).build()?)
+                    .build()?,
+            )
+            .build()?;
+        let client = BatchServiceClient::builder(&client_runtime)
+            .direct(&server_url)
+            .binding(binding.clone())
+            .http_version_policy(HttpVersionPolicy::Http1)
+            .direct_capabilities(capabilities.clone())
+            .connect()
+            .await?;
+
+        for concurrency in CONCURRENCIES {
+            for payload in &payloads {
+                execute(&client, &payload.value, warmup_iterations, concurrency).await?;
+                let started = Instant::now();
+                let mut result = execute(&client, &payload.value, iterations, concurrency).await?;
+                let duration = started.elapsed();
+                failed = failed.saturating_add(result.errors);
+                print_result(binding, concurrency, payload, duration, &mut result);
+            }
+        }
+
+        drop(client);
+        client_runtime.shutdown().await?;
+    }
+
+    server.shutdown().await?;
+    if failed != 0 {
+        return Err(
+            io::Error::other(format!("benchmark observed {failed} failed request(s)")).into(),
+        );
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct RunResult {
+    latency_ns: Vec<u64>,
+    errors: u64,
+    first_error: Option<String>,
+}
+
+async fn execute(
+    client: &BatchServiceClient,
+    payload: &Arc<Batch>,
+    iterations: usize,
+    concurrency: usize,
+) -> Result<RunResult, tokio::task::JoinError> {
+    let mut tasks = JoinSet::new();
+    for worker in 0..concurrency {
+        let worker_iterations =
+            iterations / concurrency + usize::from(worker < iterations % concurrency);
+        let client = client.clone();
+        let payload = Arc::clone(payload);
+        tasks.spawn(async move {
+            let mut result = RunResult {
+                latency_ns: Vec::with_capacity(worker_iterations),
+                ..RunResult::default()
+            };
+            for _ in 0..worker_iterations {
+                let request = payload.as_ref().clone();
+                let started = Instant::now();
+                match client.echo(request).await {
+                    Ok(response) if response.body() == payload.as_ref() => {
+                        black_box(response.body());
+                        result
+                            .latency_ns
+                            .push(u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX));
+                    }
+                    Ok(_) => {
+                        result.errors = result.errors.saturating_add(1);
+                        result.first_error.get_or_insert_with(|| {
+                            "echo response did not match request".to_owned()
+                        });
+                    }
+                    Err(error) => {
+                        result.errors = result.errors.saturating_add(1);
+                        result.first_error.get_or_insert_with(|| error.to_string());
+                    }
+                }
+            }
+            result
+        });
+    }
+
+    let mut combined = RunResult::default();
+    while let Some(result) = tasks.join_next().await {
+        let mut task = result?;
+        combined.latency_ns.append(&mut task.latency_ns);
+        combined.errors = combined.errors.saturating_add(task.errors);
+        if combined.first_error.is_none() {
+            combined.first_error = task.first_error;
+        }
+    }
+    Ok(combined)
+}
+
+fn percentile(samples: &[u64], percentile: usize) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    let rank = samples
+        .len()
+        .saturating_mul(percentile)
+        .div_ceil(100)
+        .saturating_sub(1);
+    samples[rank.min(samples.len() - 1)]
+}
+
+fn print_result(
+    binding: &HttpBindingId,
+    concurrency: usize,
+    payload: &Payload,
+    duration: Duration,
+    result: &mut RunResult,
+) {
+    result.latency_ns.sort_unstable();
+    let qps = result.latency_ns.len() as f64 / duration.as_secs_f64();
+    println!(
+        "msgpack-binding-result binding={} concurrency={concurrency} payload={} \
+         successes={} errors={} duration_ns={} qps={qps:.3} p50_ns={} p99_ns={}",
+        binding.as_str(),
+        payload.label,
+        result.latency_ns.len(),
+        result.errors,
+        duration.as_nanos(),
+        percentile(&result.latency_ns, 50),
+        percentile(&result.latency_ns, 99),
+    );
+    if let Some(error) = result.first_error.as_deref() {
+        eprintln!(
+            "msgpack binding benchmark {} {} first error: {error}",
+            binding.as_str(),
+            payload.label
+        );
+    }
+}
+
+fn positive_env(name: &str, default: usize) -> Result<usize, io::Error> {
+    let value = match env::var(name) {
+        Ok(value) => value.parse::<usize>().map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{name} must be a positive integer: {error}"),
+            )
+        })?,
+        Err(env::VarError::NotPresent) => default,
+        Err(error) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot read {name}: {error}"),
+            ));
+        }
+    };
+    if value == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{name} must be greater than zero"),
+        ));
+    }
+    Ok(value)
+}
@@ -1,16 +1,21 @@
 use super::{
-    invocation::{EndpointSource, ServiceClient, ServiceClientInner},
+    invocation::{EndpointSource, ServiceClient, ServiceClientInner, direct_instance},
     runtime::ClientRuntime,
 };
 use crate::{
-    ClientError, ClientErrorKind, InstanceRouter, Interceptor, LoadBalancer, WeightedRandom,
-    interceptor::erase_interceptor, wire::validate_json_service,
+    ClientError, ClientErrorKind, InstanceRouter, Interceptor, LoadBalancer, MethodConfig,
+    WeightedRandom, interceptor::erase_interceptor, wire::validate_json_service,
 };
 use fusen_contract::{
-    ContractError, EndpointCapabilities, HTTP_JSON_V1, HttpBindingId, HttpVersionPolicy,
-    ServiceDescriptor, ServiceEndpoint,
+    ContractError, EndpointCapabilities, HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId,
+    HttpVersionPolicy, Metadata, ServiceDescriptor, ServiceEndpoint,
+};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Arc,
+    sync::atomic::{AtomicU64, Ordering},
 };
-use std::{marker::PhantomData, sync::Arc, sync::atomic::Ordering};
 
 enum EndpointMode {
     Unset,
@@ -29,10 +34,12 @@ pub struct ClientBuilder<C> {
     binding_id: HttpBindingId,
     http_version_policy: HttpVersionPolicy,
     direct_capabilities: Option<EndpointCapabilities>,
+    direct_metadata: Metadata,
     interceptor: Vec<Arc<dyn Interceptor>>,
     attempt_interceptor: Vec<Arc<dyn Interceptor>>,
     routers: Vec<Arc<dyn InstanceRouter>>,
     load_balancer: Arc<dyn LoadBalancer>,
+    methods: HashMap<String, MethodConfig>,
     marker: PhantomData<fn() -> C>,
 }
 
@@ -52,10 +59,12 @@ impl<C> ClientBuilder<C> {
             binding_id: HttpBindingId::default(),
             http_version_policy: HttpVersionPolicy::Auto,
             direct_capabilities: None,
+            direct_metadata: Metadata::new(),
             interceptor: Vec::new(),
             attempt_interceptor: Vec::new(),
             routers: Vec::new(),
             load_balancer: Arc::new(WeightedRandom),
+            methods: HashMap::new(),
             marker: PhantomData,
         }
     }
@@ -90,6 +99,14 @@ impl<C> ClientBuilder<C> {
         self
     }
 
+    /// Attaches instance metadata, such as `zone` and `region`, to a direct endpoint.
+    ///
+    /// Instance routers see it exactly as they see metadata of a discovered instance.
+    pub fn direct_metadata(mut self, metadata: Metadata) -> Self {
+        self.direct_metadata = metadata;
+        self
+    }
+
     /// Appends interface-local logical-call interceptor.
     pub fn interceptor(mut self, interceptor: impl Interceptor) -> Self {
         self.interceptor.push(erase_interceptor(interceptor));
@@ -115,6 +132,19 @@ impl<C> ClientBuilder<C> {
         self
     }
 
+    /// Overrides deadline, retry, or interface-breaker settings for one method.
+    ///
+    /// `invocation_name` must match a method of this interface; a later call for the same method
+    /// replaces the earlier one.
+    pub fn method_config(
+        mut self,
+        invocation_name: impl Into<String>,
+        config: MethodConfig,
+    ) -> Self {
+        self.methods.insert(invocation_name.into(), config);
+        self
+    }
+
     /// Validates the interface before activating discovery or returning a ready client.
     pub async fn connect(self) -> Result<C, ClientError> {
         if self.runtime.inner.state.load(Ordering::Acquire) != super::runtime::CLIENT_RUNNING {
@@ -141,14 +171,18 @@ impl<C> ClientBuilder<C> {
                     format!("HTTP binding {} is not registered", self.binding_id),
                 )
             })?;
-        if self.binding_id.as_str() == HTTP_JSON_V1 {
+        if [HTTP_JSON_V1, HTTP_MSGPACK_V1].contains(&self.binding_id.as_str()) {
             validate_json_service(interface).map_err(|reason| {
                 ClientError::from_message(
                     ClientErrorKind::Connect,
-                    format!("invalid http-json-v1 interface: {reason}"),
+                    format!("invalid {} interface: {reason}", self.binding_id),
                 )
             })?;
         }
+        let methods =
+            self.runtime
+                .inner
+                .method_policies(interface, &self.binding_id, &self.methods)?;
         let source = match self.endpoint {
             EndpointMode::Direct(endpoint) => {
                 if self
@@ -164,15 +198,30 @@ impl<C> ClientBuilder<C> {
                         ),
                     ));
                 }
+                let endpoint = endpoint.map_err(|error| {
+                    ClientError::with_source(
+                        ClientErrorKind::Connect,
+                        "invalid direct service endpoint",
+                        error,
+                    )
+                })?;
+                direct_instance(
+                    &endpoint,
+                    self.direct_capabilities.as_ref(),
+                    &self.binding_id,
+                    &self.direct_metadata,
+                )
+                .map_err(|error| {
+                    ClientError::with_source(
+                        ClientErrorKind::Connect,
+                        "invalid direct endpoint metadata",
+                        error,
+                    )
+                })?;
                 EndpointSource::Direct {
-                    endpoint: endpoint.map_err(|error| {
-                        ClientError::with_source(
-                            ClientErrorKind::Connect,
-                            "invalid direct service endpoint",
-                            error,
-                        )
-                    })?,
+                    endpoint,
                     capabilities: self.direct_capabilities,
+                    metadata: self.direct_metadata,
                 }
             }
             EndpointMode::Discovery => {
@@ -213,6 +262,8 @@ impl<C> ClientBuilder<C> {
                 attempt_interceptor: Arc::from(attempt_interceptor),
                 routers: Arc::from(self.routers),
                 load_balancer: self.load_balancer,
+                discovered_revision: AtomicU64::new(0),
+                methods,
             }),
         };
         Ok((self.create)(client))
@@ -1,8 +1,19 @@
+#[cfg(feature = "compression")]
+use crate::ContentCoding;
+#[cfg(feature = "hot-tls")]
+use crate::TlsCertificate;
 use crate::{
     ConfigValidationError, ConfigValidationErrorKind,
     resilience::breaker::DEFAULT_ENDPOINT_IDLE_EVICTION,
 };
-use std::time::Duration;
+#[cfg(feature = "hot-tls")]
+use fusen_config::HotConfig;
+use fusen_contract::{MethodClientDefaults, ServiceSelector};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 const MIB: usize = 1024 * 1024;
 
@@ -63,6 +74,106 @@ impl QueueConfigBuilder {
     }
 }
 
+/// Latency-gradient concurrency limits applied to physical attempts.
+///
+/// Each service binding and each endpoint keeps its own limit between `min_limit` and
+/// `max_limit`. Successful attempts move it toward the ratio of the long-term to the current
+/// latency, scaled by `latency_tolerance`; `429` and timeout failures shrink it multiplicatively.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdaptiveConcurrencyConfig {
+    initial_limit: u32,
+    min_limit: u32,
+    max_limit: u32,
+    latency_tolerance: f64,
+    max_endpoint_entries: usize,
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 200,
+            latency_tolerance: 1.5,
+            max_endpoint_entries: 10_000,
+        }
+    }
+}
+
+impl AdaptiveConcurrencyConfig {
+    /// Starts a builder with conservative gradient defaults.
+    pub fn builder() -> AdaptiveConcurrencyConfigBuilder {
+        AdaptiveConcurrencyConfigBuilder(Self::default())
+    }
+
+    /// Returns the limit used before any latency is observed.
+    pub const fn initial_limit(&self) -> u32 {
+        self.initial_limit
+    }
+
+    /// Returns the lowest limit the gradient may reach.
+    pub const fn min_limit(&self) -> u32 {
+        self.min_limit
+    }
+
+    /// Returns the highest limit the gradient may reach.
+    pub const fn max_limit(&self) -> u32 {
+        self.max_limit
+    }
+
+    /// Returns how much current latency may exceed the long-term latency before shrinking.
+    pub const fn latency_tolerance(&self) -> f64 {
+        self.latency_tolerance
+    }
+
+    /// Returns how many endpoint limits are retained before idle ones are replaced.
+    pub const fn max_endpoint_entries(&self) -> usize {
+        self.max_endpoint_entries
+    }
+}
+
+/// Builder for [`AdaptiveConcurrencyConfig`].
+#[derive(Clone, Debug)]
+pub struct AdaptiveConcurrencyConfigBuilder(AdaptiveConcurrencyConfig);
+
+impl AdaptiveConcurrencyConfigBuilder {
+    /// Sets the limit used before any latency is observed.
+    pub const fn initial_limit(mut self, value: u32) -> Self {
+        self.0.initial_limit = value;
+        self
+    }
+
+    /// Sets the lowest limit the gradient may reach.
+    pub const fn min_limit(mut self, value: u32) -> Self {
+        self.0.min_limit = value;
+        self
+    }
+
+    /// Sets the highest limit the gradient may reach.
+    pub const fn max_limit(mut self, value: u32) -> Self {
+        self.0.max_limit = value;
+        self
+    }
+
+    /// Sets the tolerated ratio of current to long-term latency.
+    pub const fn latency_tolerance(mut self, value: f64) -> Self {
+        self.0.latency_tolerance = value;
+        self
+    }
+
+    /// Sets how many endpoint limits are retained before idle ones are replaced.
+    pub const fn max_endpoint_entries(mut self, value: usize) -> Self {
+        self.0.max_endpoint_entries = value;
+        self
+    }
+
+    /// Validates and builds adaptive concurrency settings.
+    pub fn build(self) -> Result<AdaptiveConcurrencyConfig, ConfigValidationError> {
+        validate_adaptive(&self.0)?;
+        Ok(self.0)
+    }
+}
+
 /// Client concurrency and buffering limits.
 #[derive(Clone, Debug)]
 pub struct ClientAdmissionConfig {
@@ -73,6 +184,7 @@ pub struct ClientAdmissionConfig {
     max_inflight_request_body_bytes: usize,
     max_inflight_response_body_bytes: usize,
     queue: QueueConfig,
+    adaptive: Option<AdaptiveConcurrencyConfig>,
 }
 
 impl Default for ClientAdmissionConfig {
@@ -85,6 +197,7 @@ impl Default for ClientAdmissionConfig {
             max_inflight_request_body_bytes: 64 * MIB,
             max_inflight_response_body_bytes: 64 * MIB,
             queue: QueueConfig::default(),
+            adaptive: None,
         }
     }
 }
@@ -129,6 +242,11 @@ impl ClientAdmissionConfig {
     pub const fn queue(&self) -> &QueueConfig {
         &self.queue
     }
+
+    /// Returns adaptive attempt concurrency limits, if enabled.
+    pub const fn adaptive(&self) -> Option<&AdaptiveConcurrencyConfig> {
+        self.adaptive.as_ref()
+    }
 }
 
 /// Builder for [`ClientAdmissionConfig`].
@@ -178,6 +296,14 @@ impl ClientAdmissionConfigBuilder {
         self
     }
 
+    /// Enables latency-gradient limits per service binding and per endpoint.
+    ///
+    /// The fixed `max_in_flight_per_endpoint` bulkhead still applies on top of these limits.
+    pub fn adaptive(mut self, value: AdaptiveConcurrencyConfig) -> Self {
+        self.0.adaptive = Some(value);
+        self
+    }
+
     /// Validates and builds admission limits.
     pub fn build(self) -> Result<ClientAdmissionConfig, ConfigValidationError> {
         validate_admission(&self.0)?;
@@ -195,6 +321,7 @@ pub struct DiscoveryConfig {
     reconnect_base: Duration,
     reconnect_cap: Duration,
     max_subscriptions: usize,
+    slow_start: Option<SlowStartConfig>,
 }
 
 impl Default for DiscoveryConfig {
@@ -207,6 +334,7 @@ impl Default for DiscoveryConfig {
             reconnect_base: Duration::from_millis(100),
             reconnect_cap: Duration::from_secs(30),
             max_subscriptions: 1024,
+            slow_start: None,
         }
     }
 }
@@ -251,6 +379,11 @@ impl DiscoveryConfig {
     pub const fn max_subscriptions(&self) -> usize {
         self.max_subscriptions
     }
+
+    /// Returns the warm-up ramp for newly discovered instances, if enabled.
+    pub const fn slow_start(&self) -> Option<&SlowStartConfig> {
+        self.slow_start.as_ref()
+    }
 }
 
 /// Builder for [`DiscoveryConfig`].
@@ -300,6 +433,12 @@ impl DiscoveryConfigBuilder {
         self
     }
 
+    /// Ramps the weight of instances that join a ready directory.
+    pub fn slow_start(mut self, value: SlowStartConfig) -> Self {
+        self.0.slow_start = Some(value);
+        self
+    }
+
     /// Validates and builds discovery settings.
     pub fn build(self) -> Result<DiscoveryConfig, ConfigValidationError> {
         validate_discovery(&self.0)?;
@@ -307,6 +446,67 @@ impl DiscoveryConfigBuilder {
     }
 }
 
+/// Warm-up ramp for instances that join a subscription after its first snapshot.
+///
+/// A new instance's weight starts at `min_weight_ratio` of its registered weight and grows
+/// linearly to the full weight over `window`. Instances present in the first snapshot start at
+/// full weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlowStartConfig {
+    window: Duration,
+    min_weight_ratio: f64,
+}
+
+impl Default for SlowStartConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(30),
+            min_weight_ratio: 0.1,
+        }
+    }
+}
+
+impl SlowStartConfig {
+    /// Starts a builder with a 30 second ramp from 10% weight.
+    pub fn builder() -> SlowStartConfigBuilder {
+        SlowStartConfigBuilder(Self::default())
+    }
+
+    /// Returns how long a new instance takes to reach its full weight.
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns the fraction of its weight a new instance starts with.
+    pub const fn min_weight_ratio(&self) -> f64 {
+        self.min_weight_ratio
+    }
+}
+
+/// Builder for [`SlowStartConfig`].
+#[derive(Clone, Debug)]
+pub struct SlowStartConfigBuilder(SlowStartConfig);
+
+impl SlowStartConfigBuilder {
+    /// Sets how long a new instance takes to reach its full weight.
+    pub const fn window(mut self, value: Duration) -> Self {
+        self.0.window = value;
+        self
+    }
+
+    /// Sets the fraction of its weight a new instance starts with.
+    pub const fn min_weight_ratio(mut self, value: f64) -> Self {
+        self.0.min_weight_ratio = value;
+        self
+    }
+
+    /// Validates and builds slow-start settings.
+    pub fn build(self) -> Result<SlowStartConfig, ConfigValidationError> {
+        validate_slow_start(&self.0)?;
+        Ok(self.0)
+    }
+}
+
 /// Built-in bounded retry settings.
 #[derive(Clone, Debug)]
 pub struct RetryConfig {
@@ -315,6 +515,7 @@ pub struct RetryConfig {
     backoff_cap: Duration,
     budget_capacity: u32,
     budget_refill_per_second: u32,
+    hedging: Option<HedgingConfig>,
 }
 
 impl Default for RetryConfig {
@@ -325,6 +526,7 @@ impl Default for RetryConfig {
             backoff_cap: Duration::from_millis(200),
             budget_capacity: 100,
             budget_refill_per_second: 10,
+            hedging: None,
         }
     }
 }
@@ -340,6 +542,11 @@ impl RetryConfig {
         self.max_attempts
     }
 
+    pub(crate) fn with_max_attempts(mut self, max_attempts: u8) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
     /// Returns the full-jitter backoff base.
     pub const fn backoff_base(&self) -> Duration {
         self.backoff_base
@@ -359,6 +566,11 @@ impl RetryConfig {
     pub const fn budget_refill_per_second(&self) -> u32 {
         self.budget_refill_per_second
     }
+
+    /// Returns tail-latency hedging settings. `None` never hedges.
+    pub const fn hedging(&self) -> Option<&HedgingConfig> {
+        self.hedging.as_ref()
+    }
 }
 
 /// Builder for [`RetryConfig`].
@@ -396,6 +608,12 @@ impl RetryConfigBuilder {
         self
     }
 
+    /// Enables tail-latency hedging for replayable methods.
+    pub fn hedging(mut self, value: HedgingConfig) -> Self {
+        self.0.hedging = Some(value);
+        self
+    }
+
     /// Validates and builds retry settings.
     pub fn build(self) -> Result<RetryConfig, ConfigValidationError> {
         validate_retry(&self.0)?;
@@ -403,8 +621,83 @@ impl RetryConfigBuilder {
     }
 }
 
-/// One rolling circuit-breaker threshold set.
+/// Tail-latency hedging for methods whose HTTP method permits automatic replay.
+///
+/// When an attempt has not produced a response after the configured latency percentile of recent
+/// successful attempts, another attempt is sent to an endpoint that has not been tried yet. The
+/// first success wins and the others are cancelled. Each hedge spends one retry token and counts
+/// toward the same attempt limit as retries. Methods with a streamed request body never hedge.
+#[derive(Clone, Debug)]
+pub struct HedgingConfig {
+    percentile: u8,
+    min_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for HedgingConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 95,
+            min_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl HedgingConfig {
+    /// Starts a builder that hedges at the p95 latency, clamped to 10 ms..=1 s.
+    pub fn builder() -> HedgingConfigBuilder {
+        HedgingConfigBuilder(Self::default())
+    }
+
+    /// Returns the latency percentile after which a hedge is sent.
+    pub const fn percentile(&self) -> u8 {
+        self.percentile
+    }
+
+    /// Returns the shortest hedge delay.
+    pub const fn min_delay(&self) -> Duration {
+        self.min_delay
+    }
+
+    /// Returns the longest hedge delay, also used before any latency has been observed.
+    pub const fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+}
+
+/// Builder for [`HedgingConfig`].
 #[derive(Clone, Debug)]
+pub struct HedgingConfigBuilder(HedgingConfig);
+
+impl HedgingConfigBuilder {
+    /// Sets the latency percentile after which a hedge is sent.
+    pub const fn percentile(mut self, value: u8) -> Self {
+        self.0.percentile = value;
+        self
+    }
+
+    /// Sets the shortest hedge delay.
+    pub const fn min_delay(mut self, value: Duration) -> Self {
+        self.0.min_delay = value;
+        self
+    }
+
+    /// Sets the longest hedge delay.
+    pub const fn max_delay(mut self, value: Duration) -> Self {
+        self.0.max_delay = value;
+        self
+    }
+
+    /// Validates and builds hedging settings.
+    pub fn build(self) -> Result<HedgingConfig, ConfigValidationError> {
+        validate_hedging(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// One rolling circuit-breaker threshold set.
+#[derive(Clone, Debug, PartialEq)]
 pub struct BreakerThreshold {
     window: Duration,
     buckets: u8,
@@ -548,6 +841,7 @@ pub struct CircuitBreakerConfig {
     max_open_duration: Duration,
     max_endpoint_entries: usize,
     idle_eviction: Duration,
+    outlier_detection: Option<OutlierDetectionConfig>,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -558,6 +852,7 @@ impl Default for CircuitBreakerConfig {
             max_open_duration: Duration::from_secs(120),
             max_endpoint_entries: 10_000,
             idle_eviction: DEFAULT_ENDPOINT_IDLE_EVICTION,
+            outlier_detection: None,
         }
     }
 }
@@ -592,6 +887,11 @@ impl CircuitBreakerConfig {
     pub const fn idle_eviction(&self) -> Duration {
         self.idle_eviction
     }
+
+    /// Returns latency outlier ejection settings for discovered endpoints, if enabled.
+    pub const fn outlier_detection(&self) -> Option<&OutlierDetectionConfig> {
+        self.outlier_detection.as_ref()
+    }
 }
 
 /// Builder for [`CircuitBreakerConfig`].
@@ -629,6 +929,12 @@ impl CircuitBreakerConfigBuilder {
         self
     }
 
+    /// Ejects discovered endpoints whose latency stands out from their peers.
+    pub fn outlier_detection(mut self, value: OutlierDetectionConfig) -> Self {
+        self.0.outlier_detection = Some(value);
+        self
+    }
+
     /// Validates and builds circuit-breaker settings.
     pub fn build(self) -> Result<CircuitBreakerConfig, ConfigValidationError> {
         validate_circuit_breaker(&self.0)?;
@@ -636,6 +942,455 @@ impl CircuitBreakerConfigBuilder {
     }
 }
 
+/// Latency-based ejection of discovered endpoints that are slow but still succeed.
+///
+/// Every `interval`, each endpoint with at least `min_requests` successful attempts is compared
+/// with the median mean latency of its service binding. When at least three endpoints qualify,
+/// those slower than `latency_ratio` times the median are ejected for `base_ejection` multiplied
+/// by their consecutive ejection count, capped at ten times `base_ejection`. At most
+/// `max_ejection_ratio` of the qualifying endpoints are ejected at once, and ejected endpoints are
+/// still used when no other endpoint is eligible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlierDetectionConfig {
+    interval: Duration,
+    latency_ratio: f64,
+    min_requests: u32,
+    base_ejection: Duration,
+    max_ejection_ratio: f64,
+}
+
+impl Default for OutlierDetectionConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            latency_ratio: 2.0,
+            min_requests: 10,
+            base_ejection: Duration::from_secs(30),
+            max_ejection_ratio: 0.5,
+        }
+    }
+}
+
+impl OutlierDetectionConfig {
+    /// Starts a builder with conservative ejection defaults.
+    pub fn builder() -> OutlierDetectionConfigBuilder {
+        OutlierDetectionConfigBuilder(Self::default())
+    }
+
+    /// Returns the evaluation interval.
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns how many times the median latency an endpoint may reach before ejection.
+    pub const fn latency_ratio(&self) -> f64 {
+        self.latency_ratio
+    }
+
+    /// Returns the successful attempts an endpoint needs per interval to be evaluated.
+    pub const fn min_requests(&self) -> u32 {
+        self.min_requests
+    }
+
+    /// Returns the ejection time for a first ejection.
+    pub const fn base_ejection(&self) -> Duration {
+        self.base_ejection
+    }
+
+    /// Returns the largest fraction of evaluated endpoints that may be ejected at once.
+    pub const fn max_ejection_ratio(&self) -> f64 {
+        self.max_ejection_ratio
+    }
+}
+
+/// Builder for [`OutlierDetectionConfig`].
+#[derive(Clone, Debug)]
+pub struct OutlierDetectionConfigBuilder(OutlierDetectionConfig);
+
+impl OutlierDetectionConfigBuilder {
+    /// Sets the evaluation interval.
+    pub const fn interval(mut self, value: Duration) -> Self {
+        self.0.interval = value;
+        self
+    }
+
+    /// Sets how many times the median latency an endpoint may reach before ejection.
+    pub const fn latency_ratio(mut self, value: f64) -> Self {
+        self.0.latency_ratio = value;
+        self
+    }
+
+    /// Sets the successful attempts an endpoint needs per interval to be evaluated.
+    pub const fn min_requests(mut self, value: u32) -> Self {
+        self.0.min_requests = value;
+        self
+    }
+
+    /// Sets the ejection time for a first ejection.
+    pub const fn base_ejection(mut self, value: Duration) -> Self {
+        self.0.base_ejection = value;
+        self
+    }
+
+    /// Sets the largest fraction of evaluated endpoints that may be ejected at once.
+    pub const fn max_ejection_ratio(mut self, value: f64) -> Self {
+        self.0.max_ejection_ratio = value;
+        self
+    }
+
+    /// Validates and builds outlier detection settings.
+    pub fn build(self) -> Result<OutlierDetectionConfig, ConfigValidationError> {
+        validate_outlier_detection(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// Per-method replacement of the runtime deadline, retry, and interface-breaker settings.
+///
+/// Unset fields fall back to the method's `#[method(timeout_ms, retries)]` defaults and then to
+/// [`ClientConfig`]. A method retry configuration gets its own retry token bucket, and a method
+/// breaker threshold gets its own interface-level breaker; endpoint breakers stay shared because
+/// they track endpoint health rather than method behavior.
+#[derive(Clone, Debug, Default)]
+pub struct MethodConfig {
+    request_timeout: Option<Duration>,
+    retry: Option<RetryConfig>,
+    service_breaker: Option<BreakerThreshold>,
+}
+
+impl MethodConfig {
+    /// Starts a builder that overrides nothing.
+    pub fn builder() -> MethodConfigBuilder {
+        MethodConfigBuilder(Self::default())
+    }
+
+    /// Returns the logical invocation timeout override.
+    pub const fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// Returns the retry settings override.
+    pub const fn retry(&self) -> Option<&RetryConfig> {
+        self.retry.as_ref()
+    }
+
+    /// Returns the method-level interface breaker thresholds.
+    pub const fn service_breaker(&self) -> Option<&BreakerThreshold> {
+        self.service_breaker.as_ref()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ConfigValidationError> {
+        if let Some(timeout) = self.request_timeout {
+            positive_duration(timeout, "client.methods.request_timeout")?;
+        }
+        if let Some(retry) = &self.retry {
+            validate_retry(retry)?;
+        }
+        if let Some(threshold) = &self.service_breaker {
+            validate_threshold(threshold, ThresholdScope::Service)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates interface-declared method defaults against the same bounds as [`MethodConfig`].
+pub(crate) fn validate_method_defaults(
+    defaults: MethodClientDefaults,
+) -> Result<(), ConfigValidationError> {
+    if let Some(timeout) = defaults.timeout() {
+        positive_duration(timeout, "client.methods.request_timeout")?;
+    }
+    if defaults.retries().is_some_and(|retries| retries > 2) {
+        return Err(out_of_range(
+            "client.methods.retries",
+            "must be between 0 and 2 inclusive",
+        ));
+    }
+    Ok(())
+}
+
+/// Builder for [`MethodConfig`].
+#[derive(Clone, Debug)]
+pub struct MethodConfigBuilder(MethodConfig);
+
+impl MethodConfigBuilder {
+    /// Sets the end-to-end logical invocation timeout for this method.
+    pub const fn request_timeout(mut self, value: Duration) -> Self {
+        self.0.request_timeout = Some(value);
+        self
+    }
+
+    /// Replaces retry settings for this method.
+    pub fn retry(mut self, value: RetryConfig) -> Self {
+        self.0.retry = Some(value);
+        self
+    }
+
+    /// Gives this method its own interface-level breaker with these thresholds.
+    pub fn service_breaker(mut self, value: BreakerThreshold) -> Self {
+        self.0.service_breaker = Some(value);
+        self
+    }
+
+    /// Validates and builds per-method settings.
+    pub fn build(self) -> Result<MethodConfig, ConfigValidationError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+/// Client TLS trust roots and optional client certificate.
+///
+/// PEM files are read when the [`crate::ClientRuntime`] is built, not by this builder.
+#[derive(Clone, Debug)]
+pub struct ClientTlsConfig {
+    webpki_roots: bool,
+    root_certificate_paths: Vec<PathBuf>,
+    client_identity: Option<ClientTlsIdentity>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum ClientTlsIdentity {
+    Files {
+        certificate_chain_path: PathBuf,
+        private_key_path: PathBuf,
+    },
+    #[cfg(feature = "hot-tls")]
+    Hot(HotConfig<TlsCertificate>),
+}
+
+impl Default for ClientTlsConfig {
+    fn default() -> Self {
+        Self {
+            webpki_roots: true,
+            root_certificate_paths: Vec::new(),
+            client_identity: None,
+        }
+    }
+}
+
+impl ClientTlsConfig {
+    /// Starts a builder that trusts the bundled WebPKI roots and presents no client certificate.
+    pub fn builder() -> ClientTlsConfigBuilder {
+        ClientTlsConfigBuilder(Self::default())
+    }
+
+    /// Returns whether the bundled WebPKI roots are trusted.
+    pub const fn webpki_roots(&self) -> bool {
+        self.webpki_roots
+    }
+
+    /// Returns additional PEM root certificate files, in configuration order.
+    pub fn root_certificate_paths(&self) -> &[PathBuf] {
+        &self.root_certificate_paths
+    }
+
+    /// Returns the PEM client certificate chain file presented for mutual TLS.
+    pub fn client_certificate_chain_path(&self) -> Option<&Path> {
+        match self.client_identity.as_ref()? {
+            ClientTlsIdentity::Files {
+                certificate_chain_path,
+                ..
+            } => Some(certificate_chain_path),
+            #[cfg(feature = "hot-tls")]
+            ClientTlsIdentity::Hot(_) => None,
+        }
+    }
+
+    /// Returns the PEM private key file matching the client certificate.
+    pub fn client_private_key_path(&self) -> Option<&Path> {
+        match self.client_identity.as_ref()? {
+            ClientTlsIdentity::Files {
+                private_key_path, ..
+            } => Some(private_key_path),
+            #[cfg(feature = "hot-tls")]
+            ClientTlsIdentity::Hot(_) => None,
+        }
+    }
+
+    /// Returns the hot client certificate presented for mutual TLS, if configured.
+    #[cfg(feature = "hot-tls")]
+    pub fn hot_client_identity(&self) -> Option<&HotConfig<TlsCertificate>> {
+        match self.client_identity.as_ref()? {
+            ClientTlsIdentity::Hot(certificate) => Some(certificate),
+            ClientTlsIdentity::Files { .. } => None,
+        }
+    }
+
+    pub(crate) const fn client_identity(&self) -> Option<&ClientTlsIdentity> {
+        self.client_identity.as_ref()
+    }
+}
+
+/// Builder for [`ClientTlsConfig`].
+#[derive(Clone, Debug)]
+pub struct ClientTlsConfigBuilder(ClientTlsConfig);
+
+impl ClientTlsConfigBuilder {
+    /// Sets whether the bundled WebPKI roots are trusted.
+    pub const fn webpki_roots(mut self, value: bool) -> Self {
+        self.0.webpki_roots = value;
+        self
+    }
+
+    /// Adds a PEM file whose certificates are trusted as additional roots.
+    pub fn root_certificate_path(mut self, value: impl Into<PathBuf>) -> Self {
+        self.0.root_certificate_paths.push(value.into());
+        self
+    }
+
+    /// Presents a PEM certificate chain, leaf first, and its private key for mutual TLS.
+    pub fn client_identity(
+        mut self,
+        certificate_chain_path: impl Into<PathBuf>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.0.client_identity = Some(ClientTlsIdentity::Files {
+            certificate_chain_path: certificate_chain_path.into(),
+            private_key_path: private_key_path.into(),
+        });
+        self
+    }
+
+    /// Presents the latest last-good certificate from a hot configuration for mutual TLS.
+    ///
+    /// New connections use the latest accepted certificate; pooled connections keep the
+    /// certificate they negotiated until they close.
+    #[cfg(feature = "hot-tls")]
+    pub fn hot_client_identity(mut self, certificate: HotConfig<TlsCertificate>) -> Self {
+        self.0.client_identity = Some(ClientTlsIdentity::Hot(certificate));
+        self
+    }
+
+    /// Validates and builds client TLS settings.
+    pub fn build(self) -> Result<ClientTlsConfig, ConfigValidationError> {
+        validate_tls(&self.0, TlsScope::Default)?;
+        Ok(self.0)
+    }
+}
+
+/// Response codings the client advertises through `Accept-Encoding`.
+///
+/// Coded responses are decoded before any [`ResponseDecoder`](crate::ResponseDecoder) runs, and
+/// the decoded size counts against the same response limits as an uncoded body.
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ClientCompressionConfig {
+    codings: Vec<ContentCoding>,
+}
+
+#[cfg(feature = "compression")]
+impl Default for ClientCompressionConfig {
+    fn default() -> Self {
+        Self {
+            codings: vec![
+                ContentCoding::Zstd,
+                ContentCoding::Brotli,
+                ContentCoding::Gzip,
+            ],
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl ClientCompressionConfig {
+    /// Starts a builder accepting zstd, br, and gzip.
+    pub fn builder() -> ClientCompressionConfigBuilder {
+        ClientCompressionConfigBuilder(Self::default())
+    }
+
+    /// Returns the advertised codings, most preferred first.
+    pub fn codings(&self) -> &[ContentCoding] {
+        &self.codings
+    }
+}
+
+/// Builder for [`ClientCompressionConfig`].
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ClientCompressionConfigBuilder(ClientCompressionConfig);
+
+#[cfg(feature = "compression")]
+impl ClientCompressionConfigBuilder {
+    /// Replaces the advertised codings, most preferred first.
+    pub fn codings(mut self, value: impl IntoIterator<Item = ContentCoding>) -> Self {
+        self.0.codings = value.into_iter().collect();
+        self
+    }
+
+    /// Validates and builds compression settings.
+    pub fn build(self) -> Result<ClientCompressionConfig, ConfigValidationError> {
+        validate_compression(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// Coding the client applies to large buffered request bodies.
+///
+/// The receiving server must decode request bodies; built-in servers built with the
+/// `compression` feature accept every [`ContentCoding`]. Streamed request bodies are never
+/// coded.
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ClientRequestCompressionConfig {
+    coding: ContentCoding,
+    min_request_bytes: usize,
+}
+
+#[cfg(feature = "compression")]
+impl Default for ClientRequestCompressionConfig {
+    fn default() -> Self {
+        Self {
+            coding: ContentCoding::Gzip,
+            min_request_bytes: 1024,
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl ClientRequestCompressionConfig {
+    /// Starts a builder that gzips request bodies of at least 1 KiB.
+    pub fn builder() -> ClientRequestCompressionConfigBuilder {
+        ClientRequestCompressionConfigBuilder(Self::default())
+    }
+
+    /// Returns the coding applied to request bodies.
+    pub const fn coding(&self) -> ContentCoding {
+        self.coding
+    }
+
+    /// Returns the smallest encoded request body that is compressed.
+    pub const fn min_request_bytes(&self) -> usize {
+        self.min_request_bytes
+    }
+}
+
+/// Builder for [`ClientRequestCompressionConfig`].
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ClientRequestCompressionConfigBuilder(ClientRequestCompressionConfig);
+
+#[cfg(feature = "compression")]
+impl ClientRequestCompressionConfigBuilder {
+    /// Sets the coding applied to request bodies.
+    pub const fn coding(mut self, value: ContentCoding) -> Self {
+        self.0.coding = value;
+        self
+    }
+
+    /// Sets the smallest encoded request body that is compressed.
+    pub const fn min_request_bytes(mut self, value: usize) -> Self {
+        self.0.min_request_bytes = value;
+        self
+    }
+
+    /// Validates and builds request compression settings.
+    pub fn build(self) -> Result<ClientRequestCompressionConfig, ConfigValidationError> {
+        validate_request_compression(&self.0)?;
+        Ok(self.0)
+    }
+}
+
 /// HTTP and HTTPS connection-pool behavior.
 #[derive(Clone, Debug)]
 pub struct ClientHttpConfig {
@@ -644,6 +1399,12 @@ pub struct ClientHttpConfig {
     http2_connections_per_host: usize,
     http2_keep_alive_interval: Option<Duration>,
     http2_keep_alive_timeout: Duration,
+    tls: ClientTlsConfig,
+    tls_overrides: Vec<(ServiceSelector, ClientTlsConfig)>,
+    #[cfg(feature = "compression")]
+    compression: Option<ClientCompressionConfig>,
+    #[cfg(feature = "compression")]
+    request_compression: Option<ClientRequestCompressionConfig>,
 }
 
 impl Default for ClientHttpConfig {
@@ -654,6 +1415,12 @@ impl Default for ClientHttpConfig {
             http2_connections_per_host: 1,
             http2_keep_alive_interval: None,
             http2_keep_alive_timeout: Duration::from_secs(20),
+            tls: ClientTlsConfig::default(),
+            tls_overrides: Vec::new(),
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "compression")]
+            request_compression: None,
         }
     }
 }
@@ -688,6 +1455,43 @@ impl ClientHttpConfig {
     pub const fn http2_keep_alive_timeout(&self) -> Duration {
         self.http2_keep_alive_timeout
     }
+
+    /// Returns TLS settings used by services without an override.
+    pub const fn tls(&self) -> &ClientTlsConfig {
+        &self.tls
+    }
+
+    /// Returns per-service TLS overrides, in configuration order.
+    pub fn tls_overrides(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (&ServiceSelector, &ClientTlsConfig)> {
+        self.tls_overrides
+            .iter()
+            .map(|(selector, tls)| (selector, tls))
+    }
+
+    /// Returns the TLS settings for one service.
+    ///
+    /// Overrides match the selector's `service[/group][@version]` identity; selector metadata is
+    /// ignored.
+    pub fn tls_for(&self, selector: &ServiceSelector) -> &ClientTlsConfig {
+        self.tls_overrides
+            .iter()
+            .find(|(candidate, _)| candidate.identity() == selector.identity())
+            .map_or(&self.tls, |(_, tls)| tls)
+    }
+
+    /// Returns response compression settings. `None` sends no `Accept-Encoding`.
+    #[cfg(feature = "compression")]
+    pub const fn compression(&self) -> Option<&ClientCompressionConfig> {
+        self.compression.as_ref()
+    }
+
+    /// Returns request body compression settings. `None` sends request bodies uncoded.
+    #[cfg(feature = "compression")]
+    pub const fn request_compression(&self) -> Option<&ClientRequestCompressionConfig> {
+        self.request_compression.as_ref()
+    }
 }
 
 /// Builder for [`ClientHttpConfig`].
@@ -725,6 +1529,32 @@ impl ClientHttpConfigBuilder {
         self
     }
 
+    /// Replaces TLS settings used by services without an override.
+    pub fn tls(mut self, value: ClientTlsConfig) -> Self {
+        self.0.tls = value;
+        self
+    }
+
+    /// Uses separate TLS settings, and separate connection pools, for one service.
+    pub fn tls_override(mut self, selector: ServiceSelector, value: ClientTlsConfig) -> Self {
+        self.0.tls_overrides.push((selector, value));
+        self
+    }
+
+    /// Asks servers for compressed responses.
+    #[cfg(feature = "compression")]
+    pub fn compression(mut self, value: ClientCompressionConfig) -> Self {
+        self.0.compression = Some(value);
+        self
+    }
+
+    /// Compresses large request bodies for servers that decode them.
+    #[cfg(feature = "compression")]
+    pub fn request_compression(mut self, value: ClientRequestCompressionConfig) -> Self {
+        self.0.request_compression = Some(value);
+        self
+    }
+
     /// Validates and builds HTTP pool settings.
     pub fn build(self) -> Result<ClientHttpConfig, ConfigValidationError> {
         validate_http(&self.0)?;
@@ -914,7 +1744,37 @@ fn validate_admission(config: &ClientAdmissionConfig) -> Result<(), ConfigValida
             "must be at least max_response_body_bytes",
         ));
     }
-    validate_queue(&config.queue)
+    validate_queue(&config.queue)?;
+    match &config.adaptive {
+        Some(adaptive) => validate_adaptive(adaptive),
+        None => Ok(()),
+    }
+}
+
+fn validate_adaptive(config: &AdaptiveConcurrencyConfig) -> Result<(), ConfigValidationError> {
+    positive_u32(config.min_limit, "client.admission.adaptive.min_limit")?;
+    if config.min_limit > config.max_limit {
+        return Err(inconsistent(
+            "client.admission.adaptive.min_limit",
+            "must not exceed max_limit",
+        ));
+    }
+    if !(config.min_limit..=config.max_limit).contains(&config.initial_limit) {
+        return Err(inconsistent(
+            "client.admission.adaptive.initial_limit",
+            "must be between min_limit and max_limit inclusive",
+        ));
+    }
+    if !config.latency_tolerance.is_finite() || config.latency_tolerance < 1.0 {
+        return Err(out_of_range(
+            "client.admission.adaptive.latency_tolerance",
+            "must be finite and at least 1",
+        ));
+    }
+    positive_usize(
+        config.max_endpoint_entries,
+        "client.admission.adaptive.max_endpoint_entries",
+    )
 }
 
 fn validate_discovery(config: &DiscoveryConfig) -> Result<(), ConfigValidationError> {
@@ -935,7 +1795,22 @@ fn validate_discovery(config: &DiscoveryConfig) -> Result<(), ConfigValidationEr
     positive_usize(
         config.max_subscriptions,
         "client.discovery.max_subscriptions",
-    )
+    )?;
+    match &config.slow_start {
+        Some(slow_start) => validate_slow_start(slow_start),
+        None => Ok(()),
+    }
+}
+
+fn validate_slow_start(config: &SlowStartConfig) -> Result<(), ConfigValidationError> {
+    positive_duration(config.window, "client.discovery.slow_start.window")?;
+    if !(config.min_weight_ratio > 0.0 && config.min_weight_ratio <= 1.0) {
+        return Err(out_of_range(
+            "client.discovery.slow_start.min_weight_ratio",
+            "must be greater than 0 and at most 1",
+        ));
+    }
+    Ok(())
 }
 
 fn validate_retry(config: &RetryConfig) -> Result<(), ConfigValidationError> {
@@ -957,7 +1832,28 @@ fn validate_retry(config: &RetryConfig) -> Result<(), ConfigValidationError> {
     positive_u32(
         config.budget_refill_per_second,
         "client.retry.budget_refill_per_second",
-    )
+    )?;
+    match &config.hedging {
+        Some(hedging) => validate_hedging(hedging),
+        None => Ok(()),
+    }
+}
+
+fn validate_hedging(config: &HedgingConfig) -> Result<(), ConfigValidationError> {
+    if !(1..=99).contains(&config.percentile) {
+        return Err(out_of_range(
+            "client.retry.hedging.percentile",
+            "must be between 1 and 99 inclusive",
+        ));
+    }
+    positive_duration(config.min_delay, "client.retry.hedging.min_delay")?;
+    if config.min_delay > config.max_delay {
+        return Err(inconsistent(
+            "client.retry.hedging.min_delay",
+            "must not exceed max_delay",
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Clone, Copy)]
@@ -1024,7 +1920,41 @@ fn validate_circuit_breaker(config: &CircuitBreakerConfig) -> Result<(), ConfigV
         config.max_endpoint_entries,
         "client.circuit_breaker.max_endpoint_entries",
     )?;
-    positive_duration(config.idle_eviction, "client.circuit_breaker.idle_eviction")
+    positive_duration(config.idle_eviction, "client.circuit_breaker.idle_eviction")?;
+    match &config.outlier_detection {
+        Some(outlier_detection) => validate_outlier_detection(outlier_detection),
+        None => Ok(()),
+    }
+}
+
+fn validate_outlier_detection(
+    config: &OutlierDetectionConfig,
+) -> Result<(), ConfigValidationError> {
+    positive_duration(
+        config.interval,
+        "client.circuit_breaker.outlier_detection.interval",
+    )?;
+    if !config.latency_ratio.is_finite() || config.latency_ratio <= 1.0 {
+        return Err(out_of_range(
+            "client.circuit_breaker.outlier_detection.latency_ratio",
+            "must be finite and greater than 1",
+        ));
+    }
+    positive_u32(
+        config.min_requests,
+        "client.circuit_breaker.outlier_detection.min_requests",
+    )?;
+    positive_duration(
+        config.base_ejection,
+        "client.circuit_breaker.outlier_detection.base_ejection",
+    )?;
+    if !(config.max_ejection_ratio > 0.0 && config.max_ejection_ratio <= 1.0) {
+        return Err(out_of_range(
+            "client.circuit_breaker.outlier_detection.max_ejection_ratio",
+            "must be greater than 0 and at most 1",
+        ));
+    }
+    Ok(())
 }
 
 fn validate_http(config: &ClientHttpConfig) -> Result<(), ConfigValidationError> {
@@ -1053,7 +1983,105 @@ fn validate_http(config: &ClientHttpConfig) -> Result<(), ConfigValidationError>
     positive_duration(
         config.http2_keep_alive_timeout,
         "client.http.http2_keep_alive_timeout",
-    )
+    )?;
+    validate_tls(&config.tls, TlsScope::Default)?;
+    let mut selectors = HashSet::with_capacity(config.tls_overrides.len());
+    for (selector, tls) in &config.tls_overrides {
+        if !selectors.insert(selector.identity()) {
+            return Err(inconsistent(
+                "client.http.tls_overrides",
+                "must not repeat a service selector",
+            ));
+        }
+        validate_tls(tls, TlsScope::Override)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum TlsScope {
+    Default,
+    Override,
+}
+
+#[cfg(feature = "compression")]
+fn validate_compression(config: &ClientCompressionConfig) -> Result<(), ConfigValidationError> {
+    if config.codings.is_empty() {
+        return Err(out_of_range(
+            "client.http.compression.codings",
+            "must contain at least one coding",
+        ));
+    }
+    if config
+        .codings
+        .iter()
+        .enumerate()
+        .any(|(index, coding)| config.codings[..index].contains(coding))
+    {
+        return Err(inconsistent(
+            "client.http.compression.codings",
+            "must not repeat a coding",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "compression")]
+fn validate_request_compression(
+    config: &ClientRequestCompressionConfig,
+) -> Result<(), ConfigValidationError> {
+    if config.min_request_bytes == 0 {
+        return Err(out_of_range(
+            "client.http.request_compression.min_request_bytes",
+            "must be positive",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_tls(config: &ClientTlsConfig, scope: TlsScope) -> Result<(), ConfigValidationError> {
+    let paths = match scope {
+        TlsScope::Default => [
+            "client.http.tls.root_certificate_paths",
+            "client.http.tls.client_certificate_chain_path",
+            "client.http.tls.client_private_key_path",
+        ],
+        TlsScope::Override => [
+            "client.http.tls_overrides.root_certificate_paths",
+            "client.http.tls_overrides.client_certificate_chain_path",
+            "client.http.tls_overrides.client_private_key_path",
+        ],
+    };
+    if config
+        .root_certificate_paths
+        .iter()
+        .any(|path| path.as_os_str().is_empty())
+    {
+        return Err(out_of_range(paths[0], "must not contain empty paths"));
+    }
+    if !config.webpki_roots && config.root_certificate_paths.is_empty() {
+        return Err(inconsistent(
+            paths[0],
+            "must not be empty when bundled WebPKI roots are disabled",
+        ));
+    }
+    if let Some(ClientTlsIdentity::Files {
+        certificate_chain_path,
+        private_key_path,
+    }) = &config.client_identity
+    {
+        non_empty_path(certificate_chain_path, paths[1])?;
+        non_empty_path(private_key_path, paths[2])?;
+    }
+    Ok(())
+}
+
+fn non_empty_path(value: &Path, field_path: &'static str) -> Result<(), ConfigValidationError> {
+    if value.as_os_str().is_empty() {
+        Err(out_of_range(field_path, "must not be empty"))
+    } else {
+        Ok(())
+    }
 }
 
 fn positive_duration(
@@ -1110,6 +2138,15 @@ mod tests {
         assert_eq!(config.circuit_breaker().endpoint().minimum_samples(), 20);
         assert_eq!(config.circuit_breaker().service().minimum_samples(), 50);
         assert_eq!(config.http().http2_connections_per_host(), 1);
+        assert!(config.http().tls().webpki_roots());
+        assert!(
+            config
+                .http()
+                .tls()
+                .client_certificate_chain_path()
+                .is_none()
+        );
+        assert_eq!(config.http().tls_overrides().len(), 0);
     }
 
     #[test]
@@ -1131,6 +2168,190 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hedging_is_opt_in_and_validates_its_delay_window() {
+        assert!(RetryConfig::default().hedging().is_none());
+        let hedging = HedgingConfig::builder()
+            .percentile(99)
+            .min_delay(Duration::from_millis(5))
+            .max_delay(Duration::from_millis(5))
+            .build()
+            .unwrap();
+        let retry = RetryConfig::builder().hedging(hedging).build().unwrap();
+        assert_eq!(retry.hedging().unwrap().percentile(), 99);
+
+        let percentile = HedgingConfig::builder()
+            .percentile(100)
+            .build()
+            .unwrap_err();
+        assert_eq!(percentile.kind(), ConfigValidationErrorKind::OutOfRange);
+        assert_eq!(percentile.field_path(), "client.retry.hedging.percentile");
+        let window = HedgingConfig::builder()
+            .min_delay(Duration::from_secs(2))
+            .build()
+            .unwrap_err();
+        assert_eq!(window.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(window.field_path(), "client.retry.hedging.min_delay");
+    }
+
+    #[test]
+    fn adaptive_concurrency_is_opt_in_and_bounded() {
+        assert!(ClientAdmissionConfig::default().adaptive().is_none());
+        let admission = ClientAdmissionConfig::builder()
+            .adaptive(
+                AdaptiveConcurrencyConfig::builder()
+                    .initial_limit(4)
+                    .min_limit(2)
+                    .max_limit(8)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let adaptive = admission.adaptive().unwrap();
+        assert_eq!(
+            (
+                adaptive.initial_limit(),
+                adaptive.min_limit(),
+                adaptive.max_limit()
+            ),
+            (4, 2, 8)
+        );
+        assert_eq!(adaptive.latency_tolerance(), 1.5);
+        assert_eq!(adaptive.max_endpoint_entries(), 10_000);
+
+        for (builder, path) in [
+            (
+                AdaptiveConcurrencyConfig::builder().min_limit(0),
+                "client.admission.adaptive.min_limit",
+            ),
+            (
+                AdaptiveConcurrencyConfig::builder()
+                    .min_limit(9)
+                    .max_limit(8),
+                "client.admission.adaptive.min_limit",
+            ),
+            (
+                AdaptiveConcurrencyConfig::builder().initial_limit(300),
+                "client.admission.adaptive.initial_limit",
+            ),
+            (
+                AdaptiveConcurrencyConfig::builder().latency_tolerance(0.5),
+                "client.admission.adaptive.latency_tolerance",
+            ),
+            (
+                AdaptiveConcurrencyConfig::builder().latency_tolerance(f64::NAN),
+                "client.admission.adaptive.latency_tolerance",
+            ),
+            (
+                AdaptiveConcurrencyConfig::builder().max_endpoint_entries(0),
+                "client.admission.adaptive.max_endpoint_entries",
+            ),
+        ] {
+            assert_eq!(builder.build().unwrap_err().field_path(), path);
+        }
+    }
+
+    #[test]
+    fn outlier_detection_and_slow_start_are_opt_in_and_bounded() {
+        assert!(
+            CircuitBreakerConfig::default()
+                .outlier_detection()
+                .is_none()
+        );
+        assert!(DiscoveryConfig::default().slow_start().is_none());
+        let breaker = CircuitBreakerConfig::builder()
+            .outlier_detection(
+                OutlierDetectionConfig::builder()
+                    .latency_ratio(3.0)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let outlier = breaker.outlier_detection().unwrap();
+        assert_eq!(outlier.latency_ratio(), 3.0);
+        assert_eq!(outlier.max_ejection_ratio(), 0.5);
+        let discovery = DiscoveryConfig::builder()
+            .slow_start(
+                SlowStartConfig::builder()
+                    .window(Duration::from_secs(60))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            discovery.slow_start().unwrap().window(),
+            Duration::from_secs(60)
+        );
+
+        for (error, path) in [
+            (
+                OutlierDetectionConfig::builder()
+                    .latency_ratio(1.0)
+                    .build()
+                    .unwrap_err(),
+                "client.circuit_breaker.outlier_detection.latency_ratio",
+            ),
+            (
+                OutlierDetectionConfig::builder()
+                    .min_requests(0)
+                    .build()
+                    .unwrap_err(),
+                "client.circuit_breaker.outlier_detection.min_requests",
+            ),
+            (
+                OutlierDetectionConfig::builder()
+                    .max_ejection_ratio(1.5)
+                    .build()
+                    .unwrap_err(),
+                "client.circuit_breaker.outlier_detection.max_ejection_ratio",
+            ),
+            (
+                SlowStartConfig::builder()
+                    .window(Duration::ZERO)
+                    .build()
+                    .unwrap_err(),
+                "client.discovery.slow_start.window",
+            ),
+            (
+                SlowStartConfig::builder()
+                    .min_weight_ratio(0.0)
+                    .build()
+                    .unwrap_err(),
+                "client.discovery.slow_start.min_weight_ratio",
+            ),
+        ] {
+            assert_eq!(error.field_path(), path);
+        }
+    }
+
+    #[test]
+    fn method_overrides_reuse_runtime_validation() {
+        let config = MethodConfig::builder()
+            .request_timeout(Duration::from_secs(60))
+            .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(config.request_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(config.retry().unwrap().max_attempts(), 1);
+        assert!(config.service_breaker().is_none());
+
+        let timeout = MethodConfig::builder()
+            .request_timeout(Duration::ZERO)
+            .build()
+            .unwrap_err();
+        assert_eq!(timeout.field_path(), "client.methods.request_timeout");
+        let retry = RetryConfig {
+            backoff_base: Duration::from_secs(1),
+            ..RetryConfig::default()
+        };
+        let retry = MethodConfig::builder().retry(retry).build().unwrap_err();
+        assert_eq!(retry.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(retry.field_path(), "client.retry.backoff_base");
+    }
+
     #[test]
     fn independent_builders_accept_cross_field_boundaries() {
         let retry = RetryConfig::builder()
@@ -1153,4 +2374,57 @@ mod tests {
             .build()
             .unwrap();
     }
+
+    #[test]
+    fn tls_overrides_match_selector_identity_and_reject_duplicates() {
+        let mesh = ServiceSelector::new("inventory", Some("mesh".to_owned()), None).unwrap();
+        let private = ClientTlsConfig::builder()
+            .webpki_roots(false)
+            .root_certificate_path("/etc/mesh/ca.pem")
+            .client_identity("/etc/mesh/client.pem", "/etc/mesh/client.key")
+            .build()
+            .unwrap();
+        let config = ClientHttpConfig::builder()
+            .tls_override(mesh.clone(), private)
+            .build()
+            .unwrap();
+        assert!(!config.tls_for(&mesh).webpki_roots());
+        assert_eq!(
+            config.tls_for(&mesh).client_private_key_path(),
+            Some(Path::new("/etc/mesh/client.key"))
+        );
+        let public = ServiceSelector::new("inventory", None, None).unwrap();
+        assert!(config.tls_for(&public).webpki_roots());
+
+        let duplicate = ClientHttpConfig::builder()
+            .tls_override(mesh.clone(), ClientTlsConfig::default())
+            .tls_override(mesh, ClientTlsConfig::default())
+            .build()
+            .unwrap_err();
+        assert_eq!(duplicate.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(duplicate.field_path(), "client.http.tls_overrides");
+    }
+
+    #[test]
+    fn tls_validation_rejects_empty_paths_and_an_empty_trust_store() {
+        let no_roots = ClientTlsConfig::builder()
+            .webpki_roots(false)
+            .build()
+            .unwrap_err();
+        assert_eq!(no_roots.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(
+            no_roots.field_path(),
+            "client.http.tls.root_certificate_paths"
+        );
+
+        let empty_key = ClientTlsConfig::builder()
+            .client_identity("/etc/mesh/client.pem", "")
+            .build()
+            .unwrap_err();
+        assert_eq!(empty_key.kind(), ConfigValidationErrorKind::OutOfRange);
+        assert_eq!(
+            empty_key.field_path(),
+            "client.http.tls.client_private_key_path"
+        );
+    }
 }
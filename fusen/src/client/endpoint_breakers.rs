@@ -1,11 +1,13 @@
 use crate::resilience::breaker::{
     BreakerConfig, BreakerPhase, CircuitBreaker, EndpointBreakerStore,
 };
-use fusen_contract::{HttpBindingId, ServiceInstance, ServiceSelector};
+use fusen_contract::{HttpBindingId, InstanceId, ServiceInstance, ServiceSelector};
 use std::{
+    borrow::Borrow,
     collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 type TransitionObserver = Arc<dyn Fn(BreakerPhase) + Send + Sync + 'static>;
@@ -17,7 +19,7 @@ pub(crate) enum EndpointBreakerSource {
     Discovery,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 struct EndpointBreakerKey {
     service: String,
     binding_id: HttpBindingId,
@@ -41,6 +43,54 @@ impl EndpointBreakerKey {
     }
 }
 
+impl Hash for EndpointBreakerKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self as &dyn EndpointBreakerKeyView).hash(state);
+    }
+}
+
+/// Borrowed form of [`EndpointBreakerKey`], so read-only lookups do not allocate a key.
+trait EndpointBreakerKeyView {
+    fn parts(&self) -> (&str, &str, EndpointBreakerSource, &str);
+}
+
+impl EndpointBreakerKeyView for EndpointBreakerKey {
+    fn parts(&self) -> (&str, &str, EndpointBreakerSource, &str) {
+        (
+            &self.service,
+            self.binding_id.as_str(),
+            self.source,
+            &self.endpoint,
+        )
+    }
+}
+
+impl EndpointBreakerKeyView for (&str, &HttpBindingId, EndpointBreakerSource, &str) {
+    fn parts(&self) -> (&str, &str, EndpointBreakerSource, &str) {
+        (self.0, self.1.as_str(), self.2, self.3)
+    }
+}
+
+impl PartialEq for dyn EndpointBreakerKeyView + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for dyn EndpointBreakerKeyView + '_ {}
+
+impl Hash for dyn EndpointBreakerKeyView + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+    }
+}
+
+impl<'a> Borrow<dyn EndpointBreakerKeyView + 'a> for EndpointBreakerKey {
+    fn borrow(&self) -> &(dyn EndpointBreakerKeyView + 'a) {
+        self
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct DiscoveryOwner {
     selector: ServiceSelector,
@@ -52,6 +102,18 @@ struct DiscoveryEndpoint {
     endpoint: String,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct DiscoveryInstance {
+    service: String,
+    instance_id: InstanceId,
+}
+
+#[derive(Debug, Default)]
+struct OwnerMembership {
+    endpoints: HashSet<DiscoveryEndpoint>,
+    instances: HashSet<DiscoveryInstance>,
+}
+
 #[derive(Debug)]
 struct EndpointBreakersInner {
     store: EndpointBreakerStore<EndpointBreakerKey>,
@@ -60,12 +122,18 @@ struct EndpointBreakersInner {
 
 #[derive(Debug, Default)]
 struct DiscoveryMemberships {
-    owners: HashMap<DiscoveryOwner, HashSet<DiscoveryEndpoint>>,
+    owners: HashMap<DiscoveryOwner, OwnerMembership>,
     references: HashMap<DiscoveryEndpoint, usize>,
+    instance_references: HashMap<DiscoveryInstance, usize>,
+    /// When instances that joined after their owner's first snapshot became discoverable.
+    joined: HashMap<DiscoveryInstance, Instant>,
 }
 
 /// Runtime-owned endpoint breaker cache and discovery membership index.
 ///
+/// The index also remembers when an instance joined an already populated directory, which drives
+/// the slow-start weight ramp.
+///
 /// The membership lock is always acquired before the store lock. Discovery lookup holds both
 /// across membership validation and insertion, so a concurrent snapshot update cannot resurrect
 /// an endpoint after removing it from the active membership.
@@ -113,6 +181,24 @@ impl EndpointBreakers {
         }
     }
 
+    /// Returns whether the cached breaker for an endpoint is open.
+    ///
+    /// Unlike [`Self::get_or_insert_observed`], this never creates a breaker and never takes the
+    /// membership lock; an endpoint without a cached breaker is treated as closed, which is what
+    /// a fresh breaker would report.
+    pub(crate) fn is_open(
+        &self,
+        service: &str,
+        binding_id: &HttpBindingId,
+        source: EndpointBreakerSource,
+        endpoint: &str,
+    ) -> bool {
+        let key = (service, binding_id, source, endpoint);
+        self.inner
+            .store
+            .is_open(&key as &dyn EndpointBreakerKeyView)
+    }
+
     pub(crate) fn replace_discovery(
         &self,
         selector: &ServiceSelector,
@@ -121,26 +207,71 @@ impl EndpointBreakers {
         let owner = DiscoveryOwner {
             selector: selector.clone(),
         };
-        let current = instances
-            .iter()
-            .map(|instance| DiscoveryEndpoint {
-                service: selector.identity().to_owned(),
-                endpoint: instance.endpoint().as_str().to_owned(),
-            })
-            .collect::<HashSet<_>>();
+        let current = OwnerMembership {
+            endpoints: instances
+                .iter()
+                .map(|instance| DiscoveryEndpoint {
+                    service: selector.identity().to_owned(),
+                    endpoint: instance.endpoint().as_str().to_owned(),
+                })
+                .collect(),
+            instances: instances
+                .iter()
+                .map(|instance| DiscoveryInstance {
+                    service: selector.identity().to_owned(),
+                    instance_id: instance.instance_id().clone(),
+                })
+                .collect(),
+        };
+        let now = Instant::now();
         let mut memberships = self
             .inner
             .memberships
             .lock()
             .unwrap_or_else(|error| error.into_inner());
+        let memberships = &mut *memberships;
+        let populated = memberships.owners.contains_key(&owner);
         let previous = memberships.owners.remove(&owner).unwrap_or_default();
-        let evicted =
-            decrement_references(&mut memberships.references, previous.difference(&current));
-        increment_references(&mut memberships.references, current.difference(&previous));
+        let evicted = decrement_references(
+            &mut memberships.references,
+            previous.endpoints.difference(&current.endpoints),
+        );
+        increment_references(
+            &mut memberships.references,
+            current.endpoints.difference(&previous.endpoints),
+        );
+        for departed in decrement_references(
+            &mut memberships.instance_references,
+            previous.instances.difference(&current.instances),
+        ) {
+            memberships.joined.remove(&departed);
+        }
+        for arrived in increment_references(
+            &mut memberships.instance_references,
+            current.instances.difference(&previous.instances),
+        ) {
+            if populated {
+                memberships.joined.insert(arrived, now);
+            }
+        }
         memberships.owners.insert(owner, current);
         self.remove_cached(evicted);
     }
 
+    /// Returns when a discovered instance joined a populated directory, if it did.
+    pub(crate) fn joined_at(&self, service: &str, instance_id: &InstanceId) -> Option<Instant> {
+        self.inner
+            .memberships
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .joined
+            .get(&DiscoveryInstance {
+                service: service.to_owned(),
+                instance_id: instance_id.clone(),
+            })
+            .copied()
+    }
+
     pub(crate) fn remove_discovery(&self, selector: &ServiceSelector) {
         let owner = DiscoveryOwner {
             selector: selector.clone(),
@@ -150,8 +281,15 @@ impl EndpointBreakers {
             .memberships
             .lock()
             .unwrap_or_else(|error| error.into_inner());
+        let memberships = &mut *memberships;
         let removed = memberships.owners.remove(&owner).unwrap_or_default();
-        let evicted = decrement_references(&mut memberships.references, removed.iter());
+        let evicted = decrement_references(&mut memberships.references, removed.endpoints.iter());
+        for departed in decrement_references(
+            &mut memberships.instance_references,
+            removed.instances.iter(),
+        ) {
+            memberships.joined.remove(&departed);
+        }
         self.remove_cached(evicted);
     }
 
@@ -167,20 +305,32 @@ impl EndpointBreakers {
     }
 }
 
-fn increment_references<'a>(
-    references: &mut HashMap<DiscoveryEndpoint, usize>,
-    endpoints: impl Iterator<Item = &'a DiscoveryEndpoint>,
-) {
+/// Adds one reference per member and returns the members that were not referenced before.
+fn increment_references<'a, K>(
+    references: &mut HashMap<K, usize>,
+    endpoints: impl Iterator<Item = &'a K>,
+) -> Vec<K>
+where
+    K: Clone + Eq + std::hash::Hash + 'a,
+{
+    let mut added = Vec::new();
     for endpoint in endpoints {
         let count = references.entry(endpoint.clone()).or_default();
+        if *count == 0 {
+            added.push(endpoint.clone());
+        }
         *count = count.saturating_add(1);
     }
+    added
 }
 
-fn decrement_references<'a>(
-    references: &mut HashMap<DiscoveryEndpoint, usize>,
-    endpoints: impl Iterator<Item = &'a DiscoveryEndpoint>,
-) -> Vec<DiscoveryEndpoint> {
+fn decrement_references<'a, K>(
+    references: &mut HashMap<K, usize>,
+    endpoints: impl Iterator<Item = &'a K>,
+) -> Vec<K>
+where
+    K: Clone + Eq + std::hash::Hash + 'a,
+{
     let mut evicted = Vec::new();
     for endpoint in endpoints {
         let remove = match references.get_mut(endpoint) {
@@ -202,7 +352,7 @@ fn decrement_references<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::resilience::breaker::DEFAULT_ENDPOINT_IDLE_EVICTION;
+    use crate::resilience::breaker::{DEFAULT_ENDPOINT_IDLE_EVICTION, FailureClass};
     use fusen_contract::{
         EndpointCapabilities, HttpBindingId, InstanceId, ServiceEndpoint, ServiceWeight,
     };
@@ -286,6 +436,57 @@ mod tests {
         assert!(Arc::ptr_eq(&retained, &retained_again));
     }
 
+    #[test]
+    fn open_lookups_never_create_breakers() {
+        let breakers = breakers();
+        let selector = selector();
+        let binding = HttpBindingId::default();
+        let discovered = instance("first", 8001);
+        breakers.replace_discovery(&selector, std::slice::from_ref(&discovered));
+        let endpoint = discovered.endpoint().as_str();
+        let is_open = |source| breakers.is_open(selector.identity(), &binding, source, endpoint);
+        assert!(!is_open(EndpointBreakerSource::Discovery));
+        assert!(breakers.inner.store.is_empty());
+
+        let breaker = breakers.get_or_insert_observed(
+            selector.identity(),
+            &binding,
+            EndpointBreakerSource::Discovery,
+            endpoint,
+            observer(),
+        );
+        for _ in 0..20 {
+            breaker.try_acquire().unwrap().fail(FailureClass::Transport);
+        }
+        assert!(is_open(EndpointBreakerSource::Discovery));
+        assert!(!is_open(EndpointBreakerSource::Direct));
+        assert_eq!(breakers.inner.store.len(), 1);
+    }
+
+    #[test]
+    fn only_instances_joining_a_populated_directory_are_warming() {
+        let breakers = breakers();
+        let selector = selector();
+        let initial = instance("initial", 8001);
+        let joined = instance("joined", 8002);
+        breakers.replace_discovery(&selector, std::slice::from_ref(&initial));
+        breakers.replace_discovery(&selector, &[initial.clone(), joined.clone()]);
+        let service = selector.identity();
+        assert!(breakers.joined_at(service, initial.instance_id()).is_none());
+        let joined_at = breakers.joined_at(service, joined.instance_id()).unwrap();
+
+        breakers.replace_discovery(&selector, &[joined.clone(), initial.clone()]);
+        assert_eq!(
+            breakers.joined_at(service, joined.instance_id()),
+            Some(joined_at)
+        );
+        breakers.replace_discovery(&selector, std::slice::from_ref(&initial));
+        assert!(breakers.joined_at(service, joined.instance_id()).is_none());
+        breakers.remove_discovery(&selector);
+        breakers.replace_discovery(&selector, &[initial, joined.clone()]);
+        assert!(breakers.joined_at(service, joined.instance_id()).is_none());
+    }
+
     #[test]
     fn discovery_cleanup_does_not_prune_direct_or_other_owner_entries() {
         let breakers = breakers();
@@ -1,18 +1,27 @@
 use super::{
     endpoint_breakers::EndpointBreakerSource,
-    runtime::{CLIENT_RUNNING, ClientHttpBinding, ClientRuntimeInner},
+    runtime::{CLIENT_RUNNING, ClientHttpBinding, ClientRuntimeInner, MethodPolicy},
     transport::{HttpTransport, TransportFailureKind, circuit_open},
 };
+#[cfg(feature = "compression")]
+use crate::wire::accept_encoding;
 use crate::{
-    Arguments, Body, Call, Context, Error, ErrorCategory, InstanceRouter, InstanceSnapshot,
-    InterceptionStage, Interceptor, LoadBalancer, Response, RouteRequest, Side,
+    Arguments, AttemptCompletion, AttemptOutcome, Body, BodyFrames, Call, Context, Error,
+    ErrorCategory, InstanceRouter, InstanceSnapshot, InterceptionStage, Interceptor, LoadBalancer,
+    Response, ResponseStream, RouteRequest, Side, SlowStartConfig,
     context::{ContextParts, ResponseAttemptCompletion},
     interceptor::{InterceptorResult, Next, Terminal},
+    policy::instance_locality,
     resilience::{
         FailureClass,
         breaker::{BreakerPermit, BreakerRejection},
         classify::{ClassifiedError, classify_error},
-        retry::{RetryDecision, RetryDecisionContext, decide_with_guards, full_jitter_backoff},
+        hedge::LatencyWindow,
+        limit::{AttemptLimitPermit, LimitSample},
+        retry::{
+            RetryDecision, RetryDecisionContext, decide_with_guards, full_jitter_backoff,
+            has_next_attempt,
+        },
     },
     runtime::{
         BoxFuture,
@@ -20,35 +29,46 @@ use crate::{
         deadline::Deadline,
         metrics::SafeMetrics,
     },
-    wire::{decode_http_response, encode_request_template, remote_protocol_error},
+    wire::{
+        GuardedBody, UploadFailure, decode_http_response, encode_request_template, has_body_stream,
+        remote_protocol_error,
+    },
 };
 use fusen_contract::{
-    EndpointCapabilities, HttpBindingId, HttpVersionPolicy, HttpVersionSet, InstanceId,
-    MethodDescriptor, MethodId, ServiceDescriptor, ServiceEndpoint, ServiceInstance, ServiceWeight,
+    ContractError, EndpointCapabilities, HttpBindingId, HttpVersionPolicy, HttpVersionSet,
+    InstanceId, Metadata, MethodDescriptor, MethodId, ServiceDescriptor, ServiceEndpoint,
+    ServiceInstance, ServiceWeight,
 };
 use fusen_observability::{
     AdmissionRejectedEvent, AttemptFinishedEvent, InvocationFinishedEvent, InvocationStartedEvent,
     MetricEvent, MetricOutcome, MetricSide,
 };
 use fusen_register::directory::{Directory, DirectoryState};
+use futures_util::{StreamExt, stream, stream::FuturesUnordered};
+#[cfg(feature = "compression")]
+use http::header::ACCEPT_ENCODING;
 use serde::de::DeserializeOwned;
 #[cfg(test)]
 use serde_json::Value;
 use std::{
     collections::HashSet,
     panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
     sync::{
         Arc, Mutex,
-        atomic::{AtomicU8, Ordering},
+        atomic::{AtomicU8, AtomicU64, Ordering},
     },
     time::{Duration, Instant as StdInstant},
 };
+use tokio::time::Instant as TokioInstant;
+use tokio_util::sync::WaitForCancellationFutureOwned;
 use tracing::Instrument;
 
 pub(crate) enum EndpointSource {
     Direct {
         endpoint: ServiceEndpoint,
         capabilities: Option<EndpointCapabilities>,
+        metadata: Metadata,
     },
     Discovery(Directory),
 }
@@ -70,6 +90,15 @@ pub(crate) struct ServiceClientInner {
     pub attempt_interceptor: Arc<[Arc<dyn Interceptor>]>,
     pub routers: Arc<[Arc<dyn InstanceRouter>]>,
     pub load_balancer: Arc<dyn LoadBalancer>,
+    /// Revision of the last discovery snapshot reported to the load balancer.
+    pub discovered_revision: AtomicU64,
+    pub methods: Arc<[MethodPolicy]>,
+}
+
+impl ServiceClientInner {
+    fn method_policy(&self, context: &Context) -> &MethodPolicy {
+        &self.methods[context.method().id().index()]
+    }
 }
 
 impl ServiceClient {
@@ -83,6 +112,62 @@ impl ServiceClient {
     where
         T: DeserializeOwned,
         F: FnOnce() -> Result<Arguments, Error> + Send,
+    {
+        self.invoke_with(method_id, call, encode, |response, _| {
+            match catch_unwind(AssertUnwindSafe(|| {
+                serde_json::from_slice(response.result_bytes())
+            })) {
+                Ok(Ok(value)) => Ok(value),
+                Ok(Err(error)) => Err(ResultDecodeFailure::Invalid(error)),
+                Err(_) => Err(ResultDecodeFailure::Panicked),
+            }
+        })
+        .await
+    }
+
+    /// Executes one server-streaming invocation; the logical deadline covers the response head.
+    ///
+    /// Items are decoded as frames arrive and keep the invocation admitted until the stream is
+    /// dropped or ends. Client shutdown cancels streams that outlive the drain period.
+    pub async fn invoke_stream<T, F>(
+        &self,
+        method_id: MethodId,
+        call: Call,
+        encode: F,
+    ) -> Result<Response<ResponseStream<T>>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: FnOnce() -> Result<Arguments, Error> + Send,
+    {
+        let force_cancel = self.inner.runtime.force_cancel.clone();
+        self.invoke_with(method_id, call, encode, move |response, scope| {
+            let body = response.take_body();
+            let frames = body
+                .take_frames()
+                .unwrap_or_else(|| Box::pin(stream::iter([Ok(body.into_frame())])));
+            Ok(decode_items(
+                frames,
+                ItemDecoder {
+                    cancel: Box::pin(force_cancel.cancelled_owned()),
+                    _admission: scope.admission.clone(),
+                    request_id: scope.request_id.to_owned(),
+                    remote: scope.remote,
+                },
+            ))
+        })
+        .await
+    }
+
+    async fn invoke_with<T, F, D>(
+        &self,
+        method_id: MethodId,
+        call: Call,
+        encode: F,
+        decode: D,
+    ) -> Result<Response<T>, Error>
+    where
+        F: FnOnce() -> Result<Arguments, Error> + Send,
+        D: FnOnce(&mut Response<Body>, DecodeScope<'_>) -> Result<T, ResultDecodeFailure>,
     {
         let method = self
             .inner
@@ -93,9 +178,10 @@ impl ServiceClient {
         if self.inner.runtime.state.load(Ordering::Acquire) != CLIENT_RUNNING {
             return Err(closed_invocation().with_request_id(request_id));
         }
-        let deadline = Deadline::after(self.inner.runtime.config.request_timeout());
-        let _admission = acquire_admission(&self.inner.runtime, deadline)
+        let deadline = Deadline::after(self.inner.methods[method_id.index()].request_timeout);
+        let admission = acquire_admission(&self.inner.runtime, deadline)
             .await
+            .map(Arc::new)
             .map_err(|error| error.with_request_id(request_id.clone()))?;
         let started = StdInstant::now();
         self.inner
@@ -121,7 +207,7 @@ impl ServiceClient {
         let attempts_started = Arc::new(AtomicU8::new(0));
         let invocation_attempts = attempts_started.clone();
         let invocation = async move {
-            let (headers, extensions) = call.into_parts();
+            let (headers, extensions, body_stream) = call.into_parts();
             let arguments = match catch_unwind(AssertUnwindSafe(encode)) {
                 Ok(result) => result?,
                 Err(_) => {
@@ -139,7 +225,7 @@ impl ServiceClient {
                 .transport()
                 .map_err(|_| closed_invocation())?;
             let response_request_id = invocation_request_id.clone();
-            let context = Context::new(ContextParts {
+            let mut context = Context::new(ContextParts {
                 side: Side::Client,
                 stage: InterceptionStage::ClientCall,
                 request_id: invocation_request_id,
@@ -162,6 +248,7 @@ impl ServiceClient {
                 response_wire_overhead: 0,
                 response_budget: self.inner.runtime.response_budget.clone(),
             });
+            context.set_body_stream(body_stream);
             let terminal = InvocationTerminal {
                 client: self.inner.as_ref(),
                 transport,
@@ -190,11 +277,16 @@ impl ServiceClient {
                 terminal.succeed_endpoint_breaker();
                 None
             };
-            let decoded = catch_unwind(AssertUnwindSafe(|| {
-                serde_json::from_slice(response.result_bytes())
-            }));
+            let decoded = decode(
+                &mut response,
+                DecodeScope {
+                    admission: &admission,
+                    request_id: &response_request_id,
+                    remote: remote_result,
+                },
+            );
             let value = match decoded {
-                Ok(Ok(value)) => {
+                Ok(value) => {
                     response.finish_attempt(None);
                     if let Some(permit) = endpoint_permit {
                         permit.succeed();
@@ -204,44 +296,8 @@ impl ServiceClient {
                     }
                     value
                 }
-                Ok(Err(error)) => {
-                    let error = if remote_result {
-                        remote_protocol_error(
-                            "invalid_result",
-                            "invocation result does not match the generated return type",
-                            &response_request_id,
-                        )
-                        .with_source(error)
-                    } else {
-                        Error::invalid_result(
-                            "invocation result does not match the generated return type",
-                            error,
-                        )
-                    };
-                    return Err(finish_result_failure(
-                        &mut response,
-                        endpoint_permit,
-                        service_permit,
-                        error,
-                        attempts,
-                        remote_result,
-                    ));
-                }
-                Err(_) => {
-                    tracing::error!("service invocation result deserialization panicked");
-                    let error = if remote_result {
-                        remote_protocol_error(
-                            "invalid_result",
-                            "invocation result does not match the generated return type",
-                            &response_request_id,
-                        )
-                    } else {
-                        Error::framework(
-                            ErrorCategory::DataLoss,
-                            "invalid_result",
-                            "invocation result does not match the generated return type",
-                        )
-                    };
+                Err(failure) => {
+                    let error = invalid_result(failure, &response_request_id, remote_result);
                     return Err(finish_result_failure(
                         &mut response,
                         endpoint_permit,
@@ -310,6 +366,72 @@ impl ServiceClient {
     }
 }
 
+enum ResultDecodeFailure {
+    Invalid(serde_json::Error),
+    Panicked,
+}
+
+struct DecodeScope<'a> {
+    admission: &'a Arc<AdmissionGuard>,
+    request_id: &'a str,
+    remote: bool,
+}
+
+fn invalid_result(failure: ResultDecodeFailure, request_id: &str, remote: bool) -> Error {
+    const MESSAGE: &str = "invocation result does not match the generated return type";
+    match failure {
+        ResultDecodeFailure::Invalid(error) if remote => {
+            remote_protocol_error("invalid_result", MESSAGE, request_id).with_source(error)
+        }
+        ResultDecodeFailure::Invalid(error) => Error::invalid_result(MESSAGE, error),
+        ResultDecodeFailure::Panicked => {
+            tracing::error!("service invocation result deserialization panicked");
+            if remote {
+                remote_protocol_error("invalid_result", MESSAGE, request_id)
+            } else {
+                Error::framework(ErrorCategory::DataLoss, "invalid_result", MESSAGE)
+            }
+        }
+    }
+}
+
+struct ItemDecoder {
+    cancel: Pin<Box<WaitForCancellationFutureOwned>>,
+    _admission: Arc<AdmissionGuard>,
+    request_id: String,
+    remote: bool,
+}
+
+// Decodes stream items until the first error, which ends the stream.
+fn decode_items<T>(frames: BodyFrames, decoder: ItemDecoder) -> ResponseStream<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    ResponseStream::new(stream::unfold(
+        Some((frames, decoder)),
+        |state| async move {
+            let (mut frames, mut decoder) = state?;
+            let frame = tokio::select! {
+                biased;
+                () = decoder.cancel.as_mut() => Err(cancelled()),
+                frame = frames.next() => frame?,
+            };
+            let item = frame.and_then(|frame| {
+                match catch_unwind(AssertUnwindSafe(|| serde_json::from_slice(&frame.bytes))) {
+                    Ok(Ok(value)) => Ok(value),
+                    Ok(Err(error)) => Err(ResultDecodeFailure::Invalid(error)),
+                    Err(_) => Err(ResultDecodeFailure::Panicked),
+                }
+                .map_err(|failure| invalid_result(failure, &decoder.request_id, decoder.remote))
+            });
+            match item {
+                Ok(value) => Some((Ok(value), Some((frames, decoder)))),
+                Err(error) => Some((Err(error.with_request_id(decoder.request_id)), None)),
+            }
+        },
+    ))
+}
+
 fn finish_result_failure(
     response: &mut Response<Body>,
     endpoint_permit: Option<BreakerPermit>,
@@ -376,11 +498,12 @@ impl InvocationTerminal<'_> {
     }
 
     async fn execute(&self, context: Context) -> InterceptorResult {
-        let service_breaker = self
+        let service_permit = self
             .client
-            .runtime
-            .service_breaker(self.client.service, &self.client.binding_id);
-        let service_permit = service_breaker.try_acquire().map_err(|_| circuit_open())?;
+            .method_policy(&context)
+            .service_breaker
+            .try_acquire()
+            .map_err(|_| circuit_open())?;
         match self.execute_attempts(context).await {
             Ok(AttemptSuccess {
                 mut response,
@@ -417,133 +540,80 @@ impl InvocationTerminal<'_> {
         let mut attempted_endpoints = HashSet::new();
         let mut attempt = 1u8;
         let head = *context.method().http_operation().method() == http::Method::HEAD;
+        let hedging = self.hedging(&context);
+        let mut running = FuturesUnordered::new();
+        let mut pending = Vec::new();
+        let started = StdInstant::now();
+        let selected = self.select_endpoint(
+            &attempt_context(&context, attempt),
+            &attempted_endpoints,
+            false,
+        )?;
+        running.push(self.start_attempt(
+            &context,
+            attempt,
+            started,
+            head,
+            selected,
+            &mut attempted_endpoints,
+            &mut pending,
+        ));
+        let mut hedge_at = hedging
+            .as_ref()
+            .map(|(_, delay)| TokioInstant::now() + *delay);
         loop {
-            let started = StdInstant::now();
-            let mut attempt_context = context.clone();
-            attempt_context.set_attempt(attempt);
-            let selected = self.select_endpoint(&attempt_context, &attempted_endpoints)?;
-            let endpoint_key = selected.instance.endpoint().as_str().to_owned();
-            attempt_context.set_stage(InterceptionStage::ClientAttempt);
-            attempt_context.set_endpoint(selected.instance.clone());
-            if !selected.auto_negotiate {
-                attempt_context.set_http_version(selected.http_version);
-            }
-            attempted_endpoints.insert(endpoint_key.clone());
-            let terminal = AttemptTerminal {
-                client: self.client,
-                transport: &self.transport,
-                endpoint: &selected.instance,
-                endpoint_key: &endpoint_key,
-                attempt,
-                started,
-                head,
-                http_version: selected.http_version,
-                auto_negotiate: selected.auto_negotiate,
-                invocation_controls: selected.invocation_controls,
-                attempts_started: self.attempts_started.as_ref(),
-                observation: Mutex::new(AttemptObservation::default()),
+            let hedge = async {
+                match hedge_at {
+                    Some(at) => tokio::time::sleep_until(at).await,
+                    None => std::future::pending().await,
+                }
             };
-            let result = Next::new(&self.client.attempt_interceptor, &terminal)
-                .run(attempt_context)
-                .await;
-            let observation = terminal.observation();
-            let failure = match &result {
-                Ok(_) => {
-                    let mut response = result.expect("successful attempt contains a response");
-                    response.seal_attempt_duration(started.elapsed());
-                    if let Some(failure) = observation.failure {
-                        self.client
-                            .runtime
-                            .metrics
-                            .record(&MetricEvent::AttemptFinished(AttemptFinishedEvent::new(
-                                self.client.binding_id.as_str(),
-                                attempt_http_version_name(
-                                    selected.auto_negotiate,
-                                    selected.http_version,
-                                    observation.http_version,
-                                ),
-                                self.client.service.selector().service_id(),
-                                context.method().invocation_name(),
-                                attempt,
-                                if failure == FailureClass::Timeout {
-                                    MetricOutcome::Timeout
-                                } else {
-                                    MetricOutcome::Error
-                                },
-                                Some(failure_name(failure)),
-                                started.elapsed(),
-                            )));
-                        selected.breaker_permit.fail(failure);
-                        response.set_attempts(self.attempts_started.load(Ordering::Acquire));
-                        return Ok(AttemptSuccess {
-                            response,
-                            endpoint_breaker_permit: None,
-                            service_breaker_failure: Some(failure),
-                        });
+            let run = tokio::select! {
+                biased;
+                Some(run) = running.next() => run,
+                () = hedge => {
+                    hedge_at = None;
+                    let started = StdInstant::now();
+                    if let Some(selected) = self.select_hedge(&context, attempt, &attempted_endpoints) {
+                        attempt = attempt.saturating_add(1);
+                        running.push(self.start_attempt(
+                            &context,
+                            attempt,
+                            started,
+                            head,
+                            selected,
+                            &mut attempted_endpoints,
+                            &mut pending,
+                        ));
+                        hedge_at = hedging
+                            .as_ref()
+                            .map(|(_, delay)| TokioInstant::now() + *delay);
                     }
-                    response.set_attempts(self.attempts_started.load(Ordering::Acquire));
-                    let endpoint_breaker_permit =
-                        if self.attempts_started.load(Ordering::Acquire) >= attempt {
-                            Some(selected.breaker_permit)
-                        } else {
-                            selected.breaker_permit.release_unattempted();
-                            None
-                        };
-                    return Ok(AttemptSuccess {
-                        response,
-                        endpoint_breaker_permit,
-                        service_breaker_failure: None,
-                    });
+                    continue;
                 }
-                Err(error) => {
-                    let failure = observation.failure.unwrap_or_else(|| classify_error(error));
-                    if let Some(breaker_failure) = observation.failure {
-                        selected.breaker_permit.fail(breaker_failure);
-                    } else if observation.transport_succeeded {
-                        selected.breaker_permit.succeed();
-                    } else if self.attempts_started.load(Ordering::Acquire) < attempt {
-                        selected.breaker_permit.release_unattempted();
-                    } else {
-                        drop(selected.breaker_permit);
+            };
+            pending.retain(|entry: &PendingAttempt| entry.attempt != run.attempt);
+            let failed = match self.finish_attempt(&context, run, hedging.as_ref()) {
+                Ok(success) => {
+                    drop(running);
+                    for loser in pending {
+                        self.supersede_attempt(&context, loser);
                     }
-                    failure
+                    return Ok(success);
                 }
+                Err(failed) => failed,
             };
-            if self.attempts_started.load(Ordering::Acquire) >= attempt {
-                self.client
-                    .runtime
-                    .metrics
-                    .record(&MetricEvent::AttemptFinished(AttemptFinishedEvent::new(
-                        self.client.binding_id.as_str(),
-                        attempt_http_version_name(
-                            selected.auto_negotiate,
-                            selected.http_version,
-                            observation.http_version,
-                        ),
-                        self.client.service.selector().service_id(),
-                        context.method().invocation_name(),
-                        attempt,
-                        if failure == FailureClass::Timeout {
-                            MetricOutcome::Timeout
-                        } else {
-                            MetricOutcome::Error
-                        },
-                        Some(failure_name(failure)),
-                        started.elapsed(),
-                    )));
+            if !running.is_empty() {
+                continue;
             }
-            let error = result
-                .expect_err("failed attempt contains an invocation error")
-                .with_attempts(self.attempts_started.load(Ordering::Acquire));
-            let retry_after = if observation.failure.is_some() {
-                observation.retry_after
-            } else {
-                error.retry_hint().retry_after()
-            };
-            let (base, cap) = (
-                self.client.runtime.config.retry().backoff_base(),
-                self.client.runtime.config.retry().backoff_cap(),
-            );
+            let AttemptFailure {
+                error,
+                failure,
+                retry_after,
+            } = failed;
+            let error = error.with_attempts(self.attempts_started.load(Ordering::Acquire));
+            let policy = self.client.method_policy(&context);
+            let (base, cap) = (policy.retry.backoff_base(), policy.retry.backoff_cap());
             let mut delay = {
                 let mut rng = rand::rng();
                 full_jitter_backoff(base, cap, attempt, &mut rng)
@@ -557,20 +627,16 @@ impl InvocationTerminal<'_> {
             }
             let decision = RetryDecisionContext::new(
                 attempt,
-                self.client.runtime.config.retry().max_attempts(),
+                policy.retry.max_attempts(),
                 context.method().allows_retries(),
                 failure,
                 remaining,
             );
-            let budget = self
-                .client
-                .runtime
-                .retry_budget(self.client.service, &self.client.binding_id);
             let policy_decision = catch_unwind(AssertUnwindSafe(|| {
                 decide_with_guards(
                     self.client.runtime.retry_policy.as_ref(),
                     &decision,
-                    &budget,
+                    &policy.retry_budget,
                 )
             }));
             if !matches!(policy_decision, Ok(RetryDecision::Retry)) {
@@ -598,41 +664,312 @@ impl InvocationTerminal<'_> {
                 () = tokio::time::sleep(delay) => {}
             }
             attempt = attempt.saturating_add(1);
+            let started = StdInstant::now();
+            let selected = self.select_endpoint(
+                &attempt_context(&context, attempt),
+                &attempted_endpoints,
+                false,
+            )?;
+            running.push(self.start_attempt(
+                &context,
+                attempt,
+                started,
+                head,
+                selected,
+                &mut attempted_endpoints,
+                &mut pending,
+            ));
+            hedge_at = hedging
+                .as_ref()
+                .map(|(_, delay)| TokioInstant::now() + *delay);
+        }
+    }
+
+    /// Returns the latency window and hedge delay when this invocation may hedge.
+    fn hedging(&self, context: &Context) -> Option<(Arc<LatencyWindow>, Duration)> {
+        let config = self.client.method_policy(context).retry.hedging()?;
+        if !context.method().allows_retries() || has_body_stream(context.method()) {
+            return None;
+        }
+        let window = self.client.runtime.hedge_latency(
+            self.client.service,
+            &self.client.binding_id,
+            context.method(),
+        );
+        let delay = window.delay(config);
+        Some((window, delay))
+    }
+
+    /// Selects an untried endpoint for a hedge, spending one retry token.
+    fn select_hedge(
+        &self,
+        context: &Context,
+        launched: u8,
+        attempted: &HashSet<String>,
+    ) -> Option<SelectedEndpoint> {
+        let policy = self.client.method_policy(context);
+        if context.deadline().remaining().is_zero()
+            || !has_next_attempt(launched, policy.retry.max_attempts())
+        {
+            return None;
+        }
+        let selected = self
+            .select_endpoint(
+                &attempt_context(context, launched.saturating_add(1)),
+                attempted,
+                true,
+            )
+            .ok()?;
+        if policy.retry_budget.try_acquire() {
+            Some(selected)
+        } else {
+            selected.breaker_permit.release_unattempted();
+            None
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_attempt<'s>(
+        &'s self,
+        context: &Context,
+        attempt: u8,
+        started: StdInstant,
+        head: bool,
+        selected: SelectedEndpoint,
+        attempted: &mut HashSet<String>,
+        pending: &mut Vec<PendingAttempt>,
+    ) -> impl Future<Output = AttemptRun> + Send + 's {
+        let endpoint_key = selected.instance.endpoint().as_str().to_owned();
+        let mut attempt_context = attempt_context(context, attempt);
+        attempt_context.set_stage(InterceptionStage::ClientAttempt);
+        attempt_context.set_endpoint(selected.instance.clone());
+        if !selected.auto_negotiate {
+            attempt_context.set_http_version(selected.http_version);
+        }
+        attempted.insert(endpoint_key.clone());
+        let balancer =
+            BalancedAttempt::start(&self.client.load_balancer, &selected.instance, started);
+        let observation = Arc::new(Mutex::new(AttemptObservation::default()));
+        pending.push(PendingAttempt {
+            attempt,
+            started,
+            http_version: selected.http_version,
+            auto_negotiate: selected.auto_negotiate,
+            locality: instance_locality(&selected.instance).map(str::to_owned),
+            observation: observation.clone(),
+        });
+        async move {
+            let terminal = AttemptTerminal {
+                client: self.client,
+                transport: &self.transport,
+                endpoint: &selected.instance,
+                endpoint_key: &endpoint_key,
+                attempt,
+                started,
+                head,
+                http_version: selected.http_version,
+                auto_negotiate: selected.auto_negotiate,
+                invocation_controls: selected.invocation_controls,
+                attempts_started: self.attempts_started.as_ref(),
+                observation,
+            };
+            let result = Next::new(&self.client.attempt_interceptor, &terminal)
+                .run(attempt_context)
+                .await;
+            let observation = terminal.observation();
+            AttemptRun {
+                attempt,
+                started,
+                selected,
+                balancer,
+                observation,
+                result,
+            }
+        }
+    }
+
+    fn finish_attempt(
+        &self,
+        context: &Context,
+        run: AttemptRun,
+        hedging: Option<&(Arc<LatencyWindow>, Duration)>,
+    ) -> Result<AttemptSuccess, AttemptFailure> {
+        let AttemptRun {
+            attempt,
+            started,
+            selected,
+            balancer,
+            observation,
+            result,
+        } = run;
+        let http_version = attempt_http_version_name(
+            selected.auto_negotiate,
+            selected.http_version,
+            observation.http_version,
+        );
+        let error = match result {
+            Ok(mut response) => {
+                response.seal_attempt_duration(started.elapsed());
+                response.set_attempts(self.attempts_started.load(Ordering::Acquire));
+                if let Some(failure) = observation.failure {
+                    self.record_attempt(
+                        context,
+                        attempt,
+                        http_version,
+                        failure_outcome(failure),
+                        Some(failure),
+                        started.elapsed(),
+                        instance_locality(&selected.instance),
+                    );
+                    selected.breaker_permit.fail(failure);
+                    balancer.finish(AttemptOutcome::Failure(failure));
+                    return Ok(AttemptSuccess {
+                        response,
+                        endpoint_breaker_permit: None,
+                        service_breaker_failure: Some(failure),
+                    });
+                }
+                let endpoint_breaker_permit = if observation.started {
+                    if let Some((window, _)) = hedging {
+                        window.record(started.elapsed());
+                    }
+                    if matches!(self.client.source, EndpointSource::Discovery(_))
+                        && let Some(detector) = self
+                            .client
+                            .runtime
+                            .outlier_detector(self.client.service, &self.client.binding_id)
+                    {
+                        detector.record(
+                            selected.instance.endpoint().as_str(),
+                            started.elapsed(),
+                            StdInstant::now(),
+                        );
+                    }
+                    balancer.finish(AttemptOutcome::Success);
+                    Some(selected.breaker_permit)
+                } else {
+                    selected.breaker_permit.release_unattempted();
+                    None
+                };
+                return Ok(AttemptSuccess {
+                    response,
+                    endpoint_breaker_permit,
+                    service_breaker_failure: None,
+                });
+            }
+            Err(error) => error,
+        };
+        let failure = observation
+            .failure
+            .unwrap_or_else(|| classify_error(&error));
+        if let Some(breaker_failure) = observation.failure {
+            selected.breaker_permit.fail(breaker_failure);
+        } else if observation.transport_succeeded {
+            selected.breaker_permit.succeed();
+        } else if !observation.started {
+            selected.breaker_permit.release_unattempted();
+        } else {
+            drop(selected.breaker_permit);
+        }
+        if observation.started {
+            balancer.finish(AttemptOutcome::Failure(failure));
+            self.record_attempt(
+                context,
+                attempt,
+                http_version,
+                failure_outcome(failure),
+                Some(failure),
+                started.elapsed(),
+                instance_locality(&selected.instance),
+            );
+        }
+        let retry_after = if observation.failure.is_some() {
+            observation.retry_after
+        } else {
+            error.retry_hint().retry_after()
+        };
+        Err(AttemptFailure {
+            error,
+            failure,
+            retry_after,
+        })
+    }
+
+    /// Reports an attempt cancelled because a concurrent hedged attempt succeeded first.
+    fn supersede_attempt(&self, context: &Context, loser: PendingAttempt) {
+        let observation = *loser
+            .observation
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        if observation.started {
+            self.record_attempt(
+                context,
+                loser.attempt,
+                attempt_http_version_name(
+                    loser.auto_negotiate,
+                    loser.http_version,
+                    observation.http_version,
+                ),
+                MetricOutcome::Superseded,
+                None,
+                loser.started.elapsed(),
+                loser.locality.as_deref(),
+            );
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn record_attempt(
+        &self,
+        context: &Context,
+        attempt: u8,
+        http_version: Option<&'static str>,
+        outcome: MetricOutcome,
+        failure: Option<FailureClass>,
+        duration: Duration,
+        locality: Option<&str>,
+    ) {
+        self.client
+            .runtime
+            .metrics
+            .record(&MetricEvent::AttemptFinished(
+                AttemptFinishedEvent::new(
+                    self.client.binding_id.as_str(),
+                    http_version,
+                    self.client.service.selector().service_id(),
+                    context.method().invocation_name(),
+                    attempt,
+                    outcome,
+                    failure.map(failure_name),
+                    duration,
+                )
+                .with_locality(locality),
+            ));
+    }
+
     fn select_endpoint(
         &self,
         context: &Context,
         attempted: &HashSet<String>,
+        untried_only: bool,
     ) -> Result<SelectedEndpoint, Error> {
         let (mut instances, source) = match &self.client.source {
             EndpointSource::Direct {
                 endpoint,
                 capabilities,
-            } => {
-                let generic_versions = if endpoint.as_url().scheme() == "https" {
-                    HttpVersionSet::ALL
-                } else {
-                    HttpVersionSet::HTTP_1_1
-                };
-                (
-                    vec![ServiceInstance::new(
-                        InstanceId::new("direct").expect("static direct instance ID is valid"),
-                        endpoint.clone(),
-                        capabilities.clone().unwrap_or_else(|| {
-                            EndpointCapabilities::new(
-                                generic_versions,
-                                [self.client.binding_id.clone()],
-                                false,
-                            )
-                            .expect("direct generic capabilities are valid")
-                        }),
-                        ServiceWeight::default(),
-                    )],
-                    EndpointBreakerSource::Direct,
-                )
-            }
+                metadata,
+            } => (
+                vec![
+                    direct_instance(
+                        endpoint,
+                        capabilities.as_ref(),
+                        &self.client.binding_id,
+                        metadata,
+                    )
+                    .expect("direct metadata is validated when the client connects"),
+                ],
+                EndpointBreakerSource::Direct,
+            ),
             EndpointSource::Discovery(directory) => {
                 let snapshot = directory.snapshot();
                 if !matches!(
@@ -641,6 +978,21 @@ impl InvocationTerminal<'_> {
                 ) {
                     return Err(no_instances());
                 }
+                if self
+                    .client
+                    .discovered_revision
+                    .swap(snapshot.revision(), Ordering::Relaxed)
+                    != snapshot.revision()
+                {
+                    let discovered = InstanceSnapshot::new(snapshot.instances().to_vec());
+                    if catch_unwind(AssertUnwindSafe(|| {
+                        self.client.load_balancer.instances_discovered(&discovered);
+                    }))
+                    .is_err()
+                    {
+                        tracing::error!("load balancer panicked and was isolated");
+                    }
+                }
                 (
                     snapshot.instances().to_vec(),
                     EndpointBreakerSource::Discovery,
@@ -667,14 +1019,37 @@ impl InvocationTerminal<'_> {
         let has_untried = instances
             .iter()
             .any(|instance| !attempted.contains(instance.endpoint().as_str()));
+        if untried_only && !has_untried {
+            return Err(no_compatible_endpoint());
+        }
         if has_untried {
             instances.retain(|instance| !attempted.contains(instance.endpoint().as_str()));
         }
+        let now = StdInstant::now();
+        let detector = match source {
+            EndpointBreakerSource::Discovery => self
+                .client
+                .runtime
+                .outlier_detector(self.client.service, &self.client.binding_id),
+            EndpointBreakerSource::Direct => None,
+        };
+        let healthy = |instance: &ServiceInstance| {
+            let endpoint = instance.endpoint().as_str();
+            !detector
+                .as_ref()
+                .is_some_and(|detector| detector.is_ejected(endpoint, now))
+                && !self.client.runtime.endpoint_breaker_is_open(
+                    self.client.service,
+                    &self.client.binding_id,
+                    source,
+                    endpoint,
+                )
+        };
         let mut routed = InstanceSnapshot::new(instances);
         for router in self.client.routers.iter() {
             let input = routed.clone();
             let output = match catch_unwind(AssertUnwindSafe(|| {
-                router.route(RouteRequest::new(context, routed.clone()))
+                router.route(RouteRequest::new(context, routed.clone()).with_health(&healthy))
             })) {
                 Ok(Ok(instances)) => instances,
                 Ok(Err(error)) => return Err(error),
@@ -693,9 +1068,13 @@ impl InvocationTerminal<'_> {
         if routed.is_empty() {
             return Err(no_instances());
         }
+        let ejected = self.ejected_endpoints(source, &routed, now);
         let mut eligible = Vec::new();
         let mut permits = Vec::new();
         for instance in routed.iter() {
+            if ejected.contains(instance.endpoint().as_str()) {
+                continue;
+            }
             let breaker = self.client.runtime.endpoint_breaker(
                 self.client.service,
                 &self.client.binding_id,
@@ -713,6 +1092,13 @@ impl InvocationTerminal<'_> {
         if eligible.is_empty() {
             return Err(circuit_open());
         }
+        if source == EndpointBreakerSource::Discovery
+            && let Some(slow_start) = self.client.runtime.config.discovery().slow_start()
+        {
+            for instance in &mut eligible {
+                *instance = self.warming_weight(instance, slow_start, now);
+            }
+        }
         let eligible = InstanceSnapshot::new(eligible);
         let index = match catch_unwind(AssertUnwindSafe(|| {
             self.client.load_balancer.select(context, &eligible)
@@ -765,12 +1151,99 @@ impl InvocationTerminal<'_> {
     }
 }
 
-struct AttemptSuccess {
-    response: Response<Body>,
+impl InvocationTerminal<'_> {
+    /// Returns the routed discovery endpoints currently ejected as latency outliers.
+    ///
+    /// When every routed endpoint is ejected, none are skipped, so ejection never empties a
+    /// routable snapshot on its own.
+    fn ejected_endpoints(
+        &self,
+        source: EndpointBreakerSource,
+        routed: &InstanceSnapshot,
+        now: StdInstant,
+    ) -> HashSet<String> {
+        if source != EndpointBreakerSource::Discovery {
+            return HashSet::new();
+        }
+        let Some(detector) = self
+            .client
+            .runtime
+            .outlier_detector(self.client.service, &self.client.binding_id)
+        else {
+            return HashSet::new();
+        };
+        let ejected = routed
+            .iter()
+            .map(|instance| instance.endpoint().as_str())
+            .filter(|endpoint| detector.is_ejected(endpoint, now))
+            .map(str::to_owned)
+            .collect::<HashSet<_>>();
+        if ejected.len() == routed.len() {
+            return HashSet::new();
+        }
+        ejected
+    }
+
+    /// Scales the weight of an instance that is still inside its slow-start window.
+    fn warming_weight(
+        &self,
+        instance: &ServiceInstance,
+        slow_start: &SlowStartConfig,
+        now: StdInstant,
+    ) -> ServiceInstance {
+        let Some(joined) = self
+            .client
+            .runtime
+            .endpoint_breakers
+            .joined_at(self.client.service.identity(), instance.instance_id())
+        else {
+            return instance.clone();
+        };
+        let age = now.saturating_duration_since(joined);
+        if age >= slow_start.window() {
+            return instance.clone();
+        }
+        let ratio = (age.as_secs_f64() / slow_start.window().as_secs_f64())
+            .max(slow_start.min_weight_ratio());
+        ServiceWeight::new(instance.weight().get() * ratio).map_or_else(
+            |_| instance.clone(),
+            |weight| instance.clone().with_weight(weight),
+        )
+    }
+}
+
+struct AttemptSuccess {
+    response: Response<Body>,
     endpoint_breaker_permit: Option<BreakerPermit>,
     service_breaker_failure: Option<FailureClass>,
 }
 
+struct AttemptFailure {
+    error: Error,
+    failure: FailureClass,
+    retry_after: Option<Duration>,
+}
+
+/// A finished physical attempt together with the endpoint it consumed.
+struct AttemptRun {
+    attempt: u8,
+    started: StdInstant,
+    selected: SelectedEndpoint,
+    balancer: BalancedAttempt,
+    observation: AttemptObservation,
+    result: InterceptorResult,
+}
+
+/// An in-flight attempt that may be superseded by a concurrent hedge.
+struct PendingAttempt {
+    attempt: u8,
+    started: StdInstant,
+    http_version: http::Version,
+    auto_negotiate: bool,
+    locality: Option<String>,
+    observation: Arc<Mutex<AttemptObservation>>,
+}
+
 struct AttemptMetricState {
     finished: bool,
     duration: Option<Duration>,
@@ -784,10 +1257,12 @@ struct AttemptMetricCompletion {
     method: &'static MethodDescriptor,
     attempt: u8,
     started: StdInstant,
+    locality: Option<String>,
     state: Mutex<AttemptMetricState>,
 }
 
 impl AttemptMetricCompletion {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         metrics: SafeMetrics,
         binding_id: HttpBindingId,
@@ -796,6 +1271,7 @@ impl AttemptMetricCompletion {
         method: &'static MethodDescriptor,
         attempt: u8,
         started: StdInstant,
+        locality: Option<String>,
     ) -> Self {
         Self {
             metrics,
@@ -805,6 +1281,7 @@ impl AttemptMetricCompletion {
             method,
             attempt,
             started,
+            locality,
             state: Mutex::new(AttemptMetricState {
                 finished: false,
                 duration: None,
@@ -826,8 +1303,8 @@ impl AttemptMetricCompletion {
             Some(_) => MetricOutcome::Error,
             None => MetricOutcome::Success,
         };
-        self.metrics
-            .record(&MetricEvent::AttemptFinished(AttemptFinishedEvent::new(
+        self.metrics.record(&MetricEvent::AttemptFinished(
+            AttemptFinishedEvent::new(
                 self.binding_id.as_str(),
                 Some(http_version_name(self.http_version)),
                 self.service.selector().service_id(),
@@ -836,7 +1313,9 @@ impl AttemptMetricCompletion {
                 outcome,
                 failure.map(failure_name),
                 duration,
-            )));
+            )
+            .with_locality(self.locality.as_deref()),
+        ));
     }
 }
 
@@ -861,6 +1340,7 @@ impl Drop for AttemptMetricCompletion {
 
 #[derive(Clone, Copy, Default)]
 struct AttemptObservation {
+    started: bool,
     failure: Option<FailureClass>,
     retry_after: Option<Duration>,
     transport_succeeded: bool,
@@ -879,7 +1359,7 @@ struct AttemptTerminal<'a> {
     auto_negotiate: bool,
     invocation_controls: bool,
     attempts_started: &'a AtomicU8,
-    observation: Mutex<AttemptObservation>,
+    observation: Arc<Mutex<AttemptObservation>>,
 }
 
 impl AttemptTerminal<'_> {
@@ -911,7 +1391,29 @@ impl Terminal for AttemptTerminal<'_> {
                     "selected endpoint concurrency is exhausted",
                 )
             })?;
-            let template = encode_request_template(
+            let limit_permit = self
+                .client
+                .runtime
+                .adaptive_permit(
+                    self.client.service,
+                    &self.client.binding_id,
+                    self.endpoint_key,
+                )
+                .map_err(|_| {
+                    self.client
+                        .runtime
+                        .metrics
+                        .record(&MetricEvent::AdmissionRejected(
+                            AdmissionRejectedEvent::new(MetricSide::Client, "adaptive_concurrency"),
+                        ));
+                    Error::framework(
+                        ErrorCategory::ResourceExhausted,
+                        "adaptive_concurrency_limited",
+                        "adaptive attempt concurrency limit is reached",
+                    )
+                })?;
+            #[cfg_attr(not(feature = "compression"), allow(unused_mut))]
+            let mut template = encode_request_template(
                 self.client.binding.request_encoder.as_ref(),
                 self.client.service,
                 context.method(),
@@ -926,7 +1428,15 @@ impl Terminal for AttemptTerminal<'_> {
                     .max_request_body_bytes(),
                 &self.client.runtime.request_budget,
             )?;
-            let request = template.to_request(
+            #[cfg(feature = "compression")]
+            if let Some(compression) = self.client.runtime.config.http().request_compression() {
+                template.compress(
+                    compression.coding(),
+                    compression.min_request_bytes(),
+                    &self.client.runtime.request_budget,
+                );
+            }
+            let mut request = template.to_request(
                 self.endpoint.endpoint(),
                 self.http_version,
                 context.request_id(),
@@ -935,6 +1445,27 @@ impl Terminal for AttemptTerminal<'_> {
                 self.invocation_controls,
                 self.client.service,
             )?;
+            #[cfg(feature = "compression")]
+            if let Some(compression) = self.client.runtime.config.http().compression() {
+                request
+                    .headers_mut()
+                    .insert(ACCEPT_ENCODING, accept_encoding(compression.codings()));
+            }
+            let upload = if has_body_stream(context.method()) {
+                let (body, failure) = GuardedBody::upload(
+                    context.take_body_stream()?,
+                    self.client
+                        .runtime
+                        .config
+                        .admission()
+                        .max_request_body_bytes(),
+                    &self.client.runtime.request_budget,
+                );
+                *request.body_mut() = body;
+                Some(failure)
+            } else {
+                None
+            };
             let attempt_span = tracing::info_span!(
                 "fusen.client.attempt",
                 request_id = %context.request_id(),
@@ -951,7 +1482,10 @@ impl Terminal for AttemptTerminal<'_> {
                     http_version_name(self.http_version),
                 );
             }
-            self.attempts_started.store(self.attempt, Ordering::Release);
+            self.attempts_started
+                .fetch_max(self.attempt, Ordering::AcqRel);
+            self.observe(|value| value.started = true);
+            let sent_at = StdInstant::now();
             let sent = tokio::select! {
                 biased;
                 () = self.client.runtime.force_cancel.cancelled() => {
@@ -959,6 +1493,7 @@ impl Terminal for AttemptTerminal<'_> {
                 }
                 result = context.deadline().run(
                     self.transport
+                        .pools(self.client.service.selector())
                         .send(request, self.auto_negotiate)
                         .instrument(attempt_span.clone())
                 ) => result,
@@ -968,9 +1503,14 @@ impl Terminal for AttemptTerminal<'_> {
                     self.observe(|value| {
                         value.failure = Some(FailureClass::Timeout);
                     });
+                    complete_limit(limit_permit, LimitSample::Overloaded);
                     return Err(deadline_exceeded());
                 }
                 Ok(Err(error)) => {
+                    // A failed body stream is the caller's error, not an endpoint failure.
+                    if let Some(error) = upload.as_ref().and_then(UploadFailure::take) {
+                        return Err(error);
+                    }
                     let failure = match error.kind {
                         TransportFailureKind::Connect => FailureClass::Connect,
                         TransportFailureKind::Io => FailureClass::Transport,
@@ -1014,6 +1554,7 @@ impl Terminal for AttemptTerminal<'_> {
                     self.observe(|value| {
                         value.failure = Some(FailureClass::Timeout);
                     });
+                    complete_limit(limit_permit, LimitSample::Overloaded);
                     Err(deadline_exceeded())
                 }
                 Ok(Err(error)) => {
@@ -1022,6 +1563,9 @@ impl Terminal for AttemptTerminal<'_> {
                         value.failure = Some(failure);
                         value.retry_after = error.retry_hint().retry_after();
                     });
+                    if failure == FailureClass::Overloaded {
+                        complete_limit(limit_permit, LimitSample::Overloaded);
+                    }
                     Err(error)
                 }
                 Ok(Ok(mut response)) => {
@@ -1034,8 +1578,10 @@ impl Terminal for AttemptTerminal<'_> {
                         context.method(),
                         self.attempt,
                         self.started,
+                        instance_locality(self.endpoint).map(str::to_owned),
                     )));
                     self.observe(|value| value.transport_succeeded = true);
+                    complete_limit(limit_permit, LimitSample::Latency(sent_at.elapsed()));
                     Ok(response)
                 }
             }
@@ -1043,6 +1589,57 @@ impl Terminal for AttemptTerminal<'_> {
     }
 }
 
+/// Reports one attempt to the load balancer and guarantees exactly one completion, which is
+/// `Cancelled` when the attempt future is dropped first.
+struct BalancedAttempt {
+    load_balancer: Arc<dyn LoadBalancer>,
+    instance: ServiceInstance,
+    started: StdInstant,
+    finished: bool,
+}
+
+impl BalancedAttempt {
+    fn start(
+        load_balancer: &Arc<dyn LoadBalancer>,
+        instance: &ServiceInstance,
+        started: StdInstant,
+    ) -> Self {
+        if catch_unwind(AssertUnwindSafe(|| load_balancer.attempt_started(instance))).is_err() {
+            tracing::error!("load balancer panicked and was isolated");
+        }
+        Self {
+            load_balancer: load_balancer.clone(),
+            instance: instance.clone(),
+            started,
+            finished: false,
+        }
+    }
+
+    fn finish(mut self, outcome: AttemptOutcome) {
+        self.report(outcome);
+    }
+
+    fn report(&mut self, outcome: AttemptOutcome) {
+        if std::mem::replace(&mut self.finished, true) {
+            return;
+        }
+        let completion = AttemptCompletion::new(&self.instance, self.started.elapsed(), outcome);
+        if catch_unwind(AssertUnwindSafe(|| {
+            self.load_balancer.attempt_finished(&completion);
+        }))
+        .is_err()
+        {
+            tracing::error!("load balancer panicked and was isolated");
+        }
+    }
+}
+
+impl Drop for BalancedAttempt {
+    fn drop(&mut self) {
+        self.report(AttemptOutcome::Cancelled);
+    }
+}
+
 struct UnattemptedBreakerPermit(Option<BreakerPermit>);
 
 impl UnattemptedBreakerPermit {
@@ -1071,6 +1668,37 @@ struct SelectedEndpoint {
     invocation_controls: bool,
 }
 
+/// Builds the single instance behind a `direct()` endpoint.
+///
+/// Without explicit capabilities the endpoint is assumed to serve only the client's binding,
+/// over HTTP/1.1 or, for HTTPS, any version ALPN negotiates.
+pub(crate) fn direct_instance(
+    endpoint: &ServiceEndpoint,
+    capabilities: Option<&EndpointCapabilities>,
+    binding_id: &HttpBindingId,
+    metadata: &Metadata,
+) -> Result<ServiceInstance, ContractError> {
+    let capabilities = match capabilities {
+        Some(capabilities) => capabilities.clone(),
+        None => {
+            let versions = if endpoint.as_url().scheme() == "https" {
+                HttpVersionSet::ALL
+            } else {
+                HttpVersionSet::HTTP_1_1
+            };
+            EndpointCapabilities::new(versions, [binding_id.clone()], false)
+                .expect("direct generic capabilities are valid")
+        }
+    };
+    ServiceInstance::new(
+        InstanceId::new("direct").expect("static direct instance ID is valid"),
+        endpoint.clone(),
+        capabilities,
+        ServiceWeight::default(),
+    )
+    .with_metadata(metadata.clone())
+}
+
 fn validate_router_output(
     input: &InstanceSnapshot,
     output: &InstanceSnapshot,
@@ -1108,7 +1736,9 @@ fn select_http_version(
     let secure = endpoint.as_url().scheme() == "https";
     match policy {
         HttpVersionPolicy::Auto => {
-            if secure && versions.contains(http::Version::HTTP_2) {
+            if secure && supports_http3(versions) {
+                Some(http::Version::HTTP_3)
+            } else if secure && versions.contains(http::Version::HTTP_2) {
                 Some(http::Version::HTTP_2)
             } else if versions.contains(http::Version::HTTP_11) {
                 Some(http::Version::HTTP_11)
@@ -1125,10 +1755,19 @@ fn select_http_version(
         HttpVersionPolicy::H2c => {
             (!secure && versions.contains(http::Version::HTTP_2)).then_some(http::Version::HTTP_2)
         }
+        #[cfg(feature = "http3")]
+        HttpVersionPolicy::Http3 => {
+            (secure && supports_http3(versions)).then_some(http::Version::HTTP_3)
+        }
         _ => None,
     }
 }
 
+// Advertised HTTP/3 is only usable when this build carries the QUIC transport.
+fn supports_http3(versions: HttpVersionSet) -> bool {
+    cfg!(feature = "http3") && versions.contains(http::Version::HTTP_3)
+}
+
 fn should_auto_negotiate(
     policy: HttpVersionPolicy,
     endpoint: &ServiceEndpoint,
@@ -1139,6 +1778,7 @@ fn should_auto_negotiate(
         && endpoint.as_url().scheme() == "https"
         && versions.contains(http::Version::HTTP_11)
         && versions.contains(http::Version::HTTP_2)
+        && !supports_http3(versions)
 }
 
 async fn acquire_admission(
@@ -1184,6 +1824,26 @@ async fn acquire_admission(
     }
 }
 
+fn complete_limit(permit: Option<AttemptLimitPermit>, sample: LimitSample) {
+    if let Some(permit) = permit {
+        permit.complete(sample);
+    }
+}
+
+fn attempt_context(context: &Context, attempt: u8) -> Context {
+    let mut context = context.clone();
+    context.set_attempt(attempt);
+    context
+}
+
+fn failure_outcome(failure: FailureClass) -> MetricOutcome {
+    if failure == FailureClass::Timeout {
+        MetricOutcome::Timeout
+    } else {
+        MetricOutcome::Error
+    }
+}
+
 fn failure_name(failure: FailureClass) -> &'static str {
     match failure {
         FailureClass::Connect => "connect",
@@ -1276,11 +1936,12 @@ fn attempt_http_version_name(
 mod tests {
     use super::*;
     use crate::{
-        Arguments, BreakerThreshold, BufferedResponse, Call, CircuitBreakerConfig,
-        ClientAdmissionConfig, ClientConfig, ClientRuntime, EncodedRequest, ErrorCode,
-        ErrorDecoder, ErrorKind, ErrorOrigin, InstanceRouter, InstanceSnapshot, InterceptorFuture,
-        LoadBalancer, RequestEncoder, RequestEncoding, ResponseDecoder, RetryConfig, RetryHint,
-        RouteRequest,
+        AdaptiveConcurrencyConfig, Arguments, BreakerThreshold, BufferedResponse, Call,
+        CircuitBreakerConfig, ClientAdmissionConfig, ClientConfig, ClientRuntime, DiscoveryConfig,
+        EncodedRequest, ErrorCode, ErrorDecoder, ErrorKind, ErrorOrigin, HedgingConfig,
+        InstanceRouter, InstanceSnapshot, InterceptorFuture, LoadBalancer, LocalityRouter,
+        MethodConfig, OutlierDetectionConfig, RequestEncoder, RequestEncoding, ResponseDecoder,
+        RetryConfig, RetryHint, RouteRequest,
         interceptor::erase_interceptor,
         resilience::breaker::BreakerState,
         runtime::budget::ByteBudget,
@@ -1293,7 +1954,7 @@ mod tests {
     use fusen_contract::{
         EndpointCapabilities, HttpBindingId, HttpOperation, HttpParameter,
         HttpParameterCardinality, HttpParameterSource, HttpVersionPolicy, HttpVersionSet,
-        MethodDescriptor, MethodId, ServiceInstance, ServiceSelector,
+        MethodClientDefaults, MethodDescriptor, MethodId, ServiceInstance, ServiceSelector,
     };
     use fusen_observability::MetricsRecorder;
     use fusen_register::directory::{DirectoryPublisher, DirectoryState, directory};
@@ -1307,6 +1968,7 @@ mod tests {
     use serde::Deserialize;
     use serde_json::json;
     use std::{
+        collections::HashMap,
         convert::Infallible,
         sync::{
             Arc, OnceLock,
@@ -1355,6 +2017,57 @@ mod tests {
         ));
     }
 
+    #[cfg(feature = "http3")]
+    #[test]
+    fn auto_prefers_advertised_http3_and_falls_back_to_http2() {
+        let binding = HttpBindingId::default();
+        let with_http3 = EndpointCapabilities::new(
+            HttpVersionSet::new([
+                http::Version::HTTP_11,
+                http::Version::HTTP_2,
+                http::Version::HTTP_3,
+            ])
+            .unwrap(),
+            [binding.clone()],
+            false,
+        )
+        .unwrap();
+        let without_http3 =
+            EndpointCapabilities::new(HttpVersionSet::ALL, [binding], false).unwrap();
+        let https: ServiceEndpoint = "https://service.example".parse().unwrap();
+        let http: ServiceEndpoint = "http://service.example".parse().unwrap();
+
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Auto, &https, &with_http3),
+            Some(http::Version::HTTP_3)
+        );
+        assert!(!should_auto_negotiate(
+            HttpVersionPolicy::Auto,
+            &https,
+            &with_http3
+        ));
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Auto, &https, &without_http3),
+            Some(http::Version::HTTP_2)
+        );
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Auto, &http, &with_http3),
+            Some(http::Version::HTTP_11)
+        );
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Http3, &https, &with_http3),
+            Some(http::Version::HTTP_3)
+        );
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Http3, &https, &without_http3),
+            None
+        );
+        assert_eq!(
+            select_http_version(HttpVersionPolicy::Http2, &https, &with_http3),
+            Some(http::Version::HTTP_2)
+        );
+    }
+
     #[derive(Debug)]
     struct CapturedAttempt {
         endpoint: &'static str,
@@ -1436,6 +2149,22 @@ mod tests {
         }
     }
 
+    #[derive(Clone, Default)]
+    struct AttemptLocalityMetrics {
+        finished: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl MetricsRecorder for AttemptLocalityMetrics {
+        fn record(&self, event: &MetricEvent<'_>) {
+            if let MetricEvent::AttemptFinished(event) = event {
+                self.finished
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .push(event.locality().map(str::to_owned));
+            }
+        }
+    }
+
     #[derive(Clone, Default)]
     struct CombinedMetrics {
         invocation: InvocationMetrics,
@@ -1467,6 +2196,79 @@ mod tests {
         }
     }
 
+    /// Records the instance ids and weights offered to the balancer, then picks the first.
+    #[derive(Clone, Default)]
+    struct OfferedWeights(Arc<Mutex<Vec<(String, f64)>>>);
+
+    impl LoadBalancer for OfferedWeights {
+        fn select(&self, _context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+            *self.0.lock().unwrap() = instances
+                .iter()
+                .map(|instance| {
+                    (
+                        instance.instance_id().as_str().to_owned(),
+                        instance.weight().get(),
+                    )
+                })
+                .collect();
+            Ok(0)
+        }
+    }
+
+    /// One hook call: the instance ID and, for `attempt_finished`, the outcome.
+    type AttemptHook = (String, Option<AttemptOutcome>);
+
+    /// Picks the first instance and records every attempt hook call.
+    #[derive(Clone, Default)]
+    struct ObservedAttempts(Arc<Mutex<Vec<AttemptHook>>>);
+
+    impl LoadBalancer for ObservedAttempts {
+        fn select(
+            &self,
+            _context: &Context,
+            _instances: &InstanceSnapshot,
+        ) -> Result<usize, Error> {
+            Ok(0)
+        }
+
+        fn attempt_started(&self, instance: &ServiceInstance) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((instance.instance_id().as_str().to_owned(), None));
+        }
+
+        fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+            self.0.lock().unwrap().push((
+                completion.instance().instance_id().as_str().to_owned(),
+                Some(completion.outcome()),
+            ));
+        }
+    }
+
+    /// Picks the first instance and records the instance IDs of every discovery report.
+    #[derive(Clone, Default)]
+    struct ObservedDiscoveries(Arc<Mutex<Vec<Vec<String>>>>);
+
+    impl LoadBalancer for ObservedDiscoveries {
+        fn select(
+            &self,
+            _context: &Context,
+            _instances: &InstanceSnapshot,
+        ) -> Result<usize, Error> {
+            Ok(0)
+        }
+
+        fn instances_discovered(&self, instances: &InstanceSnapshot) {
+            self.0.lock().unwrap().push(
+                instances
+                    .iter()
+                    .map(|instance| instance.instance_id().as_str().to_owned())
+                    .collect(),
+            );
+        }
+    }
+
     struct PanickingRouter;
 
     impl InstanceRouter for PanickingRouter {
@@ -1848,6 +2650,14 @@ mod tests {
     fn discovered_client(
         runtime: &ClientRuntime,
         instances: Vec<ServiceInstance>,
+    ) -> (DirectoryPublisher, ServiceClient) {
+        discovered_client_with_balancer(runtime, instances, Arc::new(FirstEndpoint))
+    }
+
+    fn discovered_client_with_balancer(
+        runtime: &ClientRuntime,
+        instances: Vec<ServiceInstance>,
+        load_balancer: Arc<dyn LoadBalancer>,
     ) -> (DirectoryPublisher, ServiceClient) {
         let (publisher, directory) = directory();
         runtime
@@ -1862,6 +2672,10 @@ mod tests {
             .get(&binding_id)
             .expect("default JSON binding is registered")
             .clone();
+        let methods = runtime
+            .inner
+            .method_policies(resilience_service(), &binding_id, &HashMap::new())
+            .unwrap();
         let client = ServiceClient {
             inner: Arc::new(ServiceClientInner {
                 runtime: runtime.inner.clone(),
@@ -1873,7 +2687,9 @@ mod tests {
                 interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 attempt_interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 routers: Arc::from(Vec::<Arc<dyn InstanceRouter>>::new()),
-                load_balancer: Arc::new(FirstEndpoint),
+                load_balancer,
+                discovered_revision: AtomicU64::new(0),
+                methods,
             }),
         };
         (publisher, client)
@@ -1887,6 +2703,10 @@ mod tests {
             .get(&binding_id)
             .expect("default JSON binding is registered")
             .clone();
+        let methods = runtime
+            .inner
+            .method_policies(resilience_service(), &binding_id, &HashMap::new())
+            .unwrap();
         ServiceClient {
             inner: Arc::new(ServiceClientInner {
                 runtime: runtime.inner.clone(),
@@ -1900,11 +2720,14 @@ mod tests {
                         EndpointCapabilities::new(HttpVersionSet::HTTP_1_1, [binding_id], true)
                             .unwrap(),
                     ),
+                    metadata: Metadata::new(),
                 },
                 interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 attempt_interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 routers: Arc::from(Vec::<Arc<dyn InstanceRouter>>::new()),
                 load_balancer: Arc::new(FirstEndpoint),
+                discovered_revision: AtomicU64::new(0),
+                methods,
             }),
         }
     }
@@ -1930,6 +2753,16 @@ mod tests {
         endpoint: ServiceEndpoint,
         binding_id: HttpBindingId,
         service: &'static ServiceDescriptor,
+    ) -> ServiceClient {
+        direct_client_with_methods(runtime, endpoint, binding_id, service, &HashMap::new())
+    }
+
+    fn direct_client_with_methods(
+        runtime: &ClientRuntime,
+        endpoint: ServiceEndpoint,
+        binding_id: HttpBindingId,
+        service: &'static ServiceDescriptor,
+        overrides: &HashMap<String, MethodConfig>,
     ) -> ServiceClient {
         let binding = runtime
             .inner
@@ -1937,6 +2770,10 @@ mod tests {
             .get(&binding_id)
             .expect("custom HTTP binding is registered")
             .clone();
+        let methods = runtime
+            .inner
+            .method_policies(service, &binding_id, overrides)
+            .unwrap();
         ServiceClient {
             inner: Arc::new(ServiceClientInner {
                 runtime: runtime.inner.clone(),
@@ -1950,11 +2787,14 @@ mod tests {
                         EndpointCapabilities::new(HttpVersionSet::HTTP_1_1, [binding_id], true)
                             .unwrap(),
                     ),
+                    metadata: Metadata::new(),
                 },
                 interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 attempt_interceptor: Arc::from(Vec::<Arc<dyn crate::Interceptor>>::new()),
                 routers: Arc::from(Vec::<Arc<dyn InstanceRouter>>::new()),
                 load_balancer: Arc::new(FirstEndpoint),
+                discovered_revision: AtomicU64::new(0),
+                methods,
             }),
         }
     }
@@ -2174,6 +3014,29 @@ mod tests {
         (endpoint, fixture)
     }
 
+    async fn spawn_stalled_endpoint(
+        captured: mpsc::UnboundedSender<CapturedAttempt>,
+    ) -> (ServiceEndpoint, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let fixture = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |request| {
+                let captured = captured.clone();
+                async move {
+                    capture_request("stalled", request, &captured).await;
+                    std::future::pending::<Result<HttpResponse<Full<Bytes>>, Infallible>>().await
+                }
+            });
+            let _ = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+        (endpoint, fixture)
+    }
+
     async fn spawn_full_endpoint(
         endpoint_name: &'static str,
         status: StatusCode,
@@ -2540,6 +3403,7 @@ mod tests {
         inner.source = EndpointSource::Direct {
             endpoint,
             capabilities: None,
+            metadata: Metadata::new(),
         };
         inner.attempt_interceptor = Arc::from([erase_interceptor(ObserveAutoHttpVersion {
             calls: observed.clone(),
@@ -2623,7 +3487,9 @@ mod tests {
                 response_wire_overhead: 0,
                 response_budget: runtime.inner.response_budget.clone(),
             });
-            let selected = terminal.select_endpoint(&context, &HashSet::new()).unwrap();
+            let selected = terminal
+                .select_endpoint(&context, &HashSet::new(), false)
+                .unwrap();
             assert_eq!(selected.instance.endpoint(), &first_endpoint);
             selected.breaker_permit.release_unattempted();
         }
@@ -2722,6 +3588,44 @@ mod tests {
         runtime.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn each_discovery_revision_is_reported_to_the_load_balancer_once() {
+        let runtime = ClientRuntime::builder().build().unwrap();
+        let observed = ObservedDiscoveries::default();
+        let first = instance("first", "http://127.0.0.1:1".parse().unwrap());
+        let (publisher, mut client) = discovered_client_with_balancer(
+            &runtime,
+            vec![first.clone()],
+            Arc::new(observed.clone()),
+        );
+        Arc::get_mut(&mut client.inner).unwrap().routers =
+            Arc::from([Arc::new(EmptyRouter) as Arc<dyn InstanceRouter>]);
+
+        for _ in 0..2 {
+            client
+                .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+                .await
+                .unwrap_err();
+        }
+        publisher
+            .publish_ready(vec![
+                first,
+                instance("second", "http://127.0.0.1:2".parse().unwrap()),
+            ])
+            .unwrap();
+        client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            *observed.0.lock().unwrap(),
+            [vec!["first"], vec!["first", "second"]]
+        );
+
+        drop(client);
+        runtime.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn ready_empty_discovery_snapshot_returns_no_instances_before_network_io() {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -3238,6 +4142,7 @@ mod tests {
         Arc::get_mut(&mut client.inner).unwrap().source = EndpointSource::Direct {
             endpoint,
             capabilities: None,
+            metadata: Metadata::new(),
         };
 
         let error = client
@@ -3523,6 +4428,592 @@ mod tests {
         runtime.shutdown().await.unwrap();
     }
 
+    fn tuned_service() -> &'static ServiceDescriptor {
+        static SERVICE: OnceLock<ServiceDescriptor> = OnceLock::new();
+        SERVICE.get_or_init(|| {
+            let method = |id, name: &str| {
+                MethodDescriptor::new(
+                    MethodId::new(id),
+                    name,
+                    HttpOperation::new(
+                        Method::GET,
+                        format!("/{name}"),
+                        Vec::new(),
+                        "application/json",
+                        "application/json",
+                    )
+                    .unwrap(),
+                )
+                .unwrap()
+            };
+            ServiceDescriptor::new(
+                ServiceSelector::new("tuned", None, None).unwrap(),
+                vec![
+                    method(0, "report").with_client_defaults(MethodClientDefaults::new(
+                        Some(Duration::from_secs(30)),
+                        Some(0),
+                    )),
+                    method(1, "lookup"),
+                ],
+            )
+            .unwrap()
+        })
+    }
+
+    #[tokio::test]
+    async fn method_policies_prefer_overrides_then_interface_defaults_then_runtime() {
+        let runtime = ClientRuntime::builder()
+            .config(resilience_config(
+                Duration::from_secs(2),
+                RetryConfig::default(),
+            ))
+            .build()
+            .unwrap();
+        let binding_id = HttpBindingId::default();
+        let shared_breaker = runtime.inner.service_breaker(tuned_service(), &binding_id);
+        let shared_budget = runtime.inner.retry_budget(tuned_service(), &binding_id);
+
+        let inherited = runtime
+            .inner
+            .method_policies(tuned_service(), &binding_id, &HashMap::new())
+            .unwrap();
+        assert_eq!(inherited[0].request_timeout, Duration::from_secs(30));
+        assert_eq!(inherited[0].retry.max_attempts(), 1);
+        assert_eq!(inherited[1].request_timeout, Duration::from_secs(2));
+        assert_eq!(inherited[1].retry.max_attempts(), 3);
+        for policy in inherited.iter() {
+            assert!(Arc::ptr_eq(&policy.service_breaker, &shared_breaker));
+            assert!(Arc::ptr_eq(&policy.retry_budget, &shared_budget));
+        }
+
+        let overrides = HashMap::from([
+            (
+                "report".to_owned(),
+                MethodConfig::builder()
+                    .request_timeout(Duration::from_secs(5))
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "lookup".to_owned(),
+                MethodConfig::builder()
+                    .retry(RetryConfig::builder().max_attempts(2).build().unwrap())
+                    .service_breaker(BreakerThreshold::service_builder().build().unwrap())
+                    .build()
+                    .unwrap(),
+            ),
+        ]);
+        let tuned = runtime
+            .inner
+            .method_policies(tuned_service(), &binding_id, &overrides)
+            .unwrap();
+        assert_eq!(tuned[0].request_timeout, Duration::from_secs(5));
+        assert_eq!(tuned[0].retry.max_attempts(), 1);
+        assert!(Arc::ptr_eq(&tuned[0].service_breaker, &shared_breaker));
+        assert_eq!(tuned[1].request_timeout, Duration::from_secs(2));
+        assert_eq!(tuned[1].retry.max_attempts(), 2);
+        assert!(!Arc::ptr_eq(&tuned[1].service_breaker, &shared_breaker));
+        assert!(!Arc::ptr_eq(&tuned[1].retry_budget, &shared_budget));
+
+        let unknown = HashMap::from([("missing".to_owned(), MethodConfig::default())]);
+        let error = runtime
+            .inner
+            .method_policies(tuned_service(), &binding_id, &unknown)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), crate::ClientErrorKind::Connect);
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn method_timeout_override_bounds_one_method_below_the_runtime_deadline() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (endpoint, fixture) = spawn_stalled_endpoint(captured_tx).await;
+        let runtime = ClientRuntime::builder()
+            .config(resilience_config(
+                Duration::from_secs(30),
+                RetryConfig::default(),
+            ))
+            .build()
+            .unwrap();
+        let overrides = HashMap::from([(
+            "call".to_owned(),
+            MethodConfig::builder()
+                .request_timeout(Duration::from_millis(100))
+                .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+                .build()
+                .unwrap(),
+        )]);
+        let client = direct_client_with_methods(
+            &runtime,
+            endpoint,
+            HttpBindingId::default(),
+            resilience_service(),
+            &overrides,
+        );
+
+        let started = StdInstant::now();
+        let error = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::DeadlineExceeded);
+        assert_eq!(error.attempts(), 1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(captured_rx.recv().await.unwrap().attempt, 1);
+
+        drop(client);
+        runtime.shutdown().await.unwrap();
+        fixture.abort();
+    }
+
+    #[derive(Clone, Default)]
+    struct RejectionMetrics {
+        reasons: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MetricsRecorder for RejectionMetrics {
+        fn record(&self, event: &MetricEvent<'_>) {
+            if let MetricEvent::AdmissionRejected(event) = event {
+                self.reasons
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .push(event.reason().to_owned());
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn adaptive_limit_rejects_attempts_beyond_the_endpoint_limit_until_one_finishes() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (endpoint, fixture) = spawn_stalled_endpoint(captured_tx).await;
+        let adaptive = AdaptiveConcurrencyConfig::builder()
+            .initial_limit(1)
+            .min_limit(1)
+            .max_limit(1)
+            .build()
+            .unwrap();
+        let config = ClientConfig::builder()
+            .request_timeout(Duration::from_millis(300))
+            .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+            .admission(
+                ClientAdmissionConfig::builder()
+                    .adaptive(adaptive)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let metrics = RejectionMetrics::default();
+        let runtime = ClientRuntime::builder()
+            .config(config)
+            .metrics(metrics.clone())
+            .build()
+            .unwrap();
+        let client = direct_client(&runtime, endpoint);
+
+        let first = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+                    .await
+            }
+        });
+        assert_eq!(captured_rx.recv().await.unwrap().attempt, 1);
+        let rejected = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap_err();
+        assert_eq!(rejected.category(), ErrorCategory::ResourceExhausted);
+        assert_eq!(rejected.code().as_str(), "adaptive_concurrency_limited");
+        assert_eq!(rejected.attempts(), 0);
+        assert_eq!(
+            *metrics.reasons.lock().unwrap(),
+            vec!["adaptive_concurrency".to_owned()]
+        );
+
+        let timed_out = first.await.unwrap().unwrap_err();
+        assert_eq!(timed_out.category(), ErrorCategory::DeadlineExceeded);
+        // The stalled fixture has gone away; what matters is that the freed permit admits the attempt.
+        let released = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap_err();
+        assert_ne!(released.code().as_str(), "adaptive_concurrency_limited");
+        assert_eq!(released.attempts(), 1);
+        assert_eq!(metrics.reasons.lock().unwrap().len(), 1);
+
+        drop(client);
+        runtime.shutdown().await.unwrap();
+        fixture.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn hedge_to_an_untried_endpoint_wins_and_supersedes_the_stalled_attempt() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (stalled_endpoint, stalled_fixture) = spawn_stalled_endpoint(captured_tx.clone()).await;
+        let (fast_endpoint, fast_fixture) = spawn_full_endpoint(
+            "fast",
+            StatusCode::OK,
+            Bytes::from_static(br#""hedged""#),
+            None,
+            captured_tx,
+        )
+        .await;
+        let hedging = HedgingConfig::builder()
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(20))
+            .build()
+            .unwrap();
+        let config = resilience_config(
+            Duration::from_secs(2),
+            RetryConfig::builder()
+                .budget_capacity(5)
+                .hedging(hedging)
+                .build()
+                .unwrap(),
+        );
+        let metrics = AttemptMetrics::default();
+        let runtime = ClientRuntime::builder()
+            .config(config)
+            .metrics(metrics.clone())
+            .build()
+            .unwrap();
+        let (_publisher, client) = discovered_client(
+            &runtime,
+            vec![
+                instance("stalled", stalled_endpoint),
+                instance("fast", fast_endpoint),
+            ],
+        );
+
+        let response = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap();
+        assert_eq!(response.attempts(), 2);
+        assert_eq!(response.into_body(), json!("hedged"));
+        let first = captured_rx.recv().await.unwrap();
+        let second = captured_rx.recv().await.unwrap();
+        assert_eq!((first.endpoint, first.attempt), ("stalled", 1));
+        assert_eq!((second.endpoint, second.attempt), ("fast", 2));
+        assert_eq!(first.request_id, second.request_id);
+        assert_eq!(
+            *metrics.finished.lock().unwrap(),
+            [
+                (1, MetricOutcome::Superseded, None),
+                (2, MetricOutcome::Success, None)
+            ]
+        );
+        assert_eq!(
+            runtime
+                .inner
+                .retry_budget(resilience_service(), &HttpBindingId::default())
+                .available(),
+            4
+        );
+
+        fast_fixture.await.unwrap();
+        stalled_fixture.abort();
+        drop(client);
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn load_balancer_observes_every_attempt_including_superseded_ones() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (stalled_endpoint, stalled_fixture) = spawn_stalled_endpoint(captured_tx.clone()).await;
+        let (fast_endpoint, fast_fixture) = spawn_full_endpoint(
+            "fast",
+            StatusCode::OK,
+            Bytes::from_static(br#""hedged""#),
+            None,
+            captured_tx,
+        )
+        .await;
+        let hedging = HedgingConfig::builder()
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(20))
+            .build()
+            .unwrap();
+        let config = resilience_config(
+            Duration::from_secs(2),
+            RetryConfig::builder().hedging(hedging).build().unwrap(),
+        );
+        let runtime = ClientRuntime::builder().config(config).build().unwrap();
+        let observed = ObservedAttempts::default();
+        let (_publisher, client) = discovered_client_with_balancer(
+            &runtime,
+            vec![
+                instance("stalled", stalled_endpoint),
+                instance("fast", fast_endpoint),
+            ],
+            Arc::new(observed.clone()),
+        );
+
+        let response = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap();
+        assert_eq!(response.attempts(), 2);
+        assert_eq!(captured_rx.recv().await.unwrap().endpoint, "stalled");
+        let events = observed.0.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [
+                ("stalled".to_owned(), None),
+                ("fast".to_owned(), None),
+                ("fast".to_owned(), Some(AttemptOutcome::Success)),
+                ("stalled".to_owned(), Some(AttemptOutcome::Cancelled)),
+            ]
+        );
+
+        fast_fixture.await.unwrap();
+        stalled_fixture.abort();
+        drop(client);
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn hedging_never_reuses_an_attempted_endpoint_or_exceeds_the_attempt_limit() {
+        let hedging = HedgingConfig::builder()
+            .min_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(5))
+            .build()
+            .unwrap();
+        for (max_attempts, endpoints) in [(3, 1), (1, 2)] {
+            let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+            let mut fixtures = Vec::new();
+            let mut instances = Vec::new();
+            for index in 0..endpoints {
+                let (endpoint, fixture) = spawn_stalled_endpoint(captured_tx.clone()).await;
+                fixtures.push(fixture);
+                instances.push(instance(&format!("stalled-{index}"), endpoint));
+            }
+            let config = resilience_config(
+                Duration::from_millis(100),
+                RetryConfig::builder()
+                    .max_attempts(max_attempts)
+                    .hedging(hedging.clone())
+                    .build()
+                    .unwrap(),
+            );
+            let runtime = ClientRuntime::builder().config(config).build().unwrap();
+            let (_publisher, client) = discovered_client(&runtime, instances);
+
+            let error = client
+                .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+                .await
+                .expect_err("every endpoint stalls past the deadline");
+            assert_eq!(error.code().as_str(), "deadline_exceeded");
+            assert_eq!(error.attempts(), 1);
+            assert_eq!(captured_rx.recv().await.unwrap().attempt, 1);
+            assert!(captured_rx.try_recv().is_err());
+            assert_eq!(
+                runtime
+                    .inner
+                    .retry_budget(resilience_service(), &HttpBindingId::default())
+                    .available(),
+                100
+            );
+
+            for fixture in fixtures {
+                fixture.abort();
+            }
+            drop(client);
+            runtime.shutdown().await.unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn latency_outliers_are_skipped_and_joining_instances_warm_up() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (endpoint, fixture) = spawn_full_endpoint(
+            "fast",
+            StatusCode::OK,
+            Bytes::from_static(b"\"ok\""),
+            None,
+            captured_tx,
+        )
+        .await;
+        let config = ClientConfig::builder()
+            .circuit_breaker(
+                CircuitBreakerConfig::builder()
+                    .outlier_detection(
+                        OutlierDetectionConfig::builder()
+                            .interval(Duration::from_millis(1))
+                            .min_requests(1)
+                            .base_ejection(Duration::from_secs(60))
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .discovery(
+                DiscoveryConfig::builder()
+                    .slow_start(
+                        SlowStartConfig::builder()
+                            .window(Duration::from_secs(60))
+                            .min_weight_ratio(0.25)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let runtime = ClientRuntime::builder().config(config).build().unwrap();
+        let mut instances = vec![
+            instance("fast", endpoint),
+            instance("b", "http://127.0.0.1:9".parse().unwrap()),
+            instance("c", "http://127.0.0.1:10".parse().unwrap()),
+            instance("slow", "http://127.0.0.1:11".parse().unwrap()),
+        ];
+        let offered = OfferedWeights::default();
+        let (publisher, client) =
+            discovered_client_with_balancer(&runtime, instances.clone(), Arc::new(offered.clone()));
+
+        let detector = runtime
+            .inner
+            .outlier_detector(resilience_service(), &HttpBindingId::default())
+            .unwrap();
+        for (instance, millis) in instances.iter().zip([10, 10, 10, 100]) {
+            detector.record(
+                instance.endpoint().as_str(),
+                Duration::from_millis(millis),
+                StdInstant::now(),
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // Only instances that join an already populated directory warm up.
+        instances.push(instance("joined", "http://127.0.0.1:12".parse().unwrap()));
+        runtime
+            .inner
+            .endpoint_breakers
+            .replace_discovery(resilience_service().selector(), &instances);
+        publisher.publish_ready(instances).unwrap();
+
+        let response = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap();
+        assert_eq!(response.into_body(), json!("ok"));
+        assert_eq!(captured_rx.recv().await.unwrap().endpoint, "fast");
+        let offered = offered.0.lock().unwrap().clone();
+        let ids = offered
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["fast", "b", "c", "joined"]);
+        assert!(offered[..3].iter().all(|(_, weight)| *weight == 1.0));
+        let joined = offered[3].1;
+        assert!((0.25..0.3).contains(&joined), "{joined}");
+
+        fixture.await.unwrap();
+        drop(client);
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn locality_router_fails_over_when_the_local_zone_is_open_and_tags_attempts() {
+        let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
+        let (remote_endpoint, remote_fixture) = spawn_full_endpoint(
+            "remote",
+            StatusCode::OK,
+            Bytes::from_static(b"\"ok\""),
+            None,
+            captured_tx.clone(),
+        )
+        .await;
+        let (local_endpoint, local_fixture) = spawn_full_endpoint(
+            "local",
+            StatusCode::OK,
+            Bytes::from_static(b"\"ok\""),
+            None,
+            captured_tx,
+        )
+        .await;
+        let metrics = AttemptLocalityMetrics::default();
+        let config = ClientConfig::builder()
+            .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+            .circuit_breaker(
+                CircuitBreakerConfig::builder()
+                    .endpoint(
+                        BreakerThreshold::endpoint_builder()
+                            .minimum_samples(1)
+                            .failure_ratio(0.5)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let runtime = ClientRuntime::builder()
+            .config(config)
+            .metrics(metrics.clone())
+            .build()
+            .unwrap();
+        let zoned = |id, endpoint, zone: &str| {
+            instance(id, endpoint)
+                .with_metadata(Metadata::from([("zone".to_owned(), zone.to_owned())]))
+                .unwrap()
+        };
+        let (_publisher, mut client) = discovered_client(
+            &runtime,
+            vec![
+                zoned("remote", remote_endpoint, "b1"),
+                zoned("local", local_endpoint.clone(), "a1"),
+            ],
+        );
+        Arc::get_mut(&mut client.inner).unwrap().routers =
+            Arc::from([
+                Arc::new(LocalityRouter::builder().zone("a1").build().unwrap())
+                    as Arc<dyn InstanceRouter>,
+            ]);
+
+        let response = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap();
+        assert_eq!(response.into_body(), json!("ok"));
+        assert_eq!(captured_rx.recv().await.unwrap().endpoint, "local");
+        local_fixture.await.unwrap();
+
+        // An open breaker makes the only local instance unhealthy, so the zone fails over.
+        runtime
+            .inner
+            .endpoint_breaker(
+                resilience_service(),
+                &HttpBindingId::default(),
+                EndpointBreakerSource::Discovery,
+                local_endpoint.as_str(),
+            )
+            .try_acquire()
+            .unwrap()
+            .fail(FailureClass::Transport);
+        let response = client
+            .invoke::<Value, _>(MethodId::new(0), Call::new(), empty_arguments)
+            .await
+            .unwrap();
+        assert_eq!(response.into_body(), json!("ok"));
+        assert_eq!(captured_rx.recv().await.unwrap().endpoint, "remote");
+        remote_fixture.await.unwrap();
+        assert_eq!(
+            *metrics.finished.lock().unwrap(),
+            [Some("a1".to_owned()), Some("b1".to_owned())]
+        );
+
+        drop(client);
+        runtime.shutdown().await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn retry_selection_failure_preserves_the_completed_attempt_count() {
         let (captured_tx, mut captured_rx) = mpsc::unbounded_channel();
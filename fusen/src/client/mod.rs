@@ -4,14 +4,23 @@ mod endpoint_breakers;
 mod invocation;
 mod runtime;
 mod subscription;
+mod tls;
 mod transport;
 
 pub use builder::ClientBuilder;
 pub use config::{
-    BreakerThreshold, BreakerThresholdBuilder, CircuitBreakerConfig, CircuitBreakerConfigBuilder,
+    AdaptiveConcurrencyConfig, AdaptiveConcurrencyConfigBuilder, BreakerThreshold,
+    BreakerThresholdBuilder, CircuitBreakerConfig, CircuitBreakerConfigBuilder,
     ClientAdmissionConfig, ClientAdmissionConfigBuilder, ClientConfig, ClientConfigBuilder,
-    ClientHttpConfig, ClientHttpConfigBuilder, DiscoveryConfig, DiscoveryConfigBuilder,
-    QueueConfig, QueueConfigBuilder, RetryConfig, RetryConfigBuilder,
+    ClientHttpConfig, ClientHttpConfigBuilder, ClientTlsConfig, ClientTlsConfigBuilder,
+    DiscoveryConfig, DiscoveryConfigBuilder, HedgingConfig, HedgingConfigBuilder, MethodConfig,
+    MethodConfigBuilder, OutlierDetectionConfig, OutlierDetectionConfigBuilder, QueueConfig,
+    QueueConfigBuilder, RetryConfig, RetryConfigBuilder, SlowStartConfig, SlowStartConfigBuilder,
+};
+#[cfg(feature = "compression")]
+pub use config::{
+    ClientCompressionConfig, ClientCompressionConfigBuilder, ClientRequestCompressionConfig,
+    ClientRequestCompressionConfigBuilder,
 };
 #[doc(hidden)]
 pub use invocation::ServiceClient;
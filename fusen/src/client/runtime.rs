@@ -1,21 +1,28 @@
 use super::{
-    config::ClientConfig,
+    config::{BreakerThreshold, ClientConfig, MethodConfig, RetryConfig, validate_method_defaults},
     endpoint_breakers::{EndpointBreakerSource, EndpointBreakers},
     subscription::SubscriptionManager,
     transport::HttpTransport,
 };
 use crate::{
-    ClientError, ClientErrorKind, ErrorDecoder, Interceptor, RequestEncoder, ResponseDecoder,
-    RetryPolicy,
+    AdaptiveConcurrencyConfig, ClientError, ClientErrorKind, ErrorDecoder, Interceptor,
+    RequestEncoder, ResponseDecoder, RetryPolicy,
     interceptor::erase_interceptor,
     resilience::{
         breaker::{BreakerConfig, BreakerPhase, CircuitBreaker},
+        hedge::LatencyWindow,
+        limit::{AdaptiveLimiter, AttemptLimitPermit, EndpointLimitStore},
+        outlier::OutlierDetector,
         retry::{RetryBudget, StandardRetryPolicy},
     },
-    runtime::{admission::AdmissionGate, budget::ByteBudget, metrics::SafeMetrics},
-    wire::JsonCodec,
+    runtime::{
+        admission::{AdmissionError, AdmissionGate},
+        budget::ByteBudget,
+        metrics::SafeMetrics,
+    },
+    wire::{JsonCodec, MsgpackCodec},
 };
-use fusen_contract::{HttpBindingId, ServiceDescriptor};
+use fusen_contract::{HTTP_MSGPACK_V1, HttpBindingId, MethodDescriptor, ServiceDescriptor};
 use fusen_observability::{
     CircuitState, CircuitStateChangedEvent, MetricEvent, MetricOutcome, MetricsRecorder,
     ShutdownFinishedEvent,
@@ -27,7 +34,7 @@ use std::{
         Arc, Mutex,
         atomic::{AtomicU8, Ordering},
     },
-    time::Instant as StdInstant,
+    time::{Duration, Instant as StdInstant},
 };
 use tokio::sync::{Semaphore, watch};
 use tokio_util::sync::CancellationToken;
@@ -190,6 +197,15 @@ impl ClientRuntimeBuilder {
             error_decoder: Arc::new(JsonCodec),
         });
         http_bindings.insert(HttpBindingId::default(), json);
+        let msgpack = Arc::new(ClientHttpBinding {
+            request_encoder: Arc::new(MsgpackCodec),
+            response_decoder: Arc::new(MsgpackCodec),
+            error_decoder: Arc::new(MsgpackCodec),
+        });
+        http_bindings.insert(
+            HttpBindingId::new(HTTP_MSGPACK_V1).expect("built-in binding ID is valid"),
+            msgpack,
+        );
         for (id, binding) in self.http_bindings {
             if http_bindings.insert(id.clone(), binding).is_some() {
                 return Err(ClientError::from_message(
@@ -247,7 +263,19 @@ impl ClientRuntimeBuilder {
             endpoint_breakers,
             service_breakers: Mutex::new(HashMap::new()),
             retry_budgets: Mutex::new(HashMap::new()),
+            method_breakers: Mutex::new(HashMap::new()),
+            method_retry_budgets: Mutex::new(HashMap::new()),
+            hedge_latencies: Mutex::new(HashMap::new()),
+            service_limits: Mutex::new(HashMap::new()),
+            // The store stays empty while adaptive limits are disabled.
+            endpoint_limits: EndpointLimitStore::new(
+                config
+                    .admission()
+                    .adaptive()
+                    .map_or(1, AdaptiveConcurrencyConfig::max_endpoint_entries),
+            ),
             endpoint_bulkheads: Mutex::new(HashMap::new()),
+            outlier_detectors: Mutex::new(HashMap::new()),
             shutdown: shutdown.clone(),
             force_cancel: force_cancel.clone(),
             state: state.clone(),
@@ -268,6 +296,9 @@ impl ClientRuntimeBuilder {
     }
 }
 
+/// Retry token bucket capacity and refill rate.
+type BudgetSettings = (u32, u32);
+
 pub(crate) struct ClientRuntimeInner {
     pub config: Arc<ClientConfig>,
     pub interceptor: Arc<[Arc<dyn Interceptor>]>,
@@ -284,13 +315,28 @@ pub(crate) struct ClientRuntimeInner {
     pub endpoint_breakers: EndpointBreakers,
     pub service_breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
     pub retry_budgets: Mutex<HashMap<String, Arc<RetryBudget>>>,
+    // Override state is keyed per method and kept only while callers bring the same settings.
+    pub method_breakers: Mutex<HashMap<String, (BreakerThreshold, Arc<CircuitBreaker>)>>,
+    pub method_retry_budgets: Mutex<HashMap<String, (BudgetSettings, Arc<RetryBudget>)>>,
+    pub hedge_latencies: Mutex<HashMap<String, Arc<LatencyWindow>>>,
     pub endpoint_bulkheads: Mutex<HashMap<String, Arc<Semaphore>>>,
+    pub service_limits: Mutex<HashMap<String, Arc<AdaptiveLimiter>>>,
+    pub endpoint_limits: EndpointLimitStore,
+    pub outlier_detectors: Mutex<HashMap<String, Arc<OutlierDetector>>>,
     pub shutdown: CancellationToken,
     pub force_cancel: CancellationToken,
     pub state: Arc<AtomicU8>,
     completion: watch::Receiver<Option<Result<(), ClientError>>>,
 }
 
+/// Resolved invocation policy for one method of one connected client.
+pub(crate) struct MethodPolicy {
+    pub request_timeout: Duration,
+    pub retry: RetryConfig,
+    pub retry_budget: Arc<RetryBudget>,
+    pub service_breaker: Arc<CircuitBreaker>,
+}
+
 pub(crate) struct ClientHttpBinding {
     pub request_encoder: Arc<dyn RequestEncoder>,
     pub response_decoder: Arc<dyn ResponseDecoder>,
@@ -323,29 +369,158 @@ impl ClientRuntimeInner {
         breakers
             .entry(binding_key(service, binding_id))
             .or_insert_with(|| {
-                let metrics = self.metrics.clone();
-                let binding = binding_id.as_str().to_owned();
-                let service_id = service.selector().service_id().to_owned();
-                CircuitBreaker::observed(
-                    breaker_config(
-                        self.config.circuit_breaker().service(),
-                        self.config.circuit_breaker().max_open_duration(),
-                    ),
-                    Arc::new(move |phase| {
-                        metrics.record(&MetricEvent::CircuitStateChanged(
-                            CircuitStateChangedEvent::new(
-                                "service",
-                                &binding,
-                                &service_id,
-                                metric_circuit_state(phase),
-                            ),
-                        ));
-                    }),
+                self.observed_service_breaker(
+                    service,
+                    binding_id,
+                    self.config.circuit_breaker().service(),
                 )
             })
             .clone()
     }
 
+    fn observed_service_breaker(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        threshold: &BreakerThreshold,
+    ) -> Arc<CircuitBreaker> {
+        let metrics = self.metrics.clone();
+        let binding = binding_id.as_str().to_owned();
+        let service_id = service.selector().service_id().to_owned();
+        CircuitBreaker::observed(
+            breaker_config(threshold, self.config.circuit_breaker().max_open_duration()),
+            Arc::new(move |phase| {
+                metrics.record(&MetricEvent::CircuitStateChanged(
+                    CircuitStateChangedEvent::new(
+                        "service",
+                        &binding,
+                        &service_id,
+                        metric_circuit_state(phase),
+                    ),
+                ));
+            }),
+        )
+    }
+
+    /// Resolves each method's deadline, retry, and interface-breaker policy for one client.
+    ///
+    /// Explicit overrides win over interface-declared defaults, which win over runtime settings.
+    /// Explicit retry and breaker overrides get per-method state that outlives the client, so a
+    /// reconnect with the same settings resumes it; everything else shares the runtime's
+    /// per-service budget and breaker.
+    pub(crate) fn method_policies(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        overrides: &HashMap<String, MethodConfig>,
+    ) -> Result<Arc<[MethodPolicy]>, ClientError> {
+        if let Some(name) = overrides.keys().find(|name| {
+            !service
+                .methods()
+                .iter()
+                .any(|method| method.invocation_name() == name.as_str())
+        }) {
+            return Err(ClientError::from_message(
+                ClientErrorKind::Connect,
+                format!("method configuration targets unknown method {name}"),
+            ));
+        }
+        let shared_breaker = self.service_breaker(service, binding_id);
+        let shared_budget = self.retry_budget(service, binding_id);
+        service
+            .methods()
+            .iter()
+            .map(|method| {
+                let defaults = method.client_defaults();
+                validate_method_defaults(defaults).map_err(|error| {
+                    ClientError::with_source(
+                        ClientErrorKind::Connect,
+                        format!("invalid client defaults on {}", method.invocation_name()),
+                        error,
+                    )
+                })?;
+                let config = overrides.get(method.invocation_name());
+                let request_timeout = config
+                    .and_then(MethodConfig::request_timeout)
+                    .or(defaults.timeout())
+                    .unwrap_or(self.config.request_timeout());
+                let (retry, retry_budget) = match config.and_then(MethodConfig::retry) {
+                    Some(retry) => (
+                        retry.clone(),
+                        self.method_retry_budget(service, binding_id, method, retry),
+                    ),
+                    None => (
+                        match defaults.retries() {
+                            Some(retries) => {
+                                self.config.retry().clone().with_max_attempts(retries + 1)
+                            }
+                            None => self.config.retry().clone(),
+                        },
+                        shared_budget.clone(),
+                    ),
+                };
+                let service_breaker = match config.and_then(MethodConfig::service_breaker) {
+                    Some(threshold) => self.method_breaker(service, binding_id, method, threshold),
+                    None => shared_breaker.clone(),
+                };
+                Ok(MethodPolicy {
+                    request_timeout,
+                    retry,
+                    retry_budget,
+                    service_breaker,
+                })
+            })
+            .collect()
+    }
+
+    fn method_breaker(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        method: &'static MethodDescriptor,
+        threshold: &BreakerThreshold,
+    ) -> Arc<CircuitBreaker> {
+        let mut breakers = self
+            .method_breakers
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let key = method_key(service, binding_id, method);
+        if let Some((_, breaker)) = breakers
+            .get(&key)
+            .filter(|(current, _)| current == threshold)
+        {
+            return breaker.clone();
+        }
+        // Different thresholds start over rather than inheriting another client's state.
+        let breaker = self.observed_service_breaker(service, binding_id, threshold);
+        breakers.insert(key, (threshold.clone(), breaker.clone()));
+        breaker
+    }
+
+    fn method_retry_budget(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        method: &'static MethodDescriptor,
+        retry: &RetryConfig,
+    ) -> Arc<RetryBudget> {
+        let mut budgets = self
+            .method_retry_budgets
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let key = method_key(service, binding_id, method);
+        let settings = (retry.budget_capacity(), retry.budget_refill_per_second());
+        if let Some((_, budget)) = budgets
+            .get(&key)
+            .filter(|(current, _)| *current == settings)
+        {
+            return budget.clone();
+        }
+        let budget = Arc::new(RetryBudget::new(settings.0, settings.1));
+        budgets.insert(key, (settings, budget.clone()));
+        budget
+    }
+
     pub(crate) fn endpoint_breaker(
         &self,
         service: &'static ServiceDescriptor,
@@ -374,6 +549,18 @@ impl ClientRuntimeInner {
         )
     }
 
+    /// Returns whether an endpoint's breaker is open, without creating one.
+    pub(crate) fn endpoint_breaker_is_open(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        source: EndpointBreakerSource,
+        endpoint: &str,
+    ) -> bool {
+        self.endpoint_breakers
+            .is_open(service.identity(), binding_id, source, endpoint)
+    }
+
     pub(crate) fn retry_budget(
         &self,
         service: &'static ServiceDescriptor,
@@ -394,6 +581,68 @@ impl ClientRuntimeInner {
             .clone()
     }
 
+    pub(crate) fn hedge_latency(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        method: &'static MethodDescriptor,
+    ) -> Arc<LatencyWindow> {
+        let mut latencies = self
+            .hedge_latencies
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        latencies
+            .entry(method_key(service, binding_id, method))
+            .or_default()
+            .clone()
+    }
+
+    /// Returns the latency outlier detector of one service binding, if enabled.
+    pub(crate) fn outlier_detector(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+    ) -> Option<Arc<OutlierDetector>> {
+        let config = self.config.circuit_breaker().outlier_detection()?;
+        let mut detectors = self
+            .outlier_detectors
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        Some(
+            detectors
+                .entry(binding_key(service, binding_id))
+                .or_insert_with(|| Arc::new(OutlierDetector::new(config, StdInstant::now())))
+                .clone(),
+        )
+    }
+
+    /// Admits one attempt under the adaptive service-binding and endpoint limits, if enabled.
+    pub(crate) fn adaptive_permit(
+        &self,
+        service: &'static ServiceDescriptor,
+        binding_id: &HttpBindingId,
+        endpoint: &str,
+    ) -> Result<Option<AttemptLimitPermit>, AdmissionError> {
+        let Some(config) = self.config.admission().adaptive() else {
+            return Ok(None);
+        };
+        let service_limit = self
+            .service_limits
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .entry(binding_key(service, binding_id))
+            .or_insert_with(|| Arc::new(AdaptiveLimiter::new(config)))
+            .clone();
+        let endpoint_limit = self.endpoint_limits.get_or_insert(endpoint, config);
+        let service = service_limit
+            .try_acquire()
+            .ok_or(AdmissionError::Overloaded)?;
+        let endpoint = endpoint_limit
+            .try_acquire()
+            .ok_or(AdmissionError::Overloaded)?;
+        Ok(Some(AttemptLimitPermit::new(service, endpoint)))
+    }
+
     pub(crate) fn endpoint_bulkhead(&self, endpoint: &str) -> Arc<Semaphore> {
         let mut bulkheads = self
             .endpoint_bulkheads
@@ -420,6 +669,18 @@ fn binding_key(service: &ServiceDescriptor, binding_id: &HttpBindingId) -> Strin
     format!("{}\0{}", service.identity(), binding_id.as_str())
 }
 
+fn method_key(
+    service: &ServiceDescriptor,
+    binding_id: &HttpBindingId,
+    method: &MethodDescriptor,
+) -> String {
+    format!(
+        "{}\0{}",
+        binding_key(service, binding_id),
+        method.invocation_name()
+    )
+}
+
 const fn metric_circuit_state(phase: BreakerPhase) -> CircuitState {
     match phase {
         BreakerPhase::Closed => CircuitState::Closed,
@@ -435,7 +696,7 @@ impl Drop for ClientRuntimeInner {
 }
 
 fn breaker_config(
-    threshold: &super::config::BreakerThreshold,
+    threshold: &BreakerThreshold,
     max_open_duration: std::time::Duration,
 ) -> BreakerConfig {
     BreakerConfig::new(
@@ -0,0 +1,76 @@
+use super::config::{ClientTlsConfig, ClientTlsIdentity};
+use crate::{
+    ClientError, ClientErrorKind,
+    pem::{self, PemFileError},
+};
+use rustls::{ClientConfig as TlsClientConfig, RootCertStore};
+use std::sync::Arc;
+
+/// Loads the trust store and optional client identity for one TLS configuration.
+pub(crate) fn load(config: &ClientTlsConfig) -> Result<TlsClientConfig, ClientError> {
+    let mut roots = RootCertStore::empty();
+    if config.webpki_roots() {
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    }
+    for path in config.root_certificate_paths() {
+        for certificate in
+            pem::read_certificates(path, "client", "root certificate").map_err(pem_file_error)?
+        {
+            roots.add(certificate).map_err(|error| {
+                ClientError::with_source(
+                    ClientErrorKind::Build,
+                    "client TLS root certificate file contains an invalid trust anchor",
+                    error,
+                )
+            })?;
+        }
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = TlsClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|error| {
+            ClientError::with_source(
+                ClientErrorKind::Build,
+                "failed to initialize the client TLS protocol versions",
+                error,
+            )
+        })?
+        .with_root_certificates(roots);
+    match config.client_identity() {
+        Some(ClientTlsIdentity::Files {
+            certificate_chain_path,
+            private_key_path,
+        }) => {
+            let chain =
+                pem::read_certificates(certificate_chain_path, "client", "certificate chain")
+                    .map_err(pem_file_error)?;
+            let key = pem::read_private_key(private_key_path, "client").map_err(pem_file_error)?;
+            builder.with_client_auth_cert(chain, key).map_err(|error| {
+                ClientError::with_source(
+                    ClientErrorKind::Build,
+                    "client TLS certificate chain and private key are inconsistent",
+                    error,
+                )
+            })
+        }
+        #[cfg(feature = "hot-tls")]
+        Some(ClientTlsIdentity::Hot(certificate)) => {
+            let mut tls = builder.with_client_cert_resolver(Arc::new(
+                crate::tls::HotCertificateResolver(certificate.clone()),
+            ));
+            // A resumed session reuses the identity it was issued to, which would outlive rotation.
+            tls.resumption = rustls::client::Resumption::disabled();
+            Ok(tls)
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+fn pem_file_error(error: PemFileError) -> ClientError {
+    match error.into_parts() {
+        (message, Some(source)) => {
+            ClientError::with_source(ClientErrorKind::Build, message, source)
+        }
+        (message, None) => ClientError::from_message(ClientErrorKind::Build, message),
+    }
+}
@@ -1,14 +1,19 @@
-use super::config::ClientHttpConfig;
-use crate::{ClientError, ClientErrorKind, Error, ErrorCategory, RetryHint, wire::GuardedBody};
+use super::{config::ClientHttpConfig, tls};
+#[cfg(feature = "http3")]
+use crate::wire::GuardedChunk;
+use crate::{ClientError, Error, ErrorCategory, RetryHint, wire::GuardedBody};
+use bytes::Bytes;
+use fusen_contract::ServiceSelector;
 use http::{Request, Response as HttpResponse, Uri, Version, uri::Scheme};
-use hyper::body::Incoming;
-use hyper_rustls::{ConfigBuilderExt, HttpsConnector, HttpsConnectorBuilder, MaybeHttpsStream};
+use hyper::body::{Body as HttpBody, Frame, Incoming, SizeHint};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder, MaybeHttpsStream};
 use hyper_util::{
     client::legacy::{Client, connect::HttpConnector},
     rt::{TokioExecutor, TokioTimer},
 };
 use rustls::ClientConfig as TlsClientConfig;
 use std::{
+    collections::HashMap,
     future::Future,
     io,
     pin::Pin,
@@ -27,13 +32,11 @@ type AutoSocket = Client<Connector, GuardedBody>;
 type Http2Socket = Client<RequireH2Alpn<Connector>, GuardedBody>;
 type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
+/// Connection pools for the default TLS settings and each per-service override.
 #[derive(Clone)]
 pub(crate) struct HttpTransport {
-    http1: Http1Socket,
-    auto: Box<[AutoSocket]>,
-    http2: Box<[Http2Socket]>,
-    next_auto_shard: Arc<AtomicUsize>,
-    next_http2_shard: Arc<AtomicUsize>,
+    default: HttpPools,
+    overrides: Arc<HashMap<String, HttpPools>>,
 }
 
 impl HttpTransport {
@@ -41,21 +44,41 @@ impl HttpTransport {
         connect_timeout: Duration,
         config: &ClientHttpConfig,
     ) -> Result<Self, ClientError> {
-        let provider = Arc::new(rustls::crypto::ring::default_provider());
-        let tls_config = TlsClientConfig::builder_with_provider(provider)
-            .with_safe_default_protocol_versions()
-            .map_err(|error| {
-                ClientError::with_source(
-                    ClientErrorKind::Build,
-                    "failed to initialize the client TLS protocol versions",
-                    error,
-                )
-            })?
-            .with_webpki_roots()
-            .with_no_client_auth();
-        Ok(Self::with_tls_config(connect_timeout, config, tls_config))
+        let default = HttpPools::with_tls_config(connect_timeout, config, tls::load(config.tls())?);
+        let overrides = config
+            .tls_overrides()
+            .map(|(selector, tls)| {
+                let pools = HttpPools::with_tls_config(connect_timeout, config, tls::load(tls)?);
+                Ok((selector.identity().to_owned(), pools))
+            })
+            .collect::<Result<_, ClientError>>()?;
+        Ok(Self {
+            default,
+            overrides: Arc::new(overrides),
+        })
+    }
+
+    // Overridden services never share connections, so a pooled TLS session cannot leak across
+    // trust boundaries.
+    pub(crate) fn pools(&self, service: &ServiceSelector) -> &HttpPools {
+        self.overrides
+            .get(service.identity())
+            .unwrap_or(&self.default)
     }
+}
 
+#[derive(Clone)]
+pub(crate) struct HttpPools {
+    http1: Http1Socket,
+    auto: Box<[AutoSocket]>,
+    http2: Box<[Http2Socket]>,
+    #[cfg(feature = "http3")]
+    http3: Http3Pool,
+    next_auto_shard: Arc<AtomicUsize>,
+    next_http2_shard: Arc<AtomicUsize>,
+}
+
+impl HttpPools {
     fn with_tls_config(
         connect_timeout: Duration,
         config: &ClientHttpConfig,
@@ -72,6 +95,8 @@ impl HttpTransport {
             .enable_http1()
             .enable_http2()
             .wrap_connector(http_connector(connect_timeout));
+        #[cfg(feature = "http3")]
+        let http3 = Http3Pool::new(connect_timeout, config, tls_config.clone());
         let http2_connector = RequireH2Alpn {
             inner: HttpsConnectorBuilder::new()
                 .with_tls_config(tls_config)
@@ -121,6 +146,8 @@ impl HttpTransport {
             http1,
             auto,
             http2,
+            #[cfg(feature = "http3")]
+            http3,
             next_auto_shard: Arc::new(AtomicUsize::new(0)),
             next_http2_shard: Arc::new(AtomicUsize::new(0)),
         }
@@ -130,7 +157,11 @@ impl HttpTransport {
         &self,
         request: Request<GuardedBody>,
         auto_negotiate: bool,
-    ) -> Result<HttpResponse<Incoming>, TransportFailure> {
+    ) -> Result<HttpResponse<ResponseBody>, TransportFailure> {
+        #[cfg(feature = "http3")]
+        if request.version() == Version::HTTP_3 {
+            return self.http3.send(request).await;
+        }
         let response = if auto_negotiate {
             let shard = request_shard(&request, self.auto.len(), &self.next_auto_shard);
             self.auto[shard].request(request).await
@@ -140,14 +171,356 @@ impl HttpTransport {
         } else {
             self.http1.request(request).await
         };
-        response.map_err(|error| {
-            let kind = if error.is_connect() {
-                TransportFailureKind::Connect
-            } else {
-                TransportFailureKind::Io
-            };
-            TransportFailure { kind, error }
-        })
+        response
+            .map(|response| response.map(ResponseBody::Http))
+            .map_err(|error| {
+                let kind = if error.is_connect() {
+                    TransportFailureKind::Connect
+                } else {
+                    TransportFailureKind::Io
+                };
+                TransportFailure {
+                    kind,
+                    error: TransportError::Http(error),
+                }
+            })
+    }
+}
+
+/// A response body from whichever connection pool carried the attempt.
+pub(crate) enum ResponseBody {
+    Http(Incoming),
+    #[cfg(feature = "http3")]
+    Http3(Http3Body),
+}
+
+impl HttpBody for ResponseBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.get_mut() {
+            Self::Http(body) => Pin::new(body).poll_frame(context).map_err(io::Error::other),
+            #[cfg(feature = "http3")]
+            Self::Http3(body) => body.poll_frame(context),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            Self::Http(body) => body.is_end_stream(),
+            #[cfg(feature = "http3")]
+            Self::Http3(body) => body.finished,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Self::Http(body) => body.size_hint(),
+            #[cfg(feature = "http3")]
+            Self::Http3(_) => SizeHint::default(),
+        }
+    }
+}
+
+#[cfg(feature = "http3")]
+type Http3Sender = h3::client::SendRequest<h3_quinn::OpenStreams, GuardedChunk>;
+#[cfg(feature = "http3")]
+type Http3Slot = Arc<tokio::sync::Mutex<Option<Http3Connection>>>;
+
+/// One multiplexed QUIC connection per authority; QUIC carries every stream on it.
+#[cfg(feature = "http3")]
+#[derive(Clone)]
+struct Http3Pool {
+    client_config: quinn::ClientConfig,
+    connect_timeout: Duration,
+    // Lazily bound UDP endpoints, indexed by address family (IPv4, IPv6).
+    endpoints: Arc<std::sync::Mutex<[Option<quinn::Endpoint>; 2]>>,
+    connections: Arc<std::sync::Mutex<HashMap<String, Http3Slot>>>,
+}
+
+#[cfg(feature = "http3")]
+struct Http3Connection {
+    sender: Http3Sender,
+    connection: quinn::Connection,
+}
+
+#[cfg(feature = "http3")]
+impl Http3Pool {
+    fn new(
+        connect_timeout: Duration,
+        config: &ClientHttpConfig,
+        mut tls_config: TlsClientConfig,
+    ) -> Self {
+        tls_config.alpn_protocols = vec![b"h3".to_vec()];
+        let crypto = quinn::crypto::rustls::QuicClientConfig::try_from(tls_config)
+            .expect("client TLS configurations always enable the TLS 1.3 suites QUIC requires");
+        let mut client_config = quinn::ClientConfig::new(Arc::new(crypto));
+        let mut transport = quinn::TransportConfig::default();
+        transport.max_idle_timeout(
+            config
+                .pool_idle_timeout()
+                .and_then(|timeout| quinn::IdleTimeout::try_from(timeout).ok()),
+        );
+        transport.keep_alive_interval(config.http2_keep_alive_interval());
+        client_config.transport_config(Arc::new(transport));
+        Self {
+            client_config,
+            connect_timeout,
+            endpoints: Arc::default(),
+            connections: Arc::default(),
+        }
+    }
+
+    async fn send(
+        &self,
+        request: Request<GuardedBody>,
+    ) -> Result<HttpResponse<ResponseBody>, TransportFailure> {
+        let mut sender = self
+            .sender(request.uri())
+            .await
+            .map_err(|error| TransportFailure::http3(TransportFailureKind::Connect, error))?;
+        let (parts, body) = request.into_parts();
+        let stream = sender
+            .send_request(Request::from_parts(parts, ()))
+            .await
+            .map_err(|error| {
+                TransportFailure::http3(TransportFailureKind::Io, io::Error::other(error))
+            })?;
+        let (send, mut receive) = stream.split();
+        // The server may answer before a streamed upload ends, exactly as over HTTP/2.
+        let mut upload = Box::pin(upload_http3_body(body, send));
+        let mut uploaded = false;
+        let head = {
+            let head = receive.recv_response();
+            tokio::pin!(head);
+            loop {
+                tokio::select! {
+                    biased;
+                    result = &mut upload, if !uploaded => {
+                        result.map_err(|error| {
+                            TransportFailure::http3(TransportFailureKind::Io, error)
+                        })?;
+                        uploaded = true;
+                    }
+                    head = &mut head => break head,
+                }
+            }
+        }
+        .map_err(|error| {
+            TransportFailure::http3(TransportFailureKind::Io, io::Error::other(error))
+        })?;
+        let upload = (!uploaded).then(|| AbortOnDrop(tokio::spawn(upload)));
+        let (parts, ()) = head.into_parts();
+        Ok(HttpResponse::from_parts(
+            parts,
+            ResponseBody::Http3(Http3Body {
+                stream: receive,
+                data_finished: false,
+                finished: false,
+                _upload: upload,
+            }),
+        ))
+    }
+
+    async fn sender(&self, uri: &Uri) -> io::Result<Http3Sender> {
+        if uri.scheme() != Some(&Scheme::HTTPS) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "HTTP/3 requires an https endpoint",
+            ));
+        }
+        let authority = uri.authority().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "endpoint has no authority")
+        })?;
+        let slot = self
+            .connections
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .entry(authority.as_str().to_owned())
+            .or_default()
+            .clone();
+        // Concurrent first requests share one handshake instead of racing several.
+        let mut guard = slot.lock().await;
+        if let Some(connection) = guard
+            .as_ref()
+            .filter(|connection| connection.connection.close_reason().is_none())
+        {
+            return Ok(connection.sender.clone());
+        }
+        let connection = tokio::time::timeout(self.connect_timeout, self.connect(authority, &slot))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "QUIC connect timed out"))??;
+        let sender = connection.sender.clone();
+        *guard = Some(connection);
+        Ok(sender)
+    }
+
+    async fn connect(
+        &self,
+        authority: &http::uri::Authority,
+        slot: &Http3Slot,
+    ) -> io::Result<Http3Connection> {
+        let host = authority.host();
+        let port = authority.port_u16().unwrap_or(443);
+        let address = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+            .await?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "endpoint host did not resolve")
+            })?;
+        let endpoint = self.endpoint(address.is_ipv6())?;
+        let connection = endpoint
+            .connect_with(
+                self.client_config.clone(),
+                address,
+                host.trim_matches(['[', ']']),
+            )
+            .map_err(io::Error::other)?
+            .await?;
+        let (mut driver, sender) = h3::client::builder()
+            .build(h3_quinn::Connection::new(connection.clone()))
+            .await
+            .map_err(io::Error::other)?;
+        let connections = Arc::downgrade(&self.connections);
+        let key = authority.as_str().to_owned();
+        let slot = Arc::downgrade(slot);
+        tokio::spawn(async move {
+            let _ = std::future::poll_fn(|context| driver.poll_close(context)).await;
+            // Forget the authority once its connection is gone, so the pool only holds live peers.
+            if let (Some(connections), Some(slot)) = (connections.upgrade(), slot.upgrade()) {
+                remove_closed_slot(&connections, &key, &slot);
+            }
+        });
+        Ok(Http3Connection { sender, connection })
+    }
+
+    fn endpoint(&self, ipv6: bool) -> io::Result<quinn::Endpoint> {
+        let mut endpoints = self
+            .endpoints
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let slot = &mut endpoints[usize::from(ipv6)];
+        if let Some(endpoint) = slot {
+            return Ok(endpoint.clone());
+        }
+        let local = if ipv6 {
+            std::net::SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, 0))
+        } else {
+            std::net::SocketAddr::from((std::net::Ipv4Addr::UNSPECIFIED, 0))
+        };
+        let endpoint = quinn::Endpoint::client(local)?;
+        *slot = Some(endpoint.clone());
+        Ok(endpoint)
+    }
+}
+
+#[cfg(feature = "http3")]
+fn remove_closed_slot(
+    connections: &std::sync::Mutex<HashMap<String, Http3Slot>>,
+    authority: &str,
+    slot: &Http3Slot,
+) {
+    let mut connections = connections
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    if !connections
+        .get(authority)
+        .is_some_and(|current| Arc::ptr_eq(current, slot))
+    {
+        return;
+    }
+    // A held slot is handshaking a replacement; its caller keeps the entry.
+    let Ok(current) = slot.try_lock() else {
+        return;
+    };
+    if current
+        .as_ref()
+        .is_none_or(|connection| connection.connection.close_reason().is_some())
+    {
+        drop(current);
+        connections.remove(authority);
+    }
+}
+
+#[cfg(feature = "http3")]
+async fn upload_http3_body(
+    mut body: GuardedBody,
+    mut send: h3::client::RequestStream<h3_quinn::SendStream<GuardedChunk>, GuardedChunk>,
+) -> io::Result<()> {
+    while let Some(frame) =
+        std::future::poll_fn(|context| Pin::new(&mut body).poll_frame(context)).await
+    {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(error) => {
+                // Reset the stream so the server sees truncation rather than a complete body.
+                send.stop_stream(h3::error::Code::H3_REQUEST_CANCELLED);
+                return Err(io::Error::other(error));
+            }
+        };
+        if let Ok(chunk) = frame.into_data() {
+            send.send_data(chunk).await.map_err(io::Error::other)?;
+        }
+    }
+    send.finish().await.map_err(io::Error::other)
+}
+
+/// The response half of an HTTP/3 request stream.
+#[cfg(feature = "http3")]
+pub(crate) struct Http3Body {
+    stream: h3::client::RequestStream<h3_quinn::RecvStream, GuardedChunk>,
+    data_finished: bool,
+    finished: bool,
+    _upload: Option<AbortOnDrop>,
+}
+
+#[cfg(feature = "http3")]
+impl Http3Body {
+    fn poll_frame(
+        &mut self,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        use bytes::Buf;
+
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        if !self.data_finished {
+            match std::task::ready!(self.stream.poll_recv_data(context)) {
+                Ok(Some(mut chunk)) => {
+                    return Poll::Ready(Some(Ok(Frame::data(
+                        chunk.copy_to_bytes(chunk.remaining()),
+                    ))));
+                }
+                Ok(None) => self.data_finished = true,
+                Err(error) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(io::Error::other(error))));
+                }
+            }
+        }
+        let trailers = std::task::ready!(self.stream.poll_recv_trailers(context));
+        self.finished = true;
+        match trailers {
+            Ok(Some(trailers)) => Poll::Ready(Some(Ok(Frame::trailers(trailers)))),
+            Ok(None) => Poll::Ready(None),
+            Err(error) => Poll::Ready(Some(Err(io::Error::other(error)))),
+        }
+    }
+}
+
+/// Stops a request upload that outlived its response.
+#[cfg(feature = "http3")]
+struct AbortOnDrop(tokio::task::JoinHandle<io::Result<()>>);
+
+#[cfg(feature = "http3")]
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
     }
 }
 
@@ -220,10 +593,27 @@ pub(crate) enum TransportFailureKind {
 #[derive(Debug)]
 pub(crate) struct TransportFailure {
     pub kind: TransportFailureKind,
-    error: hyper_util::client::legacy::Error,
+    error: TransportError,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum TransportError {
+    #[error(transparent)]
+    Http(hyper_util::client::legacy::Error),
+    #[cfg(feature = "http3")]
+    #[error(transparent)]
+    Http3(io::Error),
 }
 
 impl TransportFailure {
+    #[cfg(feature = "http3")]
+    fn http3(kind: TransportFailureKind, error: io::Error) -> Self {
+        Self {
+            kind,
+            error: TransportError::Http3(error),
+        }
+    }
+
     pub(crate) fn into_error(self) -> Error {
         Error::internal("HTTP transport failed", self.error).with_retry_hint(RetryHint::Retryable)
     }
@@ -351,7 +741,7 @@ mod tests {
         fixture: &TlsFixture,
         tls_config: TlsClientConfig,
     ) -> Result<Bytes, TransportFailure> {
-        let transport = HttpTransport::with_tls_config(
+        let transport = HttpPools::with_tls_config(
             Duration::from_secs(1),
             &ClientHttpConfig::default(),
             tls_config,
@@ -371,7 +761,7 @@ mod tests {
         fixture: &TlsFixture,
         tls_config: TlsClientConfig,
     ) -> Result<(Version, Bytes), TransportFailure> {
-        let transport = HttpTransport::with_tls_config(
+        let transport = HttpPools::with_tls_config(
             Duration::from_secs(1),
             &ClientHttpConfig::default(),
             tls_config,
@@ -393,11 +783,8 @@ mod tests {
             .http2_connections_per_host(3)
             .build()
             .unwrap();
-        let transport = HttpTransport::with_tls_config(
-            Duration::from_secs(1),
-            &config,
-            client_tls_config(None),
-        );
+        let transport =
+            HttpPools::with_tls_config(Duration::from_secs(1), &config, client_tls_config(None));
 
         assert_eq!(transport.auto.len(), 3);
         assert_eq!(transport.http2.len(), 3);
@@ -519,4 +906,32 @@ mod tests {
         assert_eq!(error.kind, TransportFailureKind::Connect);
         fixture.task.abort();
     }
+
+    #[cfg(feature = "http3")]
+    #[tokio::test]
+    async fn http3_pool_drops_only_the_closed_slot_it_owns() {
+        let connections = std::sync::Mutex::new(HashMap::new());
+        let closed = Http3Slot::default();
+        let connecting = Http3Slot::default();
+        connections
+            .lock()
+            .unwrap()
+            .insert("closed:443".to_owned(), Arc::clone(&closed));
+        connections
+            .lock()
+            .unwrap()
+            .insert("connecting:443".to_owned(), Arc::clone(&connecting));
+
+        // A stale driver must not remove a slot that has since replaced its own.
+        remove_closed_slot(&connections, "closed:443", &Http3Slot::default());
+        assert!(connections.lock().unwrap().contains_key("closed:443"));
+
+        let handshake = connecting.lock().await;
+        remove_closed_slot(&connections, "connecting:443", &connecting);
+        assert!(connections.lock().unwrap().contains_key("connecting:443"));
+        drop(handshake);
+
+        remove_closed_slot(&connections, "closed:443", &closed);
+        assert!(!connections.lock().unwrap().contains_key("closed:443"));
+    }
 }
@@ -213,6 +213,114 @@ pub trait ErrorDecoder: Send + Sync + 'static {
     fn decode(&self, method: &'static MethodDescriptor, response: BufferedResponse) -> Error;
 }
 
+/// Bounded HTTP request data presented to a server-side request decoder.
+#[derive(Clone)]
+pub struct BufferedRequest {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl std::fmt::Debug for BufferedRequest {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("BufferedRequest")
+            .field("header_count", &self.headers.len())
+            .field("body_length", &self.body.len())
+            .finish()
+    }
+}
+
+impl BufferedRequest {
+    pub(crate) const fn new(headers: HeaderMap, body: Bytes) -> Self {
+        Self { headers, body }
+    }
+
+    /// Returns request headers, including `Content-Type`.
+    pub const fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns bounded request bytes after any `Content-Encoding` was removed.
+    pub const fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Consumes the request into HTTP semantic parts.
+    pub fn into_parts(self) -> (HeaderMap, Bytes) {
+        (self.headers, self.body)
+    }
+}
+
+/// Buffered invocation result presented to a server-side response encoder.
+pub struct ResponseEncoding<'a> {
+    status: StatusCode,
+    headers: &'a HeaderMap,
+    body: &'a [u8],
+    max_body_bytes: usize,
+}
+
+impl<'a> ResponseEncoding<'a> {
+    pub(crate) const fn new(
+        status: StatusCode,
+        headers: &'a HeaderMap,
+        body: &'a [u8],
+        max_body_bytes: usize,
+    ) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+            max_body_bytes,
+        }
+    }
+
+    /// Returns the HTTP status selected by the handler.
+    pub const fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns application response headers.
+    pub const fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+
+    /// Returns the invocation result as one JSON document.
+    pub const fn body(&self) -> &[u8] {
+        self.body
+    }
+
+    /// Returns the configured response body limit that the encoded bytes must fit.
+    pub const fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+}
+
+/// Decodes one buffered server request body of a registered binding.
+pub trait RequestDecoder: Send + Sync + 'static {
+    /// Decodes the body into the JSON document that `#[param(body)]` fields are bound from.
+    ///
+    /// A returned error is sent to the caller as Problem Details and should normally use
+    /// [`crate::ErrorCategory::InvalidArgument`]. A panic is isolated as a server codec failure.
+    fn decode(
+        &self,
+        method: &'static MethodDescriptor,
+        request: BufferedRequest,
+    ) -> Result<serde_json::Value, Error>;
+}
+
+/// Encodes one buffered server success response of a registered binding.
+pub trait ResponseEncoder: Send + Sync + 'static {
+    /// Re-encodes the JSON invocation result as the binding's response body.
+    ///
+    /// Output longer than [`ResponseEncoding::max_body_bytes`] is rejected with
+    /// `response_too_large`; streaming responses and errors never reach the encoder.
+    fn encode(
+        &self,
+        method: &'static MethodDescriptor,
+        response: ResponseEncoding<'_>,
+    ) -> Result<Bytes, Error>;
+}
+
 impl<T> RequestEncoder for std::sync::Arc<T>
 where
     T: RequestEncoder + ?Sized,
@@ -244,6 +352,32 @@ where
     }
 }
 
+impl<T> RequestDecoder for std::sync::Arc<T>
+where
+    T: RequestDecoder + ?Sized,
+{
+    fn decode(
+        &self,
+        method: &'static MethodDescriptor,
+        request: BufferedRequest,
+    ) -> Result<serde_json::Value, Error> {
+        (**self).decode(method, request)
+    }
+}
+
+impl<T> ResponseEncoder for std::sync::Arc<T>
+where
+    T: ResponseEncoder + ?Sized,
+{
+    fn encode(
+        &self,
+        method: &'static MethodDescriptor,
+        response: ResponseEncoding<'_>,
+    ) -> Result<Bytes, Error> {
+        (**self).encode(method, response)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +403,19 @@ mod tests {
         assert!(debug.contains("body_length: 12"));
         assert!(!debug.contains("private-"));
     }
+
+    #[test]
+    fn buffered_request_debug_never_expands_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            http::HeaderValue::from_static("private-authorization"),
+        );
+        let request = BufferedRequest::new(headers, Bytes::from_static(b"private-body"));
+
+        let debug = format!("{request:?}");
+        assert!(debug.contains("header_count: 1"));
+        assert!(debug.contains("body_length: 12"));
+        assert!(!debug.contains("private-"));
+    }
 }
@@ -1,15 +1,20 @@
-use crate::runtime::{
-    budget::{BudgetedWriteFailure, BudgetedWriter, ByteBudget, BytePermit},
-    deadline::Deadline,
+use crate::{
+    BodyStream, BodyStreamSlot,
+    runtime::{
+        budget::{BudgetedWriteFailure, BudgetedWriter, ByteBudget, BytePermit},
+        deadline::Deadline,
+    },
 };
 use bytes::Bytes;
 use fusen_contract::{HttpBindingId, MethodDescriptor, ServiceDescriptor, ServiceInstance};
+use futures_util::Stream;
 use http::{Extensions, HeaderMap, StatusCode};
 use serde_json::{Map, Value};
 use std::{
     fmt,
     num::NonZeroU8,
     ops::{Deref, DerefMut},
+    pin::Pin,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -135,6 +140,7 @@ pub struct Call {
     headers: HeaderMap,
     extensions: Extensions,
     call_info: Option<CallInfo>,
+    body_stream: Option<BodyStreamSlot>,
 }
 
 impl fmt::Debug for Call {
@@ -144,6 +150,7 @@ impl fmt::Debug for Call {
             .field("header_count", &self.headers.len())
             .field("extension_count", &self.extensions.len())
             .field("call_info", &self.call_info)
+            .field("body_stream", &self.body_stream.is_some())
             .finish()
     }
 }
@@ -155,6 +162,7 @@ impl Call {
             headers: HeaderMap::new(),
             extensions: Extensions::new(),
             call_info: None,
+            body_stream: None,
         }
     }
 
@@ -183,8 +191,13 @@ impl Call {
         self.call_info.as_ref()
     }
 
-    pub(crate) fn into_parts(self) -> (HeaderMap, Extensions) {
-        (self.headers, self.extensions)
+    pub(crate) fn with_body_stream(mut self, body: BodyStream) -> Self {
+        self.body_stream = Some(BodyStreamSlot::new(body));
+        self
+    }
+
+    pub(crate) fn into_parts(self) -> (HeaderMap, Extensions, Option<BodyStreamSlot>) {
+        (self.headers, self.extensions, self.body_stream)
     }
 
     pub(crate) fn from_server(context: &Context) -> Self {
@@ -192,6 +205,7 @@ impl Call {
             headers: context.headers.clone(),
             extensions: context.extensions.clone(),
             call_info: Some(context.call_info()),
+            body_stream: None,
         }
     }
 }
@@ -205,12 +219,24 @@ enum ResponseSchemaOrigin {
     },
 }
 
+/// One encoded JSON stream item and the budget reservation that covers it.
+pub(crate) struct BodyFrame {
+    pub bytes: Bytes,
+    pub permit: Option<Arc<BytePermit>>,
+}
+
+pub(crate) type BodyFrames = Pin<Box<dyn Stream<Item = Result<BodyFrame, crate::Error>> + Send>>;
+
 /// Budget-aware encoded JSON body carried through interceptor and transport.
+///
+/// A streaming body carries encoded items instead of one buffered value. Its byte accessors see
+/// an empty body, and clones share the item stream so only one consumer can take it.
 #[derive(Clone)]
 pub struct Body {
     bytes: Bytes,
     budget_permit: Option<Arc<BytePermit>>,
     schema_origin: ResponseSchemaOrigin,
+    frames: Option<Arc<Mutex<Option<BodyFrames>>>>,
 }
 
 impl fmt::Debug for Body {
@@ -218,6 +244,7 @@ impl fmt::Debug for Body {
         formatter
             .debug_struct("Body")
             .field("length", &self.bytes.len())
+            .field("stream", &self.frames.is_some())
             .finish()
     }
 }
@@ -238,19 +265,69 @@ impl Body {
         self.bytes.is_empty()
     }
 
+    /// Returns whether this body streams encoded items rather than holding one buffered value.
+    pub const fn is_stream(&self) -> bool {
+        self.frames.is_some()
+    }
+
     /// Creates an encoded body from already bounded bytes.
     pub fn from_bytes(bytes: Bytes) -> Self {
         Self {
             bytes,
             budget_permit: None,
             schema_origin: ResponseSchemaOrigin::Unclassified,
+            frames: None,
+        }
+    }
+
+    pub(crate) fn from_frames(frames: BodyFrames) -> Self {
+        Self {
+            frames: Some(Arc::new(Mutex::new(Some(frames)))),
+            ..Self::from_bytes(Bytes::new())
         }
     }
 
+    pub(crate) fn encode_with_budget<T: serde::Serialize>(
+        value: &T,
+        limit: usize,
+        wire_overhead: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> Result<Self, crate::Error> {
+        let mut writer = BudgetedWriter::new(limit, budget, wire_overhead)
+            .map_err(|_| response_budget_exhausted())?;
+        serde_json::to_writer(&mut writer, value).map_err(|error| match writer.failure() {
+            Some(BudgetedWriteFailure::LimitExceeded) => response_too_large(),
+            Some(BudgetedWriteFailure::BudgetExhausted) => response_budget_exhausted(),
+            None => crate::Error::internal("failed to serialize invocation response", error),
+        })?;
+        let (bytes, permit) = writer.into_parts();
+        Ok(Self {
+            budget_permit: Some(permit),
+            ..Self::from_bytes(bytes)
+        })
+    }
+
     pub(crate) fn into_parts(self) -> (Bytes, Option<Arc<BytePermit>>) {
         (self.bytes, self.budget_permit)
     }
 
+    pub(crate) fn into_frame(self) -> BodyFrame {
+        BodyFrame {
+            bytes: self.bytes,
+            permit: self.budget_permit,
+        }
+    }
+
+    /// Takes the item stream; clones that lost the race observe `None`.
+    pub(crate) fn take_frames(&self) -> Option<BodyFrames> {
+        self.frames.as_ref().and_then(|frames| {
+            frames
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .take()
+        })
+    }
+
     pub(crate) fn hold_budget(&mut self, permit: BytePermit) {
         self.budget_permit = Some(Arc::new(permit));
     }
@@ -472,19 +549,7 @@ impl Response<Body> {
         wire_overhead: usize,
         budget: &Arc<ByteBudget>,
     ) -> Result<Self, crate::Error> {
-        let mut writer = BudgetedWriter::new(limit, budget, wire_overhead)
-            .map_err(|_| response_budget_exhausted())?;
-        serde_json::to_writer(&mut writer, &value).map_err(|error| match writer.failure() {
-            Some(BudgetedWriteFailure::LimitExceeded) => response_too_large(),
-            Some(BudgetedWriteFailure::BudgetExhausted) => response_budget_exhausted(),
-            None => crate::Error::internal("failed to serialize invocation response", error),
-        })?;
-        let (bytes, permit) = writer.into_parts();
-        Ok(Self::new(Body {
-            bytes,
-            budget_permit: Some(permit),
-            schema_origin: ResponseSchemaOrigin::Unclassified,
-        }))
+        Body::encode_with_budget(&value, limit, wire_overhead, budget).map(Self::new)
     }
 
     pub(crate) fn from_json_bytes(bytes: Bytes) -> Self {
@@ -495,15 +560,18 @@ impl Response<Body> {
         self.body.as_bytes()
     }
 
-    pub(crate) fn into_wire_parts(self) -> (StatusCode, HeaderMap, Bytes, Option<Arc<BytePermit>>) {
-        let (bytes, permit) = self.body.into_parts();
-        (self.status, self.headers, bytes, permit)
+    pub(crate) fn into_wire_parts(self) -> (StatusCode, HeaderMap, Body) {
+        (self.status, self.headers, self.body)
     }
 
     pub(crate) fn hold_budget(&mut self, permit: BytePermit) {
         self.body.hold_budget(permit);
     }
 
+    pub(crate) fn take_body(&mut self) -> Body {
+        std::mem::replace(&mut self.body, Body::from_bytes(Bytes::new()))
+    }
+
     pub(crate) fn mark_declared_serialize_schema_origin(
         &mut self,
         method: &'static MethodDescriptor,
@@ -591,6 +659,7 @@ pub struct Context {
     response_limit: usize,
     response_wire_overhead: usize,
     response_budget: Arc<ByteBudget>,
+    body_stream: Option<BodyStreamSlot>,
 }
 
 impl fmt::Debug for Context {
@@ -660,6 +729,7 @@ impl Context {
             response_limit: parts.response_limit,
             response_wire_overhead: parts.response_wire_overhead,
             response_budget: parts.response_budget,
+            body_stream: None,
         }
     }
 
@@ -797,6 +867,22 @@ impl Context {
         self.arguments = Some(arguments);
     }
 
+    pub(crate) fn set_body_stream(&mut self, body: Option<BodyStreamSlot>) {
+        self.body_stream = body;
+    }
+
+    /// Takes the streamed request body; clones that lose the race observe an error.
+    pub(crate) fn take_body_stream(&self) -> Result<BodyStream, crate::Error> {
+        match &self.body_stream {
+            Some(body) => body.take(),
+            None => Err(crate::Error::framework(
+                crate::ErrorCategory::Internal,
+                "body_stream_missing",
+                "invocation does not carry a streamed request body",
+            )),
+        }
+    }
+
     pub(crate) fn call_info(&self) -> CallInfo {
         CallInfo {
             request_id: self.request_id.clone(),
@@ -101,6 +101,8 @@ pub enum ErrorCategory {
     PermissionDenied,
     /// A request or response body exceeds its configured limit.
     PayloadTooLarge,
+    /// The request body uses a media type or content coding the server cannot accept.
+    UnsupportedMediaType,
     /// Admission or another bounded resource is exhausted.
     ResourceExhausted,
     /// No healthy service or transport is currently available.
@@ -129,6 +131,7 @@ impl ErrorCategory {
             Self::Unauthenticated => StatusCode::UNAUTHORIZED,
             Self::PermissionDenied => StatusCode::FORBIDDEN,
             Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Self::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
             Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
             Self::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
@@ -566,6 +569,7 @@ pub(crate) fn category_from_status(status: StatusCode) -> ErrorCategory {
         StatusCode::NOT_FOUND => ErrorCategory::NotFound,
         StatusCode::CONFLICT => ErrorCategory::Conflict,
         StatusCode::PAYLOAD_TOO_LARGE => ErrorCategory::PayloadTooLarge,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCategory::UnsupportedMediaType,
         StatusCode::TOO_MANY_REQUESTS => ErrorCategory::ResourceExhausted,
         StatusCode::SERVICE_UNAVAILABLE => ErrorCategory::Unavailable,
         StatusCode::GATEWAY_TIMEOUT | StatusCode::REQUEST_TIMEOUT => {
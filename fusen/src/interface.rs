@@ -23,6 +23,7 @@ pub fn http_method(
                 ArgumentSource::Body => fusen_contract::HttpParameterSource::Body,
                 ArgumentSource::QueryMap => fusen_contract::HttpParameterSource::QueryMap,
                 ArgumentSource::HeaderMap => fusen_contract::HttpParameterSource::HeaderMap,
+                ArgumentSource::BodyStream => fusen_contract::HttpParameterSource::BodyStream,
             };
             let cardinality = if field.repeated {
                 fusen_contract::HttpParameterCardinality::Repeated
@@ -58,6 +59,8 @@ pub enum ArgumentSource {
     QueryMap,
     /// An object expanded into request headers.
     HeaderMap,
+    /// The raw request body streamed in chunks.
+    BodyStream,
 }
 
 /// Static wire metadata for one named interface parameter.
@@ -87,6 +90,12 @@ pub fn encode_argument<T: Serialize>(value: &T) -> Result<Value, Error> {
         .map_err(|error| Error::internal("failed to serialize invocation argument", error))
 }
 
+/// Attaches the streamed request body of a `#[param(body_stream)]` parameter to call metadata.
+#[doc(hidden)]
+pub fn with_body_stream(call: crate::Call, body: crate::BodyStream) -> crate::Call {
+    call.with_body_stream(body)
+}
+
 pub(crate) fn decode_argument<T: DeserializeOwned>(
     value: Value,
     text_encoded: bool,
@@ -5,14 +5,16 @@
 extern crate self as fusen_rs;
 
 mod client;
-/// Client-side HTTP request and response codec extension APIs.
+/// Client- and server-side HTTP request and response codec extension APIs.
 pub mod codec;
 mod context;
+pub(crate) use context::{BodyFrame, BodyFrames};
 mod error;
 pub(crate) use error::RemoteErrorParts;
 /// Shared client/server interceptor API.
 pub mod interceptor;
 mod interface;
+mod pem;
 /// Client routing, load balancing, and retry policy APIs.
 pub mod policy;
 mod projection;
@@ -22,18 +24,30 @@ mod runtime;
 pub mod sensitive;
 mod server;
 mod service;
+mod stream;
+pub(crate) use stream::BodyStreamSlot;
+#[cfg(feature = "hot-tls")]
+mod tls;
 mod wire;
 
 pub use client::{
-    BreakerThreshold, BreakerThresholdBuilder, CircuitBreakerConfig, CircuitBreakerConfigBuilder,
+    AdaptiveConcurrencyConfig, AdaptiveConcurrencyConfigBuilder, BreakerThreshold,
+    BreakerThresholdBuilder, CircuitBreakerConfig, CircuitBreakerConfigBuilder,
     ClientAdmissionConfig, ClientAdmissionConfigBuilder, ClientBuilder, ClientConfig,
     ClientConfigBuilder, ClientHttpConfig, ClientHttpConfigBuilder, ClientRuntime,
-    ClientRuntimeBuilder, ClientState, DiscoveryConfig, DiscoveryConfigBuilder, QueueConfig,
-    QueueConfigBuilder, RetryConfig, RetryConfigBuilder,
+    ClientRuntimeBuilder, ClientState, ClientTlsConfig, ClientTlsConfigBuilder, DiscoveryConfig,
+    DiscoveryConfigBuilder, HedgingConfig, HedgingConfigBuilder, MethodConfig, MethodConfigBuilder,
+    OutlierDetectionConfig, OutlierDetectionConfigBuilder, QueueConfig, QueueConfigBuilder,
+    RetryConfig, RetryConfigBuilder, SlowStartConfig, SlowStartConfigBuilder,
+};
+#[cfg(feature = "compression")]
+pub use client::{
+    ClientCompressionConfig, ClientCompressionConfigBuilder, ClientRequestCompressionConfig,
+    ClientRequestCompressionConfigBuilder,
 };
 pub use codec::{
-    BufferedResponse, EncodedRequest, ErrorDecoder, RequestEncoder, RequestEncoding,
-    ResponseDecoder,
+    BufferedRequest, BufferedResponse, EncodedRequest, ErrorDecoder, RequestDecoder,
+    RequestEncoder, RequestEncoding, ResponseDecoder, ResponseEncoder, ResponseEncoding,
 };
 pub use context::{Arguments, Body, Call, CallInfo, Context, InterceptionStage, Response, Side};
 pub use error::{
@@ -43,26 +57,42 @@ pub use error::{
 };
 pub use fusen_contract as contract;
 pub use fusen_contract::{
-    EndpointCapabilities, HTTP_JSON_V1, HttpBindingId, HttpOperation, HttpParameter,
-    HttpParameterCardinality, HttpParameterSource, HttpVersionPolicy, HttpVersionSet,
-    MethodSensitivity, SensitiveArgument, SensitiveField, SensitiveFields, SensitiveShape,
-    SensitiveShapeResolver, SensitivityKind,
+    EndpointCapabilities, HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId, HttpOperation,
+    HttpParameter, HttpParameterCardinality, HttpParameterSource, HttpVersionPolicy,
+    HttpVersionSet, MethodPriority, MethodSensitivity, SensitiveArgument, SensitiveField,
+    SensitiveFields, SensitiveShape, SensitiveShapeResolver, SensitivityKind,
 };
 pub use fusen_observability::{MetricsRecorder, NoopMetricsRecorder};
 pub use fusen_procedural_macro::{interface, method};
 pub use fusen_register::{RegistrationHandle, Registry, SubscriptionHandle};
 pub use interceptor::{Interceptor, InterceptorFuture, InterceptorResult, Next};
-pub use policy::{InstanceRouter, InstanceSnapshot, LoadBalancer, RouteRequest, WeightedRandom};
+pub use policy::{
+    AttemptCompletion, AttemptOutcome, ConsistentHash, ConsistentHashBuilder, InstanceRouter,
+    InstanceSnapshot, LeastRequest, LoadBalancer, LocalityRouter, LocalityRouterBuilder, PeakEwma,
+    RoundRobin, RouteRequest, TrafficRoute, TrafficRouter, TrafficRule, TrafficRuleBuilder,
+    TrafficRules, TrafficRulesBuilder, WeightedRandom,
+};
 pub use resilience::{FailureClass, RetryDecision, RetryDecisionContext, RetryPolicy};
 pub use sensitive::{
     PolicySanitizer, ProjectionLimits, Sanitization, SanitizationContext, SanitizationTarget,
     SanitizedValue, Sanitizer,
 };
+#[cfg(feature = "hot-tls")]
+pub use server::{HotServerTlsConfig, HotServerTlsConfigBuilder};
 pub use server::{
-    HttpServerConfig, HttpServerConfigBuilder, RunningServer, Server, ServerBuilder, ServerConfig,
-    ServerConfigBuilder, ServerHandle, ServerRegistryConfig, ServerRegistryConfigBuilder,
-    ServerRequestConfig, ServerRequestConfigBuilder, ServerState,
+    HttpServerConfig, HttpServerConfigBuilder, LoadSheddingConfig, LoadSheddingConfigBuilder,
+    RateLimitConfig, RateLimitConfigBuilder, RateLimitRule, RateLimitRuleBuilder, RunningServer,
+    Server, ServerBuilder, ServerConfig, ServerConfigBuilder, ServerHandle, ServerRegistryConfig,
+    ServerRegistryConfigBuilder, ServerRequestConfig, ServerRequestConfigBuilder, ServerState,
+    ServerTlsConfig, ServerTlsConfigBuilder,
 };
+#[cfg(feature = "compression")]
+pub use server::{ServerCompressionConfig, ServerCompressionConfigBuilder};
+pub use stream::{BodyChunk, BodyStream, ResponseStream};
+#[cfg(feature = "hot-tls")]
+pub use tls::TlsCertificate;
+#[cfg(feature = "compression")]
+pub use wire::ContentCoding;
 
 /// Versioned ABI used exclusively by generated code.
 #[doc(hidden)]
@@ -70,17 +100,20 @@ pub mod __macro {
     /// ABI version used by fusen-rs 0.9 generated code.
     pub mod v1 {
         pub use crate::client::ServiceClient;
-        pub use crate::interface::{ArgumentField, ArgumentSource, encode_argument, http_method};
+        pub use crate::interface::{
+            ArgumentField, ArgumentSource, encode_argument, http_method, with_body_stream,
+        };
         pub use crate::service::{
             IntoServerService, PreparedService, ServerInvocation, ServerService, method_not_found,
         };
         pub use crate::{
-            Arguments, Call, ClientBuilder, ClientRuntime, Error, Interceptor, InterceptorFuture,
-            Response,
+            Arguments, BodyStream, Call, ClientBuilder, ClientRuntime, Error, Interceptor,
+            InterceptorFuture, Response, ResponseStream,
         };
         pub use fusen_contract::{
-            MethodDescriptor, MethodId, MethodSensitivity, SensitiveArgument, SensitiveFields,
-            SensitiveShape, SensitivityKind, ServiceDescriptor, ServiceSelector,
+            MethodClientDefaults, MethodDescriptor, MethodId, MethodPriority, MethodSensitivity,
+            SensitiveArgument, SensitiveFields, SensitiveShape, SensitivityKind, ServiceDescriptor,
+            ServiceSelector,
         };
         pub use http;
     }
@@ -0,0 +1,89 @@
+use rustls::pki_types::{
+    CertificateDer, PrivateKeyDer,
+    pem::{self, PemObject},
+};
+use std::path::Path;
+
+/// A TLS PEM file that could not be loaded, described without quoting the file's contents.
+pub(crate) struct PemFileError {
+    message: String,
+    source: Option<std::io::Error>,
+}
+
+impl PemFileError {
+    /// Returns the public message and, for I/O failures only, the underlying error.
+    pub(crate) fn into_parts(self) -> (String, Option<std::io::Error>) {
+        (self.message, self.source)
+    }
+}
+
+/// Reads every certificate in `path`; `side` and `item` name the file in error messages.
+pub(crate) fn read_certificates(
+    path: &Path,
+    side: &'static str,
+    item: &'static str,
+) -> Result<Vec<CertificateDer<'static>>, PemFileError> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|error| pem_error(side, item, error))?;
+    if certificates.is_empty() {
+        return Err(PemFileError {
+            message: format!("{side} TLS {item} file contains no certificates"),
+            source: None,
+        });
+    }
+    Ok(certificates)
+}
+
+/// Reads the first private key in `path`.
+pub(crate) fn read_private_key(
+    path: &Path,
+    side: &'static str,
+) -> Result<PrivateKeyDer<'static>, PemFileError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|error| pem_error(side, "private key", error))
+}
+
+// Parser errors may quote PEM lines, so only I/O failures retain their source.
+fn pem_error(side: &'static str, item: &'static str, error: pem::Error) -> PemFileError {
+    match error {
+        pem::Error::Io(error) => PemFileError {
+            message: format!("failed to read the {side} TLS {item} file"),
+            source: Some(error),
+        },
+        pem::Error::NoItemsFound => PemFileError {
+            message: format!("{side} TLS {item} file contains no PEM {item}"),
+            source: None,
+        },
+        _ => PemFileError {
+            message: format!("{side} TLS {item} file is not valid PEM"),
+            source: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_failures_never_quote_the_file() {
+        let path = std::env::temp_dir().join(format!("fusen-pem-{}.pem", std::process::id()));
+        std::fs::write(
+            &path,
+            "-----BEGIN CERTIFICATE-----\nc2VjcmV0!!\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        let error = read_certificates(&path, "server", "certificate chain").unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        let (message, source) = error.into_parts();
+        assert_eq!(
+            message,
+            "server TLS certificate chain file is not valid PEM"
+        );
+        assert!(source.is_none());
+
+        let (message, source) = read_private_key(&path, "client").unwrap_err().into_parts();
+        assert_eq!(message, "failed to read the client TLS private key file");
+        assert!(source.is_some());
+    }
+}
@@ -0,0 +1,1332 @@
+mod traffic;
+
+use crate::error::{ConfigValidationError, ConfigValidationErrorKind};
+pub use crate::{
+    context::Context,
+    error::{Error, ErrorCategory},
+    resilience::{FailureClass, RetryDecision, RetryDecisionContext, RetryPolicy},
+};
+pub use fusen_contract::ServiceInstance;
+use rand::RngExt;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+pub use traffic::{
+    TrafficRoute, TrafficRouter, TrafficRule, TrafficRuleBuilder, TrafficRules, TrafficRulesBuilder,
+};
+
+/// Most endpoints whose balancing state is retained before idle entries are pruned.
+const MAX_TRACKED_ENDPOINTS: usize = 10_000;
+
+/// Immutable provider set passed through instance routers and load balancers.
+#[derive(Clone, Debug)]
+pub struct InstanceSnapshot(Arc<[ServiceInstance]>);
+
+impl InstanceSnapshot {
+    /// Creates a snapshot from owned instances.
+    pub fn new(instances: Vec<ServiceInstance>) -> Self {
+        Self(Arc::from(instances))
+    }
+
+    /// Returns whether two snapshots share the same allocation.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for InstanceSnapshot {
+    type Target = [ServiceInstance];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Input to one instance-routing decision.
+pub struct RouteRequest<'a> {
+    context: &'a Context,
+    instances: InstanceSnapshot,
+    health: Option<&'a dyn Fn(&ServiceInstance) -> bool>,
+}
+
+impl<'a> RouteRequest<'a> {
+    pub(crate) fn new(context: &'a Context, instances: InstanceSnapshot) -> Self {
+        Self {
+            context,
+            instances,
+            health: None,
+        }
+    }
+
+    pub(crate) fn with_health(mut self, health: &'a dyn Fn(&ServiceInstance) -> bool) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Returns attempt-scoped service invocation metadata.
+    pub const fn context(&self) -> &Context {
+        self.context
+    }
+
+    /// Returns the current immutable provider snapshot.
+    pub const fn instances(&self) -> &InstanceSnapshot {
+        &self.instances
+    }
+
+    /// Returns whether the runtime currently expects `instance` to accept an attempt.
+    ///
+    /// An instance is unhealthy while its endpoint breaker is open or while it is ejected as a
+    /// latency outlier; instances without such state are healthy.
+    pub fn is_healthy(&self, instance: &ServiceInstance) -> bool {
+        self.health.is_none_or(|health| health(instance))
+    }
+
+    /// Consumes this request and returns the provider snapshot.
+    pub fn into_instances(self) -> InstanceSnapshot {
+        self.instances
+    }
+}
+
+/// Filters or reorders a discovery snapshot before endpoint selection.
+pub trait InstanceRouter: Send + Sync + 'static {
+    /// Returns the eligible snapshot for this attempt.
+    fn route(&self, request: RouteRequest<'_>) -> Result<InstanceSnapshot, Error>;
+}
+
+impl<T> InstanceRouter for Arc<T>
+where
+    T: InstanceRouter + ?Sized,
+{
+    fn route(&self, request: RouteRequest<'_>) -> Result<InstanceSnapshot, Error> {
+        (**self).route(request)
+    }
+}
+
+/// Built-in router that keeps attempts in the client's own zone or region.
+///
+/// Instances are grouped by their `zone` and `region` metadata into tiers: the local zone, the
+/// local region, then every instance. The first tier with at least one healthy instance and a
+/// healthy fraction of at least `min_healthy_ratio` is routed, so traffic fails over to the
+/// region and then to every zone as the local tier degrades, and returns once it recovers.
+/// Instances without locality metadata only belong to the last tier.
+#[derive(Clone, Debug)]
+pub struct LocalityRouter {
+    zone: Option<String>,
+    region: Option<String>,
+    min_healthy_ratio: f64,
+}
+
+impl LocalityRouter {
+    /// Instance metadata key naming the availability zone.
+    pub const ZONE_KEY: &'static str = "zone";
+    /// Instance metadata key naming the region.
+    pub const REGION_KEY: &'static str = "region";
+    /// Default healthy fraction below which a locality tier fails over to the next one.
+    pub const DEFAULT_MIN_HEALTHY_RATIO: f64 = 0.7;
+
+    /// Starts a router builder; at least one of the local zone or region must be set.
+    pub fn builder() -> LocalityRouterBuilder {
+        LocalityRouterBuilder {
+            zone: None,
+            region: None,
+            min_healthy_ratio: Self::DEFAULT_MIN_HEALTHY_RATIO,
+        }
+    }
+
+    /// Returns the local zone.
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    /// Returns the local region.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Returns the healthy fraction a locality tier needs to keep its traffic.
+    pub const fn min_healthy_ratio(&self) -> f64 {
+        self.min_healthy_ratio
+    }
+}
+
+impl InstanceRouter for LocalityRouter {
+    fn route(&self, request: RouteRequest<'_>) -> Result<InstanceSnapshot, Error> {
+        let tiers = [
+            self.zone.as_deref().map(|zone| (Self::ZONE_KEY, zone)),
+            self.region
+                .as_deref()
+                .map(|region| (Self::REGION_KEY, region)),
+        ];
+        for (key, value) in tiers.into_iter().flatten() {
+            let local = request
+                .instances()
+                .iter()
+                .filter(|instance| instance.metadata().get(key).map(String::as_str) == Some(value))
+                .cloned()
+                .collect::<Vec<_>>();
+            let healthy = local
+                .iter()
+                .filter(|instance| request.is_healthy(instance))
+                .count();
+            if healthy > 0 && healthy as f64 >= local.len() as f64 * self.min_healthy_ratio {
+                return Ok(InstanceSnapshot::new(local));
+            }
+        }
+        Ok(request.into_instances())
+    }
+}
+
+/// Builder for [`LocalityRouter`].
+#[derive(Clone, Debug)]
+pub struct LocalityRouterBuilder {
+    zone: Option<String>,
+    region: Option<String>,
+    min_healthy_ratio: f64,
+}
+
+impl LocalityRouterBuilder {
+    /// Sets the client's zone, matched against the `zone` instance metadata.
+    pub fn zone(mut self, value: impl Into<String>) -> Self {
+        self.zone = Some(value.into());
+        self
+    }
+
+    /// Sets the client's region, matched against the `region` instance metadata.
+    pub fn region(mut self, value: impl Into<String>) -> Self {
+        self.region = Some(value.into());
+        self
+    }
+
+    /// Sets the healthy fraction, from 0 to 1, a tier needs before traffic fails over.
+    ///
+    /// `0.0` fails over only when no instance of the tier is healthy.
+    pub const fn min_healthy_ratio(mut self, value: f64) -> Self {
+        self.min_healthy_ratio = value;
+        self
+    }
+
+    /// Validates and builds the router.
+    pub fn build(self) -> Result<LocalityRouter, ConfigValidationError> {
+        if self.zone.is_none() && self.region.is_none() {
+            return Err(ConfigValidationError::new(
+                ConfigValidationErrorKind::Inconsistent,
+                "instance_router.locality",
+                "requires a zone or a region",
+            ));
+        }
+        if self.zone.as_deref().is_some_and(str::is_empty) {
+            return Err(out_of_range(
+                "instance_router.locality.zone",
+                "must not be empty",
+            ));
+        }
+        if self.region.as_deref().is_some_and(str::is_empty) {
+            return Err(out_of_range(
+                "instance_router.locality.region",
+                "must not be empty",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.min_healthy_ratio) {
+            return Err(out_of_range(
+                "instance_router.locality.min_healthy_ratio",
+                "must be between 0 and 1 inclusive",
+            ));
+        }
+        Ok(LocalityRouter {
+            zone: self.zone,
+            region: self.region,
+            min_healthy_ratio: self.min_healthy_ratio,
+        })
+    }
+}
+
+/// Returns the zone of an instance, or its region when no zone is advertised.
+pub(crate) fn instance_locality(instance: &ServiceInstance) -> Option<&str> {
+    instance
+        .metadata()
+        .get(LocalityRouter::ZONE_KEY)
+        .or_else(|| instance.metadata().get(LocalityRouter::REGION_KEY))
+        .map(String::as_str)
+}
+
+/// How a physical attempt reported to a [`LoadBalancer`] ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AttemptOutcome {
+    /// The endpoint returned a successful response head.
+    Success,
+    /// The attempt failed with the given classification.
+    Failure(FailureClass),
+    /// The attempt was cancelled, superseded by a hedge, or rejected locally before it was sent.
+    Cancelled,
+}
+
+/// Completion of one physical attempt, reported after [`LoadBalancer::attempt_started`].
+#[derive(Clone, Copy, Debug)]
+pub struct AttemptCompletion<'a> {
+    instance: &'a ServiceInstance,
+    latency: Duration,
+    outcome: AttemptOutcome,
+}
+
+impl<'a> AttemptCompletion<'a> {
+    pub(crate) const fn new(
+        instance: &'a ServiceInstance,
+        latency: Duration,
+        outcome: AttemptOutcome,
+    ) -> Self {
+        Self {
+            instance,
+            latency,
+            outcome,
+        }
+    }
+
+    /// Returns the instance the attempt was sent to.
+    pub const fn instance(&self) -> &ServiceInstance {
+        self.instance
+    }
+
+    /// Returns the time from attempt start to the response head or failure.
+    pub const fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Returns how the attempt ended.
+    pub const fn outcome(&self) -> AttemptOutcome {
+        self.outcome
+    }
+}
+
+/// Selects one endpoint index from a routed snapshot.
+///
+/// The runtime reports every attempt sent to a selected instance through
+/// [`attempt_started`](Self::attempt_started) and exactly one later
+/// [`attempt_finished`](Self::attempt_finished), so stateful balancers can track in-flight work
+/// and latency, and reports each new discovery snapshot through
+/// [`instances_discovered`](Self::instances_discovered). The hooks default to no-ops and must
+/// not block.
+pub trait LoadBalancer: Send + Sync + 'static {
+    /// Returns a valid index into `instances`.
+    fn select(&self, context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error>;
+
+    /// Observes a newly discovered provider set before it is filtered and routed.
+    ///
+    /// Snapshots passed to [`select`](Self::select) are subsets of the latest discovered set.
+    /// Clients with fixed endpoints never report one.
+    fn instances_discovered(&self, instances: &InstanceSnapshot) {
+        let _ = instances;
+    }
+
+    /// Observes that an attempt is about to be sent to `instance`.
+    fn attempt_started(&self, instance: &ServiceInstance) {
+        let _ = instance;
+    }
+
+    /// Observes the end of an attempt previously reported to `attempt_started`.
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        let _ = completion;
+    }
+}
+
+impl<T> LoadBalancer for Arc<T>
+where
+    T: LoadBalancer + ?Sized,
+{
+    fn select(&self, context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        (**self).select(context, instances)
+    }
+
+    fn instances_discovered(&self, instances: &InstanceSnapshot) {
+        (**self).instances_discovered(instances);
+    }
+
+    fn attempt_started(&self, instance: &ServiceInstance) {
+        (**self).attempt_started(instance);
+    }
+
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        (**self).attempt_finished(completion);
+    }
+}
+
+/// Built-in weighted-random load balancer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WeightedRandom;
+
+impl LoadBalancer for WeightedRandom {
+    fn select(&self, _context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        if instances.is_empty() {
+            return Err(no_instances());
+        }
+        let total = instances
+            .iter()
+            .map(|instance| instance.weight().get())
+            .sum::<f64>();
+        if !total.is_finite() || total <= 0.0 {
+            return Err(no_instances());
+        }
+        let mut target = rand::rng().random_range(0.0..total);
+        for (index, instance) in instances.iter().enumerate() {
+            if target < instance.weight().get() {
+                return Ok(index);
+            }
+            target -= instance.weight().get();
+        }
+        Ok(instances.len() - 1)
+    }
+}
+
+/// Built-in round-robin load balancer; it ignores instance weights.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl LoadBalancer for RoundRobin {
+    fn select(&self, _context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        if instances.is_empty() {
+            return Err(no_instances());
+        }
+        Ok(self.next.fetch_add(1, Ordering::Relaxed) % instances.len())
+    }
+}
+
+/// Built-in power-of-two-choices balancer that prefers the instance with fewer in-flight
+/// attempts per unit of weight.
+#[derive(Debug, Default)]
+pub struct LeastRequest {
+    in_flight: InFlight,
+}
+
+impl LoadBalancer for LeastRequest {
+    fn select(&self, _context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        power_of_two_choices(instances, |instance| {
+            (self.in_flight.get(instance) + 1) as f64 / instance.weight().get()
+        })
+    }
+
+    fn attempt_started(&self, instance: &ServiceInstance) {
+        self.in_flight.start(instance);
+    }
+
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        self.in_flight.finish(completion.instance());
+    }
+}
+
+/// In-flight attempt counts per endpoint; endpoints without attempts have no entry.
+#[derive(Debug, Default)]
+struct InFlight {
+    counts: Mutex<HashMap<String, usize>>,
+}
+
+impl InFlight {
+    fn start(&self, instance: &ServiceInstance) {
+        *self
+            .lock()
+            .entry(instance.endpoint().as_str().to_owned())
+            .or_default() += 1;
+    }
+
+    fn finish(&self, instance: &ServiceInstance) {
+        let mut counts = self.lock();
+        let endpoint = instance.endpoint().as_str();
+        if let Some(count) = counts.get_mut(endpoint) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(endpoint);
+            }
+        }
+    }
+
+    fn get(&self, instance: &ServiceInstance) -> usize {
+        self.lock()
+            .get(instance.endpoint().as_str())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Built-in power-of-two-choices balancer over peak-EWMA latency.
+///
+/// Each endpoint's cost is its latency estimate times its in-flight attempts plus one, divided by
+/// its weight. A slower response raises the estimate immediately, while a faster one moves it
+/// toward the sample by an amount that grows with the time since the previous update, using the
+/// configured decay time constant. Failures never lower the estimate, and endpoints without
+/// samples start from the default round-trip time.
+#[derive(Debug)]
+pub struct PeakEwma {
+    default_rtt: Duration,
+    decay: Duration,
+    endpoints: Mutex<HashMap<String, EwmaState>>,
+}
+
+#[derive(Debug)]
+struct EwmaState {
+    rtt_nanos: f64,
+    updated: Instant,
+    in_flight: usize,
+}
+
+impl PeakEwma {
+    /// Default latency assumed for endpoints without samples.
+    pub const DEFAULT_RTT: Duration = Duration::from_millis(30);
+    /// Default decay time constant.
+    pub const DEFAULT_DECAY: Duration = Duration::from_secs(10);
+
+    /// Creates a balancer with an initial latency estimate and a positive decay time constant.
+    pub fn new(default_rtt: Duration, decay: Duration) -> Result<Self, ConfigValidationError> {
+        if decay.is_zero() {
+            return Err(out_of_range(
+                "load_balancer.peak_ewma.decay",
+                "must be greater than zero",
+            ));
+        }
+        Ok(Self {
+            default_rtt,
+            decay,
+            endpoints: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, EwmaState>> {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl Default for PeakEwma {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_RTT, Self::DEFAULT_DECAY).expect("default decay is positive")
+    }
+}
+
+impl LoadBalancer for PeakEwma {
+    fn select(&self, _context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        let endpoints = self.lock();
+        let default_rtt = self.default_rtt.as_nanos() as f64;
+        power_of_two_choices(instances, |instance| {
+            let (rtt, in_flight) = endpoints
+                .get(instance.endpoint().as_str())
+                .map_or((default_rtt, 0), |state| (state.rtt_nanos, state.in_flight));
+            rtt * (in_flight + 1) as f64 / instance.weight().get()
+        })
+    }
+
+    fn attempt_started(&self, instance: &ServiceInstance) {
+        let mut endpoints = self.lock();
+        let now = Instant::now();
+        if endpoints.len() >= MAX_TRACKED_ENDPOINTS
+            && !endpoints.contains_key(instance.endpoint().as_str())
+        {
+            // Idle estimates older than a few time constants carry no information.
+            let horizon = self.decay.saturating_mul(5);
+            endpoints.retain(|_, state| {
+                state.in_flight > 0 || now.saturating_duration_since(state.updated) < horizon
+            });
+        }
+        let default_rtt = self.default_rtt.as_nanos() as f64;
+        endpoints
+            .entry(instance.endpoint().as_str().to_owned())
+            .or_insert_with(|| EwmaState {
+                rtt_nanos: default_rtt,
+                updated: now,
+                in_flight: 0,
+            })
+            .in_flight += 1;
+    }
+
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        let mut endpoints = self.lock();
+        let Some(state) = endpoints.get_mut(completion.instance().endpoint().as_str()) else {
+            return;
+        };
+        state.in_flight = state.in_flight.saturating_sub(1);
+        let now = Instant::now();
+        let sample = completion.latency().as_nanos() as f64;
+        let rtt = match completion.outcome() {
+            AttemptOutcome::Success if sample > state.rtt_nanos => sample,
+            AttemptOutcome::Success => {
+                let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+                let retained = (-elapsed / self.decay.as_secs_f64()).exp();
+                state.rtt_nanos * retained + sample * (1.0 - retained)
+            }
+            AttemptOutcome::Failure(_) => state.rtt_nanos.max(sample),
+            AttemptOutcome::Cancelled => return,
+        };
+        state.rtt_nanos = rtt;
+        state.updated = now;
+    }
+}
+
+/// Built-in ring-hash load balancer that keeps calls with the same key on the same instance.
+///
+/// The key is a named call argument or a request header. Each `InstanceId` owns `replicas`
+/// points on a hash ring, so an instance joining or leaving only remaps the keys between its
+/// points and their neighbours. Weights are ignored. With bounded load, an instance already
+/// carrying `load_factor` times its fair share of in-flight attempts is skipped and the key
+/// spills over to the next instance on the ring; like [`LeastRequest`], attempts are counted per
+/// endpoint. Calls without a key are spread at random.
+#[derive(Debug)]
+pub struct ConsistentHash {
+    key: HashKey,
+    replicas: usize,
+    load_factor: f64,
+    ring: Mutex<Arc<HashRing>>,
+    in_flight: InFlight,
+}
+
+#[derive(Clone, Debug)]
+enum HashKey {
+    Argument(String),
+    Header(http::HeaderName),
+}
+
+/// Ring points of every instance seen since discovery last changed, as `(hash, member index)`
+/// sorted by hash.
+#[derive(Debug, Default)]
+struct HashRing {
+    members: HashMap<String, usize>,
+    points: Vec<(u64, usize)>,
+}
+
+impl HashRing {
+    /// Returns a copy of this ring that also has the members of `instances` it lacks.
+    fn with_members(&self, instances: &InstanceSnapshot, replicas: usize) -> Self {
+        let mut members = self.members.clone();
+        let mut points = self.points.clone();
+        for instance in instances.iter() {
+            let id = instance.instance_id().as_str();
+            let next = members.len();
+            let member = *members.entry(id.to_owned()).or_insert(next);
+            if member == next {
+                for replica in 0..replicas as u64 {
+                    points.push((
+                        stable_hash(&[id.as_bytes(), &replica.to_le_bytes()]),
+                        member,
+                    ));
+                }
+            }
+        }
+        points.sort_unstable();
+        Self { members, points }
+    }
+
+    /// Returns a copy of this ring without the members that are absent from `instances`, or
+    /// `None` if every member is present.
+    fn retain(&self, instances: &InstanceSnapshot) -> Option<Self> {
+        let present = instances
+            .iter()
+            .map(|instance| instance.instance_id().as_str())
+            .collect::<HashSet<_>>();
+        if self.members.keys().all(|id| present.contains(id.as_str())) {
+            return None;
+        }
+        let mut renumbered = vec![None; self.members.len()];
+        let mut members = HashMap::with_capacity(present.len());
+        for (id, member) in &self.members {
+            if present.contains(id.as_str()) {
+                renumbered[*member] = Some(members.len());
+                members.insert(id.clone(), members.len());
+            }
+        }
+        let points = self
+            .points
+            .iter()
+            .filter_map(|(point, member)| Some((*point, renumbered[*member]?)))
+            .collect();
+        Some(Self { members, points })
+    }
+
+    /// Maps each member to its index in `instances`, or returns `None` if `instances` has an
+    /// instance the ring lacks.
+    fn positions(&self, instances: &InstanceSnapshot) -> Option<Vec<Option<usize>>> {
+        let mut positions = vec![None; self.members.len()];
+        for (index, instance) in instances.iter().enumerate() {
+            positions[*self.members.get(instance.instance_id().as_str())?] = Some(index);
+        }
+        Some(positions)
+    }
+}
+
+impl ConsistentHash {
+    /// Default ring points per instance.
+    pub const DEFAULT_REPLICAS: usize = 160;
+    /// Default multiple of the mean in-flight load an instance may carry before spilling over.
+    pub const DEFAULT_LOAD_FACTOR: f64 = 1.25;
+
+    /// Starts a balancer keyed by the named call argument.
+    ///
+    /// String arguments hash their contents; other JSON values hash their serialized form.
+    pub fn argument_builder(name: impl Into<String>) -> ConsistentHashBuilder {
+        ConsistentHashBuilder::new(KeySource::Argument(name.into()))
+    }
+
+    /// Starts a balancer keyed by the value of a request header.
+    pub fn header_builder(name: impl Into<String>) -> ConsistentHashBuilder {
+        ConsistentHashBuilder::new(KeySource::Header(name.into()))
+    }
+
+    fn key(&self, context: &Context) -> Option<u64> {
+        match &self.key {
+            HashKey::Argument(name) => match context.arguments()?.get(name)? {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(value) => Some(stable_hash(&[value.as_bytes()])),
+                value => Some(stable_hash(&[value.to_string().as_bytes()])),
+            },
+            HashKey::Header(name) => context
+                .headers()
+                .get(name)
+                .map(|value| stable_hash(&[value.as_bytes()])),
+        }
+    }
+
+    /// Returns the cached ring and the snapshot index of each of its members.
+    ///
+    /// The ring holds every instance seen since discovery last changed. Router-filtered
+    /// snapshots and retries without already attempted instances skip the points of absent
+    /// members, which picks the same owners as a ring built from the subset, so alternating
+    /// subsets share one ring. Unseen instances add only their own points; members are removed
+    /// by [`instances_discovered`](LoadBalancer::instances_discovered).
+    fn ring(&self, instances: &InstanceSnapshot) -> (Arc<HashRing>, Vec<Option<usize>>) {
+        let mut cached = self.ring.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some(positions) = cached.positions(instances) {
+            return (cached.clone(), positions);
+        }
+        let ring = Arc::new(cached.with_members(instances, self.replicas));
+        let positions = ring
+            .positions(instances)
+            .expect("a ring covers the instances it was extended with");
+        *cached = ring.clone();
+        (ring, positions)
+    }
+}
+
+impl LoadBalancer for ConsistentHash {
+    fn select(&self, context: &Context, instances: &InstanceSnapshot) -> Result<usize, Error> {
+        match instances.len() {
+            0 => return Err(no_instances()),
+            1 => return Ok(0),
+            _ => {}
+        }
+        let Some(key) = self.key(context) else {
+            return Ok(rand::rng().random_range(0..instances.len()));
+        };
+        let (ring, positions) = self.ring(instances);
+        let loads = instances
+            .iter()
+            .map(|instance| self.in_flight.get(instance))
+            .collect::<Vec<_>>();
+        let total = loads.iter().sum::<usize>();
+        // Some instance is always below this bound, because it exceeds the mean load.
+        let capacity =
+            ((total + 1) as f64 * self.load_factor / instances.len() as f64).ceil() as usize;
+        let start = ring.points.partition_point(|(point, _)| *point < key);
+        let mut owner = None;
+        for (_, member) in ring
+            .points
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(ring.points.len())
+        {
+            let Some(index) = positions[*member] else {
+                continue;
+            };
+            if loads[index] < capacity {
+                return Ok(index);
+            }
+            owner.get_or_insert(index);
+        }
+        Ok(owner.unwrap_or_default())
+    }
+
+    fn instances_discovered(&self, instances: &InstanceSnapshot) {
+        let mut cached = self.ring.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some(ring) = cached.retain(instances) {
+            *cached = Arc::new(ring);
+        }
+    }
+
+    fn attempt_started(&self, instance: &ServiceInstance) {
+        self.in_flight.start(instance);
+    }
+
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        self.in_flight.finish(completion.instance());
+    }
+}
+
+#[derive(Clone, Debug)]
+enum KeySource {
+    Argument(String),
+    Header(String),
+}
+
+/// Builder for [`ConsistentHash`].
+#[derive(Clone, Debug)]
+pub struct ConsistentHashBuilder {
+    key: KeySource,
+    replicas: usize,
+    load_factor: f64,
+}
+
+impl ConsistentHashBuilder {
+    const fn new(key: KeySource) -> Self {
+        Self {
+            key,
+            replicas: ConsistentHash::DEFAULT_REPLICAS,
+            load_factor: ConsistentHash::DEFAULT_LOAD_FACTOR,
+        }
+    }
+
+    /// Sets the ring points per instance, from 1 to 1024; more points spread keys more evenly.
+    pub const fn replicas(mut self, value: usize) -> Self {
+        self.replicas = value;
+        self
+    }
+
+    /// Sets the bounded-load factor, which must be greater than 1.
+    ///
+    /// `f64::INFINITY` disables spillover, so a key always maps to its ring owner.
+    pub const fn load_factor(mut self, value: f64) -> Self {
+        self.load_factor = value;
+        self
+    }
+
+    /// Validates and builds the balancer.
+    pub fn build(self) -> Result<ConsistentHash, ConfigValidationError> {
+        let key = match self.key {
+            KeySource::Argument(name) if name.is_empty() => {
+                return Err(out_of_range(
+                    "load_balancer.consistent_hash.argument",
+                    "must not be empty",
+                ));
+            }
+            KeySource::Argument(name) => HashKey::Argument(name),
+            KeySource::Header(name) => {
+                HashKey::Header(http::HeaderName::try_from(name).map_err(|_| {
+                    out_of_range(
+                        "load_balancer.consistent_hash.header",
+                        "must be a valid HTTP header name",
+                    )
+                })?)
+            }
+        };
+        if !(1..=1024).contains(&self.replicas) {
+            return Err(out_of_range(
+                "load_balancer.consistent_hash.replicas",
+                "must be between 1 and 1024 inclusive",
+            ));
+        }
+        if self.load_factor.is_nan() || self.load_factor <= 1.0 {
+            return Err(out_of_range(
+                "load_balancer.consistent_hash.load_factor",
+                "must be greater than 1",
+            ));
+        }
+        Ok(ConsistentHash {
+            key,
+            replicas: self.replicas,
+            load_factor: self.load_factor,
+            ring: Mutex::default(),
+            in_flight: InFlight::default(),
+        })
+    }
+}
+
+/// FNV-1a with a SplitMix64 finalizer; stable across processes and Rust releases, so every
+/// client maps a key to the same instance.
+fn stable_hash(parts: &[&[u8]]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+const fn out_of_range(field_path: &'static str, reason: &'static str) -> ConfigValidationError {
+    ConfigValidationError::new(ConfigValidationErrorKind::OutOfRange, field_path, reason)
+}
+
+/// Picks two distinct instances at random and returns the one with the lower cost.
+fn power_of_two_choices(
+    instances: &InstanceSnapshot,
+    cost: impl Fn(&ServiceInstance) -> f64,
+) -> Result<usize, Error> {
+    match instances.len() {
+        0 => Err(no_instances()),
+        1 => Ok(0),
+        len => {
+            let mut rng = rand::rng();
+            let first = rng.random_range(0..len);
+            let second = (first + rng.random_range(1..len)) % len;
+            if cost(&instances[second]) < cost(&instances[first]) {
+                Ok(second)
+            } else {
+                Ok(first)
+            }
+        }
+    }
+}
+
+pub(crate) fn no_instances() -> Error {
+    Error::framework(
+        ErrorCategory::Unavailable,
+        "no_instances",
+        "no eligible service instances",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Arguments, InterceptionStage, Side,
+        context::ContextParts,
+        runtime::{budget::ByteBudget, deadline::Deadline},
+    };
+    use fusen_contract::{
+        EndpointCapabilities, HttpBindingId, HttpOperation, InstanceId, MethodDescriptor, MethodId,
+        ServiceDescriptor, ServiceSelector, ServiceWeight,
+    };
+    use std::sync::OnceLock;
+
+    pub(super) fn context() -> Context {
+        keyed_context(Arguments::new(), http::HeaderMap::new())
+    }
+
+    fn user_context(user: &str) -> Context {
+        let mut arguments = Arguments::new();
+        arguments.insert("user".to_owned(), user.into());
+        keyed_context(arguments, http::HeaderMap::new())
+    }
+
+    pub(super) fn keyed_context(arguments: Arguments, headers: http::HeaderMap) -> Context {
+        static SERVICE: OnceLock<ServiceDescriptor> = OnceLock::new();
+        let interface = SERVICE.get_or_init(|| {
+            let operation = HttpOperation::new(
+                http::Method::GET,
+                "/call",
+                Vec::new(),
+                "application/json",
+                "application/json",
+            )
+            .unwrap();
+            ServiceDescriptor::new(
+                ServiceSelector::new("balancer-test", None, None).unwrap(),
+                vec![MethodDescriptor::new(MethodId::new(0), "call", operation).unwrap()],
+            )
+            .unwrap()
+        });
+        Context::new(ContextParts {
+            side: Side::Client,
+            stage: InterceptionStage::ClientAttempt,
+            request_id: "balance".to_owned(),
+            binding_id: HttpBindingId::default(),
+            http_version: None,
+            interface,
+            method: interface.method(MethodId::new(0)).unwrap(),
+            deadline: Deadline::after(Duration::from_secs(1)),
+            attempt: None,
+            endpoint: None,
+            headers,
+            extensions: http::Extensions::new(),
+            arguments: Some(arguments),
+            response_limit: 1024,
+            response_wire_overhead: 0,
+            response_budget: ByteBudget::new(1024),
+        })
+    }
+
+    pub(super) fn instance(port: u16, weight: f64) -> ServiceInstance {
+        ServiceInstance::new(
+            InstanceId::new(format!("i{port}")).unwrap(),
+            format!("http://127.0.0.1:{port}").parse().unwrap(),
+            EndpointCapabilities::default(),
+            ServiceWeight::new(weight).unwrap(),
+        )
+    }
+
+    fn snapshot(instances: &[ServiceInstance]) -> InstanceSnapshot {
+        InstanceSnapshot::new(instances.to_vec())
+    }
+
+    fn finish(
+        balancer: &impl LoadBalancer,
+        instance: &ServiceInstance,
+        millis: u64,
+        outcome: AttemptOutcome,
+    ) {
+        balancer.attempt_finished(&AttemptCompletion::new(
+            instance,
+            Duration::from_millis(millis),
+            outcome,
+        ));
+    }
+
+    #[test]
+    fn round_robin_cycles_through_every_instance() {
+        let balancer = RoundRobin::default();
+        let context = context();
+        let instances = snapshot(&[instance(1, 1.0), instance(2, 5.0), instance(3, 1.0)]);
+        let picks = (0..6)
+            .map(|_| balancer.select(&context, &instances).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(picks, [0, 1, 2, 0, 1, 2]);
+        assert!(balancer.select(&context, &snapshot(&[])).is_err());
+    }
+
+    #[test]
+    fn least_request_avoids_the_busier_of_two_instances_until_it_drains() {
+        let balancer = LeastRequest::default();
+        let context = context();
+        let (busy, idle) = (instance(1, 1.0), instance(2, 1.0));
+        let instances = snapshot(&[busy.clone(), idle.clone()]);
+        balancer.attempt_started(&busy);
+        for _ in 0..32 {
+            assert_eq!(balancer.select(&context, &instances).unwrap(), 1);
+        }
+        finish(&balancer, &busy, 1, AttemptOutcome::Cancelled);
+        assert!(balancer.in_flight.lock().is_empty());
+
+        // Weight divides the in-flight cost, so a heavier instance absorbs more attempts.
+        let heavy = instance(3, 3.0);
+        let instances = snapshot(&[heavy.clone(), idle.clone()]);
+        balancer.attempt_started(&heavy);
+        for _ in 0..32 {
+            assert_eq!(balancer.select(&context, &instances).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn peak_ewma_jumps_to_slow_samples_and_ignores_cancellations() {
+        let balancer = PeakEwma::new(Duration::from_millis(30), Duration::from_secs(10)).unwrap();
+        let context = context();
+        let (slow, fast) = (instance(1, 1.0), instance(2, 1.0));
+        let instances = snapshot(&[slow.clone(), fast.clone()]);
+        for (endpoint, millis) in [(&slow, 200), (&fast, 20)] {
+            balancer.attempt_started(endpoint);
+            finish(&balancer, endpoint, millis, AttemptOutcome::Success);
+        }
+        for _ in 0..32 {
+            assert_eq!(balancer.select(&context, &instances).unwrap(), 1);
+        }
+
+        // A fast failure or a cancelled attempt must not make the slow endpoint look healthy.
+        balancer.attempt_started(&slow);
+        finish(
+            &balancer,
+            &slow,
+            1,
+            AttemptOutcome::Failure(FailureClass::Transport),
+        );
+        balancer.attempt_started(&slow);
+        finish(&balancer, &slow, 1, AttemptOutcome::Cancelled);
+        let rtt = balancer.lock()[slow.endpoint().as_str()].rtt_nanos;
+        assert_eq!(rtt, Duration::from_millis(200).as_nanos() as f64);
+        assert_eq!(
+            PeakEwma::new(Duration::ZERO, Duration::ZERO)
+                .unwrap_err()
+                .field_path(),
+            "load_balancer.peak_ewma.decay"
+        );
+    }
+
+    #[test]
+    fn consistent_hash_is_sticky_and_only_remaps_keys_of_a_departed_instance() {
+        let balancer = ConsistentHash::argument_builder("user")
+            .load_factor(f64::INFINITY)
+            .build()
+            .unwrap();
+        let all = (1..=5).map(|port| instance(port, 1.0)).collect::<Vec<_>>();
+        let owner = |instances: &[ServiceInstance], user: &str| {
+            let index = balancer
+                .select(&user_context(user), &snapshot(instances))
+                .unwrap();
+            instances[index].instance_id().as_str().to_owned()
+        };
+        let users = (0..200)
+            .map(|user| format!("user-{user}"))
+            .collect::<Vec<_>>();
+        let before = users
+            .iter()
+            .map(|user| owner(&all, user))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            before,
+            users
+                .iter()
+                .map(|user| owner(&all, user))
+                .collect::<Vec<_>>()
+        );
+        assert!((1..=5).all(|port| before.contains(&format!("i{port}"))));
+
+        // Reordering the snapshot keeps every owner; removing one instance moves only its keys.
+        let mut reordered = all.clone();
+        reordered.reverse();
+        assert!(
+            users
+                .iter()
+                .zip(&before)
+                .all(|(user, id)| owner(&reordered, user) == *id)
+        );
+        let remaining = &all[1..];
+        for (user, id) in users.iter().zip(&before) {
+            if id != "i1" {
+                assert_eq!(owner(remaining, user), *id);
+            }
+        }
+
+        let header = ConsistentHash::header_builder("x-user").build().unwrap();
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-user", "alice".parse().unwrap());
+        let keyed = keyed_context(Arguments::new(), headers);
+        let first = header.select(&keyed, &snapshot(&all)).unwrap();
+        assert!((0..16).all(|_| header.select(&keyed, &snapshot(&all)).unwrap() == first));
+        assert!(header.select(&context(), &snapshot(&all)).unwrap() < all.len());
+    }
+
+    #[test]
+    fn consistent_hash_moves_keys_only_to_an_added_instance_and_reuses_its_ring_for_subsets() {
+        let balancer = ConsistentHash::argument_builder("user")
+            .load_factor(f64::INFINITY)
+            .build()
+            .unwrap();
+        let all = (1..=5).map(|port| instance(port, 1.0)).collect::<Vec<_>>();
+        let owner = |instances: &[ServiceInstance], user: &str| {
+            let index = balancer
+                .select(&user_context(user), &snapshot(instances))
+                .unwrap();
+            instances[index].instance_id().as_str().to_owned()
+        };
+        let users = (0..200)
+            .map(|user| format!("user-{user}"))
+            .collect::<Vec<_>>();
+        let before = users
+            .iter()
+            .map(|user| owner(&all[..4], user))
+            .collect::<Vec<_>>();
+        let after = users
+            .iter()
+            .map(|user| owner(&all, user))
+            .collect::<Vec<_>>();
+        assert!(after.iter().any(|id| id == "i5"));
+        assert!(
+            before
+                .iter()
+                .zip(&after)
+                .all(|(old, new)| old == new || new == "i5")
+        );
+
+        // Filtered snapshots, such as a retry that excludes attempted instances, keep the ring.
+        let ring = balancer.ring(&snapshot(&all)).0;
+        owner(&all[1..3], "user-0");
+        assert!(Arc::ptr_eq(&ring, &balancer.ring(&snapshot(&all)).0));
+    }
+
+    #[test]
+    fn consistent_hash_keeps_one_ring_for_alternating_subsets_until_discovery_changes() {
+        let balancer = ConsistentHash::argument_builder("user")
+            .load_factor(f64::INFINITY)
+            .build()
+            .unwrap();
+        let all = (1..=4).map(|port| instance(port, 1.0)).collect::<Vec<_>>();
+        let (canary, stable) = (snapshot(&all[..2]), snapshot(&all[2..]));
+        let users = (0..50)
+            .map(|user| format!("user-{user}"))
+            .collect::<Vec<_>>();
+        let owners = |balancer: &ConsistentHash, instances: &InstanceSnapshot| {
+            users
+                .iter()
+                .map(|user| balancer.select(&user_context(user), instances).unwrap())
+                .collect::<Vec<_>>()
+        };
+        owners(&balancer, &canary);
+        owners(&balancer, &stable);
+        let ring = balancer.ring(&canary).0;
+        assert_eq!(ring.members.len(), 4);
+        for _ in 0..4 {
+            owners(&balancer, &canary);
+            owners(&balancer, &stable);
+        }
+        assert!(Arc::ptr_eq(&ring, &balancer.ring(&stable).0));
+
+        // Each subset still picks the owners of a ring built from that subset alone.
+        let fresh = ConsistentHash::argument_builder("user")
+            .load_factor(f64::INFINITY)
+            .build()
+            .unwrap();
+        assert_eq!(owners(&balancer, &stable), owners(&fresh, &stable));
+
+        // Discovery dropping an instance removes only its points; an unchanged set keeps the ring.
+        balancer.instances_discovered(&snapshot(&all[1..]));
+        let pruned = balancer.ring(&stable).0;
+        assert_eq!(pruned.members.len(), 3);
+        assert_eq!(pruned.points.len(), 3 * ConsistentHash::DEFAULT_REPLICAS);
+        balancer.instances_discovered(&snapshot(&all[1..]));
+        assert!(Arc::ptr_eq(&pruned, &balancer.ring(&stable).0));
+        assert_eq!(owners(&balancer, &stable), owners(&fresh, &stable));
+    }
+
+    #[test]
+    fn consistent_hash_spills_over_from_an_overloaded_owner() {
+        let balancer = ConsistentHash::argument_builder("user")
+            .load_factor(1.5)
+            .build()
+            .unwrap();
+        let all = (1..=3).map(|port| instance(port, 1.0)).collect::<Vec<_>>();
+        let instances = snapshot(&all);
+        let context = user_context("hot-key");
+        let owner = balancer.select(&context, &instances).unwrap();
+        // With nothing in flight the owner is below the bound ceil(1 * 1.5 / 3) = 1.
+        assert_eq!(balancer.select(&context, &instances).unwrap(), owner);
+        balancer.attempt_started(&all[owner]);
+        let spilled = balancer.select(&context, &instances).unwrap();
+        // The key spills to the first other instance clockwise from its ring position.
+        let (ring, positions) = balancer.ring(&instances);
+        let key = balancer.key(&context).unwrap();
+        let start = ring.points.partition_point(|(point, _)| *point < key);
+        let next = ring
+            .points
+            .iter()
+            .cycle()
+            .skip(start)
+            .filter_map(|(_, member)| positions[*member])
+            .find(|index| *index != owner)
+            .unwrap();
+        assert_eq!(spilled, next);
+        balancer.attempt_started(&all[spilled]);
+        finish(&balancer, &all[owner], 1, AttemptOutcome::Success);
+        assert_eq!(balancer.select(&context, &instances).unwrap(), owner);
+
+        for (builder, path) in [
+            (
+                ConsistentHash::argument_builder(""),
+                "load_balancer.consistent_hash.argument",
+            ),
+            (
+                ConsistentHash::header_builder("bad header"),
+                "load_balancer.consistent_hash.header",
+            ),
+            (
+                ConsistentHash::argument_builder("user").replicas(0),
+                "load_balancer.consistent_hash.replicas",
+            ),
+            (
+                ConsistentHash::argument_builder("user").load_factor(1.0),
+                "load_balancer.consistent_hash.load_factor",
+            ),
+        ] {
+            assert_eq!(builder.build().unwrap_err().field_path(), path);
+        }
+    }
+
+    fn located(port: u16, zone: &str, region: &str) -> ServiceInstance {
+        instance(port, 1.0)
+            .with_metadata(
+                [("zone", zone), ("region", region)]
+                    .into_iter()
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+            )
+            .unwrap()
+    }
+
+    fn route_ports(
+        router: &LocalityRouter,
+        instances: &[ServiceInstance],
+        unhealthy: &[u16],
+    ) -> Vec<u16> {
+        let context = context();
+        let health = |instance: &ServiceInstance| {
+            !unhealthy.contains(&instance.endpoint().as_url().port().unwrap())
+        };
+        router
+            .route(RouteRequest::new(&context, snapshot(instances)).with_health(&health))
+            .unwrap()
+            .iter()
+            .map(|instance| instance.endpoint().as_url().port().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn locality_router_prefers_the_local_zone_and_fails_over_by_healthy_fraction() {
+        let router = LocalityRouter::builder()
+            .zone("a1")
+            .region("a")
+            .min_healthy_ratio(0.5)
+            .build()
+            .unwrap();
+        let instances = [
+            located(1, "a1", "a"),
+            located(2, "a1", "a"),
+            located(3, "a2", "a"),
+            located(4, "b1", "b"),
+            instance(5, 1.0),
+        ];
+
+        assert_eq!(route_ports(&router, &instances, &[]), [1, 2]);
+        // Half of the zone is still healthy, which meets the ratio.
+        assert_eq!(route_ports(&router, &instances, &[1]), [1, 2]);
+        // The zone drops to 0/2 healthy, so the region takes over with 1/3 < 0.5 failing too.
+        assert_eq!(route_ports(&router, &instances, &[1, 2]), [1, 2, 3, 4, 5]);
+        let region_only = LocalityRouter::builder().region("a").build().unwrap();
+        assert_eq!(route_ports(&region_only, &instances, &[]), [1, 2, 3]);
+        // 2/3 healthy is below the default ratio of 0.7.
+        assert_eq!(route_ports(&region_only, &instances, &[1]), [1, 2, 3, 4, 5]);
+        assert_eq!(route_ports(&region_only, &instances[3..], &[]), [4, 5]);
+
+        // Without runtime health every instance counts as healthy.
+        let context = context();
+        let routed = router
+            .route(RouteRequest::new(&context, snapshot(&instances)))
+            .unwrap();
+        assert_eq!(routed.len(), 2);
+        assert_eq!(instance_locality(&instances[3]), Some("b1"));
+        assert_eq!(instance_locality(&instances[4]), None);
+
+        for (builder, path) in [
+            (LocalityRouter::builder(), "instance_router.locality"),
+            (
+                LocalityRouter::builder().zone(""),
+                "instance_router.locality.zone",
+            ),
+            (
+                LocalityRouter::builder().region(""),
+                "instance_router.locality.region",
+            ),
+            (
+                LocalityRouter::builder().zone("a1").min_healthy_ratio(1.5),
+                "instance_router.locality.min_healthy_ratio",
+            ),
+            (
+                LocalityRouter::builder()
+                    .zone("a1")
+                    .min_healthy_ratio(f64::NAN),
+                "instance_router.locality.min_healthy_ratio",
+            ),
+        ] {
+            assert_eq!(builder.build().unwrap_err().field_path(), path);
+        }
+    }
+}
@@ -0,0 +1,635 @@
+//! Declarative header, method, and percentage routing onto metadata-selected instance subsets.
+
+use super::{
+    Error, InstanceRouter, InstanceSnapshot, RouteRequest, ServiceInstance, out_of_range,
+    stable_hash,
+};
+use crate::{
+    context::Context,
+    error::{ConfigValidationError, ConfigValidationErrorKind},
+};
+#[cfg(feature = "hot-traffic-rules")]
+use fusen_config::HotConfig;
+use fusen_contract::Metadata;
+use http::{HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc};
+
+/// Ordered routing rules evaluated by [`TrafficRouter`]; the first matching rule applies.
+///
+/// As a hot configuration document it has a `rules` array whose entries take a unique `name`,
+/// optional `service`, `method`, `headers` (a table of exact values), `hash_header`, and
+/// `strict`, and a `routes` array of `{ weight, metadata }` entries. A lone route may omit its
+/// `weight`. The whole document is validated like the builders, so an invalid revision never
+/// replaces the rules in use.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(try_from = "TrafficRulesDocument")]
+pub struct TrafficRules {
+    rules: Vec<TrafficRule>,
+}
+
+impl TrafficRules {
+    /// Starts a builder with no rules, which routes every attempt to the full snapshot.
+    pub fn builder() -> TrafficRulesBuilder {
+        TrafficRulesBuilder(Self::default())
+    }
+
+    /// Returns the rules in evaluation order.
+    pub fn rules(&self) -> &[TrafficRule] {
+        &self.rules
+    }
+
+    fn matching(&self, context: &Context) -> Option<&TrafficRule> {
+        self.rules.iter().find(|rule| rule.matches(context))
+    }
+}
+
+/// Builder for [`TrafficRules`].
+#[derive(Clone, Debug)]
+pub struct TrafficRulesBuilder(TrafficRules);
+
+impl TrafficRulesBuilder {
+    /// Appends one rule after those already added.
+    pub fn rule(mut self, value: TrafficRule) -> Self {
+        self.0.rules.push(value);
+        self
+    }
+
+    /// Validates and builds the rule set.
+    pub fn build(self) -> Result<TrafficRules, ConfigValidationError> {
+        let mut names = HashSet::new();
+        if !self
+            .0
+            .rules
+            .iter()
+            .all(|rule| names.insert(rule.name.as_str()))
+        {
+            return Err(ConfigValidationError::new(
+                ConfigValidationErrorKind::Inconsistent,
+                "instance_router.traffic.rules.name",
+                "must be unique",
+            ));
+        }
+        Ok(self.0)
+    }
+}
+
+/// One routing rule: which attempts it matches and how they are split across instance subsets.
+///
+/// Without filters the rule matches every attempt. `service` and `method` restrict it to one
+/// service ID or invocation method name, and every configured header must be present with
+/// exactly the configured value. A matching attempt picks one route by weight, keyed by the
+/// `hash_header` value when present and by the request ID otherwise, so retries and hedges of
+/// one call, and every call carrying the same header value, land in the same route.
+#[derive(Clone, Debug)]
+pub struct TrafficRule {
+    name: String,
+    service: Option<String>,
+    method: Option<String>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    hash_header: Option<HeaderName>,
+    strict: bool,
+    routes: Vec<TrafficRoute>,
+}
+
+impl TrafficRule {
+    /// Starts a rule with the given name, unique within its rule set.
+    pub fn builder(name: impl Into<String>) -> TrafficRuleBuilder {
+        TrafficRuleBuilder {
+            name: name.into(),
+            service: None,
+            method: None,
+            headers: Vec::new(),
+            hash_header: None,
+            strict: false,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Returns the rule name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the service ID this rule is restricted to, if any.
+    pub fn service(&self) -> Option<&str> {
+        self.service.as_deref()
+    }
+
+    /// Returns the invocation method name this rule is restricted to, if any.
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    /// Returns the request headers that must all match exactly.
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.headers
+    }
+
+    /// Returns the request header whose value keys the percentage split, if any.
+    pub const fn hash_header(&self) -> Option<&HeaderName> {
+        self.hash_header.as_ref()
+    }
+
+    /// Returns whether an empty selected subset is routed as-is instead of falling back.
+    pub const fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Returns the weighted routes, whose weights add up to 100.
+    pub fn routes(&self) -> &[TrafficRoute] {
+        &self.routes
+    }
+
+    fn matches(&self, context: &Context) -> bool {
+        self.service
+            .as_deref()
+            .is_none_or(|service| service == context.interface().selector().service_id())
+            && self
+                .method
+                .as_deref()
+                .is_none_or(|method| method == context.method().invocation_name())
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| context.headers().get(name) == Some(value))
+    }
+
+    fn pick(&self, context: &Context) -> &TrafficRoute {
+        let key = self
+            .hash_header
+            .as_ref()
+            .and_then(|name| context.headers().get(name))
+            .map_or(context.request_id().as_bytes(), HeaderValue::as_bytes);
+        // The rule name salts the hash so that separate splits are independent of each other.
+        let mut roll = (stable_hash(&[self.name.as_bytes(), b"\0", key]) % 100) as u32;
+        for route in &self.routes {
+            if roll < route.weight {
+                return route;
+            }
+            roll -= route.weight;
+        }
+        unreachable!("validated route weights add up to 100")
+    }
+}
+
+/// Builder for [`TrafficRule`].
+#[derive(Clone, Debug)]
+pub struct TrafficRuleBuilder {
+    name: String,
+    service: Option<String>,
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    hash_header: Option<String>,
+    strict: bool,
+    routes: Vec<TrafficRoute>,
+}
+
+impl TrafficRuleBuilder {
+    /// Restricts the rule to one service ID.
+    pub fn service(mut self, value: impl Into<String>) -> Self {
+        self.service = Some(value.into());
+        self
+    }
+
+    /// Restricts the rule to one invocation method name.
+    pub fn method(mut self, value: impl Into<String>) -> Self {
+        self.method = Some(value.into());
+        self
+    }
+
+    /// Requires a request header with exactly this value; repeated calls must all match.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Keys the percentage split by this request header, such as a user ID, instead of the
+    /// request ID, so each caller consistently lands in the same route.
+    pub fn hash_header(mut self, value: impl Into<String>) -> Self {
+        self.hash_header = Some(value.into());
+        self
+    }
+
+    /// Routes an empty selected subset as-is, failing the attempt with `no_instances`, instead
+    /// of falling back to the full snapshot.
+    pub const fn strict(mut self, value: bool) -> Self {
+        self.strict = value;
+        self
+    }
+
+    /// Appends a route sending `weight` percent of matching attempts to the instances whose
+    /// metadata contains every `selector` entry; an empty selector selects every instance.
+    pub fn route(mut self, weight: u32, selector: Metadata) -> Self {
+        self.routes.push(TrafficRoute {
+            weight,
+            metadata: selector,
+        });
+        self
+    }
+
+    /// Validates and builds the rule.
+    pub fn build(self) -> Result<TrafficRule, ConfigValidationError> {
+        if self.name.is_empty() {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.name",
+                "must not be empty",
+            ));
+        }
+        if self.service.as_deref().is_some_and(str::is_empty) {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.service",
+                "must not be empty",
+            ));
+        }
+        if self.method.as_deref().is_some_and(str::is_empty) {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.method",
+                "must not be empty",
+            ));
+        }
+        let headers = self
+            .headers
+            .into_iter()
+            .map(|(name, value)| {
+                let name = HeaderName::try_from(name).map_err(|_| {
+                    out_of_range(
+                        "instance_router.traffic.rules.headers",
+                        "must be valid HTTP header names",
+                    )
+                })?;
+                let value = HeaderValue::try_from(value).map_err(|_| {
+                    out_of_range(
+                        "instance_router.traffic.rules.headers",
+                        "must have valid HTTP header values",
+                    )
+                })?;
+                Ok((name, value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let hash_header = self
+            .hash_header
+            .map(|name| {
+                HeaderName::try_from(name).map_err(|_| {
+                    out_of_range(
+                        "instance_router.traffic.rules.hash_header",
+                        "must be a valid HTTP header name",
+                    )
+                })
+            })
+            .transpose()?;
+        if self.routes.is_empty() {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.routes",
+                "must not be empty",
+            ));
+        }
+        if self.routes.iter().any(|route| route.weight == 0) {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.routes.weight",
+                "must be greater than zero",
+            ));
+        }
+        if self
+            .routes
+            .iter()
+            .try_fold(0_u32, |total, route| total.checked_add(route.weight))
+            != Some(100)
+        {
+            return Err(ConfigValidationError::new(
+                ConfigValidationErrorKind::Inconsistent,
+                "instance_router.traffic.rules.routes.weight",
+                "must add up to 100",
+            ));
+        }
+        if self
+            .routes
+            .iter()
+            .any(|route| route.metadata.keys().any(String::is_empty))
+        {
+            return Err(out_of_range(
+                "instance_router.traffic.rules.routes.metadata",
+                "must not contain empty keys",
+            ));
+        }
+        Ok(TrafficRule {
+            name: self.name,
+            service: self.service,
+            method: self.method,
+            headers,
+            hash_header,
+            strict: self.strict,
+            routes: self.routes,
+        })
+    }
+}
+
+/// A weighted share of a rule's traffic and the metadata selecting its instances.
+#[derive(Clone, Debug)]
+pub struct TrafficRoute {
+    weight: u32,
+    metadata: Metadata,
+}
+
+impl TrafficRoute {
+    /// Returns the percentage of matching attempts sent to this route.
+    pub const fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// Returns the metadata entries a selected instance must carry.
+    pub const fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn selects(&self, instance: &ServiceInstance) -> bool {
+        self.metadata
+            .iter()
+            .all(|(key, value)| instance.metadata().get(key) == Some(value))
+    }
+}
+
+/// Built-in router that applies [`TrafficRules`], such as canary splits by instance `version`.
+///
+/// An attempt matched by no rule keeps the full snapshot. When the route picked for a matching
+/// attempt selects no instance, the full snapshot is used unless the rule is strict. With
+/// [`hot`](Self::hot) the rules follow a last-good hot configuration, and each attempt reads the
+/// current revision.
+#[derive(Clone, Debug)]
+pub struct TrafficRouter {
+    source: TrafficRulesSource,
+}
+
+#[derive(Clone, Debug)]
+enum TrafficRulesSource {
+    Static(Arc<TrafficRules>),
+    #[cfg(feature = "hot-traffic-rules")]
+    Hot(HotConfig<TrafficRules>),
+}
+
+impl TrafficRouter {
+    /// Creates a router with a fixed rule set.
+    pub fn new(rules: TrafficRules) -> Self {
+        Self {
+            source: TrafficRulesSource::Static(Arc::new(rules)),
+        }
+    }
+
+    /// Creates a router whose rules follow a hot configuration.
+    ///
+    /// A revision that fails validation is rejected as a whole and the previous rules stay in
+    /// use; [`HotConfig::last_error`] reports the rejection.
+    #[cfg(feature = "hot-traffic-rules")]
+    pub fn hot(rules: HotConfig<TrafficRules>) -> Self {
+        Self {
+            source: TrafficRulesSource::Hot(rules),
+        }
+    }
+
+    /// Returns the rules currently in use.
+    pub fn rules(&self) -> Arc<TrafficRules> {
+        match &self.source {
+            TrafficRulesSource::Static(rules) => rules.clone(),
+            #[cfg(feature = "hot-traffic-rules")]
+            TrafficRulesSource::Hot(rules) => rules.current(),
+        }
+    }
+}
+
+impl InstanceRouter for TrafficRouter {
+    fn route(&self, request: RouteRequest<'_>) -> Result<InstanceSnapshot, Error> {
+        let rules = self.rules();
+        let Some(rule) = rules.matching(request.context()) else {
+            return Ok(request.into_instances());
+        };
+        let route = rule.pick(request.context());
+        if route.metadata.is_empty() {
+            return Ok(request.into_instances());
+        }
+        let selected = request
+            .instances()
+            .iter()
+            .filter(|instance| route.selects(instance))
+            .cloned()
+            .collect::<Vec<_>>();
+        if selected.is_empty() && !rule.strict {
+            return Ok(request.into_instances());
+        }
+        Ok(InstanceSnapshot::new(selected))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrafficRulesDocument {
+    #[serde(default)]
+    rules: Vec<TrafficRuleDocument>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrafficRuleDocument {
+    name: String,
+    service: Option<String>,
+    method: Option<String>,
+    #[serde(default)]
+    headers: Metadata,
+    hash_header: Option<String>,
+    #[serde(default)]
+    strict: bool,
+    #[serde(default)]
+    routes: Vec<TrafficRouteDocument>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrafficRouteDocument {
+    weight: Option<u32>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+impl TryFrom<TrafficRulesDocument> for TrafficRules {
+    type Error = ConfigValidationError;
+
+    fn try_from(document: TrafficRulesDocument) -> Result<Self, Self::Error> {
+        let mut builder = Self::builder();
+        for rule in document.rules {
+            let mut rule_builder = TrafficRule::builder(rule.name).strict(rule.strict);
+            if let Some(service) = rule.service {
+                rule_builder = rule_builder.service(service);
+            }
+            if let Some(method) = rule.method {
+                rule_builder = rule_builder.method(method);
+            }
+            for (name, value) in rule.headers {
+                rule_builder = rule_builder.header(name, value);
+            }
+            if let Some(hash_header) = rule.hash_header {
+                rule_builder = rule_builder.hash_header(hash_header);
+            }
+            let lone = rule.routes.len() == 1;
+            for route in rule.routes {
+                let weight = match route.weight {
+                    Some(weight) => weight,
+                    None if lone => 100,
+                    None => {
+                        return Err(out_of_range(
+                            "instance_router.traffic.rules.routes.weight",
+                            "is required when a rule has several routes",
+                        ));
+                    }
+                };
+                rule_builder = rule_builder.route(weight, route.metadata);
+            }
+            builder = builder.rule(rule_builder.build()?);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::tests::{instance, keyed_context};
+
+    fn versioned(port: u16, version: &str) -> ServiceInstance {
+        instance(port, 1.0)
+            .with_metadata(Metadata::from([("version".to_owned(), version.to_owned())]))
+            .unwrap()
+    }
+
+    fn selector(version: &str) -> Metadata {
+        Metadata::from([("version".to_owned(), version.to_owned())])
+    }
+
+    fn route_ports(router: &TrafficRouter, headers: &[(&str, &str)]) -> Vec<u16> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                    HeaderValue::from_str(value).unwrap(),
+                )
+            })
+            .collect();
+        let context = keyed_context(crate::Arguments::new(), headers);
+        let instances = [versioned(1, "stable"), versioned(2, "canary")];
+        router
+            .route(RouteRequest::new(
+                &context,
+                InstanceSnapshot::new(instances.to_vec()),
+            ))
+            .unwrap()
+            .iter()
+            .map(|instance| instance.endpoint().as_url().port().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn traffic_rules_match_headers_then_split_by_percentage() {
+        let rules: TrafficRules = fusen_config::parse_toml(
+            r#"
+            [[rules]]
+            name = "testers"
+            method = "call"
+            headers = { x-user-group = "beta" }
+            routes = [{ metadata = { version = "canary" } }]
+
+            [[rules]]
+            name = "canary-split"
+            hash_header = "x-user-id"
+            routes = [
+                { weight = 20, metadata = { version = "canary" } },
+                { weight = 80, metadata = { version = "stable" } },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.rules()[0].headers()[0].0.as_str(), "x-user-group");
+        assert_eq!(rules.rules()[0].routes()[0].weight(), 100);
+        let router = TrafficRouter::new(rules);
+
+        assert_eq!(
+            route_ports(&router, &[("x-user-group", "beta"), ("x-user-id", "u1")]),
+            [2]
+        );
+        let canary = (0..1000)
+            .filter(|user| route_ports(&router, &[("x-user-id", &format!("user-{user}"))]) == [2])
+            .count();
+        assert!((150..250).contains(&canary), "{canary}");
+        // The same user always lands on the same side of the split.
+        let sticky = route_ports(&router, &[("x-user-id", "user-7")]);
+        assert!((0..10).all(|_| route_ports(&router, &[("x-user-id", "user-7")]) == sticky));
+    }
+
+    #[test]
+    fn empty_subsets_fall_back_unless_the_rule_is_strict() {
+        let rules = |strict| {
+            TrafficRules::builder()
+                .rule(
+                    TrafficRule::builder("shadow")
+                        .strict(strict)
+                        .route(100, selector("shadow"))
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(route_ports(&TrafficRouter::new(rules(false)), &[]), [1, 2]);
+        assert!(route_ports(&TrafficRouter::new(rules(true)), &[]).is_empty());
+        assert_eq!(
+            route_ports(&TrafficRouter::new(TrafficRules::default()), &[]),
+            [1, 2]
+        );
+    }
+
+    #[test]
+    fn traffic_rule_documents_are_validated_as_a_whole() {
+        for (document, path) in [
+            (r#"{"rules":[{"name":"","routes":[{}]}]}"#, "rules.name"),
+            (
+                r#"{"rules":[{"name":"a","routes":[{}]},{"name":"a","routes":[{}]}]}"#,
+                "rules.name",
+            ),
+            (
+                r#"{"rules":[{"name":"a","method":"","routes":[{}]}]}"#,
+                "rules.method",
+            ),
+            (
+                r#"{"rules":[{"name":"a","headers":{"a b":"1"},"routes":[{}]}]}"#,
+                "rules.headers",
+            ),
+            (
+                r#"{"rules":[{"name":"a","hash_header":"a b","routes":[{}]}]}"#,
+                "rules.hash_header",
+            ),
+            (r#"{"rules":[{"name":"a"}]}"#, "rules.routes"),
+            (
+                r#"{"rules":[{"name":"a","routes":[{"weight":0},{"weight":100}]}]}"#,
+                "rules.routes.weight",
+            ),
+            (
+                r#"{"rules":[{"name":"a","routes":[{"weight":50},{"weight":40}]}]}"#,
+                "rules.routes.weight",
+            ),
+            (
+                r#"{"rules":[{"name":"a","routes":[{},{"weight":50}]}]}"#,
+                "rules.routes.weight",
+            ),
+            (
+                r#"{"rules":[{"name":"a","routes":[{"metadata":{"":"x"}}]}]}"#,
+                "rules.routes.metadata",
+            ),
+        ] {
+            let error = serde_json::from_str::<TrafficRules>(document).unwrap_err();
+            let expected = format!("at instance_router.traffic.{path} ");
+            assert!(error.to_string().contains(&expected), "{document}: {error}");
+        }
+        assert!(
+            serde_json::from_str::<TrafficRules>(r#"{"rules":[{"name":"a","weight":1}]}"#).is_err()
+        );
+    }
+}
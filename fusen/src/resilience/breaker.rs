@@ -1,6 +1,7 @@
 //! Rolling-window endpoint and service circuit breakers.
 
 use std::{
+    borrow::Borrow,
     collections::HashMap,
     fmt,
     hash::Hash,
@@ -302,6 +303,13 @@ impl CircuitBreaker {
         }
     }
 
+    /// Returns whether the breaker is open and would reject a new attempt right now.
+    pub(crate) fn is_open(&self) -> bool {
+        let now = Instant::now();
+        let inner = self.inner.lock().unwrap_or_else(|error| error.into_inner());
+        matches!(inner.state, MachineState::Open { until, .. } if now < until)
+    }
+
     #[cfg(test)]
     pub(crate) fn snapshot(&self) -> BreakerSnapshot {
         let now = Instant::now();
@@ -640,6 +648,19 @@ where
         self.get_or_insert_with_observer(key, Some(observer))
     }
 
+    /// Returns whether a cached breaker is open, without inserting or touching its entry.
+    pub(crate) fn is_open<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.entries
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(key)
+            .is_some_and(|entry| entry.breaker.is_open())
+    }
+
     /// Creates an observed breaker without retaining it in the endpoint cache.
     ///
     /// Discovery uses this for an invocation that still holds an older directory snapshot after
@@ -0,0 +1,81 @@
+//! Rolling latency windows that derive hedge delays.
+
+use crate::HedgingConfig;
+use std::{sync::Mutex, time::Duration};
+
+/// Number of recent successful attempt latencies retained per method.
+const WINDOW_SAMPLES: usize = 128;
+
+/// Bounded ring of recent successful attempt latencies for one method.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyWindow {
+    state: Mutex<WindowState>,
+}
+
+#[derive(Debug, Default)]
+struct WindowState {
+    samples: Vec<Duration>,
+    next: usize,
+}
+
+impl LatencyWindow {
+    pub(crate) fn record(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|error| error.into_inner());
+        if state.samples.len() < WINDOW_SAMPLES {
+            state.samples.push(latency);
+        } else {
+            let next = state.next;
+            state.samples[next] = latency;
+        }
+        state.next = (state.next + 1) % WINDOW_SAMPLES;
+    }
+
+    /// Returns the configured percentile clamped to the delay window, or the longest delay before
+    /// any latency has been observed.
+    pub(crate) fn delay(&self, config: &HedgingConfig) -> Duration {
+        let mut samples = self
+            .state
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .samples
+            .clone();
+        if samples.is_empty() {
+            return config.max_delay();
+        }
+        samples.sort_unstable();
+        let rank = (samples.len() * usize::from(config.percentile())).div_ceil(100);
+        samples[rank.saturating_sub(1)].clamp(config.min_delay(), config.max_delay())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_tracks_the_percentile_of_the_most_recent_samples() {
+        let config = HedgingConfig::builder()
+            .percentile(90)
+            .min_delay(Duration::from_millis(2))
+            .max_delay(Duration::from_millis(500))
+            .build()
+            .unwrap();
+        let window = LatencyWindow::default();
+        assert_eq!(window.delay(&config), Duration::from_millis(500));
+
+        for millis in 1..=10 {
+            window.record(Duration::from_millis(millis * 10));
+        }
+        assert_eq!(window.delay(&config), Duration::from_millis(90));
+
+        for _ in 0..WINDOW_SAMPLES {
+            window.record(Duration::from_millis(1));
+        }
+        assert_eq!(window.delay(&config), Duration::from_millis(2));
+
+        for _ in 0..WINDOW_SAMPLES {
+            window.record(Duration::from_secs(5));
+        }
+        assert_eq!(window.delay(&config), Duration::from_millis(500));
+    }
+}
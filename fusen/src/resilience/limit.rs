@@ -0,0 +1,299 @@
+//! Latency-gradient concurrency limits for physical attempts.
+
+use crate::AdaptiveConcurrencyConfig;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Weight of one sample in the long-term latency average.
+const LONG_WINDOW_SAMPLES: f64 = 100.0;
+/// Fraction of each computed limit applied per sample.
+const SMOOTHING: f64 = 0.2;
+/// Multiplicative decrease applied on overload or timeout.
+const BACKOFF_RATIO: f64 = 0.9;
+/// Lowest gradient applied by one latency sample.
+const MIN_GRADIENT: f64 = 0.5;
+
+/// One adaptive limit shared by every attempt to a service binding or endpoint.
+#[derive(Debug)]
+pub(crate) struct AdaptiveLimiter {
+    min_limit: f64,
+    max_limit: f64,
+    latency_tolerance: f64,
+    state: Mutex<LimitState>,
+}
+
+#[derive(Debug)]
+struct LimitState {
+    limit: f64,
+    in_flight: usize,
+    long_latency: Option<f64>,
+}
+
+/// How a finished attempt informs its limits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LimitSample {
+    Latency(Duration),
+    Overloaded,
+    Ignore,
+}
+
+impl AdaptiveLimiter {
+    pub(crate) fn new(config: &AdaptiveConcurrencyConfig) -> Self {
+        Self {
+            min_limit: f64::from(config.min_limit()),
+            max_limit: f64::from(config.max_limit()),
+            latency_tolerance: config.latency_tolerance(),
+            state: Mutex::new(LimitState {
+                limit: f64::from(config.initial_limit()),
+                in_flight: 0,
+                long_latency: None,
+            }),
+        }
+    }
+
+    /// Returns the current whole-attempt limit.
+    #[cfg(test)]
+    pub(crate) fn limit(&self) -> usize {
+        self.lock().limit as usize
+    }
+
+    /// Admits one attempt when the in-flight count is below the current limit.
+    pub(crate) fn try_acquire(self: &Arc<Self>) -> Option<LimitPermit> {
+        let mut state = self.lock();
+        if state.in_flight >= state.limit as usize {
+            return None;
+        }
+        state.in_flight += 1;
+        Some(LimitPermit {
+            limiter: Some(self.clone()),
+        })
+    }
+
+    fn release(&self, sample: LimitSample) {
+        let mut state = self.lock();
+        let in_flight = state.in_flight;
+        state.in_flight = in_flight.saturating_sub(1);
+        match sample {
+            LimitSample::Ignore => {}
+            LimitSample::Overloaded => {
+                state.limit = (state.limit * BACKOFF_RATIO).clamp(self.min_limit, self.max_limit);
+            }
+            LimitSample::Latency(latency) => {
+                let sample = latency.as_secs_f64().max(f64::EPSILON);
+                let long = match state.long_latency {
+                    Some(long) => long + (sample - long) / LONG_WINDOW_SAMPLES,
+                    None => sample,
+                };
+                state.long_latency = Some(long);
+                // An under-used limit says nothing about capacity; do not grow it.
+                if (in_flight as f64) * 2.0 < state.limit {
+                    return;
+                }
+                let gradient = (self.latency_tolerance * long / sample).clamp(MIN_GRADIENT, 1.0);
+                let target = state.limit * gradient + state.limit.sqrt();
+                state.limit = (state.limit * (1.0 - SMOOTHING) + target * SMOOTHING)
+                    .clamp(self.min_limit, self.max_limit);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimitState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+#[derive(Debug)]
+struct EndpointLimit {
+    limiter: Arc<AdaptiveLimiter>,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct EndpointLimits {
+    uses: u64,
+    entries: HashMap<String, EndpointLimit>,
+}
+
+/// Bounded endpoint-limit map that replaces idle, least recently used entries when full.
+#[derive(Debug)]
+pub(crate) struct EndpointLimitStore {
+    max_entries: usize,
+    limits: Mutex<EndpointLimits>,
+}
+
+impl EndpointLimitStore {
+    pub(crate) fn new(max_entries: usize) -> Self {
+        debug_assert!(max_entries > 0);
+        Self {
+            max_entries,
+            limits: Mutex::new(EndpointLimits::default()),
+        }
+    }
+
+    pub(crate) fn get_or_insert(
+        &self,
+        endpoint: &str,
+        config: &AdaptiveConcurrencyConfig,
+    ) -> Arc<AdaptiveLimiter> {
+        let mut limits = self
+            .limits
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        limits.uses += 1;
+        let now = limits.uses;
+        let entries = &mut limits.entries;
+        if let Some(entry) = entries.get_mut(endpoint) {
+            entry.last_used = now;
+            return entry.limiter.clone();
+        }
+        // A busy entry is only replaced when every entry is busy; its permits keep their Arc.
+        if entries.len() >= self.max_entries
+            && let Some(victim) = entries
+                .iter()
+                .min_by_key(|(_, entry)| (entry.limiter.lock().in_flight > 0, entry.last_used))
+                .map(|(endpoint, _)| endpoint.clone())
+        {
+            entries.remove(&victim);
+        }
+        let limiter = Arc::new(AdaptiveLimiter::new(config));
+        entries.insert(
+            endpoint.to_owned(),
+            EndpointLimit {
+                limiter: limiter.clone(),
+                last_used: now,
+            },
+        );
+        limiter
+    }
+
+    #[cfg(test)]
+    fn contains(&self, endpoint: &str) -> bool {
+        self.limits
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .entries
+            .contains_key(endpoint)
+    }
+}
+
+/// One admitted attempt; dropping it without a sample leaves the limit unchanged.
+#[derive(Debug)]
+pub(crate) struct LimitPermit {
+    limiter: Option<Arc<AdaptiveLimiter>>,
+}
+
+impl LimitPermit {
+    pub(crate) fn complete(mut self, sample: LimitSample) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release(sample);
+        }
+    }
+}
+
+impl Drop for LimitPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release(LimitSample::Ignore);
+        }
+    }
+}
+
+/// Service-binding and endpoint permits held together for one attempt.
+#[derive(Debug)]
+pub(crate) struct AttemptLimitPermit {
+    service: LimitPermit,
+    endpoint: LimitPermit,
+}
+
+impl AttemptLimitPermit {
+    pub(crate) const fn new(service: LimitPermit, endpoint: LimitPermit) -> Self {
+        Self { service, endpoint }
+    }
+
+    pub(crate) fn complete(self, sample: LimitSample) {
+        self.service.complete(sample);
+        self.endpoint.complete(sample);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(initial: u32) -> Arc<AdaptiveLimiter> {
+        Arc::new(AdaptiveLimiter::new(
+            &AdaptiveConcurrencyConfig::builder()
+                .initial_limit(initial)
+                .min_limit(2)
+                .max_limit(64)
+                .build()
+                .unwrap(),
+        ))
+    }
+
+    fn saturate(limiter: &Arc<AdaptiveLimiter>, latency: Duration) {
+        let permits = std::iter::from_fn(|| limiter.try_acquire()).collect::<Vec<_>>();
+        assert_eq!(permits.len(), limiter.limit());
+        for permit in permits {
+            permit.complete(LimitSample::Latency(latency));
+        }
+    }
+
+    #[test]
+    fn limit_grows_under_steady_latency_and_shrinks_when_latency_climbs() {
+        let limiter = limiter(8);
+        for _ in 0..10 {
+            saturate(&limiter, Duration::from_millis(10));
+        }
+        let grown = limiter.limit();
+        assert!(grown > 8, "steady latency grows the limit, got {grown}");
+
+        // Until the long-term average catches up, a latency jump shrinks the limit.
+        saturate(&limiter, Duration::from_millis(200));
+        saturate(&limiter, Duration::from_millis(200));
+        assert!(limiter.limit() < grown);
+    }
+
+    #[test]
+    fn overload_backs_off_to_the_floor_and_idle_samples_do_not_grow() {
+        let limiter = limiter(8);
+        let permit = limiter.try_acquire().unwrap();
+        permit.complete(LimitSample::Latency(Duration::from_millis(1)));
+        assert_eq!(limiter.limit(), 8);
+
+        for _ in 0..50 {
+            limiter
+                .try_acquire()
+                .unwrap()
+                .complete(LimitSample::Overloaded);
+        }
+        assert_eq!(limiter.limit(), 2);
+        let held = [limiter.try_acquire(), limiter.try_acquire()];
+        assert!(held.iter().all(Option::is_some));
+        assert!(limiter.try_acquire().is_none());
+        drop(held);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[test]
+    fn full_endpoint_store_replaces_the_idle_entry_used_least_recently() {
+        let config = AdaptiveConcurrencyConfig::default();
+        let store = EndpointLimitStore::new(3);
+        let busy = store.get_or_insert("a", &config).try_acquire().unwrap();
+        store.get_or_insert("b", &config);
+        store.get_or_insert("c", &config);
+        store.get_or_insert("b", &config);
+
+        // "a" is the oldest but busy, so the idle "c" is replaced rather than "b".
+        store.get_or_insert("d", &config);
+        assert!(store.contains("a") && store.contains("b") && store.contains("d"));
+        assert!(!store.contains("c"));
+
+        drop(busy);
+        store.get_or_insert("e", &config);
+        assert!(!store.contains("a"));
+    }
+}
@@ -1,7 +1,11 @@
-//! Retry and circuit-breaker policy foundations.
+//! Retry, hedging, adaptive concurrency, outlier ejection, and circuit-breaker policy
+//! foundations.
 
 pub(crate) mod breaker;
 pub(crate) mod classify;
+pub(crate) mod hedge;
+pub(crate) mod limit;
+pub(crate) mod outlier;
 pub(crate) mod retry;
 
 pub use breaker::FailureClass;
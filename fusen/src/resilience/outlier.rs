@@ -0,0 +1,215 @@
+//! Latency outlier ejection for the endpoints of one service binding.
+
+use crate::OutlierDetectionConfig;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Fewest evaluated endpoints for which a median is a meaningful baseline.
+const MIN_EVALUATED_ENDPOINTS: usize = 3;
+/// Longest ejection as a multiple of the base ejection time.
+const MAX_EJECTION_MULTIPLIER: u32 = 10;
+
+/// Per-interval successful attempt latencies and current ejections.
+#[derive(Debug)]
+pub(crate) struct OutlierDetector {
+    interval: Duration,
+    latency_ratio: f64,
+    min_requests: u32,
+    base_ejection: Duration,
+    max_ejection_ratio: f64,
+    state: Mutex<OutlierState>,
+}
+
+#[derive(Debug)]
+struct OutlierState {
+    window_start: Instant,
+    endpoints: HashMap<String, EndpointStats>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    requests: u32,
+    total: Duration,
+    ejected_until: Option<Instant>,
+    /// Consecutive ejections; each whole interval spent released forgives one.
+    ejections: u32,
+}
+
+impl OutlierDetector {
+    pub(crate) fn new(config: &OutlierDetectionConfig, now: Instant) -> Self {
+        Self {
+            interval: config.interval(),
+            latency_ratio: config.latency_ratio(),
+            min_requests: config.min_requests(),
+            base_ejection: config.base_ejection(),
+            max_ejection_ratio: config.max_ejection_ratio(),
+            state: Mutex::new(OutlierState {
+                window_start: now,
+                endpoints: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Records the latency of one successful attempt.
+    pub(crate) fn record(&self, endpoint: &str, latency: Duration, now: Instant) {
+        let mut state = self.lock();
+        self.roll(&mut state, now);
+        if !state.endpoints.contains_key(endpoint) {
+            state
+                .endpoints
+                .insert(endpoint.to_owned(), EndpointStats::default());
+        }
+        let stats = state
+            .endpoints
+            .get_mut(endpoint)
+            .expect("endpoint stats were just inserted");
+        stats.requests = stats.requests.saturating_add(1);
+        stats.total = stats.total.saturating_add(latency);
+    }
+
+    /// Returns whether the endpoint is currently ejected.
+    pub(crate) fn is_ejected(&self, endpoint: &str, now: Instant) -> bool {
+        let mut state = self.lock();
+        self.roll(&mut state, now);
+        state
+            .endpoints
+            .get(endpoint)
+            .and_then(|stats| stats.ejected_until)
+            .is_some_and(|until| until > now)
+    }
+
+    fn roll(&self, state: &mut OutlierState, now: Instant) {
+        if now.saturating_duration_since(state.window_start) < self.interval {
+            return;
+        }
+        state.window_start = now;
+        let mut evaluated = state
+            .endpoints
+            .iter()
+            .filter(|(_, stats)| stats.requests >= self.min_requests)
+            .map(|(endpoint, stats)| (endpoint.clone(), stats.total / stats.requests))
+            .collect::<Vec<_>>();
+        let mut ejected = Vec::new();
+        if evaluated.len() >= MIN_EVALUATED_ENDPOINTS {
+            evaluated.sort_by_key(|(_, mean)| *mean);
+            let median = evaluated[(evaluated.len() - 1) / 2].1;
+            let threshold = median.mul_f64(self.latency_ratio);
+            let already = state
+                .endpoints
+                .values()
+                .filter(|stats| stats.ejected_until.is_some_and(|until| until > now))
+                .count();
+            let allowed = ((evaluated.len() as f64 * self.max_ejection_ratio) as usize)
+                .saturating_sub(already);
+            ejected.extend(
+                evaluated
+                    .iter()
+                    .rev()
+                    .take_while(|(_, mean)| *mean > threshold)
+                    .take(allowed)
+                    .map(|(endpoint, _)| endpoint.clone()),
+            );
+        }
+        for (endpoint, stats) in &mut state.endpoints {
+            if ejected.contains(endpoint) {
+                stats.ejections = stats.ejections.saturating_add(1);
+                let multiplier = stats.ejections.min(MAX_EJECTION_MULTIPLIER);
+                stats.ejected_until = Some(now + self.base_ejection * multiplier);
+            } else if stats.ejected_until.is_some_and(|until| until <= now) {
+                stats.ejected_until = None;
+            } else if stats.ejected_until.is_none() {
+                stats.ejections = stats.ejections.saturating_sub(1);
+            }
+            stats.requests = 0;
+            stats.total = Duration::ZERO;
+        }
+        // Endpoints that left the snapshot stop producing samples and age out here.
+        state
+            .endpoints
+            .retain(|_, stats| stats.ejected_until.is_some() || stats.ejections > 0);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OutlierState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(10);
+    const EJECTION: Duration = Duration::from_secs(30);
+
+    fn detector(max_ejection_ratio: f64, now: Instant) -> OutlierDetector {
+        OutlierDetector::new(
+            &OutlierDetectionConfig::builder()
+                .interval(INTERVAL)
+                .min_requests(2)
+                .base_ejection(EJECTION)
+                .max_ejection_ratio(max_ejection_ratio)
+                .build()
+                .unwrap(),
+            now,
+        )
+    }
+
+    fn sample(detector: &OutlierDetector, latencies: &[(&str, u64)], now: Instant) {
+        for (endpoint, millis) in latencies {
+            for _ in 0..2 {
+                detector.record(endpoint, Duration::from_millis(*millis), now);
+            }
+        }
+    }
+
+    #[test]
+    fn slow_endpoint_is_ejected_for_a_growing_time_and_then_forgiven() {
+        let mut now = Instant::now();
+        let detector = detector(0.5, now);
+        let peers = [("a", 10), ("b", 12), ("c", 11), ("slow", 50)];
+        sample(&detector, &peers, now);
+        now += INTERVAL;
+        assert!(detector.is_ejected("slow", now));
+        assert!(!detector.is_ejected("a", now));
+
+        now += EJECTION;
+        assert!(!detector.is_ejected("slow", now));
+        sample(&detector, &peers, now);
+        now += INTERVAL;
+        assert!(detector.is_ejected("slow", now));
+        assert!(detector.is_ejected("slow", now + EJECTION));
+        assert!(!detector.is_ejected("slow", now + EJECTION * 2));
+    }
+
+    #[test]
+    fn too_few_samples_or_endpoints_eject_nothing_and_ejections_are_capped() {
+        let mut now = Instant::now();
+        let detector = detector(0.2, now);
+        sample(&detector, &[("a", 10), ("slow", 50)], now);
+        detector.record("c", Duration::from_millis(10), now);
+        now += INTERVAL;
+        assert!(!detector.is_ejected("slow", now));
+
+        // A fifth of five endpoints may be ejected at once; the slowest goes first.
+        sample(
+            &detector,
+            &[
+                ("a", 10),
+                ("b", 10),
+                ("c", 10),
+                ("slow", 60),
+                ("slowest", 70),
+            ],
+            now,
+        );
+        now += INTERVAL;
+        let ejected = ["a", "b", "c", "slow", "slowest"]
+            .into_iter()
+            .filter(|endpoint| detector.is_ejected(endpoint, now))
+            .collect::<Vec<_>>();
+        assert_eq!(ejected, ["slowest"]);
+    }
+}
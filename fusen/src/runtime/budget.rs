@@ -82,6 +82,16 @@ impl BytePermit {
     pub(crate) fn belongs_to(&self, budget: &Arc<ByteBudget>) -> bool {
         Arc::ptr_eq(&self.budget, budget)
     }
+
+    /// Moves `bytes` of this reservation into a new permit without touching the shared budget.
+    pub(crate) fn split(&self, bytes: usize) -> BytePermit {
+        let previous = self.bytes.fetch_sub(bytes, Ordering::AcqRel);
+        debug_assert!(previous >= bytes);
+        BytePermit {
+            budget: self.budget.clone(),
+            bytes: AtomicUsize::new(bytes),
+        }
+    }
 }
 
 impl Drop for BytePermit {
@@ -169,6 +179,18 @@ mod tests {
         assert_eq!(budget.used(), 0);
     }
 
+    #[test]
+    fn split_permits_release_their_own_share() {
+        let budget = ByteBudget::new(8);
+        let permit = budget.try_reserve(8).unwrap();
+        let frame = permit.split(3);
+        assert_eq!((permit.bytes(), frame.bytes()), (5, 3));
+        drop(permit);
+        assert_eq!(budget.used(), 3);
+        drop(frame);
+        assert_eq!(budget.used(), 0);
+    }
+
     #[test]
     fn budgeted_writer_reserves_before_growth_and_releases_on_failure() {
         use std::io::Write;
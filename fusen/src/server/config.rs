@@ -1,6 +1,18 @@
+#[cfg(feature = "compression")]
+use crate::ContentCoding;
+#[cfg(feature = "hot-tls")]
+use crate::TlsCertificate;
 use crate::{ConfigValidationError, ConfigValidationErrorKind};
-use fusen_contract::{EndpointCapabilities, HttpBindingId, HttpVersionSet};
-use std::time::Duration;
+#[cfg(any(feature = "hot-tls", feature = "hot-rate-limit"))]
+use fusen_config::HotConfig;
+use fusen_contract::{EndpointCapabilities, HttpBindingId, HttpVersionSet, Metadata};
+use http::HeaderName;
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 const MIB: usize = 1024 * 1024;
 
@@ -15,6 +27,8 @@ pub struct ServerRequestConfig {
     max_inflight_response_body_bytes: usize,
     queue_capacity: usize,
     queue_max_wait: Duration,
+    load_shedding: Option<LoadSheddingConfig>,
+    rate_limit: Option<RateLimitSource>,
 }
 
 impl Default for ServerRequestConfig {
@@ -28,6 +42,8 @@ impl Default for ServerRequestConfig {
             max_inflight_response_body_bytes: 64 * MIB,
             queue_capacity: 0,
             queue_max_wait: Duration::from_millis(50),
+            load_shedding: None,
+            rate_limit: None,
         }
     }
 }
@@ -77,6 +93,29 @@ impl ServerRequestConfig {
     pub const fn queue_max_wait(&self) -> Duration {
         self.queue_max_wait
     }
+
+    /// Returns priority-aware load shedding settings, if enabled.
+    pub const fn load_shedding(&self) -> Option<&LoadSheddingConfig> {
+        self.load_shedding.as_ref()
+    }
+
+    /// Returns the rate limit quotas currently in force, if enabled.
+    pub fn rate_limit(&self) -> Option<Arc<RateLimitConfig>> {
+        self.rate_limit.as_ref().map(RateLimitSource::current)
+    }
+
+    /// Returns the hot quota source, if configured.
+    #[cfg(feature = "hot-rate-limit")]
+    pub fn hot_rate_limit(&self) -> Option<&HotConfig<RateLimitConfig>> {
+        match &self.rate_limit {
+            Some(RateLimitSource::Hot(quotas)) => Some(quotas),
+            Some(RateLimitSource::Static(_)) | None => None,
+        }
+    }
+
+    pub(crate) const fn rate_limit_source(&self) -> Option<&RateLimitSource> {
+        self.rate_limit.as_ref()
+    }
 }
 
 /// Builder for [`ServerRequestConfig`].
@@ -132,6 +171,30 @@ impl ServerRequestConfigBuilder {
         self
     }
 
+    /// Sheds lower-priority requests while the admission queue delay stays above target.
+    ///
+    /// Requires a non-zero queue capacity, whose wait time is the overload signal.
+    pub fn load_shedding(mut self, value: LoadSheddingConfig) -> Self {
+        self.0.load_shedding = Some(value);
+        self
+    }
+
+    /// Rejects requests over the given quotas before their body is polled.
+    pub fn rate_limit(mut self, value: RateLimitConfig) -> Self {
+        self.0.rate_limit = Some(RateLimitSource::Static(Arc::new(value)));
+        self
+    }
+
+    /// Rejects requests over the latest last-good quotas before their body is polled.
+    ///
+    /// Each accepted update replaces every quota and starts from full buckets; rejected documents
+    /// are reported through [`HotConfig::last_error`] and leave the current quotas in force.
+    #[cfg(feature = "hot-rate-limit")]
+    pub fn hot_rate_limit(mut self, value: HotConfig<RateLimitConfig>) -> Self {
+        self.0.rate_limit = Some(RateLimitSource::Hot(value));
+        self
+    }
+
     /// Validates and builds request limits.
     pub fn build(self) -> Result<ServerRequestConfig, ConfigValidationError> {
         validate_request(&self.0)?;
@@ -139,7 +202,454 @@ impl ServerRequestConfigBuilder {
     }
 }
 
-/// Plain HTTP/1.1 and h2c server settings.
+/// Priority-aware load shedding driven by admission queue delay.
+///
+/// When the shortest queue delay seen during a whole interval exceeds the target, the server sheds
+/// one more priority class, starting with `low`; each interval at or below target restores one.
+/// `critical` requests are never shed.
+#[derive(Clone, Debug)]
+pub struct LoadSheddingConfig {
+    target_queue_delay: Duration,
+    interval: Duration,
+    retry_after: Duration,
+}
+
+impl Default for LoadSheddingConfig {
+    fn default() -> Self {
+        Self {
+            target_queue_delay: Duration::from_millis(10),
+            interval: Duration::from_millis(100),
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
+
+impl LoadSheddingConfig {
+    /// Starts a builder with a 10 ms target, a 100 ms interval, and a 1 s retry delay.
+    pub fn builder() -> LoadSheddingConfigBuilder {
+        LoadSheddingConfigBuilder(Self::default())
+    }
+
+    /// Returns the acceptable standing queue delay.
+    pub const fn target_queue_delay(&self) -> Duration {
+        self.target_queue_delay
+    }
+
+    /// Returns the window over which the shortest queue delay is compared with the target.
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the `Retry-After` delay sent with shed responses.
+    pub const fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+/// Builder for [`LoadSheddingConfig`].
+#[derive(Clone, Debug)]
+pub struct LoadSheddingConfigBuilder(LoadSheddingConfig);
+
+impl LoadSheddingConfigBuilder {
+    /// Sets the acceptable standing queue delay.
+    pub const fn target_queue_delay(mut self, value: Duration) -> Self {
+        self.0.target_queue_delay = value;
+        self
+    }
+
+    /// Sets the window over which the shortest queue delay is compared with the target.
+    pub const fn interval(mut self, value: Duration) -> Self {
+        self.0.interval = value;
+        self
+    }
+
+    /// Sets the `Retry-After` delay sent with shed responses, rounded up to whole seconds.
+    pub const fn retry_after(mut self, value: Duration) -> Self {
+        self.0.retry_after = value;
+        self
+    }
+
+    /// Validates and builds load shedding settings.
+    pub fn build(self) -> Result<LoadSheddingConfig, ConfigValidationError> {
+        validate_load_shedding(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// Where the server reads rate limit quotas from.
+#[derive(Clone, Debug)]
+pub(crate) enum RateLimitSource {
+    Static(Arc<RateLimitConfig>),
+    #[cfg(feature = "hot-rate-limit")]
+    Hot(HotConfig<RateLimitConfig>),
+}
+
+impl RateLimitSource {
+    pub(crate) fn current(&self) -> Arc<RateLimitConfig> {
+        match self {
+            Self::Static(quotas) => quotas.clone(),
+            #[cfg(feature = "hot-rate-limit")]
+            Self::Hot(quotas) => quotas.current(),
+        }
+    }
+}
+
+/// Token-bucket quotas checked at the `ServerHead` stage, before a request body is polled.
+///
+/// A request must fit every rule that matches it. Rejected requests fail with a retryable
+/// `429 rate_limited` whose `Retry-After` is the time until the tightest bucket refills.
+///
+/// As a hot configuration document it has an optional `max_keys` and a `rules` array whose
+/// entries take `permits`, optional `period_ms` (default 1000), `burst`, `service`, `method`,
+/// and `header`; the document is validated like the builder.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "RateLimitDocument")]
+pub struct RateLimitConfig {
+    rules: Vec<RateLimitRule>,
+    max_keys: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_keys: 10_000,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Starts a builder with no rules and at most 10,000 header-keyed buckets.
+    pub fn builder() -> RateLimitConfigBuilder {
+        RateLimitConfigBuilder(Self::default())
+    }
+
+    /// Returns the rules in declaration order.
+    pub fn rules(&self) -> &[RateLimitRule] {
+        &self.rules
+    }
+
+    /// Returns the cap on buckets tracked for distinct header values across all rules.
+    ///
+    /// Once full, header values without a bucket share one overflow bucket per rule, separate
+    /// from the bucket of requests that omit the header.
+    pub const fn max_keys(&self) -> usize {
+        self.max_keys
+    }
+}
+
+/// Builder for [`RateLimitConfig`].
+#[derive(Clone, Debug)]
+pub struct RateLimitConfigBuilder(RateLimitConfig);
+
+impl RateLimitConfigBuilder {
+    /// Appends one rule.
+    pub fn rule(mut self, value: RateLimitRule) -> Self {
+        self.0.rules.push(value);
+        self
+    }
+
+    /// Sets the cap on buckets tracked for distinct header values.
+    pub const fn max_keys(mut self, value: usize) -> Self {
+        self.0.max_keys = value;
+        self
+    }
+
+    /// Validates and builds rate limit quotas.
+    pub fn build(self) -> Result<RateLimitConfig, ConfigValidationError> {
+        validate_rate_limit(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// One quota: `permits` per `period`, with up to `burst` requests admitted back to back.
+///
+/// Without filters the rule applies to every request. `service` and `method` restrict it to one
+/// service ID or invocation method name, and `header` gives each distinct value of that request
+/// header its own bucket; requests without the header share one bucket.
+#[derive(Clone, Debug)]
+pub struct RateLimitRule {
+    permits: u32,
+    period: Duration,
+    burst: u32,
+    service: Option<String>,
+    method: Option<String>,
+    header: Option<HeaderName>,
+}
+
+impl RateLimitRule {
+    /// Starts a global rule admitting `permits` requests per `period`, with an equal burst.
+    pub fn builder(permits: u32, period: Duration) -> RateLimitRuleBuilder {
+        RateLimitRuleBuilder {
+            rule: Self {
+                permits,
+                period,
+                burst: permits,
+                service: None,
+                method: None,
+                header: None,
+            },
+            header: None,
+        }
+    }
+
+    /// Returns the sustained number of requests per period.
+    pub const fn permits(&self) -> u32 {
+        self.permits
+    }
+
+    /// Returns the period over which `permits` requests are admitted.
+    pub const fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the number of requests a full bucket admits back to back.
+    pub const fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Returns the service ID this rule is restricted to, if any.
+    pub fn service(&self) -> Option<&str> {
+        self.service.as_deref()
+    }
+
+    /// Returns the invocation method name this rule is restricted to, if any.
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    /// Returns the request header whose values key separate buckets, if any.
+    pub const fn header(&self) -> Option<&HeaderName> {
+        self.header.as_ref()
+    }
+}
+
+/// Builder for [`RateLimitRule`].
+#[derive(Clone, Debug)]
+pub struct RateLimitRuleBuilder {
+    rule: RateLimitRule,
+    header: Option<String>,
+}
+
+impl RateLimitRuleBuilder {
+    /// Sets the number of requests a full bucket admits back to back.
+    pub const fn burst(mut self, value: u32) -> Self {
+        self.rule.burst = value;
+        self
+    }
+
+    /// Restricts the rule to one service ID.
+    pub fn service(mut self, value: impl Into<String>) -> Self {
+        self.rule.service = Some(value.into());
+        self
+    }
+
+    /// Restricts the rule to one invocation method name.
+    pub fn method(mut self, value: impl Into<String>) -> Self {
+        self.rule.method = Some(value.into());
+        self
+    }
+
+    /// Keys one bucket per distinct value of this request header, such as a caller ID.
+    pub fn header(mut self, value: impl Into<String>) -> Self {
+        self.header = Some(value.into());
+        self
+    }
+
+    /// Validates and builds the rule.
+    pub fn build(mut self) -> Result<RateLimitRule, ConfigValidationError> {
+        if let Some(header) = self.header {
+            self.rule.header = Some(HeaderName::try_from(header).map_err(|_| {
+                out_of_range(
+                    "server.request.rate_limit.rules.header",
+                    "must be a valid HTTP header name",
+                )
+            })?);
+        }
+        validate_rate_limit_rule(&self.rule)?;
+        Ok(self.rule)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RateLimitDocument {
+    max_keys: Option<usize>,
+    #[serde(default)]
+    rules: Vec<RateLimitRuleDocument>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RateLimitRuleDocument {
+    permits: u32,
+    period_ms: Option<u64>,
+    burst: Option<u32>,
+    service: Option<String>,
+    method: Option<String>,
+    header: Option<String>,
+}
+
+impl TryFrom<RateLimitDocument> for RateLimitConfig {
+    type Error = ConfigValidationError;
+
+    fn try_from(document: RateLimitDocument) -> Result<Self, Self::Error> {
+        let mut builder = Self::builder();
+        if let Some(max_keys) = document.max_keys {
+            builder = builder.max_keys(max_keys);
+        }
+        for rule in document.rules {
+            let mut rule_builder = RateLimitRule::builder(
+                rule.permits,
+                Duration::from_millis(rule.period_ms.unwrap_or(1000)),
+            );
+            if let Some(burst) = rule.burst {
+                rule_builder = rule_builder.burst(burst);
+            }
+            if let Some(service) = rule.service {
+                rule_builder = rule_builder.service(service);
+            }
+            if let Some(method) = rule.method {
+                rule_builder = rule_builder.method(method);
+            }
+            if let Some(header) = rule.header {
+                rule_builder = rule_builder.header(header);
+            }
+            builder = builder.rule(rule_builder.build()?);
+        }
+        builder.build()
+    }
+}
+
+/// PEM certificate material for the built-in TLS listener.
+#[derive(Clone, Debug)]
+pub struct ServerTlsConfig {
+    certificate_chain_path: PathBuf,
+    private_key_path: PathBuf,
+    handshake_timeout: Duration,
+}
+
+impl ServerTlsConfig {
+    /// Starts a builder from a PEM certificate chain file and a PEM private key file.
+    pub fn builder(
+        certificate_chain_path: impl Into<PathBuf>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> ServerTlsConfigBuilder {
+        ServerTlsConfigBuilder(Self {
+            certificate_chain_path: certificate_chain_path.into(),
+            private_key_path: private_key_path.into(),
+            handshake_timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Returns the PEM certificate chain path, leaf certificate first.
+    pub fn certificate_chain_path(&self) -> &Path {
+        &self.certificate_chain_path
+    }
+
+    /// Returns the PEM PKCS#8, PKCS#1, or SEC1 private key path.
+    pub fn private_key_path(&self) -> &Path {
+        &self.private_key_path
+    }
+
+    /// Returns the per-connection TLS handshake deadline.
+    pub const fn handshake_timeout(&self) -> Duration {
+        self.handshake_timeout
+    }
+}
+
+/// Builder for [`ServerTlsConfig`].
+#[derive(Clone, Debug)]
+pub struct ServerTlsConfigBuilder(ServerTlsConfig);
+
+impl ServerTlsConfigBuilder {
+    /// Sets the per-connection TLS handshake deadline.
+    pub const fn handshake_timeout(mut self, value: Duration) -> Self {
+        self.0.handshake_timeout = value;
+        self
+    }
+
+    /// Validates and builds TLS settings without reading the PEM files.
+    pub fn build(self) -> Result<ServerTlsConfig, ConfigValidationError> {
+        validate_tls(&self.0)?;
+        Ok(self.0)
+    }
+}
+
+/// Built-in TLS listener whose certificate follows a last-good hot configuration.
+///
+/// Each new handshake uses the latest accepted certificate; established connections keep the
+/// certificate they negotiated until they close.
+#[cfg(feature = "hot-tls")]
+#[derive(Clone, Debug)]
+pub struct HotServerTlsConfig {
+    certificate: HotConfig<TlsCertificate>,
+    handshake_timeout: Duration,
+}
+
+#[cfg(feature = "hot-tls")]
+impl HotServerTlsConfig {
+    /// Starts a builder from the hot certificate source.
+    pub fn builder(certificate: HotConfig<TlsCertificate>) -> HotServerTlsConfigBuilder {
+        HotServerTlsConfigBuilder(Self {
+            certificate,
+            handshake_timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Returns the hot certificate source.
+    pub const fn certificate(&self) -> &HotConfig<TlsCertificate> {
+        &self.certificate
+    }
+
+    /// Returns the per-connection TLS handshake deadline.
+    pub const fn handshake_timeout(&self) -> Duration {
+        self.handshake_timeout
+    }
+}
+
+/// Builder for [`HotServerTlsConfig`].
+#[cfg(feature = "hot-tls")]
+#[derive(Clone, Debug)]
+pub struct HotServerTlsConfigBuilder(HotServerTlsConfig);
+
+#[cfg(feature = "hot-tls")]
+impl HotServerTlsConfigBuilder {
+    /// Sets the per-connection TLS handshake deadline.
+    pub const fn handshake_timeout(mut self, value: Duration) -> Self {
+        self.0.handshake_timeout = value;
+        self
+    }
+
+    /// Validates and builds hot TLS settings.
+    pub fn build(self) -> Result<HotServerTlsConfig, ConfigValidationError> {
+        positive_duration(
+            self.0.handshake_timeout,
+            "server.http.tls.handshake_timeout",
+        )?;
+        Ok(self.0)
+    }
+}
+
+/// The certificate source installed on the listener.
+#[derive(Clone, Debug)]
+pub(crate) enum ListenerTls {
+    Files(ServerTlsConfig),
+    #[cfg(feature = "hot-tls")]
+    Hot(HotServerTlsConfig),
+}
+
+impl ListenerTls {
+    pub(crate) const fn handshake_timeout(&self) -> Duration {
+        match self {
+            Self::Files(config) => config.handshake_timeout,
+            #[cfg(feature = "hot-tls")]
+            Self::Hot(config) => config.handshake_timeout,
+        }
+    }
+}
+
+/// HTTP/1.1, HTTP/2, and optional TLS listener settings.
 #[derive(Clone, Debug)]
 pub struct HttpServerConfig {
     max_connections: usize,
@@ -151,6 +661,9 @@ pub struct HttpServerConfig {
     http2_max_concurrent_streams: u32,
     http2_keep_alive_interval: Option<Duration>,
     http2_keep_alive_timeout: Duration,
+    tls: Option<ListenerTls>,
+    #[cfg(feature = "compression")]
+    compression: Option<ServerCompressionConfig>,
 }
 
 impl Default for HttpServerConfig {
@@ -165,6 +678,9 @@ impl Default for HttpServerConfig {
             http2_max_concurrent_streams: 128,
             http2_keep_alive_interval: Some(Duration::from_secs(30)),
             http2_keep_alive_timeout: Duration::from_secs(10),
+            tls: None,
+            #[cfg(feature = "compression")]
+            compression: None,
         }
     }
 }
@@ -219,6 +735,36 @@ impl HttpServerConfig {
     pub const fn http2_keep_alive_timeout(&self) -> Duration {
         self.http2_keep_alive_timeout
     }
+
+    /// Returns file-based listener TLS settings. `None` with no hot TLS keeps plaintext HTTP/1.1
+    /// and h2c.
+    pub const fn tls(&self) -> Option<&ServerTlsConfig> {
+        match &self.tls {
+            Some(ListenerTls::Files(config)) => Some(config),
+            #[cfg(feature = "hot-tls")]
+            Some(ListenerTls::Hot(_)) => None,
+            None => None,
+        }
+    }
+
+    /// Returns hot-reloaded listener TLS settings, if configured.
+    #[cfg(feature = "hot-tls")]
+    pub const fn hot_tls(&self) -> Option<&HotServerTlsConfig> {
+        match &self.tls {
+            Some(ListenerTls::Hot(config)) => Some(config),
+            Some(ListenerTls::Files(_)) | None => None,
+        }
+    }
+
+    pub(crate) const fn listener_tls(&self) -> Option<&ListenerTls> {
+        self.tls.as_ref()
+    }
+
+    /// Returns response compression settings. `None` always sends identity bodies.
+    #[cfg(feature = "compression")]
+    pub const fn compression(&self) -> Option<&ServerCompressionConfig> {
+        self.compression.as_ref()
+    }
 }
 
 /// Builder for [`HttpServerConfig`].
@@ -280,6 +826,26 @@ impl HttpServerConfigBuilder {
         self
     }
 
+    /// Terminates TLS on the listener and negotiates HTTP/2 or HTTP/1.1 through ALPN.
+    pub fn tls(mut self, value: ServerTlsConfig) -> Self {
+        self.0.tls = Some(ListenerTls::Files(value));
+        self
+    }
+
+    /// Terminates TLS with a hot-reloaded certificate; replaces any file-based TLS settings.
+    #[cfg(feature = "hot-tls")]
+    pub fn hot_tls(mut self, value: HotServerTlsConfig) -> Self {
+        self.0.tls = Some(ListenerTls::Hot(value));
+        self
+    }
+
+    /// Compresses buffered responses for clients whose `Accept-Encoding` allows it.
+    #[cfg(feature = "compression")]
+    pub fn compression(mut self, value: ServerCompressionConfig) -> Self {
+        self.0.compression = Some(value);
+        self
+    }
+
     /// Validates and builds HTTP settings.
     pub fn build(self) -> Result<HttpServerConfig, ConfigValidationError> {
         validate_http(&self.0)?;
@@ -287,6 +853,74 @@ impl HttpServerConfigBuilder {
     }
 }
 
+/// Response compression negotiated from the request's `Accept-Encoding`.
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ServerCompressionConfig {
+    codings: Vec<ContentCoding>,
+    min_response_bytes: usize,
+}
+
+#[cfg(feature = "compression")]
+impl Default for ServerCompressionConfig {
+    fn default() -> Self {
+        Self {
+            codings: vec![
+                ContentCoding::Zstd,
+                ContentCoding::Brotli,
+                ContentCoding::Gzip,
+            ],
+            min_response_bytes: 1024,
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl ServerCompressionConfig {
+    /// Starts a builder preferring zstd, then br, then gzip, for responses of 1 KiB or more.
+    pub fn builder() -> ServerCompressionConfigBuilder {
+        ServerCompressionConfigBuilder(Self::default())
+    }
+
+    /// Returns the codings the server may apply, most preferred first.
+    pub fn codings(&self) -> &[ContentCoding] {
+        &self.codings
+    }
+
+    /// Returns the smallest response body that is worth compressing.
+    pub const fn min_response_bytes(&self) -> usize {
+        self.min_response_bytes
+    }
+}
+
+/// Builder for [`ServerCompressionConfig`].
+#[cfg(feature = "compression")]
+#[derive(Clone, Debug)]
+pub struct ServerCompressionConfigBuilder(ServerCompressionConfig);
+
+#[cfg(feature = "compression")]
+impl ServerCompressionConfigBuilder {
+    /// Replaces the codings the server may apply, most preferred first.
+    ///
+    /// Among the codings a request accepts, the server always picks the first one listed here.
+    pub fn codings(mut self, value: impl IntoIterator<Item = ContentCoding>) -> Self {
+        self.0.codings = value.into_iter().collect();
+        self
+    }
+
+    /// Sets the smallest response body that is worth compressing.
+    pub const fn min_response_bytes(mut self, value: usize) -> Self {
+        self.0.min_response_bytes = value;
+        self
+    }
+
+    /// Validates and builds compression settings.
+    pub fn build(self) -> Result<ServerCompressionConfig, ConfigValidationError> {
+        validate_compression(&self.0)?;
+        Ok(self.0)
+    }
+}
+
 /// Registry startup operation limits.
 #[derive(Clone, Debug)]
 pub struct ServerRegistryConfig {
@@ -361,6 +995,7 @@ impl ServerRegistryConfigBuilder {
 #[derive(Clone, Debug)]
 pub struct ServerConfig {
     capabilities: EndpointCapabilities,
+    metadata: Metadata,
     request: ServerRequestConfig,
     http: HttpServerConfig,
     registry: ServerRegistryConfig,
@@ -376,6 +1011,7 @@ impl Default for ServerConfig {
                 true,
             )
             .expect("built-in server capabilities are valid"),
+            metadata: Metadata::new(),
             request: ServerRequestConfig::default(),
             http: HttpServerConfig::default(),
             registry: ServerRegistryConfig::default(),
@@ -395,6 +1031,11 @@ impl ServerConfig {
         &self.capabilities
     }
 
+    /// Returns user metadata published with every service registration.
+    pub const fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
     /// Returns request limits.
     pub const fn request(&self) -> &ServerRequestConfig {
         &self.request
@@ -416,10 +1057,15 @@ impl ServerConfig {
     }
 
     pub(crate) fn validate(&self) -> Result<(), ConfigValidationError> {
-        if self.capabilities.bindings() != [HttpBindingId::default()] {
+        // Bindings other than the two built-in ones are checked against the codecs that
+        // ServerBuilder::http_binding registers.
+        if !self
+            .capabilities
+            .supports_binding(&HttpBindingId::default())
+        {
             return Err(inconsistent(
                 "server.capabilities.bindings",
-                "built-in server supports only http-json-v1",
+                "built-in server always serves http-json-v1",
             ));
         }
         validate_request(&self.request)?;
@@ -443,6 +1089,15 @@ impl ServerConfigBuilder {
         self
     }
 
+    /// Replaces user metadata, such as `zone` and `region`, published with every registration.
+    ///
+    /// Keys must be non-empty, free of surrounding whitespace and control characters, and outside
+    /// the reserved `fusen.` prefix; an invalid key fails startup with a validation error.
+    pub fn metadata(mut self, value: Metadata) -> Self {
+        self.0.metadata = value;
+        self
+    }
+
     /// Replaces request limits.
     pub fn request(mut self, value: ServerRequestConfig) -> Self {
         self.0.request = value;
@@ -506,6 +1161,66 @@ fn validate_request(config: &ServerRequestConfig) -> Result<(), ConfigValidation
             "must be positive when queue_capacity is non-zero",
         ));
     }
+    match &config.load_shedding {
+        Some(_) if config.queue_capacity == 0 => Err(inconsistent(
+            "server.request.load_shedding",
+            "requires a non-zero queue_capacity",
+        )),
+        Some(shedding) => validate_load_shedding(shedding),
+        None => Ok(()),
+    }
+}
+
+fn validate_load_shedding(config: &LoadSheddingConfig) -> Result<(), ConfigValidationError> {
+    positive_duration(
+        config.target_queue_delay,
+        "server.request.load_shedding.target_queue_delay",
+    )?;
+    positive_duration(config.interval, "server.request.load_shedding.interval")?;
+    positive_duration(
+        config.retry_after,
+        "server.request.load_shedding.retry_after",
+    )?;
+    if config.target_queue_delay >= config.interval {
+        return Err(inconsistent(
+            "server.request.load_shedding.target_queue_delay",
+            "must be shorter than interval",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rate_limit(config: &RateLimitConfig) -> Result<(), ConfigValidationError> {
+    positive_usize(config.max_keys, "server.request.rate_limit.max_keys")?;
+    config.rules.iter().try_for_each(validate_rate_limit_rule)
+}
+
+fn validate_rate_limit_rule(rule: &RateLimitRule) -> Result<(), ConfigValidationError> {
+    if rule.permits == 0 {
+        return Err(out_of_range(
+            "server.request.rate_limit.rules.permits",
+            "must be greater than zero",
+        ));
+    }
+    positive_duration(rule.period, "server.request.rate_limit.rules.period")?;
+    if rule.burst == 0 {
+        return Err(out_of_range(
+            "server.request.rate_limit.rules.burst",
+            "must be greater than zero",
+        ));
+    }
+    if rule.service.as_deref().is_some_and(str::is_empty) {
+        return Err(out_of_range(
+            "server.request.rate_limit.rules.service",
+            "must not be empty",
+        ));
+    }
+    if rule.method.as_deref().is_some_and(str::is_empty) {
+        return Err(out_of_range(
+            "server.request.rate_limit.rules.method",
+            "must not be empty",
+        ));
+    }
     Ok(())
 }
 
@@ -537,6 +1252,55 @@ fn validate_http(config: &HttpServerConfig) -> Result<(), ConfigValidationError>
     positive_duration(
         config.http2_keep_alive_timeout,
         "server.http.http2_keep_alive_timeout",
+    )?;
+    match &config.tls {
+        Some(ListenerTls::Files(tls)) => validate_tls(tls),
+        #[cfg(feature = "hot-tls")]
+        Some(ListenerTls::Hot(tls)) => {
+            positive_duration(tls.handshake_timeout, "server.http.tls.handshake_timeout")
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn validate_compression(config: &ServerCompressionConfig) -> Result<(), ConfigValidationError> {
+    if config.codings.is_empty() {
+        return Err(out_of_range(
+            "server.http.compression.codings",
+            "must contain at least one coding",
+        ));
+    }
+    if config
+        .codings
+        .iter()
+        .enumerate()
+        .any(|(index, coding)| config.codings[..index].contains(coding))
+    {
+        return Err(inconsistent(
+            "server.http.compression.codings",
+            "must not repeat a coding",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_tls(config: &ServerTlsConfig) -> Result<(), ConfigValidationError> {
+    if config.certificate_chain_path.as_os_str().is_empty() {
+        return Err(out_of_range(
+            "server.http.tls.certificate_chain_path",
+            "must not be empty",
+        ));
+    }
+    if config.private_key_path.as_os_str().is_empty() {
+        return Err(out_of_range(
+            "server.http.tls.private_key_path",
+            "must not be empty",
+        ));
+    }
+    positive_duration(
+        config.handshake_timeout,
+        "server.http.tls.handshake_timeout",
     )
 }
 
@@ -582,6 +1346,7 @@ const fn inconsistent(field_path: &'static str, reason: &'static str) -> ConfigV
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fusen_contract::{HTTP_JSON_V1, HTTP_MSGPACK_V1};
 
     #[test]
     fn default_getters_match_the_runtime_contract() {
@@ -596,6 +1361,9 @@ mod tests {
         assert_eq!(config.request().max_concurrent_requests(), 1024);
         assert_eq!(config.http().max_connections(), 2048);
         assert_eq!(config.http().http2_max_concurrent_streams(), 128);
+        assert!(config.http().tls().is_none());
+        #[cfg(feature = "compression")]
+        assert!(config.http().compression().is_none());
         assert_eq!(
             config.registry().operation_timeout(),
             Duration::from_secs(5)
@@ -641,4 +1409,160 @@ mod tests {
             .build()
             .unwrap();
     }
+
+    #[test]
+    fn load_shedding_is_opt_in_and_requires_a_queue() {
+        assert!(ServerRequestConfig::default().load_shedding().is_none());
+        let shedding = LoadSheddingConfig::builder().build().unwrap();
+        assert_eq!(shedding.target_queue_delay(), Duration::from_millis(10));
+        assert_eq!(shedding.interval(), Duration::from_millis(100));
+        assert_eq!(shedding.retry_after(), Duration::from_secs(1));
+
+        let error = ServerRequestConfig::builder()
+            .load_shedding(shedding.clone())
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(error.field_path(), "server.request.load_shedding");
+        let request = ServerRequestConfig::builder()
+            .queue_capacity(64)
+            .load_shedding(shedding)
+            .build()
+            .unwrap();
+        assert!(request.load_shedding().is_some());
+
+        for (builder, path) in [
+            (
+                LoadSheddingConfig::builder().retry_after(Duration::ZERO),
+                "server.request.load_shedding.retry_after",
+            ),
+            (
+                LoadSheddingConfig::builder().target_queue_delay(Duration::from_millis(100)),
+                "server.request.load_shedding.target_queue_delay",
+            ),
+        ] {
+            assert_eq!(builder.build().unwrap_err().field_path(), path);
+        }
+    }
+
+    #[test]
+    fn rate_limit_documents_validate_like_the_builder() {
+        assert!(ServerRequestConfig::default().rate_limit().is_none());
+        let quotas: RateLimitConfig = serde_json::from_str(
+            r#"{"max_keys":64,"rules":[
+                {"permits":100},
+                {"permits":5,"period_ms":60000,"burst":2,"service":"inventory",
+                 "method":"export","header":"X-Caller-Id"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(quotas.max_keys(), 64);
+        let [global, caller] = quotas.rules() else {
+            panic!("two rules");
+        };
+        assert_eq!(global.burst(), 100);
+        assert_eq!(global.period(), Duration::from_secs(1));
+        assert_eq!(caller.period(), Duration::from_secs(60));
+        assert_eq!(caller.burst(), 2);
+        assert_eq!(caller.service(), Some("inventory"));
+        assert_eq!(caller.method(), Some("export"));
+        assert_eq!(caller.header().unwrap().as_str(), "x-caller-id");
+
+        let request = ServerRequestConfig::builder()
+            .rate_limit(quotas)
+            .build()
+            .unwrap();
+        assert_eq!(request.rate_limit().unwrap().rules().len(), 2);
+
+        for (document, path) in [
+            (r#"{"rules":[{"permits":0}]}"#, "rules.permits"),
+            (r#"{"rules":[{"permits":1,"period_ms":0}]}"#, "rules.period"),
+            (r#"{"rules":[{"permits":1,"burst":0}]}"#, "rules.burst"),
+            (r#"{"rules":[{"permits":1,"method":""}]}"#, "rules.method"),
+            (
+                r#"{"rules":[{"permits":1,"header":"a b"}]}"#,
+                "rules.header",
+            ),
+            (r#"{"max_keys":0}"#, "max_keys"),
+        ] {
+            let error = serde_json::from_str::<RateLimitConfig>(document).unwrap_err();
+            let expected = format!("at server.request.rate_limit.{path} ");
+            assert!(error.to_string().contains(&expected), "{document}: {error}");
+        }
+        assert!(serde_json::from_str::<RateLimitConfig>(r#"{"rules":[],"limit":1}"#).is_err());
+    }
+
+    #[test]
+    fn capabilities_always_include_json() {
+        let capabilities = |bindings: &[&str]| {
+            EndpointCapabilities::new(
+                HttpVersionSet::ALL,
+                bindings
+                    .iter()
+                    .map(|binding| HttpBindingId::new(*binding).unwrap()),
+                true,
+            )
+            .unwrap()
+        };
+        ServerConfig::builder()
+            .capabilities(capabilities(&[
+                HTTP_MSGPACK_V1,
+                HTTP_JSON_V1,
+                "vendor.http-v2",
+            ]))
+            .build()
+            .unwrap();
+        let error = ServerConfig::builder()
+            .capabilities(capabilities(&[HTTP_MSGPACK_V1]))
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigValidationErrorKind::Inconsistent);
+        assert_eq!(error.field_path(), "server.capabilities.bindings");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_validation_rejects_empty_and_repeated_codings() {
+        let compression = ServerCompressionConfig::builder().build().unwrap();
+        assert_eq!(
+            compression.codings(),
+            &[
+                ContentCoding::Zstd,
+                ContentCoding::Brotli,
+                ContentCoding::Gzip
+            ]
+        );
+        assert_eq!(compression.min_response_bytes(), 1024);
+
+        let error = ServerCompressionConfig::builder()
+            .codings([])
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigValidationErrorKind::OutOfRange);
+        assert_eq!(error.field_path(), "server.http.compression.codings");
+
+        let error = ServerCompressionConfig::builder()
+            .codings([ContentCoding::Gzip, ContentCoding::Gzip])
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigValidationErrorKind::Inconsistent);
+    }
+
+    #[test]
+    fn tls_validation_rejects_empty_paths_and_zero_handshake_timeout() {
+        let tls = ServerTlsConfig::builder("chain.pem", "key.pem")
+            .build()
+            .unwrap();
+        assert_eq!(tls.handshake_timeout(), Duration::from_secs(10));
+        HttpServerConfig::builder().tls(tls).build().unwrap();
+
+        let error = ServerTlsConfig::builder("", "key.pem").build().unwrap_err();
+        assert_eq!(error.field_path(), "server.http.tls.certificate_chain_path");
+        let error = ServerTlsConfig::builder("chain.pem", "key.pem")
+            .handshake_timeout(Duration::ZERO)
+            .build()
+            .unwrap_err();
+        assert_eq!(error.kind(), ConfigValidationErrorKind::OutOfRange);
+        assert_eq!(error.field_path(), "server.http.tls.handshake_timeout");
+    }
 }
@@ -1,9 +1,16 @@
+#[cfg(feature = "compression")]
+use super::ServerCompressionConfig;
 use super::{
-    Readiness,
+    LoadSheddingConfig, Readiness,
+    config::RateLimitSource,
+    rate_limit::RateLimiter,
     routes::{MatchedRoute, RouteTable, validate_query_pairs},
+    shed::LoadShedder,
 };
+#[cfg(feature = "compression")]
+use crate::wire::negotiate;
 use crate::{
-    Context, Error, ErrorCategory, InterceptionStage, RetryHint, Side,
+    BodyStreamSlot, Context, Error, ErrorCategory, InterceptionStage, RetryHint, Side,
     context::ContextParts,
     interceptor::{Next, Terminal},
     runtime::{
@@ -14,13 +21,15 @@ use crate::{
     },
     service::ServerInvocation,
     wire::{
-        self, GuardedBody, RequestControl, encode_problem, encode_success, parse_content_length,
-        parse_request_control, read_body, validate_attempt, validate_content_type,
+        self, ContentCoding, GuardedBody, RequestControl, ResponseBinding, ServerBinding,
+        accepts_media_type, encode_problem, encode_success, parse_content_length, parse_priority,
+        parse_request_control, read_body, read_body_stream, request_content_coding,
+        validate_attempt, validate_body_stream_content_type, validate_content_type,
         validate_http_version, validated_request_id_header,
     },
 };
 use bytes::Bytes;
-use fusen_contract::{HTTP_JSON_V1, HttpBindingId, HttpVersionSet};
+use fusen_contract::{HttpBindingId, HttpVersionSet, MethodDescriptor, MethodPriority};
 use fusen_observability::{
     AdmissionRejectedEvent, InvocationFinishedEvent, InvocationStartedEvent, MetricEvent,
     MetricOutcome, MetricSide,
@@ -29,15 +38,14 @@ use futures_util::FutureExt;
 use http::{
     HeaderMap, Request, Response as HttpResponse, StatusCode,
     header::{
-        ACCEPT, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, HOST, TE, TRAILER, TRANSFER_ENCODING,
-        UPGRADE,
+        ACCEPT, ACCEPT_ENCODING, CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, HOST,
+        TE, TRAILER, TRANSFER_ENCODING, UPGRADE, VARY,
     },
 };
 use hyper::{
     body::{Body as HttpBody, Incoming},
     service::Service,
 };
-use serde_json::Value;
 use std::{
     convert::Infallible,
     future::Future,
@@ -55,6 +63,7 @@ pub(crate) struct HttpApp {
     readiness: Arc<Readiness>,
     http_versions: HttpVersionSet,
     invocation_controls: bool,
+    bindings: Arc<[ServerBinding]>,
     request_timeout: Duration,
     max_uri_bytes: usize,
     max_query_pairs: usize,
@@ -65,14 +74,19 @@ pub(crate) struct HttpApp {
     admission: Arc<AdmissionGate>,
     queue_slots: Option<Arc<Semaphore>>,
     queue_max_wait: Duration,
+    shedder: Option<Arc<LoadShedder>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
     request_budget: Arc<ByteBudget>,
     response_budget: Arc<ByteBudget>,
+    #[cfg(feature = "compression")]
+    compression: Option<ServerCompressionConfig>,
     metrics: SafeMetrics,
 }
 
 pub(crate) struct HttpAppConfig {
     pub http_versions: HttpVersionSet,
     pub invocation_controls: bool,
+    pub bindings: Arc<[ServerBinding]>,
     pub request_timeout: Duration,
     pub max_uri_bytes: usize,
     pub max_query_pairs: usize,
@@ -83,8 +97,12 @@ pub(crate) struct HttpAppConfig {
     pub max_concurrent_requests: usize,
     pub queue_capacity: usize,
     pub queue_max_wait: Duration,
+    pub load_shedding: Option<LoadSheddingConfig>,
+    pub rate_limit: Option<RateLimitSource>,
     pub request_byte_budget: usize,
     pub response_byte_budget: usize,
+    #[cfg(feature = "compression")]
+    pub compression: Option<ServerCompressionConfig>,
 }
 
 impl HttpApp {
@@ -99,6 +117,7 @@ impl HttpApp {
             readiness,
             http_versions: config.http_versions,
             invocation_controls: config.invocation_controls,
+            bindings: config.bindings,
             request_timeout: config.request_timeout,
             max_uri_bytes: config.max_uri_bytes,
             max_query_pairs: config.max_query_pairs,
@@ -110,8 +129,16 @@ impl HttpApp {
             queue_slots: (config.queue_capacity > 0)
                 .then(|| Arc::new(Semaphore::new(config.queue_capacity))),
             queue_max_wait: config.queue_max_wait,
+            shedder: config
+                .load_shedding
+                .map(|shedding| Arc::new(LoadShedder::new(&shedding, StdInstant::now()))),
+            rate_limiter: config
+                .rate_limit
+                .map(|quotas| Arc::new(RateLimiter::new(quotas))),
             request_budget: ByteBudget::new(config.request_byte_budget),
             response_budget: ByteBudget::new(config.response_byte_budget),
+            #[cfg(feature = "compression")]
+            compression: config.compression,
             metrics,
         }
     }
@@ -126,6 +153,11 @@ impl HttpApp {
 
     async fn handle(&self, request: Request<Incoming>) -> HttpResponse<GuardedBody> {
         let is_head = request.method() == http::Method::HEAD;
+        #[cfg(feature = "compression")]
+        let coding = self
+            .compression
+            .as_ref()
+            .and_then(|compression| negotiate(request.headers(), compression.codings()));
         let fallback_request_id = uuid::Uuid::new_v4().simple().to_string();
         let mut response = match self.try_handle(request).await {
             Ok(response) => response,
@@ -139,6 +171,31 @@ impl HttpApp {
         if is_head {
             *response.body_mut() = GuardedBody::new(Bytes::new(), None);
         }
+        if !self.bindings.is_empty() {
+            // Success bodies follow Accept once a binding other than http-json-v1 is enabled.
+            response
+                .headers_mut()
+                .append(VARY, http::HeaderValue::from_static("accept"));
+        }
+        #[cfg(feature = "compression")]
+        if let Some(compression) = &self.compression {
+            // The representation depends on Accept-Encoding whether or not this one is coded.
+            response
+                .headers_mut()
+                .append(VARY, http::HeaderValue::from_static("accept-encoding"));
+            if let Some(coding) = coding.filter(|_| !is_head)
+                && response.body_mut().compress(
+                    coding,
+                    compression.min_response_bytes(),
+                    &self.response_budget,
+                )
+            {
+                response.headers_mut().insert(
+                    CONTENT_ENCODING,
+                    http::HeaderValue::from_static(coding.as_str()),
+                );
+            }
+        }
         response
     }
 
@@ -221,14 +278,17 @@ impl HttpApp {
         }
         validate_query_pairs(request.uri().query(), self.max_query_pairs)?;
         validate_attempt(control.attempt, matched.route.method.allows_retries())?;
-        let _admission = self.acquire_admission(control.deadline).await?;
+        self.check_rate_limit(request.headers(), &matched)?;
+        let priority = self.request_priority(request.headers(), matched.route.method)?;
+        let admission = self.acquire_admission(control.deadline, priority).await?;
 
+        let binding = self.reported_binding(request.headers());
         let started = StdInstant::now();
         let http_version = request.version();
         self.metrics.record(&MetricEvent::InvocationStarted(
             InvocationStartedEvent::new(
                 MetricSide::Server,
-                HTTP_JSON_V1,
+                binding.as_str(),
                 Some(http_version_name(http_version)),
                 matched.route.service.selector().service_id(),
                 matched.route.method.invocation_name(),
@@ -237,15 +297,19 @@ impl HttpApp {
         let span = tracing::info_span!(
             "fusen.server.invocation",
             request_id = %control.request_id,
-            http_binding = HTTP_JSON_V1,
+            http_binding = binding.as_str(),
             network_protocol_version = ?request.version(),
             service = matched.route.service.selector().service_id(),
             method = matched.route.method.invocation_name(),
             attempt = control.attempt,
         );
-        let processed = AssertUnwindSafe(
-            self.execute_matched(request, &matched, &control),
-        )
+        let processed = AssertUnwindSafe(self.execute_matched(
+            request,
+            &matched,
+            &control,
+            has_controls,
+            binding.clone(),
+        ))
         .catch_unwind()
         .instrument(span)
         .await
@@ -275,7 +339,7 @@ impl HttpApp {
         self.metrics.record(&MetricEvent::InvocationFinished(
             InvocationFinishedEvent::new(
                 MetricSide::Server,
-                HTTP_JSON_V1,
+                binding.as_str(),
                 Some(http_version_name(http_version)),
                 matched.route.service.selector().service_id(),
                 matched.route.method.invocation_name(),
@@ -287,6 +351,10 @@ impl HttpApp {
             ),
         ));
         let mut response = response;
+        if response.body().is_stream() {
+            // Items are produced while the body is written, so admission lasts until it ends.
+            response.body_mut().hold(admission);
+        }
         if *controls_negotiated {
             response.headers_mut().insert(
                 wire::REQUEST_ID,
@@ -304,16 +372,29 @@ impl HttpApp {
         request: Request<Incoming>,
         matched: &MatchedRoute,
         control: &RequestControl,
+        invocation_controls: bool,
+        binding: HttpBindingId,
     ) -> Result<HttpResponse<GuardedBody>, Error> {
         let request_headers = application_headers(request.headers());
         let content_length = parse_content_length(request.headers())?;
         let body_required = matched.has_body();
-        validate_content_type(
-            request.headers(),
-            matched.route.method.http_operation().consumes(),
-            body_required,
-        )?;
+        let body_stream = matched.has_body_stream();
+        let consumes = matched.route.method.http_operation().consumes();
+        let body_binding = if body_stream {
+            validate_body_stream_content_type(request.headers(), consumes)?;
+            None
+        } else {
+            validate_content_type(request.headers(), consumes, body_required, &self.bindings)?
+        };
+        let response_binding =
+            self.accepted_binding(request.headers())
+                .map(|binding| ResponseBinding {
+                    binding,
+                    method: matched.route.method,
+                });
+        let content_coding = request_content_coding(request.headers(), body_stream)?;
         if !body_required
+            && !body_stream
             && (content_length.is_some_and(|length| length > 0)
                 || request.headers().contains_key(TRANSFER_ENCODING))
         {
@@ -323,7 +404,7 @@ impl HttpApp {
             side: Side::Server,
             stage: InterceptionStage::ServerHead,
             request_id: control.request_id.clone(),
-            binding_id: HttpBindingId::default(),
+            binding_id: binding,
             http_version: Some(request.version()),
             interface: matched.route.service,
             method: matched.route.method,
@@ -343,6 +424,8 @@ impl HttpApp {
             matched,
             control,
             content_length,
+            content_coding,
+            body_binding,
             body_required,
         };
         let response = control
@@ -353,9 +436,12 @@ impl HttpApp {
         encode_success(
             response,
             matched.route.method.http_operation().produces(),
+            response_binding,
             *matched.route.method.http_operation().method() == http::Method::HEAD,
             self.max_response_body,
             &self.response_budget,
+            &control.request_id,
+            invocation_controls,
         )
     }
 
@@ -369,29 +455,35 @@ impl HttpApp {
             matched,
             control,
             content_length,
+            content_coding,
+            body_binding,
             body_required,
         } = execution;
         let query = request.uri().query().map(str::to_owned);
-        let (_parts, body) = request.into_parts();
-        let request_headers = application_headers(&_parts.headers);
+        let (parts, body) = request.into_parts();
+        let request_headers = application_headers(&parts.headers);
         let arguments = if body_required {
             let (bytes, body_permit) = control
                 .deadline
                 .run(read_body(
                     body,
                     content_length,
+                    content_coding,
                     self.max_request_body,
                     &self.request_budget,
                 ))
                 .await
                 .map_err(|_| deadline_exceeded())??;
-            let body = serde_json::from_slice::<Value>(&bytes).map_err(|_| {
-                Error::framework(
-                    ErrorCategory::InvalidArgument,
-                    "invalid_json",
-                    "HTTP request body is invalid JSON",
-                )
-            })?;
+            let body = match body_binding {
+                Some(binding) => binding.decode(matched.route.method, parts.headers, bytes)?,
+                None => serde_json::from_slice(&bytes).map_err(|_| {
+                    Error::framework(
+                        ErrorCategory::InvalidArgument,
+                        "invalid_json",
+                        "HTTP request body is invalid JSON",
+                    )
+                })?,
+            };
             let arguments = matched.http_arguments(
                 query.as_deref(),
                 &request_headers,
@@ -400,6 +492,19 @@ impl HttpApp {
             )?;
             drop(body_permit);
             arguments
+        } else if matched.has_body_stream() {
+            // The handler reads the body under the request deadline, one budgeted chunk at a time.
+            context.set_body_stream(Some(BodyStreamSlot::new(read_body_stream(
+                body,
+                self.max_request_body,
+                &self.request_budget,
+            ))));
+            matched.http_arguments(
+                query.as_deref(),
+                &request_headers,
+                None,
+                self.max_query_pairs,
+            )?
         } else {
             validate_body_absent(body).await?;
             matched.http_arguments(
@@ -423,6 +528,25 @@ impl HttpApp {
             .map_err(|_| deadline_exceeded())?
     }
 
+    /// Returns the first enabled binding that `Accept` names with a non-zero weight.
+    fn accepted_binding(&self, headers: &HeaderMap) -> Option<&ServerBinding> {
+        self.bindings
+            .iter()
+            .find(|binding| accepts_media_type(headers, binding.media_type()))
+    }
+
+    /// Returns the binding whose representation a request carries or asks for.
+    ///
+    /// Bodyless requests without a matching `Accept` carry nothing binding-specific and are
+    /// reported as `http-json-v1`.
+    fn reported_binding(&self, headers: &HeaderMap) -> HttpBindingId {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| self.bindings.iter().find(|binding| binding.matches(value)))
+            .or_else(|| self.accepted_binding(headers))
+            .map_or_else(HttpBindingId::default, |binding| binding.id().clone())
+    }
+
     fn validate_head(&self, request: &Request<Incoming>) -> Result<(), Error> {
         if request.uri().to_string().len() > self.max_uri_bytes {
             return Err(Error::framework(
@@ -456,9 +580,57 @@ impl HttpApp {
         Ok(())
     }
 
-    async fn acquire_admission(&self, deadline: Deadline) -> Result<AdmissionGuard, Error> {
+    /// Rejects over-quota requests before they wait for admission or poll their body.
+    fn check_rate_limit(&self, headers: &HeaderMap, matched: &MatchedRoute) -> Result<(), Error> {
+        let Some(limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        limiter
+            .check(
+                matched.route.service.selector().service_id(),
+                matched.route.method.invocation_name(),
+                headers,
+                StdInstant::now(),
+            )
+            .map_err(|wait| {
+                self.metrics.record(&MetricEvent::AdmissionRejected(
+                    AdmissionRejectedEvent::new(MetricSide::Server, "rate_limited"),
+                ));
+                rate_limited(wait)
+            })
+    }
+
+    /// Resolves the load-shedding class; `x-fusen-priority` overrides the method declaration.
+    fn request_priority(
+        &self,
+        headers: &HeaderMap,
+        method: &MethodDescriptor,
+    ) -> Result<MethodPriority, Error> {
+        if self.shedder.is_none() {
+            return Ok(method.priority());
+        }
+        Ok(parse_priority(headers)?.unwrap_or(method.priority()))
+    }
+
+    async fn acquire_admission(
+        &self,
+        deadline: Deadline,
+        priority: MethodPriority,
+    ) -> Result<AdmissionGuard, Error> {
+        let queued = StdInstant::now();
+        if let Some(shedder) = &self.shedder
+            && shedder.sheds(priority, queued)
+        {
+            self.metrics.record(&MetricEvent::AdmissionRejected(
+                AdmissionRejectedEvent::new(MetricSide::Server, "load_shed"),
+            ));
+            return Err(load_shed(shedder.retry_after()));
+        }
         match self.admission.try_enter() {
-            Ok(guard) => return Ok(guard),
+            Ok(guard) => {
+                self.observe_queue_delay(queued);
+                return Ok(guard);
+            }
             Err(AdmissionError::Draining) => return Err(draining()),
             Err(AdmissionError::Overloaded) => {}
         }
@@ -475,6 +647,9 @@ impl HttpApp {
         let queue_deadline = deadline.min(Deadline::after(self.queue_max_wait));
         let result = queue_deadline.run(self.admission.enter()).await;
         drop(queue_permit);
+        if matches!(result, Ok(Ok(_)) | Err(_)) {
+            self.observe_queue_delay(queued);
+        }
         match result {
             Ok(Ok(guard)) => Ok(guard),
             Ok(Err(AdmissionError::Draining)) => Err(draining()),
@@ -487,6 +662,13 @@ impl HttpApp {
             )),
         }
     }
+
+    fn observe_queue_delay(&self, queued: StdInstant) {
+        if let Some(shedder) = &self.shedder {
+            let now = StdInstant::now();
+            shedder.observe(now.saturating_duration_since(queued), now);
+        }
+    }
 }
 
 impl Service<Request<Incoming>> for HttpApp {
@@ -540,6 +722,8 @@ struct HeadTerminal<'a> {
     matched: &'a MatchedRoute,
     control: &'a RequestControl,
     content_length: Option<usize>,
+    content_coding: Option<ContentCoding>,
+    body_binding: Option<&'a ServerBinding>,
     body_required: bool,
 }
 
@@ -547,6 +731,8 @@ struct BodyExecution<'a> {
     matched: &'a MatchedRoute,
     control: &'a RequestControl,
     content_length: Option<usize>,
+    content_coding: Option<ContentCoding>,
+    body_binding: Option<&'a ServerBinding>,
     body_required: bool,
 }
 
@@ -567,6 +753,8 @@ impl Terminal for HeadTerminal<'_> {
                         matched: self.matched,
                         control: self.control,
                         content_length: self.content_length,
+                        content_coding: self.content_coding,
+                        body_binding: self.body_binding,
                         body_required: self.body_required,
                     },
                 )
@@ -646,7 +834,9 @@ fn application_headers(headers: &HeaderMap) -> HeaderMap {
     let mut headers = headers.clone();
     for name in [
         ACCEPT,
+        ACCEPT_ENCODING,
         CONNECTION,
+        CONTENT_ENCODING,
         CONTENT_TYPE,
         CONTENT_LENGTH,
         HOST,
@@ -666,6 +856,24 @@ fn application_headers(headers: &HeaderMap) -> HeaderMap {
     headers
 }
 
+fn load_shed(retry_after: Duration) -> Error {
+    Error::framework(
+        ErrorCategory::ResourceExhausted,
+        "load_shed",
+        "server is shedding lower-priority requests",
+    )
+    .with_retry_hint(RetryHint::After(retry_after))
+}
+
+fn rate_limited(retry_after: Duration) -> Error {
+    Error::framework(
+        ErrorCategory::ResourceExhausted,
+        "rate_limited",
+        "request rate exceeds the configured quota",
+    )
+    .with_retry_hint(RetryHint::After(retry_after))
+}
+
 fn overloaded() -> Error {
     Error::framework(
         ErrorCategory::ResourceExhausted,
@@ -748,7 +956,9 @@ mod tests {
         let mut headers = HeaderMap::new();
         for name in [
             "accept",
+            "accept-encoding",
             "connection",
+            "content-encoding",
             "content-length",
             "content-type",
             "host",
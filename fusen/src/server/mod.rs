@@ -1,23 +1,27 @@
 mod config;
 mod http;
+mod rate_limit;
 mod routes;
+mod shed;
+mod tls;
 mod transport;
 
 use crate::{
-    Interceptor, ServerError, ServerErrorKind,
+    Interceptor, RequestDecoder, ResponseEncoder, ServerError, ServerErrorKind,
     interceptor::erase_interceptor,
     runtime::metrics::SafeMetrics,
     server::{
         http::{HttpApp, HttpAppConfig},
         routes::{Route, RouteTable},
+        tls::ServerTls,
         transport::{AcceptOutcome, DrainCommand, TransportConfig},
     },
     service::{IntoServerService, PreparedService},
-    wire::validate_json_service,
+    wire::{ServerBinding, validate_json_service},
 };
 use fusen_contract::{
-    ContractError, InstanceId, ServiceDescriptor, ServiceEndpoint, ServiceRegistration,
-    ServiceWeight,
+    ContractError, HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId, InstanceId, ServiceDescriptor,
+    ServiceEndpoint, ServiceRegistration, ServiceWeight,
 };
 use fusen_observability::{
     MetricEvent, MetricOutcome, MetricsRecorder, RegistryOperationEvent, ShutdownFinishedEvent,
@@ -41,11 +45,16 @@ use tokio::{
 };
 use tokio_util::sync::CancellationToken;
 
+#[cfg(feature = "hot-tls")]
+pub use config::{HotServerTlsConfig, HotServerTlsConfigBuilder};
 pub use config::{
-    HttpServerConfig, HttpServerConfigBuilder, ServerConfig, ServerConfigBuilder,
-    ServerRegistryConfig, ServerRegistryConfigBuilder, ServerRequestConfig,
-    ServerRequestConfigBuilder,
+    HttpServerConfig, HttpServerConfigBuilder, LoadSheddingConfig, LoadSheddingConfigBuilder,
+    RateLimitConfig, RateLimitConfigBuilder, RateLimitRule, RateLimitRuleBuilder, ServerConfig,
+    ServerConfigBuilder, ServerRegistryConfig, ServerRegistryConfigBuilder, ServerRequestConfig,
+    ServerRequestConfigBuilder, ServerTlsConfig, ServerTlsConfigBuilder,
 };
+#[cfg(feature = "compression")]
+pub use config::{ServerCompressionConfig, ServerCompressionConfigBuilder};
 
 pub(crate) const NOT_READY: u8 = 0;
 pub(crate) const READY: u8 = 1;
@@ -115,17 +124,26 @@ struct NamedRegistry {
     registry: Arc<dyn Registry>,
 }
 
-/// Validated plaintext HTTP/1.1 and h2c server that has not yet bound its listener.
+/// Validated HTTP/1.1 and HTTP/2 server that has not yet bound its listener.
 pub struct Server {
     address: SocketAddr,
     advertised_endpoint: Option<ServiceEndpoint>,
     config: ServerConfig,
+    tls: Option<ServerTls>,
     registries: Vec<NamedRegistry>,
     descriptors: Vec<&'static ServiceDescriptor>,
     routes: Arc<RouteTable>,
+    bindings: Arc<[ServerBinding]>,
     metrics: SafeMetrics,
 }
 
+struct PendingBinding {
+    id: HttpBindingId,
+    media_type: String,
+    request_decoder: Arc<dyn RequestDecoder>,
+    response_encoder: Arc<dyn ResponseEncoder>,
+}
+
 /// Builder for a clean-slate [`Server`].
 pub struct ServerBuilder {
     address: Result<SocketAddr, AddrParseError>,
@@ -135,6 +153,7 @@ pub struct ServerBuilder {
     head_interceptor: Vec<Arc<dyn Interceptor>>,
     interceptor: Vec<Arc<dyn Interceptor>>,
     services: Vec<PreparedService>,
+    http_bindings: Vec<PendingBinding>,
     metrics: Option<Arc<dyn MetricsRecorder>>,
 }
 
@@ -149,6 +168,7 @@ impl Server {
             head_interceptor: Vec::new(),
             interceptor: Vec::new(),
             services: Vec::new(),
+            http_bindings: Vec::new(),
             metrics: None,
         }
     }
@@ -169,14 +189,15 @@ impl Server {
                 error,
             )
         })?;
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
         let advertised = match self.advertised_endpoint {
             Some(endpoint) => endpoint,
-            None => format!("http://{local_addr}")
+            None => format!("{scheme}://{local_addr}")
                 .parse::<ServiceEndpoint>()
                 .map_err(|error| {
                     ServerError::with_source(
                         ServerErrorKind::Validation,
-                        "failed to construct advertised plaintext endpoint",
+                        "failed to construct advertised listener endpoint",
                         error,
                     )
                 })?,
@@ -210,6 +231,7 @@ impl Server {
             HttpAppConfig {
                 http_versions: self.config.capabilities().http_versions(),
                 invocation_controls: self.config.capabilities().invocation_controls(),
+                bindings: self.bindings,
                 request_timeout: request.timeout(),
                 max_uri_bytes: http_config.max_uri_bytes(),
                 max_query_pairs: http_config.max_query_pairs(),
@@ -220,8 +242,12 @@ impl Server {
                 max_concurrent_requests: request.max_concurrent_requests(),
                 queue_capacity: request.queue_capacity(),
                 queue_max_wait: request.queue_max_wait(),
+                load_shedding: request.load_shedding().cloned(),
+                rate_limit: request.rate_limit_source().cloned(),
                 request_byte_budget: request.max_inflight_request_body_bytes(),
                 response_byte_budget: request.max_inflight_response_body_bytes(),
+                #[cfg(feature = "compression")]
+                compression: http_config.compression().cloned(),
             },
             self.metrics.clone(),
         );
@@ -241,6 +267,7 @@ impl Server {
             completion: completion_sender,
             registrations,
             config: self.config,
+            tls: self.tls,
             metrics: self.metrics,
         }));
 
@@ -282,7 +309,8 @@ impl ServerBuilder {
 
     /// Sets the externally reachable HTTP or HTTPS endpoint published to registries.
     ///
-    /// HTTPS describes an external TLS terminator and does not enable TLS on the local listener.
+    /// Without an explicit endpoint, the bound socket is advertised as `https://` when
+    /// [`HttpServerConfig::tls`] is configured and as `http://` otherwise.
     pub fn advertised_endpoint(mut self, endpoint: impl AsRef<str>) -> Self {
         self.advertised_endpoint = Some(endpoint.as_ref().parse::<ServiceEndpoint>());
         self
@@ -318,6 +346,28 @@ impl ServerBuilder {
         self
     }
 
+    /// Registers a server-side HTTP binding served for requests whose `Content-Type` or
+    /// `Accept` names `media_type`.
+    ///
+    /// The binding shares the `http-json-v1` method mapping and Problem Details errors; only
+    /// buffered request bodies and buffered success responses use its codec. It is served only
+    /// while [`ServerConfig::capabilities`] advertises `id`.
+    pub fn http_binding(
+        mut self,
+        id: HttpBindingId,
+        media_type: impl AsRef<str>,
+        request_decoder: impl RequestDecoder,
+        response_encoder: impl ResponseEncoder,
+    ) -> Self {
+        self.http_bindings.push(PendingBinding {
+            id,
+            media_type: media_type.as_ref().to_owned(),
+            request_decoder: Arc::new(request_decoder),
+            response_encoder: Arc::new(response_encoder),
+        });
+        self
+    }
+
     /// Adds one macro-generated interface server.
     pub fn interface(mut self, interface: impl IntoServerService) -> Self {
         self.services.push(interface.into_server_service());
@@ -348,6 +398,13 @@ impl ServerBuilder {
             )
         })?;
         validate_registry_names(&self.registries)?;
+        let bindings = build_bindings(self.http_bindings, &self.config)?;
+        let tls = self
+            .config
+            .http()
+            .listener_tls()
+            .map(|tls| ServerTls::load(tls, self.config.capabilities().http_versions()))
+            .transpose()?;
         if self.services.is_empty() {
             return Err(ServerError::from_message(
                 ServerErrorKind::Validation,
@@ -410,14 +467,66 @@ impl ServerBuilder {
             address,
             advertised_endpoint,
             config: self.config,
+            tls,
             registries: self.registries,
             descriptors: descriptor_list,
             routes: Arc::new(routes),
+            bindings,
             metrics: SafeMetrics::new(self.metrics),
         })
     }
 }
 
+/// Resolves every advertised binding other than `http-json-v1` to its server codec.
+fn build_bindings(
+    pending: Vec<PendingBinding>,
+    config: &ServerConfig,
+) -> Result<Arc<[ServerBinding]>, ServerError> {
+    let mut registered: Vec<ServerBinding> = Vec::with_capacity(pending.len());
+    for binding in pending {
+        let binding = ServerBinding::registered(
+            binding.id,
+            &binding.media_type,
+            binding.request_decoder,
+            binding.response_encoder,
+        )
+        .map_err(|reason| ServerError::from_message(ServerErrorKind::Validation, reason))?;
+        if let Some(existing) = registered.iter().find(|existing| {
+            existing.id() == binding.id() || existing.media_type() == binding.media_type()
+        }) {
+            return Err(ServerError::from_message(
+                ServerErrorKind::Validation,
+                format!(
+                    "HTTP binding {} conflicts with registered binding {}",
+                    binding.id(),
+                    existing.id()
+                ),
+            ));
+        }
+        registered.push(binding);
+    }
+    let mut bindings = Vec::new();
+    for id in config.capabilities().bindings() {
+        match id.as_str() {
+            HTTP_JSON_V1 => {}
+            HTTP_MSGPACK_V1 => bindings.push(ServerBinding::msgpack()),
+            _ => {
+                let binding = registered
+                    .iter()
+                    .find(|binding| binding.id() == id)
+                    .ok_or_else(|| {
+                        ServerError::from_message(
+                            ServerErrorKind::Validation,
+                            format!("server capabilities advertise unregistered HTTP binding {id}"),
+                        )
+                    })?;
+                bindings.push(binding.clone());
+            }
+        }
+    }
+    Ok(Arc::from(bindings))
+}
+
 /// A bound server that reached Ready.
 pub struct RunningServer {
     inner: Arc<ServerHandleInner>,
@@ -536,6 +645,7 @@ struct Coordinator {
     completion: watch::Sender<Option<Result<(), ServerError>>>,
     registrations: Vec<PlannedRegistration>,
     config: ServerConfig,
+    tls: Option<ServerTls>,
     metrics: SafeMetrics,
 }
 
@@ -553,6 +663,7 @@ async fn coordinate(mut coordinator: Coordinator) {
         http2_max_concurrent_streams: http.http2_max_concurrent_streams(),
         http2_keep_alive_interval: http.http2_keep_alive_interval(),
         http2_keep_alive_timeout: http.http2_keep_alive_timeout(),
+        tls: coordinator.tls.take(),
     };
     let listener = coordinator
         .listener
@@ -1010,7 +1121,15 @@ fn build_registration_plan(
                 endpoint.clone(),
                 config.capabilities().clone(),
                 ServiceWeight::default(),
-            );
+            )
+            .with_metadata(config.metadata().clone())
+            .map_err(|error| {
+                ServerError::with_source(
+                    ServerErrorKind::Validation,
+                    "invalid server registration metadata",
+                    error,
+                )
+            })?;
             plan.push(PlannedRegistration {
                 name: registry.name.clone(),
                 registry: registry.registry.clone(),
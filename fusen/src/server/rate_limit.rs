@@ -0,0 +1,266 @@
+//! GCRA rate limiting checked before admission, while only request headers are known.
+
+use super::config::{RateLimitConfig, RateLimitRule, RateLimitSource};
+use http::HeaderMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Buckets for the quotas currently in force.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    source: RateLimitSource,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    quotas: Arc<RateLimitConfig>,
+    /// Theoretical arrival time per bucket; a bucket at or before `now` is full.
+    buckets: HashMap<BucketKey, Instant>,
+    keyed: usize,
+    /// No header-keyed bucket refills before this instant, so pruning cannot free a slot.
+    full_until: Option<Instant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct BucketKey {
+    rule: usize,
+    caller: Caller,
+}
+
+/// Which of a rule's buckets a request draws from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Caller {
+    /// Requests without the rule's header, or every request of a rule without one.
+    Unkeyed,
+    /// Header values that found no free slot under `max_keys`.
+    Overflow,
+    Keyed(Box<[u8]>),
+}
+
+impl BucketKey {
+    const fn is_keyed(&self) -> bool {
+        matches!(self.caller, Caller::Keyed(_))
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(source: RateLimitSource) -> Self {
+        let quotas = source.current();
+        Self {
+            source,
+            state: Mutex::new(LimiterState {
+                quotas,
+                buckets: HashMap::new(),
+                keyed: 0,
+                full_until: None,
+            }),
+        }
+    }
+
+    /// Takes one permit from every matching bucket, or none and returns the wait until all fit.
+    pub(crate) fn check(
+        &self,
+        service_id: &str,
+        method: &str,
+        headers: &HeaderMap,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let quotas = self.source.current();
+        let mut state = self.lock();
+        if !Arc::ptr_eq(&state.quotas, &quotas) {
+            // Replaced quotas start from full buckets rather than reinterpreting old ones.
+            *state = LimiterState {
+                quotas: quotas.clone(),
+                buckets: HashMap::new(),
+                keyed: 0,
+                full_until: None,
+            };
+        }
+        let mut taken = Vec::new();
+        let mut wait = Duration::ZERO;
+        for (index, rule) in quotas.rules().iter().enumerate() {
+            if !matches(rule, service_id, method) {
+                continue;
+            }
+            let key = state.bucket_key(index, rule, headers, quotas.max_keys(), now);
+            let tat = state.buckets.get(&key).copied().unwrap_or(now).max(now);
+            let emission = rule.period() / rule.permits();
+            let next = tat + emission;
+            let ahead = next.saturating_duration_since(now);
+            let tolerance = emission.saturating_mul(rule.burst());
+            if ahead > tolerance {
+                wait = wait.max(ahead - tolerance);
+            } else {
+                taken.push((key, next));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        for (key, next) in taken {
+            if key.is_keyed() && !state.buckets.contains_key(&key) {
+                state.keyed += 1;
+            }
+            state.buckets.insert(key, next);
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl LimiterState {
+    /// Picks the header-keyed bucket, falling back to the rule's overflow bucket when none fits.
+    fn bucket_key(
+        &mut self,
+        rule_index: usize,
+        rule: &RateLimitRule,
+        headers: &HeaderMap,
+        max_keys: usize,
+        now: Instant,
+    ) -> BucketKey {
+        let bucket = |caller| BucketKey {
+            rule: rule_index,
+            caller,
+        };
+        let Some(value) = rule.header().and_then(|name| headers.get(name)) else {
+            return bucket(Caller::Unkeyed);
+        };
+        let key = bucket(Caller::Keyed(value.as_bytes().into()));
+        if self.keyed < max_keys || self.buckets.contains_key(&key) {
+            return key;
+        }
+        if self.full_until.is_none_or(|until| until <= now) {
+            self.prune(now);
+        }
+        // Overflowing callers never draw from the bucket of callers that omit the header.
+        if self.keyed < max_keys {
+            key
+        } else {
+            bucket(Caller::Overflow)
+        }
+    }
+
+    /// Drops full header-keyed buckets; they are indistinguishable from absent ones.
+    fn prune(&mut self, now: Instant) {
+        self.buckets
+            .retain(|key, tat| !key.is_keyed() || *tat > now);
+        self.keyed = self.buckets.keys().filter(|key| key.is_keyed()).count();
+        self.full_until = self
+            .buckets
+            .iter()
+            .filter(|(key, _)| key.is_keyed())
+            .map(|(_, tat)| *tat)
+            .min();
+    }
+}
+
+fn matches(rule: &RateLimitRule, service_id: &str, method: &str) -> bool {
+    rule.service().is_none_or(|service| service == service_id)
+        && rule.method().is_none_or(|name| name == method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn limiter(max_keys: usize, rules: impl IntoIterator<Item = RateLimitRule>) -> RateLimiter {
+        let config = rules
+            .into_iter()
+            .fold(
+                RateLimitConfig::builder().max_keys(max_keys),
+                |builder, rule| builder.rule(rule),
+            )
+            .build()
+            .unwrap();
+        RateLimiter::new(RateLimitSource::Static(Arc::new(config)))
+    }
+
+    fn caller(id: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-caller", id.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn burst_is_admitted_then_requests_are_spaced_by_the_emission_interval() {
+        let limiter = limiter(
+            16,
+            [RateLimitRule::builder(4, SECOND).burst(2).build().unwrap()],
+        );
+        let headers = HeaderMap::new();
+        let start = Instant::now();
+        assert_eq!(limiter.check("svc", "m", &headers, start), Ok(()));
+        assert_eq!(limiter.check("svc", "m", &headers, start), Ok(()));
+        assert_eq!(
+            limiter.check("svc", "m", &headers, start),
+            Err(Duration::from_millis(250))
+        );
+        let later = start + Duration::from_millis(250);
+        assert_eq!(limiter.check("svc", "m", &headers, later), Ok(()));
+        assert!(limiter.check("svc", "m", &headers, later).is_err());
+    }
+
+    #[test]
+    fn every_matching_rule_must_admit_and_rejections_take_no_permits() {
+        let limiter = limiter(
+            16,
+            [
+                RateLimitRule::builder(10, SECOND).build().unwrap(),
+                RateLimitRule::builder(1, SECOND)
+                    .service("inventory")
+                    .method("export")
+                    .build()
+                    .unwrap(),
+            ],
+        );
+        let headers = HeaderMap::new();
+        let now = Instant::now();
+        assert_eq!(limiter.check("inventory", "export", &headers, now), Ok(()));
+        assert_eq!(
+            limiter.check("inventory", "export", &headers, now),
+            Err(SECOND)
+        );
+        // The global rule was not charged for the rejected call: nine permits remain.
+        for _ in 0..9 {
+            assert_eq!(limiter.check("inventory", "list", &headers, now), Ok(()));
+        }
+        assert!(limiter.check("inventory", "list", &headers, now).is_err());
+    }
+
+    #[test]
+    fn header_values_get_bounded_buckets_and_overflow_shares_a_separate_one() {
+        let limiter = limiter(
+            2,
+            [RateLimitRule::builder(1, SECOND)
+                .header("x-caller")
+                .build()
+                .unwrap()],
+        );
+        let now = Instant::now();
+        assert_eq!(limiter.check("svc", "m", &caller("a"), now), Ok(()));
+        assert_eq!(limiter.check("svc", "m", &caller("b"), now), Ok(()));
+        assert!(limiter.check("svc", "m", &caller("a"), now).is_err());
+
+        // Past the key cap, new callers share an overflow bucket, so rotating caller IDs cannot
+        // drain the quota of callers without the header.
+        assert_eq!(limiter.check("svc", "m", &caller("c"), now), Ok(()));
+        assert!(limiter.check("svc", "m", &caller("d"), now).is_err());
+        assert_eq!(limiter.check("svc", "m", &HeaderMap::new(), now), Ok(()));
+        assert!(limiter.check("svc", "m", &HeaderMap::new(), now).is_err());
+
+        // Once a keyed bucket refills it is pruned and its slot reused.
+        let later = now + SECOND;
+        assert_eq!(limiter.check("svc", "m", &caller("d"), later), Ok(()));
+        assert!(limiter.check("svc", "m", &caller("d"), later).is_err());
+        assert_eq!(limiter.check("svc", "m", &HeaderMap::new(), later), Ok(()));
+    }
+}
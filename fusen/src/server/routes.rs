@@ -239,6 +239,8 @@ impl MatchedRoute {
                 HttpParameterSource::HeaderMap => {
                     Value::Object(header_map(headers, &explicit_headers)?)
                 }
+                // Dispatch takes the body stream from the context, not from the arguments.
+                HttpParameterSource::BodyStream => continue,
                 _ => {
                     return Err(Error::framework(
                         ErrorCategory::Unimplemented,
@@ -265,6 +267,15 @@ impl MatchedRoute {
                 )
             })
     }
+
+    pub(crate) fn has_body_stream(&self) -> bool {
+        self.route
+            .method
+            .http_operation()
+            .parameters()
+            .iter()
+            .any(|parameter| parameter.source() == HttpParameterSource::BodyStream)
+    }
 }
 
 fn header_value(headers: &HeaderMap, name: &str) -> Result<Value, Error> {
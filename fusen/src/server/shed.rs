@@ -0,0 +1,164 @@
+//! Priority-aware load shedding driven by admission queue delay.
+
+use super::LoadSheddingConfig;
+use fusen_contract::MethodPriority;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Highest shed level; it rejects every class below `Critical`.
+const MAX_LEVEL: u8 = MethodPriority::Critical as u8;
+
+/// Tracks the shortest queue delay per interval and the priority classes currently shed.
+#[derive(Debug)]
+pub(crate) struct LoadShedder {
+    target: Duration,
+    interval: Duration,
+    retry_after: Duration,
+    state: Mutex<ShedState>,
+}
+
+#[derive(Debug)]
+struct ShedState {
+    /// Requests whose priority ranks below this level are shed.
+    level: u8,
+    window_start: Instant,
+    window_min: Option<Duration>,
+}
+
+impl LoadShedder {
+    pub(crate) fn new(config: &LoadSheddingConfig, now: Instant) -> Self {
+        Self {
+            target: config.target_queue_delay(),
+            interval: config.interval(),
+            retry_after: config.retry_after(),
+            state: Mutex::new(ShedState {
+                level: 0,
+                window_start: now,
+                window_min: None,
+            }),
+        }
+    }
+
+    pub(crate) const fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+
+    /// Returns whether a request of this priority must be rejected before admission.
+    pub(crate) fn sheds(&self, priority: MethodPriority, now: Instant) -> bool {
+        let mut state = self.lock();
+        self.roll(&mut state, now);
+        (priority as u8) < state.level
+    }
+
+    /// Records how long one request waited for admission.
+    pub(crate) fn observe(&self, queue_delay: Duration, now: Instant) {
+        let mut state = self.lock();
+        self.roll(&mut state, now);
+        state.window_min = Some(
+            state
+                .window_min
+                .map_or(queue_delay, |min| min.min(queue_delay)),
+        );
+    }
+
+    fn roll(&self, state: &mut ShedState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.window_start);
+        if elapsed < self.interval {
+            return;
+        }
+        // Only a delay that stayed above target for a whole window is a standing queue.
+        if state.window_min.is_some_and(|min| min > self.target) {
+            state.level = (state.level + 1).min(MAX_LEVEL);
+        } else {
+            state.level = state.level.saturating_sub(1);
+        }
+        // Each further window without samples restores one more class.
+        let idle = elapsed.as_nanos() / self.interval.as_nanos() - 1;
+        state.level = state
+            .level
+            .saturating_sub(u8::try_from(idle).unwrap_or(u8::MAX));
+        state.window_start = now;
+        state.window_min = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ShedState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn shedder(now: Instant) -> LoadShedder {
+        LoadShedder::new(
+            &LoadSheddingConfig::builder()
+                .target_queue_delay(Duration::from_millis(10))
+                .interval(INTERVAL)
+                .build()
+                .unwrap(),
+            now,
+        )
+    }
+
+    fn shed_classes(shedder: &LoadShedder, now: Instant) -> Vec<MethodPriority> {
+        [
+            MethodPriority::Low,
+            MethodPriority::Normal,
+            MethodPriority::High,
+            MethodPriority::Critical,
+        ]
+        .into_iter()
+        .filter(|priority| shedder.sheds(*priority, now))
+        .collect()
+    }
+
+    #[test]
+    fn standing_queue_sheds_one_more_class_per_interval_but_never_critical() {
+        let start = Instant::now();
+        let shedder = shedder(start);
+        let mut now = start;
+        for expected in [
+            vec![MethodPriority::Low],
+            vec![MethodPriority::Low, MethodPriority::Normal],
+            vec![
+                MethodPriority::Low,
+                MethodPriority::Normal,
+                MethodPriority::High,
+            ],
+            vec![
+                MethodPriority::Low,
+                MethodPriority::Normal,
+                MethodPriority::High,
+            ],
+        ] {
+            shedder.observe(Duration::from_millis(40), now);
+            now += INTERVAL;
+            assert_eq!(shed_classes(&shedder, now), expected);
+        }
+    }
+
+    #[test]
+    fn one_short_delay_per_interval_or_idle_windows_restore_classes() {
+        let start = Instant::now();
+        let shedder = shedder(start);
+        let mut now = start;
+        for _ in 0..3 {
+            shedder.observe(Duration::from_millis(40), now);
+            now += INTERVAL;
+        }
+        assert_eq!(shed_classes(&shedder, now).len(), 3);
+
+        shedder.observe(Duration::from_millis(40), now);
+        shedder.observe(Duration::from_millis(1), now);
+        now += INTERVAL;
+        assert_eq!(shed_classes(&shedder, now).len(), 2);
+
+        now += INTERVAL * 2;
+        assert!(shed_classes(&shedder, now).is_empty());
+    }
+}
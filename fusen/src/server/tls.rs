@@ -0,0 +1,102 @@
+use super::config::ListenerTls;
+use crate::{
+    ServerError, ServerErrorKind,
+    pem::{self, PemFileError},
+};
+use fusen_contract::HttpVersionSet;
+use rustls::ServerConfig as TlsServerConfig;
+use std::{sync::Arc, time::Duration};
+use tokio_rustls::TlsAcceptor;
+
+/// Immutable TLS acceptor shared by every accepted connection.
+#[derive(Clone)]
+pub(crate) struct ServerTls {
+    pub acceptor: TlsAcceptor,
+    pub handshake_timeout: Duration,
+}
+
+impl ServerTls {
+    pub(crate) fn load(
+        config: &ListenerTls,
+        http_versions: HttpVersionSet,
+    ) -> Result<Self, ServerError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = TlsServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|error| {
+                ServerError::with_source(
+                    ServerErrorKind::Validation,
+                    "failed to initialize the server TLS protocol versions",
+                    error,
+                )
+            })?
+            .with_no_client_auth();
+        let mut tls_config = match config {
+            ListenerTls::Files(files) => {
+                let chain = pem::read_certificates(
+                    files.certificate_chain_path(),
+                    "server",
+                    "certificate chain",
+                )
+                .map_err(pem_file_error)?;
+                let key = pem::read_private_key(files.private_key_path(), "server")
+                    .map_err(pem_file_error)?;
+                builder.with_single_cert(chain, key).map_err(|error| {
+                    ServerError::with_source(
+                        ServerErrorKind::Validation,
+                        "server TLS certificate chain and private key are inconsistent",
+                        error,
+                    )
+                })?
+            }
+            #[cfg(feature = "hot-tls")]
+            ListenerTls::Hot(hot) => builder.with_cert_resolver(Arc::new(
+                crate::tls::HotCertificateResolver(hot.certificate().clone()),
+            )),
+        };
+        tls_config.alpn_protocols = alpn_protocols(http_versions);
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(tls_config)),
+            handshake_timeout: config.handshake_timeout(),
+        })
+    }
+}
+
+// ALPN is ordered by server preference; HTTP/2 wins whenever both sides enable it.
+fn alpn_protocols(http_versions: HttpVersionSet) -> Vec<Vec<u8>> {
+    let mut protocols = Vec::with_capacity(2);
+    if http_versions.contains(http::Version::HTTP_2) {
+        protocols.push(b"h2".to_vec());
+    }
+    if http_versions.contains(http::Version::HTTP_11) {
+        protocols.push(b"http/1.1".to_vec());
+    }
+    protocols
+}
+
+fn pem_file_error(error: PemFileError) -> ServerError {
+    match error.into_parts() {
+        (message, Some(source)) => {
+            ServerError::with_source(ServerErrorKind::Validation, message, source)
+        }
+        (message, None) => ServerError::from_message(ServerErrorKind::Validation, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpn_prefers_h2_and_only_lists_enabled_versions() {
+        assert_eq!(
+            alpn_protocols(HttpVersionSet::ALL),
+            [b"h2".to_vec(), b"http/1.1".to_vec()]
+        );
+        assert_eq!(
+            alpn_protocols(HttpVersionSet::HTTP_1_1),
+            [b"http/1.1".to_vec()]
+        );
+        assert_eq!(alpn_protocols(HttpVersionSet::HTTP_2), [b"h2".to_vec()]);
+    }
+}
@@ -1,4 +1,4 @@
-use super::http::HttpApp;
+use super::{http::HttpApp, tls::ServerTls};
 use hyper::rt::Executor;
 #[cfg(test)]
 use hyper_util::rt::TokioExecutor;
@@ -8,6 +8,7 @@ use hyper_util::{
 };
 use std::{future::Future, io, sync::Arc, time::Duration};
 use tokio::{
+    io::{AsyncRead, AsyncWrite},
     net::TcpListener,
     sync::{Semaphore, broadcast, mpsc, oneshot},
     task::JoinSet,
@@ -70,6 +71,7 @@ pub(crate) struct TransportConfig {
     pub http2_max_concurrent_streams: u32,
     pub http2_keep_alive_interval: Option<Duration>,
     pub http2_keep_alive_timeout: Duration,
+    pub tls: Option<ServerTls>,
 }
 
 pub(crate) struct DrainCommand {
@@ -130,23 +132,34 @@ async fn run_inner(
                     let mut shutdown = graceful.subscribe();
                     let app = app.clone();
                     let builder = builder.clone();
+                    let tls = config.tls.clone();
                     tasks.spawn(async move {
                         let _permit = permit;
-                        let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), app);
-                        tokio::pin!(connection);
-                        tokio::select! {
-                            result = &mut connection => {
-                                if let Err(error) = result {
-                                    tracing::debug!(?error, "HTTP connection closed with protocol error");
+                        let Some(tls) = tls else {
+                            serve_connection(&builder, stream, app, shutdown).await;
+                            return;
+                        };
+                        // The handshake runs inside the connection task so a slow peer
+                        // cannot stall the accept loop.
+                        let handshake = tokio::time::timeout(
+                            tls.handshake_timeout,
+                            tls.acceptor.accept(stream),
+                        );
+                        let stream = tokio::select! {
+                            result = handshake => match result {
+                                Ok(Ok(stream)) => stream,
+                                Ok(Err(error)) => {
+                                    tracing::debug!(?error, "TLS handshake failed");
+                                    return;
                                 }
-                            }
-                            _ = shutdown.recv() => {
-                                connection.as_mut().graceful_shutdown();
-                                if let Err(error) = connection.await {
-                                    tracing::debug!(?error, "HTTP graceful connection drain failed");
+                                Err(_) => {
+                                    tracing::debug!("TLS handshake deadline elapsed");
+                                    return;
                                 }
-                            }
-                        }
+                            },
+                            _ = shutdown.recv() => return,
+                        };
+                        serve_connection(&builder, stream, app, shutdown).await;
                     });
                 }
                 Err(error) => match accept_failures.record_failure(error.kind()) {
@@ -220,6 +233,31 @@ async fn run_inner(
     }
 }
 
+async fn serve_connection<I>(
+    builder: &Builder<TransportExecutor>,
+    stream: I,
+    app: HttpApp,
+    mut shutdown: broadcast::Receiver<()>,
+) where
+    I: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), app);
+    tokio::pin!(connection);
+    tokio::select! {
+        result = &mut connection => {
+            if let Err(error) = result {
+                tracing::debug!(?error, "HTTP connection closed with protocol error");
+            }
+        }
+        _ = shutdown.recv() => {
+            connection.as_mut().graceful_shutdown();
+            if let Err(error) = connection.await {
+                tracing::debug!(?error, "HTTP graceful connection drain failed");
+            }
+        }
+    }
+}
+
 async fn drain_connection_tasks(tasks: &mut JoinSet<()>) {
     while let Some(result) = tasks.join_next().await {
         if let Err(error) = result {
@@ -295,7 +333,7 @@ mod tests {
     use super::*;
     use crate::{
         runtime::budget::ByteBudget,
-        wire::{GuardedBody, GuardedChunk},
+        wire::{GuardedBody, GuardedChunk, StreamAborted},
     };
     use bytes::Bytes;
     use http::{Request, Response};
@@ -333,7 +371,7 @@ mod tests {
 
     impl Body for DropNotifyingBody {
         type Data = GuardedChunk;
-        type Error = Infallible;
+        type Error = StreamAborted;
 
         fn poll_frame(
             mut self: Pin<&mut Self>,
@@ -360,6 +398,7 @@ mod tests {
             http2_max_concurrent_streams: 1,
             http2_keep_alive_interval: None,
             http2_keep_alive_timeout: Duration::from_secs(1),
+            tls: None,
         }
     }
 
@@ -1,9 +1,9 @@
 use crate::{
-    Arguments, Call, Context, Error, ErrorCategory, Interceptor, InterceptorFuture,
-    InterceptorResult, Response, interceptor::erase_interceptor,
+    Arguments, Body, BodyStream, Call, Context, Error, ErrorCategory, Interceptor,
+    InterceptorFuture, InterceptorResult, Response, ResponseStream, interceptor::erase_interceptor,
 };
 use fusen_contract::{MethodId, ServiceDescriptor};
-use futures_util::FutureExt;
+use futures_util::{FutureExt, StreamExt};
 use serde::Serialize;
 use std::{panic::AssertUnwindSafe, sync::Arc};
 
@@ -56,6 +56,12 @@ impl ServerInvocation {
         crate::interface::decode_argument(value, text_encoded)
     }
 
+    /// Takes the streamed request body for a `#[param(body_stream)]` parameter.
+    #[doc(hidden)]
+    pub fn take_body_stream(&mut self) -> Result<BodyStream, Error> {
+        self.context.take_body_stream()
+    }
+
     /// Rejects arguments that are absent from the generated method schema.
     #[doc(hidden)]
     pub fn finish_arguments(&self) -> Result<(), Error> {
@@ -79,6 +85,28 @@ impl ServerInvocation {
         encoded.set_attempts(attempts);
         Ok(encoded)
     }
+
+    /// Encodes a streaming handler response, serializing each item under its own reservation.
+    #[doc(hidden)]
+    pub fn encode_stream_response<T: Serialize + 'static>(
+        self,
+        response: Response<ResponseStream<T>>,
+    ) -> InterceptorResult {
+        let (items, status, headers, extensions, attempts) = response.into_parts();
+        let limit = self.max_response_body;
+        let budget = self.response_budget;
+        let frames = items.into_inner().map(move |item| {
+            item.and_then(|value| Body::encode_with_budget(&value, limit, 0, &budget))
+                .map(Body::into_frame)
+        });
+        let mut encoded = Response::new(Body::from_frames(Box::pin(frames)));
+        encoded.mark_declared_serialize_schema_origin(self.context.method());
+        encoded.set_status(status)?;
+        *encoded.headers_mut() = headers;
+        *encoded.extensions_mut() = extensions;
+        encoded.set_attempts(attempts);
+        Ok(encoded)
+    }
 }
 
 /// Creates the stable dispatch error for an unknown declaration-order method ID.
@@ -0,0 +1,207 @@
+use crate::{Error, ErrorCategory, runtime::budget::BytePermit};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt, stream};
+use std::{
+    fmt,
+    ops::Deref,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as TaskContext, Poll},
+};
+
+/// Typed items of a server-streaming response.
+///
+/// Interface methods declare `Result<Response<ResponseStream<T>>, Error>` to send each `T` as one
+/// NDJSON line or Server-Sent Event. Servers serialize items as the stream is polled and clients
+/// decode them as frames arrive, so the response byte limit and budget apply to each item rather
+/// than to the whole body. An `Err` item ends the stream.
+pub struct ResponseStream<T> {
+    items: Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>,
+}
+
+impl<T> ResponseStream<T> {
+    /// Wraps a stream of items.
+    pub fn new<S>(items: S) -> Self
+    where
+        S: Stream<Item = Result<T, Error>> + Send + 'static,
+    {
+        Self {
+            items: Box::pin(items),
+        }
+    }
+
+    /// Creates a stream that yields the given items in order.
+    pub fn from_items<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = Result<T, Error>>,
+        I::IntoIter: Send + 'static,
+        T: 'static,
+    {
+        Self::new(stream::iter(items))
+    }
+
+    pub(crate) fn into_inner(self) -> Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>> {
+        self.items
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.items.as_mut().poll_next(context)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<T> fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ResponseStream")
+            .finish_non_exhaustive()
+    }
+}
+
+/// One chunk of a streamed request body.
+///
+/// Chunks read by a server stay reserved against the in-flight request byte budget until they
+/// are dropped or converted with [`BodyChunk::into_bytes`].
+pub struct BodyChunk {
+    bytes: Bytes,
+    _permit: Option<BytePermit>,
+}
+
+impl BodyChunk {
+    pub(crate) fn budgeted(bytes: Bytes, permit: BytePermit) -> Self {
+        Self {
+            bytes,
+            _permit: Some(permit),
+        }
+    }
+
+    /// Returns the chunk bytes.
+    pub const fn as_bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Releases the budget reservation and returns the chunk bytes.
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+impl From<Bytes> for BodyChunk {
+    fn from(bytes: Bytes) -> Self {
+        Self {
+            bytes,
+            _permit: None,
+        }
+    }
+}
+
+impl Deref for BodyChunk {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for BodyChunk {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("BodyChunk")
+            .field("length", &self.bytes.len())
+            .finish()
+    }
+}
+
+/// Raw request body of a client-streaming method.
+///
+/// Interface methods declare one `#[param(body_stream)] BodyStream` parameter to send the body
+/// with chunked transfer encoding instead of one buffered JSON document. The request byte limit
+/// and budget apply to each chunk rather than to the whole body; larger chunks are split. Only
+/// POST and PATCH methods may stream a body because it cannot be replayed. An `Err` chunk aborts
+/// the upload.
+pub struct BodyStream {
+    chunks: Pin<Box<dyn Stream<Item = Result<BodyChunk, Error>> + Send>>,
+}
+
+impl BodyStream {
+    /// Wraps a stream of body chunks.
+    pub fn new<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+    {
+        Self::from_body_chunks(chunks.map(|chunk| chunk.map(BodyChunk::from)))
+    }
+
+    /// Creates a body that yields the given chunks in order.
+    pub fn from_chunks<I>(chunks: I) -> Self
+    where
+        I: IntoIterator<Item = Bytes>,
+        I::IntoIter: Send + 'static,
+    {
+        Self::new(stream::iter(chunks).map(Ok))
+    }
+
+    pub(crate) fn from_body_chunks<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<BodyChunk, Error>> + Send + 'static,
+    {
+        Self {
+            chunks: Box::pin(chunks),
+        }
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<BodyChunk, Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.chunks.as_mut().poll_next(context)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("BodyStream").finish_non_exhaustive()
+    }
+}
+
+/// Shared take-once slot; clones of a `Call` or `Context` race for the single body stream.
+#[derive(Clone)]
+pub(crate) struct BodyStreamSlot(Arc<Mutex<Option<BodyStream>>>);
+
+impl BodyStreamSlot {
+    pub(crate) fn new(body: BodyStream) -> Self {
+        Self(Arc::new(Mutex::new(Some(body))))
+    }
+
+    pub(crate) fn take(&self) -> Result<BodyStream, Error> {
+        self.0
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take()
+            .ok_or_else(|| {
+                Error::framework(
+                    ErrorCategory::Internal,
+                    "body_stream_consumed",
+                    "a streamed request body can be consumed only once and cannot be replayed",
+                )
+            })
+    }
+}
@@ -0,0 +1,464 @@
+use super::UnsupportedCoding;
+use crate::runtime::budget::{BudgetedWriter, ByteBudget, BytePermit};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, header::ACCEPT_ENCODING};
+use std::{fmt, io::Write, sync::Arc};
+
+// RFC 9659 caps the zstd window a Content-Encoding decoder must accept at 8 MiB.
+const ZSTD_WINDOW_LOG_MAX: u32 = 23;
+const GZIP_LEVEL: u32 = 6;
+const ZSTD_LEVEL: i32 = 3;
+const BROTLI_QUALITY: u32 = 5;
+const BROTLI_WINDOW_LOG: u32 = 22;
+const DECODE_BUFFER_BYTES: usize = 8 * 1024;
+
+/// HTTP `Content-Encoding` supported by the built-in HTTP binding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentCoding {
+    /// `gzip`, RFC 9110 section 8.4.1.3.
+    Gzip,
+    /// `zstd`, RFC 8878, with the 8 MiB decoder window from RFC 9659.
+    Zstd,
+    /// `br`, RFC 7932.
+    Brotli,
+}
+
+impl ContentCoding {
+    /// Returns the registered content-coding token.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Brotli => "br",
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        [Self::Gzip, Self::Zstd, Self::Brotli]
+            .into_iter()
+            .find(|coding| token.eq_ignore_ascii_case(coding.as_str()))
+            .or_else(|| token.eq_ignore_ascii_case("x-gzip").then_some(Self::Gzip))
+    }
+}
+
+impl fmt::Display for ContentCoding {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Builds the `Accept-Encoding` value that advertises `codings` in preference order.
+pub(crate) fn accept_encoding(codings: &[ContentCoding]) -> HeaderValue {
+    let value = codings
+        .iter()
+        .map(|coding| coding.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&value).expect("content-coding tokens are valid header values")
+}
+
+/// Picks the first coding in `preference` that the request's `Accept-Encoding` allows.
+///
+/// Quality values only decide acceptability: the server's preference order ranks the accepted
+/// codings. A missing or unreadable header selects no coding.
+pub(crate) fn negotiate(
+    headers: &HeaderMap,
+    preference: &[ContentCoding],
+) -> Option<ContentCoding> {
+    let mut explicit = Vec::new();
+    let mut wildcard = None;
+    for value in headers.get_all(ACCEPT_ENCODING) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        for item in value.split(',') {
+            let mut parameters = item.split(';');
+            let token = parameters.next().unwrap_or_default().trim();
+            let accepted = parameters
+                .find_map(|parameter| {
+                    let (name, value) = parameter.split_once('=')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("q")
+                        .then(|| value.trim().parse::<f32>().ok())
+                })
+                .is_none_or(|quality| {
+                    quality.is_some_and(|quality| quality > 0.0 && quality <= 1.0)
+                });
+            if token == "*" {
+                wildcard = Some(accepted);
+            } else if let Some(coding) = ContentCoding::parse(token) {
+                explicit.push((coding, accepted));
+            }
+        }
+    }
+    preference.iter().copied().find(|coding| {
+        explicit
+            .iter()
+            .find(|(candidate, _)| candidate == coding)
+            .map(|(_, accepted)| *accepted)
+            .or(wildcard)
+            .unwrap_or(false)
+    })
+}
+
+/// Reads the single coding applied to a body; `identity` and an absent header mean none.
+pub(crate) fn content_coding(
+    headers: &HeaderMap,
+) -> Result<Option<ContentCoding>, UnsupportedCoding> {
+    let mut values = headers.get_all(http::header::CONTENT_ENCODING).iter();
+    let Some(value) = values.next() else {
+        return Ok(None);
+    };
+    if values.next().is_some() {
+        return Err(UnsupportedCoding);
+    }
+    let token = value.to_str().map_err(|_| UnsupportedCoding)?.trim();
+    if token.eq_ignore_ascii_case("identity") {
+        return Ok(None);
+    }
+    ContentCoding::parse(token)
+        .map(Some)
+        .ok_or(UnsupportedCoding)
+}
+
+/// Compresses `body`, reserving the output against `budget`.
+///
+/// Returns `None` when the encoding would not be smaller than `body` or the budget cannot hold
+/// it; the caller then sends the body unchanged.
+pub(crate) fn compress(
+    coding: ContentCoding,
+    body: &[u8],
+    budget: &Arc<ByteBudget>,
+) -> Option<(Bytes, Arc<BytePermit>)> {
+    let limit = body.len().checked_sub(1)?;
+    let writer = BudgetedWriter::new(limit, budget, 0).ok()?;
+    let writer = match coding {
+        ContentCoding::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(writer, flate2::Compression::new(GZIP_LEVEL));
+            encoder.write_all(body).ok()?;
+            encoder.finish().ok()?
+        }
+        ContentCoding::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, ZSTD_LEVEL).ok()?;
+            encoder.set_pledged_src_size(Some(body.len() as u64)).ok()?;
+            encoder.write_all(body).ok()?;
+            encoder.finish().ok()?
+        }
+        ContentCoding::Brotli => {
+            let mut encoder = brotli::CompressorWriter::new(
+                writer,
+                DECODE_BUFFER_BYTES,
+                BROTLI_QUALITY,
+                BROTLI_WINDOW_LOG,
+            );
+            encoder.write_all(body).ok()?;
+            encoder.into_inner()
+        }
+    };
+    // Brotli finishes its stream while unwrapping and drops write errors, so check the writer.
+    writer.failure().is_none().then(|| writer.into_parts())
+}
+
+/// Why a coded body could not be decoded within its limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DecodeFailure {
+    TooLarge,
+    BudgetExhausted,
+    Corrupt,
+}
+
+/// Decodes a coded body as its chunks arrive.
+///
+/// Every decoded byte is reserved against the budget before it is buffered, and decoding stops
+/// as soon as the output would exceed `max_body`, so a small coded body cannot expand past the
+/// limits that apply to an uncoded one. The coded chunks themselves are never buffered.
+pub(crate) struct Decoder {
+    coding: ContentCoding,
+    // The sink waits here until the first coded byte arrives; an empty body decodes to nothing.
+    pending: Option<DecodedSink>,
+    inner: Option<DecoderInner>,
+}
+
+enum DecoderInner {
+    Gzip(Box<flate2::write::MultiGzDecoder<DecodedSink>>),
+    Zstd(Box<zstd::stream::zio::Writer<DecodedSink, zstd::stream::raw::Decoder<'static>>>),
+    Brotli(Box<brotli::DecompressorWriter<DecodedSink>>),
+}
+
+impl Decoder {
+    pub(crate) fn new(
+        coding: ContentCoding,
+        max_body: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> Result<Self, DecodeFailure> {
+        let permit = budget
+            .try_reserve(0)
+            .ok_or(DecodeFailure::BudgetExhausted)?;
+        Ok(Self {
+            coding,
+            pending: Some(DecodedSink {
+                bytes: Vec::new(),
+                max_body,
+                permit,
+                failure: None,
+            }),
+            inner: None,
+        })
+    }
+
+    /// Feeds the next coded chunk through the decoder.
+    pub(crate) fn write(&mut self, chunk: &[u8]) -> Result<(), DecodeFailure> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        if let Some(sink) = self.pending.take() {
+            self.inner = Some(DecoderInner::new(self.coding, sink)?);
+        }
+        let inner = self
+            .inner
+            .as_mut()
+            .expect("a started decoder owns its sink");
+        let result = match inner {
+            DecoderInner::Gzip(decoder) => decoder.write_all(chunk),
+            DecoderInner::Zstd(decoder) => decoder.write_all(chunk),
+            DecoderInner::Brotli(decoder) => decoder.write_all(chunk),
+        };
+        result.map_err(|_| inner.sink().failure.unwrap_or(DecodeFailure::Corrupt))
+    }
+
+    /// Completes the coded stream and returns the decoded body with its reservation.
+    pub(crate) fn finish(self) -> Result<(Bytes, BytePermit), DecodeFailure> {
+        let (sink, complete) = match (self.pending, self.inner) {
+            (Some(sink), _) => (sink, true),
+            (None, Some(DecoderInner::Gzip(decoder))) => {
+                let mut decoder = *decoder;
+                let complete = decoder.try_finish().is_ok();
+                (
+                    decoder.finish().map_err(|_| DecodeFailure::Corrupt)?,
+                    complete,
+                )
+            }
+            (None, Some(DecoderInner::Zstd(decoder))) => {
+                let mut decoder = *decoder;
+                let complete = decoder.finish().is_ok();
+                (decoder.into_inner().0, complete)
+            }
+            (None, Some(DecoderInner::Brotli(decoder))) => match decoder.into_inner() {
+                Ok(sink) => (sink, true),
+                Err(sink) => (sink, false),
+            },
+            (None, None) => unreachable!("a decoder always owns its sink"),
+        };
+        if let Some(failure) = sink.failure {
+            return Err(failure);
+        }
+        if !complete {
+            return Err(DecodeFailure::Corrupt);
+        }
+        Ok((Bytes::from(sink.bytes), sink.permit))
+    }
+}
+
+impl DecoderInner {
+    fn new(coding: ContentCoding, sink: DecodedSink) -> Result<Self, DecodeFailure> {
+        Ok(match coding {
+            ContentCoding::Gzip => Self::Gzip(Box::new(flate2::write::MultiGzDecoder::new(sink))),
+            ContentCoding::Zstd => {
+                let mut decoder =
+                    zstd::stream::raw::Decoder::new().map_err(|_| DecodeFailure::Corrupt)?;
+                decoder
+                    .set_parameter(zstd::stream::raw::DParameter::WindowLogMax(
+                        ZSTD_WINDOW_LOG_MAX,
+                    ))
+                    .map_err(|_| DecodeFailure::Corrupt)?;
+                Self::Zstd(Box::new(zstd::stream::zio::Writer::new(sink, decoder)))
+            }
+            ContentCoding::Brotli => Self::Brotli(Box::new(brotli::DecompressorWriter::new(
+                sink,
+                DECODE_BUFFER_BYTES,
+            ))),
+        })
+    }
+
+    fn sink(&self) -> &DecodedSink {
+        match self {
+            Self::Gzip(decoder) => decoder.get_ref(),
+            Self::Zstd(decoder) => decoder.writer(),
+            Self::Brotli(decoder) => decoder.get_ref(),
+        }
+    }
+}
+
+struct DecodedSink {
+    bytes: Vec<u8>,
+    max_body: usize,
+    permit: BytePermit,
+    failure: Option<DecodeFailure>,
+}
+
+impl Write for DecodedSink {
+    fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+        if self.failure.is_none()
+            && self
+                .bytes
+                .len()
+                .checked_add(buffer.len())
+                .is_none_or(|length| length > self.max_body)
+        {
+            self.failure = Some(DecodeFailure::TooLarge);
+        }
+        if self.failure.is_none() && !self.permit.grow(buffer.len()) {
+            self.failure = Some(DecodeFailure::BudgetExhausted);
+        }
+        if self.failure.is_some() {
+            return Err(std::io::Error::other("decoded body limit exceeded"));
+        }
+        self.bytes.extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ContentCoding; 3] = [
+        ContentCoding::Zstd,
+        ContentCoding::Brotli,
+        ContentCoding::Gzip,
+    ];
+
+    fn decompress(
+        coding: ContentCoding,
+        encoded: &[u8],
+        max_body: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> Result<(Bytes, BytePermit), DecodeFailure> {
+        let mut decoder = Decoder::new(coding, max_body, budget)?;
+        // Odd-sized chunks split frames and headers the way a network would.
+        for chunk in encoded.chunks(997) {
+            decoder.write(chunk)?;
+        }
+        decoder.finish()
+    }
+
+    fn headers(value: &'static str) -> HeaderMap {
+        HeaderMap::from_iter([(ACCEPT_ENCODING, HeaderValue::from_static(value))])
+    }
+
+    #[test]
+    fn negotiation_follows_server_preference_among_accepted_codings() {
+        assert_eq!(negotiate(&HeaderMap::new(), &ALL), None);
+        assert_eq!(
+            negotiate(&headers("gzip, br;q=0.5"), &ALL),
+            Some(ContentCoding::Brotli)
+        );
+        assert_eq!(
+            negotiate(&headers("zstd;q=0, GZIP"), &ALL),
+            Some(ContentCoding::Gzip)
+        );
+        assert_eq!(
+            negotiate(&headers("*;q=0.1, br;q=0"), &ALL),
+            Some(ContentCoding::Zstd)
+        );
+        assert_eq!(negotiate(&headers("*;q=0"), &ALL), None);
+        assert_eq!(negotiate(&headers("gzip;q=2"), &ALL), None);
+        assert_eq!(
+            negotiate(&headers("x-gzip"), &[ContentCoding::Gzip]),
+            Some(ContentCoding::Gzip)
+        );
+        assert_eq!(negotiate(&headers("identity, deflate"), &ALL), None);
+    }
+
+    #[test]
+    fn content_coding_accepts_one_known_token() {
+        let coded = |value| {
+            content_coding(&HeaderMap::from_iter([(
+                http::header::CONTENT_ENCODING,
+                HeaderValue::from_static(value),
+            )]))
+        };
+        assert_eq!(content_coding(&HeaderMap::new()).unwrap(), None);
+        assert_eq!(coded("identity").unwrap(), None);
+        assert_eq!(coded(" br ").unwrap(), Some(ContentCoding::Brotli));
+        assert!(coded("deflate").is_err());
+        assert!(coded("gzip, br").is_err());
+    }
+
+    #[test]
+    fn every_coding_round_trips_and_is_budgeted() {
+        let body = "fusen ".repeat(4096).into_bytes();
+        for coding in ALL {
+            let budget = ByteBudget::new(body.len() * 2);
+            let (encoded, permit) = compress(coding, &body, &budget).unwrap();
+            assert!(
+                encoded.len() < body.len(),
+                "{coding} must shrink repetitive text"
+            );
+            assert_eq!(permit.bytes(), encoded.len());
+            drop(permit);
+            let (decoded, permit) = decompress(coding, &encoded, body.len(), &budget).unwrap();
+            assert_eq!(decoded, body);
+            assert_eq!(budget.used(), body.len());
+            drop(permit);
+            assert_eq!(budget.used(), 0);
+        }
+    }
+
+    #[test]
+    fn empty_coded_bodies_decode_to_nothing() {
+        let budget = ByteBudget::new(1);
+        for coding in ALL {
+            let (decoded, _permit) = decompress(coding, b"", 16, &budget).unwrap();
+            assert!(decoded.is_empty());
+        }
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn incompressible_bodies_are_left_unchanged() {
+        let budget = ByteBudget::new(1024);
+        for coding in ALL {
+            assert!(compress(coding, b"x", &budget).is_none());
+            assert!(compress(coding, b"", &budget).is_none());
+        }
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn decoding_stops_at_the_body_limit_and_the_budget() {
+        let bomb = vec![0; 1024 * 1024];
+        for coding in ALL {
+            let budget = ByteBudget::new(bomb.len() * 2);
+            let (encoded, permit) = compress(coding, &bomb, &budget).unwrap();
+            drop(permit);
+            assert_eq!(
+                decompress(coding, &encoded, 64 * 1024, &budget).unwrap_err(),
+                DecodeFailure::TooLarge
+            );
+            let small = ByteBudget::new(64 * 1024);
+            assert_eq!(
+                decompress(coding, &encoded, bomb.len(), &small).unwrap_err(),
+                DecodeFailure::BudgetExhausted
+            );
+            assert_eq!(budget.used(), 0);
+            assert_eq!(small.used(), 0);
+            assert_eq!(
+                decompress(coding, b"not a coded body", bomb.len(), &budget).unwrap_err(),
+                DecodeFailure::Corrupt
+            );
+            assert_eq!(
+                decompress(coding, &encoded[..encoded.len() / 2], bomb.len(), &budget).unwrap_err(),
+                DecodeFailure::Corrupt,
+                "{coding} must reject a truncated body"
+            );
+            assert_eq!(budget.used(), 0);
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use super::{
+    StreamFraming, codec_panic, is_json_mime, response_budget_exhausted, response_too_large,
+};
+use crate::{
+    BufferedRequest, Error, ErrorCategory, RequestDecoder, ResponseEncoder, ResponseEncoding,
+    runtime::budget::{BudgetedWriteFailure, BudgetedWriter, ByteBudget, BytePermit},
+};
+use bytes::Bytes;
+use fusen_contract::{HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId, MethodDescriptor};
+use http::{HeaderMap, HeaderValue, StatusCode, header::ACCEPT};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    io::Cursor,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Arc,
+};
+
+/// Media type of `http-msgpack-v1` request bodies and buffered success responses.
+pub(crate) const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+// serde_json stops at 128 levels; MessagePack documents get the same nesting allowance.
+const MAX_DEPTH: usize = 128;
+
+/// Wire representation of a buffered invocation body.
+///
+/// Arguments and results are JSON values inside the runtime in both cases; MessagePack is only
+/// the representation on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum BodyFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl BodyFormat {
+    pub(crate) fn encode(self, value: &Value) -> Result<Bytes, Error> {
+        match self {
+            Self::Json => serde_json::to_vec(value)
+                .map(Bytes::from)
+                .map_err(|error| Error::internal("failed to encode JSON request body", error)),
+            Self::MessagePack => rmp_serde::to_vec(value).map(Bytes::from).map_err(|error| {
+                Error::internal("failed to encode MessagePack request body", error)
+            }),
+        }
+    }
+}
+
+/// Non-JSON body representation that the server selects by `Content-Type` and `Accept`.
+#[derive(Clone)]
+pub(crate) struct ServerBinding {
+    id: HttpBindingId,
+    media_type: String,
+    codec: ServerBindingCodec,
+}
+
+impl std::fmt::Debug for ServerBinding {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("ServerBinding")
+            .field("id", &self.id)
+            .field("media_type", &self.media_type)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+enum ServerBindingCodec {
+    // Transcoded in-tree so the output is reserved against the response budget as it grows.
+    MessagePack,
+    Registered {
+        request_decoder: Arc<dyn RequestDecoder>,
+        response_encoder: Arc<dyn ResponseEncoder>,
+    },
+}
+
+impl ServerBinding {
+    pub(crate) fn msgpack() -> Self {
+        Self {
+            id: HttpBindingId::new(HTTP_MSGPACK_V1).expect("built-in binding ID is valid"),
+            media_type: MSGPACK_CONTENT_TYPE.to_owned(),
+            codec: ServerBindingCodec::MessagePack,
+        }
+    }
+
+    /// Creates an application binding after checking that its media type is unambiguous.
+    pub(crate) fn registered(
+        id: HttpBindingId,
+        media_type: &str,
+        request_decoder: Arc<dyn RequestDecoder>,
+        response_encoder: Arc<dyn ResponseEncoder>,
+    ) -> Result<Self, String> {
+        if [HTTP_JSON_V1, HTTP_MSGPACK_V1].contains(&id.as_str()) {
+            return Err(format!("HTTP binding {id} is built in"));
+        }
+        let parsed = media_type
+            .parse::<mime::Mime>()
+            .map_err(|_| format!("HTTP binding {id} has an invalid media type"))?;
+        if parsed.type_() == mime::STAR || parsed.subtype() == mime::STAR {
+            return Err(format!("HTTP binding {id} media type must not be a range"));
+        }
+        if is_json_mime(&parsed)
+            || parsed.essence_str() == MSGPACK_CONTENT_TYPE
+            || StreamFraming::for_media_type(parsed.essence_str()).is_some()
+        {
+            return Err(format!(
+                "HTTP binding {id} media type {} is reserved by a built-in representation",
+                parsed.essence_str()
+            ));
+        }
+        Ok(Self {
+            id,
+            media_type: parsed.essence_str().to_owned(),
+            codec: ServerBindingCodec::Registered {
+                request_decoder,
+                response_encoder,
+            },
+        })
+    }
+
+    pub(crate) const fn id(&self) -> &HttpBindingId {
+        &self.id
+    }
+
+    /// Returns the lowercase media type essence that selects this binding.
+    pub(crate) fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    pub(crate) fn matches(&self, value: &HeaderValue) -> bool {
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| value.parse::<mime::Mime>().ok())
+            .is_some_and(|value| value.essence_str() == self.media_type)
+    }
+
+    /// Decodes one buffered request body into the JSON document body fields are bound from.
+    pub(crate) fn decode(
+        &self,
+        method: &'static MethodDescriptor,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<Value, Error> {
+        match &self.codec {
+            ServerBindingCodec::MessagePack => decode_msgpack(&body).ok_or_else(|| {
+                Error::framework(
+                    ErrorCategory::InvalidArgument,
+                    "invalid_msgpack",
+                    "HTTP request body is not a MessagePack document with a JSON representation",
+                )
+            }),
+            ServerBindingCodec::Registered {
+                request_decoder, ..
+            } => catch_unwind(AssertUnwindSafe(|| {
+                request_decoder.decode(method, BufferedRequest::new(headers, body))
+            }))
+            .map_err(|_| codec_panic("request decoder"))?,
+        }
+    }
+
+    /// Re-encodes a buffered JSON result, returning any permit already covering the output.
+    pub(crate) fn encode(
+        &self,
+        method: &'static MethodDescriptor,
+        status: StatusCode,
+        headers: &HeaderMap,
+        json: &[u8],
+        max_body: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> Result<(Bytes, Option<Arc<BytePermit>>), Error> {
+        match &self.codec {
+            ServerBindingCodec::MessagePack => json_to_msgpack(json, max_body, budget)
+                .map(|(encoded, permit)| (encoded, Some(permit))),
+            ServerBindingCodec::Registered {
+                response_encoder, ..
+            } => {
+                let encoded = catch_unwind(AssertUnwindSafe(|| {
+                    response_encoder.encode(
+                        method,
+                        ResponseEncoding::new(status, headers, json, max_body),
+                    )
+                }))
+                .map_err(|_| codec_panic("response encoder"))??;
+                if encoded.len() > max_body {
+                    return Err(response_too_large());
+                }
+                Ok((encoded, None))
+            }
+        }
+    }
+}
+
+pub(crate) fn is_msgpack_media_type(value: &HeaderValue) -> bool {
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .is_some_and(|value| value.essence_str() == MSGPACK_CONTENT_TYPE)
+}
+
+/// Returns whether `Accept` lists the media type `essence` itself with a non-zero weight.
+///
+/// Wildcard ranges keep selecting JSON, so only callers that name a binding receive it.
+pub(crate) fn accepts_media_type(headers: &HeaderMap, essence: &str) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| range.trim().parse::<mime::Mime>().ok())
+        .any(|range| {
+            range.essence_str() == essence
+                && range
+                    .get_param("q")
+                    .is_none_or(|weight| weight.as_str().parse::<f32>().is_ok_and(|q| q > 0.0))
+        })
+}
+
+/// Decodes one MessagePack document into the JSON data model.
+///
+/// Binary and extension values, non-string map keys and trailing bytes have no JSON equivalent
+/// and are rejected rather than coerced.
+fn decode_msgpack(bytes: &[u8]) -> Option<Value> {
+    let mut deserializer = rmp_serde::Deserializer::new(Cursor::new(bytes));
+    deserializer.set_max_depth(MAX_DEPTH);
+    let value = Value::deserialize(&mut deserializer).ok()?;
+    (deserializer.position() == bytes.len() as u64).then_some(value)
+}
+
+/// Re-encodes a MessagePack response body as the JSON bytes held by [`crate::Body`].
+pub(crate) fn msgpack_to_json(bytes: &[u8]) -> Option<Bytes> {
+    serde_json::to_vec(&decode_msgpack(bytes)?)
+        .ok()
+        .map(Bytes::from)
+}
+
+/// Re-encodes a buffered JSON result as MessagePack, reserving the output against `budget`.
+pub(crate) fn json_to_msgpack(
+    json: &[u8],
+    max_body: usize,
+    budget: &Arc<ByteBudget>,
+) -> Result<(Bytes, Arc<BytePermit>), Error> {
+    let mut writer =
+        BudgetedWriter::new(max_body, budget, 0).map_err(|_| response_budget_exhausted())?;
+    let mut deserializer = serde_json::Deserializer::from_slice(json);
+    let transcoded = serde_transcode::transcode(
+        &mut deserializer,
+        &mut rmp_serde::Serializer::new(&mut writer),
+    );
+    match writer.failure() {
+        Some(BudgetedWriteFailure::LimitExceeded) => return Err(response_too_large()),
+        Some(BudgetedWriteFailure::BudgetExhausted) => return Err(response_budget_exhausted()),
+        None => {}
+    }
+    transcoded
+        .ok()
+        .and_then(|()| deserializer.end().ok())
+        .ok_or_else(|| {
+            Error::framework(
+                ErrorCategory::Internal,
+                "invalid_response_body",
+                "invocation result is not valid JSON",
+            )
+        })?;
+    Ok(writer.into_parts())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_selects_msgpack_only_with_a_positive_weight() {
+        for (accept, expected) in [
+            ("application/msgpack", true),
+            ("application/json, application/msgpack;q=0.1", true),
+            ("application/msgpack;q=0", false),
+            ("application/json", false),
+            ("*/*", false),
+        ] {
+            let headers = HeaderMap::from_iter([(ACCEPT, HeaderValue::from_static(accept))]);
+            assert_eq!(
+                accepts_media_type(&headers, MSGPACK_CONTENT_TYPE),
+                expected,
+                "{accept}"
+            );
+        }
+    }
+
+    #[test]
+    fn msgpack_round_trips_the_json_data_model() {
+        let value = serde_json::json!({
+            "id": u64::MAX,
+            "delta": -7,
+            "ratio": 0.25,
+            "tags": ["a", null, true],
+            "nested": {"empty": {}},
+        });
+        let packed = BodyFormat::MessagePack.encode(&value).unwrap();
+        assert_eq!(decode_msgpack(&packed), Some(value.clone()));
+        assert_eq!(
+            serde_json::from_slice::<Value>(&msgpack_to_json(&packed).unwrap()).unwrap(),
+            value
+        );
+
+        let budget = ByteBudget::new(1024);
+        let json = serde_json::to_vec(&value).unwrap();
+        let (transcoded, permit) = json_to_msgpack(&json, 1024, &budget).unwrap();
+        assert_eq!(transcoded, packed);
+        assert_eq!(permit.bytes(), packed.len());
+    }
+
+    #[test]
+    fn msgpack_decoding_rejects_values_without_a_json_form() {
+        let mut trailing = rmp_serde::to_vec(&1).unwrap();
+        trailing.push(0xc0);
+        // bin 8, an integer map key, and an array nested past the depth limit.
+        let mut deep = vec![0x91; MAX_DEPTH + 1];
+        deep.push(0xc0);
+        for bytes in [
+            trailing,
+            vec![0xc4, 0x01, 0x00],
+            vec![0x81, 0x01, 0xc0],
+            deep,
+        ] {
+            assert_eq!(decode_msgpack(&bytes), None, "{bytes:x?}");
+        }
+        let mut shallow = vec![0x91; MAX_DEPTH - 1];
+        shallow.push(0xc0);
+        assert!(decode_msgpack(&shallow).is_some());
+    }
+
+    #[test]
+    fn transcoding_respects_the_response_limit_and_budget() {
+        let json = serde_json::to_vec(&"x".repeat(64)).unwrap();
+        let error = json_to_msgpack(&json, 32, &ByteBudget::new(1024)).unwrap_err();
+        assert_eq!(error.code().as_str(), "response_too_large");
+        let error = json_to_msgpack(&json, 1024, &ByteBudget::new(32)).unwrap_err();
+        assert_eq!(error.code().as_str(), "response_byte_budget_exhausted");
+    }
+}
@@ -8,22 +8,22 @@ use crate::{
 };
 use bytes::{Buf, Bytes};
 use fusen_contract::{
-    HttpParameterCardinality, HttpParameterSource, MethodDescriptor, ServiceDescriptor,
-    ServiceEndpoint,
+    HttpParameterCardinality, HttpParameterSource, MethodDescriptor, MethodPriority,
+    ServiceDescriptor, ServiceEndpoint,
 };
 use http::{
     HeaderMap, HeaderName, HeaderValue, Method, Request, Response as HttpResponse, StatusCode, Uri,
     Version,
     header::{
-        ACCEPT, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, HOST, TE, TRAILER,
-        TRANSFER_ENCODING, UPGRADE,
+        ACCEPT, CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, HOST, TE,
+        TRAILER, TRANSFER_ENCODING, UPGRADE,
     },
 };
 use hyper::body::{Body as HttpBody, Frame, Incoming};
 use serde_json::{Map, Value};
 use std::{
+    any::Any,
     collections::HashSet,
-    convert::Infallible,
     panic::{AssertUnwindSafe, catch_unwind},
     pin::Pin,
     sync::Arc,
@@ -32,13 +32,28 @@ use std::{
 };
 use uuid::Uuid;
 
+#[cfg(feature = "compression")]
+mod compression;
+mod format;
 pub(crate) mod problem;
+mod stream;
+
+#[cfg(feature = "compression")]
+pub use compression::ContentCoding;
+#[cfg(feature = "compression")]
+use compression::{DecodeFailure, Decoder, compress, content_coding};
+#[cfg(feature = "compression")]
+pub(crate) use compression::{accept_encoding, negotiate};
+pub(crate) use format::{BodyFormat, ServerBinding, accepts_media_type, is_msgpack_media_type};
+use format::{MSGPACK_CONTENT_TYPE, msgpack_to_json};
 
 #[cfg(test)]
 #[allow(unused_imports)]
 pub(crate) use problem::ProblemDetails;
 use problem::{decode_head_error, decode_problem, validate_response_request_id};
 pub(crate) use problem::{encode_problem, remote_protocol_error};
+use stream::{ChunkWriter, FrameWriter, decode_frames, read_chunks};
+pub(crate) use stream::{StreamAborted, StreamFraming, UploadFailure};
 
 #[cfg(test)]
 pub(crate) const JSON_CONTENT_TYPE: &str = "application/json";
@@ -48,10 +63,37 @@ pub(crate) const TIMEOUT_MS: HeaderName = HeaderName::from_static("x-fusen-timeo
 pub(crate) const ATTEMPT: HeaderName = HeaderName::from_static("x-fusen-attempt");
 pub(crate) const SERVICE_GROUP: HeaderName = HeaderName::from_static("x-fusen-service-group");
 pub(crate) const SERVICE_VERSION: HeaderName = HeaderName::from_static("x-fusen-service-version");
+pub(crate) const PRIORITY: HeaderName = HeaderName::from_static("x-fusen-priority");
 const MAX_TIMEOUT_MS: u64 = 86_400_000;
 const EMERGENCY_PROBLEM_LIMIT: usize = 4 * 1024;
 const CHUNK_RESERVATION: usize = 4 * 1024;
 
+/// The body carries a `Content-Encoding` this binding cannot decode.
+#[derive(Debug)]
+pub(crate) struct UnsupportedCoding;
+
+/// Stands in for the codecs when the `compression` feature is off, so no body carries a coding.
+#[cfg(not(feature = "compression"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ContentCoding {}
+
+/// Accepts only an absent or `identity` `Content-Encoding` while no codec is compiled in.
+#[cfg(not(feature = "compression"))]
+fn content_coding(headers: &HeaderMap) -> Result<Option<ContentCoding>, UnsupportedCoding> {
+    let mut values = headers.get_all(CONTENT_ENCODING).iter();
+    match (values.next(), values.next()) {
+        (None, _) => Ok(None),
+        (Some(value), None)
+            if value
+                .to_str()
+                .is_ok_and(|token| token.trim().eq_ignore_ascii_case("identity")) =>
+        {
+            Ok(None)
+        }
+        _ => Err(UnsupportedCoding),
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RequestControl {
     pub request_id: String,
@@ -106,6 +148,25 @@ pub(crate) fn validate_attempt(attempt: u8, method_allows_retries: bool) -> Resu
     }
 }
 
+/// Returns the load-shedding class from `x-fusen-priority`, if the request sent one.
+pub(crate) fn parse_priority(headers: &HeaderMap) -> Result<Option<MethodPriority>, Error> {
+    one_header(headers, &PRIORITY)?
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(MethodPriority::parse)
+                .ok_or_else(|| {
+                    Error::framework(
+                        ErrorCategory::InvalidArgument,
+                        "invalid_priority",
+                        "x-fusen-priority must be low, normal, high, or critical",
+                    )
+                })
+        })
+        .transpose()
+}
+
 pub(crate) fn validate_request_id(value: &str) -> Result<(), Error> {
     if request_id_is_valid(value) {
         Ok(())
@@ -216,7 +277,7 @@ pub(crate) struct JsonCodec;
 
 impl RequestEncoder for JsonCodec {
     fn encode(&self, request: RequestEncoding<'_>) -> Result<EncodedRequest, Error> {
-        encode_json_request(request)
+        encode_http_request(request, BodyFormat::Json)
     }
 }
 
@@ -259,6 +320,61 @@ impl ErrorDecoder for JsonCodec {
     }
 }
 
+/// `http-msgpack-v1` codec; errors remain Problem Details JSON and are decoded by [`JsonCodec`].
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct MsgpackCodec;
+
+impl RequestEncoder for MsgpackCodec {
+    fn encode(&self, request: RequestEncoding<'_>) -> Result<EncodedRequest, Error> {
+        encode_http_request(request, BodyFormat::MessagePack)
+    }
+}
+
+impl ResponseDecoder for MsgpackCodec {
+    fn decode(
+        &self,
+        method: &'static MethodDescriptor,
+        response: BufferedResponse,
+    ) -> Result<Response<Body>, Error> {
+        if matches!(
+            response.status(),
+            StatusCode::NO_CONTENT | StatusCode::RESET_CONTENT
+        ) {
+            return ResponseDecoder::decode(&JsonCodec, method, response);
+        }
+        let mut values = response.headers().get_all(CONTENT_TYPE).iter();
+        let matches = values.next().is_some_and(is_msgpack_media_type);
+        if !matches || values.next().is_some() {
+            return Err(invalid_response_content_type(
+                MSGPACK_CONTENT_TYPE,
+                response.request_id(),
+                response.headers(),
+            ));
+        }
+        let request_id = response.request_id().to_owned();
+        let (status, _version, headers, body) = response.into_parts();
+        let body = msgpack_to_json(&body).ok_or_else(|| {
+            remote_protocol_error(
+                "invalid_response_body",
+                "remote response body is not a MessagePack document with a JSON representation",
+                &request_id,
+            )
+            .with_headers(response_headers_without_control(headers.clone()))
+        })?;
+        let mut decoded = Response::from_json_bytes(body);
+        decoded.mark_declared_deserialize_schema_origin(method);
+        *decoded.headers_mut() = headers;
+        decoded.set_status(status)?;
+        Ok(decoded)
+    }
+}
+
+impl ErrorDecoder for MsgpackCodec {
+    fn decode(&self, method: &'static MethodDescriptor, response: BufferedResponse) -> Error {
+        ErrorDecoder::decode(&JsonCodec, method, response)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RequestTemplate {
     pub method: Method,
@@ -269,6 +385,27 @@ pub(crate) struct RequestTemplate {
 }
 
 impl RequestTemplate {
+    /// Replaces a body of at least `min_bytes` with its `coding` encoding for every attempt.
+    ///
+    /// Bodies that an encoder already coded, or that do not shrink, are sent unchanged.
+    #[cfg(feature = "compression")]
+    pub(crate) fn compress(
+        &mut self,
+        coding: ContentCoding,
+        min_bytes: usize,
+        budget: &Arc<ByteBudget>,
+    ) {
+        if self.body.len() < min_bytes || self.headers.contains_key(CONTENT_ENCODING) {
+            return;
+        }
+        if let Some((body, permit)) = compress(coding, &self.body, budget) {
+            self.body = body;
+            self.budget_permit = permit;
+            self.headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static(coding.as_str()));
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn to_request(
         &self,
@@ -351,6 +488,13 @@ pub(crate) fn encode_request_template(
     if body.len() > max_body {
         return Err(request_too_large());
     }
+    if !body.is_empty() && has_body_stream(method) {
+        return Err(Error::framework(
+            ErrorCategory::Internal,
+            "invalid_encoded_request",
+            "request encoder produced a buffered body for a streamed request body",
+        ));
+    }
     let budget_permit = Arc::new(
         budget
             .try_reserve(body.len())
@@ -365,7 +509,10 @@ pub(crate) fn encode_request_template(
     })
 }
 
-fn encode_json_request(request: RequestEncoding<'_>) -> Result<EncodedRequest, Error> {
+fn encode_http_request(
+    request: RequestEncoding<'_>,
+    format: BodyFormat,
+) -> Result<EncodedRequest, Error> {
     let mapping = request.method().http_operation();
     let mut headers = request.headers().clone();
     reject_binding_owned_headers(&headers)?;
@@ -441,6 +588,8 @@ fn encode_json_request(request: RequestEncoding<'_>) -> Result<EncodedRequest, E
             HttpParameterSource::HeaderMap => {
                 append_header_map(&mut headers, &value, &explicit_headers)?
             }
+            // The transport sends the body stream after the template is encoded.
+            HttpParameterSource::BodyStream => {}
             _ => return Err(unsupported_http_parameter_source()),
         }
     }
@@ -463,17 +612,29 @@ fn encode_json_request(request: RequestEncoding<'_>) -> Result<EncodedRequest, E
         );
     }
     let body = body
-        .map(|value| {
-            serde_json::to_vec(&value)
-                .map(Bytes::from)
-                .map_err(|error| Error::internal("failed to encode JSON request body", error))
-        })
+        .map(|value| format.encode(&value))
         .transpose()?
         .unwrap_or_default();
-    if !body.is_empty() {
+    // Streamed bodies and stream responses keep their declared media types in every format.
+    let (consumes, produces) = match format {
+        BodyFormat::Json => (mapping.consumes(), mapping.produces()),
+        BodyFormat::MessagePack => (
+            if has_body_stream(request.method()) {
+                mapping.consumes()
+            } else {
+                MSGPACK_CONTENT_TYPE
+            },
+            if StreamFraming::for_media_type(mapping.produces()).is_some() {
+                mapping.produces()
+            } else {
+                MSGPACK_CONTENT_TYPE
+            },
+        ),
+    };
+    if !body.is_empty() || has_body_stream(request.method()) {
         headers.insert(
             CONTENT_TYPE,
-            HeaderValue::from_str(mapping.consumes()).map_err(|_| {
+            HeaderValue::from_str(consumes).map_err(|_| {
                 Error::framework(
                     ErrorCategory::InvalidArgument,
                     "invalid_consumes_media_type",
@@ -484,7 +645,7 @@ fn encode_json_request(request: RequestEncoding<'_>) -> Result<EncodedRequest, E
     }
     headers.insert(
         ACCEPT,
-        HeaderValue::from_str(mapping.produces()).map_err(|_| {
+        HeaderValue::from_str(produces).map_err(|_| {
             Error::framework(
                 ErrorCategory::InvalidArgument,
                 "invalid_produces_media_type",
@@ -729,6 +890,7 @@ fn reject_binding_owned_headers(headers: &HeaderMap) -> Result<(), Error> {
         &SERVICE_GROUP,
         &SERVICE_VERSION,
         &CONTENT_LENGTH,
+        &CONTENT_ENCODING,
         &HOST,
         &CONNECTION,
         &TE,
@@ -753,6 +915,7 @@ fn is_runtime_owned_header(name: &HeaderName) -> bool {
         &SERVICE_GROUP,
         &SERVICE_VERSION,
         &CONTENT_LENGTH,
+        &CONTENT_ENCODING,
         &HOST,
         &CONNECTION,
         &TE,
@@ -824,16 +987,46 @@ fn endpoint_uri(endpoint: &ServiceEndpoint, path_and_query: &str) -> Result<Uri,
         .map_err(|error| Error::internal("failed to construct canonical HTTP URI", error))
 }
 
+/// Buffered success representation other than JSON, chosen from the request's `Accept`.
+#[derive(Clone, Copy)]
+pub(crate) struct ResponseBinding<'a> {
+    pub binding: &'a ServerBinding,
+    pub method: &'static MethodDescriptor,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn encode_success(
     response: Response<Body>,
     produces: &str,
+    binding: Option<ResponseBinding<'_>>,
     suppress_body: bool,
     max_body: usize,
     budget: &Arc<ByteBudget>,
+    request_id: &str,
+    invocation_controls: bool,
 ) -> Result<HttpResponse<GuardedBody>, Error> {
-    let (status, headers, result, existing_permit) = response.into_wire_parts();
+    let (status, headers, body) = response.into_wire_parts();
     let suppress_body =
         suppress_body || matches!(status, StatusCode::NO_CONTENT | StatusCode::RESET_CONTENT);
+    let framing = StreamFraming::for_media_type(produces);
+    let frames = body.take_frames();
+    if frames.is_some() && framing.is_none() {
+        return Err(Error::framework(
+            ErrorCategory::Internal,
+            "undeclared_response_stream",
+            "streaming response requires a streaming produces media type",
+        ));
+    }
+    let (result, existing_permit) = body.into_parts();
+    let binding = binding.filter(|_| framing.is_none() && !suppress_body);
+    let (result, existing_permit) = match binding {
+        Some(ResponseBinding { binding, method }) => {
+            let (encoded, permit) =
+                binding.encode(method, status, &headers, &result, max_body, budget)?;
+            (encoded, permit.or(existing_permit))
+        }
+        None => (result, existing_permit),
+    };
     let total = if suppress_body { 0 } else { result.len() };
     if total > max_body {
         return Err(response_too_large());
@@ -852,19 +1045,28 @@ pub(crate) fn encode_success(
                 .ok_or_else(response_budget_exhausted)?,
         ),
     };
-    let body = GuardedBody::new(
-        if suppress_body { Bytes::new() } else { result },
-        Some(permit),
-    );
+    let body = match (framing, frames) {
+        (_, _) if suppress_body => GuardedBody::new(Bytes::new(), Some(permit)),
+        (Some(framing), Some(frames)) => GuardedBody::stream(FrameWriter::new(
+            frames,
+            framing,
+            request_id,
+            invocation_controls,
+        )),
+        // An interceptor may answer a streaming method with one buffered item.
+        (Some(framing), None) => GuardedBody::new(framing.frame(&result)?, Some(permit)),
+        (None, _) => GuardedBody::new(result, Some(permit)),
+    };
     let mut encoded = HttpResponse::builder()
         .status(status)
         .body(body)
         .map_err(|error| Error::internal("failed to build HTTP response", error))?;
     *encoded.headers_mut() = response_headers_without_control(headers);
     if !suppress_body {
+        let content_type = binding.map_or(produces, |binding| binding.binding.media_type());
         encoded.headers_mut().insert(
             CONTENT_TYPE,
-            HeaderValue::from_str(produces).map_err(|_| {
+            HeaderValue::from_str(content_type).map_err(|_| {
                 Error::framework(
                     ErrorCategory::Internal,
                     "invalid_produces_media_type",
@@ -893,13 +1095,13 @@ fn response_too_large() -> Error {
 }
 
 #[allow(clippy::too_many_arguments)]
-pub(crate) async fn decode_http_response(
+pub(crate) async fn decode_http_response<B: WireBody>(
     response_decoder: &dyn ResponseDecoder,
     error_decoder: &dyn ErrorDecoder,
     head: bool,
     method: &'static MethodDescriptor,
     expected_request_id: &str,
-    response: HttpResponse<Incoming>,
+    response: HttpResponse<B>,
     max_body: usize,
     budget: &std::sync::Arc<ByteBudget>,
     invocation_controls: bool,
@@ -939,6 +1141,19 @@ pub(crate) async fn decode_http_response(
         response.set_status(status)?;
         return Ok(response);
     }
+    if status.is_success()
+        && let Some(framing) = StreamFraming::for_media_type(method.http_operation().produces())
+    {
+        return decode_stream_response(
+            framing,
+            method,
+            expected_request_id,
+            response,
+            max_body,
+            budget,
+            invocation_controls,
+        );
+    }
     if status.is_success() && matches!(status, StatusCode::NO_CONTENT | StatusCode::RESET_CONTENT) {
         let (parts, body) = response.into_parts();
         drop(body);
@@ -966,18 +1181,37 @@ pub(crate) async fn decode_http_response(
     }
     let content_length = parse_response_content_length(response.headers(), expected_request_id)?;
     let (parts, body) = response.into_parts();
-    let decoder_headers = response_headers_for_decoder(parts.headers);
+    let mut decoder_headers = response_headers_for_decoder(parts.headers);
     let application_headers = response_headers_without_control(decoder_headers.clone());
-    let (body, permit) =
-        read_response_body(body, content_length, max_body, budget, expected_request_id)
-            .await
-            .map_err(|error| {
-                if error.origin() == ErrorOrigin::Remote {
-                    error.with_headers(application_headers.clone())
-                } else {
-                    error
-                }
-            })?;
+    let coding = content_coding(&decoder_headers).map_err(|_| {
+        remote_protocol_error(
+            "unsupported_content_encoding",
+            "response Content-Encoding is not supported",
+            expected_request_id,
+        )
+        .with_headers(application_headers.clone())
+    })?;
+    let (body, permit) = read_response_body(
+        body,
+        content_length,
+        coding,
+        max_body,
+        budget,
+        expected_request_id,
+    )
+    .await
+    .map_err(|error| {
+        if error.origin() == ErrorOrigin::Remote {
+            error.with_headers(application_headers.clone())
+        } else {
+            error
+        }
+    })?;
+    if coding.is_some() {
+        // Decoders see the body exactly as the server produced it before coding.
+        decoder_headers.remove(CONTENT_ENCODING);
+        decoder_headers.remove(CONTENT_LENGTH);
+    }
     let buffered = BufferedResponse::new(
         status,
         version,
@@ -1005,6 +1239,58 @@ pub(crate) async fn decode_http_response(
     Ok(decoded)
 }
 
+// Streaming successes bypass the buffered response decoder; items are decoded as frames arrive.
+fn decode_stream_response<B: WireBody>(
+    framing: StreamFraming,
+    method: &'static MethodDescriptor,
+    expected_request_id: &str,
+    response: HttpResponse<B>,
+    max_body: usize,
+    budget: &Arc<ByteBudget>,
+    invocation_controls: bool,
+) -> Result<Response<Body>, Error> {
+    let status = response.status();
+    let (parts, body) = response.into_parts();
+    let frames = if matches!(status, StatusCode::NO_CONTENT | StatusCode::RESET_CONTENT) {
+        drop(body);
+        Box::pin(futures_util::stream::empty()) as crate::BodyFrames
+    } else {
+        let mut values = parts.headers.get_all(CONTENT_TYPE).iter();
+        let matches = values.next().is_some_and(|value| {
+            value.to_str().ok().and_then(StreamFraming::for_media_type) == Some(framing)
+        });
+        if !matches || values.next().is_some() {
+            return Err(remote_protocol_error(
+                "invalid_content_type",
+                "response Content-Type does not match the declared stream media type",
+                expected_request_id,
+            )
+            .with_headers(response_headers_without_control(parts.headers)));
+        }
+        if !matches!(content_coding(&parts.headers), Ok(None)) {
+            return Err(remote_protocol_error(
+                "unsupported_content_encoding",
+                "streaming responses cannot carry a Content-Encoding",
+                expected_request_id,
+            )
+            .with_headers(response_headers_without_control(parts.headers)));
+        }
+        decode_frames(
+            body,
+            framing,
+            max_body,
+            budget,
+            expected_request_id,
+            invocation_controls,
+        )?
+    };
+    let mut decoded = Response::new(Body::from_frames(frames));
+    decoded.mark_declared_deserialize_schema_origin(method);
+    *decoded.headers_mut() = response_headers_without_control(parts.headers);
+    decoded.set_status(status)?;
+    Ok(decoded)
+}
+
 fn hold_decoded_response_budget(
     response: &mut Response<Body>,
     permit: BytePermit,
@@ -1047,6 +1333,7 @@ fn response_headers_for_decoder(mut headers: HeaderMap) -> HeaderMap {
 fn response_headers_without_control(mut headers: HeaderMap) -> HeaderMap {
     for name in [
         CONNECTION,
+        CONTENT_ENCODING,
         CONTENT_TYPE,
         CONTENT_LENGTH,
         TE,
@@ -1077,17 +1364,21 @@ pub(crate) fn validate_http_version(version: Version) -> Result<(), Error> {
     }
 }
 
+// HTTP/3 responses only come from the QUIC transport, which is compiled in on request.
 fn validate_response_http_version(
     version: Version,
     request_id: &str,
     headers: &HeaderMap,
 ) -> Result<(), Error> {
-    if matches!(version, Version::HTTP_11 | Version::HTTP_2) {
+    if matches!(
+        version,
+        Version::HTTP_11 | Version::HTTP_2 | Version::HTTP_3
+    ) {
         Ok(())
     } else {
         Err(remote_protocol_error(
             "invalid_http_version",
-            "remote response must use HTTP/1.1 or HTTP/2",
+            "remote response must use HTTP/1.1, HTTP/2 or HTTP/3",
             request_id,
         )
         .with_headers(response_headers_without_control(headers.clone())))
@@ -1102,14 +1393,22 @@ fn unsupported_http_parameter_source() -> Error {
     )
 }
 
-pub(crate) fn validate_content_type(
+/// Validates a buffered request body's media type and returns the binding it is encoded in.
+///
+/// `None` is the JSON representation; other media types are accepted only when one of the
+/// server's enabled `bindings` carries them.
+pub(crate) fn validate_content_type<'a>(
     headers: &HeaderMap,
     expected: &str,
     body_required: bool,
-) -> Result<(), Error> {
-    match one_header(headers, &CONTENT_TYPE)? {
-        Some(value) if media_type_matches(value, expected) => Ok(()),
-        None if !body_required => Ok(()),
+    bindings: &'a [ServerBinding],
+) -> Result<Option<&'a ServerBinding>, Error> {
+    let value = one_header(headers, &CONTENT_TYPE)?;
+    let binding = value.and_then(|value| bindings.iter().find(|binding| binding.matches(value)));
+    match value {
+        Some(value) if media_type_matches(value, expected) => Ok(None),
+        Some(_) if binding.is_some() => Ok(binding),
+        None if !body_required => Ok(None),
         _ => Err(Error::framework(
             ErrorCategory::InvalidArgument,
             "invalid_content_type",
@@ -1120,6 +1419,53 @@ pub(crate) fn validate_content_type(
     }
 }
 
+/// Streamed request bodies are opaque bytes, so only the declared media type essence must match.
+pub(crate) fn validate_body_stream_content_type(
+    headers: &HeaderMap,
+    expected: &str,
+) -> Result<(), Error> {
+    let matches = one_header(headers, &CONTENT_TYPE)?
+        .and_then(|value| value.to_str().ok()?.parse::<mime::Mime>().ok())
+        .zip(expected.parse::<mime::Mime>().ok())
+        .is_some_and(|(actual, expected)| actual.essence_str() == expected.essence_str());
+    if matches {
+        Ok(())
+    } else {
+        Err(Error::framework(
+            ErrorCategory::InvalidArgument,
+            "invalid_content_type",
+            format!("request Content-Type must be {expected}"),
+        ))
+    }
+}
+
+/// Reads the coding of a request body before any interceptor runs.
+///
+/// Streamed bodies reach the handler chunk by chunk and are never decoded, so they must be sent
+/// without a coding.
+pub(crate) fn request_content_coding(
+    headers: &HeaderMap,
+    body_stream: bool,
+) -> Result<Option<ContentCoding>, Error> {
+    match content_coding(headers) {
+        Ok(None) => Ok(None),
+        Ok(Some(coding)) if !body_stream => Ok(Some(coding)),
+        _ => Err(Error::framework(
+            ErrorCategory::UnsupportedMediaType,
+            "unsupported_content_encoding",
+            "request Content-Encoding is not supported",
+        )),
+    }
+}
+
+pub(crate) fn has_body_stream(method: &MethodDescriptor) -> bool {
+    method
+        .http_operation()
+        .parameters()
+        .iter()
+        .any(|parameter| parameter.source() == HttpParameterSource::BodyStream)
+}
+
 fn validate_json_response_content_type(
     headers: &HeaderMap,
     expected: &str,
@@ -1143,6 +1489,12 @@ pub(crate) fn validate_json_service(service: &ServiceDescriptor) -> Result<(), S
             ("produces", operation.produces()),
         ] {
             if !is_json_media_type(value) {
+                if field == "produces" && StreamFraming::for_media_type(value).is_some() {
+                    continue;
+                }
+                if field == "consumes" && has_body_stream(method) {
+                    continue;
+                }
                 return Err(format!(
                     "method {} has {field} media type {value:?}; http-json-v1 requires application/json or a concrete application subtype ending in +json",
                     method.invocation_name(),
@@ -1218,15 +1570,31 @@ pub(crate) fn parse_content_length(headers: &HeaderMap) -> Result<Option<usize>,
         })
 }
 
+/// A received HTTP body, independent of the connection type that produced it.
+pub(crate) trait WireBody:
+    HttpBody<Data = Bytes, Error: std::error::Error + Send + Sync + 'static> + Send + Unpin + 'static
+{
+}
+
+impl<B> WireBody for B where
+    B: HttpBody<Data = Bytes, Error: std::error::Error + Send + Sync + 'static>
+        + Send
+        + Unpin
+        + 'static
+{
+}
+
 pub(crate) async fn read_body(
     body: Incoming,
     content_length: Option<usize>,
+    coding: Option<ContentCoding>,
     max_body: usize,
     budget: &std::sync::Arc<ByteBudget>,
 ) -> Result<(Bytes, BytePermit), Error> {
     read_body_with_role(
         body,
         content_length,
+        coding,
         max_body,
         budget,
         BodyReadRole::Request,
@@ -1234,9 +1602,19 @@ pub(crate) async fn read_body(
     .await
 }
 
-async fn read_response_body(
+/// Hands a streamed request body to the handler as chunks of at most `max_chunk` bytes.
+pub(crate) fn read_body_stream(
     body: Incoming,
+    max_chunk: usize,
+    budget: &std::sync::Arc<ByteBudget>,
+) -> crate::BodyStream {
+    read_chunks(body, max_chunk, budget)
+}
+
+async fn read_response_body<B: WireBody>(
+    body: B,
     content_length: Option<usize>,
+    coding: Option<ContentCoding>,
     max_body: usize,
     budget: &std::sync::Arc<ByteBudget>,
     request_id: &str,
@@ -1244,6 +1622,7 @@ async fn read_response_body(
     read_body_with_role(
         body,
         content_length,
+        coding,
         max_body,
         budget,
         BodyReadRole::Response { request_id },
@@ -1292,6 +1671,31 @@ impl BodyReadRole<'_> {
         }
     }
 
+    #[cfg(feature = "compression")]
+    fn invalid_coding(self) -> Error {
+        match self {
+            Self::Request => Error::framework(
+                ErrorCategory::InvalidArgument,
+                "invalid_content_encoding",
+                "request body does not match its Content-Encoding",
+            ),
+            Self::Response { request_id } => remote_protocol_error(
+                "invalid_content_encoding",
+                "response body does not match its Content-Encoding",
+                request_id,
+            ),
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    fn decode_failed(self, failure: DecodeFailure) -> Error {
+        match failure {
+            DecodeFailure::TooLarge => self.too_large(),
+            DecodeFailure::BudgetExhausted => self.budget_exhausted(),
+            DecodeFailure::Corrupt => self.invalid_coding(),
+        }
+    }
+
     fn content_length_mismatch(self) -> Error {
         match self {
             Self::Request => Error::framework(
@@ -1324,9 +1728,10 @@ fn client_response_too_large() -> Error {
     )
 }
 
-async fn read_body_with_role(
-    mut body: Incoming,
+async fn read_body_with_role<B: WireBody>(
+    mut body: B,
     content_length: Option<usize>,
+    coding: Option<ContentCoding>,
     max_body: usize,
     budget: &std::sync::Arc<ByteBudget>,
     role: BodyReadRole<'_>,
@@ -1334,6 +1739,12 @@ async fn read_body_with_role(
     if content_length.is_some_and(|length| length > max_body) {
         return Err(role.too_large());
     }
+    #[cfg(feature = "compression")]
+    if let Some(coding) = coding {
+        return read_coded_body(body, content_length, coding, max_body, budget, role).await;
+    }
+    #[cfg(not(feature = "compression"))]
+    let None = coding;
     let initial_reservation = content_length.unwrap_or(0);
     let permit = budget
         .try_reserve(initial_reservation)
@@ -1370,6 +1781,42 @@ async fn read_body_with_role(
     Ok((Bytes::from(bytes), permit))
 }
 
+/// Decodes a coded body chunk by chunk; only the decoded output is buffered and budgeted.
+#[cfg(feature = "compression")]
+async fn read_coded_body<B: WireBody>(
+    mut body: B,
+    content_length: Option<usize>,
+    coding: ContentCoding,
+    max_body: usize,
+    budget: &std::sync::Arc<ByteBudget>,
+    role: BodyReadRole<'_>,
+) -> Result<(Bytes, BytePermit), Error> {
+    let mut decoder =
+        Decoder::new(coding, max_body, budget).map_err(|failure| role.decode_failed(failure))?;
+    let mut received = 0usize;
+    while let Some(frame) =
+        std::future::poll_fn(|context| Pin::new(&mut body).poll_frame(context)).await
+    {
+        let frame = frame.map_err(|error| role.stream_failed(error))?;
+        let Ok(chunk) = frame.into_data() else {
+            continue;
+        };
+        received = received
+            .checked_add(chunk.len())
+            .filter(|received| *received <= max_body)
+            .ok_or_else(|| role.too_large())?;
+        decoder
+            .write(&chunk)
+            .map_err(|failure| role.decode_failed(failure))?;
+    }
+    if content_length.is_some_and(|length| length != received) {
+        return Err(role.content_length_mismatch());
+    }
+    decoder
+        .finish()
+        .map_err(|failure| role.decode_failed(failure))
+}
+
 fn payload_too_large() -> Error {
     Error::framework(
         ErrorCategory::PayloadTooLarge,
@@ -1428,6 +1875,9 @@ pub(crate) struct GuardedBody {
     next_chunk: usize,
     remaining: usize,
     permit: Option<Arc<BytePermit>>,
+    stream: Option<FrameWriter>,
+    upload: Option<ChunkWriter>,
+    held: Option<Box<dyn Any + Send + Sync>>,
 }
 
 impl GuardedBody {
@@ -1438,7 +1888,71 @@ impl GuardedBody {
             next_chunk: 0,
             remaining,
             permit,
+            stream: None,
+            upload: None,
+            held: None,
+        }
+    }
+
+    fn stream(writer: FrameWriter) -> Self {
+        Self {
+            stream: Some(writer),
+            ..Self::new(Bytes::new(), None)
+        }
+    }
+
+    /// Creates a client request body that sends `body` chunk by chunk.
+    ///
+    /// The returned handle reports why the upload aborted once the transport fails.
+    pub(crate) fn upload(
+        body: crate::BodyStream,
+        max_chunk: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> (Self, UploadFailure) {
+        let failure = UploadFailure::default();
+        let body = Self {
+            upload: Some(ChunkWriter::new(body, max_chunk, budget, failure.clone())),
+            ..Self::new(Bytes::new(), None)
+        };
+        (body, failure)
+    }
+
+    /// Replaces a buffered body of at least `min_bytes` with its `coding` encoding.
+    ///
+    /// Streams and bodies that do not shrink are left untouched; returns whether the body changed.
+    #[cfg(feature = "compression")]
+    pub(crate) fn compress(
+        &mut self,
+        coding: ContentCoding,
+        min_bytes: usize,
+        budget: &Arc<ByteBudget>,
+    ) -> bool {
+        let [Some(body), None, None] = &self.chunks else {
+            return false;
+        };
+        if self.stream.is_some()
+            || self.upload.is_some()
+            || self.next_chunk != 0
+            || body.len() < min_bytes
+        {
+            return false;
         }
+        let Some((body, permit)) = compress(coding, body, budget) else {
+            return false;
+        };
+        let held = self.held.take();
+        *self = Self::new(body, Some(permit));
+        self.held = held;
+        true
+    }
+
+    pub(crate) const fn is_stream(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Keeps `guard` alive until the body is dropped, e.g. admission for a streaming response.
+    pub(crate) fn hold(&mut self, guard: impl Any + Send + Sync) {
+        self.held = Some(Box::new(guard));
     }
 }
 
@@ -1464,12 +1978,22 @@ impl Buf for GuardedChunk {
 
 impl HttpBody for GuardedBody {
     type Data = GuardedChunk;
-    type Error = Infallible;
+    type Error = StreamAborted;
 
     fn poll_frame(
         mut self: Pin<&mut Self>,
-        _context: &mut TaskContext<'_>,
+        context: &mut TaskContext<'_>,
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if let Some(writer) = self.stream.as_mut() {
+            return writer
+                .poll_chunk(context)
+                .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data)));
+        }
+        if let Some(writer) = self.upload.as_mut() {
+            return writer
+                .poll_chunk(context)
+                .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data)));
+        }
         while self.next_chunk < self.chunks.len() {
             let index = self.next_chunk;
             self.next_chunk += 1;
@@ -1489,11 +2013,19 @@ impl HttpBody for GuardedBody {
     }
 
     fn is_end_stream(&self) -> bool {
-        self.remaining == 0
+        match (&self.stream, &self.upload) {
+            (Some(writer), _) => writer.is_finished(),
+            (None, Some(writer)) => writer.is_finished(),
+            (None, None) => self.remaining == 0,
+        }
     }
 
     fn size_hint(&self) -> hyper::body::SizeHint {
-        hyper::body::SizeHint::with_exact(self.remaining as u64)
+        if self.stream.is_some() || self.upload.is_some() {
+            hyper::body::SizeHint::default()
+        } else {
+            hyper::body::SizeHint::with_exact(self.remaining as u64)
+        }
     }
 }
 
@@ -1882,13 +2414,14 @@ mod tests {
         let mut arguments = Arguments::new();
         arguments.insert("query_map".into(), serde_json::json!({"page": 2}));
         arguments.insert("page".into(), Value::Null);
-        let error = encode_json_request(RequestEncoding::new(
-            service,
-            method,
-            &arguments,
-            &HeaderMap::new(),
-        ))
-        .unwrap_err();
+        let error = JsonCodec
+            .encode(RequestEncoding::new(
+                service,
+                method,
+                &arguments,
+                &HeaderMap::new(),
+            ))
+            .unwrap_err();
         assert_eq!(error.code().as_str(), "duplicate_query_parameter");
 
         let mut arguments = Arguments::new();
@@ -1896,23 +2429,25 @@ mod tests {
         arguments.insert("page".into(), serde_json::json!(1));
         arguments.insert("header_map".into(), serde_json::json!({"x-scope": "map"}));
         arguments.insert("x-scope".into(), Value::Null);
-        let error = encode_json_request(RequestEncoding::new(
-            service,
-            method,
-            &arguments,
-            &HeaderMap::new(),
-        ))
-        .unwrap_err();
+        let error = JsonCodec
+            .encode(RequestEncoding::new(
+                service,
+                method,
+                &arguments,
+                &HeaderMap::new(),
+            ))
+            .unwrap_err();
         assert_eq!(error.code().as_str(), "header_binding_conflict");
 
         arguments.insert("header_map".into(), Value::Null);
-        let encoded = encode_json_request(RequestEncoding::new(
-            service,
-            method,
-            &arguments,
-            &HeaderMap::new(),
-        ))
-        .unwrap();
+        let encoded = JsonCodec
+            .encode(RequestEncoding::new(
+                service,
+                method,
+                &arguments,
+                &HeaderMap::new(),
+            ))
+            .unwrap();
         assert_eq!(encoded.path_and_query(), "/items?page=1");
         assert!(!encoded.headers().contains_key("x-scope"));
         assert!(!encoded.headers().contains_key(COOKIE));
@@ -1921,24 +2456,26 @@ mod tests {
             HeaderName::from_static("x-scope"),
             HeaderValue::from_static("call"),
         )]);
-        let error = encode_json_request(RequestEncoding::new(
-            service,
-            method,
-            &arguments,
-            &call_headers,
-        ))
-        .unwrap_err();
+        let error = JsonCodec
+            .encode(RequestEncoding::new(
+                service,
+                method,
+                &arguments,
+                &call_headers,
+            ))
+            .unwrap_err();
         assert_eq!(error.code().as_str(), "header_binding_conflict");
 
         let call_headers =
             HeaderMap::from_iter([(COOKIE, HeaderValue::from_static("session=call"))]);
-        let error = encode_json_request(RequestEncoding::new(
-            service,
-            method,
-            &arguments,
-            &call_headers,
-        ))
-        .unwrap_err();
+        let error = JsonCodec
+            .encode(RequestEncoding::new(
+                service,
+                method,
+                &arguments,
+                &call_headers,
+            ))
+            .unwrap_err();
         assert_eq!(error.code().as_str(), "header_binding_conflict");
     }
 
@@ -1969,6 +2506,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn priority_header_is_optional_exact_and_single_valued() {
+        assert_eq!(parse_priority(&HeaderMap::new()).unwrap(), None);
+        let headers = HeaderMap::from_iter([(PRIORITY, HeaderValue::from_static("low"))]);
+        assert_eq!(parse_priority(&headers).unwrap(), Some(MethodPriority::Low));
+
+        let mut duplicate = HeaderMap::new();
+        duplicate.append(PRIORITY, HeaderValue::from_static("low"));
+        duplicate.append(PRIORITY, HeaderValue::from_static("high"));
+        assert_eq!(
+            parse_priority(&duplicate).unwrap_err().code().as_str(),
+            "duplicate_control_header"
+        );
+        for value in ["urgent", "Low", ""] {
+            let headers = HeaderMap::from_iter([(PRIORITY, HeaderValue::from_static(value))]);
+            let error = parse_priority(&headers).unwrap_err();
+            assert_eq!(error.category(), ErrorCategory::InvalidArgument);
+            assert_eq!(error.code().as_str(), "invalid_priority");
+        }
+    }
+
     #[test]
     fn response_content_type_must_be_one_json_media_type() {
         let mut invalid_utf8 = HeaderMap::new();
@@ -2024,7 +2582,7 @@ mod tests {
         }
 
         let headers = HeaderMap::from_iter([(CONTENT_TYPE, HeaderValue::from_static("text/json"))]);
-        let error = validate_content_type(&headers, JSON_CONTENT_TYPE, true).unwrap_err();
+        let error = validate_content_type(&headers, JSON_CONTENT_TYPE, true, &[]).unwrap_err();
         assert_eq!(error.category(), ErrorCategory::InvalidArgument);
         assert_eq!(error.code().as_str(), "invalid_content_type");
     }
@@ -2085,7 +2643,7 @@ mod tests {
 
     #[test]
     fn unsupported_response_http_versions_are_remote_protocol_errors() {
-        for version in [Version::HTTP_09, Version::HTTP_10, Version::HTTP_3] {
+        for version in [Version::HTTP_09, Version::HTTP_10] {
             let error = validate_response_http_version(
                 version,
                 "request-1",
@@ -2107,7 +2665,7 @@ mod tests {
             assert!(!error.headers().contains_key(CONTENT_TYPE));
         }
 
-        for version in [Version::HTTP_11, Version::HTTP_2] {
+        for version in [Version::HTTP_11, Version::HTTP_2, Version::HTTP_3] {
             validate_response_http_version(version, "request-1", &HeaderMap::new()).unwrap();
         }
     }
@@ -2220,7 +2778,17 @@ mod tests {
         let budget = ByteBudget::new(4);
         let response = Response::success_with_budget("ok", 4, 0, &budget).unwrap();
         assert_eq!(budget.used(), 4);
-        let response = encode_success(response, JSON_CONTENT_TYPE, false, 4, &budget).unwrap();
+        let response = encode_success(
+            response,
+            JSON_CONTENT_TYPE,
+            None,
+            false,
+            4,
+            &budget,
+            "request-1",
+            true,
+        )
+        .unwrap();
         assert_eq!(response.body().size_hint().exact(), Some(4));
         let body = response.into_body().collect().await.unwrap().to_bytes();
         assert_eq!(body, r#""ok""#);
@@ -2237,7 +2805,17 @@ mod tests {
             let budget = ByteBudget::new(16);
             let mut response = Response::success_with_budget("ok", 16, 0, &budget).unwrap();
             response.set_status(status).unwrap();
-            let encoded = encode_success(response, JSON_CONTENT_TYPE, head, 16, &budget).unwrap();
+            let encoded = encode_success(
+                response,
+                JSON_CONTENT_TYPE,
+                None,
+                head,
+                16,
+                &budget,
+                "request-1",
+                true,
+            )
+            .unwrap();
             assert_eq!(encoded.status(), status);
             assert!(encoded.headers().get(CONTENT_TYPE).is_none());
             assert_eq!(encoded.body().size_hint().exact(), Some(0));
@@ -2287,7 +2865,17 @@ mod tests {
             .headers_mut()
             .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
 
-        let encoded = encode_success(response, JSON_CONTENT_TYPE, false, 1024, &budget).unwrap();
+        let encoded = encode_success(
+            response,
+            JSON_CONTENT_TYPE,
+            None,
+            false,
+            1024,
+            &budget,
+            "request-1",
+            true,
+        )
+        .unwrap();
         assert!(encoded.headers().get(CONTENT_LENGTH).is_none());
         assert!(encoded.headers().get(TRANSFER_ENCODING).is_none());
         assert!(encoded.headers().get(REQUEST_ID).is_none());
@@ -2296,4 +2884,21 @@ mod tests {
             JSON_CONTENT_TYPE
         );
     }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn request_codings_are_unsupported_without_the_compression_feature() {
+        let coded = HeaderMap::from_iter([(CONTENT_ENCODING, HeaderValue::from_static("gzip"))]);
+        let error = request_content_coding(&coded, false).unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::UnsupportedMediaType);
+        assert_eq!(error.code().as_str(), "unsupported_content_encoding");
+
+        let identity =
+            HeaderMap::from_iter([(CONTENT_ENCODING, HeaderValue::from_static("identity"))]);
+        assert!(matches!(request_content_coding(&identity, false), Ok(None)));
+        assert!(matches!(
+            request_content_coding(&HeaderMap::new(), true),
+            Ok(None)
+        ));
+    }
 }
@@ -171,6 +171,46 @@ pub(super) fn decode_problem(
     .with_headers(headers)
 }
 
+/// Encodes a mid-stream failure as the bounded Problem Details document its terminal frame carries.
+pub(super) fn encode_stream_problem(
+    error: &Error,
+    request_id: &str,
+    invocation_controls: bool,
+) -> Bytes {
+    let (mut problem, _status) = problem_from_error(error, request_id, None);
+    if !invocation_controls {
+        problem.request_id = None;
+    }
+    bounded_problem(&problem)
+}
+
+/// Decodes a mid-stream error frame. The response head was already 2xx, so the status comes from
+/// the problem document itself.
+pub(super) fn decode_stream_problem(
+    expected_request_id: &str,
+    body: &[u8],
+    strict_controls: bool,
+) -> Error {
+    let status = serde_json::from_slice::<ProblemDetails>(body)
+        .ok()
+        .and_then(|problem| StatusCode::from_u16(problem.status).ok())
+        .filter(|status| status.is_client_error() || status.is_server_error());
+    match status {
+        Some(status) => decode_problem(
+            status,
+            expected_request_id,
+            body,
+            HeaderMap::new(),
+            strict_controls,
+        ),
+        None => remote_protocol_error(
+            "invalid_stream_error",
+            "response stream error event is not a valid Problem Details document",
+            expected_request_id,
+        ),
+    }
+}
+
 pub(super) fn decode_head_error(
     status: StatusCode,
     expected_request_id: &str,
@@ -700,6 +740,7 @@ fn framework_category_name(category: ErrorCategory) -> Option<&'static str> {
         ErrorCategory::Unauthenticated => Some("unauthenticated"),
         ErrorCategory::PermissionDenied => Some("permission-denied"),
         ErrorCategory::PayloadTooLarge => Some("payload-too-large"),
+        ErrorCategory::UnsupportedMediaType => Some("unsupported-media-type"),
         ErrorCategory::ResourceExhausted => Some("resource-exhausted"),
         ErrorCategory::Unavailable => Some("unavailable"),
         ErrorCategory::DeadlineExceeded => Some("deadline-exceeded"),
@@ -719,6 +760,7 @@ fn framework_category_from_name(category: &str) -> Option<ErrorCategory> {
         "unauthenticated" => Some(ErrorCategory::Unauthenticated),
         "permission-denied" => Some(ErrorCategory::PermissionDenied),
         "payload-too-large" => Some(ErrorCategory::PayloadTooLarge),
+        "unsupported-media-type" => Some(ErrorCategory::UnsupportedMediaType),
         "resource-exhausted" => Some(ErrorCategory::ResourceExhausted),
         "unavailable" => Some(ErrorCategory::Unavailable),
         "deadline-exceeded" => Some(ErrorCategory::DeadlineExceeded),
@@ -738,6 +780,7 @@ fn category_from_status(status: StatusCode) -> ErrorCategory {
         StatusCode::NOT_FOUND => ErrorCategory::NotFound,
         StatusCode::CONFLICT => ErrorCategory::Conflict,
         StatusCode::PAYLOAD_TOO_LARGE => ErrorCategory::PayloadTooLarge,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCategory::UnsupportedMediaType,
         StatusCode::TOO_MANY_REQUESTS => ErrorCategory::ResourceExhausted,
         StatusCode::SERVICE_UNAVAILABLE => ErrorCategory::Unavailable,
         StatusCode::GATEWAY_TIMEOUT | StatusCode::REQUEST_TIMEOUT => {
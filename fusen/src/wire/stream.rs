@@ -0,0 +1,740 @@
+use super::{
+    BodyReadRole, EMERGENCY_PROBLEM_LIMIT, GuardedChunk, WireBody,
+    client_response_budget_exhausted, client_response_too_large,
+    problem::{decode_stream_problem, encode_stream_problem},
+    request_budget_exhausted,
+};
+use crate::{
+    BodyChunk, BodyFrame, BodyFrames, BodyStream, Error, ErrorCategory,
+    runtime::budget::{ByteBudget, BytePermit},
+};
+use bytes::{Bytes, BytesMut};
+use futures_util::{Stream, stream};
+use hyper::body::{Body as HttpBody, Incoming};
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as TaskContext, Poll},
+    time::Duration,
+};
+
+pub(crate) const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+pub(crate) const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+/// Unread upload bytes discarded after the handler stops reading, before the connection closes.
+const LINGER_MAX_BYTES: usize = 1024 * 1024;
+const LINGER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Starts the terminal NDJSON error line. It is the RFC 7464 record separator, which can never
+/// start a JSON text, so the line cannot be mistaken for an item.
+const NDJSON_ERROR_PREFIX: u8 = 0x1e;
+
+/// Wire framing of a server-streaming response, selected by the declared `produces` media type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StreamFraming {
+    /// One compact JSON item per `\n`-terminated line; a failure arrives as a final line holding
+    /// Problem Details after [`NDJSON_ERROR_PREFIX`].
+    Ndjson,
+    /// One `data:` line per JSON item; failures arrive as an `error` event with Problem Details.
+    EventStream,
+}
+
+impl StreamFraming {
+    pub(crate) fn for_media_type(value: &str) -> Option<Self> {
+        let media_type = value.parse::<mime::Mime>().ok()?;
+        match media_type.essence_str() {
+            NDJSON_CONTENT_TYPE => Some(Self::Ndjson),
+            EVENT_STREAM_CONTENT_TYPE => Some(Self::EventStream),
+            _ => None,
+        }
+    }
+
+    pub(super) fn frame(self, item: &[u8]) -> Result<Bytes, Error> {
+        // Compact JSON never contains raw line breaks, so any here would split one item in two.
+        if item.iter().any(|byte| matches!(byte, b'\n' | b'\r')) {
+            return Err(Error::framework(
+                ErrorCategory::Internal,
+                "invalid_stream_item",
+                "encoded stream item contains a line break",
+            ));
+        }
+        let mut framed = BytesMut::with_capacity(item.len() + 8);
+        match self {
+            Self::Ndjson => {
+                framed.extend_from_slice(item);
+                framed.extend_from_slice(b"\n");
+            }
+            Self::EventStream => {
+                framed.extend_from_slice(b"data: ");
+                framed.extend_from_slice(item);
+                framed.extend_from_slice(b"\n\n");
+            }
+        }
+        Ok(framed.freeze())
+    }
+
+    /// Frames the Problem Details document that ends a failed stream.
+    pub(super) fn error_frame(self, problem: &[u8]) -> Bytes {
+        let mut framed = BytesMut::with_capacity(problem.len() + 22);
+        match self {
+            Self::Ndjson => {
+                framed.extend_from_slice(&[NDJSON_ERROR_PREFIX]);
+                framed.extend_from_slice(problem);
+                framed.extend_from_slice(b"\n");
+            }
+            Self::EventStream => {
+                framed.extend_from_slice(b"event: error\ndata: ");
+                framed.extend_from_slice(problem);
+                framed.extend_from_slice(b"\n\n");
+            }
+        }
+        framed.freeze()
+    }
+}
+
+/// Server half: frames encoded items as the response body is polled.
+pub(super) struct FrameWriter {
+    frames: Option<BodyFrames>,
+    framing: StreamFraming,
+    request_id: String,
+    invocation_controls: bool,
+}
+
+/// A streamed body failed part-way; the body must abort so the peer sees truncation.
+#[derive(Debug)]
+pub(crate) struct StreamAborted;
+
+impl std::fmt::Display for StreamAborted {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("response stream aborted")
+    }
+}
+
+impl std::error::Error for StreamAborted {}
+
+impl FrameWriter {
+    pub(super) fn new(
+        frames: BodyFrames,
+        framing: StreamFraming,
+        request_id: &str,
+        invocation_controls: bool,
+    ) -> Self {
+        Self {
+            frames: Some(frames),
+            framing,
+            request_id: request_id.to_owned(),
+            invocation_controls,
+        }
+    }
+
+    pub(super) const fn is_finished(&self) -> bool {
+        self.frames.is_none()
+    }
+
+    pub(super) fn poll_chunk(
+        &mut self,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<GuardedChunk, StreamAborted>>> {
+        let Some(frames) = self.frames.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let polled = catch_unwind(AssertUnwindSafe(|| frames.as_mut().poll_next(context)))
+            .unwrap_or_else(|_| {
+                tracing::error!("response stream panicked");
+                Poll::Ready(Some(Err(Error::framework(
+                    ErrorCategory::Internal,
+                    "handler_panic",
+                    "interface handler failed",
+                ))))
+            });
+        let error = match polled {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(None) => {
+                self.frames = None;
+                return Poll::Ready(None);
+            }
+            Poll::Ready(Some(Ok(frame))) => match self.framing.frame(&frame.bytes) {
+                Ok(bytes) => {
+                    return Poll::Ready(Some(Ok(GuardedChunk {
+                        bytes,
+                        _permit: frame.permit,
+                    })));
+                }
+                Err(error) => error,
+            },
+            Poll::Ready(Some(Err(error))) => error,
+        };
+        self.frames = None;
+        tracing::warn!(
+            request_id = %self.request_id,
+            code = %error.code(),
+            "response stream ended with an error"
+        );
+        let problem = encode_stream_problem(&error, &self.request_id, self.invocation_controls);
+        Poll::Ready(Some(Ok(GuardedChunk {
+            bytes: self.framing.error_frame(&problem),
+            _permit: None,
+        })))
+    }
+}
+
+/// Client half: turns a streaming response body into encoded items.
+pub(super) fn decode_frames<B: WireBody>(
+    body: B,
+    framing: StreamFraming,
+    max_item: usize,
+    budget: &Arc<ByteBudget>,
+    request_id: &str,
+    invocation_controls: bool,
+) -> Result<BodyFrames, Error> {
+    let reader = FrameReader {
+        body,
+        framing,
+        buffer: BytesMut::new(),
+        permit: budget
+            .try_reserve(0)
+            .ok_or_else(client_response_budget_exhausted)?,
+        body_finished: false,
+        event: Event::default(),
+        max_item,
+        request_id: request_id.to_owned(),
+        invocation_controls,
+    };
+    Ok(Box::pin(stream::unfold(
+        Some(reader),
+        |reader| async move {
+            let mut reader = reader?;
+            match reader.next_frame().await {
+                Ok(Some(frame)) => Some((Ok(frame), Some(reader))),
+                Ok(None) => None,
+                Err(error) => Some((Err(error), None)),
+            }
+        },
+    )))
+}
+
+#[derive(Default)]
+struct Event {
+    data: Vec<u8>,
+    has_data: bool,
+    kind: EventKind,
+    consumed: usize,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum EventKind {
+    #[default]
+    Message,
+    Error,
+    Ignored,
+}
+
+struct FrameReader<B> {
+    body: B,
+    framing: StreamFraming,
+    buffer: BytesMut,
+    // Covers every byte read from the wire until it is handed to a frame or discarded.
+    permit: BytePermit,
+    body_finished: bool,
+    event: Event,
+    max_item: usize,
+    request_id: String,
+    invocation_controls: bool,
+}
+
+impl<B: WireBody> FrameReader<B> {
+    async fn next_frame(&mut self) -> Result<Option<BodyFrame>, Error> {
+        loop {
+            while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line = self.buffer.split_to(end + 1);
+                if let Some(frame) = self.accept_line(line)? {
+                    return Ok(Some(frame));
+                }
+            }
+            if self.body_finished {
+                // A trailing NDJSON item may omit its newline; a partial event is discarded.
+                if self.framing == StreamFraming::Ndjson && !self.buffer.is_empty() {
+                    let line = self.buffer.split();
+                    return self.accept_line(line);
+                }
+                return Ok(None);
+            }
+            if self.buffer.len() > self.line_limit() {
+                return Err(client_response_too_large());
+            }
+            let role = BodyReadRole::Response {
+                request_id: &self.request_id,
+            };
+            let Some(frame) =
+                std::future::poll_fn(|context| Pin::new(&mut self.body).poll_frame(context)).await
+            else {
+                self.body_finished = true;
+                continue;
+            };
+            let frame = frame.map_err(|error| role.stream_failed(error))?;
+            if let Ok(chunk) = frame.into_data() {
+                if !self.permit.grow(chunk.len()) {
+                    return Err(client_response_budget_exhausted());
+                }
+                self.buffer.extend_from_slice(&chunk);
+            }
+        }
+    }
+
+    fn accept_line(&mut self, line: BytesMut) -> Result<Option<BodyFrame>, Error> {
+        let consumed = line.len();
+        let mut line = line.freeze();
+        let content = line
+            .iter()
+            .rposition(|byte| !matches!(byte, b'\n' | b'\r'))
+            .map_or(0, |last| last + 1);
+        line.truncate(content);
+        match self.framing {
+            StreamFraming::Ndjson => {
+                let permit = self.permit.split(consumed);
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    return Ok(None);
+                }
+                if let Some(problem) = line.strip_prefix(&[NDJSON_ERROR_PREFIX]) {
+                    return Err(decode_stream_problem(
+                        &self.request_id,
+                        problem,
+                        self.invocation_controls,
+                    ));
+                }
+                if line.len() > self.max_item {
+                    return Err(client_response_too_large());
+                }
+                Ok(Some(BodyFrame {
+                    bytes: line,
+                    permit: Some(Arc::new(permit)),
+                }))
+            }
+            StreamFraming::EventStream => {
+                self.event.consumed += consumed;
+                if line.is_empty() {
+                    return self.dispatch_event();
+                }
+                if line.starts_with(b":") {
+                    return Ok(None);
+                }
+                let (field, value) = match line.iter().position(|byte| *byte == b':') {
+                    Some(colon) => {
+                        let value = &line[colon + 1..];
+                        (&line[..colon], value.strip_prefix(b" ").unwrap_or(value))
+                    }
+                    None => (&line[..], &[][..]),
+                };
+                match field {
+                    b"data" => {
+                        if self.event.has_data {
+                            self.event.data.push(b'\n');
+                        }
+                        self.event.has_data = true;
+                        self.event.data.extend_from_slice(value);
+                        if self.event.data.len() > self.event_limit() {
+                            return Err(client_response_too_large());
+                        }
+                    }
+                    b"event" => {
+                        self.event.kind = match value {
+                            b"" | b"message" => EventKind::Message,
+                            b"error" => EventKind::Error,
+                            _ => EventKind::Ignored,
+                        };
+                    }
+                    _ => {}
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    // Error events carry a bounded Problem Details document, which may exceed a small item limit.
+    fn event_limit(&self) -> usize {
+        match self.event.kind {
+            EventKind::Error => self.max_item.max(EMERGENCY_PROBLEM_LIMIT),
+            EventKind::Message | EventKind::Ignored => self.max_item,
+        }
+    }
+
+    fn line_limit(&self) -> usize {
+        match self.framing {
+            StreamFraming::Ndjson => self
+                .max_item
+                .max(EMERGENCY_PROBLEM_LIMIT + 1)
+                .saturating_add(2),
+            StreamFraming::EventStream => self
+                .max_item
+                .max(EMERGENCY_PROBLEM_LIMIT)
+                .saturating_add(b"data: \r\n".len()),
+        }
+    }
+
+    fn dispatch_event(&mut self) -> Result<Option<BodyFrame>, Error> {
+        let event = std::mem::take(&mut self.event);
+        let permit = self.permit.split(event.consumed);
+        if !event.has_data {
+            return Ok(None);
+        }
+        match event.kind {
+            EventKind::Message => Ok(Some(BodyFrame {
+                bytes: Bytes::from(event.data),
+                permit: Some(Arc::new(permit)),
+            })),
+            EventKind::Error => Err(decode_stream_problem(
+                &self.request_id,
+                &event.data,
+                self.invocation_controls,
+            )),
+            EventKind::Ignored => Ok(None),
+        }
+    }
+}
+
+/// Bytes received or accepted for sending but not yet handed out as bounded chunks.
+struct PendingChunk {
+    bytes: Bytes,
+    permit: BytePermit,
+}
+
+impl PendingChunk {
+    /// Splits off the next chunk of at most `max_chunk` bytes with its share of the reservation.
+    fn next(&mut self, max_chunk: usize) -> Option<(Bytes, BytePermit)> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let length = self.bytes.len().min(max_chunk.max(1));
+        Some((self.bytes.split_to(length), self.permit.split(length)))
+    }
+}
+
+/// Client half of a client-streaming request: sends body chunks as the request body is polled.
+pub(super) struct ChunkWriter {
+    chunks: Option<BodyStream>,
+    pending: Option<PendingChunk>,
+    max_chunk: usize,
+    budget: Arc<ByteBudget>,
+    failure: UploadFailure,
+}
+
+/// Records why a streamed request body aborted; the transport only reports the truncation.
+#[derive(Clone, Default)]
+pub(crate) struct UploadFailure(Arc<Mutex<Option<Error>>>);
+
+impl UploadFailure {
+    pub(crate) fn take(&self) -> Option<Error> {
+        self.0
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take()
+    }
+
+    fn set(&self, error: Error) {
+        *self.0.lock().unwrap_or_else(|error| error.into_inner()) = Some(error);
+    }
+}
+
+impl ChunkWriter {
+    pub(super) fn new(
+        chunks: BodyStream,
+        max_chunk: usize,
+        budget: &Arc<ByteBudget>,
+        failure: UploadFailure,
+    ) -> Self {
+        Self {
+            chunks: Some(chunks),
+            pending: None,
+            max_chunk,
+            budget: budget.clone(),
+            failure,
+        }
+    }
+
+    pub(super) const fn is_finished(&self) -> bool {
+        self.chunks.is_none() && self.pending.is_none()
+    }
+
+    pub(super) fn poll_chunk(
+        &mut self,
+        context: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<GuardedChunk, StreamAborted>>> {
+        loop {
+            if let Some(pending) = self.pending.as_mut() {
+                if let Some((bytes, permit)) = pending.next(self.max_chunk) {
+                    return Poll::Ready(Some(Ok(GuardedChunk {
+                        bytes,
+                        _permit: Some(Arc::new(permit)),
+                    })));
+                }
+                self.pending = None;
+            }
+            let Some(chunks) = self.chunks.as_mut() else {
+                return Poll::Ready(None);
+            };
+            let polled = catch_unwind(AssertUnwindSafe(|| {
+                Pin::new(&mut *chunks).poll_next(context)
+            }))
+            .unwrap_or_else(|_| {
+                tracing::error!("request body stream panicked");
+                Poll::Ready(Some(Err(Error::framework(
+                    ErrorCategory::Internal,
+                    "body_stream_panic",
+                    "request body stream failed",
+                ))))
+            });
+            let error = match polled {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => {
+                    self.chunks = None;
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let bytes = chunk.into_bytes();
+                    match self.budget.try_reserve(bytes.len()) {
+                        Some(permit) => {
+                            self.pending = Some(PendingChunk { bytes, permit });
+                            continue;
+                        }
+                        None => request_budget_exhausted(),
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => error,
+            };
+            self.chunks = None;
+            self.failure.set(error);
+            return Poll::Ready(Some(Err(StreamAborted)));
+        }
+    }
+}
+
+/// Server half of a client-streaming request: yields chunks reserved against the request budget.
+///
+/// Frames larger than `max_chunk` are split, so the handler never holds more than one frame
+/// beyond what it has already taken.
+pub(super) fn read_chunks(
+    body: Incoming,
+    max_chunk: usize,
+    budget: &Arc<ByteBudget>,
+) -> BodyStream {
+    let reader = ChunkReader {
+        body: LingeringBody(Some(body)),
+        pending: None,
+        max_chunk,
+        budget: budget.clone(),
+    };
+    BodyStream::from_body_chunks(stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        match reader.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(reader))),
+            Ok(None) => None,
+            Err(error) => Some((Err(error), None)),
+        }
+    }))
+}
+
+struct ChunkReader {
+    body: LingeringBody,
+    pending: Option<PendingChunk>,
+    max_chunk: usize,
+    budget: Arc<ByteBudget>,
+}
+
+impl ChunkReader {
+    async fn next_chunk(&mut self) -> Result<Option<BodyChunk>, Error> {
+        let role = BodyReadRole::Request;
+        loop {
+            if let Some((bytes, permit)) = self
+                .pending
+                .as_mut()
+                .and_then(|pending| pending.next(self.max_chunk))
+            {
+                return Ok(Some(BodyChunk::budgeted(bytes, permit)));
+            }
+            self.pending = None;
+            let Some(frame) =
+                std::future::poll_fn(|context| Pin::new(self.body.get()).poll_frame(context)).await
+            else {
+                return Ok(None);
+            };
+            let frame = frame.map_err(|error| role.stream_failed(error))?;
+            if let Ok(bytes) = frame.into_data() {
+                let permit = self
+                    .budget
+                    .try_reserve(bytes.len())
+                    .ok_or_else(|| role.budget_exhausted())?;
+                self.pending = Some(PendingChunk { bytes, permit });
+            }
+        }
+    }
+}
+
+/// Upload body that keeps reading a bounded remainder after the handler drops it.
+///
+/// Closing an HTTP/1.1 connection with unread request bytes resets it, and a client still writing
+/// its upload then fails with a broken pipe before it reads the early response. Discarding the
+/// rest lets that client finish writing and observe the rejection instead.
+struct LingeringBody(Option<Incoming>);
+
+impl LingeringBody {
+    fn get(&mut self) -> &mut Incoming {
+        self.0
+            .as_mut()
+            .expect("lingering body is only taken when dropped")
+    }
+}
+
+impl Drop for LingeringBody {
+    fn drop(&mut self) {
+        let Some(mut body) = self.0.take().filter(|body| !body.is_end_stream()) else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
+            let mut remaining = LINGER_MAX_BYTES;
+            let _ = tokio::time::timeout(LINGER_TIMEOUT, async {
+                while let Some(Ok(frame)) =
+                    std::future::poll_fn(|context| Pin::new(&mut body).poll_frame(context)).await
+                {
+                    let len = frame.data_ref().map_or(0, Bytes::len);
+                    let Some(left) = remaining.checked_sub(len) else {
+                        return;
+                    };
+                    remaining = left;
+                }
+            })
+            .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn framing_is_selected_by_media_type_essence() {
+        assert_eq!(
+            StreamFraming::for_media_type("application/x-ndjson"),
+            Some(StreamFraming::Ndjson)
+        );
+        assert_eq!(
+            StreamFraming::for_media_type("text/event-stream; charset=utf-8"),
+            Some(StreamFraming::EventStream)
+        );
+        assert_eq!(StreamFraming::for_media_type("application/json"), None);
+        assert_eq!(StreamFraming::for_media_type("not a media type"), None);
+    }
+
+    #[test]
+    fn items_are_framed_per_line_and_line_breaks_are_rejected() {
+        assert_eq!(
+            StreamFraming::Ndjson.frame(br#"{"a":1}"#).unwrap(),
+            Bytes::from_static(b"{\"a\":1}\n")
+        );
+        assert_eq!(
+            StreamFraming::EventStream.frame(b"1").unwrap(),
+            Bytes::from_static(b"data: 1\n\n")
+        );
+        let error = StreamFraming::Ndjson.frame(b"1\n2").unwrap_err();
+        assert_eq!(error.code().as_str(), "invalid_stream_item");
+    }
+
+    #[test]
+    fn error_events_round_trip_problem_details() {
+        let error =
+            Error::application(ErrorCategory::Conflict, "export_conflict", "conflict").unwrap();
+        let problem = encode_stream_problem(&error, "request-1", true);
+        let event = StreamFraming::EventStream.error_frame(&problem);
+        let data = event
+            .strip_prefix(b"event: error\ndata: ")
+            .and_then(|data| data.strip_suffix(b"\n\n"))
+            .expect("error events carry one data line");
+        let decoded = decode_stream_problem("request-1", data, true);
+        assert_eq!(decoded.kind(), ErrorKind::Application);
+        assert_eq!(decoded.code().as_str(), "export_conflict");
+
+        let invalid = decode_stream_problem("request-1", b"{\"status\":200}", true);
+        assert_eq!(invalid.code().as_str(), "invalid_stream_error");
+    }
+
+    fn failing_frames(items: &[&'static str]) -> BodyFrames {
+        let error =
+            Error::application(ErrorCategory::Conflict, "export_conflict", "conflict").unwrap();
+        Box::pin(stream::iter(
+            items
+                .iter()
+                .map(|item| {
+                    Ok(BodyFrame {
+                        bytes: Bytes::from_static(item.as_bytes()),
+                        permit: None,
+                    })
+                })
+                .chain([Err(error)])
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    fn written(items: &[&'static str]) -> Vec<Bytes> {
+        let mut writer = FrameWriter::new(
+            failing_frames(items),
+            StreamFraming::Ndjson,
+            "request-1",
+            true,
+        );
+        let mut context = TaskContext::from_waker(std::task::Waker::noop());
+        let mut chunks = Vec::new();
+        loop {
+            match writer.poll_chunk(&mut context) {
+                Poll::Ready(Some(Ok(chunk))) => chunks.push(chunk.bytes),
+                Poll::Ready(None) => break,
+                Poll::Ready(Some(Err(StreamAborted))) => panic!("NDJSON failures end in band"),
+                Poll::Pending => panic!("a ready stream never waits"),
+            }
+        }
+        assert!(writer.is_finished());
+        chunks
+    }
+
+    async fn read(chunks: Vec<Bytes>) -> Vec<Result<Bytes, Error>> {
+        let body = http_body_util::Full::new(chunks.concat().into());
+        let budget = ByteBudget::new(64 * 1024);
+        decode_frames(body, StreamFraming::Ndjson, 64, &budget, "request-1", true)
+            .unwrap()
+            .map(|frame| frame.map(|frame| frame.bytes))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn ndjson_failures_after_items_end_with_an_error_line() {
+        let chunks = written(&["1", "2"]);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1], Bytes::from_static(b"2\n"));
+        assert_eq!(chunks[2][0], NDJSON_ERROR_PREFIX);
+        assert!(chunks[2].ends_with(b"\n"));
+
+        let received = read(chunks).await;
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[0].as_ref().unwrap(), &Bytes::from_static(b"1"));
+        assert_eq!(received[1].as_ref().unwrap(), &Bytes::from_static(b"2"));
+        let error = received[2].as_ref().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Application);
+        assert_eq!(error.code().as_str(), "export_conflict");
+    }
+
+    #[tokio::test]
+    async fn ndjson_failures_before_the_first_item_are_the_only_line() {
+        let chunks = written(&[]);
+        assert_eq!(chunks.len(), 1);
+
+        let received = read(chunks).await;
+        assert_eq!(received.len(), 1);
+        let error = received[0].as_ref().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Application);
+        assert_eq!(error.code().as_str(), "export_conflict");
+    }
+}
@@ -0,0 +1,231 @@
+//! Real-socket coverage for client-streaming request bodies.
+
+use bytes::Bytes;
+use fusen_rs::{
+    BodyStream, ClientAdmissionConfig, ClientConfig, ClientRuntime, Error, ErrorCategory,
+    ErrorOrigin, Response, RunningServer, SensitiveFields, Server, ServerConfig,
+    ServerRequestConfig, interface,
+};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// Signalled when `hoard` rejects an upload whose retained chunks exceed the request budget.
+static HOARD_REJECTED: Notify = Notify::const_new();
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, SensitiveFields)]
+struct UploadSummary {
+    name: String,
+    bytes: usize,
+    largest_chunk: usize,
+    checksum: u64,
+}
+
+#[interface(name = "client-streaming-e2e")]
+trait UploadService {
+    #[fusen_rs::method(method = "POST", path = "/uploads/{name}")]
+    async fn upload(
+        &self,
+        #[param(path)] name: String,
+        #[param(body_stream)] body: BodyStream,
+    ) -> Result<Response<UploadSummary>, Error>;
+
+    #[fusen_rs::method(method = "PATCH", path = "/uploads/hoard")]
+    async fn hoard(&self, #[param(body_stream)] body: BodyStream)
+    -> Result<Response<usize>, Error>;
+}
+
+struct UploadServiceImpl;
+
+impl UploadService for UploadServiceImpl {
+    async fn upload(
+        &self,
+        name: String,
+        mut body: BodyStream,
+    ) -> Result<Response<UploadSummary>, Error> {
+        let mut summary = UploadSummary {
+            name,
+            bytes: 0,
+            largest_chunk: 0,
+            checksum: 0,
+        };
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            summary.bytes += chunk.len();
+            summary.largest_chunk = summary.largest_chunk.max(chunk.len());
+            summary.checksum = checksum(summary.checksum, &chunk);
+        }
+        Ok(Response::new(summary))
+    }
+
+    async fn hoard(&self, mut body: BodyStream) -> Result<Response<usize>, Error> {
+        // Retained chunks keep their budget reservation, so the upload cannot outgrow it.
+        let mut retained = Vec::new();
+        while let Some(chunk) = body.next().await {
+            match chunk {
+                Ok(chunk) => retained.push(chunk),
+                Err(error) => {
+                    HOARD_REJECTED.notify_one();
+                    return Err(error);
+                }
+            }
+        }
+        Ok(Response::new(
+            retained.iter().map(|chunk| chunk.len()).sum(),
+        ))
+    }
+}
+
+fn checksum(seed: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(seed, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(u64::from(*byte))
+    })
+}
+
+const CHUNK_LIMIT: usize = 64;
+const INFLIGHT_LIMIT: usize = 256;
+
+async fn start_server() -> RunningServer {
+    let config = ServerConfig::builder()
+        .request(
+            ServerRequestConfig::builder()
+                .max_request_body_bytes(CHUNK_LIMIT)
+                .max_inflight_request_body_bytes(INFLIGHT_LIMIT)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(config)
+        .interface(UploadServiceServer::new(UploadServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect(server: &RunningServer, max_request_body: usize) -> UploadServiceClient {
+    let config = ClientConfig::builder()
+        .admission(
+            ClientAdmissionConfig::builder()
+                .max_request_body_bytes(max_request_body)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let runtime = ClientRuntime::builder().config(config).build().unwrap();
+    UploadServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn payload(chunks: usize, size: usize) -> Vec<Bytes> {
+    (0..chunks)
+        .map(|index| Bytes::from(vec![(index % 251) as u8; size]))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn upload_streams_far_more_than_the_body_limit_in_bounded_chunks() {
+    let server = start_server().await;
+    // Client chunks of 1000 bytes are split to the client's own 100-byte limit on the wire.
+    let client = connect(&server, 100).await;
+
+    let chunks = payload(50, 1000);
+    let expected = chunks.iter().fold(0, |hash, chunk| checksum(hash, chunk));
+    let summary = client
+        .upload("bulk".to_owned(), BodyStream::from_chunks(chunks))
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(summary.name, "bulk");
+    assert_eq!(summary.bytes, 50_000);
+    assert_eq!(summary.checksum, expected);
+    assert!(
+        summary.largest_chunk <= CHUNK_LIMIT,
+        "server chunks are bounded by max_request_body_bytes, got {}",
+        summary.largest_chunk
+    );
+
+    let empty = client
+        .upload("empty".to_owned(), BodyStream::from_chunks([]))
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(empty.bytes, 0);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retained_chunks_are_bounded_by_the_inflight_request_budget() {
+    let server = start_server().await;
+    let client = connect(&server, CHUNK_LIMIT).await;
+
+    let fits = client
+        .hoard(BodyStream::from_chunks(payload(3, CHUNK_LIMIT)))
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(fits, 3 * CHUNK_LIMIT);
+
+    // The fifth chunk exceeds the server budget; the rest is only written after the server has
+    // rejected the upload, so it must be absorbed without hiding the early response.
+    let mut chunks = payload(100, CHUNK_LIMIT);
+    let rest = chunks.split_off(INFLIGHT_LIMIT / CHUNK_LIMIT + 1);
+    let rejected = stream::once(HOARD_REJECTED.notified()).filter_map(|()| async { None });
+    let body = stream::iter(chunks.into_iter().map(Ok))
+        .chain(rejected)
+        .chain(stream::iter(rest.into_iter().map(Ok)));
+    let error = client.hoard(BodyStream::new(body)).await.unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.category(), ErrorCategory::ResourceExhausted);
+    assert_eq!(error.code().as_str(), "body_byte_budget_exhausted");
+
+    // An upload that keeps writing while the server rejects it still sees the rejection.
+    let error = client
+        .hoard(BodyStream::from_chunks(payload(100, CHUNK_LIMIT)))
+        .await
+        .unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.code().as_str(), "body_byte_budget_exhausted");
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_failing_body_stream_aborts_the_upload_with_its_own_error() {
+    let server = start_server().await;
+    let client = connect(&server, CHUNK_LIMIT).await;
+
+    let chunks = stream::iter([
+        Ok(Bytes::from_static(b"first")),
+        Err(Error::application(
+            ErrorCategory::Unavailable,
+            "source_unavailable",
+            "upload source went away",
+        )
+        .unwrap()),
+    ]);
+    let error = client
+        .upload("broken".to_owned(), BodyStream::new(chunks))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code().as_str(), "source_unavailable");
+    assert_eq!(error.origin(), ErrorOrigin::Local);
+
+    // The aborted upload does not poison the connection pool.
+    let summary = client
+        .upload("after".to_owned(), BodyStream::from_chunks(payload(2, 8)))
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(summary.bytes, 16);
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,277 @@
+//! Real-socket coverage for private client trust roots, per-service overrides, and mutual TLS.
+
+mod support;
+
+use fusen_contract::ServiceSelector;
+use fusen_rs::{
+    ClientConfig, ClientErrorKind, ClientHttpConfig, ClientRuntime, ClientTlsConfig, Error,
+    HttpServerConfig, Response, RunningServer, Server, ServerConfig, ServerTlsConfig, interface,
+};
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use rustls::{
+    RootCertStore, ServerConfig as TlsServerConfig,
+    pki_types::{CertificateDer, PrivatePkcs8KeyDer},
+    server::WebPkiClientVerifier,
+};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use support::pem;
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_rustls::TlsAcceptor;
+
+#[interface(name = "client-tls")]
+trait ClientTlsService {
+    #[fusen_rs::method(method = "GET", path = "/client-tls/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+}
+
+struct ClientTlsServiceImpl;
+
+impl ClientTlsService for ClientTlsServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
+struct Identity {
+    chain: PathBuf,
+    key: PathBuf,
+    certificate: CertificateDer<'static>,
+    key_der: Vec<u8>,
+}
+
+struct PemDirectory(PathBuf);
+
+impl PemDirectory {
+    fn new(name: &str) -> Self {
+        let directory = std::env::temp_dir().join(format!(
+            "fusen-client-tls-{name}-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        Self(directory)
+    }
+
+    fn identity(&self, name: &str) -> Identity {
+        let CertifiedKey { cert, key_pair } =
+            generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+        let chain = self.0.join(format!("{name}.pem"));
+        let key = self.0.join(format!("{name}.key"));
+        std::fs::write(&chain, pem("CERTIFICATE", cert.der())).unwrap();
+        std::fs::write(&key, pem("PRIVATE KEY", &key_pair.serialize_der())).unwrap();
+        Identity {
+            chain,
+            key,
+            certificate: cert.der().clone(),
+            key_der: key_pair.serialize_der(),
+        }
+    }
+}
+
+impl Drop for PemDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn selector() -> ServiceSelector {
+    ServiceSelector::new("client-tls", None, None).unwrap()
+}
+
+fn runtime(http: ClientHttpConfig) -> ClientRuntime {
+    ClientRuntime::builder()
+        .config(ClientConfig::builder().http(http).build().unwrap())
+        .build()
+        .unwrap()
+}
+
+async fn ping(runtime: &ClientRuntime, endpoint: &str) -> Result<String, Error> {
+    let client = ClientTlsServiceClient::builder(runtime)
+        .direct(endpoint)
+        .connect()
+        .await
+        .unwrap();
+    client.ping().await.map(Response::into_body)
+}
+
+async fn start_tls_server(identity: &Identity) -> RunningServer {
+    let tls = ServerTlsConfig::builder(&identity.chain, &identity.key)
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .http(HttpServerConfig::builder().tls(tls).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .interface(ClientTlsServiceServer::new(ClientTlsServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+// Terminates mutual TLS in front of a plaintext server so the test controls client verification.
+async fn spawn_mtls_proxy(
+    server: &Identity,
+    client: &Identity,
+    upstream: SocketAddr,
+) -> (SocketAddr, JoinHandle<()>) {
+    let mut roots = RootCertStore::empty();
+    roots.add(client.certificate.clone()).unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .unwrap();
+    let mut config = TlsServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![server.certificate.clone()],
+            PrivatePkcs8KeyDer::from(server.key_der.clone()).into(),
+        )
+        .unwrap();
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let task = tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                return;
+            };
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut tls) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut upstream = tokio::net::TcpStream::connect(upstream).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut tls, &mut upstream).await;
+            });
+        }
+    });
+    (address, task)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn private_roots_trust_a_server_that_webpki_roots_reject() {
+    let pem = PemDirectory::new("roots");
+    let identity = pem.identity("server");
+    let server = start_tls_server(&identity).await;
+    let endpoint = format!("https://{}", server.local_addr());
+
+    let public = runtime(ClientHttpConfig::default());
+    assert!(ping(&public, &endpoint).await.is_err());
+
+    let private = ClientTlsConfig::builder()
+        .webpki_roots(false)
+        .root_certificate_path(&identity.chain)
+        .build()
+        .unwrap();
+    let overridden = runtime(
+        ClientHttpConfig::builder()
+            .tls_override(selector(), private.clone())
+            .build()
+            .unwrap(),
+    );
+    assert_eq!(ping(&overridden, &endpoint).await.unwrap(), "pong");
+
+    let default = runtime(ClientHttpConfig::builder().tls(private).build().unwrap());
+    assert_eq!(ping(&default, &endpoint).await.unwrap(), "pong");
+
+    for runtime in [public, overridden, default] {
+        runtime.shutdown().await.unwrap();
+    }
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_identity_is_presented_for_mutual_tls() {
+    let pem = PemDirectory::new("mtls");
+    let server_identity = pem.identity("server");
+    let client_identity = pem.identity("client");
+    let upstream = Server::builder("127.0.0.1:0")
+        .interface(ClientTlsServiceServer::new(ClientTlsServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let (proxy, proxy_task) =
+        spawn_mtls_proxy(&server_identity, &client_identity, upstream.local_addr()).await;
+    let endpoint = format!("https://{proxy}");
+
+    let anonymous = runtime(
+        ClientHttpConfig::builder()
+            .tls(
+                ClientTlsConfig::builder()
+                    .root_certificate_path(&server_identity.chain)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap(),
+    );
+    assert!(ping(&anonymous, &endpoint).await.is_err());
+
+    let mutual = runtime(
+        ClientHttpConfig::builder()
+            .tls(
+                ClientTlsConfig::builder()
+                    .root_certificate_path(&server_identity.chain)
+                    .client_identity(&client_identity.chain, &client_identity.key)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap(),
+    );
+    assert_eq!(ping(&mutual, &endpoint).await.unwrap(), "pong");
+
+    anonymous.shutdown().await.unwrap();
+    mutual.shutdown().await.unwrap();
+    proxy_task.abort();
+    upstream.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_client_tls_material_fails_runtime_build() {
+    let pem = PemDirectory::new("invalid");
+    let identity = pem.identity("client");
+    let other = pem.identity("other");
+    let garbage = pem.0.join("garbage.pem");
+    std::fs::write(&garbage, "not a pem document").unwrap();
+
+    for (tls, message) in [
+        (
+            ClientTlsConfig::builder().root_certificate_path(&garbage),
+            "client TLS root certificate file contains no certificates",
+        ),
+        (
+            ClientTlsConfig::builder().root_certificate_path(pem.0.join("missing.pem")),
+            "failed to read the client TLS root certificate file",
+        ),
+        (
+            ClientTlsConfig::builder().client_identity(&identity.chain, &garbage),
+            "client TLS private key file contains no PEM private key",
+        ),
+        (
+            ClientTlsConfig::builder().client_identity(&identity.chain, &other.key),
+            "client TLS certificate chain and private key are inconsistent",
+        ),
+    ] {
+        let http = ClientHttpConfig::builder()
+            .tls_override(selector(), tls.build().unwrap())
+            .build()
+            .unwrap();
+        let error = ClientRuntime::builder()
+            .config(ClientConfig::builder().http(http).build().unwrap())
+            .build()
+            .err()
+            .expect("invalid client TLS material must fail the runtime build");
+        assert_eq!(error.kind(), ClientErrorKind::Build);
+        assert_eq!(error.message(), message);
+    }
+}
@@ -0,0 +1,416 @@
+//! Real-socket coverage for negotiated response and request body compression.
+
+use bytes::Bytes;
+use fusen_rs::{
+    ClientAdmissionConfig, ClientCompressionConfig, ClientConfig, ClientHttpConfig,
+    ClientRequestCompressionConfig, ClientRuntime, ContentCoding, Context, Error, ErrorCategory,
+    ErrorOrigin, HttpServerConfig, Interceptor, InterceptorFuture, Next, Response, RunningServer,
+    Server, ServerCompressionConfig, ServerConfig, ServerRequestConfig, interface,
+};
+use http::{
+    HeaderMap, Request, Response as HttpResponse,
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, VARY},
+};
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::{body::Incoming, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    io::{Read, Write},
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+use tokio::{net::TcpListener, sync::mpsc};
+
+#[interface(name = "compression-e2e")]
+trait ReportService {
+    #[fusen_rs::method(method = "GET", path = "/reports")]
+    async fn report(&self, #[param(query)] size: usize) -> Result<Response<String>, Error>;
+
+    #[fusen_rs::method(method = "POST", path = "/reports")]
+    async fn upload(&self, #[param(body)] report: String) -> Result<Response<usize>, Error>;
+}
+
+struct ReportServiceImpl;
+
+impl ReportService for ReportServiceImpl {
+    async fn report(&self, size: usize) -> Result<Response<String>, Error> {
+        Ok(Response::new(report(size)))
+    }
+
+    async fn upload(&self, report: String) -> Result<Response<usize>, Error> {
+        Ok(Response::new(report.len()))
+    }
+}
+
+fn report(size: usize) -> String {
+    "row;".repeat(size / 4)
+}
+
+async fn start_server(codings: &[ContentCoding]) -> RunningServer {
+    let compression = ServerCompressionConfig::builder()
+        .codings(codings.iter().copied())
+        .min_response_bytes(256)
+        .build()
+        .unwrap();
+    let config = ServerConfig::builder()
+        .http(
+            HttpServerConfig::builder()
+                .compression(compression)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(config)
+        .interface(ReportServiceServer::new(ReportServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect(addr: SocketAddr, max_response_body: usize) -> ReportServiceClient {
+    let config = ClientConfig::builder()
+        .admission(
+            ClientAdmissionConfig::builder()
+                .max_response_body_bytes(max_response_body)
+                .build()
+                .unwrap(),
+        )
+        .http(
+            ClientHttpConfig::builder()
+                .compression(ClientCompressionConfig::builder().build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let runtime = ClientRuntime::builder().config(config).build().unwrap();
+    ReportServiceClient::builder(&runtime)
+        .direct(format!("http://{addr}"))
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn raw_get(
+    addr: SocketAddr,
+    path: &str,
+    accept_encoding: Option<&str>,
+) -> (HeaderMap, Bytes) {
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+    let mut request = Request::get(path).header("host", addr.to_string());
+    if let Some(value) = accept_encoding {
+        request = request.header(ACCEPT_ENCODING, value);
+    }
+    let response = sender
+        .send_request(request.body(Empty::<Bytes>::new()).unwrap())
+        .await
+        .unwrap();
+    let (parts, body) = response.into_parts();
+    (parts.headers, body.collect().await.unwrap().to_bytes())
+}
+
+async fn raw_upload(addr: SocketAddr, content_encoding: &str, body: Bytes) -> (u16, Value) {
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+    let request = Request::post("/reports")
+        .header("host", addr.to_string())
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_ENCODING, content_encoding)
+        .body(Full::new(body))
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    let status = response.status().as_u16();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn decode(coding: &str, body: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    match coding {
+        "gzip" => flate2::read::GzDecoder::new(body)
+            .read_to_end(&mut decoded)
+            .unwrap(),
+        "br" => brotli::Decompressor::new(body, 4096)
+            .read_to_end(&mut decoded)
+            .unwrap(),
+        "zstd" => zstd::stream::read::Decoder::new(body)
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap(),
+        other => panic!("unexpected coding {other}"),
+    };
+    decoded
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_applies_its_preferred_accepted_coding_above_the_threshold() {
+    let server = start_server(&[
+        ContentCoding::Zstd,
+        ContentCoding::Brotli,
+        ContentCoding::Gzip,
+    ])
+    .await;
+    let addr = server.local_addr();
+    let expected = serde_json::to_vec(&report(64 * 1024)).unwrap();
+
+    for (accept, coding) in [
+        ("gzip, br;q=0.5", "br"),
+        ("gzip", "gzip"),
+        ("zstd;q=0, *", "br"),
+        ("*", "zstd"),
+    ] {
+        let (headers, body) = raw_get(addr, "/reports?size=65536", Some(accept)).await;
+        assert_eq!(
+            headers[CONTENT_ENCODING], coding,
+            "Accept-Encoding: {accept}"
+        );
+        assert_eq!(headers[VARY], "accept-encoding");
+        assert!(body.len() < expected.len() / 10);
+        assert_eq!(decode(coding, &body), expected);
+    }
+
+    for (path, accept) in [
+        ("/reports?size=65536", None),
+        ("/reports?size=65536", Some("identity")),
+        ("/reports?size=64", Some("gzip")),
+    ] {
+        let (headers, _) = raw_get(addr, path, accept).await;
+        assert!(!headers.contains_key(CONTENT_ENCODING), "{path} {accept:?}");
+        assert_eq!(headers[VARY], "accept-encoding");
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_negotiates_and_decodes_within_the_response_limit() {
+    let server = start_server(&[ContentCoding::Gzip]).await;
+    let client = connect(server.local_addr(), 128 * 1024).await;
+
+    let response = client.report(100_000).await.unwrap();
+    assert!(!response.headers().contains_key(CONTENT_ENCODING));
+    assert_eq!(response.into_body(), report(100_000));
+
+    // The wire body is tiny, but the decoded body still has to fit the client limit.
+    let error = client.report(200_000).await.unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::PayloadTooLarge);
+    assert_eq!(error.code().as_str(), "response_too_large");
+
+    server.shutdown().await.unwrap();
+}
+
+async fn spawn_coded_fixture(
+    content_encoding: &'static str,
+    body: Bytes,
+) -> (SocketAddr, mpsc::UnboundedReceiver<(HeaderMap, Bytes)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (captured_tx, captured_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let body = body.clone();
+            let captured = captured_tx.clone();
+            let service = service_fn(move |request: Request<Incoming>| {
+                let body = body.clone();
+                let captured = captured.clone();
+                async move {
+                    let (parts, request_body) = request.into_parts();
+                    let request_body = request_body.collect().await.unwrap().to_bytes();
+                    let _ = captured.send((parts.headers.clone(), request_body));
+                    let mut response = HttpResponse::builder()
+                        .header(CONTENT_TYPE, "application/json")
+                        .header(CONTENT_ENCODING, content_encoding)
+                        .body(Full::new(body))
+                        .unwrap();
+                    if let Some(request_id) = parts.headers.get("x-request-id") {
+                        response
+                            .headers_mut()
+                            .insert("x-request-id", request_id.clone());
+                    }
+                    Ok::<_, Infallible>(response)
+                }
+            });
+            tokio::spawn(
+                hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service),
+            );
+        }
+    });
+    (addr, captured_rx)
+}
+
+fn gzip(body: &[u8]) -> Bytes {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(body).unwrap();
+    Bytes::from(encoder.finish().unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compression_bombs_and_unknown_codings_fail_locally() {
+    // 64 MiB of zeros compresses to about 64 KiB.
+    let bomb = gzip(&vec![b' '; 64 * 1024 * 1024]);
+    let (addr, mut captured) = spawn_coded_fixture("gzip", bomb).await;
+    let client = connect(addr, 1024 * 1024).await;
+    let error = client.report(1).await.unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Local);
+    assert_eq!(error.code().as_str(), "response_too_large");
+    assert_eq!(
+        captured.recv().await.unwrap().0[ACCEPT_ENCODING],
+        "zstd, br, gzip"
+    );
+
+    let (addr, _captured) = spawn_coded_fixture("gzip", Bytes::from_static(b"\"plain\"")).await;
+    let error = connect(addr, 1024).await.report(1).await.unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.code().as_str(), "invalid_content_encoding");
+
+    let (addr, _captured) = spawn_coded_fixture("deflate", Bytes::from_static(b"x")).await;
+    let error = connect(addr, 1024).await.report(1).await.unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.code().as_str(), "unsupported_content_encoding");
+}
+
+struct CountHeads(Arc<AtomicUsize>);
+
+impl Interceptor for CountHeads {
+    fn intercept<'a>(&'a self, context: Context, next: Next<'a>) -> InterceptorFuture<'a> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        next.run(context)
+    }
+}
+
+async fn start_upload_server(heads: Arc<AtomicUsize>) -> RunningServer {
+    let config = ServerConfig::builder()
+        .request(
+            ServerRequestConfig::builder()
+                .max_request_body_bytes(64 * 1024)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(config)
+        .interface(ReportServiceServer::new(ReportServiceImpl).head_interceptor(CountHeads(heads)))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect_compressing(addr: SocketAddr, coding: ContentCoding) -> ReportServiceClient {
+    let config = ClientConfig::builder()
+        .http(
+            ClientHttpConfig::builder()
+                .request_compression(
+                    ClientRequestCompressionConfig::builder()
+                        .coding(coding)
+                        .min_request_bytes(1024)
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let runtime = ClientRuntime::builder().config(config).build().unwrap();
+    ReportServiceClient::builder(&runtime)
+        .direct(format!("http://{addr}"))
+        .connect()
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_compresses_large_request_bodies_above_the_threshold() {
+    let (addr, mut captured) = spawn_coded_fixture("identity", Bytes::from_static(b"1")).await;
+    let client = connect_compressing(addr, ContentCoding::Zstd).await;
+
+    client.upload(report(32 * 1024)).await.unwrap();
+    let (headers, body) = captured.recv().await.unwrap();
+    assert_eq!(headers[CONTENT_ENCODING], "zstd");
+    assert_eq!(
+        decode("zstd", &body),
+        serde_json::to_vec(&report(32 * 1024)).unwrap()
+    );
+
+    client.upload(report(512)).await.unwrap();
+    let (headers, body) = captured.recv().await.unwrap();
+    assert!(!headers.contains_key(CONTENT_ENCODING));
+    assert_eq!(body, serde_json::to_vec(&report(512)).unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_decodes_request_bodies_within_the_request_limit() {
+    let heads = Arc::new(AtomicUsize::new(0));
+    let server = start_upload_server(heads.clone()).await;
+    for coding in [
+        ContentCoding::Gzip,
+        ContentCoding::Zstd,
+        ContentCoding::Brotli,
+    ] {
+        let client = connect_compressing(server.local_addr(), coding).await;
+        assert_eq!(
+            client.upload(report(60 * 1024)).await.unwrap().into_body(),
+            60 * 1024
+        );
+
+        // The coded body is tiny, but its decoded size exceeds the server limit.
+        let error = client.upload(report(1024 * 1024)).await.unwrap_err();
+        assert_eq!(error.origin(), ErrorOrigin::Remote);
+        assert_eq!(error.category(), ErrorCategory::PayloadTooLarge);
+        assert_eq!(error.code().as_str(), "payload_too_large");
+    }
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_rejects_unsupported_request_codings_before_head_interceptors() {
+    let heads = Arc::new(AtomicUsize::new(0));
+    let server = start_upload_server(heads.clone()).await;
+    let addr = server.local_addr();
+    let body = Bytes::from(serde_json::to_vec(&report(4096)).unwrap());
+
+    for coding in ["deflate", "gzip, br"] {
+        let (status, problem) = raw_upload(addr, coding, body.clone()).await;
+        assert_eq!(status, 415, "Content-Encoding: {coding}");
+        assert_eq!(problem["code"], "unsupported_content_encoding");
+        assert_eq!(
+            problem["type"],
+            "urn:fusen:error:unsupported-media-type:unsupported_content_encoding"
+        );
+    }
+    assert_eq!(heads.load(Ordering::SeqCst), 0);
+
+    let (status, problem) = raw_upload(addr, "gzip", body.clone()).await;
+    assert_eq!(status, 400);
+    assert_eq!(problem["code"], "invalid_content_encoding");
+
+    let (status, problem) = raw_upload(addr, "gzip", gzip(&vec![b' '; 16 * 1024 * 1024])).await;
+    assert_eq!(status, 413);
+    assert_eq!(problem["code"], "payload_too_large");
+
+    let (status, problem) = raw_upload(addr, "identity", body).await;
+    assert_eq!(status, 200);
+    assert_eq!(problem, 4096);
+    assert_eq!(heads.load(Ordering::SeqCst), 3);
+    server.shutdown().await.unwrap();
+}
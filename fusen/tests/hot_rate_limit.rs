@@ -0,0 +1,105 @@
+//! Hot rate limit quotas through `fusen-config`.
+
+mod support;
+
+use fusen_config::{ConfigDocument, ConfigFormat, HotConfig, provider::ConfigPublisher};
+use fusen_rs::{
+    ClientConfig, ClientRuntime, Error, RateLimitConfig, Response, RetryConfig, RetryHint, Server,
+    ServerConfig, ServerRequestConfig, interface,
+};
+use std::time::Duration;
+use support::{hot_config, wait_for_rejection};
+
+#[interface(name = "hot-rate-limit")]
+trait QuotaService {
+    #[fusen_rs::method(method = "GET", path = "/quota/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+}
+
+struct QuotaServiceImpl;
+
+impl QuotaService for QuotaServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
+fn quotas(content: &str) -> ConfigDocument {
+    ConfigDocument::new(ConfigFormat::Toml, content)
+}
+
+async fn hot_quotas(initial: &str) -> (HotConfig<RateLimitConfig>, ConfigPublisher) {
+    hot_config("rate-limit", quotas(initial)).await
+}
+
+#[tokio::test]
+async fn replaced_quotas_apply_to_the_next_request_and_invalid_ones_are_ignored() {
+    let (mut hot, publisher) =
+        hot_quotas("[[rules]]\npermits = 1\nperiod_ms = 60000\nmethod = 'ping'\n").await;
+    let server = Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .request(
+                    ServerRequestConfig::builder()
+                        .hot_rate_limit(hot.clone())
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .interface(QuotaServiceServer::new(QuotaServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let runtime = ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let client = QuotaServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .connect()
+        .await
+        .unwrap();
+
+    client.ping().await.unwrap();
+    let limited = client.ping().await.unwrap_err();
+    assert_eq!(limited.code().as_str(), "rate_limited");
+    assert_eq!(
+        limited.retry_hint(),
+        RetryHint::After(Duration::from_secs(60))
+    );
+
+    // New quotas start from full buckets.
+    publisher
+        .publish(quotas("[[rules]]\npermits = 2\nperiod_ms = 60000\n"))
+        .unwrap();
+    hot.changed().await.unwrap();
+    client.ping().await.unwrap();
+    client.ping().await.unwrap();
+    assert_eq!(
+        client.ping().await.unwrap_err().retry_hint(),
+        RetryHint::After(Duration::from_secs(30))
+    );
+
+    publisher
+        .publish(quotas("[[rules]]\npermits = 0\n"))
+        .unwrap();
+    wait_for_rejection(&hot).await;
+    assert_eq!(hot.current().rules()[0].permits(), 2);
+    assert_eq!(
+        client.ping().await.unwrap_err().code().as_str(),
+        "rate_limited"
+    );
+
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,279 @@
+//! Hot certificate rotation through `fusen-config` for the server listener and client mTLS.
+
+mod support;
+
+use bytes::Bytes;
+use fusen_config::{ConfigDocument, ConfigFormat, HotConfig, provider::ConfigPublisher};
+use fusen_rs::{
+    ClientConfig, ClientHttpConfig, ClientRuntime, ClientTlsConfig, Error, HotServerTlsConfig,
+    HttpServerConfig, Response, RunningServer, Server, ServerConfig, TlsCertificate, interface,
+};
+use http::{Request, StatusCode};
+use http_body_util::{Empty, Full};
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use rustls::{
+    ClientConfig as TlsClientConfig, RootCertStore, ServerConfig as TlsServerConfig,
+    pki_types::{CertificateDer, PrivatePkcs8KeyDer, ServerName},
+    server::WebPkiClientVerifier,
+};
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+use support::{hot_config, pem, wait_for_rejection};
+use tokio::{net::TcpListener, net::TcpStream, task::JoinHandle};
+use tokio_rustls::{TlsAcceptor, TlsConnector, client::TlsStream};
+
+#[interface(name = "hot-tls")]
+trait HotTlsService {
+    #[fusen_rs::method(method = "GET", path = "/hot-tls/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+}
+
+struct HotTlsServiceImpl;
+
+impl HotTlsService for HotTlsServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
+struct Material {
+    certificate: CertificateDer<'static>,
+    key_der: Vec<u8>,
+    document: ConfigDocument,
+}
+
+fn material() -> Material {
+    let CertifiedKey { cert, key_pair } =
+        generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+    let document = ConfigDocument::new(
+        ConfigFormat::Toml,
+        format!(
+            "certificate_chain = '''\n{}'''\nprivate_key = '''\n{}'''\n",
+            pem("CERTIFICATE", cert.der()),
+            pem("PRIVATE KEY", &key_pair.serialize_der())
+        ),
+    );
+    Material {
+        certificate: cert.der().clone(),
+        key_der: key_pair.serialize_der(),
+        document,
+    }
+}
+
+async fn hot_certificate(initial: &Material) -> (HotConfig<TlsCertificate>, ConfigPublisher) {
+    hot_config("tls", initial.document.clone()).await
+}
+
+async fn start_hot_server(certificate: HotConfig<TlsCertificate>) -> RunningServer {
+    let tls = HotServerTlsConfig::builder(certificate).build().unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .http(HttpServerConfig::builder().hot_tls(tls).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .interface(HotTlsServiceServer::new(HotTlsServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect(server: &RunningServer, trusted: &[&Material]) -> TlsStream<TcpStream> {
+    let mut roots = RootCertStore::empty();
+    for material in trusted {
+        roots.add(material.certificate.clone()).unwrap();
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = TlsClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let stream = TcpStream::connect(server.local_addr()).await.unwrap();
+    TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("127.0.0.1").unwrap(), stream)
+        .await
+        .unwrap()
+}
+
+fn leaf(stream: &TlsStream<TcpStream>) -> CertificateDer<'static> {
+    stream.get_ref().1.peer_certificates().unwrap()[0].clone()
+}
+
+async fn ping_over(
+    sender: &mut hyper::client::conn::http1::SendRequest<Empty<Bytes>>,
+) -> StatusCode {
+    let request = Request::get("https://127.0.0.1/hot-tls/ping")
+        .body(Empty::new())
+        .unwrap();
+    sender.send_request(request).await.unwrap().status()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_handshakes_use_rotated_certificates_and_keep_the_last_good_one() {
+    let first = material();
+    let second = material();
+    let (mut hot, publisher) = hot_certificate(&first).await;
+    let server = start_hot_server(hot.clone()).await;
+
+    let established = connect(&server, &[&first, &second]).await;
+    assert_eq!(leaf(&established), first.certificate);
+    let (mut established, connection) =
+        hyper::client::conn::http1::handshake(TokioIo::new(established))
+            .await
+            .unwrap();
+    let established_connection = tokio::spawn(connection);
+    assert_eq!(ping_over(&mut established).await, StatusCode::OK);
+
+    publisher.publish(second.document.clone()).unwrap();
+    hot.changed().await.unwrap();
+    assert_eq!(
+        leaf(&connect(&server, &[&first, &second]).await),
+        second.certificate
+    );
+    assert_eq!(ping_over(&mut established).await, StatusCode::OK);
+
+    publisher
+        .publish(ConfigDocument::new(
+            ConfigFormat::Toml,
+            "certificate_chain = 'not pem'\nprivate_key = 'not pem'\n",
+        ))
+        .unwrap();
+    let error = wait_for_rejection(&hot).await;
+    assert!(!error.to_string().contains("not pem"));
+    assert_eq!(
+        leaf(&connect(&server, &[&first, &second]).await),
+        second.certificate
+    );
+
+    drop(established);
+    established_connection.abort();
+    server.shutdown().await.unwrap();
+}
+
+// Serves one request per connection and records the client certificate that authenticated it.
+async fn spawn_mtls_server(
+    server: &Material,
+    clients: &[&Material],
+) -> (
+    String,
+    Arc<Mutex<Vec<CertificateDer<'static>>>>,
+    JoinHandle<()>,
+) {
+    let mut roots = RootCertStore::empty();
+    for client in clients {
+        roots.add(client.certificate.clone()).unwrap();
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .unwrap();
+    let mut config = TlsServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![server.certificate.clone()],
+            PrivatePkcs8KeyDer::from(server.key_der.clone()).into(),
+        )
+        .unwrap();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("https://{}", listener.local_addr().unwrap());
+    let presented = Arc::new(Mutex::new(Vec::new()));
+    let recorded = presented.clone();
+    let task = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let Ok(stream) = acceptor.accept(stream).await else {
+                continue;
+            };
+            let certificate = stream.get_ref().1.peer_certificates().unwrap()[0].clone();
+            recorded.lock().unwrap().push(certificate);
+            let service = service_fn(|_| async {
+                Ok::<_, Infallible>(
+                    http::Response::builder()
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .body(Full::new(Bytes::from_static(b"\"pong\"")))
+                        .unwrap(),
+                )
+            });
+            tokio::spawn(
+                hyper::server::conn::http1::Builder::new()
+                    .keep_alive(false)
+                    .serve_connection(TokioIo::new(stream), service),
+            );
+        }
+    });
+    (endpoint, presented, task)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_presents_the_rotated_identity_on_new_connections() {
+    let server = material();
+    let first = material();
+    let second = material();
+    let (endpoint, presented, server_task) = spawn_mtls_server(&server, &[&first, &second]).await;
+    let (mut hot, publisher) = hot_certificate(&first).await;
+
+    let server_root = std::env::temp_dir().join(format!(
+        "fusen-hot-tls-root-{}.pem",
+        uuid::Uuid::new_v4().simple()
+    ));
+    std::fs::write(&server_root, pem("CERTIFICATE", &server.certificate)).unwrap();
+    let tls = ClientTlsConfig::builder()
+        .webpki_roots(false)
+        .root_certificate_path(&server_root)
+        .hot_client_identity(hot.clone())
+        .build()
+        .unwrap();
+    let runtime = ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .http(ClientHttpConfig::builder().tls(tls).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    std::fs::remove_file(&server_root).unwrap();
+    let client = HotTlsServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(client.ping().await.unwrap().into_body(), "pong");
+    publisher.publish(second.document.clone()).unwrap();
+    hot.changed().await.unwrap();
+    assert_eq!(client.ping().await.unwrap().into_body(), "pong");
+
+    publisher
+        .publish(ConfigDocument::new(
+            ConfigFormat::Toml,
+            "private_key = ''\n",
+        ))
+        .unwrap();
+    wait_for_rejection(&hot).await;
+    assert_eq!(client.ping().await.unwrap().into_body(), "pong");
+
+    assert_eq!(
+        *presented.lock().unwrap(),
+        [
+            first.certificate.clone(),
+            second.certificate.clone(),
+            second.certificate.clone()
+        ]
+    );
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server_task.abort();
+}
@@ -0,0 +1,98 @@
+//! Hot traffic routing rules through `fusen-config`.
+
+mod support;
+
+use fusen_config::{ConfigDocument, ConfigFormat, HotConfig, provider::ConfigPublisher};
+use fusen_rs::{
+    ClientConfig, ClientRuntime, Error, Response, RetryConfig, Server, TrafficRouter, TrafficRules,
+    contract::Metadata, interface,
+};
+use support::{hot_config, wait_for_rejection};
+
+#[interface(name = "hot-traffic-rules")]
+trait ReleaseService {
+    #[fusen_rs::method(method = "GET", path = "/release/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+}
+
+struct ReleaseServiceImpl;
+
+impl ReleaseService for ReleaseServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
+fn rules(content: &str) -> ConfigDocument {
+    ConfigDocument::new(ConfigFormat::Toml, content)
+}
+
+fn only(version: &str) -> String {
+    format!(
+        "[[rules]]\nname = 'release'\nmethod = 'ping'\nstrict = true\n\
+         routes = [{{ metadata = {{ version = '{version}' }} }}]\n"
+    )
+}
+
+async fn hot_rules(initial: &str) -> (HotConfig<TrafficRules>, ConfigPublisher) {
+    hot_config("traffic-rules", rules(initial)).await
+}
+
+#[tokio::test]
+async fn replaced_rules_route_the_next_call_and_invalid_revisions_are_ignored() {
+    let (mut hot, publisher) = hot_rules(&only("canary")).await;
+    let server = Server::builder("127.0.0.1:0")
+        .interface(ReleaseServiceServer::new(ReleaseServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let runtime = ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let client = ReleaseServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .direct_metadata(Metadata::from([(
+            "version".to_owned(),
+            "stable".to_owned(),
+        )]))
+        .instance_router(TrafficRouter::hot(hot.clone()))
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        client.ping().await.unwrap_err().code().as_str(),
+        "no_instances"
+    );
+
+    publisher.publish(rules(&only("stable"))).unwrap();
+    hot.changed().await.unwrap();
+    client.ping().await.unwrap();
+
+    // Weights that do not add up to 100 reject the whole revision.
+    publisher
+        .publish(rules(
+            "[[rules]]\nname = 'release'\nstrict = true\nroutes = [\n\
+             { weight = 50, metadata = { version = 'canary' } },\n\
+             { weight = 40, metadata = { version = 'stable' } },\n]\n",
+        ))
+        .unwrap();
+    wait_for_rejection(&hot).await;
+    assert_eq!(
+        hot.current().rules()[0].routes()[0].metadata()["version"],
+        "stable"
+    );
+    client.ping().await.unwrap();
+
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
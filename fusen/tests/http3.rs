@@ -0,0 +1,270 @@
+//! Real-socket coverage for the HTTP/3 client transport and its capability-based selection.
+
+mod support;
+
+use bytes::{Buf, Bytes};
+use fusen_rs::{
+    ClientConfig, ClientHttpConfig, ClientRuntime, ClientTlsConfig, Error, HttpServerConfig,
+    Response, RunningServer, Server, ServerConfig, ServerTlsConfig,
+    contract::{EndpointCapabilities, HttpBindingId, HttpVersionPolicy, HttpVersionSet},
+    interface,
+};
+use http::{StatusCode, Version, header::CONTENT_TYPE};
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use rustls::{
+    ServerConfig as TlsServerConfig,
+    pki_types::{CertificateDer, PrivatePkcs8KeyDer},
+};
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use support::pem;
+use tokio::task::JoinHandle;
+
+#[interface(name = "http3-e2e")]
+trait QuicService {
+    #[fusen_rs::method(method = "GET", path = "/quic/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+
+    #[fusen_rs::method(method = "POST", path = "/quic/echo")]
+    async fn echo(&self, text: String) -> Result<Response<String>, Error>;
+}
+
+struct TcpServiceImpl;
+
+impl QuicService for TcpServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("tcp".to_owned()))
+    }
+
+    async fn echo(&self, text: String) -> Result<Response<String>, Error> {
+        Ok(Response::new(text))
+    }
+}
+
+struct Identity {
+    directory: PathBuf,
+    chain: PathBuf,
+    key: PathBuf,
+    certificate: CertificateDer<'static>,
+    key_der: Vec<u8>,
+}
+
+impl Identity {
+    fn generate() -> Self {
+        let CertifiedKey { cert, key_pair } =
+            generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+        let directory =
+            std::env::temp_dir().join(format!("fusen-http3-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let chain = directory.join("chain.pem");
+        let key = directory.join("key.pem");
+        std::fs::write(&chain, pem("CERTIFICATE", cert.der())).unwrap();
+        std::fs::write(&key, pem("PRIVATE KEY", &key_pair.serialize_der())).unwrap();
+        Self {
+            directory,
+            chain,
+            key,
+            certificate: cert.der().clone(),
+            key_der: key_pair.serialize_der(),
+        }
+    }
+}
+
+impl Drop for Identity {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
+
+async fn start_tcp_server(identity: &Identity) -> RunningServer {
+    let tls = ServerTlsConfig::builder(&identity.chain, &identity.key)
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .http(HttpServerConfig::builder().tls(tls).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .interface(QuicServiceServer::new(TcpServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+type Seen = Arc<Mutex<Vec<(String, String, Bytes)>>>;
+
+// A minimal http-json-v1 peer over QUIC that records each request and always answers "quic".
+fn spawn_quic_server(identity: &Identity, address: SocketAddr) -> (JoinHandle<()>, Seen) {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut tls = TlsServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![identity.certificate.clone()],
+            PrivatePkcs8KeyDer::from(identity.key_der.clone()).into(),
+        )
+        .unwrap();
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+    let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls).unwrap();
+    let endpoint =
+        quinn::Endpoint::server(quinn::ServerConfig::with_crypto(Arc::new(crypto)), address)
+            .unwrap();
+    let seen = Seen::default();
+    let recorded = seen.clone();
+    let task = tokio::spawn(async move {
+        while let Some(incoming) = endpoint.accept().await {
+            let seen = recorded.clone();
+            tokio::spawn(async move {
+                let connection = incoming.await.unwrap();
+                let mut connection =
+                    h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(connection))
+                        .await
+                        .unwrap();
+                while let Ok(Some(resolver)) = connection.accept().await {
+                    let seen = seen.clone();
+                    tokio::spawn(async move {
+                        let (request, mut stream) = resolver.resolve_request().await.unwrap();
+                        let mut body = Vec::new();
+                        while let Some(mut chunk) = stream.recv_data().await.unwrap() {
+                            body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+                        }
+                        seen.lock().unwrap().push((
+                            request.method().to_string(),
+                            request.uri().path().to_owned(),
+                            Bytes::from(body),
+                        ));
+                        let response = http::Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(())
+                            .unwrap();
+                        stream.send_response(response).await.unwrap();
+                        stream
+                            .send_data(Bytes::from_static(b"\"quic\""))
+                            .await
+                            .unwrap();
+                        stream.finish().await.unwrap();
+                    });
+                }
+            });
+        }
+    });
+    (task, seen)
+}
+
+fn runtime(identity: &Identity) -> ClientRuntime {
+    let tls = ClientTlsConfig::builder()
+        .webpki_roots(false)
+        .root_certificate_path(&identity.chain)
+        .build()
+        .unwrap();
+    ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .http(ClientHttpConfig::builder().tls(tls).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
+
+fn capabilities(versions: &[Version]) -> EndpointCapabilities {
+    EndpointCapabilities::new(
+        HttpVersionSet::new(versions.iter().copied()).unwrap(),
+        [HttpBindingId::default()],
+        false,
+    )
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn advertised_http3_is_preferred_and_http2_serves_everything_else() {
+    let identity = Identity::generate();
+    let tcp = start_tcp_server(&identity).await;
+    // QUIC listens on the UDP port matching the TCP listener, as an H3-capable endpoint would.
+    let (quic, seen) = spawn_quic_server(&identity, tcp.local_addr());
+    let endpoint = format!("https://{}", tcp.local_addr());
+    let runtime = runtime(&identity);
+
+    let http3 = QuicServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .direct_capabilities(capabilities(&[
+            Version::HTTP_11,
+            Version::HTTP_2,
+            Version::HTTP_3,
+        ]))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(http3.ping().await.unwrap().into_body(), "quic");
+    assert_eq!(
+        http3.echo("uploaded".to_owned()).await.unwrap().into_body(),
+        "quic"
+    );
+    assert_eq!(http3.ping().await.unwrap().into_body(), "quic");
+
+    let fallback = QuicServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .direct_capabilities(capabilities(&[Version::HTTP_11, Version::HTTP_2]))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(fallback.ping().await.unwrap().into_body(), "tcp");
+
+    let pinned = QuicServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .http_version_policy(HttpVersionPolicy::Http2)
+        .direct_capabilities(capabilities(&[Version::HTTP_2, Version::HTTP_3]))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(pinned.ping().await.unwrap().into_body(), "tcp");
+
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(
+        seen.iter()
+            .map(|(method, path, _)| (method.as_str(), path.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("GET", "/quic/ping"),
+            ("POST", "/quic/echo"),
+            ("GET", "/quic/ping")
+        ]
+    );
+    assert!(seen[0].2.is_empty());
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&seen[1].2).unwrap(),
+        serde_json::json!({ "text": "uploaded" })
+    );
+
+    runtime.shutdown().await.unwrap();
+    quic.abort();
+    tcp.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn http3_policy_requires_an_advertising_endpoint() {
+    let identity = Identity::generate();
+    let runtime = runtime(&identity);
+    let client = QuicServiceClient::builder(&runtime)
+        .direct("https://127.0.0.1:9")
+        .http_version_policy(HttpVersionPolicy::Http3)
+        .direct_capabilities(capabilities(&[Version::HTTP_11, Version::HTTP_2]))
+        .connect()
+        .await
+        .unwrap();
+
+    let error = client.ping().await.unwrap_err();
+    assert_eq!(error.code().as_str(), "no_compatible_endpoint");
+
+    runtime.shutdown().await.unwrap();
+}
@@ -0,0 +1,304 @@
+//! Real-socket coverage for the `http-msgpack-v1` binding.
+
+mod support;
+
+use bytes::Bytes;
+use fusen_rs::{
+    ClientConfig, ClientRuntime, Error, ErrorCategory, ErrorOrigin, HTTP_JSON_V1, HTTP_MSGPACK_V1,
+    HttpBindingId, Response, ResponseStream, RunningServer, SensitiveFields, interface,
+};
+use futures_util::StreamExt;
+use http::{
+    Request, Response as HttpResponse,
+    header::{CONTENT_TYPE, VARY},
+};
+use http_body_util::Full;
+use hyper::{body::Incoming, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use support::{RecordBinding, raw_post};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SensitiveFields)]
+struct Order {
+    id: u64,
+    items: Vec<String>,
+    total: f64,
+    note: Option<String>,
+}
+
+#[interface(name = "msgpack-e2e")]
+trait OrderService {
+    #[fusen_rs::method(method = "POST", path = "/orders/{region}")]
+    async fn place(
+        &self,
+        #[param(path)] region: String,
+        #[param(body)] order: Order,
+    ) -> Result<Response<Order>, Error>;
+
+    #[fusen_rs::method(method = "GET", path = "/orders/export")]
+    async fn export(
+        &self,
+        #[param(query)] count: u32,
+    ) -> Result<Response<ResponseStream<u64>>, Error>;
+}
+
+struct OrderServiceImpl;
+
+impl OrderService for OrderServiceImpl {
+    async fn place(&self, region: String, mut order: Order) -> Result<Response<Order>, Error> {
+        if order.items.is_empty() {
+            return Err(Error::application(
+                ErrorCategory::InvalidArgument,
+                "empty_order",
+                "an order needs at least one item",
+            )
+            .unwrap());
+        }
+        order.note = Some(region);
+        Ok(Response::new(order))
+    }
+
+    async fn export(&self, count: u32) -> Result<Response<ResponseStream<u64>>, Error> {
+        Ok(Response::new(ResponseStream::from_items(
+            (0..u64::from(count)).map(Ok).collect::<Vec<_>>(),
+        )))
+    }
+}
+
+fn msgpack_binding() -> HttpBindingId {
+    HttpBindingId::new(HTTP_MSGPACK_V1).unwrap()
+}
+
+fn order() -> Order {
+    Order {
+        id: u64::MAX,
+        items: vec!["tea".to_owned(), "茶".to_owned()],
+        total: 12.5,
+        note: None,
+    }
+}
+
+async fn start_server(bindings: &[&str], seen: Arc<Mutex<Vec<String>>>) -> RunningServer {
+    support::start_server(bindings, |builder| {
+        builder.interface(
+            OrderServiceServer::new(OrderServiceImpl).head_interceptor(RecordBinding(seen)),
+        )
+    })
+    .await
+}
+
+async fn connect(addr: SocketAddr, binding: HttpBindingId) -> OrderServiceClient {
+    let runtime = ClientRuntime::builder()
+        .config(ClientConfig::default())
+        .build()
+        .unwrap();
+    OrderServiceClient::builder(&runtime)
+        .direct(format!("http://{addr}"))
+        .binding(binding)
+        .connect()
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn generated_clients_round_trip_over_the_msgpack_binding() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let server = start_server(&[HTTP_JSON_V1, HTTP_MSGPACK_V1], seen.clone()).await;
+    let client = connect(server.local_addr(), msgpack_binding()).await;
+
+    let placed = client
+        .place("eu".to_owned(), order())
+        .await
+        .unwrap()
+        .into_body();
+    assert_eq!(
+        placed,
+        Order {
+            note: Some("eu".to_owned()),
+            ..order()
+        }
+    );
+
+    // Errors keep the Problem Details contract of http-json-v1.
+    let error = client
+        .place(
+            "eu".to_owned(),
+            Order {
+                items: Vec::new(),
+                ..order()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.code().as_str(), "empty_order");
+
+    // Streaming responses keep their declared NDJSON framing.
+    let items = client
+        .export(3)
+        .await
+        .unwrap()
+        .into_body()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(items, [0, 1, 2]);
+
+    // JSON clients keep working against the same server.
+    let json = connect(server.local_addr(), HttpBindingId::default()).await;
+    json.place("us".to_owned(), order()).await.unwrap();
+
+    // A bodyless streaming call carries nothing MessagePack-specific, so the server reports JSON.
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [HTTP_MSGPACK_V1, HTTP_MSGPACK_V1, HTTP_JSON_V1, HTTP_JSON_V1]
+    );
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_selects_the_body_format_from_content_type_and_accept() {
+    let server = start_server(
+        &[HTTP_JSON_V1, HTTP_MSGPACK_V1],
+        Arc::new(Mutex::new(Vec::new())),
+    )
+    .await;
+    let addr = server.local_addr();
+    let packed = rmp_serde::to_vec_named(&order()).unwrap();
+
+    let (status, headers, body) = raw_post(
+        addr,
+        "/orders/eu",
+        "application/msgpack",
+        Some("application/msgpack"),
+        packed.clone(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], "application/msgpack");
+    assert_eq!(headers[VARY], "accept");
+    assert_eq!(
+        rmp_serde::from_slice::<Order>(&body)
+            .unwrap()
+            .note
+            .as_deref(),
+        Some("eu")
+    );
+
+    // A MessagePack request may still ask for JSON, and the other way round.
+    let (status, headers, body) =
+        raw_post(addr, "/orders/eu", "application/msgpack", None, packed).await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], "application/json");
+    assert_eq!(serde_json::from_slice::<Order>(&body).unwrap().id, u64::MAX);
+    let (status, headers, body) = raw_post(
+        addr,
+        "/orders/eu",
+        "application/json",
+        Some("application/json, application/msgpack;q=0.5"),
+        serde_json::to_vec(&order()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], "application/msgpack");
+    assert_eq!(rmp_serde::from_slice::<Order>(&body).unwrap().id, u64::MAX);
+    let (_, headers, _) = raw_post(
+        addr,
+        "/orders/eu",
+        "application/json",
+        Some("application/msgpack;q=0"),
+        serde_json::to_vec(&order()).unwrap(),
+    )
+    .await;
+    assert_eq!(headers[CONTENT_TYPE], "application/json");
+
+    // Binary values and trailing bytes have no JSON representation.
+    let mut trailing = rmp_serde::to_vec_named(&order()).unwrap();
+    trailing.push(0xc0);
+    for body in [trailing, vec![0xc4, 0x01, 0x00]] {
+        let (status, headers, problem) =
+            raw_post(addr, "/orders/eu", "application/msgpack", None, body).await;
+        assert_eq!(status, 400);
+        assert_eq!(headers[CONTENT_TYPE], "application/problem+json");
+        let problem = serde_json::from_slice::<Value>(&problem).unwrap();
+        assert_eq!(problem["code"], "invalid_msgpack");
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_only_servers_reject_msgpack_bodies() {
+    let server = start_server(&[HTTP_JSON_V1], Arc::new(Mutex::new(Vec::new()))).await;
+    let addr = server.local_addr();
+
+    let (status, headers, problem) = raw_post(
+        addr,
+        "/orders/eu",
+        "application/msgpack",
+        Some("application/msgpack"),
+        rmp_serde::to_vec_named(&order()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert!(!headers.contains_key(VARY));
+    let problem = serde_json::from_slice::<Value>(&problem).unwrap();
+    assert_eq!(problem["code"], "invalid_content_type");
+
+    // Accept alone does not change the representation a JSON-only server sends.
+    let (status, headers, _) = raw_post(
+        addr,
+        "/orders/eu",
+        "application/json",
+        Some("application/msgpack"),
+        serde_json::to_vec(&order()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], "application/json");
+
+    server.shutdown().await.unwrap();
+}
+
+async fn spawn_fixture(content_type: &'static str, body: &'static [u8]) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let service = service_fn(move |_request: Request<Incoming>| async move {
+                Ok::<_, Infallible>(
+                    HttpResponse::builder()
+                        .header(CONTENT_TYPE, content_type)
+                        .body(Full::new(Bytes::from_static(body)))
+                        .unwrap(),
+                )
+            });
+            tokio::spawn(
+                hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service),
+            );
+        }
+    });
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn msgpack_clients_reject_mislabelled_or_corrupt_responses() {
+    for (content_type, body, code) in [
+        ("application/json", &b"{}"[..], "invalid_content_type"),
+        ("application/msgpack", &[0xc1][..], "invalid_response_body"),
+    ] {
+        let client = connect(spawn_fixture(content_type, body).await, msgpack_binding()).await;
+        let error = client.place("eu".to_owned(), order()).await.unwrap_err();
+        assert_eq!(error.origin(), ErrorOrigin::Remote);
+        assert_eq!(error.code().as_str(), code, "{content_type}");
+    }
+}
@@ -6,11 +6,12 @@ use fusen_config::{
     provider as config_provider,
 };
 use fusen_rs::{
-    Body, BufferedResponse, ClientRuntime, Context, EncodedRequest, Error, ErrorCategory,
-    ErrorDecoder, ErrorKind, ErrorOrigin, FailureClass, InstanceRouter, InstanceSnapshot,
-    Interceptor, InterceptorFuture, LoadBalancer, MetricsRecorder, Next, RequestEncoder,
-    RequestEncoding, Response, ResponseDecoder, RetryDecision, RetryDecisionContext, RetryPolicy,
-    RouteRequest,
+    AttemptCompletion, AttemptOutcome, Body, BufferedRequest, BufferedResponse, ClientRuntime,
+    Context, EncodedRequest, EndpointCapabilities, Error, ErrorCategory, ErrorDecoder, ErrorKind,
+    ErrorOrigin, FailureClass, HTTP_JSON_V1, HttpVersionSet, InstanceRouter, InstanceSnapshot,
+    Interceptor, InterceptorFuture, LoadBalancer, MetricsRecorder, Next, RequestDecoder,
+    RequestEncoder, RequestEncoding, Response, ResponseDecoder, ResponseEncoder, ResponseEncoding,
+    RetryDecision, RetryDecisionContext, RetryPolicy, RouteRequest, Server, ServerConfig,
     contract::{HttpBindingId, MethodDescriptor},
     observability::MetricEvent,
     registry::{
@@ -52,6 +53,14 @@ impl LoadBalancer for ExternalLoadBalancer {
     fn select(&self, _context: &Context, _instances: &InstanceSnapshot) -> Result<usize, Error> {
         Ok(0)
     }
+
+    fn attempt_finished(&self, completion: &AttemptCompletion<'_>) {
+        let _ = (
+            completion.instance().endpoint(),
+            completion.latency(),
+            matches!(completion.outcome(), AttemptOutcome::Failure(_)),
+        );
+    }
 }
 
 struct ExternalRetryPolicy;
@@ -168,6 +177,51 @@ impl ErrorDecoder for ExternalErrorDecoder {
     }
 }
 
+struct ExternalRequestDecoder;
+
+impl RequestDecoder for ExternalRequestDecoder {
+    fn decode(
+        &self,
+        _method: &'static MethodDescriptor,
+        request: BufferedRequest,
+    ) -> Result<serde_json::Value, Error> {
+        let (_headers, body) = request.into_parts();
+        serde_json::from_slice(&body).map_err(|_| {
+            Error::local(
+                ErrorCategory::InvalidArgument,
+                "external_binding_body",
+                "external binding rejected the request body",
+            )
+            .unwrap()
+        })
+    }
+}
+
+struct ExternalResponseEncoder;
+
+impl ResponseEncoder for ExternalResponseEncoder {
+    fn encode(
+        &self,
+        _method: &'static MethodDescriptor,
+        response: ResponseEncoding<'_>,
+    ) -> Result<Bytes, Error> {
+        let _ = (
+            response.status(),
+            response.headers(),
+            response.max_body_bytes(),
+        );
+        Ok(Bytes::copy_from_slice(response.body()))
+    }
+}
+
+struct ExternalPing;
+
+impl PublicSpiContract for ExternalPing {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
 fn accepts_interceptor(_: impl Interceptor) {}
 fn accepts_router(_: impl InstanceRouter) {}
 fn accepts_load_balancer(_: impl LoadBalancer) {}
@@ -178,6 +232,8 @@ fn accepts_metrics(_: impl MetricsRecorder) {}
 fn accepts_request_encoder(_: Arc<dyn RequestEncoder>) {}
 fn accepts_response_decoder(_: Arc<dyn ResponseDecoder>) {}
 fn accepts_error_decoder(_: Arc<dyn ErrorDecoder>) {}
+fn accepts_request_decoder(_: Arc<dyn RequestDecoder>) {}
+fn accepts_response_encoder(_: Arc<dyn ResponseEncoder>) {}
 
 #[tokio::test]
 async fn external_implementations_are_object_safe_and_arc_forwarding_is_complete() {
@@ -191,6 +247,8 @@ async fn external_implementations_are_object_safe_and_arc_forwarding_is_complete
     let request_encoder: Arc<dyn RequestEncoder> = Arc::new(ExternalRequestEncoder);
     let response_decoder: Arc<dyn ResponseDecoder> = Arc::new(ExternalResponseDecoder);
     let error_decoder: Arc<dyn ErrorDecoder> = Arc::new(ExternalErrorDecoder);
+    let request_decoder: Arc<dyn RequestDecoder> = Arc::new(ExternalRequestDecoder);
+    let response_encoder: Arc<dyn ResponseEncoder> = Arc::new(ExternalResponseEncoder);
 
     accepts_interceptor(interceptor.clone());
     accepts_router(router.clone());
@@ -215,6 +273,28 @@ async fn external_implementations_are_object_safe_and_arc_forwarding_is_complete
         )
         .build()
         .unwrap();
+    let capabilities = EndpointCapabilities::new(
+        HttpVersionSet::ALL,
+        [HttpBindingId::new(HTTP_JSON_V1).unwrap(), binding.clone()],
+        true,
+    )
+    .unwrap();
+    let _server = Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .capabilities(capabilities)
+                .build()
+                .unwrap(),
+        )
+        .http_binding(
+            binding.clone(),
+            "application/vnd.example.external",
+            request_decoder.clone(),
+            response_encoder.clone(),
+        )
+        .interface(PublicSpiContractServer::new(ExternalPing))
+        .build()
+        .unwrap();
     let _client_builder = PublicSpiContractClient::builder(&runtime)
         .binding(binding)
         .interceptor(interceptor)
@@ -224,6 +304,8 @@ async fn external_implementations_are_object_safe_and_arc_forwarding_is_complete
     accepts_request_encoder(request_encoder);
     accepts_response_decoder(response_decoder);
     accepts_error_decoder(error_decoder);
+    accepts_request_decoder(request_decoder);
+    accepts_response_encoder(response_encoder);
 
     let _ = FailureClass::Transport;
     runtime.shutdown().await.unwrap();
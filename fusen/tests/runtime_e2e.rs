@@ -1,15 +1,15 @@
 //! Real-socket coverage for HTTP/1.1, h2c, and bounded server draining.
 
 use fusen_rs::{
-    ClientConfig, ClientRuntime, Context, Error, ErrorCategory, ErrorOrigin, Interceptor,
-    InterceptorFuture, Next, Response, RetryConfig, Server, ServerConfig, ServerErrorKind,
-    ServerState,
-    contract::{EndpointCapabilities, HttpVersionPolicy, HttpVersionSet},
+    BreakerThreshold, ClientConfig, ClientErrorKind, ClientRuntime, Context, Error, ErrorCategory,
+    ErrorOrigin, InstanceRouter, InstanceSnapshot, Interceptor, InterceptorFuture, MethodConfig,
+    Next, Response, RetryConfig, RouteRequest, Server, ServerConfig, ServerErrorKind, ServerState,
+    contract::{EndpointCapabilities, HttpVersionPolicy, HttpVersionSet, Metadata},
     interface,
 };
 use std::{
     sync::{
-        Arc,
+        Arc, Mutex,
         atomic::{AtomicUsize, Ordering},
     },
     time::Duration,
@@ -128,6 +128,27 @@ impl LogicalInterceptorService for RetryOnceService {
     }
 }
 
+#[interface(name = "method-defaults-e2e")]
+trait MethodDefaultsService {
+    #[fusen_rs::method(method = "GET", path = "/method-defaults", retries = 0)]
+    async fn execute(&self) -> Result<Response<String>, Error>;
+}
+
+impl MethodDefaultsService for RetryOnceService {
+    async fn execute(&self) -> Result<Response<String>, Error> {
+        LogicalInterceptorService::execute(self).await
+    }
+}
+
+struct AlwaysUnavailable(Arc<AtomicUsize>);
+
+impl MethodDefaultsService for AlwaysUnavailable {
+    async fn execute(&self) -> Result<Response<String>, Error> {
+        self.0.fetch_add(1, Ordering::AcqRel);
+        Err(Error::local(ErrorCategory::Unavailable, "down", "always unavailable").unwrap())
+    }
+}
+
 struct InvocationCounter(Arc<AtomicUsize>);
 
 impl Interceptor for InvocationCounter {
@@ -274,6 +295,119 @@ async fn client_interceptor_runs_once_around_two_physical_attempts() {
     server.shutdown().await.unwrap();
 }
 
+struct ZoneRecorder(Arc<Mutex<Vec<Option<String>>>>);
+
+impl InstanceRouter for ZoneRecorder {
+    fn route(&self, request: RouteRequest<'_>) -> Result<InstanceSnapshot, Error> {
+        self.0.lock().unwrap().extend(
+            request
+                .instances()
+                .iter()
+                .map(|instance| instance.metadata().get("zone").cloned()),
+        );
+        Ok(request.into_instances())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn direct_metadata_reaches_instance_routers_and_is_validated_on_connect() {
+    let server = Server::builder("127.0.0.1:0")
+        .interface(WireServiceServer::new(WireServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let endpoint = format!("http://{}", server.local_addr());
+    let runtime = ClientRuntime::builder().build().unwrap();
+    let zones = Arc::new(Mutex::new(Vec::new()));
+    let client = WireServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .direct_metadata(Metadata::from([("zone".to_owned(), "a1".to_owned())]))
+        .instance_router(ZoneRecorder(zones.clone()))
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        client
+            .lookup("zoned".into(), None)
+            .await
+            .unwrap()
+            .into_body(),
+        "zoned:false"
+    );
+    assert_eq!(*zones.lock().unwrap(), [Some("a1".to_owned())]);
+
+    let error = WireServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .direct_metadata(Metadata::from([("fusen.zone".to_owned(), "a1".to_owned())]))
+        .connect()
+        .await
+        .err()
+        .expect("reserved metadata keys are rejected");
+    assert_eq!(error.kind(), ClientErrorKind::Connect);
+
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn builder_method_config_overrides_macro_retry_defaults() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = Server::builder("127.0.0.1:0")
+        .interface(MethodDefaultsServiceServer::new(RetryOnceService {
+            attempts: attempts.clone(),
+        }))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let endpoint = format!("http://{}", server.local_addr());
+    let runtime = ClientRuntime::builder().build().unwrap();
+
+    let declared = MethodDefaultsServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .connect()
+        .await
+        .unwrap();
+    let error = declared.execute().await.unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::Unavailable);
+    assert_eq!(error.attempts(), 1);
+
+    attempts.store(0, Ordering::Release);
+    let overridden = MethodDefaultsServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .method_config(
+            "execute",
+            MethodConfig::builder()
+                .retry(RetryConfig::builder().max_attempts(2).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .connect()
+        .await
+        .unwrap();
+    let response = overridden.execute().await.unwrap();
+    assert_eq!(response.attempts(), 2);
+    assert_eq!(response.into_body(), "complete");
+
+    let unknown = MethodDefaultsServiceClient::builder(&runtime)
+        .direct(&endpoint)
+        .method_config("missing", MethodConfig::default())
+        .connect()
+        .await
+        .err()
+        .expect("unknown method names fail during connect");
+    assert_eq!(unknown.kind(), ClientErrorKind::Connect);
+
+    drop((declared, overridden));
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn graceful_shutdown_drains_an_inflight_h2_stream() {
     let entered = Arc::new(Barrier::new(2));
@@ -407,3 +541,64 @@ async fn wait_for_state(server: &fusen_rs::RunningServer, expected: ServerState)
     .await
     .expect("server state transition must complete");
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn method_breaker_overrides_survive_reconnects() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = Server::builder("127.0.0.1:0")
+        .interface(MethodDefaultsServiceServer::new(AlwaysUnavailable(
+            calls.clone(),
+        )))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let endpoint = format!("http://{}", server.local_addr());
+    let runtime = ClientRuntime::builder().build().unwrap();
+    let threshold = |minimum_samples| {
+        MethodConfig::builder()
+            .service_breaker(
+                BreakerThreshold::service_builder()
+                    .minimum_samples(minimum_samples)
+                    .open_duration(Duration::from_secs(60))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    };
+    let connect = |config: MethodConfig| {
+        MethodDefaultsServiceClient::builder(&runtime)
+            .direct(&endpoint)
+            .method_config("execute", config)
+            .connect()
+    };
+
+    let first = connect(threshold(2)).await.unwrap();
+    for _ in 0..2 {
+        assert_eq!(first.execute().await.unwrap_err().code().as_str(), "down");
+    }
+    assert_eq!(
+        first.execute().await.unwrap_err().code().as_str(),
+        "circuit_open"
+    );
+    drop(first);
+
+    // A reconnect with the same override resumes the open breaker instead of a fresh one.
+    let second = connect(threshold(2)).await.unwrap();
+    assert_eq!(
+        second.execute().await.unwrap_err().code().as_str(),
+        "circuit_open"
+    );
+    assert_eq!(calls.load(Ordering::Acquire), 2);
+
+    // Different thresholds do not inherit the state.
+    let other = connect(threshold(3)).await.unwrap();
+    assert_eq!(other.execute().await.unwrap_err().code().as_str(), "down");
+    assert_eq!(calls.load(Ordering::Acquire), 3);
+
+    drop((second, other));
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,340 @@
+//! Real-socket coverage for application bindings registered through `ServerBuilder::http_binding`.
+
+mod support;
+
+use bytes::Bytes;
+use fusen_rs::{
+    Body, BufferedRequest, BufferedResponse, ClientRuntime, EncodedRequest, Error, ErrorCategory,
+    ErrorDecoder, HTTP_JSON_V1, HTTP_MSGPACK_V1, HttpBindingId, RequestDecoder, RequestEncoder,
+    RequestEncoding, Response, ResponseDecoder, ResponseEncoder, ResponseEncoding, RunningServer,
+    SensitiveFields, ServerErrorKind, contract::MethodDescriptor, interface,
+};
+use http::{
+    HeaderMap, HeaderValue, Method,
+    header::{ACCEPT, CONTENT_TYPE, VARY},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use support::{RecordBinding, raw_post, server_builder};
+
+const HEX_BINDING: &str = "vendor.http-hex-v1";
+const HEX_MEDIA_TYPE: &str = "application/vnd.example.hex";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SensitiveFields)]
+struct Note {
+    text: String,
+    tags: Vec<String>,
+}
+
+#[interface(name = "server-binding-e2e")]
+trait NoteService {
+    #[fusen_rs::method(method = "POST", path = "/notes")]
+    async fn annotate(&self, #[param(body)] note: Note) -> Result<Response<Note>, Error>;
+}
+
+struct NoteServiceImpl;
+
+impl NoteService for NoteServiceImpl {
+    async fn annotate(&self, mut note: Note) -> Result<Response<Note>, Error> {
+        note.tags.push("seen".to_owned());
+        Ok(Response::new(note))
+    }
+}
+
+fn note() -> Note {
+    Note {
+        text: "茶".to_owned(),
+        tags: vec!["a".to_owned()],
+    }
+}
+
+fn hex_binding() -> HttpBindingId {
+    HttpBindingId::new(HEX_BINDING).unwrap()
+}
+
+fn to_hex(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| format!("{byte:02x}").into_bytes())
+        .collect()
+}
+
+fn from_hex(bytes: &[u8]) -> Option<Vec<u8>> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    bytes
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Carries JSON documents as lowercase hex text, so a JSON-only peer cannot read them.
+struct HexCodec;
+
+impl RequestDecoder for HexCodec {
+    fn decode(
+        &self,
+        _method: &'static MethodDescriptor,
+        request: BufferedRequest,
+    ) -> Result<Value, Error> {
+        let bytes = from_hex(request.body());
+        if bytes.as_deref() == Some(b"panic") {
+            panic!("hex decoder panicked");
+        }
+        bytes
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| {
+                Error::local(
+                    ErrorCategory::InvalidArgument,
+                    "invalid_hex",
+                    "request body is not hex-encoded JSON",
+                )
+                .unwrap()
+            })
+    }
+}
+
+impl ResponseEncoder for HexCodec {
+    fn encode(
+        &self,
+        _method: &'static MethodDescriptor,
+        response: ResponseEncoding<'_>,
+    ) -> Result<Bytes, Error> {
+        Ok(Bytes::from(to_hex(response.body())))
+    }
+}
+
+impl RequestEncoder for HexCodec {
+    fn encode(&self, request: RequestEncoding<'_>) -> Result<EncodedRequest, Error> {
+        let body = serde_json::to_vec(&request.arguments()["note"]).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(HEX_MEDIA_TYPE));
+        headers.insert(ACCEPT, HeaderValue::from_static(HEX_MEDIA_TYPE));
+        Ok(EncodedRequest::new(
+            Method::POST,
+            "/notes",
+            headers,
+            Bytes::from(to_hex(&body)),
+        ))
+    }
+}
+
+impl ResponseDecoder for HexCodec {
+    fn decode(
+        &self,
+        _method: &'static MethodDescriptor,
+        response: BufferedResponse,
+    ) -> Result<Response<Body>, Error> {
+        assert_eq!(response.headers()[CONTENT_TYPE], HEX_MEDIA_TYPE);
+        let json = from_hex(response.body()).expect("server answers in hex");
+        let mut decoded = Response::new(Body::from_bytes(Bytes::from(json)));
+        decoded.set_status(response.status())?;
+        Ok(decoded)
+    }
+}
+
+impl ErrorDecoder for HexCodec {
+    fn decode(&self, _method: &'static MethodDescriptor, _response: BufferedResponse) -> Error {
+        Error::local(
+            ErrorCategory::Unavailable,
+            "hex_binding_error",
+            "hex binding received an error response",
+        )
+        .unwrap()
+    }
+}
+
+async fn start_server(bindings: &[&str], seen: Arc<Mutex<Vec<String>>>) -> RunningServer {
+    support::start_server(bindings, |builder| {
+        builder
+            .http_binding(hex_binding(), HEX_MEDIA_TYPE, HexCodec, HexCodec)
+            .interface(
+                NoteServiceServer::new(NoteServiceImpl).head_interceptor(RecordBinding(seen)),
+            )
+    })
+    .await
+}
+
+fn hex_note() -> Vec<u8> {
+    to_hex(&serde_json::to_vec(&note()).unwrap())
+}
+
+fn problem_code(body: &[u8]) -> String {
+    serde_json::from_slice::<Value>(body).unwrap()["code"]
+        .as_str()
+        .unwrap()
+        .to_owned()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn generated_clients_call_a_registered_server_binding() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let server = start_server(&[HTTP_JSON_V1, HEX_BINDING], seen.clone()).await;
+    let runtime = ClientRuntime::builder()
+        .http_binding(hex_binding(), HexCodec, HexCodec, HexCodec)
+        .build()
+        .unwrap();
+    let hex = NoteServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .binding(hex_binding())
+        .connect()
+        .await
+        .unwrap();
+    let json = NoteServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .connect()
+        .await
+        .unwrap();
+
+    for client in [hex, json] {
+        let annotated = client.annotate(note()).await.unwrap().into_body();
+        assert_eq!(annotated.tags, ["a", "seen"]);
+        assert_eq!(annotated.text, "茶");
+    }
+    assert_eq!(*seen.lock().unwrap(), [HEX_BINDING, HTTP_JSON_V1]);
+
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_selects_registered_bindings_by_content_type_and_accept() {
+    let server = start_server(
+        &[HTTP_JSON_V1, HEX_BINDING],
+        Arc::new(Mutex::new(Vec::new())),
+    )
+    .await;
+    let addr = server.local_addr();
+
+    let (status, headers, body) = raw_post(
+        addr,
+        "/notes",
+        HEX_MEDIA_TYPE,
+        Some(HEX_MEDIA_TYPE),
+        hex_note(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], HEX_MEDIA_TYPE);
+    assert_eq!(headers[VARY], "accept");
+    let annotated = serde_json::from_slice::<Note>(&from_hex(&body).unwrap()).unwrap();
+    assert_eq!(annotated.tags, ["a", "seen"]);
+
+    // Parameters are ignored, and the response representation follows Accept alone.
+    let (status, headers, body) = raw_post(
+        addr,
+        "/notes",
+        "application/vnd.example.hex; charset=us-ascii",
+        None,
+        hex_note(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], "application/json");
+    assert_eq!(serde_json::from_slice::<Note>(&body).unwrap().text, "茶");
+    let (status, headers, body) = raw_post(
+        addr,
+        "/notes",
+        "application/json",
+        Some("application/json, application/vnd.example.hex;q=0.5"),
+        serde_json::to_vec(&note()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers[CONTENT_TYPE], HEX_MEDIA_TYPE);
+    assert!(from_hex(&body).is_some());
+
+    // Decoder errors become Problem Details, and decoder panics stay inside the server.
+    let (status, headers, problem) =
+        raw_post(addr, "/notes", HEX_MEDIA_TYPE, None, b"zz".to_vec()).await;
+    assert_eq!(status, 400);
+    assert_eq!(headers[CONTENT_TYPE], "application/problem+json");
+    assert_eq!(problem_code(&problem), "invalid_hex");
+    let (status, _, problem) =
+        raw_post(addr, "/notes", HEX_MEDIA_TYPE, None, to_hex(b"panic")).await;
+    assert_eq!(status, 500);
+    assert_eq!(problem_code(&problem), "codec_panic");
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn registered_bindings_are_served_only_while_advertised() {
+    let server = start_server(&[HTTP_JSON_V1], Arc::new(Mutex::new(Vec::new()))).await;
+    let addr = server.local_addr();
+
+    let (status, headers, problem) = raw_post(
+        addr,
+        "/notes",
+        HEX_MEDIA_TYPE,
+        Some(HEX_MEDIA_TYPE),
+        hex_note(),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert!(!headers.contains_key(VARY));
+    assert_eq!(problem_code(&problem), "invalid_content_type");
+
+    server.shutdown().await.unwrap();
+}
+
+#[test]
+fn server_builds_reject_unregistered_or_ambiguous_bindings() {
+    let error = match server_builder(&[HTTP_JSON_V1, HEX_BINDING])
+        .interface(NoteServiceServer::new(NoteServiceImpl))
+        .build()
+    {
+        Ok(_) => panic!("advertised bindings need a registered codec"),
+        Err(error) => error,
+    };
+    assert_eq!(error.kind(), ServerErrorKind::Validation);
+    assert!(error.message().contains("unregistered HTTP binding"));
+
+    for (id, media_type, reason) in [
+        (HTTP_MSGPACK_V1, HEX_MEDIA_TYPE, "built in"),
+        (HEX_BINDING, "application/vnd.example+json", "reserved"),
+        (HEX_BINDING, "application/msgpack", "reserved"),
+        (HEX_BINDING, "application/x-ndjson", "reserved"),
+        (HEX_BINDING, "application/*", "range"),
+        (HEX_BINDING, "hex", "invalid media type"),
+    ] {
+        let error = match server_builder(&[HTTP_JSON_V1])
+            .http_binding(
+                HttpBindingId::new(id).unwrap(),
+                media_type,
+                HexCodec,
+                HexCodec,
+            )
+            .interface(NoteServiceServer::new(NoteServiceImpl))
+            .build()
+        {
+            Ok(_) => panic!("{media_type} must be rejected for {id}"),
+            Err(error) => error,
+        };
+        assert_eq!(error.kind(), ServerErrorKind::Validation);
+        assert!(error.message().contains(reason), "{}", error.message());
+    }
+
+    for second in [
+        (HEX_BINDING, "text/x-hex"),
+        ("vendor.other", HEX_MEDIA_TYPE),
+    ] {
+        let error = match server_builder(&[HTTP_JSON_V1])
+            .http_binding(hex_binding(), HEX_MEDIA_TYPE, HexCodec, HexCodec)
+            .http_binding(
+                HttpBindingId::new(second.0).unwrap(),
+                second.1,
+                HexCodec,
+                HexCodec,
+            )
+            .interface(NoteServiceServer::new(NoteServiceImpl))
+            .build()
+        {
+            Ok(_) => panic!("bindings must not share an ID or media type"),
+            Err(error) => error,
+        };
+        assert!(error.message().contains("conflicts"), "{}", error.message());
+    }
+}
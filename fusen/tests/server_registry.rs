@@ -6,7 +6,7 @@ use fusen_register::{
 };
 use fusen_rs::{
     ClientRuntime, Error, Response, Server, ServerConfig, ServerErrorKind, ServerRegistryConfig,
-    ServerState, interface,
+    ServerState, contract::Metadata, interface,
 };
 use std::{
     future::pending,
@@ -212,6 +212,69 @@ async fn registrations_follow_stable_order_and_close_in_reverse() {
     assert_eq!(events, expected);
 }
 
+struct MetadataRegistry(Arc<Mutex<Vec<Metadata>>>);
+
+impl Registry for MetadataRegistry {
+    fn prepare_registration(
+        &self,
+        request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        push(&self.0, request.into_registration().metadata().clone());
+        Ok(provider::registration(async { Ok(()) }, || async {
+            Ok(())
+        }))
+    }
+
+    fn prepare_subscription(
+        &self,
+        _request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        Err(RegistryError::message(
+            fusen_register::error::RegistryOperation::PrepareSubscription,
+            fusen_register::error::RegistryErrorKind::InvalidResource,
+            "test registry does not support subscriptions",
+        ))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_metadata_is_published_with_every_registration() {
+    let zone = Metadata::from([("zone".to_owned(), "a1".to_owned())]);
+    let published = Arc::new(Mutex::new(Vec::new()));
+    let server = Server::builder("127.0.0.1:0")
+        .config(
+            ServerConfig::builder()
+                .metadata(zone.clone())
+                .build()
+                .unwrap(),
+        )
+        .registry("registry", MetadataRegistry(published.clone()))
+        .interface(ZetaRegistryServiceServer::new(RegistryServiceImpl))
+        .interface(AlphaRegistryServiceServer::new(RegistryServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    server.shutdown().await.unwrap();
+    assert_eq!(snapshot(&published), [zone.clone(), zone]);
+
+    let reserved = Metadata::from([("fusen.zone".to_owned(), "a1".to_owned())]);
+    let Err(error) = Server::builder("127.0.0.1:0")
+        .config(ServerConfig::builder().metadata(reserved).build().unwrap())
+        .registry("registry", MetadataRegistry(published.clone()))
+        .interface(AlphaRegistryServiceServer::new(RegistryServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+    else {
+        panic!("reserved registration metadata keys are rejected");
+    };
+    assert_eq!(error.kind(), ServerErrorKind::Validation);
+    assert_eq!(snapshot(&published).len(), 2);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn shutdown_closes_listener_before_registry_and_connection_drain_in_parallel() {
     let entered = Arc::new(Barrier::new(2));
@@ -444,11 +507,11 @@ fn close_events(events: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
         .collect()
 }
 
-fn push(events: &Arc<Mutex<Vec<String>>>, event: String) {
+fn push<T>(events: &Arc<Mutex<Vec<T>>>, event: T) {
     events.lock().unwrap().push(event);
 }
 
-fn snapshot(events: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+fn snapshot<T: Clone>(events: &Arc<Mutex<Vec<T>>>) -> Vec<T> {
     events.lock().unwrap().clone()
 }
 
@@ -8,8 +8,9 @@ use fusen_register::{
 };
 use fusen_rs::{
     ClientAdmissionConfig, ClientConfig, ClientRuntime, Context, Error, ErrorCategory, ErrorOrigin,
-    HttpServerConfig, Interceptor, InterceptorFuture, Next, Response, RetryConfig, Server,
-    ServerConfig, ServerRequestConfig, ServerState, interface,
+    HttpServerConfig, Interceptor, InterceptorFuture, LoadSheddingConfig, Next, RateLimitConfig,
+    RateLimitRule, Response, RetryConfig, RetryHint, Server, ServerConfig, ServerRequestConfig,
+    ServerState, interface,
 };
 use futures_util::{StreamExt as _, stream};
 use http::{Method, Request, StatusCode, Version};
@@ -54,6 +55,9 @@ trait ResourceService {
 
     #[fusen_rs::method(method = "OPTIONS", path = "/resources/options")]
     async fn options(&self) -> Result<Response<String>, Error>;
+
+    #[fusen_rs::method(method = "GET", path = "/resources/background", priority = "low")]
+    async fn background(&self) -> Result<Response<String>, Error>;
 }
 
 struct ResourceServiceImpl {
@@ -90,6 +94,10 @@ impl ResourceService for ResourceServiceImpl {
     async fn options(&self) -> Result<Response<String>, Error> {
         Ok(Response::new("options".to_owned()))
     }
+
+    async fn background(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("background".to_owned()))
+    }
 }
 
 struct Saturation {
@@ -441,6 +449,186 @@ async fn the_1025th_request_is_rejected_while_1024_are_in_flight() {
     server.shutdown().await.unwrap();
 }
 
+#[tokio::test]
+async fn rate_limited_requests_are_rejected_per_caller_before_the_body_is_read() {
+    let quotas = RateLimitConfig::builder()
+        .rule(
+            RateLimitRule::builder(1, Duration::from_secs(60))
+                .method("echo")
+                .header("x-caller-id")
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let server = start_resource_server(
+        ServerConfig::builder()
+            .request(
+                ServerRequestConfig::builder()
+                    .rate_limit(quotas)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap(),
+    )
+    .await;
+    let addr = server.local_addr();
+    let runtime = ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let client = ResourceServiceClient::builder(&runtime)
+        .direct(format!("http://{addr}"))
+        .connect()
+        .await
+        .unwrap();
+
+    // Requests without the caller header share one bucket.
+    assert_eq!(
+        client.echo("first".into()).await.unwrap().into_body(),
+        "first"
+    );
+    let limited = client.echo("second".into()).await.unwrap_err();
+    assert_eq!(limited.category(), ErrorCategory::ResourceExhausted);
+    assert_eq!(limited.origin(), ErrorOrigin::Remote);
+    assert_eq!(limited.code().as_str(), "rate_limited");
+    assert_eq!(
+        limited.retry_hint(),
+        RetryHint::After(Duration::from_secs(60))
+    );
+    assert_eq!(
+        client.options().await.unwrap().into_body(),
+        "options",
+        "rules restricted to one method leave the others alone"
+    );
+
+    let with_caller = |caller: &str, content_length: usize, expect_continue: bool| {
+        request_head_with_options(
+            "POST",
+            "/resources/echo",
+            content_length,
+            caller,
+            None,
+            "close",
+            expect_continue,
+        )
+        .replace(
+            "Connection:",
+            &format!("x-caller-id: {caller}\r\nConnection:"),
+        )
+    };
+    let response = exchange(addr, with_caller("alice", 7, false), b"\"alice\"").await;
+    assert_eq!(response.status, 200);
+    // The second call is answered while its announced body is still unsent.
+    let response = exchange(addr, with_caller("alice", 16, true), b"").await;
+    assert_problem(&response, 429, "rate_limited");
+    assert!(problem(&response).retryable);
+    let response = exchange(addr, with_caller("bob", 5, false), b"\"bob\"").await;
+    assert_eq!(response.status, 200);
+
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn standing_queue_delay_sheds_low_priority_requests_with_retry_after() {
+    let saturation = Saturation::new();
+    let server_config = ServerConfig::builder()
+        .request(
+            ServerRequestConfig::builder()
+                .max_concurrent_requests(1)
+                .queue_capacity(4)
+                .queue_max_wait(Duration::from_secs(5))
+                .load_shedding(
+                    LoadSheddingConfig::builder()
+                        .target_queue_delay(Duration::from_millis(5))
+                        .interval(Duration::from_millis(300))
+                        .retry_after(Duration::from_secs(2))
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let server = Server::builder("127.0.0.1:0")
+        .config(server_config)
+        .interface(ResourceServiceServer::new(ResourceServiceImpl {
+            saturation: Some(saturation.clone()),
+        }))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+    let runtime = ClientRuntime::builder()
+        .config(
+            ClientConfig::builder()
+                .retry(RetryConfig::builder().max_attempts(1).build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let client = ResourceServiceClient::builder(&runtime)
+        .direct(format!("http://{addr}"))
+        .connect()
+        .await
+        .unwrap();
+
+    // The second request waits behind the first; its admission delay is far above target.
+    let first = tokio::spawn({
+        let client = client.clone();
+        async move { client.hold("first".into()).await }
+    });
+    saturation.wait_until_entered(1).await;
+    let second = tokio::spawn({
+        let client = client.clone();
+        async move { client.hold("second".into()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    saturation.release.add_permits(1);
+    saturation.wait_until_entered(2).await;
+    first.await.unwrap().unwrap();
+    tokio::time::sleep(Duration::from_millis(320)).await;
+
+    let shed = client.background().await.unwrap_err();
+    assert_eq!(shed.category(), ErrorCategory::ResourceExhausted);
+    assert_eq!(shed.origin(), ErrorOrigin::Remote);
+    assert_eq!(shed.code().as_str(), "load_shed");
+    assert_eq!(shed.retry_hint(), RetryHint::After(Duration::from_secs(2)));
+    assert_eq!(shed.attempts(), 1);
+
+    let head = request_head("GET", "/resources/hold/third", 0, "shed-by-header", None)
+        .replace("Connection:", "x-fusen-priority: low\r\nConnection:");
+    let response = exchange(addr, head, b"").await;
+    assert_problem(&response, 429, "load_shed");
+    assert!(problem(&response).retryable);
+    let head = request_head("GET", "/resources/background", 0, "bad-priority", None)
+        .replace("Connection:", "x-fusen-priority: urgent\r\nConnection:");
+    assert_problem(&exchange(addr, head, b"").await, 400, "invalid_priority");
+
+    saturation.release.add_permits(1);
+    second.await.unwrap().unwrap();
+    assert_eq!(
+        client.echo("normal".into()).await.unwrap().into_body(),
+        "normal"
+    );
+
+    drop(client);
+    runtime.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn request_byte_budget_is_restored_after_body_cancellation() {
     let server = start_resource_server(body_limited_config(Duration::from_secs(5))).await;
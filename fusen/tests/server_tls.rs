@@ -0,0 +1,283 @@
+//! Real-socket coverage for the built-in TLS listener and its ALPN negotiation.
+
+mod support;
+
+use bytes::Bytes;
+use fusen_register::{
+    RegistrationHandle, RegistrationRequest, Registry, SubscriptionHandle, SubscriptionRequest,
+    error::{RegistryError, RegistryErrorKind, RegistryOperation},
+    provider,
+};
+use fusen_rs::{
+    Error, HttpServerConfig, Response, RunningServer, Server, ServerConfig, ServerErrorKind,
+    ServerTlsConfig, interface,
+};
+use http::{Request, StatusCode, Version};
+use http_body_util::{BodyExt, Empty};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use rustls::{
+    ClientConfig as TlsClientConfig, RootCertStore,
+    pki_types::{CertificateDer, ServerName},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use support::pem;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+
+#[interface(name = "tls-e2e")]
+trait TlsService {
+    #[fusen_rs::method(method = "GET", path = "/tls/ping")]
+    async fn ping(&self) -> Result<Response<String>, Error>;
+}
+
+struct TlsServiceImpl;
+
+impl TlsService for TlsServiceImpl {
+    async fn ping(&self) -> Result<Response<String>, Error> {
+        Ok(Response::new("pong".to_owned()))
+    }
+}
+
+struct CapturingRegistry(Arc<Mutex<Vec<String>>>);
+
+impl Registry for CapturingRegistry {
+    fn prepare_registration(
+        &self,
+        request: RegistrationRequest,
+    ) -> Result<RegistrationHandle, RegistryError> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(request.registration().endpoint().as_url().to_string());
+        Ok(provider::registration(async { Ok(()) }, || async {
+            Ok(())
+        }))
+    }
+
+    fn prepare_subscription(
+        &self,
+        _request: SubscriptionRequest,
+    ) -> Result<SubscriptionHandle, RegistryError> {
+        Err(RegistryError::message(
+            RegistryOperation::PrepareSubscription,
+            RegistryErrorKind::InvalidResource,
+            "TLS test registry does not support subscriptions",
+        ))
+    }
+}
+
+struct PemFixture {
+    directory: PathBuf,
+    chain: PathBuf,
+    key: PathBuf,
+    certificate: CertificateDer<'static>,
+}
+
+impl PemFixture {
+    fn generate(name: &str) -> Self {
+        let CertifiedKey { cert, key_pair } =
+            generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+        let directory = std::env::temp_dir().join(format!(
+            "fusen-server-tls-{name}-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        let chain = directory.join("chain.pem");
+        let key = directory.join("key.pem");
+        std::fs::write(&chain, pem("CERTIFICATE", cert.der())).unwrap();
+        std::fs::write(&key, pem("PRIVATE KEY", &key_pair.serialize_der())).unwrap();
+        Self {
+            directory,
+            chain,
+            key,
+            certificate: cert.der().clone(),
+        }
+    }
+
+    fn config(&self) -> ServerConfig {
+        tls_server_config(&self.chain, &self.key)
+    }
+}
+
+impl Drop for PemFixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
+
+fn tls_server_config(chain: &Path, key: &Path) -> ServerConfig {
+    let tls = ServerTlsConfig::builder(chain, key)
+        .handshake_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    ServerConfig::builder()
+        .http(HttpServerConfig::builder().tls(tls).build().unwrap())
+        .graceful_shutdown_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap()
+}
+
+async fn start_tls_server(
+    fixture: &PemFixture,
+    endpoints: Arc<Mutex<Vec<String>>>,
+) -> RunningServer {
+    Server::builder("127.0.0.1:0")
+        .config(fixture.config())
+        .registry("capture", CapturingRegistry(endpoints))
+        .interface(TlsServiceServer::new(TlsServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect_tls(
+    server: &RunningServer,
+    certificate: CertificateDer<'static>,
+    alpn: &[&[u8]],
+) -> tokio_rustls::client::TlsStream<TcpStream> {
+    let mut roots = RootCertStore::empty();
+    roots.add(certificate).unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = TlsClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+    let stream = TcpStream::connect(server.local_addr()).await.unwrap();
+    TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("127.0.0.1").unwrap(), stream)
+        .await
+        .unwrap()
+}
+
+fn ping_request(version: Version) -> Request<Empty<Bytes>> {
+    Request::builder()
+        .method("GET")
+        .version(version)
+        .uri("https://127.0.0.1/tls/ping")
+        .body(Empty::new())
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tls_listener_negotiates_h2_and_http1_through_alpn() {
+    let fixture = PemFixture::generate("alpn");
+    let endpoints = Arc::new(Mutex::new(Vec::new()));
+    let server = start_tls_server(&fixture, endpoints.clone()).await;
+
+    let stream = connect_tls(&server, fixture.certificate.clone(), &[b"h2", b"http/1.1"]).await;
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+    let (mut sender, connection) = hyper::client::conn::http2::Builder::new(TokioExecutor::new())
+        .handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    let h2_connection = tokio::spawn(connection);
+    let response = sender
+        .send_request(ping_request(Version::HTTP_2))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "\"pong\""
+    );
+    drop(sender);
+    h2_connection.abort();
+
+    let stream = connect_tls(&server, fixture.certificate.clone(), &[b"http/1.1"]).await;
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    let h1_connection = tokio::spawn(connection);
+    let response = sender
+        .send_request(ping_request(Version::HTTP_11))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_11);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "\"pong\""
+    );
+    drop(sender);
+    h1_connection.abort();
+
+    let address = server.local_addr();
+    assert_eq!(*endpoints.lock().unwrap(), [format!("https://{address}/")]);
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tls_listener_rejects_plaintext_requests_without_serving_them() {
+    let fixture = PemFixture::generate("plaintext");
+    let server = start_tls_server(&fixture, Arc::new(Mutex::new(Vec::new()))).await;
+
+    let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+    stream
+        .write_all(b"GET /tls/ping HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("TLS listener must close a plaintext connection");
+    assert!(!response.starts_with(b"HTTP/1.1"));
+    server.shutdown().await.unwrap();
+}
+
+#[test]
+fn invalid_pem_material_is_rejected_when_the_server_is_built() {
+    let fixture = PemFixture::generate("invalid");
+    let garbage = fixture.directory.join("garbage.pem");
+    std::fs::write(&garbage, "not a pem document").unwrap();
+    let missing = fixture.directory.join("missing.pem");
+
+    for (chain, key, message) in [
+        (
+            &garbage,
+            &fixture.key,
+            "server TLS certificate chain file contains no certificates",
+        ),
+        (
+            &fixture.chain,
+            &garbage,
+            "server TLS private key file contains no PEM private key",
+        ),
+        (
+            &missing,
+            &fixture.key,
+            "failed to read the server TLS certificate chain file",
+        ),
+    ] {
+        let error = Server::builder("127.0.0.1:0")
+            .config(tls_server_config(chain, key))
+            .interface(TlsServiceServer::new(TlsServiceImpl))
+            .build()
+            .err()
+            .expect("invalid TLS material must fail validation");
+        assert_eq!(error.kind(), ServerErrorKind::Validation);
+        assert_eq!(error.message(), message);
+    }
+
+    let other = PemFixture::generate("mismatch");
+    let error = Server::builder("127.0.0.1:0")
+        .config(tls_server_config(&fixture.chain, &other.key))
+        .interface(TlsServiceServer::new(TlsServiceImpl))
+        .build()
+        .err()
+        .expect("a key that does not match the leaf certificate must fail validation");
+    assert_eq!(error.kind(), ServerErrorKind::Validation);
+}
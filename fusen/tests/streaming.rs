@@ -0,0 +1,205 @@
+//! Real-socket coverage for server-streaming NDJSON and Server-Sent Events responses.
+
+use fusen_rs::{
+    ClientAdmissionConfig, ClientConfig, ClientRuntime, Error, ErrorCategory, ErrorKind,
+    ErrorOrigin, Response, ResponseStream, RunningServer, Server, ServerConfig,
+    ServerRequestConfig, interface,
+};
+use futures_util::StreamExt;
+use http::header::CONTENT_TYPE;
+
+#[interface(name = "streaming-e2e")]
+trait StreamingService {
+    #[fusen_rs::method(method = "GET", path = "/stream/export")]
+    async fn export(
+        &self,
+        #[param(query)] count: u32,
+        #[param(query)] fail: bool,
+    ) -> Result<Response<ResponseStream<String>>, Error>;
+
+    #[fusen_rs::method(
+        method = "GET",
+        path = "/stream/events",
+        produces = "text/event-stream"
+    )]
+    async fn events(
+        &self,
+        #[param(query)] count: u32,
+        #[param(query)] fail: bool,
+    ) -> Result<Response<ResponseStream<String>>, Error>;
+}
+
+struct StreamingServiceImpl;
+
+fn items(count: u32, fail: bool) -> ResponseStream<String> {
+    let failure = fail.then(|| {
+        Err(Error::application(
+            ErrorCategory::Conflict,
+            "export_interrupted",
+            "export was interrupted",
+        )
+        .unwrap())
+    });
+    ResponseStream::from_items(
+        (0..count)
+            .map(|index| Ok(format!("item-{index:04}")))
+            .chain(failure)
+            .collect::<Vec<_>>(),
+    )
+}
+
+impl StreamingService for StreamingServiceImpl {
+    async fn export(
+        &self,
+        count: u32,
+        fail: bool,
+    ) -> Result<Response<ResponseStream<String>>, Error> {
+        Ok(Response::new(items(count, fail)))
+    }
+
+    async fn events(
+        &self,
+        count: u32,
+        fail: bool,
+    ) -> Result<Response<ResponseStream<String>>, Error> {
+        Ok(Response::new(items(count, fail)))
+    }
+}
+
+const BODY_LIMIT: usize = 64;
+
+async fn start_server() -> RunningServer {
+    let config = ServerConfig::builder()
+        .request(
+            ServerRequestConfig::builder()
+                .max_response_body_bytes(BODY_LIMIT)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    Server::builder("127.0.0.1:0")
+        .config(config)
+        .interface(StreamingServiceServer::new(StreamingServiceImpl))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn connect(server: &RunningServer, max_response_body: usize) -> StreamingServiceClient {
+    let config = ClientConfig::builder()
+        .admission(
+            ClientAdmissionConfig::builder()
+                .max_response_body_bytes(max_response_body)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    let runtime = ClientRuntime::builder().config(config).build().unwrap();
+    StreamingServiceClient::builder(&runtime)
+        .direct(format!("http://{}", server.local_addr()))
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn expected(count: u32) -> Vec<String> {
+    (0..count).map(|index| format!("item-{index:04}")).collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn ndjson_stream_applies_body_limits_per_item() {
+    let server = start_server().await;
+    let client = connect(&server, BODY_LIMIT).await;
+
+    // 100 items are far larger than either side's whole-body limit.
+    let response = client.export(100, false).await.unwrap();
+    assert_eq!(
+        response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|value| value.as_bytes()),
+        None,
+        "Content-Type is a wire control header"
+    );
+    let received = response
+        .into_body()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(received, expected(100));
+
+    let empty = client.export(0, false).await.unwrap().into_body();
+    assert!(empty.collect::<Vec<_>>().await.is_empty());
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn event_stream_round_trips_items_and_a_terminal_problem() {
+    let server = start_server().await;
+    let client = connect(&server, BODY_LIMIT).await;
+
+    let received = client
+        .events(3, false)
+        .await
+        .unwrap()
+        .into_body()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(received, expected(3));
+
+    let mut stream = client.events(2, true).await.unwrap().into_body();
+    assert_eq!(stream.next().await.unwrap().unwrap(), "item-0000");
+    assert_eq!(stream.next().await.unwrap().unwrap(), "item-0001");
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Application);
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.category(), ErrorCategory::Conflict);
+    assert_eq!(error.code().as_str(), "export_interrupted");
+    assert!(stream.next().await.is_none(), "an error ends the stream");
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn ndjson_failures_end_the_stream_with_a_terminal_problem() {
+    let server = start_server().await;
+    let client = connect(&server, BODY_LIMIT).await;
+
+    let mut stream = client.export(2, true).await.unwrap().into_body();
+    assert_eq!(stream.next().await.unwrap().unwrap(), "item-0000");
+    assert_eq!(stream.next().await.unwrap().unwrap(), "item-0001");
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Application);
+    assert_eq!(error.origin(), ErrorOrigin::Remote);
+    assert_eq!(error.code().as_str(), "export_interrupted");
+    assert!(stream.next().await.is_none());
+
+    // A failure before the first item still arrives after the 2xx head, as the only line.
+    let mut stream = client.export(0, true).await.unwrap().into_body();
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::Conflict);
+    assert_eq!(error.code().as_str(), "export_interrupted");
+    assert!(stream.next().await.is_none());
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn oversized_item_fails_the_client_stream() {
+    let server = start_server().await;
+    let client = connect(&server, 8).await;
+
+    let mut stream = client.export(1, false).await.unwrap().into_body();
+    let error = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::PayloadTooLarge);
+    assert_eq!(error.code().as_str(), "response_too_large");
+    assert!(stream.next().await.is_none());
+
+    server.shutdown().await.unwrap();
+}
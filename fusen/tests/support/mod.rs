@@ -0,0 +1,161 @@
+//! Fixtures shared by the integration tests; each test binary uses a subset of them.
+#![allow(dead_code)]
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use fusen_config::{
+    ConfigDocument, ConfigError, ConfigHandle, ConfigKey, ConfigSource, HotConfig,
+    provider::{self, ConfigPublisher},
+};
+use fusen_rs::{
+    Context, EndpointCapabilities, HttpBindingId, HttpVersionSet, Interceptor, InterceptorFuture,
+    Next, RunningServer, Server, ServerBuilder, ServerConfig,
+};
+use http::{
+    HeaderMap, Request,
+    header::{ACCEPT, CONTENT_TYPE},
+};
+use http_body_util::{BodyExt, Full};
+use hyper_util::rt::TokioIo;
+use serde::de::DeserializeOwned;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Encodes one DER item as PEM, because the workspace builds rcgen without its PEM feature.
+pub fn pem(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut document = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        document.push_str(std::str::from_utf8(line).unwrap());
+        document.push('\n');
+    }
+    document.push_str(&format!("-----END {label}-----\n"));
+    document
+}
+
+/// In-memory source whose publisher the test drives directly.
+struct MemorySource {
+    initial: ConfigDocument,
+    publisher: Arc<Mutex<Option<ConfigPublisher>>>,
+}
+
+impl ConfigSource for MemorySource {
+    fn prepare(&self, _key: ConfigKey) -> Result<ConfigHandle, ConfigError> {
+        let initial = self.initial.clone();
+        let slot = self.publisher.clone();
+        Ok(provider::lifecycle(move |publisher| {
+            *slot.lock().unwrap() = Some(publisher);
+            (async move { Ok(initial) }, || async { Ok(()) })
+        }))
+    }
+}
+
+/// Activates `key` from a [`MemorySource`] and returns the typed value with its publisher.
+pub async fn hot_config<T>(key: &str, initial: ConfigDocument) -> (HotConfig<T>, ConfigPublisher)
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    let slot = Arc::new(Mutex::new(None));
+    let source = MemorySource {
+        initial,
+        publisher: slot.clone(),
+    };
+    let handle = source.prepare(ConfigKey::new(key).unwrap()).unwrap();
+    handle.activate().await.unwrap();
+    let hot = handle.typed::<T>().unwrap();
+    let publisher = slot.lock().unwrap().take().unwrap();
+    (hot, publisher)
+}
+
+/// Waits until `hot` reports that a published update was rejected.
+pub async fn wait_for_rejection<T>(hot: &HotConfig<T>) -> ConfigError {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(error) = hot.last_error() {
+                return error;
+            }
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("the rejected update must be reported")
+}
+
+/// Records the binding of every request that reaches an interface.
+pub struct RecordBinding(pub Arc<Mutex<Vec<String>>>);
+
+impl Interceptor for RecordBinding {
+    fn intercept<'a>(&'a self, context: Context, next: Next<'a>) -> InterceptorFuture<'a> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(context.binding_id().as_str().to_owned());
+        next.run(context)
+    }
+}
+
+/// Returns a loopback server builder that advertises `bindings` over every HTTP version.
+pub fn server_builder(bindings: &[&str]) -> ServerBuilder {
+    let capabilities = EndpointCapabilities::new(
+        HttpVersionSet::ALL,
+        bindings
+            .iter()
+            .map(|binding| HttpBindingId::new(*binding).unwrap()),
+        true,
+    )
+    .unwrap();
+    Server::builder("127.0.0.1:0").config(
+        ServerConfig::builder()
+            .capabilities(capabilities)
+            .build()
+            .unwrap(),
+    )
+}
+
+/// Starts a [`server_builder`] server; `configure` adds the interface and any binding codecs.
+pub async fn start_server(
+    bindings: &[&str],
+    configure: impl FnOnce(ServerBuilder) -> ServerBuilder,
+) -> RunningServer {
+    configure(server_builder(bindings))
+        .build()
+        .unwrap()
+        .start()
+        .await
+        .unwrap()
+}
+
+/// Sends one HTTP/1.1 `POST` of `body` to `path` and returns the status, headers, and body.
+pub async fn raw_post(
+    addr: SocketAddr,
+    path: &str,
+    content_type: &str,
+    accept: Option<&str>,
+    body: Vec<u8>,
+) -> (u16, HeaderMap, Bytes) {
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+    let mut request = Request::post(path)
+        .header("host", addr.to_string())
+        .header(CONTENT_TYPE, content_type);
+    if let Some(accept) = accept {
+        request = request.header(ACCEPT, accept);
+    }
+    let response = sender
+        .send_request(request.body(Full::new(Bytes::from(body))).unwrap())
+        .await
+        .unwrap();
+    let status = response.status().as_u16();
+    let (parts, body) = response.into_parts();
+    (
+        status,
+        parts.headers,
+        body.collect().await.unwrap().to_bytes(),
+    )
+}
@@ -6,7 +6,8 @@ use http::{HeaderMap, StatusCode};
 use serde_json::{Map, Value};
 use std::{
     ops::{Deref, DerefMut},
-    sync::{Arc, LazyLock},
+    pin::Pin,
+    sync::{Arc, LazyLock, Mutex},
 };
 
 #[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -62,9 +63,18 @@ mod runtime {
     }
 }
 
+pub(crate) struct BodyFrame {
+    pub bytes: Bytes,
+    pub permit: Option<Arc<runtime::budget::BytePermit>>,
+}
+
+pub(crate) type BodyFrames =
+    Pin<Box<dyn futures_util::Stream<Item = Result<BodyFrame, Error>> + Send>>;
+
 pub struct Body {
     bytes: Bytes,
     permit: Option<Arc<runtime::budget::BytePermit>>,
+    frames: Option<Mutex<Option<BodyFrames>>>,
 }
 
 impl Body {
@@ -72,9 +82,25 @@ impl Body {
         Self {
             bytes,
             permit: None,
+            frames: None,
         }
     }
 
+    fn from_frames(frames: BodyFrames) -> Self {
+        Self {
+            frames: Some(Mutex::new(Some(frames))),
+            ..Self::from_bytes(Bytes::new())
+        }
+    }
+
+    fn take_frames(&self) -> Option<BodyFrames> {
+        self.frames
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take()
+    }
+
     fn len(&self) -> usize {
         self.bytes.len()
     }
@@ -174,19 +200,12 @@ impl Response<Body> {
         Ok(Self::new(Body {
             bytes: result,
             permit: Some(permit),
+            frames: None,
         }))
     }
 
-    pub(crate) fn into_wire_parts(
-        self,
-    ) -> (
-        StatusCode,
-        HeaderMap,
-        Bytes,
-        Option<Arc<runtime::budget::BytePermit>>,
-    ) {
-        let (bytes, permit) = self.body.into_parts();
-        (self.status, self.headers, bytes, permit)
+    pub(crate) fn into_wire_parts(self) -> (StatusCode, HeaderMap, Body) {
+        (self.status, self.headers, self.body)
     }
 
     pub(crate) fn hold_budget(&mut self, permit: runtime::budget::BytePermit) {
@@ -200,6 +219,15 @@ impl Response<Body> {
     }
 }
 
+mod stream {
+    include!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../fusen/src/stream.rs"
+    ));
+}
+
+pub(crate) use stream::{BodyChunk, BodyStream};
+
 mod interceptor {
     pub(crate) trait InterceptorDyn: Send + Sync {}
 }
@@ -219,11 +247,12 @@ mod codec {
 }
 
 pub(crate) use codec::{
-    BufferedResponse, EncodedRequest, ErrorDecoder, RequestEncoder, RequestEncoding,
-    ResponseDecoder,
+    BufferedRequest, BufferedResponse, EncodedRequest, ErrorDecoder, RequestDecoder,
+    RequestEncoder, RequestEncoding, ResponseDecoder, ResponseEncoder, ResponseEncoding,
 };
 
 #[allow(clippy::items_after_test_module)]
+#[cfg_attr(not(feature = "compression"), allow(unused_imports))]
 mod wire {
     include!(concat!(
         env!("CARGO_MANIFEST_DIR"),
@@ -288,6 +317,42 @@ mod wire {
         }
     }
 
+    #[cfg(feature = "compression")]
+    pub(super) fn exercise_content_coding(data: &[u8], max_body: usize) {
+        let codings = [
+            ContentCoding::Zstd,
+            ContentCoding::Brotli,
+            ContentCoding::Gzip,
+        ];
+        for value in [
+            HeaderValue::from_bytes(data).ok(),
+            Some(accept_encoding(&codings)),
+        ] {
+            let headers =
+                HeaderMap::from_iter(value.map(|value| (http::header::ACCEPT_ENCODING, value)));
+            let _ = negotiate(&headers, &codings);
+            let accept = HeaderMap::from_iter(
+                headers
+                    .get(http::header::ACCEPT_ENCODING)
+                    .map(|value| (http::header::ACCEPT, value.clone())),
+            );
+            let _ = accepts_media_type(&accept, MSGPACK_CONTENT_TYPE);
+        }
+        let budget = ByteBudget::new(max_body.max(1));
+        let step = usize::from(data.first().copied().unwrap_or(1)).max(1);
+        for coding in codings {
+            let Ok(mut decoder) = Decoder::new(coding, max_body, &budget) else {
+                continue;
+            };
+            if data.chunks(step).all(|chunk| decoder.write(chunk).is_ok())
+                && let Ok((decoded, permit)) = decoder.finish()
+            {
+                assert!(decoded.len() <= max_body);
+                assert_eq!(permit.bytes(), decoded.len());
+            }
+        }
+    }
+
     pub(super) fn exercise_http_binding(path: &str, query: &str, body: &[u8]) {
         let (service, method) = crate::descriptor();
         let budget = ByteBudget::new(64 * 1024);
@@ -302,17 +367,29 @@ mod wire {
         );
         arguments.insert(
             "body".to_owned(),
-            serde_json::from_slice(body).unwrap_or(serde_json::Value::Null),
+            serde_json::from_slice(body)
+                .or_else(|_| {
+                    ServerBinding::msgpack().decode(
+                        method,
+                        HeaderMap::new(),
+                        Bytes::copy_from_slice(body),
+                    )
+                })
+                .unwrap_or(serde_json::Value::Null),
         );
-        if let Ok(template) = encode_request_template(
-            &JsonCodec,
-            service,
-            method,
-            &arguments,
-            &HeaderMap::new(),
-            64 * 1024,
-            &budget,
-        ) {
+        let codecs: [&dyn RequestEncoder; 2] = [&JsonCodec, &MsgpackCodec];
+        for template in codecs.into_iter().filter_map(|codec| {
+            encode_request_template(
+                codec,
+                service,
+                method,
+                &arguments,
+                &HeaderMap::new(),
+                64 * 1024,
+                &budget,
+            )
+            .ok()
+        }) {
             let endpoint = "http://127.0.0.1:8080"
                 .parse::<ServiceEndpoint>()
                 .expect("static plaintext endpoint is valid");
@@ -428,15 +505,38 @@ pub fn fuzz_wire_codec(data: &[u8]) {
     }
 
     let budget = runtime::budget::ByteBudget::new(max_body.max(1));
-    for suppress_body in [false, true] {
-        let _ = wire::encode_success(
-            Response::fixture(Bytes::copy_from_slice(body)),
-            "application/json",
-            suppress_body,
-            max_body,
-            &budget,
-        );
+    for produces in [
+        "application/json",
+        "application/x-ndjson",
+        "text/event-stream",
+    ] {
+        let msgpack = wire::ServerBinding::msgpack();
+        let (_, method) = descriptor();
+        for (binding, suppress_body) in [
+            (None, false),
+            (None, true),
+            (
+                Some(wire::ResponseBinding {
+                    binding: &msgpack,
+                    method,
+                }),
+                false,
+            ),
+        ] {
+            let _ = wire::encode_success(
+                Response::fixture(Bytes::copy_from_slice(body)),
+                produces,
+                binding,
+                suppress_body,
+                max_body,
+                &budget,
+                "fuzz-request",
+                false,
+            );
+        }
     }
+    #[cfg(feature = "compression")]
+    wire::exercise_content_coding(body, max_body);
 }
 
 pub fn fuzz_http_binding(data: &[u8]) {